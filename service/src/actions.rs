@@ -0,0 +1,36 @@
+// Control actions log: every fan duty and CPU TDP the service applies, with what drove it, so
+// charts can overlay what the controller did on the temperatures that followed. Entries use the
+// telemetry sample clock (`ts_ms`, Unix ms) and are kept in memory only, capped at `MAX_ENTRIES`;
+// `GET /actions/history` returns the ones inside the retained telemetry window. Callers record
+// successful writes only, and the tasks only write on change, so the log stays compact.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::types::{ControlAction, ControlActionKind, ControlActionReason};
+
+const MAX_ENTRIES: usize = 5000;
+
+static LOG: Mutex<VecDeque<ControlAction>> = Mutex::new(VecDeque::new());
+
+/// Log an applied action; `fan_index` is `None` for all fans or a non-fan action.
+pub fn record(kind: ControlActionKind, fan_index: Option<u32>, value: u32, reason: ControlActionReason) {
+    let Ok(mut log) = LOG.lock() else { return };
+    if log.len() >= MAX_ENTRIES {
+        log.pop_front();
+    }
+    log.push_back(ControlAction {
+        ts_ms: chrono::Utc::now().timestamp_millis(),
+        kind,
+        fan_index,
+        value,
+        reason,
+    });
+}
+
+/// Actions at or after `since_ms`, oldest first.
+pub fn since(since_ms: i64) -> Vec<ControlAction> {
+    let Ok(log) = LOG.lock() else { return Vec::new() };
+    let start = log.partition_point(|a| a.ts_ms < since_ms);
+    log.range(start..).cloned().collect()
+}
@@ -0,0 +1,64 @@
+// Active alerts: conditions the service noticed that the user should act on (weak charger, ...).
+//
+// Alerts are keyed by a stable id so the detecting task can raise them every tick without
+// duplicates and clear them once the condition goes away. Only transitions are logged.
+
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use tracing::{info, warn};
+
+use crate::types::{Alert, AlertSeverity};
+
+fn store() -> &'static RwLock<BTreeMap<String, Alert>> {
+    static ALERTS: OnceLock<RwLock<BTreeMap<String, Alert>>> = OnceLock::new();
+    ALERTS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Raise (or refresh the message of) the alert `id`. `since_ms` is kept from the first raise.
+pub fn raise(id: &str, severity: AlertSeverity, message: String) {
+    let Ok(mut w) = store().write() else { return };
+    match w.get_mut(id) {
+        Some(existing) => {
+            existing.severity = severity;
+            existing.message = message;
+        }
+        None => {
+            warn!("alert raised [{}]: {}", id, message);
+            w.insert(
+                id.to_string(),
+                Alert {
+                    id: id.to_string(),
+                    severity,
+                    message,
+                    since_ms: unix_time_ms(),
+                    since: None,
+                },
+            );
+        }
+    }
+}
+
+/// Clear the alert `id` if it is active.
+pub fn clear(id: &str) {
+    let Ok(mut w) = store().write() else { return };
+    if w.remove(id).is_some() {
+        info!("alert cleared [{}]", id);
+    }
+}
+
+/// Currently active alerts, oldest first.
+pub fn active() -> Vec<Alert> {
+    let mut out: Vec<Alert> = store()
+        .read()
+        .map(|m| m.values().cloned().collect())
+        .unwrap_or_default();
+    out.sort_by_key(|a| a.since_ms);
+    out
+}
+
+fn unix_time_ms() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let now = SystemTime::now();
+    now.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64
+}
@@ -0,0 +1,216 @@
+// Nightly backups: once a day, at `backup.hour` local time or at the first chance after it when
+// the machine was off or asleep, the `backup` task writes a snapshot of the saved config and the
+// long-term history (fan hours, charging history, the running report period) to
+// `<backup.dir>/<device_id>/snapshot-<timestamp>.json` and keeps the newest `backup.retention`.
+// Pointing `backup.dir` outside the config directory (another drive, a synced folder) keeps the
+// history through an uninstall.
+//
+// A snapshot has the fields of a config export, so it can also be imported as one. A reinstall
+// generates a new device ID, so listing and restoring cover every device directory in the
+// archive; the config part is restored like an import (a different model's settings are
+// skipped unless forced).
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{BackupConfig, BackupEntry, BackupList, Config, DeviceIdentity};
+
+const PREFIX: &str = "snapshot-";
+const SUFFIX: &str = ".json";
+const STAMP: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub device: DeviceIdentity,
+    pub exported_at_ms: i64,
+    pub service_version: String,
+    /// The saved config; kept as JSON so a snapshot from another version still lists and restores
+    pub config: Value,
+    #[serde(default)]
+    pub history: BTreeMap<String, Value>,
+}
+
+/// History kept in a snapshot, by key, with how to take and restore it
+type HistoryPart = (&'static str, fn() -> Option<Value>, fn(Value) -> Result<(), String>);
+
+const HISTORY: [HistoryPart; 3] = [
+    ("fan_hours", crate::fan_hours::export, crate::fan_hours::restore),
+    (
+        "battery_sessions",
+        crate::battery_sessions::export,
+        crate::battery_sessions::restore,
+    ),
+    ("report_period", crate::reports::export, crate::reports::restore),
+];
+
+pub fn root(cfg: &BackupConfig) -> PathBuf {
+    match &cfg.dir {
+        Some(dir) => PathBuf::from(dir),
+        None => crate::config::config_path().with_file_name("backups"),
+    }
+}
+
+/// Local time a snapshot was taken, from its file name.
+fn taken_at(name: &str) -> Option<NaiveDateTime> {
+    let stamp = name.strip_prefix(PREFIX)?.strip_suffix(SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, STAMP).ok()
+}
+
+/// Device IDs are hex and snapshot names parse with `taken_at`, so neither can leave the archive.
+pub fn is_device_id(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn snapshot_path(cfg: &BackupConfig, device_id: &str, name: &str) -> Result<PathBuf, String> {
+    if !is_device_id(device_id) {
+        return Err("not a device ID".to_string());
+    }
+    if taken_at(name).is_none() {
+        return Err("not a snapshot name".to_string());
+    }
+    Ok(root(cfg).join(device_id).join(name))
+}
+
+/// Snapshots in one device directory, oldest first.
+fn snapshots_in(dir: &Path) -> Vec<(String, NaiveDateTime)> {
+    let mut out: Vec<(String, NaiveDateTime)> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            let at = taken_at(&name)?;
+            Some((name, at))
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+/// Local date of this device's newest snapshot.
+pub fn last_snapshot_date(cfg: &BackupConfig) -> Option<NaiveDate> {
+    snapshots_in(&root(cfg).join(crate::device::id()))
+        .last()
+        .map(|(_, at)| at.date())
+}
+
+/// A nightly snapshot is due once the hour has come on a day without one.
+pub fn due(last: Option<NaiveDate>, now: NaiveDateTime, hour: u32) -> bool {
+    now.hour() >= hour && last.is_none_or(|d| d < now.date())
+}
+
+pub fn list(cfg: &BackupConfig) -> BackupList {
+    let root = root(cfg);
+    let mut entries = Vec::new();
+    for dir in std::fs::read_dir(&root).into_iter().flatten().flatten() {
+        let Ok(device_id) = dir.file_name().into_string() else {
+            continue;
+        };
+        for (name, at) in snapshots_in(&dir.path()) {
+            entries.push(BackupEntry {
+                size_bytes: dir.path().join(&name).metadata().map(|m| m.len()).unwrap_or(0),
+                created_at_ms: Local
+                    .from_local_datetime(&at)
+                    .earliest()
+                    .map(|t| t.timestamp_millis())
+                    .unwrap_or_default(),
+                this_device: device_id == crate::device::id(),
+                device_id: device_id.clone(),
+                name,
+            });
+        }
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.created_at_ms));
+    BackupList {
+        dir: root.display().to_string(),
+        entries,
+    }
+}
+
+/// Write a snapshot of `config` (the saved config, without temporary overrides) and the history,
+/// then delete the oldest snapshots beyond `retention`.
+pub fn create(cfg: &BackupConfig, config: &Config) -> Result<BackupEntry, String> {
+    let dir = root(cfg).join(crate::device::id());
+    std::fs::create_dir_all(&dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    let now = Local::now();
+    let name = format!("{}{}{}", PREFIX, now.format(STAMP), SUFFIX);
+    let snapshot = Snapshot {
+        device: crate::device::identity(),
+        exported_at_ms: now.timestamp_millis(),
+        service_version: env!("CARGO_PKG_VERSION").to_string(),
+        config: serde_json::to_value(config).map_err(|e| e.to_string())?,
+        history: HISTORY
+            .iter()
+            .filter_map(|(key, export, _)| export().map(|v| (key.to_string(), v)))
+            .collect(),
+    };
+    let s = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    let path = dir.join(&name);
+    std::fs::write(&path, &s).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+
+    let existing = snapshots_in(&dir);
+    let excess = existing.len().saturating_sub(cfg.retention.max(1) as usize);
+    for (old, _) in &existing[..excess] {
+        if let Err(e) = std::fs::remove_file(dir.join(old)) {
+            tracing::warn!("backup: failed to delete {}: {}", old, e);
+        }
+    }
+    Ok(BackupEntry {
+        device_id: crate::device::id().to_string(),
+        name,
+        created_at_ms: snapshot.exported_at_ms,
+        size_bytes: s.len() as u64,
+        this_device: true,
+    })
+}
+
+pub fn read(cfg: &BackupConfig, device_id: &str, name: &str) -> Result<Snapshot, String> {
+    let path = snapshot_path(cfg, device_id, name)?;
+    let raw = std::fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&raw).map_err(|e| format!("{} is not a snapshot: {}", name, e))
+}
+
+/// Restore the history kept in a snapshot; returns the keys restored and the failures.
+pub fn restore_history(history: BTreeMap<String, Value>) -> (Vec<String>, Vec<String>) {
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    for (key, value) in history {
+        match HISTORY.iter().find(|(k, _, _)| *k == key) {
+            Some((_, _, restore)) => match restore(value) {
+                Ok(()) => restored.push(key),
+                Err(e) => failed.push(format!("{}: {}", key, e)),
+            },
+            None => failed.push(format!("{}: not known to this version", key)),
+        }
+    }
+    (restored, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn nightly_snapshot_catches_up_after_the_hour() {
+        let yesterday = NaiveDate::from_ymd_opt(2026, 3, 1);
+        assert!(!due(yesterday, at("2026-03-02 02:59"), 3));
+        assert!(due(yesterday, at("2026-03-02 03:00"), 3));
+        // Asleep at 3:00: taken when the machine is back
+        assert!(due(yesterday, at("2026-03-02 17:30"), 3));
+        assert!(!due(NaiveDate::from_ymd_opt(2026, 3, 2), at("2026-03-02 17:30"), 3));
+        assert!(due(None, at("2026-03-02 03:00"), 3));
+
+        assert_eq!(taken_at("snapshot-20260302-030000.json"), Some(at("2026-03-02 03:00")));
+        assert_eq!(taken_at("config-20260302-030000.000.json"), None);
+        assert!(snapshot_path(&BackupConfig::default(), "../x", "snapshot-20260302-030000.json").is_err());
+        assert!(snapshot_path(&BackupConfig::default(), "abc", "../config.json").is_err());
+    }
+}
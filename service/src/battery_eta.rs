@@ -0,0 +1,135 @@
+// Time-to-full / time-to-empty for `/power`. The EC's present rate swings with load (and briefly
+// drops between charger phases), so the battery sessions task folds every sample into an
+// exponentially weighted average per direction and the estimates use that. Time to full counts
+// to the configured charge limit, where the EC stops charging, not to 100%.
+
+use std::sync::Mutex;
+
+use crate::cli::framework_tool_parser::PowerBatteryInfo;
+use crate::types::BatteryEta;
+
+/// Weight of the newest sample; at the 10 s sampling interval this settles in about a minute
+const SMOOTHING_ALPHA: f32 = 0.2;
+/// A longer gap (sleep, service stopped) restarts the average from the next sample
+const MAX_SAMPLE_GAP_MS: i64 = 5 * 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Charging,
+    Discharging,
+}
+
+struct Smoothed {
+    direction: Direction,
+    rate_ma: f32,
+    last_ms: i64,
+}
+
+static SMOOTHED: Mutex<Option<Smoothed>> = Mutex::new(None);
+
+fn direction(p: &PowerBatteryInfo) -> Option<Direction> {
+    match (p.charging, p.discharging) {
+        (Some(true), _) => Some(Direction::Charging),
+        (_, Some(true)) => Some(Direction::Discharging),
+        _ => None,
+    }
+}
+
+/// Fold one `--power` reading into the average; idle or a direction change restarts it.
+pub fn record(now_ms: i64, p: &PowerBatteryInfo) {
+    let Ok(mut guard) = SMOOTHED.lock() else { return };
+    let (Some(dir), Some(rate)) = (direction(p), p.present_rate_ma.filter(|r| *r > 0)) else {
+        *guard = None;
+        return;
+    };
+    let rate = rate as f32;
+    let rate_ma = match guard.as_ref() {
+        Some(prev) if prev.direction == dir && now_ms - prev.last_ms <= MAX_SAMPLE_GAP_MS => {
+            prev.rate_ma + SMOOTHING_ALPHA * (rate - prev.rate_ma)
+        }
+        _ => rate,
+    };
+    *guard = Some(Smoothed {
+        direction: dir,
+        rate_ma,
+        last_ms: now_ms,
+    });
+}
+
+/// Estimates for the reading `p`: the smoothed rate when it matches the current direction,
+/// otherwise the instantaneous one (e.g. right after plugging in).
+pub fn estimate(p: &PowerBatteryInfo, charge_limit_max_pct: Option<u8>) -> BatteryEta {
+    let dir = direction(p);
+    let smoothed = SMOOTHED
+        .lock()
+        .ok()
+        .and_then(|s| s.as_ref().filter(|s| Some(s.direction) == dir).map(|s| s.rate_ma));
+    let rate_ma = smoothed.or(p.present_rate_ma.filter(|r| *r > 0).map(|r| r as f32));
+    compute(
+        dir,
+        rate_ma,
+        p.remaining_capacity_mah,
+        p.last_full_charge_capacity_mah,
+        charge_limit_max_pct,
+    )
+}
+
+fn compute(
+    dir: Option<Direction>,
+    rate_ma: Option<f32>,
+    remaining_mah: Option<u32>,
+    full_mah: Option<u32>,
+    charge_limit_max_pct: Option<u8>,
+) -> BatteryEta {
+    let mut eta = BatteryEta {
+        smoothed_rate_ma: rate_ma.map(|r| r.round() as u32),
+        ..Default::default()
+    };
+    let (Some(dir), Some(rate), Some(remaining)) = (dir, rate_ma.filter(|r| *r > 0.0), remaining_mah) else {
+        return eta;
+    };
+    let minutes = |mah: f32| (mah.max(0.0) / rate * 60.0).round() as u32;
+    match dir {
+        Direction::Charging => {
+            if let Some(full) = full_mah {
+                let limit = charge_limit_max_pct.unwrap_or(100).min(100) as f32;
+                eta.time_to_full_min = Some(minutes(full as f32 * limit / 100.0 - remaining as f32));
+            }
+        }
+        Direction::Discharging => eta.time_to_empty_min = Some(minutes(remaining as f32)),
+    }
+    eta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_to_full_stops_at_charge_limit() {
+        // 2000 mAh to go to 80% of 5000 mAh at 2000 mA
+        let eta = compute(
+            Some(Direction::Charging),
+            Some(2000.0),
+            Some(2000),
+            Some(5000),
+            Some(80),
+        );
+        assert_eq!((eta.time_to_full_min, eta.time_to_empty_min), (Some(60), None));
+        // Already above the limit
+        let eta = compute(Some(Direction::Charging), Some(500.0), Some(4500), Some(5000), Some(80));
+        assert_eq!(eta.time_to_full_min, Some(0));
+        let eta = compute(
+            Some(Direction::Discharging),
+            Some(1000.0),
+            Some(2500),
+            Some(5000),
+            Some(80),
+        );
+        assert_eq!((eta.time_to_full_min, eta.time_to_empty_min), (None, Some(150)));
+        assert_eq!(
+            compute(None, None, Some(2500), Some(5000), None).time_to_empty_min,
+            None
+        );
+    }
+}
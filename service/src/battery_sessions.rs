@@ -0,0 +1,184 @@
+// Charging history: the `battery_sessions` task samples `--power` and splits the stream into
+// charge/discharge sessions (start/end SoC, duration, energy from voltage × current). Completed
+// sessions go to `battery_sessions.json` next to the config; the one in progress stays in memory.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use tracing::warn;
+
+use crate::cli::framework_tool_parser::PowerBatteryInfo;
+use crate::types::{BatterySession, BatterySessionKind, BatterySessionsResponse, PowerProfile};
+
+/// Completed sessions kept in the log.
+const MAX_SESSIONS: usize = 500;
+/// Sessions shorter than this (plug bounce, brief top-up) are dropped.
+const MIN_SESSION_MS: i64 = 60_000;
+/// A longer gap between samples (sleep, service stopped) ends the session at the last sample.
+const MAX_SAMPLE_GAP_MS: i64 = 5 * 60_000;
+
+#[derive(Debug, Default)]
+struct Tracker {
+    current: Option<BatterySession>,
+}
+
+impl Tracker {
+    /// Fold one reading in; returns the session that just ended, if any.
+    fn sample(&mut self, now_ms: i64, p: &PowerBatteryInfo, profile: Option<&PowerProfile>) -> Option<BatterySession> {
+        let kind = match (p.charging, p.discharging) {
+            (Some(true), _) => Some(BatterySessionKind::Charging),
+            (_, Some(true)) => Some(BatterySessionKind::Discharging),
+            _ => None,
+        };
+        let soc = p.percentage;
+
+        let mut ended = None;
+        if let Some(cur) = &mut self.current {
+            let dt = now_ms - cur.end_ms;
+            if !(0..=MAX_SAMPLE_GAP_MS).contains(&dt) || kind != Some(cur.kind) || soc.is_none() {
+                ended = self.current.take();
+            } else {
+                let watts = match (p.present_voltage_mv, p.present_rate_ma) {
+                    (Some(mv), Some(ma)) => mv as f32 * ma as f32 / 1_000_000.0,
+                    _ => cur.avg_watts,
+                };
+                cur.energy_wh += watts * dt as f32 / 3_600_000.0;
+                cur.end_ms = now_ms;
+                cur.end_soc_pct = soc.unwrap_or(cur.end_soc_pct);
+                let hours = (cur.end_ms - cur.start_ms) as f32 / 3_600_000.0;
+                if hours > 0.0 {
+                    cur.avg_watts = cur.energy_wh / hours;
+                }
+            }
+        }
+        if self.current.is_none() {
+            if let (Some(kind), Some(soc)) = (kind, soc) {
+                self.current = Some(BatterySession {
+                    kind,
+                    start_ms: now_ms,
+                    end_ms: now_ms,
+                    start_soc_pct: soc,
+                    end_soc_pct: soc,
+                    energy_wh: 0.0,
+                    avg_watts: 0.0,
+                    profile: profile.cloned(),
+                });
+            }
+        }
+        ended.filter(|s| s.end_ms - s.start_ms >= MIN_SESSION_MS)
+    }
+}
+
+fn log_path() -> PathBuf {
+    crate::config::config_path().with_file_name("battery_sessions.json")
+}
+
+fn tracker() -> &'static Mutex<Tracker> {
+    static TRACKER: OnceLock<Mutex<Tracker>> = OnceLock::new();
+    TRACKER.get_or_init(|| Mutex::new(Tracker::default()))
+}
+
+fn log() -> &'static Mutex<VecDeque<BatterySession>> {
+    static LOG: OnceLock<Mutex<VecDeque<BatterySession>>> = OnceLock::new();
+    LOG.get_or_init(|| {
+        let loaded = std::fs::read_to_string(log_path())
+            .ok()
+            .and_then(|s| serde_json::from_str::<VecDeque<BatterySession>>(&s).ok());
+        Mutex::new(loaded.unwrap_or_default())
+    })
+}
+
+/// Record a `--power` reading taken at `now_ms` under `profile`.
+pub fn record(now_ms: i64, p: &PowerBatteryInfo, profile: Option<&PowerProfile>) {
+    let ended = match tracker().lock() {
+        Ok(mut t) => t.sample(now_ms, p, profile),
+        Err(_) => return,
+    };
+    let Some(session) = ended else { return };
+    let Ok(mut log) = log().lock() else { return };
+    log.push_back(session);
+    while log.len() > MAX_SESSIONS {
+        log.pop_front();
+    }
+    save(&log);
+}
+
+fn save(log: &VecDeque<BatterySession>) {
+    let path = log_path();
+    match serde_json::to_string(log) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("battery sessions: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("battery sessions: failed to serialize log: {}", e),
+    }
+}
+
+/// The completed sessions as kept in `battery_sessions.json`, for backups.
+pub fn export() -> Option<serde_json::Value> {
+    let log = log().lock().ok()?;
+    serde_json::to_value(&*log).ok()
+}
+
+/// Replace the completed sessions with ones from `export`.
+pub fn restore(value: serde_json::Value) -> Result<(), String> {
+    let mut restored: VecDeque<BatterySession> = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    while restored.len() > MAX_SESSIONS {
+        restored.pop_front();
+    }
+    let Ok(mut log) = log().lock() else {
+        return Err("charging history is unavailable".to_string());
+    };
+    *log = restored;
+    save(&log);
+    Ok(())
+}
+
+pub fn snapshot() -> BatterySessionsResponse {
+    BatterySessionsResponse {
+        sessions: log().lock().map(|l| l.iter().cloned().collect()).unwrap_or_default(),
+        current: tracker().lock().ok().and_then(|t| t.current.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(charging: bool, soc: u32) -> PowerBatteryInfo {
+        PowerBatteryInfo {
+            charging: Some(charging),
+            discharging: Some(!charging),
+            percentage: Some(soc),
+            present_voltage_mv: Some(15_000),
+            present_rate_ma: Some(1_000),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn splits_sessions_and_integrates_energy() {
+        let mut t = Tracker::default();
+        // 30 min discharging at 15 W, sampled every 10s
+        for i in 0..=180 {
+            assert!(t
+                .sample(i * 10_000, &reading(false, 80 - (i / 10) as u32), None)
+                .is_none());
+        }
+        let done = t.sample(1_810_000, &reading(true, 62), None).expect("discharge ended");
+        assert_eq!(done.kind, BatterySessionKind::Discharging);
+        assert_eq!((done.start_soc_pct, done.end_soc_pct), (80, 62));
+        assert!((done.energy_wh - 7.5).abs() < 0.01);
+        assert!((done.avg_watts - 15.0).abs() < 0.01);
+
+        // A short charge bounce is dropped; a long gap ends the session
+        assert!(t.sample(1_820_000, &reading(false, 62), None).is_none());
+        assert!(t.sample(1_830_000, &reading(false, 62), None).is_none());
+        assert!(t
+            .sample(1_830_000 + MAX_SAMPLE_GAP_MS + 1, &reading(false, 50), None)
+            .is_none());
+        assert_eq!(t.current.as_ref().map(|c| c.start_soc_pct), Some(50));
+    }
+}
@@ -0,0 +1,94 @@
+// Installer defaults: `bootstrap.json` next to the executable, written by the MSI with the port,
+// CORS origins and update repo the release was built with. It replaces the environment the
+// WinSW wrapper used to inject, which left the service without a port (and panicking at start)
+// whenever that wiring did not apply. Runtime FRAMEWORK_CONTROL_* variables still win over it,
+// and values baked in at build time (Linux) come after it. Placeholders the build did not replace
+// (`@FRAMEWORK_CONTROL_PORT@`) and empty strings count as unset.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use tracing::{info, warn};
+
+const FILE_NAME: &str = "bootstrap.json";
+
+#[derive(Debug, Default, Deserialize)]
+struct Bootstrap {
+    #[serde(default)]
+    port: Option<String>,
+    #[serde(default)]
+    allowed_origins: Option<String>,
+    #[serde(default)]
+    update_repo: Option<String>,
+}
+
+fn load() -> Bootstrap {
+    let Some(path) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(FILE_NAME)))
+    else {
+        return Bootstrap::default();
+    };
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return Bootstrap::default();
+    };
+    match serde_json::from_str(&text) {
+        Ok(b) => {
+            info!("installer defaults read from {}", path.display());
+            b
+        }
+        Err(e) => {
+            warn!("ignoring {}: {}", path.display(), e);
+            Bootstrap::default()
+        }
+    }
+}
+
+fn get() -> &'static Bootstrap {
+    static BOOTSTRAP: OnceLock<Bootstrap> = OnceLock::new();
+    BOOTSTRAP.get_or_init(load)
+}
+
+/// A value the installer actually filled in.
+fn filled(v: &Option<String>) -> Option<String> {
+    let v = v.as_deref()?.trim();
+    (!v.is_empty() && !v.starts_with('@')).then(|| v.to_string())
+}
+
+pub fn port() -> Option<u16> {
+    let port = filled(&get().port)?;
+    match port.parse() {
+        Ok(p) => Some(p),
+        Err(_) => {
+            warn!("{}: port '{}' is not a valid port", FILE_NAME, port);
+            None
+        }
+    }
+}
+
+/// Comma-separated CORS origins; may be filled in yet empty after trimming (same-origin only).
+pub fn allowed_origins() -> Option<String> {
+    get()
+        .allowed_origins
+        .as_deref()
+        .filter(|v| !v.trim_start().starts_with('@'))
+        .map(String::from)
+}
+
+pub fn update_repo() -> Option<String> {
+    filled(&get().update_repo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreplaced_placeholders_count_as_unset() {
+        assert_eq!(filled(&Some("@FRAMEWORK_CONTROL_PORT@".into())), None);
+        assert_eq!(filled(&Some("  ".into())), None);
+        assert_eq!(filled(&Some(" 8090 ".into())).as_deref(), Some("8090"));
+        let parsed: Bootstrap = serde_json::from_str(include_str!("../wix/bootstrap.json")).unwrap();
+        assert_eq!(filled(&parsed.update_repo), None);
+    }
+}
@@ -0,0 +1,209 @@
+// Bridge for a tray companion: one compact state snapshot (long-pollable) and a quick-action list
+// built from the config, so a tray app needs neither the full endpoints nor its own knowledge of
+// the config layout. Actions are `PartialConfig` patches the tray posts to `/config` as-is, so
+// they go through the same validation and revision check as the UI.
+
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+use crate::sensors::metadata;
+use crate::state::AppState;
+use crate::types::{
+    AlertSeverity, BatteryConfig, BridgeState, Config, FanControlConfig, FanControlMode, PartialConfig, PowerConfig,
+    PowerProfile, QuickAction, SettingU8,
+};
+
+/// Longest a long-poll is held before returning the unchanged state.
+pub const MAX_WAIT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+fn severity_rank(s: AlertSeverity) -> u8 {
+    match s {
+        AlertSeverity::Info => 0,
+        AlertSeverity::Warning => 1,
+        AlertSeverity::Critical => 2,
+    }
+}
+
+pub async fn snapshot(state: &AppState) -> BridgeState {
+    let (revision, fan_mode) = {
+        let c = state.config.read().await;
+        (c.revision, c.fan.mode.clone())
+    };
+    let latest = state.telemetry_samples.read().await.back().cloned();
+    let (fan_rpms, hottest) = match latest {
+        Some(s) => (s.rpms, s.temps.into_iter().max_by_key(|(_, t)| *t)),
+        None => (Vec::new(), None),
+    };
+    let cli = { state.framework_tool.read().await.clone() };
+    let power = match cli {
+        Some(cli) => cli.power_with_fallback().await.ok(),
+        None => None,
+    };
+    let (board, _) = metadata::board();
+    let alerts = crate::alerts::active();
+    let mut alert_ids: Vec<&str> = alerts.iter().map(|a| a.id.as_str()).collect();
+    alert_ids.sort_unstable();
+
+    let mut snap = BridgeState {
+        version: String::new(),
+        config_revision: revision,
+        fan_mode,
+        fan_rpms,
+        max_temp_c: hottest.as_ref().map(|(_, t)| *t),
+        hottest_sensor: hottest.as_ref().map(|(name, _)| name.clone()),
+        hottest_sensor_label: hottest.map(|(name, _)| metadata::display_name(board, &name).text),
+        battery_pct: power.as_ref().and_then(|p| p.percentage),
+        ac_present: power.as_ref().and_then(|p| p.ac_present),
+        charging: power.as_ref().and_then(|p| p.charging),
+        alert_severity: alerts.iter().map(|a| a.severity).max_by_key(|s| severity_rank(*s)),
+        alert_count: alerts.len() as u32,
+        safe_mode: crate::safe_mode::is_active(),
+    };
+    let mut h = std::collections::hash_map::DefaultHasher::new();
+    (
+        snap.config_revision,
+        format!("{:?}", snap.fan_mode),
+        snap.battery_pct,
+        snap.ac_present,
+        snap.charging,
+        &alert_ids,
+        snap.safe_mode,
+    )
+        .hash(&mut h);
+    snap.version = format!("{:016x}", h.finish());
+    snap
+}
+
+/// The snapshot once its version differs from `since`, or after `wait` (capped at `MAX_WAIT`).
+pub async fn wait_for_change(state: &AppState, since: &str, wait: Duration) -> BridgeState {
+    let deadline = tokio::time::Instant::now() + wait.min(MAX_WAIT);
+    loop {
+        let snap = snapshot(state).await;
+        if snap.version != since || tokio::time::Instant::now() + POLL_INTERVAL > deadline {
+            return snap;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Tray menu entries for what this config can switch between: the fan modes it has settings
+/// for, the charge limit, and the AC profile's battery-limit override.
+pub fn actions(cfg: &Config) -> Vec<QuickAction> {
+    let fan_mode = cfg.fan.mode.clone().unwrap_or(FanControlMode::Disabled);
+    let fan = |id: &str, label: String, mode: FanControlMode| QuickAction {
+        id: id.to_string(),
+        label,
+        active: fan_mode == mode,
+        confirm: false,
+        patch: PartialConfig {
+            fan: Some(FanControlConfig {
+                mode: Some(mode),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    };
+    let mut out = vec![fan("fan_auto", "Fans: automatic".into(), FanControlMode::Disabled)];
+    if cfg.fan.curve.is_some() {
+        out.push(fan("fan_curve", "Fans: curve".into(), FanControlMode::Curve));
+    }
+    if let Some(t) = &cfg.fan.target {
+        out.push(fan(
+            "fan_target",
+            format!("Fans: hold {}", crate::units::format_temp(t.target_c as f32, &cfg.ui)),
+            FanControlMode::Target,
+        ));
+    }
+    if cfg.fan.rpm.is_some() {
+        out.push(fan("fan_rpm", "Fans: RPM curve".into(), FanControlMode::Rpm));
+    }
+    if let Some(m) = &cfg.fan.manual {
+        out.push(fan(
+            "fan_manual",
+            format!("Fans: manual {}%", m.duty_pct),
+            FanControlMode::Manual,
+        ));
+    }
+
+    if let Some(limit) = &cfg.battery.charge_limit_max_pct {
+        out.push(QuickAction {
+            id: "charge_limit".into(),
+            label: format!("Limit charge to {}%", limit.value),
+            active: limit.enabled,
+            // Turning a limit below 100% off needs `confirm=true` (see `POST /config`)
+            confirm: limit.enabled && limit.value < 100,
+            patch: PartialConfig {
+                battery: Some(BatteryConfig {
+                    charge_limit_max_pct: Some(SettingU8 {
+                        enabled: !limit.enabled,
+                        value: limit.value,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        });
+    }
+    if cfg.power.ac.is_some() {
+        let lifted = cfg.power.ac.as_ref().and_then(|p| p.lift_battery_limits) == Some(true);
+        let limited = cfg
+            .battery
+            .charge_limit_max_pct
+            .as_ref()
+            .is_some_and(|l| l.enabled && l.value < 100);
+        out.push(QuickAction {
+            id: "lift_battery_limits".into(),
+            label: "Full charge while on AC".into(),
+            active: lifted,
+            // Lifting sets aside a limit below 100% while on AC, which also needs `confirm=true`
+            confirm: !lifted && limited,
+            patch: PartialConfig {
+                power: Some(PowerConfig {
+                    ac: Some(PowerProfile {
+                        lift_battery_limits: Some(!lifted),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_toggle_current_settings() {
+        let mut cfg = Config::default();
+        cfg.fan.mode = Some(FanControlMode::Curve);
+        cfg.battery.charge_limit_max_pct = Some(SettingU8 {
+            enabled: true,
+            value: 80,
+        });
+        let actions = actions(&cfg);
+        let ids: Vec<&str> = actions.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["fan_auto", "charge_limit"]);
+        assert!(!actions[0].active);
+        let limit = actions[1]
+            .patch
+            .battery
+            .as_ref()
+            .unwrap()
+            .charge_limit_max_pct
+            .as_ref()
+            .unwrap();
+        assert!(!limit.enabled);
+        assert_eq!(limit.value, 80);
+        assert!(actions[1].confirm);
+
+        cfg.power.ac = Some(PowerProfile::default());
+        let lift = super::actions(&cfg).pop().unwrap();
+        assert_eq!(lift.id, "lift_battery_limits");
+        assert!(lift.confirm);
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+use tracing::info;
+
+use crate::utils::global_cache;
+
+/// Fan of a discrete AMD GPU (the Framework 16 expansion-bay module) driven through the amdgpu
+/// hwmon `pwm1` interface. Only hwmon chips that expose both a fan and a writable PWM qualify,
+/// which also skips the APU's integrated GPU (same driver, no fan).
+#[derive(Clone, Debug)]
+pub struct AmdGpuFan {
+    dir: PathBuf,
+}
+
+// pwm1_enable values from the amdgpu hwmon ABI
+const PWM_MANUAL: &str = "1";
+const PWM_AUTO: &str = "2";
+
+impl AmdGpuFan {
+    /// Find the dGPU fan, re-scanning at most every 30s (the module can be absent or its
+    /// driver still loading at boot).
+    pub async fn detect() -> Option<Self> {
+        const TTL: std::time::Duration = std::time::Duration::from_secs(30);
+        global_cache::cache_get_or_update("amdgpu_fan.detect", TTL, true, || async {
+            scan()
+                .await
+                .ok_or_else(|| "no amdgpu hwmon with a controllable fan".to_string())
+        })
+        .await
+        .ok()
+    }
+
+    pub async fn rpm(&self) -> Option<u32> {
+        read_u32(&self.dir.join("fan1_input")).await
+    }
+
+    /// Current PWM as a 0..=100 duty.
+    pub async fn duty_pct(&self) -> Option<u32> {
+        let pwm = read_u32(&self.dir.join("pwm1")).await?;
+        let max = self.pwm_max().await;
+        Some(((pwm * 100 + max / 2) / max).min(100))
+    }
+
+    /// Edge/junction/memory temperatures in millidegrees, keyed by the amdgpu label.
+    pub async fn temps(&self) -> Vec<(String, i64)> {
+        let mut out = Vec::new();
+        for n in 1..=3 {
+            let Some(raw) = read_u32(&self.dir.join(format!("temp{}_input", n))).await else {
+                continue;
+            };
+            let label = fs::read_to_string(self.dir.join(format!("temp{}_label", n)))
+                .await
+                .map(|l| l.trim().to_string())
+                .unwrap_or_else(|_| format!("temp{}", n));
+            out.push((label, raw as i64));
+        }
+        out
+    }
+
+    pub async fn set_duty(&self, percent: u32) -> Result<(), String> {
+        let pwm = percent.min(100) * self.pwm_max().await / 100;
+        write(&self.dir.join("pwm1_enable"), PWM_MANUAL).await?;
+        write(&self.dir.join("pwm1"), &pwm.to_string()).await
+    }
+
+    /// Hand the fan back to the GPU firmware.
+    pub async fn auto(&self) -> Result<(), String> {
+        write(&self.dir.join("pwm1_enable"), PWM_AUTO).await
+    }
+
+    async fn pwm_max(&self) -> u32 {
+        read_u32(&self.dir.join("pwm1_max"))
+            .await
+            .filter(|v| *v > 0)
+            .unwrap_or(255)
+    }
+}
+
+async fn scan() -> Option<AmdGpuFan> {
+    let mut entries = fs::read_dir("/sys/class/hwmon").await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let dir = entry.path();
+        let is_amdgpu = fs::read_to_string(dir.join("name"))
+            .await
+            .map(|n| n.trim() == "amdgpu")
+            .unwrap_or(false);
+        if !is_amdgpu || !dir.join("fan1_input").exists() || !dir.join("pwm1_enable").exists() {
+            continue;
+        }
+        info!("amdgpu fan detected at {}", dir.display());
+        return Some(AmdGpuFan { dir });
+    }
+    None
+}
+
+async fn read_u32(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).await.ok()?.trim().parse().ok()
+}
+
+async fn write(path: &Path, value: &str) -> Result<(), String> {
+    fs::write(path, value)
+        .await
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+use tracing::info;
+
+use crate::utils::global_cache;
+
+/// Power cap of a discrete AMD GPU (the Framework 16 expansion-bay module) through the amdgpu
+/// hwmon `power1_cap` interface (microwatts). As for [`super::amdgpu_fan::AmdGpuFan`], only the
+/// hwmon chip with a fan qualifies, which skips the APU's integrated GPU.
+#[derive(Clone, Debug)]
+pub struct AmdGpuPower {
+    dir: PathBuf,
+}
+
+const UW_PER_W: u64 = 1_000_000;
+
+impl AmdGpuPower {
+    /// Find the dGPU power cap, re-scanning at most every 30s.
+    pub async fn detect() -> Option<Self> {
+        const TTL: std::time::Duration = std::time::Duration::from_secs(30);
+        global_cache::cache_get_or_update("amdgpu_power.detect", TTL, true, || async {
+            scan()
+                .await
+                .ok_or_else(|| "no amdgpu hwmon with a power cap".to_string())
+        })
+        .await
+        .ok()
+    }
+
+    /// GPU utilization (`gpu_busy_percent` of the PCI device).
+    pub async fn busy_pct(&self) -> Option<u32> {
+        read_u64(&self.dir.join("device").join("gpu_busy_percent"))
+            .await
+            .map(|v| v.min(100) as u32)
+    }
+
+    pub async fn cap_watts(&self) -> Option<u32> {
+        self.read_watts("power1_cap").await
+    }
+
+    /// Allowed cap range; `power1_cap_min` is often 0, so callers apply their own floor.
+    pub async fn cap_range_watts(&self) -> Option<(u32, u32)> {
+        let max = self.read_watts("power1_cap_max").await?;
+        let min = self.read_watts("power1_cap_min").await.unwrap_or(0);
+        Some((min, max))
+    }
+
+    /// The cap the driver boots with.
+    pub async fn default_cap_watts(&self) -> Option<u32> {
+        self.read_watts("power1_cap_default").await
+    }
+
+    pub async fn set_cap_watts(&self, watts: u32) -> Result<(), String> {
+        let path = self.dir.join("power1_cap");
+        fs::write(&path, (watts as u64 * UW_PER_W).to_string())
+            .await
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+
+    async fn read_watts(&self, file: &str) -> Option<u32> {
+        read_u64(&self.dir.join(file)).await.map(|uw| (uw / UW_PER_W) as u32)
+    }
+}
+
+async fn scan() -> Option<AmdGpuPower> {
+    let mut entries = fs::read_dir("/sys/class/hwmon").await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let dir = entry.path();
+        let is_amdgpu = fs::read_to_string(dir.join("name"))
+            .await
+            .map(|n| n.trim() == "amdgpu")
+            .unwrap_or(false);
+        if !is_amdgpu || !dir.join("fan1_input").exists() || !dir.join("power1_cap").exists() {
+            continue;
+        }
+        info!("amdgpu power cap detected at {}", dir.display());
+        return Some(AmdGpuPower { dir });
+    }
+    None
+}
+
+async fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).await.ok()?.trim().parse().ok()
+}
@@ -1,10 +1,14 @@
 use super::framework_tool_parser::{
-    parse_power, parse_thermal, parse_versions, PowerBatteryInfo, ThermalParsed, VersionsParsed,
+    active_charger, parse_als, parse_pd_ports, parse_power, parse_thermal, parse_versions, PdPortInfo,
+    PowerBatteryInfo, ThermalParsed, VersionsParsed,
 };
 use crate::utils::{download as dl, github as gh, global_cache};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+/// Default maximum age of a cached `--thermal` read
+pub const THERMAL_CACHE_AGE: Duration = Duration::from_millis(1000);
+
 /// True after a framework_tool process fails (spawn/exit/timeout), cleared on
 /// success, so the resolver re-validates only when a real call hit trouble.
 static TOOL_SUSPECT: AtomicBool = AtomicBool::new(false);
@@ -13,6 +17,11 @@ pub fn tool_suspect() -> bool {
     TOOL_SUSPECT.load(Ordering::Relaxed)
 }
 
+/// Force the resolver to re-validate the tool on its next pass (e.g. after resume from hibernate).
+pub fn mark_suspect() {
+    TOOL_SUSPECT.store(true, Ordering::Relaxed);
+}
+
 #[cfg(target_os = "windows")]
 use crate::utils::wget as wg;
 use tokio::process::Command;
@@ -43,6 +52,12 @@ impl FrameworkTool {
         Ok(cli)
     }
 
+    /// Use a previously resolved path as-is, skipping resolution and the `--versions` check.
+    /// For early-boot work only; the resolver still validates the tool for everything else.
+    pub fn from_cached_path(path: String) -> Option<Self> {
+        std::path::Path::new(&path).is_file().then_some(Self { path })
+    }
+
     pub async fn power(&self) -> Result<PowerBatteryInfo, String> {
         const TTL: Duration = Duration::from_millis(2000);
         global_cache::cache_get_or_update("framework_tool.power", TTL, true, || async {
@@ -52,9 +67,50 @@ impl FrameworkTool {
         .await
     }
 
+    /// `power()` with a platform fallback for when the EC is busy or slow to answer.
+    /// Windows falls back to the WMI battery classes; other platforms return the EC result as-is.
+    pub async fn power_with_fallback(&self) -> Result<PowerBatteryInfo, String> {
+        #[cfg(target_os = "windows")]
+        {
+            const EC_BUDGET: Duration = Duration::from_secs(3);
+            let ec_err = match tokio::time::timeout(EC_BUDGET, self.power()).await {
+                Ok(Ok(p)) => return Ok(p),
+                Ok(Err(e)) => e,
+                Err(_) => "framework_tool --power timed out".to_string(),
+            };
+            warn!("power: {}; falling back to WMI battery data", ec_err);
+            super::windows_battery::read()
+                .await
+                .map_err(|e| format!("{ec_err}; WMI fallback failed: {e}"))
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            self.power().await
+        }
+    }
+
+    pub async fn pd_ports(&self) -> Result<Vec<PdPortInfo>, String> {
+        const TTL: Duration = Duration::from_millis(5000);
+        global_cache::cache_get_or_update("framework_tool.pd_ports", TTL, true, || async {
+            let out = self.run(&["--pd-ports"]).await?;
+            Ok(parse_pd_ports(&out))
+        })
+        .await
+    }
+
+    /// The USB-C port/adapter currently charging the system, if any.
+    pub async fn charger(&self) -> Result<Option<PdPortInfo>, String> {
+        Ok(active_charger(&self.pd_ports().await?))
+    }
+
     pub async fn thermal(&self) -> Result<ThermalParsed, String> {
-        const TTL: Duration = Duration::from_millis(1000);
-        global_cache::cache_get_or_update("framework_tool.thermal", TTL, true, || async {
+        self.thermal_max_age(THERMAL_CACHE_AGE).await
+    }
+
+    /// Like `thermal`, but lets callers that need denser samples (diagnostics) accept a shorter
+    /// cache age. A fresh read refreshes the shared cache entry for everyone else too.
+    pub async fn thermal_max_age(&self, max_age: Duration) -> Result<ThermalParsed, String> {
+        global_cache::cache_get_or_update("framework_tool.thermal", max_age, true, || async {
             let out = self.run(&["--thermal"]).await?;
             Ok(parse_thermal(&out))
         })
@@ -74,20 +130,56 @@ impl FrameworkTool {
             args.push(idxs.as_str());
         }
         args.push(percent_s.as_str());
-        let _ = self.run(&args).await?;
+        let _ = self.run_write(&args).await?;
+        Ok(())
+    }
+
+    /// Have the EC hold `rpm` on one fan (all fans when `fan_index` is `None`).
+    pub async fn set_fan_rpm(&self, rpm: u32, fan_index: Option<u32>) -> Result<(), String> {
+        let rpm_s = rpm.to_string();
+        let fan_idx_s = fan_index.map(|idx| idx.to_string());
+        let mut args: Vec<&str> = vec!["--fansetrpm"];
+        if let Some(ref idxs) = fan_idx_s {
+            args.push(idxs.as_str());
+        }
+        args.push(rpm_s.as_str());
+        let _ = self.run_write(&args).await?;
+        Ok(())
+    }
+
+    /// Ambient light sensor reading in lux
+    pub async fn als_lux(&self) -> Result<u32, String> {
+        const TTL: Duration = Duration::from_millis(1000);
+        global_cache::cache_get_or_update("framework_tool.als", TTL, true, || async {
+            let out = self.run(&["--sensors"]).await?;
+            parse_als(&out).ok_or_else(|| "no ambient light sensor reading".to_string())
+        })
+        .await
+    }
+
+    pub async fn set_kblight(&self, percent: u32) -> Result<(), String> {
+        let arg = percent.min(100).to_string();
+        let _ = self.run_write(&["--kblight", &arg]).await?;
         Ok(())
     }
 
     pub async fn autofanctrl(&self) -> Result<(), String> {
-        let _ = self.run(&["--autofanctrl"]).await?;
+        let _ = self.run_write(&["--autofanctrl"]).await?;
         Ok(())
     }
 
     /// Get charge limit min/max percentage as reported by EC
     pub async fn charge_limit_get(&self) -> Result<super::framework_tool_parser::BatteryChargeLimitInfo, String> {
+        self.charge_limit_get_max_age(Duration::from_millis(2000)).await
+    }
+
+    /// Like `charge_limit_get`, with a caller-chosen cache age (zero to read back a write).
+    pub async fn charge_limit_get_max_age(
+        &self,
+        max_age: Duration,
+    ) -> Result<super::framework_tool_parser::BatteryChargeLimitInfo, String> {
         use super::framework_tool_parser::parse_charge_limit;
-        const TTL: Duration = Duration::from_millis(2000);
-        global_cache::cache_get_or_update("framework_tool.charge_limit", TTL, true, || async {
+        global_cache::cache_get_or_update("framework_tool.charge_limit", max_age, true, || async {
             let out = self.run(&["--charge-limit"]).await?;
             let info = parse_charge_limit(&out);
             if info.charge_limit_min_pct.is_some() || info.charge_limit_max_pct.is_some() {
@@ -102,7 +194,7 @@ impl FrameworkTool {
     /// Set max charge limit percentage
     pub async fn charge_limit_set(&self, max_pct: u8) -> Result<(), String> {
         let arg = max_pct.to_string();
-        let _ = self.run(&["--charge-limit", &arg]).await?;
+        let _ = self.run_write(&["--charge-limit", &arg]).await?;
         Ok(())
     }
 
@@ -112,17 +204,26 @@ impl FrameworkTool {
         match soc_threshold_pct {
             Some(soc) => {
                 let s = soc.to_string();
-                let _ = self.run(&["--charge-rate-limit", &rate, &s]).await?;
+                let _ = self.run_write(&["--charge-rate-limit", &rate, &s]).await?;
             }
             None => {
-                let _ = self.run(&["--charge-rate-limit", &rate]).await?;
+                let _ = self.run_write(&["--charge-rate-limit", &rate]).await?;
             }
         }
         Ok(())
     }
 
+    /// `run` for commands that change EC state; recorded instead of run during a dry run.
+    async fn run_write(&self, args: &[&str]) -> Result<String, String> {
+        if crate::dry_run::intercept(|| crate::dry_run::command_line(&self.path, args)) {
+            return Ok(String::new());
+        }
+        self.run(args).await
+    }
+
     async fn run(&self, args: &[&str]) -> Result<String, String> {
         use tokio::time::{timeout, Duration};
+        let started = std::time::Instant::now();
         let result = async {
             let child = Command::new(&self.path)
                 .args(args)
@@ -146,6 +247,8 @@ impl FrameworkTool {
         }
         .await;
         TOOL_SUSPECT.store(result.is_err(), Ordering::Relaxed);
+        crate::metrics::record_cli_call("framework_tool", args, started.elapsed(), result.is_ok());
+        crate::diagnostics::capture::record_raw(args, started.elapsed(), &result);
         result
     }
 }
@@ -240,7 +343,7 @@ pub async fn attempt_install_via_direct_download() -> Result<(), String> {
         base_dir.to_string_lossy(),
         url
     );
-    let final_path = dl::download_to_path(&url, &base_dir.to_string_lossy().to_string()).await?;
+    let final_path = dl::download_to_path(&url, base_dir.to_string_lossy().as_ref()).await?;
 
     if let Ok(meta) = std::fs::metadata(&final_path) {
         info!("downloaded size: {} bytes", meta.len());
@@ -293,6 +293,86 @@ pub fn parse_power(stdout: &str) -> PowerBatteryInfo {
     }
 }
 
+/// One USB-C port from `framework_tool --pd-ports`.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct PdPortInfo {
+    pub port: u32,
+    /// Physical location as printed by framework_tool (e.g. "Right Back")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Sink, Source or Disconnected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Adapter type: PD, TypeC, Proprietary, Bc12Dcp, ... or None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charging_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voltage_mv: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_limit_ma: Option<u32>,
+    /// Power of the negotiated contract
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub negotiated_watts: Option<f32>,
+}
+
+/// Parse `framework_tool --pd-ports`, which prints a block per port:
+/// "USB-C Port 0 (Right Back):" followed by indented "Role:", "Charging Type:",
+/// "Voltage Now: 20.0 V, Max: 20.0 V", "Current Lim: 3250 mA, Max: 3250 mA", "Max Power: 65.0 W".
+pub fn parse_pd_ports(stdout: &str) -> Vec<PdPortInfo> {
+    let mut ports: Vec<PdPortInfo> = vec![];
+    for line in stdout.lines() {
+        let l = line.trim();
+        if let Some(rest) = l.strip_prefix("USB-C Port") {
+            let rest = rest.trim().trim_end_matches(':');
+            let (num, location) = match rest.split_once('(') {
+                Some((n, loc)) => (n.trim(), Some(loc.trim_end_matches(')').trim().to_string())),
+                None => (rest, None),
+            };
+            if let Ok(port) = num.parse::<u32>() {
+                ports.push(PdPortInfo {
+                    port,
+                    location,
+                    ..Default::default()
+                });
+            }
+            continue;
+        }
+        let Some(cur) = ports.last_mut() else { continue };
+        let Some((key, value)) = l.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        // Only the "now" part matters; the ", Max: ..." tail is the port's capability.
+        let first_number = |unit: &str| -> Option<f32> {
+            let head = value.split(',').next()?.trim();
+            head.strip_suffix(unit)?.trim().parse::<f32>().ok()
+        };
+        match key.trim() {
+            "Role" => cur.role = Some(value.to_string()),
+            "Charging Type" => cur.charging_type = Some(value.to_string()),
+            "Voltage Now" => cur.voltage_mv = first_number("V").map(|v| (v * 1000.0).round() as u32),
+            "Current Lim" => cur.current_limit_ma = first_number("mA").map(|v| v as u32),
+            "Max Power" => cur.negotiated_watts = first_number("W"),
+            _ => {}
+        }
+    }
+    ports
+}
+
+/// The port currently powering the system: a sink with an adapter attached. When several
+/// adapters are plugged in, the EC charges from the strongest one.
+pub fn active_charger(ports: &[PdPortInfo]) -> Option<PdPortInfo> {
+    ports
+        .iter()
+        .filter(|p| p.role.as_deref() == Some("Sink"))
+        .filter(|p| !matches!(p.charging_type.as_deref(), None | Some("None")))
+        .max_by(|a, b| {
+            let (wa, wb) = (a.negotiated_watts.unwrap_or(0.0), b.negotiated_watts.unwrap_or(0.0));
+            wa.total_cmp(&wb)
+        })
+        .cloned()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
 pub struct VersionsParsed {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -308,6 +388,17 @@ pub struct VersionsParsed {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ec_current_image: Option<String>,
 }
+/// Ambient light in lux from `--sensors` (e.g. `ALS:   120 Lux`); `None` without an ALS.
+pub fn parse_als(stdout: &str) -> Option<u32> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .find(|l| l.to_ascii_uppercase().starts_with("ALS"))
+        .and_then(|l| l.split_once(':'))
+        .and_then(|(_, v)| v.split_whitespace().next())
+        .and_then(|n| n.parse().ok())
+}
+
 pub fn parse_versions(text: &str) -> VersionsParsed {
     let mut out = VersionsParsed::default();
     if text.is_empty() {
@@ -410,6 +501,12 @@ mod tests {
         assert_eq!(rpms(&t), vec![0, 2917]);
     }
     #[test]
+    fn parse_als_reads_lux() {
+        let s = "Accelerometers:\n  Lid Angle:   120 Deg\nALS:       87 Lux\n";
+        assert_eq!(parse_als(s), Some(87));
+        assert_eq!(parse_als("Accelerometers:\n  Lid Angle: 120 Deg\n"), None);
+    }
+    #[test]
     fn parse_thermal_named_fans() {
         let s = r#"
   APU:          50 C
@@ -485,4 +582,40 @@ Battery Status
         assert_eq!(p.cycle_count, Some(58));
         assert_eq!(p.charging, Some(true));
     }
+
+    #[test]
+    fn parse_pd_ports_picks_active_charger() {
+        let s = r#"
+USB-C Port 0 (Right Back):
+  Role:          Sink
+  Charging Type: PD
+  Voltage Now:   20.0 V, Max: 20.0 V
+  Current Lim:   3250 mA, Max: 3250 mA
+  Dual Role:     Charger
+  Max Power:     65.0 W
+USB-C Port 1 (Right Front):
+  Role:          Disconnected
+  Charging Type: None
+  Voltage Now:   0.0 V, Max: 0.0 V
+  Current Lim:   0 mA, Max: 0 mA
+  Dual Role:     Charger
+  Max Power:     0.0 W
+USB-C Port 2 (Left Front):
+  Role:          Sink
+  Charging Type: TypeC
+  Voltage Now:   5.0 V, Max: 5.0 V
+  Current Lim:   3000 mA, Max: 3000 mA
+  Dual Role:     Charger
+  Max Power:     15.0 W
+"#;
+        let ports = parse_pd_ports(s);
+        assert_eq!(ports.len(), 3);
+        assert_eq!(ports[0].location.as_deref(), Some("Right Back"));
+        assert_eq!(ports[0].voltage_mv, Some(20000));
+        assert_eq!(ports[0].current_limit_ma, Some(3250));
+        let c = active_charger(&ports).expect("charger");
+        assert_eq!(c.port, 0);
+        assert_eq!(c.charging_type.as_deref(), Some("PD"));
+        assert_eq!(c.negotiated_watts, Some(65.0));
+    }
 }
@@ -3,11 +3,15 @@ use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::{debug, info, warn};
 
+/// Package powercap zone; AMD CPUs expose theirs under the same name
+pub const RAPL_ZONE: &str = "/sys/class/powercap/intel-rapl:0";
+
 /// Linux-native power management using kernel interfaces
 #[derive(Clone)]
 pub struct LinuxPower {
     amd_pstate: Option<AmdPStateBackend>,
     cpufreq: Option<CpufreqBackend>,
+    boost: Option<BoostBackend>,
 }
 
 impl LinuxPower {
@@ -16,6 +20,7 @@ impl LinuxPower {
 
         let amd_pstate = AmdPStateBackend::detect().await;
         let cpufreq = CpufreqBackend::detect().await;
+        let boost = BoostBackend::detect().await;
 
         // Log what we found
         if amd_pstate.is_some() {
@@ -24,12 +29,19 @@ impl LinuxPower {
         if cpufreq.is_some() {
             info!("cpufreq detected: governor and frequency control available");
         }
+        if boost.is_some() {
+            info!("cpufreq boost switch detected: boost control available");
+        }
 
         if amd_pstate.is_none() && cpufreq.is_none() {
             warn!("No power management interfaces found");
         }
 
-        Ok(Self { amd_pstate, cpufreq })
+        Ok(Self {
+            amd_pstate,
+            cpufreq,
+            boost,
+        })
     }
 
     pub async fn get_capabilities(&self) -> PowerCapabilities {
@@ -53,6 +65,8 @@ impl LinuxPower {
             }
         }
 
+        caps.supports_boost = self.boost.is_some();
+
         caps
     }
 
@@ -75,6 +89,16 @@ impl LinuxPower {
             }
         }
 
+        if let Some(boost) = &self.boost {
+            state.boost = boost.get().await.ok();
+        }
+
+        // Long-term package limit (PL1), read-only here: firmware usually locks it on AMD
+        let pl1_path = Path::new(RAPL_ZONE).join("constraint_0_power_limit_uw");
+        if let Ok(uw) = read_sysfs_u64(&pl1_path).await {
+            state.tdp_limit_watts = Some((uw / 1_000_000) as u32);
+        }
+
         Ok(state)
     }
 
@@ -96,6 +120,11 @@ impl LinuxPower {
         amd_pstate.set_epp_preference(preference).await
     }
 
+    pub async fn set_boost(&self, enabled: bool) -> Result<(), String> {
+        let boost = self.boost.as_ref().ok_or("cpufreq boost switch not available")?;
+        boost.set(enabled).await
+    }
+
     /// Applies frequency limits, clamping against hardware range.
     /// Pass None for either value to reset it to the hardware default.
     pub async fn set_freq_limits(&self, min_mhz: Option<u32>, max_mhz: Option<u32>) -> Result<(), String> {
@@ -175,6 +204,57 @@ impl AmdPStateBackend {
     }
 }
 
+// Boost Backend: the global cpufreq switch (acpi-cpufreq, older amd-pstate) or the per-policy
+// switches amd-pstate exposes on newer kernels
+#[derive(Clone)]
+struct BoostBackend {
+    paths: Vec<PathBuf>,
+}
+
+impl BoostBackend {
+    async fn detect() -> Option<Self> {
+        let cpufreq_path = Path::new("/sys/devices/system/cpu/cpufreq");
+        let global = cpufreq_path.join("boost");
+        if global.exists() {
+            return Some(Self { paths: vec![global] });
+        }
+
+        let mut paths = Vec::new();
+        if let Ok(mut entries) = fs::read_dir(cpufreq_path).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let boost_path = entry.path().join("boost");
+                if entry.file_name().to_string_lossy().starts_with("policy") && boost_path.exists() {
+                    paths.push(boost_path);
+                }
+            }
+        }
+        if paths.is_empty() {
+            return None;
+        }
+        paths.sort();
+
+        debug!("Found {} cpufreq boost switch(es)", paths.len());
+
+        Some(Self { paths })
+    }
+
+    async fn get(&self) -> Result<bool, String> {
+        let first = self
+            .paths
+            .first()
+            .ok_or_else(|| "No boost paths available".to_string())?;
+        Ok(read_sysfs_u64(first).await? != 0)
+    }
+
+    async fn set(&self, enabled: bool) -> Result<(), String> {
+        for path in &self.paths {
+            write_sysfs_string(path, if enabled { "1" } else { "0" }).await?;
+        }
+        debug!("Set boost: {}", enabled);
+        Ok(())
+    }
+}
+
 // Cpufreq Backend (Governor + frequency limits)
 #[derive(Clone)]
 struct CpufreqBackend {
@@ -326,7 +406,7 @@ fn cpu_index(path: &Path) -> Option<u32> {
     suffix.parse::<u32>().ok()
 }
 
-fn sort_cpu_paths(cpu_paths: &mut Vec<PathBuf>) {
+fn sort_cpu_paths(cpu_paths: &mut [PathBuf]) {
     cpu_paths.sort_by_key(|p| cpu_index(p).unwrap_or(u32::MAX));
 }
 
@@ -378,12 +458,14 @@ async fn read_sysfs_string(path: &Path) -> Result<String, String> {
 }
 
 async fn write_sysfs_u64(path: &Path, value: u64) -> Result<(), String> {
-    fs::write(path, value.to_string())
-        .await
-        .map_err(|e| format!("Failed to write to {}: {}", path.display(), e))
+    write_sysfs_string(path, &value.to_string()).await
 }
 
+/// Recorded instead of written during a dry run.
 async fn write_sysfs_string(path: &Path, value: &str) -> Result<(), String> {
+    if crate::dry_run::intercept(|| format!("echo {} > {}", value, path.display())) {
+        return Ok(());
+    }
     fs::write(path, value)
         .await
         .map_err(|e| format!("Failed to write to {}: {}", path.display(), e))
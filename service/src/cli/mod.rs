@@ -1,5 +1,6 @@
 pub mod framework_tool;
 pub mod framework_tool_parser;
+pub mod power_backend;
 
 #[cfg(target_os = "windows")]
 pub mod ryzen_adj;
@@ -7,11 +8,24 @@ pub mod ryzen_adj;
 #[cfg(target_os = "windows")]
 pub mod ryzen_adj_parser;
 
+#[cfg(target_os = "windows")]
+pub mod windows_battery;
+
+#[cfg(any(target_os = "windows", test))]
+pub mod powercfg;
+
 #[cfg(target_os = "linux")]
 pub mod linux_power;
 
+#[cfg(target_os = "linux")]
+pub mod amdgpu_fan;
+
+#[cfg(target_os = "linux")]
+pub mod amdgpu_power;
+
 // For imports: crate::cli::FrameworkTool
 pub use framework_tool::FrameworkTool;
+pub use power_backend::{PowerBackend, PowerBackends};
 
 // RyzenAdj exports (Windows only)
 #[cfg(target_os = "windows")]
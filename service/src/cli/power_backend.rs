@@ -0,0 +1,196 @@
+// Power backends behind one interface: RyzenAdj (plus powercfg for boost and scheduler hints) on
+// Windows, AMD P-State/cpufreq sysfs on Linux. The power task, routes and validation go through `PowerBackend`
+// and check `capabilities()` before calling a setter; setters a backend lacks fail with an
+// "unsupported" error. Which backend is active follows `power.backend` (auto, a specific one, or
+// disabled) and is resolved per use, so resolver/uninstall changes to the underlying handles are
+// picked up immediately.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::types::{PowerBackendKind, PowerCapabilities, PowerState, SchedulerHints};
+
+#[cfg(target_os = "windows")]
+use crate::cli::RyzenAdj;
+
+#[cfg(target_os = "linux")]
+use crate::cli::LinuxPower;
+
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+pub trait PowerBackend: Send + Sync {
+    fn kind(&self) -> PowerBackendKind;
+
+    fn capabilities(&self) -> Pin<Box<dyn Future<Output = PowerCapabilities> + Send + '_>>;
+
+    /// What is applied right now (the backend fills the fields it can read)
+    fn state(&self) -> BackendFuture<'_, PowerState>;
+
+    fn set_tdp_watts(&self, _watts: u32) -> BackendFuture<'_, ()> {
+        unsupported(self.kind(), "TDP")
+    }
+
+    /// Individual STAPM/fast/slow limits in watts; `None` leaves that limit alone
+    fn set_power_limits(&self, _stapm: Option<u32>, _fast: Option<u32>, _slow: Option<u32>) -> BackendFuture<'_, ()> {
+        unsupported(self.kind(), "power limits")
+    }
+
+    fn set_thermal_limit_c(&self, _celsius: u32) -> BackendFuture<'_, ()> {
+        unsupported(self.kind(), "thermal limit")
+    }
+
+    fn set_governor<'a>(&'a self, _governor: &'a str) -> BackendFuture<'a, ()> {
+        unsupported(self.kind(), "governor")
+    }
+
+    fn set_epp_preference<'a>(&'a self, _preference: &'a str) -> BackendFuture<'a, ()> {
+        unsupported(self.kind(), "EPP")
+    }
+
+    /// Configured (not live) `(min, max)` frequency limits in MHz
+    fn frequency_limits(&self) -> BackendFuture<'_, (u32, u32)> {
+        unsupported(self.kind(), "frequency limits")
+    }
+
+    /// `None` resets that bound to the hardware default
+    fn set_frequency_limits(&self, _min_mhz: Option<u32>, _max_mhz: Option<u32>) -> BackendFuture<'_, ()> {
+        unsupported(self.kind(), "frequency limits")
+    }
+
+    fn set_boost(&self, _enabled: bool) -> BackendFuture<'_, ()> {
+        unsupported(self.kind(), "boost")
+    }
+
+    /// Writes the fields set in `hints`, leaving the others alone
+    fn set_scheduler_hints<'a>(&'a self, _hints: &'a SchedulerHints) -> BackendFuture<'a, ()> {
+        unsupported(self.kind(), "scheduler hints")
+    }
+}
+
+fn unsupported<'a, T: Send + 'a>(kind: PowerBackendKind, what: &str) -> BackendFuture<'a, T> {
+    let msg = format!("{} is not supported by the {:?} power backend", what, kind);
+    Box::pin(async move { Err(msg) })
+}
+
+/// Whether `kind` can be selected on this platform.
+pub fn available(kind: PowerBackendKind) -> bool {
+    match kind {
+        PowerBackendKind::Auto | PowerBackendKind::Disabled => true,
+        PowerBackendKind::Ryzenadj => cfg!(target_os = "windows"),
+        PowerBackendKind::Linux => cfg!(target_os = "linux"),
+    }
+}
+
+/// Handles to every backend compiled into this build; each is `None` until its resolver finds it.
+#[derive(Clone)]
+pub struct PowerBackends {
+    #[cfg(target_os = "windows")]
+    pub ryzenadj: Arc<RwLock<Option<RyzenAdj>>>,
+
+    #[cfg(target_os = "linux")]
+    pub linux: Arc<RwLock<Option<LinuxPower>>>,
+}
+
+impl PowerBackends {
+    /// The backend `choice` resolves to, if it is available right now.
+    pub async fn select(&self, choice: PowerBackendKind) -> Option<Arc<dyn PowerBackend>> {
+        match choice {
+            #[cfg(target_os = "windows")]
+            PowerBackendKind::Auto | PowerBackendKind::Ryzenadj => {
+                let ryz = self.ryzenadj.read().await.clone()?;
+                Some(Arc::new(ryz))
+            }
+            #[cfg(target_os = "linux")]
+            PowerBackendKind::Auto | PowerBackendKind::Linux => {
+                let lp = self.linux.read().await.clone()?;
+                Some(Arc::new(lp))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl PowerBackend for RyzenAdj {
+    fn kind(&self) -> PowerBackendKind {
+        PowerBackendKind::Ryzenadj
+    }
+
+    // Boost mode and scheduler hints go through powercfg, which every Windows install has
+    fn capabilities(&self) -> Pin<Box<dyn Future<Output = PowerCapabilities> + Send + '_>> {
+        Box::pin(async move {
+            PowerCapabilities {
+                supports_boost: true,
+                supports_scheduler_hints: true,
+                ..self.get_capabilities()
+            }
+        })
+    }
+
+    fn state(&self) -> BackendFuture<'_, PowerState> {
+        Box::pin(async move {
+            let mut state = self.get_state().await?;
+            state.boost = crate::cli::powercfg::boost_enabled().await.ok().flatten();
+            state.scheduler_hints = crate::cli::powercfg::scheduler_hints().await.ok();
+            Ok(state)
+        })
+    }
+
+    fn set_tdp_watts(&self, watts: u32) -> BackendFuture<'_, ()> {
+        Box::pin(RyzenAdj::set_tdp_watts(self, watts))
+    }
+
+    fn set_power_limits(&self, stapm: Option<u32>, fast: Option<u32>, slow: Option<u32>) -> BackendFuture<'_, ()> {
+        Box::pin(RyzenAdj::set_power_limits(self, stapm, fast, slow))
+    }
+
+    fn set_thermal_limit_c(&self, celsius: u32) -> BackendFuture<'_, ()> {
+        Box::pin(RyzenAdj::set_thermal_limit_c(self, celsius))
+    }
+
+    fn set_boost(&self, enabled: bool) -> BackendFuture<'_, ()> {
+        Box::pin(crate::cli::powercfg::set_boost(enabled))
+    }
+
+    fn set_scheduler_hints<'a>(&'a self, hints: &'a SchedulerHints) -> BackendFuture<'a, ()> {
+        Box::pin(crate::cli::powercfg::set_scheduler_hints(hints))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl PowerBackend for LinuxPower {
+    fn kind(&self) -> PowerBackendKind {
+        PowerBackendKind::Linux
+    }
+
+    fn capabilities(&self) -> Pin<Box<dyn Future<Output = PowerCapabilities> + Send + '_>> {
+        Box::pin(self.get_capabilities())
+    }
+
+    fn state(&self) -> BackendFuture<'_, PowerState> {
+        Box::pin(self.get_state())
+    }
+
+    fn set_governor<'a>(&'a self, governor: &'a str) -> BackendFuture<'a, ()> {
+        Box::pin(LinuxPower::set_governor(self, governor))
+    }
+
+    fn set_epp_preference<'a>(&'a self, preference: &'a str) -> BackendFuture<'a, ()> {
+        Box::pin(LinuxPower::set_epp_preference(self, preference))
+    }
+
+    fn frequency_limits(&self) -> BackendFuture<'_, (u32, u32)> {
+        Box::pin(self.get_configured_frequency_limits())
+    }
+
+    fn set_frequency_limits(&self, min_mhz: Option<u32>, max_mhz: Option<u32>) -> BackendFuture<'_, ()> {
+        Box::pin(self.set_freq_limits(min_mhz, max_mhz))
+    }
+
+    fn set_boost(&self, enabled: bool) -> BackendFuture<'_, ()> {
+        Box::pin(LinuxPower::set_boost(self, enabled))
+    }
+}
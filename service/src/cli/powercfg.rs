@@ -0,0 +1,169 @@
+// Processor performance boost mode (PERFBOOSTMODE) and the scheduler hints (heterogeneous
+// scheduling policy, processor state min/max, sleep timeout) on the active power scheme via
+// `powercfg`. Both the AC and DC indexes are written, so a value holds across a plug/unplug until
+// the power task applies the other profile. Some of these are hidden by default, hence `/qh`.
+
+#[cfg(target_os = "windows")]
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use crate::types::SchedulerHints;
+#[cfg(target_os = "windows")]
+use crate::utils::global_cache;
+
+#[cfg(target_os = "windows")]
+const BOOST_SETTING: [&str; 3] = ["SCHEME_CURRENT", "SUB_PROCESSOR", "PERFBOOSTMODE"];
+/// PERFBOOSTMODE index for "Disabled"
+const BOOST_OFF: u32 = 0;
+/// PERFBOOSTMODE index for "Aggressive", the Windows default on most laptops
+#[cfg(target_os = "windows")]
+const BOOST_ON: u32 = 2;
+
+/// `Some(enabled)` when AC and DC agree; `None` when they differ (the power task then rewrites both).
+#[cfg(target_os = "windows")]
+pub async fn boost_enabled() -> Result<Option<bool>, String> {
+    const QUERY_TTL: Duration = Duration::from_millis(2000);
+    global_cache::cache_get_or_update("powercfg.boost", QUERY_TTL, true, || async {
+        let mut args = vec!["/qh"];
+        args.extend(BOOST_SETTING);
+        let out = run(&args).await?;
+        let (ac, dc) = parse_ac_dc_indexes(&out).ok_or("no AC/DC index in powercfg output")?;
+        Ok(if ac == dc { Some(ac != BOOST_OFF) } else { None })
+    })
+    .await
+}
+
+#[cfg(target_os = "windows")]
+pub async fn set_boost(enabled: bool) -> Result<(), String> {
+    let value = if enabled { BOOST_ON } else { BOOST_OFF }.to_string();
+    for verb in ["/setacvalueindex", "/setdcvalueindex"] {
+        let mut args = vec![verb];
+        args.extend(BOOST_SETTING);
+        args.push(&value);
+        run_write(&args).await?;
+    }
+    // Value index changes only take effect once the scheme is re-activated
+    run_write(&["/setactive", "SCHEME_CURRENT"]).await?;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+const SCHED_POLICY_SETTING: [&str; 3] = ["SCHEME_CURRENT", "SUB_PROCESSOR", "SCHEDPOLICY"];
+#[cfg(target_os = "windows")]
+const PROC_MIN_SETTING: [&str; 3] = ["SCHEME_CURRENT", "SUB_PROCESSOR", "PROCTHROTTLEMIN"];
+#[cfg(target_os = "windows")]
+const PROC_MAX_SETTING: [&str; 3] = ["SCHEME_CURRENT", "SUB_PROCESSOR", "PROCTHROTTLEMAX"];
+#[cfg(target_os = "windows")]
+const STANDBY_SETTING: [&str; 3] = ["SCHEME_CURRENT", "SUB_SLEEP", "STANDBYIDLE"];
+
+/// The index when AC and DC agree; `None` when they differ (the power task then rewrites both).
+#[cfg(target_os = "windows")]
+async fn query_index(setting: [&str; 3]) -> Result<Option<u32>, String> {
+    let mut args = vec!["/qh"];
+    args.extend(setting);
+    let out = run(&args).await?;
+    let (ac, dc) = parse_ac_dc_indexes(&out).ok_or("no AC/DC index in powercfg output")?;
+    Ok((ac == dc).then_some(ac))
+}
+
+#[cfg(target_os = "windows")]
+pub async fn scheduler_hints() -> Result<SchedulerHints, String> {
+    // Four powercfg runs per read, for settings that only change when written
+    const QUERY_TTL: Duration = Duration::from_millis(5000);
+    global_cache::cache_get_or_update("powercfg.scheduler_hints", QUERY_TTL, true, || async {
+        Ok(SchedulerHints {
+            heterogeneous_policy: query_index(SCHED_POLICY_SETTING).await?,
+            processor_min_pct: query_index(PROC_MIN_SETTING).await?,
+            processor_max_pct: query_index(PROC_MAX_SETTING).await?,
+            standby_timeout_min: query_index(STANDBY_SETTING).await?.map(|s| s / 60),
+        })
+    })
+    .await
+}
+
+#[cfg(target_os = "windows")]
+pub async fn set_scheduler_hints(hints: &SchedulerHints) -> Result<(), String> {
+    let writes = [
+        (SCHED_POLICY_SETTING, hints.heterogeneous_policy),
+        (PROC_MIN_SETTING, hints.processor_min_pct),
+        (PROC_MAX_SETTING, hints.processor_max_pct),
+        (STANDBY_SETTING, hints.standby_timeout_min.map(|m| m * 60)),
+    ];
+    for (setting, value) in writes {
+        let Some(value) = value.map(|v| v.to_string()) else {
+            continue;
+        };
+        for verb in ["/setacvalueindex", "/setdcvalueindex"] {
+            let mut args = vec![verb];
+            args.extend(setting);
+            args.push(&value);
+            run_write(&args).await?;
+        }
+    }
+    run_write(&["/setactive", "SCHEME_CURRENT"]).await?;
+    Ok(())
+}
+
+/// `run` for commands that change the power scheme; recorded instead of run during a dry run.
+#[cfg(target_os = "windows")]
+async fn run_write(args: &[&str]) -> Result<String, String> {
+    if crate::dry_run::intercept(|| crate::dry_run::command_line("powercfg", args)) {
+        return Ok(String::new());
+    }
+    run(args).await
+}
+
+#[cfg(target_os = "windows")]
+async fn run(args: &[&str]) -> Result<String, String> {
+    let out = tokio::process::Command::new("powercfg")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("failed to run powercfg: {e}"))?;
+    if !out.status.success() {
+        return Err(format!(
+            "powercfg {} exited with {}: {}",
+            args.join(" "),
+            out.status,
+            String::from_utf8_lossy(&out.stdout).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// The current AC and DC indexes are the last two hex values of a single-setting query. Labels
+/// are localized, so only the `0x` values are relied on (possible indexes are printed as decimal).
+fn parse_ac_dc_indexes(out: &str) -> Option<(u32, u32)> {
+    let values: Vec<u32> = out
+        .lines()
+        .filter_map(|l| l.rsplit(':').next())
+        .filter_map(|v| v.trim().strip_prefix("0x"))
+        .filter_map(|v| u32::from_str_radix(v, 16).ok())
+        .collect();
+    match values.as_slice() {
+        [.., ac, dc] => Some((*ac, *dc)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_current_indexes_from_query() {
+        let out = "\
+Power Scheme GUID: 381b4222-f694-41f0-9685-ff5bb260df2e  (Balanced)
+  Subgroup GUID: 54533251-82be-4824-96c1-47b60b740d00  (Processor power management)
+    Power Setting GUID: be337238-0d82-4146-a960-4f3749d470c7  (Processor performance boost mode)
+      Possible Setting Index: 000
+      Possible Setting Friendly Name: Disabled
+      Possible Setting Index: 002
+      Possible Setting Friendly Name: Aggressive
+    Current AC Power Setting Index: 0x00000002
+    Current DC Power Setting Index: 0x00000000
+";
+        assert_eq!(parse_ac_dc_indexes(out), Some((2, BOOST_OFF)));
+        assert_eq!(parse_ac_dc_indexes("Possible Setting Index: 000"), None);
+    }
+}
@@ -29,14 +29,40 @@ impl RyzenAdj {
     pub async fn set_tdp_watts(&self, watts: u32) -> Result<(), String> {
         let mw = watts.saturating_mul(1000).to_string();
         let _ = self
-            .run(&["--stapm-limit", &mw, "--fast-limit", &mw, "--slow-limit", &mw])
+            .run_write(&["--stapm-limit", &mw, "--fast-limit", &mw, "--slow-limit", &mw])
             .await?;
         Ok(())
     }
 
+    /// Set the STAPM/fast/slow limits individually (expects watts); `None` leaves that limit alone
+    pub async fn set_power_limits(
+        &self,
+        stapm_watts: Option<u32>,
+        fast_watts: Option<u32>,
+        slow_watts: Option<u32>,
+    ) -> Result<(), String> {
+        let mut args = Vec::new();
+        for (flag, watts) in [
+            ("--stapm-limit", stapm_watts),
+            ("--fast-limit", fast_watts),
+            ("--slow-limit", slow_watts),
+        ] {
+            if let Some(w) = watts {
+                args.push(flag.to_string());
+                args.push(w.saturating_mul(1000).to_string());
+            }
+        }
+        if args.is_empty() {
+            return Ok(());
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let _ = self.run_write(&args).await?;
+        Ok(())
+    }
+
     /// Set thermal limit (Tctl) in degrees Celsius
     pub async fn set_thermal_limit_c(&self, celsius: u32) -> Result<(), String> {
-        let _ = self.run(&["--tctl-temp", &celsius.to_string()]).await?;
+        let _ = self.run_write(&["--tctl-temp", &celsius.to_string()]).await?;
         Ok(())
     }
 
@@ -64,6 +90,8 @@ impl RyzenAdj {
             supports_epp: false,
             supports_governor: false,
             supports_frequency_limits: false,
+            supports_boost: false,
+            supports_scheduler_hints: false,
             available_epp_preferences: None,
             available_governors: None,
             frequency_min_mhz: None,
@@ -78,21 +106,18 @@ impl RyzenAdj {
         let info = self.info().await?;
         Ok(PowerState {
             tdp_limit_watts: info.tdp_watts,
+            stapm_limit_watts: info.stapm_watts,
+            fast_limit_watts: info.fast_limit_watts,
+            slow_limit_watts: info.slow_limit_watts,
             thermal_limit_c: info.thermal_limit_c,
-            epp_preference: None,
-            governor: None,
-            min_freq_mhz: None,
-            max_freq_mhz: None,
+            ..Default::default()
         })
     }
 
-    /// Apply a power profile (uses TDP and thermal fields, ignores Linux-specific fields)
+    /// Apply a power profile (uses TDP, power limit and thermal fields, ignores Linux-specific fields)
     pub async fn apply_profile(&self, profile: &PowerProfile) -> Result<(), String> {
-        if let Some(tdp) = &profile.tdp_watts {
-            if tdp.enabled && tdp.value > 0 {
-                self.set_tdp_watts(tdp.value).await?;
-            }
-        }
+        let [stapm, fast, slow] = profile.power_limits();
+        self.set_power_limits(stapm, fast, slow).await?;
 
         if let Some(thermal) = &profile.thermal_limit_c {
             if thermal.enabled && thermal.value > 0 {
@@ -103,6 +128,14 @@ impl RyzenAdj {
         Ok(())
     }
 
+    /// `run` for commands that change SMU limits; recorded instead of run during a dry run.
+    async fn run_write(&self, args: &[&str]) -> Result<String, String> {
+        if crate::dry_run::intercept(|| crate::dry_run::command_line(&self.path, args)) {
+            return Ok(String::new());
+        }
+        self.run(args).await
+    }
+
     async fn run(&self, args: &[&str]) -> Result<String, String> {
         use tokio::time::{timeout, Duration};
         let args: Vec<&str> = {
@@ -113,25 +146,31 @@ impl RyzenAdj {
             }
             v
         };
-        let child = Command::new(&self.path)
-            .args(&args)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("spawn failed: {e}"))?;
-        let output = timeout(Duration::from_secs(60), child.wait_with_output())
-            .await
-            .map_err(|_| "ryzenadj timed out".to_string())
-            .and_then(|res| res.map_err(|e| format!("wait failed: {e}")))?;
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            Err(format!(
-                "exit {}: {}",
-                output.status,
-                String::from_utf8_lossy(&output.stderr)
-            ))
+        let started = std::time::Instant::now();
+        let result = async {
+            let child = Command::new(&self.path)
+                .args(&args)
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .spawn()
+                .map_err(|e| format!("spawn failed: {e}"))?;
+            let output = timeout(Duration::from_secs(60), child.wait_with_output())
+                .await
+                .map_err(|_| "ryzenadj timed out".to_string())
+                .and_then(|res| res.map_err(|e| format!("wait failed: {e}")))?;
+            if output.status.success() {
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            } else {
+                Err(format!(
+                    "exit {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr)
+                ))
+            }
         }
+        .await;
+        crate::metrics::record_cli_call("ryzenadj", &args, started.elapsed(), result.is_ok());
+        result
     }
 }
 
@@ -199,7 +238,7 @@ pub async fn attempt_install_via_direct_download() -> Result<(), String> {
         base_dir.to_string_lossy(),
         url
     );
-    let final_path = dl::download_to_path(&url, &base_dir.to_string_lossy().to_string()).await?;
+    let final_path = dl::download_to_path(&url, base_dir.to_string_lossy().as_ref()).await?;
 
     if let Ok(meta) = std::fs::metadata(&final_path) {
         info!("ryzenadj downloaded size: {} bytes", meta.len());
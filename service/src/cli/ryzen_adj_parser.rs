@@ -2,12 +2,23 @@ use poem_openapi::Object;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::types::PowerRails;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
 pub struct RyzenAdjInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tdp_watts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stapm_watts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fast_limit_watts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_limit_watts: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thermal_limit_c: Option<u32>,
+    /// Live per-rail power from the PM table (families whose table ryzenadj can decode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rails: Option<PowerRails>,
 }
 
 /// Parse output of `ryzenadj --info --dump-table`
@@ -18,6 +29,9 @@ pub fn parse_info(text: &str) -> RyzenAdjInfo {
         return info;
     }
     let mut limits_w: Vec<f32> = Vec::new();
+    let mut core_w: Option<f32> = None;
+    let mut soc_w: Option<f32> = None;
+    let mut socket_w: Option<f32> = None;
 
     // Matches lines like: | STAPM LIMIT         |    67.000 | stapm-limit        |
     // Columns: name | value | parameter
@@ -32,21 +46,49 @@ pub fn parse_info(text: &str) -> RyzenAdjInfo {
                 let name = c.get(1).map(|m| m.as_str().trim()).unwrap_or("").to_ascii_uppercase();
                 let val = c.get(2).and_then(|m| m.as_str().trim().parse::<f32>().ok());
                 if let Some(v) = val {
-                    // Collect power limit candidates
-                    if name.contains("STAPM LIMIT")
-                        || name.contains("PPT LIMIT FAST")
-                        || name.contains("PPT LIMIT SLOW")
-                    {
+                    // Power limits, individually and as TDP candidates
+                    let limit = if name.contains("STAPM LIMIT") {
+                        Some(&mut info.stapm_watts)
+                    } else if name.contains("PPT LIMIT FAST") {
+                        Some(&mut info.fast_limit_watts)
+                    } else if name.contains("PPT LIMIT SLOW") {
+                        Some(&mut info.slow_limit_watts)
+                    } else {
+                        None
+                    };
+                    if let Some(slot) = limit {
+                        *slot = Some(v.round().max(1.0) as u32);
                         limits_w.push(v);
                     }
                     // Thermal limit
                     if name.contains("THM LIMIT CORE") || name.contains("TCTL") {
                         info.thermal_limit_c = Some(v.round() as u32);
                     }
+                    // Rail power: one CORE POWER row per core, plus SoC and whole-socket rows
+                    if name.starts_with("CORE POWER") {
+                        core_w = Some(core_w.unwrap_or(0.0) + v);
+                    } else if name == "SOC POWER" {
+                        soc_w = Some(v);
+                    } else if name == "SOCKET POWER" {
+                        socket_w = Some(v);
+                    }
                 }
             }
         }
     }
+    if core_w.is_some() || soc_w.is_some() || socket_w.is_some() {
+        // The table has no GFX power row; the iGPU is what remains of the socket power
+        let gfx_w = match (socket_w, core_w, soc_w) {
+            (Some(socket), Some(core), Some(soc)) => Some((socket - core - soc).max(0.0)),
+            _ => None,
+        };
+        info.rails = Some(PowerRails {
+            socket_watts: socket_w,
+            core_watts: core_w,
+            soc_watts: soc_w,
+            gfx_watts: gfx_w,
+        });
+    }
     if !limits_w.is_empty() {
         let min_w = limits_w.into_iter().fold(f32::INFINITY, f32::min);
         if min_w.is_finite() {
@@ -55,3 +97,37 @@ pub fn parse_info(text: &str) -> RyzenAdjInfo {
     }
     info
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_info_rail_power() {
+        let s = r#"
+| STAPM LIMIT         |    28.000 | stapm-limit        |
+| CORE POWER 0        |     1.500 |                    |
+| CORE POWER 1        |     2.500 |                    |
+| SOC POWER           |     3.000 |                    |
+| SOCKET POWER        |    15.000 |                    |
+"#;
+        let rails = parse_info(s).rails.expect("rails");
+        assert_eq!(rails.core_watts, Some(4.0));
+        assert_eq!(rails.soc_watts, Some(3.0));
+        assert_eq!(rails.gfx_watts, Some(8.0));
+    }
+
+    #[test]
+    fn parse_info_separate_limits() {
+        let s = r#"
+| STAPM LIMIT         |    28.000 | stapm-limit        |
+| PPT LIMIT FAST      |    42.000 | fast-limit         |
+| PPT LIMIT SLOW      |    35.400 | slow-limit         |
+"#;
+        let info = parse_info(s);
+        assert_eq!(info.stapm_watts, Some(28));
+        assert_eq!(info.fast_limit_watts, Some(42));
+        assert_eq!(info.slow_limit_watts, Some(35));
+        assert_eq!(info.tdp_watts, Some(28));
+    }
+}
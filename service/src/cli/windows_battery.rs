@@ -0,0 +1,109 @@
+use super::framework_tool_parser::PowerBatteryInfo;
+use crate::utils::global_cache;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Read battery state from the WMI battery classes (`root\wmi` BatteryStatus & co, plus
+/// `Win32_Battery`). Used as a fallback when `framework_tool --power` fails or is slow, e.g.
+/// while another process holds the EC. Capacities are reported by Windows in mWh and are
+/// converted to mAh using the present (or design) voltage to match the EC-derived fields.
+pub async fn read() -> Result<PowerBatteryInfo, String> {
+    const TTL: Duration = Duration::from_millis(2000);
+    global_cache::cache_get_or_update("wmi.battery", TTL, true, || async {
+        let ps = r#"
+$s = Get-CimInstance -Namespace root\wmi -ClassName BatteryStatus -ErrorAction SilentlyContinue | Select-Object -First 1
+$f = Get-CimInstance -Namespace root\wmi -ClassName BatteryFullChargedCapacity -ErrorAction SilentlyContinue | Select-Object -First 1
+$d = Get-CimInstance -Namespace root\wmi -ClassName BatteryStaticData -ErrorAction SilentlyContinue | Select-Object -First 1
+$c = Get-CimInstance -Namespace root\wmi -ClassName BatteryCycleCount -ErrorAction SilentlyContinue | Select-Object -First 1
+$b = Get-CimInstance Win32_Battery -ErrorAction SilentlyContinue | Select-Object -First 1
+"PowerOnline=$($s.PowerOnline)"
+"Charging=$($s.Charging)"
+"Discharging=$($s.Discharging)"
+"Voltage=$($s.Voltage)"
+"ChargeRate=$($s.ChargeRate)"
+"DischargeRate=$($s.DischargeRate)"
+"RemainingCapacity=$($s.RemainingCapacity)"
+"FullChargedCapacity=$($f.FullChargedCapacity)"
+"DesignedCapacity=$($d.DesignedCapacity)"
+"CycleCount=$($c.CycleCount)"
+"EstimatedChargeRemaining=$($b.EstimatedChargeRemaining)"
+"DesignVoltage=$($b.DesignVoltage)"
+"#;
+        let out = Command::new("powershell")
+            .arg("-NoProfile")
+            .arg("-NonInteractive")
+            .arg("-Command")
+            .arg(ps)
+            .output()
+            .await
+            .map_err(|e| format!("failed to run powershell: {e}"))?;
+        if !out.status.success() {
+            return Err(format!("WMI battery query exited with {}", out.status));
+        }
+        let info = parse_wmi_battery(&String::from_utf8_lossy(&out.stdout));
+        if info.battery_present == Some(true) {
+            Ok(info)
+        } else {
+            Err("no battery reported by WMI".to_string())
+        }
+    })
+    .await
+}
+
+/// Parse the `Key=Value` lines emitted by the query in [`read`]. Empty values mean the class or
+/// property was unavailable and leave the corresponding field unset.
+fn parse_wmi_battery(stdout: &str) -> PowerBatteryInfo {
+    let mut kv = std::collections::HashMap::new();
+    for line in stdout.lines() {
+        if let Some((k, v)) = line.trim().split_once('=') {
+            let v = v.trim();
+            if !v.is_empty() {
+                kv.insert(k.trim().to_string(), v.to_string());
+            }
+        }
+    }
+    let num = |k: &str| kv.get(k).and_then(|v| v.parse::<u32>().ok());
+    let flag = |k: &str| kv.get(k).map(|v| v.eq_ignore_ascii_case("true"));
+
+    let present_voltage_mv = num("Voltage").filter(|v| *v > 0);
+    let design_voltage_mv = num("DesignVoltage").filter(|v| *v > 0);
+    let to_mah = |mwh: u32| -> Option<u32> {
+        let mv = present_voltage_mv.or(design_voltage_mv)?;
+        Some(((mwh as u64) * 1000 / mv as u64) as u32)
+    };
+
+    let charging = flag("Charging");
+    let discharging = flag("Discharging");
+    // BatteryStatus reports the rate in mW in whichever direction the battery is moving.
+    let rate_mw = if discharging == Some(true) {
+        num("DischargeRate")
+    } else {
+        num("ChargeRate")
+    };
+    let present_rate_ma = match (rate_mw, present_voltage_mv) {
+        (Some(mw), Some(mv)) => Some(((mw as u64) * 1000 / mv as u64) as u32),
+        _ => None,
+    };
+
+    let remaining_capacity_mah = num("RemainingCapacity").and_then(to_mah);
+    let last_full_charge_capacity_mah = num("FullChargedCapacity").and_then(to_mah);
+    let percentage = num("EstimatedChargeRemaining");
+    let battery_present = Some(percentage.is_some() || remaining_capacity_mah.is_some());
+
+    PowerBatteryInfo {
+        ac_present: flag("PowerOnline"),
+        battery_present,
+        last_full_charge_capacity_mah,
+        remaining_capacity_mah,
+        percentage,
+        soc_pct: percentage,
+        present_voltage_mv,
+        present_rate_ma,
+        design_capacity_mah: num("DesignedCapacity").and_then(to_mah),
+        design_voltage_mv,
+        cycle_count: num("CycleCount"),
+        charging,
+        discharging,
+        ..Default::default()
+    }
+}
@@ -5,6 +5,10 @@ use std::path::PathBuf;
 use crate::types::Config;
 use tracing::info;
 
+/// Held by config writers (`POST /config`, temporary overrides and their revert) from the
+/// revision check until the new config is stored.
+pub static WRITE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 pub fn config_path() -> PathBuf {
     // Explicit override always wins (all platforms)
     if let Ok(p) = std::env::var("FRAMEWORK_CONTROL_CONFIG") {
@@ -21,7 +25,7 @@ pub fn config_path() -> PathBuf {
     // Linux: system-wide config
     #[cfg(target_os = "linux")]
     {
-        return PathBuf::from("/etc").join("framework-control").join("config.json");
+        PathBuf::from("/etc").join("framework-control").join("config.json")
     }
 
     // Unsupported platforms: make this explicit instead of silently picking a path.
@@ -40,17 +44,30 @@ pub fn load() -> Config {
                 info!("Loaded config from {:?}", path);
                 return cfg;
             }
+            if let Some(cfg) = crate::config_check::salvage(&buf) {
+                info!(
+                    "Loaded config from {:?} without the sections that failed to parse",
+                    path
+                );
+                return cfg;
+            }
         }
     }
     Config::default()
 }
 
+/// Written to a temporary file and renamed over the config, so readers (the service's own
+/// `POST /config/reload`, configuration management tools) never see a half-written file.
 pub fn save(cfg: &Config) -> Result<(), String> {
     let path = config_path();
     if let Some(dir) = path.parent() {
         create_dir_all(dir).map_err(|e| e.to_string())?;
     }
-    let mut f = File::create(&path).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
     let s = serde_json::to_string_pretty(cfg).map_err(|e| e.to_string())?;
-    f.write_all(s.as_bytes()).map_err(|e| e.to_string())
+    let mut f = File::create(&tmp).map_err(|e| e.to_string())?;
+    f.write_all(s.as_bytes()).map_err(|e| e.to_string())?;
+    f.sync_all().map_err(|e| e.to_string())?;
+    drop(f);
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
 }
@@ -0,0 +1,352 @@
+// Startup config sanity check. A config that fails to parse used to be replaced wholesale by the
+// defaults, and one that parses but holds bad values was handed to the background tasks, which
+// then failed the same way every loop. Instead:
+// - a section that no longer parses (hand edit, downgrade) is dropped and the rest loads;
+// - a section that fails validation is quarantined and runs on its defaults;
+// - once framework_tool is up, references to hardware this machine does not have (curve
+//   sensors, fan indexes) are repaired in the running config.
+// Quarantined sections are copied to `config.quarantine.json` next to the config. Nothing here
+// rewrites `config.json`; the next accepted write persists the repaired state. Every finding is
+// reported by `/health` until a write to its section replaces it.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::{sleep, Duration};
+use tracing::warn;
+
+use crate::cli::FrameworkTool;
+use crate::types::{Config, ConfigIssue, ConfigIssueAction, CurveAmbientConfig};
+use crate::validation::{self, Errors};
+
+/// Give the extra sensor providers this long to report before judging curve sensors.
+const EXTRA_SENSORS_WAIT: Duration = Duration::from_secs(60);
+
+static ISSUES: RwLock<Vec<ConfigIssue>> = RwLock::new(Vec::new());
+static QUARANTINE: Mutex<BTreeMap<String, Value>> = Mutex::new(BTreeMap::new());
+
+/// Findings since startup that no write has replaced yet.
+pub fn issues() -> Vec<ConfigIssue> {
+    ISSUES.read().map(|v| v.clone()).unwrap_or_default()
+}
+
+/// Forget the findings for `section` after a write replaced it.
+pub fn clear_section(section: &str) {
+    if let Ok(mut w) = ISSUES.write() {
+        w.retain(|i| section_of(&i.field) != section);
+    }
+}
+
+/// Top-level section of a field path (`fan.curve.points[2][1]` -> `fan`).
+fn section_of(field: &str) -> &str {
+    field.split(['.', '[']).next().unwrap_or(field)
+}
+
+fn record(field: &str, message: String, action: ConfigIssueAction) {
+    let Ok(mut w) = ISSUES.write() else { return };
+    if !w.iter().any(|i| i.field == field && i.message == message) {
+        warn!("config check: {}: {} ({:?})", field, message, action);
+        w.push(ConfigIssue {
+            field: field.to_string(),
+            message,
+            action,
+        });
+    }
+}
+
+fn quarantine_path() -> PathBuf {
+    crate::config::config_path().with_file_name("config.quarantine.json")
+}
+
+/// Keep the original of a dropped section (`""` for the whole file) where the user can find it.
+fn quarantine(section: &str, value: Value) {
+    let Ok(mut q) = QUARANTINE.lock() else { return };
+    q.insert(section.to_string(), value);
+    let path = quarantine_path();
+    match serde_json::to_string_pretty(&*q) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("config check: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("config check: failed to serialize quarantined sections: {}", e),
+    }
+}
+
+/// Load what still parses from a config file that does not parse as a whole.
+pub fn salvage(raw: &str) -> Option<Config> {
+    let Ok(Value::Object(sections)) = serde_json::from_str::<Value>(raw) else {
+        record(
+            "",
+            "config file is not a JSON object; running on defaults".into(),
+            ConfigIssueAction::Quarantined,
+        );
+        quarantine("", Value::String(raw.to_string()));
+        return None;
+    };
+    let mut kept = serde_json::Map::new();
+    for (section, value) in sections {
+        let probe = Value::Object(serde_json::Map::from_iter([(section.clone(), value.clone())]));
+        match serde_json::from_value::<Config>(probe) {
+            Ok(_) => {
+                kept.insert(section, value);
+            }
+            Err(e) => {
+                record(
+                    &section,
+                    format!("section does not parse ({}); running on defaults", e),
+                    ConfigIssueAction::Quarantined,
+                );
+                quarantine(&section, value);
+            }
+        }
+    }
+    serde_json::from_value(Value::Object(kept)).ok()
+}
+
+/// Validate every section of the loaded config as a write would; failing sections run on
+/// their defaults.
+pub fn check_loaded(cfg: &mut Config) {
+    check_section("fan", &mut cfg.fan, validation::fan);
+    check_section("power", &mut cfg.power, |e, p| validation::power(e, p, None));
+    check_section("battery", &mut cfg.battery, validation::battery);
+    check_section("telemetry", &mut cfg.telemetry, validation::telemetry);
+    check_section("updates", &mut cfg.updates, validation::updates);
+    check_section(
+        "keyboard_backlight",
+        &mut cfg.keyboard_backlight,
+        validation::keyboard_backlight,
+    );
+    check_section("reports", &mut cfg.reports, validation::reports);
+    check_section("network", &mut cfg.network, validation::network);
+    check_section("sensors", &mut cfg.sensors, validation::sensors);
+    check_section("logging", &mut cfg.logging, validation::logging);
+    check_section("backup", &mut cfg.backup, validation::backup);
+}
+
+fn check_section<T: Serialize + Default>(section: &str, value: &mut T, validate: impl FnOnce(&mut Errors, &T)) {
+    let mut errs = Errors::default();
+    validate(&mut errs, value);
+    let Err(fields) = errs.into_result() else { return };
+    for f in fields {
+        record(
+            &f.field,
+            format!("{}; section running on defaults", f.message),
+            ConfigIssueAction::Quarantined,
+        );
+    }
+    quarantine(section, serde_json::to_value(&*value).unwrap_or_default());
+    *value = T::default();
+}
+
+/// Once framework_tool is available and the extra sensor providers have reported, repair
+/// references to sensors and fans this machine does not have. Runs once per start.
+pub async fn check_hardware(
+    cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
+    cfg: Arc<tokio::sync::RwLock<Config>>,
+) {
+    let cli = loop {
+        if let Some(cli) = cli_lock.read().await.clone() {
+            break cli;
+        }
+        sleep(Duration::from_secs(1)).await;
+    };
+    let waited = std::time::Instant::now();
+    while !crate::sensors::extra_collected() && waited.elapsed() < EXTRA_SENSORS_WAIT {
+        sleep(Duration::from_secs(1)).await;
+    }
+    let thermal = match crate::sensors::thermal(&cli).await {
+        Ok(t) => t,
+        Err(e) => {
+            warn!("config check: thermal read failed, skipping hardware checks: {}", e);
+            return;
+        }
+    };
+    let mut cfg = cfg.write().await;
+    repair_hardware(&mut cfg, &thermal.temps, thermal.fans.len() as u32);
+}
+
+fn repair_hardware(cfg: &mut Config, temps: &BTreeMap<String, i32>, fan_count: u32) {
+    let zones = cfg.sensors.zones.clone();
+    let repair_sensors = |field: &str, sensors: &mut Vec<String>, fallback_all: bool| {
+        if temps.is_empty() {
+            return;
+        }
+        let before = sensors.len();
+        sensors.retain(|name| {
+            let covered = crate::sensors::zones::expand(std::slice::from_ref(name), temps, &zones)
+                .iter()
+                .any(|s| temps.keys().any(|t| t.eq_ignore_ascii_case(s)));
+            if !covered {
+                record(
+                    field,
+                    format!("sensor '{}' is not reported on this machine; removed", name),
+                    ConfigIssueAction::Repaired,
+                );
+            }
+            covered
+        });
+        if sensors.is_empty() && before > 0 && fallback_all {
+            *sensors = temps.keys().cloned().collect();
+        }
+    };
+    if let Some(c) = cfg.fan.curve.as_mut() {
+        repair_sensors("fan.curve.sensors", &mut c.curve.sensors, true);
+    }
+    for (i, o) in cfg.fan.overrides.iter_mut().flatten().enumerate() {
+        if let Some(c) = o.curve.as_mut() {
+            repair_sensors(&format!("fan.overrides[{}].curve.sensors", i), &mut c.sensors, true);
+        }
+    }
+    // Empty lists already mean "every sensor" (target and rpm modes) or "every GPU sensor" (GPU curve)
+    if let Some(t) = cfg.fan.target.as_mut() {
+        repair_sensors("fan.target.sensors", &mut t.sensors, false);
+    }
+    if let Some(r) = cfg.fan.rpm.as_mut() {
+        repair_sensors("fan.rpm.sensors", &mut r.sensors, false);
+    }
+    if let Some(c) = cfg.fan.gpu.as_mut().and_then(|g| g.curve.as_mut()) {
+        repair_sensors("fan.gpu.curve.sensors", &mut c.sensors, false);
+    }
+    // An ambient sensor that is not reported would leave the curve unshifted without a word
+    let repair_ambient = |field: &str, ambient: &mut Option<CurveAmbientConfig>| {
+        let Some(name) = ambient.as_ref().map(|a| a.sensor.clone()) else {
+            return;
+        };
+        let reported = crate::sensors::zones::expand(std::slice::from_ref(&name), temps, &zones)
+            .iter()
+            .any(|s| temps.keys().any(|t| t.eq_ignore_ascii_case(s)));
+        if !temps.is_empty() && !reported {
+            record(
+                field,
+                format!(
+                    "ambient sensor '{}' is not reported on this machine; compensation removed",
+                    name
+                ),
+                ConfigIssueAction::Repaired,
+            );
+            *ambient = None;
+        }
+    };
+    if let Some(c) = cfg.fan.curve.as_mut() {
+        repair_ambient("fan.curve.ambient", &mut c.curve.ambient);
+    }
+    for (i, o) in cfg.fan.overrides.iter_mut().flatten().enumerate() {
+        if let Some(c) = o.curve.as_mut() {
+            repair_ambient(&format!("fan.overrides[{}].curve.ambient", i), &mut c.ambient);
+        }
+    }
+    if let Some(c) = cfg.fan.gpu.as_mut().and_then(|g| g.curve.as_mut()) {
+        repair_ambient("fan.gpu.curve.ambient", &mut c.ambient);
+    }
+
+    if fan_count == 0 {
+        return;
+    }
+    if let Some(overrides) = cfg.fan.overrides.as_mut() {
+        overrides.retain(|o| {
+            let present = o.index < fan_count;
+            if !present {
+                record(
+                    "fan.overrides",
+                    format!("fan {} does not exist ({} fans); override removed", o.index, fan_count),
+                    ConfigIssueAction::Repaired,
+                );
+            }
+            present
+        });
+    }
+    if let Some(curves) = cfg.fan.rpm.as_mut().and_then(|r| r.fans.as_mut()) {
+        curves.retain(|c| {
+            let present = c.index < fan_count;
+            if !present {
+                record(
+                    "fan.rpm.fans",
+                    format!("fan {} does not exist ({} fans); RPM curve removed", c.index, fan_count),
+                    ConfigIssueAction::Repaired,
+                );
+            }
+            present
+        });
+    }
+    if let Some(duties) = cfg.fan.manual.as_mut().and_then(|m| m.fan_duties.as_mut()) {
+        duties.retain(|d| {
+            let present = d.index < fan_count;
+            if !present {
+                record(
+                    "fan.manual.fan_duties",
+                    format!("fan {} does not exist ({} fans); duty removed", d.index, fan_count),
+                    ConfigIssueAction::Repaired,
+                );
+            }
+            present
+        });
+    }
+    if let Some(index) = cfg.fan.gpu.as_ref().and_then(|g| g.ec_fan_index) {
+        if index >= fan_count {
+            record(
+                "fan.gpu.ec_fan_index",
+                format!(
+                    "fan {} does not exist ({} fans); GPU fan control disabled",
+                    index, fan_count
+                ),
+                ConfigIssueAction::Quarantined,
+            );
+            quarantine("fan.gpu", serde_json::to_value(&cfg.fan.gpu).unwrap_or_default());
+            cfg.fan.gpu = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CurveConfig, FanOverride, GlobalCurveConfig};
+
+    #[test]
+    fn repairs_missing_sensors_and_fans() {
+        let mut cfg = Config::default();
+        cfg.fan.curve = Some(GlobalCurveConfig {
+            curve: CurveConfig {
+                sensors: vec!["PECI".into(), "cpu".into()],
+                ..Default::default()
+            },
+            poll_ms: 2000,
+        });
+        cfg.fan.overrides = Some(vec![
+            FanOverride {
+                index: 0,
+                manual: None,
+                curve: Some(CurveConfig {
+                    sensors: vec!["NVMe 0".into()],
+                    ambient: Some(CurveAmbientConfig {
+                        sensor: "F75303_AMB".into(),
+                        reference_c: 25,
+                        shift_per_c: 1.0,
+                        max_shift_c: 10,
+                    }),
+                    ..Default::default()
+                }),
+            },
+            FanOverride {
+                index: 2,
+                manual: None,
+                curve: None,
+            },
+        ]);
+        let temps = BTreeMap::from([("APU".to_string(), 60), ("F75303_Local".to_string(), 40)]);
+        repair_hardware(&mut cfg, &temps, 2);
+
+        // `cpu` is a zone covering APU; PECI is not on this board
+        assert_eq!(cfg.fan.curve.unwrap().curve.sensors, ["cpu"]);
+        let overrides = cfg.fan.overrides.unwrap();
+        assert_eq!(overrides.len(), 1);
+        // Nothing left: fall back to every sensor
+        assert_eq!(overrides[0].curve.as_ref().unwrap().sensors, ["APU", "F75303_Local"]);
+        assert!(overrides[0].curve.as_ref().unwrap().ambient.is_none());
+        assert_eq!(section_of("fan.overrides[0].curve.sensors"), "fan");
+    }
+}
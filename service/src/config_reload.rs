@@ -0,0 +1,183 @@
+// Reloading `config.json` written by configuration management (Ansible, Nix, Puppet, ...).
+//
+// Such tools own the file and want it applied without a restart. `POST /config/reload` reads it
+// under `config::WRITE_LOCK` (so it never sees the service's own write half done, and no write
+// lands between the read and the apply), validates every section as a write would and applies
+// the sections that differ from the running config. A rejected file changes nothing. The file is
+// not rewritten, so the tool does not see its own output drift; the revision in it is ignored
+// and the running revision is bumped instead.
+//
+// The same contract is exposed as exit codes for tools that run commands rather than HTTP
+// requests:
+// - `--check-config [PATH]` validates a file offline (Ansible `validate:`, a Nix build check);
+// - `--reload-config [--port N]` asks the running service to reload and prints its answer.
+// Both exit with `EXIT_OK`, `EXIT_INVALID`, `EXIT_UNREADABLE` or (reload only) `EXIT_UNREACHABLE`.
+// `/health` reports the outcome of the last reload as `last_reload`.
+
+use std::path::Path;
+use std::sync::RwLock;
+
+use serde_json::Value;
+
+use crate::types::{Config, ConfigReloadStatus, FieldError};
+use crate::validation::{self, Errors};
+
+/// Valid; for a reload also applied (or nothing had changed)
+pub const EXIT_OK: i32 = 0;
+/// The file parses but fails validation; the running config is untouched
+pub const EXIT_INVALID: i32 = 1;
+/// The file is missing or is not a config
+pub const EXIT_UNREADABLE: i32 = 2;
+/// No service answered on the port
+pub const EXIT_UNREACHABLE: i32 = 3;
+
+/// Error codes of a rejected reload (422)
+pub const CODE_INVALID: &str = "invalid_config";
+pub const CODE_UNREADABLE: &str = "unreadable_config";
+
+/// Top-level config sections, as named in `POST /config`
+pub const SECTIONS: [&str; 12] = [
+    "fan",
+    "power",
+    "battery",
+    "updates",
+    "telemetry",
+    "ui",
+    "keyboard_backlight",
+    "reports",
+    "network",
+    "sensors",
+    "logging",
+    "backup",
+];
+
+static LAST: RwLock<Option<ConfigReloadStatus>> = RwLock::new(None);
+
+pub fn last() -> Option<ConfigReloadStatus> {
+    LAST.read().ok().and_then(|l| l.clone())
+}
+
+pub fn record(status: ConfigReloadStatus) {
+    if let Ok(mut w) = LAST.write() {
+        *w = Some(status);
+    }
+}
+
+/// Read a config file strictly: unlike `config::load`, nothing is salvaged or defaulted.
+pub fn read(path: &Path) -> Result<Config, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&raw).map_err(|e| format!("{} is not a valid config: {}", path.display(), e))
+}
+
+/// Sections whose values differ between `a` and `b`; the revision is not compared.
+pub fn changed_sections(a: &Config, b: &Config) -> Vec<&'static str> {
+    let (Ok(a), Ok(b)) = (serde_json::to_value(a), serde_json::to_value(b)) else {
+        return SECTIONS.to_vec();
+    };
+    SECTIONS
+        .into_iter()
+        .filter(|s| a.get(*s).unwrap_or(&Value::Null) != b.get(*s).unwrap_or(&Value::Null))
+        .collect()
+}
+
+/// Every section's validation without the power backend's capabilities, which need the
+/// running service.
+pub fn validate(cfg: &Config) -> Result<(), Vec<FieldError>> {
+    let mut errs = Errors::default();
+    validation::fan(&mut errs, &cfg.fan);
+    validation::power(&mut errs, &cfg.power, None);
+    validation::battery(&mut errs, &cfg.battery);
+    validation::telemetry(&mut errs, &cfg.telemetry);
+    validation::keyboard_backlight(&mut errs, &cfg.keyboard_backlight);
+    validation::reports(&mut errs, &cfg.reports);
+    validation::network(&mut errs, &cfg.network);
+    validation::sensors(&mut errs, &cfg.sensors);
+    validation::logging(&mut errs, &cfg.logging);
+    validation::backup(&mut errs, &cfg.backup);
+    validation::updates(&mut errs, &cfg.updates);
+    errs.into_result()
+}
+
+/// `--check-config [PATH]`: validate `path` (default: the service's config path).
+pub fn check_file(path: Option<&str>) -> i32 {
+    let path = path.map(Into::into).unwrap_or_else(crate::config::config_path);
+    crate::managed_profiles::load();
+    let cfg = match read(&path) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_UNREADABLE;
+        }
+    };
+    match validate(&cfg) {
+        Ok(()) => {
+            println!("{}: ok", path.display());
+            EXIT_OK
+        }
+        Err(fields) => {
+            for f in fields {
+                eprintln!("{}: {}", f.field, f.message);
+            }
+            EXIT_INVALID
+        }
+    }
+}
+
+/// `--reload-config`: `POST /api/config/reload` on this machine and print the response. The
+/// port defaults to the one the config file names, so after a port change in the file pass the
+/// old one.
+pub async fn request_reload(port: Option<u16>) -> i32 {
+    // Strict read: `config::load` would quarantine a broken file from this client process
+    let port = port.unwrap_or_else(|| {
+        let network = read(&crate::config::config_path())
+            .map(|c| c.network)
+            .unwrap_or_default();
+        crate::network::port_for(&network)
+    });
+    let url = format!("http://{}:{}/api/config/reload", crate::network::BIND_HOST, port);
+    let resp = match reqwest::Client::new().post(&url).send().await {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("no service at {}: {}", url, e);
+            return EXIT_UNREACHABLE;
+        }
+    };
+    let status = resp.status();
+    let body: Value = resp
+        .text()
+        .await
+        .ok()
+        .and_then(|t| serde_json::from_str(&t).ok())
+        .unwrap_or_default();
+    if status.is_success() {
+        println!("{}", body);
+        return EXIT_OK;
+    }
+    eprintln!("{}", body);
+    match body.get("code").and_then(Value::as_str) {
+        Some(CODE_INVALID) => EXIT_INVALID,
+        Some(CODE_UNREADABLE) => EXIT_UNREADABLE,
+        _ => EXIT_UNREACHABLE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_sections_but_not_the_revision() {
+        let a = Config::default();
+        let mut b = Config {
+            revision: 7,
+            ..Config::default()
+        };
+        assert!(changed_sections(&a, &b).is_empty());
+        b.battery.charge_limit_max_pct = Some(crate::types::SettingU8 {
+            enabled: true,
+            value: 80,
+        });
+        b.ui.theme = Some("dark".into());
+        assert_eq!(changed_sections(&a, &b), ["battery", "ui"]);
+    }
+}
@@ -0,0 +1,209 @@
+// Device identity: a random ID generated on first start and kept in `device.json` next to the
+// config, plus the board detected from `--versions`. It is carried by config exports, telemetry
+// reports and debug captures so data from several machines can be told apart, and config
+// backups are kept under `backups/<device_id>/` so a config directory shared or synced between
+// machines does not mix them up.
+//
+// A config bundle imported from a different model does not get its model-specific settings
+// applied (TDP limits, sensor names, fan calibrations and per-fan settings differ per board)
+// unless the import is forced.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::types::{BoardModel, Config, ConfigBundle, DeviceIdentity, PartialConfig};
+
+#[derive(Serialize, Deserialize)]
+struct DeviceFile {
+    device_id: String,
+}
+
+fn device_path() -> PathBuf {
+    crate::config::config_path().with_file_name("device.json")
+}
+
+/// 128 random bits as hex; std's `RandomState` is seeded from the OS per instance.
+fn generate_id() -> String {
+    let half = || {
+        let mut h = RandomState::new().build_hasher();
+        h.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default(),
+        );
+        h.write_u32(std::process::id());
+        h.finish()
+    };
+    format!("{:016x}{:016x}", half(), half())
+}
+
+fn load_or_create() -> String {
+    let path = device_path();
+    if let Some(id) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<DeviceFile>(&s).ok())
+        .map(|f| f.device_id)
+        .filter(|id| !id.trim().is_empty())
+    {
+        return id;
+    }
+    let device_id = generate_id();
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            serde_json::to_string_pretty(&DeviceFile {
+                device_id: device_id.clone(),
+            })
+            .map_err(|e| e.to_string())
+        })
+        .and_then(|s| std::fs::write(&path, s).map_err(|e| e.to_string()));
+    match written {
+        Ok(()) => info!("device: generated device ID {}", device_id),
+        // Still usable for this run; a new ID is generated next start
+        Err(e) => warn!("device: failed to write {}: {}", path.display(), e),
+    }
+    device_id
+}
+
+pub fn id() -> &'static str {
+    static ID: OnceLock<String> = OnceLock::new();
+    ID.get_or_init(load_or_create)
+}
+
+pub fn identity() -> DeviceIdentity {
+    let (model, mainboard_type) = crate::sensors::metadata::board();
+    DeviceIdentity {
+        device_id: id().to_string(),
+        model,
+        mainboard_type,
+    }
+}
+
+pub fn bundle(cfg: &Config) -> ConfigBundle {
+    ConfigBundle {
+        device: identity(),
+        exported_at_ms: chrono::Utc::now().timestamp_millis(),
+        service_version: env!("CARGO_PKG_VERSION").to_string(),
+        config: cfg.clone(),
+    }
+}
+
+/// Save `cfg` as a bundle under `backups/<device_id>/` and return its path.
+pub fn backup(cfg: &Config) -> Result<PathBuf, String> {
+    let dir = crate::config::config_path().with_file_name("backups").join(id());
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!(
+        "config-{}.json",
+        chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+    ));
+    let s = serde_json::to_string_pretty(&bundle(cfg)).map_err(|e| e.to_string())?;
+    std::fs::write(&path, s).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// How an imported bundle's origin compares with this device
+pub struct Origin {
+    pub model_mismatch: bool,
+    pub warnings: Vec<String>,
+}
+
+pub fn compare(source: Option<&DeviceIdentity>, here: &DeviceIdentity) -> Origin {
+    let mut warnings = Vec::new();
+    let Some(source) = source else {
+        warnings.push("The bundle does not say which device it came from".to_string());
+        return Origin {
+            model_mismatch: false,
+            warnings,
+        };
+    };
+    let model_mismatch =
+        source.model != here.model && source.model != BoardModel::Unknown && here.model != BoardModel::Unknown;
+    if model_mismatch {
+        warnings.push(format!(
+            "The bundle was exported on a {} but this is a {}",
+            describe(source),
+            describe(here)
+        ));
+    } else if source.device_id != here.device_id {
+        if source.model == BoardModel::Unknown || here.model == BoardModel::Unknown {
+            warnings.push("The bundle comes from another device whose model could not be compared".to_string());
+        } else {
+            warnings.push(format!("The bundle comes from another {}", describe(source)));
+        }
+    }
+    Origin {
+        model_mismatch,
+        warnings,
+    }
+}
+
+fn describe(d: &DeviceIdentity) -> &str {
+    match d.model {
+        BoardModel::Laptop12 => "Laptop 12",
+        BoardModel::Laptop13Intel => "Laptop 13 (Intel)",
+        BoardModel::Laptop13Amd => "Laptop 13 (AMD)",
+        BoardModel::Laptop16 => "Laptop 16",
+        BoardModel::Desktop => "Desktop",
+        BoardModel::Unknown => d.mainboard_type.as_deref().unwrap_or("unknown model"),
+    }
+}
+
+/// Drop the settings that only make sense on the model they were made on; returns what was
+/// dropped as config paths. Dropped fields keep their current values.
+pub fn strip_model_specific(cfg: &mut PartialConfig) -> Vec<String> {
+    let mut skipped = Vec::new();
+    if cfg.power.take().is_some() {
+        skipped.push("power".to_string());
+    }
+    if cfg.sensors.take().is_some() {
+        skipped.push("sensors".to_string());
+    }
+    if let Some(fan) = cfg.fan.as_mut() {
+        if fan.calibration.take().is_some() {
+            skipped.push("fan.calibration".to_string());
+        }
+        if fan.calibrations.take().is_some() {
+            skipped.push("fan.calibrations".to_string());
+        }
+        if fan.overrides.take().is_some() {
+            skipped.push("fan.overrides".to_string());
+        }
+        if fan.gpu.take().is_some() {
+            skipped.push("fan.gpu".to_string());
+        }
+    }
+    skipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, model: BoardModel) -> DeviceIdentity {
+        DeviceIdentity {
+            device_id: id.into(),
+            model,
+            mainboard_type: None,
+        }
+    }
+
+    #[test]
+    fn only_a_known_different_model_is_a_mismatch() {
+        let here = device("a", BoardModel::Laptop13Amd);
+        let origin = compare(Some(&device("b", BoardModel::Laptop16)), &here);
+        assert!(origin.model_mismatch);
+        let origin = compare(Some(&device("b", BoardModel::Unknown)), &here);
+        assert!(!origin.model_mismatch && origin.warnings.len() == 1);
+        let origin = compare(Some(&device("a", BoardModel::Laptop13Amd)), &here);
+        assert!(!origin.model_mismatch && origin.warnings.is_empty());
+        assert_ne!(generate_id(), generate_id());
+    }
+}
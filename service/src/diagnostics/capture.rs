@@ -0,0 +1,209 @@
+// Debug capture (`POST /diagnostics/capture?seconds=N`): for the capture window every
+// framework_tool invocation, from the capture and from the background tasks alike, is kept with
+// its raw output, while the capture itself reads thermal twice a second (and power, charge limit
+// and PD ports every few seconds) past the caches and logs what the parsers made of each read.
+// The telemetry task samples at least twice a second meanwhile. The result is a zip for bug
+// reports about parsing or EC quirks: `raw.jsonl`, `parsed.jsonl`, `telemetry.json` (the regular
+// samples in the window) and `info.json`. One capture at a time.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::info;
+
+use crate::cli::FrameworkTool;
+use crate::types::TelemetrySample;
+
+const MIN_SECONDS: u32 = 5;
+const MAX_SECONDS: u32 = 300;
+pub const DEFAULT_SECONDS: u32 = 60;
+const TICK: Duration = Duration::from_millis(500);
+/// Telemetry poll interval while a capture runs, when the configured one is slower
+pub const TELEMETRY_POLL_MS: u64 = 500;
+/// Raw calls kept per capture, so a misbehaving loop cannot grow the archive without bound
+const MAX_RAW_CALLS: usize = 20_000;
+
+#[derive(Serialize)]
+struct RawCall {
+    ts_ms: i64,
+    args: Vec<String>,
+    duration_ms: u64,
+    ok: bool,
+    /// stdout on success, the error (exit status and stderr) otherwise
+    output: String,
+}
+
+#[derive(Serialize)]
+struct ParsedRead {
+    ts_ms: i64,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Raw calls of the capture in progress; `None` while no capture runs.
+static RAW: Mutex<Option<Vec<RawCall>>> = Mutex::new(None);
+
+pub fn active() -> bool {
+    RAW.lock().is_ok_and(|r| r.is_some())
+}
+
+/// Keep a framework_tool call for the capture in progress, if any.
+pub fn record_raw(args: &[&str], elapsed: Duration, result: &Result<String, String>) {
+    let Ok(mut raw) = RAW.lock() else { return };
+    let Some(calls) = raw.as_mut().filter(|c| c.len() < MAX_RAW_CALLS) else {
+        return;
+    };
+    let (ok, output) = match result {
+        Ok(out) => (true, out.clone()),
+        Err(e) => (false, e.clone()),
+    };
+    calls.push(RawCall {
+        ts_ms: chrono::Utc::now().timestamp_millis(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        duration_ms: elapsed.as_millis() as u64,
+        ok,
+        output,
+    });
+}
+
+/// Held while a capture runs; stops recording on drop.
+pub struct CaptureGuard;
+
+impl CaptureGuard {
+    /// Start recording, or `None` when a capture is already running.
+    pub fn try_acquire() -> Option<Self> {
+        let mut raw = RAW.lock().ok()?;
+        if raw.is_some() {
+            return None;
+        }
+        *raw = Some(Vec::new());
+        Some(CaptureGuard)
+    }
+
+    fn take(&self) -> Vec<RawCall> {
+        RAW.lock()
+            .ok()
+            .and_then(|mut r| r.replace(Vec::new()))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for CaptureGuard {
+    fn drop(&mut self) {
+        if let Ok(mut raw) = RAW.lock() {
+            *raw = None;
+        }
+    }
+}
+
+fn parsed<T: Serialize>(kind: &'static str, result: Result<T, String>) -> ParsedRead {
+    let (value, error) = match result {
+        Ok(v) => (serde_json::to_value(v).ok(), None),
+        Err(e) => (None, Some(e)),
+    };
+    ParsedRead {
+        ts_ms: chrono::Utc::now().timestamp_millis(),
+        kind,
+        value,
+        error,
+    }
+}
+
+/// Run a capture of `seconds` (clamped to 5-300) and return the zip archive.
+pub async fn run(
+    guard: &CaptureGuard,
+    cli: &FrameworkTool,
+    seconds: u32,
+    samples: &tokio::sync::RwLock<VecDeque<TelemetrySample>>,
+) -> Result<Vec<u8>, String> {
+    let seconds = seconds.clamp(MIN_SECONDS, MAX_SECONDS);
+    let started_ms = chrono::Utc::now().timestamp_millis();
+    info!("capture: recording framework_tool output for {}s", seconds);
+
+    let mut reads = vec![parsed("versions", cli.versions().await)];
+    let deadline = Instant::now() + Duration::from_secs(seconds as u64);
+    let mut tick: u32 = 0;
+    while Instant::now() < deadline {
+        let next = Instant::now() + TICK;
+        reads.push(parsed("thermal", cli.thermal_max_age(Duration::ZERO).await));
+        if tick.is_multiple_of(4) {
+            reads.push(parsed("power", cli.power().await));
+        }
+        if tick.is_multiple_of(10) {
+            reads.push(parsed(
+                "charge_limit",
+                cli.charge_limit_get_max_age(Duration::ZERO).await,
+            ));
+            reads.push(parsed("pd_ports", cli.pd_ports().await));
+        }
+        tick += 1;
+        tokio::time::sleep_until(next).await;
+    }
+    // Let the telemetry task's last fast sample land
+    sleep(TICK).await;
+
+    let raw = guard.take();
+    let telemetry: Vec<TelemetrySample> = samples
+        .read()
+        .await
+        .iter()
+        .filter(|s| s.ts_ms >= started_ms)
+        .cloned()
+        .collect();
+    let info = serde_json::json!({
+        "service_version": env!("CARGO_PKG_VERSION"),
+        "device": crate::device::identity(),
+        "os": std::env::consts::OS,
+        "framework_tool": cli.path,
+        "started_ms": started_ms,
+        "seconds": seconds,
+        "raw_calls": raw.len(),
+        "raw_calls_truncated": raw.len() >= MAX_RAW_CALLS,
+    });
+    info!("capture: done ({} framework_tool calls)", raw.len());
+    archive(&info, &raw, &reads, &telemetry)
+}
+
+fn jsonl<T: Serialize>(items: &[T]) -> Result<String, String> {
+    let mut out = String::new();
+    for item in items {
+        out.push_str(&serde_json::to_string(item).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn archive(
+    info: &serde_json::Value,
+    raw: &[RawCall],
+    reads: &[ParsedRead],
+    telemetry: &[TelemetrySample],
+) -> Result<Vec<u8>, String> {
+    let files = [
+        (
+            "info.json",
+            serde_json::to_string_pretty(info).map_err(|e| e.to_string())?,
+        ),
+        ("raw.jsonl", jsonl(raw)?),
+        ("parsed.jsonl", jsonl(reads)?),
+        (
+            "telemetry.json",
+            serde_json::to_string(telemetry).map_err(|e| e.to_string())?,
+        ),
+    ];
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in files {
+        zip.start_file(name, options).map_err(|e| format!("zip failed: {e}"))?;
+        zip.write_all(content.as_bytes())
+            .map_err(|e| format!("zip failed: {e}"))?;
+    }
+    let cursor = zip.finish().map_err(|e| format!("zip failed: {e}"))?;
+    Ok(cursor.into_inner())
+}
@@ -0,0 +1,311 @@
+// Fan curve A/B comparison (`POST /fan/compare`). A live run puts each of two `fan.profiles` in
+// place as a temporary `fan` override, lets the machine settle under it, then runs the stress
+// test's load for the same duration while sampling thermal once a second; the override reverts
+// afterwards (or by itself when its TTL runs out, if the request is dropped). The historical mode
+// compares two windows of the telemetry history instead. Both report average/max temperature of
+// the compared sensors, fan time above an RPM threshold and a noise estimate derived from RPM.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::types::{
+    Config, CurveCompareReport, CurveCompareRequest, CurveCompareResult, StressRequest, TelemetrySample, TimeWindow,
+};
+
+pub const DEFAULT_DURATION_SECS: u32 = 120;
+pub const DEFAULT_SETTLE_SECS: u32 = 60;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// The temporary override outlives its phase by this much, so it only reverts on its own when
+/// the run was abandoned
+const OVERRIDE_MARGIN_SECS: u64 = 60;
+/// Longer gaps between samples (sleep, service stopped) are not counted as fan time.
+const MAX_SAMPLE_GAP_MS: i64 = 10_000;
+/// Noise model: a Framework laptop fan at this speed is about this loud; sound power follows the
+/// fan affinity law (50·log10 of the speed ratio)
+const NOISE_REF_RPM: f32 = 3000.0;
+const NOISE_REF_DBA: f32 = 35.0;
+
+/// Hottest of `sensors` (sensor or zone names; every sensor when empty) in one sample.
+pub fn temperature(sample: &TelemetrySample, sensors: &[String], zones: &BTreeMap<String, Vec<String>>) -> Option<i32> {
+    if sensors.is_empty() {
+        return sample.temps.values().copied().max();
+    }
+    crate::sensors::zones::expand(sensors, &sample.temps, zones)
+        .iter()
+        .filter_map(|name| sample.temps.get(name).copied())
+        .max()
+}
+
+/// Estimated dB(A) of all spinning fans together; `None` while none spins.
+pub fn noise_dba(rpms: &[u32]) -> Option<f32> {
+    let energy: f32 = rpms
+        .iter()
+        .filter(|&&rpm| rpm > 0)
+        .map(|&rpm| 10f32.powf((NOISE_REF_DBA + 50.0 * (rpm as f32 / NOISE_REF_RPM).log10()) / 10.0))
+        .sum();
+    (energy > 0.0).then(|| 10.0 * energy.log10())
+}
+
+fn round1(v: f32) -> f32 {
+    (v * 10.0).round() / 10.0
+}
+
+fn summarize(
+    label: &str,
+    samples: &[TelemetrySample],
+    sensors: &[String],
+    zones: &BTreeMap<String, Vec<String>>,
+    fan_rpm_threshold: u32,
+) -> CurveCompareResult {
+    let temps: Vec<i32> = samples.iter().filter_map(|s| temperature(s, sensors, zones)).collect();
+    let fastest: Vec<u32> = samples.iter().filter_map(|s| s.rpms.iter().copied().max()).collect();
+    let mut fan_ms_above = 0;
+    for pair in samples.windows(2) {
+        let dt = pair[1].ts_ms - pair[0].ts_ms;
+        if (0..=MAX_SAMPLE_GAP_MS).contains(&dt) && pair[1].rpms.iter().any(|&rpm| rpm >= fan_rpm_threshold) {
+            fan_ms_above += dt;
+        }
+    }
+    // Silent samples count as no sound energy rather than being skipped
+    let energy: f32 = samples
+        .iter()
+        .filter_map(|s| noise_dba(&s.rpms))
+        .map(|db| 10f32.powf(db / 10.0))
+        .sum();
+    CurveCompareResult {
+        label: label.to_string(),
+        start_ms: samples.first().map(|s| s.ts_ms).unwrap_or_default(),
+        end_ms: samples.last().map(|s| s.ts_ms).unwrap_or_default(),
+        samples: samples.len() as u32,
+        avg_temp_c: (!temps.is_empty())
+            .then(|| round1(temps.iter().map(|&t| t as f32).sum::<f32>() / temps.len() as f32)),
+        max_temp_c: temps.iter().copied().max(),
+        fan_secs_above_threshold: round1(fan_ms_above as f32 / 1000.0),
+        avg_rpm: (!fastest.is_empty()).then(|| fastest.iter().sum::<u32>() / fastest.len() as u32),
+        est_noise_dba: (energy > 0.0).then(|| round1(10.0 * (energy / samples.len() as f32).log10())),
+    }
+}
+
+fn report(a: CurveCompareResult, b: CurveCompareResult, fan_rpm_threshold: u32) -> CurveCompareReport {
+    let delta = |x: Option<f32>, y: Option<f32>| Some(round1(y? - x?));
+    let delta_avg_temp_c = delta(a.avg_temp_c, b.avg_temp_c);
+    let delta_noise_dba = delta(a.est_noise_dba, b.est_noise_dba);
+    let mut parts = Vec::new();
+    match delta_avg_temp_c {
+        Some(d) if d.abs() < 0.05 => parts.push("same average temperature".to_string()),
+        Some(d) => parts.push(format!(
+            "{:.1} °C {} on average",
+            d.abs(),
+            if d < 0.0 { "cooler" } else { "warmer" }
+        )),
+        None => {}
+    }
+    match delta_noise_dba {
+        Some(d) if d.abs() < 0.05 => parts.push("about as loud".to_string()),
+        Some(d) => parts.push(format!(
+            "about {:.1} dB(A) {}",
+            d.abs(),
+            if d < 0.0 { "quieter" } else { "louder" }
+        )),
+        None => {}
+    }
+    let summary = if parts.is_empty() {
+        "not enough data to compare".to_string()
+    } else {
+        format!("{} vs {}: {}", b.label, a.label, parts.join(", "))
+    };
+    CurveCompareReport {
+        a,
+        b,
+        fan_rpm_threshold,
+        delta_avg_temp_c,
+        delta_noise_dba,
+        summary,
+    }
+}
+
+/// Compare two windows of the telemetry history.
+pub fn compare_windows(
+    history: &[TelemetrySample],
+    a: &TimeWindow,
+    b: &TimeWindow,
+    sensors: &[String],
+    zones: &BTreeMap<String, Vec<String>>,
+    fan_rpm_threshold: u32,
+) -> Result<CurveCompareReport, String> {
+    let pick = |label: &str, w: &TimeWindow| {
+        let samples: Vec<TelemetrySample> = history
+            .iter()
+            .filter(|s| (w.start_ms..=w.end_ms).contains(&s.ts_ms))
+            .cloned()
+            .collect();
+        if samples.len() < 2 {
+            return Err(format!(
+                "{} has {} telemetry sample(s); history only reaches back `telemetry.retain_seconds`",
+                label,
+                samples.len()
+            ));
+        }
+        Ok(summarize(label, &samples, sensors, zones, fan_rpm_threshold))
+    };
+    Ok(report(pick("window A", a)?, pick("window B", b)?, fan_rpm_threshold))
+}
+
+/// Make `name` the running fan profile as a temporary override of the `fan` section.
+async fn use_profile(cfg: &Arc<RwLock<Config>>, name: &str, ttl_s: u64) -> Result<(), String> {
+    let _writer = crate::config::WRITE_LOCK.lock().await;
+    let running = cfg.read().await.clone();
+    if !crate::fan_profiles::exists(&running.fan, name) {
+        return Err(format!("no fan profile named '{}'", name));
+    }
+    crate::temporary::begin(&running, &["fan"], ttl_s);
+    let mut next = running.clone();
+    crate::fan_profiles::activate(&mut next.fan, name);
+    next.revision = running.revision + 1;
+    *cfg.write().await = next;
+    crate::tasks::fan_curve::request_reapply();
+    Ok(())
+}
+
+/// Expire the `fan` override now, putting the saved fan config back.
+async fn restore(cfg: &Arc<RwLock<Config>>) {
+    {
+        let _writer = crate::config::WRITE_LOCK.lock().await;
+        let running = cfg.read().await.clone();
+        crate::temporary::begin(&running, &["fan"], 0);
+    }
+    crate::temporary::revert(cfg, false).await;
+}
+
+/// Restores the saved fan config once the comparison ends, including when the request is
+/// cancelled midway and the run is dropped.
+struct RestoreGuard(Option<Arc<RwLock<Config>>>);
+
+impl RestoreGuard {
+    async fn restore(mut self) {
+        if let Some(cfg) = self.0.take() {
+            restore(&cfg).await;
+        }
+    }
+}
+
+impl Drop for RestoreGuard {
+    fn drop(&mut self) {
+        if let Some(cfg) = self.0.take() {
+            warn!("curve compare: cancelled; restoring the saved fan config");
+            tokio::spawn(async move { restore(&cfg).await });
+        }
+    }
+}
+
+async fn run_phase(
+    cli: &FrameworkTool,
+    cfg: &Arc<RwLock<Config>>,
+    name: &str,
+    req: &CurveCompareRequest,
+) -> Result<Vec<TelemetrySample>, String> {
+    let settle = req.settle_secs.unwrap_or(DEFAULT_SETTLE_SECS) as u64;
+    let duration = req.duration_secs.unwrap_or(DEFAULT_DURATION_SECS) as u64;
+    use_profile(cfg, name, settle + duration + OVERRIDE_MARGIN_SECS).await?;
+    info!("curve compare: '{}' settling for {}s", name, settle);
+    sleep(Duration::from_secs(settle)).await;
+
+    let load = super::stress::Load::start(&StressRequest {
+        duration_secs: duration as u32,
+        threads: req.threads,
+        sample_ms: None,
+    })?;
+    let deadline = Instant::now() + Duration::from_secs(duration);
+    let mut samples = Vec::new();
+    while Instant::now() < deadline {
+        let next = Instant::now() + SAMPLE_INTERVAL;
+        match crate::sensors::thermal_max_age(cli, SAMPLE_INTERVAL / 2).await {
+            Ok(t) => samples.push(TelemetrySample {
+                ts_ms: chrono::Utc::now().timestamp_millis(),
+                temps: t.temps,
+                rpms: t.fans.iter().map(|f| f.rpm).collect(),
+                duty_pct: None,
+                power_rails: None,
+            }),
+            Err(e) => warn!("curve compare: sample failed: {}", e),
+        }
+        tokio::time::sleep_until(next.min(deadline)).await;
+    }
+    load.stop().await;
+    Ok(samples)
+}
+
+/// Run both profiles back-to-back under the same load, then put the saved fan config back.
+pub async fn run(
+    cli: &FrameworkTool,
+    cfg: &Arc<RwLock<Config>>,
+    req: &CurveCompareRequest,
+    fan_rpm_threshold: u32,
+) -> Result<CurveCompareReport, String> {
+    let (Some(a), Some(b)) = (req.profile_a.as_deref(), req.profile_b.as_deref()) else {
+        return Err("profile_a and profile_b are required for a live comparison".into());
+    };
+    let zones = cfg.read().await.sensors.zones.clone();
+    let guard = RestoreGuard(Some(cfg.clone()));
+    let result = async {
+        let first = run_phase(cli, cfg, a, req).await?;
+        let second = run_phase(cli, cfg, b, req).await?;
+        Ok::<_, String>((first, second))
+    }
+    .await;
+    guard.restore().await;
+    let (first, second) = result?;
+    let report = report(
+        summarize(a, &first, &req.sensors, &zones, fan_rpm_threshold),
+        summarize(b, &second, &req.sensors, &zones, fan_rpm_threshold),
+        fan_rpm_threshold,
+    );
+    info!("curve compare: {}", report.summary);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ts_ms: i64, apu: i32, rpm: u32) -> TelemetrySample {
+        TelemetrySample {
+            ts_ms,
+            temps: BTreeMap::from([("APU".to_string(), apu), ("Battery".to_string(), 30)]),
+            rpms: vec![rpm, 0],
+            duty_pct: None,
+            power_rails: None,
+        }
+    }
+
+    #[test]
+    fn quieter_curve_runs_warmer() {
+        let history = vec![
+            sample(0, 60, 4000),
+            sample(1000, 64, 4200),
+            sample(2000, 62, 4000),
+            sample(10_000, 70, 2000),
+            sample(11_000, 74, 2400),
+            sample(12_000, 72, 0),
+        ];
+        let w = |start_ms, end_ms| TimeWindow { start_ms, end_ms };
+        let zones = BTreeMap::new();
+        let r = compare_windows(&history, &w(0, 2000), &w(10_000, 12_000), &["APU".into()], &zones, 3000).unwrap();
+        assert_eq!(r.a.avg_temp_c, Some(62.0));
+        assert_eq!(r.b.max_temp_c, Some(74));
+        assert_eq!(r.a.fan_secs_above_threshold, 2.0);
+        assert_eq!(r.b.fan_secs_above_threshold, 0.0);
+        assert_eq!(r.delta_avg_temp_c, Some(10.0));
+        assert!(r.delta_noise_dba.is_some_and(|d| d < -10.0));
+        assert!(r
+            .summary
+            .starts_with("window B vs window A: 10.0 °C warmer on average, about"));
+        assert!(compare_windows(&history, &w(3000, 4000), &w(0, 2000), &[], &zones, 3000).is_err());
+        assert_eq!(noise_dba(&[3000, 0]), Some(35.0));
+    }
+}
@@ -0,0 +1,147 @@
+// Record-and-fit: propose fan curve points from the telemetry history instead of hand-dragging.
+//
+// For every sample taken under service fan control, the temperature RESPONSE_WINDOW later tells
+// whether that duty held the temperature (no rise). Per TEMP_BUCKET_C bucket the lowest holding
+// duty is what the curve needs there; buckets where nothing held get the highest duty seen plus
+// a margin. The result is made monotonic, capped at the noise limit up to the target
+// temperature, and ramps to 100% just above it.
+
+use std::collections::BTreeMap;
+
+use crate::types::{CurveFitRequest, CurveFitResult, TelemetrySample};
+
+const RESPONSE_WINDOW_MS: i64 = 30_000;
+const TEMP_BUCKET_C: i32 = 5;
+const MIN_SPAN_MS: i64 = 2 * 60_000;
+const UNHELD_MARGIN_PCT: u32 = 10;
+
+pub fn fit(samples: &[TelemetrySample], req: &CurveFitRequest) -> Result<CurveFitResult, String> {
+    let series: Vec<(i64, i32, u32)> = samples
+        .iter()
+        .filter_map(|s| Some((s.ts_ms, hottest(&s.temps, &req.sensors)?, s.duty_pct?)))
+        .collect();
+    let span_ms = match (series.first(), series.last()) {
+        (Some(first), Some(last)) => last.0 - first.0,
+        _ => 0,
+    };
+    if span_ms < MIN_SPAN_MS {
+        return Err(format!(
+            "need at least {} minutes of telemetry recorded under manual or curve fan control",
+            MIN_SPAN_MS / 60_000
+        ));
+    }
+    let observed_max_temp_c = series.iter().map(|s| s.1).max().unwrap_or_default();
+
+    // Per bucket: (lowest duty that held, highest duty seen)
+    let mut buckets: BTreeMap<i32, (Option<u32>, u32)> = BTreeMap::new();
+    let mut later = 0;
+    for (i, &(ts, temp, duty)) in series.iter().enumerate() {
+        later = later.max(i);
+        while later < series.len() && series[later].0 < ts + RESPONSE_WINDOW_MS {
+            later += 1;
+        }
+        let Some(&(_, temp_after, _)) = series.get(later) else {
+            break;
+        };
+        let entry = buckets
+            .entry(temp.div_euclid(TEMP_BUCKET_C) * TEMP_BUCKET_C)
+            .or_insert((None, 0));
+        entry.1 = entry.1.max(duty);
+        if temp_after <= temp {
+            entry.0 = Some(entry.0.map_or(duty, |d| d.min(duty)));
+        }
+    }
+
+    let target = req.target_max_temp_c as i32;
+    let cap = req.max_duty_pct.min(100);
+    let mut points: Vec<[u32; 2]> = Vec::new();
+    let mut needed = 0;
+    let mut meets_target = true;
+    for (&bucket, &(held, seen)) in &buckets {
+        if bucket > target {
+            break;
+        }
+        // Running max keeps the curve monotonic
+        needed = needed.max(held.unwrap_or((seen + UNHELD_MARGIN_PCT).min(100)));
+        if needed > cap {
+            meets_target = false;
+        }
+        let temp = (bucket + TEMP_BUCKET_C / 2).clamp(0, target - 1) as u32;
+        let duty = needed.min(cap);
+        if points.last().map(|p| p[1]) != Some(duty) && points.last().is_none_or(|p| p[0] < temp) {
+            points.push([temp, duty]);
+        }
+    }
+    // Spend the whole noise budget at the target, then go to full speed just above it
+    points.push([target as u32, cap]);
+    points.push([(target + TEMP_BUCKET_C).min(110) as u32, 100]);
+
+    let mut note = if meets_target {
+        format!(
+            "Recorded telemetry held {} °C or less with at most {}% duty",
+            target, cap
+        )
+    } else {
+        format!(
+            "Recorded telemetry needed more than {}% to hold {} °C; expect higher temperatures or raise the noise cap",
+            cap, target
+        )
+    };
+    if observed_max_temp_c < target {
+        note.push_str(&format!(
+            " (temperatures only reached {} °C, so the curve near the target is extrapolated)",
+            observed_max_temp_c
+        ));
+    }
+
+    Ok(CurveFitResult {
+        points,
+        meets_target,
+        samples_used: series.len() as u32,
+        observed_max_temp_c,
+        note,
+    })
+}
+
+fn hottest(temps: &BTreeMap<String, i32>, sensors: &[String]) -> Option<i32> {
+    temps
+        .iter()
+        .filter(|(name, _)| sensors.is_empty() || sensors.iter().any(|s| s.eq_ignore_ascii_case(name)))
+        .map(|(_, v)| *v)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ts_secs: i64, temp: i32, duty: u32) -> TelemetrySample {
+        TelemetrySample {
+            ts_ms: ts_secs * 1000,
+            temps: BTreeMap::from([("APU".to_string(), temp)]),
+            rpms: vec![],
+            duty_pct: Some(duty),
+            power_rails: None,
+        }
+    }
+
+    #[test]
+    fn fits_holding_duty_under_the_noise_cap() {
+        // 30% lets the APU climb from 50 to 70 °C; 60% then holds it at 70 °C.
+        let mut samples: Vec<TelemetrySample> = (0..40).map(|i| sample(i * 5, 50 + i as i32 / 2, 30)).collect();
+        samples.extend((40..100).map(|i| sample(i * 5, 70, 60)));
+        let req = CurveFitRequest {
+            sensors: vec![],
+            target_max_temp_c: 75,
+            max_duty_pct: 70,
+        };
+        let fit = fit(&samples, &req).expect("enough data");
+        assert!(fit.meets_target);
+        assert_eq!(fit.points.first().map(|p| p[1]), Some(40));
+        assert!(fit.points.contains(&[72, 60]));
+        assert_eq!(fit.points[fit.points.len() - 2..], [[75, 70], [80, 100]]);
+        assert!(fit.points.windows(2).all(|w| w[0][0] < w[1][0] && w[0][1] <= w[1][1]));
+
+        assert!(super::fit(&samples[..10], &req).is_err());
+    }
+}
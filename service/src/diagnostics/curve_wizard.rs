@@ -0,0 +1,413 @@
+// Curve tuning wizard (`POST /fan/curve-wizard`): under the stress test's load, hold every fan at
+// a series of duties, highest first, and wait at each for the temperature to stop moving (or for
+// the step's time to run out). Each step gives the temperature the machine settles at under full
+// load with that duty. The lowest duty whose steady temperature stays HEADROOM_C under the
+// user's ceiling, interpolated between the steps around it, is the quietest one that holds the
+// load; the proposed curve rises to it from the lowest measured duty and goes to 100% just past
+// the ceiling. Descending stops at the first step that passes the ceiling, as lower duties only
+// run hotter, and a step ends early once the temperature passes the ceiling by TRIP_MARGIN_C.
+//
+// The run is a background task: the request returns at once and `GET /fan/curve-wizard` reports
+// its progress. Fan control is held for the duration and handed back afterwards, also when the
+// run fails or is cancelled. Nothing is saved until `POST /fan/curve-wizard/apply`. The last run
+// is kept in memory only.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::RwLock;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::stress::Load;
+use crate::cli::FrameworkTool;
+use crate::types::{
+    Config, CurveWizardPhase, CurveWizardProposal, CurveWizardRequest, CurveWizardStatus, CurveWizardStep,
+    StressRequest, TelemetrySample,
+};
+
+pub const DEFAULT_DUTIES: [u32; 5] = [100, 80, 60, 45, 30];
+pub const DEFAULT_STEP_SECS: u32 = 240;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Samples per half of the settle check: the step has settled once the mean of the last window
+/// is within SETTLED_DELTA_C of the window before it
+const SETTLE_WINDOW: usize = 30;
+const SETTLED_DELTA_C: f32 = 0.5;
+/// A step ends at once when the temperature passes the ceiling by this much
+const TRIP_MARGIN_C: u32 = 5;
+/// The proposal aims the full-load temperature this far under the ceiling
+const HEADROOM_C: u32 = 3;
+/// The proposed curve starts rising from the lowest measured duty this far under the ceiling
+const RAMP_SPAN_C: u32 = 20;
+/// ... and reaches 100% this far past it
+const FULL_ABOVE_C: u32 = 2;
+
+static STATUS: Mutex<CurveWizardStatus> = Mutex::new(CurveWizardStatus {
+    phase: CurveWizardPhase::Idle,
+    started_ms: None,
+    max_temp_c: None,
+    duties: Vec::new(),
+    current_duty_pct: None,
+    current_temp_c: None,
+    steps: Vec::new(),
+    proposal: None,
+    error: None,
+});
+static CANCEL: AtomicBool = AtomicBool::new(false);
+
+pub fn status() -> CurveWizardStatus {
+    STATUS
+        .lock()
+        .map(|s| s.clone())
+        .unwrap_or_else(|e| e.into_inner().clone())
+}
+
+fn update(f: impl FnOnce(&mut CurveWizardStatus)) {
+    if let Ok(mut s) = STATUS.lock() {
+        f(&mut s);
+    }
+}
+
+/// Ask the run in progress to stop after its current sample; false when none is running.
+pub fn cancel() -> bool {
+    if status().phase != CurveWizardPhase::Running {
+        return false;
+    }
+    CANCEL.store(true, Ordering::Relaxed);
+    true
+}
+
+/// Duties of a run, highest first and without repeats.
+pub fn duties(req: &CurveWizardRequest) -> Vec<u32> {
+    let mut duties = req.duties.clone().unwrap_or_else(|| DEFAULT_DUTIES.to_vec());
+    duties.sort_unstable_by(|a, b| b.cmp(a));
+    duties.dedup();
+    duties
+}
+
+/// Start a run in the background; `guard` holds the diagnostics slot until it ends.
+pub fn start(
+    cli: FrameworkTool,
+    cfg: Arc<RwLock<Config>>,
+    req: CurveWizardRequest,
+    guard: super::RunGuard,
+) -> CurveWizardStatus {
+    let duties = duties(&req);
+    CANCEL.store(false, Ordering::Relaxed);
+    update(|s| {
+        *s = CurveWizardStatus {
+            phase: CurveWizardPhase::Running,
+            started_ms: Some(chrono::Utc::now().timestamp_millis()),
+            max_temp_c: Some(req.max_temp_c),
+            duties: duties.clone(),
+            current_duty_pct: None,
+            current_temp_c: None,
+            steps: Vec::new(),
+            proposal: None,
+            error: None,
+        }
+    });
+    info!(
+        "curve wizard: started, ceiling {} °C, duties {:?}",
+        req.max_temp_c, duties
+    );
+    tokio::spawn(async move {
+        let _guard = guard;
+        let outcome = run(&cli, &cfg, &req, &duties).await;
+        update(|s| {
+            s.current_duty_pct = None;
+            s.current_temp_c = None;
+            match outcome {
+                Ok(Some(proposal)) => {
+                    info!("curve wizard: {}", proposal.note);
+                    s.phase = CurveWizardPhase::Finished;
+                    s.proposal = Some(proposal);
+                }
+                Ok(None) => {
+                    info!("curve wizard: cancelled");
+                    s.phase = CurveWizardPhase::Cancelled;
+                }
+                Err(e) => {
+                    warn!("curve wizard: {}", e);
+                    s.phase = CurveWizardPhase::Failed;
+                    s.error = Some(e);
+                }
+            }
+        });
+    });
+    status()
+}
+
+/// Measure every step, then propose; `None` when cancelled.
+async fn run(
+    cli: &FrameworkTool,
+    cfg: &Arc<RwLock<Config>>,
+    req: &CurveWizardRequest,
+    duties: &[u32],
+) -> Result<Option<CurveWizardProposal>, String> {
+    let zones = cfg.read().await.sensors.zones.clone();
+    let hold = crate::tasks::fan_curve::ExternalHold::acquire();
+    let load = Load::start(&StressRequest {
+        duration_secs: 0,
+        threads: req.threads,
+        sample_ms: None,
+    })?;
+    let mut steps = Vec::new();
+    let mut outcome = Ok(true);
+    for &duty in duties {
+        match measure(cli, duty, req, &zones).await {
+            Ok(Some(step)) => {
+                info!(
+                    "curve wizard: {}% settled at {:.1} °C after {} s{}",
+                    duty,
+                    step.steady_temp_c,
+                    step.secs,
+                    if step.tripped { " (tripped)" } else { "" }
+                );
+                let done = step.tripped || step.steady_temp_c > req.max_temp_c as f32;
+                steps.push(step.clone());
+                update(|s| s.steps.push(step));
+                if done {
+                    break;
+                }
+            }
+            Ok(None) => {
+                outcome = Ok(false);
+                break;
+            }
+            Err(e) => {
+                outcome = Err(format!("{}% step: {}", duty, e));
+                break;
+            }
+        }
+    }
+    load.stop().await;
+    if crate::safe_mode::is_active() {
+        if let Err(e) = cli.autofanctrl().await {
+            warn!("curve wizard: failed to restore EC fan control: {}", e);
+        }
+    }
+    drop(hold);
+    if !outcome? {
+        return Ok(None);
+    }
+    propose(&steps, req.max_temp_c, &req.sensors).map(Some)
+}
+
+/// Hold `duty` until the temperature settles, the step's time runs out or it trips; `None` when
+/// the run was cancelled meanwhile.
+async fn measure(
+    cli: &FrameworkTool,
+    duty: u32,
+    req: &CurveWizardRequest,
+    zones: &BTreeMap<String, Vec<String>>,
+) -> Result<Option<CurveWizardStep>, String> {
+    cli.set_fan_duty(duty, None).await?;
+    update(|s| s.current_duty_pct = Some(duty));
+    let limit = Duration::from_secs(req.step_secs.unwrap_or(DEFAULT_STEP_SECS) as u64);
+    let trip = (req.max_temp_c + TRIP_MARGIN_C) as i32;
+    let started = Instant::now();
+    let mut temps: Vec<f32> = Vec::new();
+    let mut rpms: Vec<Vec<u32>> = Vec::new();
+    loop {
+        if CANCEL.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let next = Instant::now() + SAMPLE_INTERVAL;
+        let mut tripped = false;
+        match crate::sensors::thermal_max_age(cli, SAMPLE_INTERVAL / 2).await {
+            Ok(t) => {
+                let sample = TelemetrySample {
+                    ts_ms: chrono::Utc::now().timestamp_millis(),
+                    temps: t.temps,
+                    rpms: t.fans.iter().map(|f| f.rpm).collect(),
+                    duty_pct: Some(duty),
+                    power_rails: None,
+                };
+                if let Some(temp) = super::curve_compare::temperature(&sample, &req.sensors, zones) {
+                    update(|s| s.current_temp_c = Some(temp));
+                    temps.push(temp as f32);
+                    tripped = temp >= trip;
+                }
+                rpms.push(sample.rpms);
+            }
+            Err(e) => warn!("curve wizard: sample failed: {}", e),
+        }
+        let steady = steady_temp(&temps);
+        let timed_out = started.elapsed() >= limit;
+        if tripped || steady.is_some() || timed_out {
+            let Some(&last) = temps.last() else {
+                return Err("no reading of the selected sensors".into());
+            };
+            let steady_temp_c = match steady {
+                Some(t) if !tripped => t,
+                // The last reading: it is where the temperature got to, and the settle window
+                // still holds how it started
+                _ if tripped => last,
+                _ => mean(&temps[temps.len().saturating_sub(SETTLE_WINDOW)..]),
+            };
+            let avg_rpm = (!rpms.is_empty()).then(|| {
+                (rpms
+                    .iter()
+                    .map(|r| r.iter().copied().max().unwrap_or(0) as u64)
+                    .sum::<u64>()
+                    / rpms.len() as u64) as u32
+            });
+            let est_noise_dba = {
+                let energy: Vec<f32> = rpms
+                    .iter()
+                    .filter_map(|r| super::curve_compare::noise_dba(r))
+                    .map(|db| 10f32.powf(db / 10.0))
+                    .collect();
+                (!energy.is_empty()).then(|| round1(10.0 * (energy.iter().sum::<f32>() / energy.len() as f32).log10()))
+            };
+            return Ok(Some(CurveWizardStep {
+                duty_pct: duty,
+                steady_temp_c: round1(steady_temp_c),
+                settled: steady.is_some() && !tripped,
+                tripped,
+                secs: started.elapsed().as_secs() as u32,
+                avg_rpm,
+                est_noise_dba,
+            }));
+        }
+        tokio::time::sleep_until(next).await;
+    }
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len().max(1) as f32
+}
+
+fn round1(v: f32) -> f32 {
+    (v * 10.0).round() / 10.0
+}
+
+/// Mean of the last `SETTLE_WINDOW` readings once it is within `SETTLED_DELTA_C` of the window
+/// before it.
+fn steady_temp(temps: &[f32]) -> Option<f32> {
+    if temps.len() < 2 * SETTLE_WINDOW {
+        return None;
+    }
+    let last = mean(&temps[temps.len() - SETTLE_WINDOW..]);
+    let before = mean(&temps[temps.len() - 2 * SETTLE_WINDOW..temps.len() - SETTLE_WINDOW]);
+    ((last - before).abs() <= SETTLED_DELTA_C).then_some(last)
+}
+
+/// Curve from the measured steps: the lowest duty that keeps the full load `HEADROOM_C` under
+/// `max_temp_c`, reached from the lowest measured duty over `RAMP_SPAN_C`.
+fn propose(steps: &[CurveWizardStep], max_temp_c: u32, sensors: &[String]) -> Result<CurveWizardProposal, String> {
+    let mut sorted: Vec<&CurveWizardStep> = steps.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.duty_pct));
+    let (Some(highest), Some(lowest)) = (sorted.first(), sorted.last()) else {
+        return Err("no step finished".into());
+    };
+    let target = max_temp_c.saturating_sub(HEADROOM_C);
+    let held = |s: &CurveWizardStep| s.steady_temp_c <= target as f32;
+
+    let (hold, meets_target, note) = match sorted.iter().position(|s| !held(s)) {
+        Some(0) => (
+            100,
+            false,
+            format!(
+                "even {}% let the sensors reach {:.1} °C; the curve runs the fans at 100% from {} °C",
+                highest.duty_pct, highest.steady_temp_c, target
+            ),
+        ),
+        Some(i) => {
+            // Between the last step that held and the first that did not, temperature falls
+            // roughly linearly with duty
+            let (above, below) = (sorted[i - 1], sorted[i]);
+            let frac = (below.steady_temp_c - target as f32) / (below.steady_temp_c - above.steady_temp_c);
+            let duty = below.duty_pct as f32 + (above.duty_pct - below.duty_pct) as f32 * frac;
+            let hold = (duty.ceil() as u32).clamp(below.duty_pct, above.duty_pct);
+            (
+                hold,
+                true,
+                format!("{}% should hold the load at about {} °C", hold, target),
+            )
+        }
+        None => (
+            lowest.duty_pct,
+            true,
+            format!(
+                "the lowest measured duty, {}%, held the load at {:.1} °C; measuring lower duties may \
+                 give a quieter curve",
+                lowest.duty_pct, lowest.steady_temp_c
+            ),
+        ),
+    };
+
+    let floor = lowest.duty_pct.min(hold);
+    let mut points = vec![[target.saturating_sub(RAMP_SPAN_C - HEADROOM_C), floor], [target, hold]];
+    if hold < 100 {
+        points.push([max_temp_c + FULL_ABOVE_C, 100]);
+    }
+    Ok(CurveWizardProposal {
+        points,
+        sensors: sensors.to_vec(),
+        hold_duty_pct: hold,
+        meets_target,
+        est_noise_dba: noise_at(&sorted, hold),
+        note,
+    })
+}
+
+/// Noise estimate at `duty`, linear between the measured steps around it (`steps` highest duty
+/// first).
+fn noise_at(steps: &[&CurveWizardStep], duty: u32) -> Option<f32> {
+    let above = steps.iter().rev().find(|s| s.duty_pct >= duty)?;
+    let Some(below) = steps.iter().find(|s| s.duty_pct <= duty) else {
+        return above.est_noise_dba.filter(|_| above.duty_pct == duty);
+    };
+    let (hi, lo) = (above.est_noise_dba?, below.est_noise_dba?);
+    if above.duty_pct == below.duty_pct {
+        return Some(hi);
+    }
+    let frac = (duty - below.duty_pct) as f32 / (above.duty_pct - below.duty_pct) as f32;
+    Some(round1(lo + (hi - lo) * frac))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(duty_pct: u32, steady_temp_c: f32, est_noise_dba: f32) -> CurveWizardStep {
+        CurveWizardStep {
+            duty_pct,
+            steady_temp_c,
+            settled: true,
+            tripped: false,
+            secs: 120,
+            avg_rpm: None,
+            est_noise_dba: Some(est_noise_dba),
+        }
+    }
+
+    #[test]
+    fn proposes_the_quietest_duty_under_the_ceiling() {
+        // Still warming up, then flat
+        let mut temps: Vec<f32> = (0..30).map(|i| 60.0 + i as f32 * 0.5).collect();
+        assert_eq!(steady_temp(&temps), None);
+        temps.extend(std::iter::repeat_n(75.0, 60));
+        assert_eq!(steady_temp(&temps), Some(75.0));
+
+        let steps = vec![step(100, 70.0, 40.0), step(80, 74.0, 36.0), step(60, 81.0, 30.0)];
+        let p = propose(&steps, 80, &["APU".into()]).unwrap();
+        // 77 °C sits 4/7 of the way from 60% (81 °C) to 80% (74 °C)
+        assert_eq!(p.hold_duty_pct, 72);
+        assert!(p.meets_target);
+        assert_eq!(p.points, vec![[60, 60], [77, 72], [82, 100]]);
+        assert_eq!(p.est_noise_dba, Some(33.6));
+
+        let hot = propose(&steps[..1], 65, &[]).unwrap();
+        assert!(!hot.meets_target);
+        assert_eq!(hot.points, vec![[45, 100], [62, 100]]);
+
+        let cool = propose(&steps, 90, &[]).unwrap();
+        assert_eq!(cool.hold_duty_pct, 60);
+        assert_eq!(cool.est_noise_dba, Some(30.0));
+        assert!(propose(&[], 80, &[]).is_err());
+    }
+}
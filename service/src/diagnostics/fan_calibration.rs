@@ -0,0 +1,159 @@
+// Fan calibration (`POST /fan/calibrate`): per fan header, step the duty down from 100% and record
+// the RPM the fan settles at, so each fan gets its own duty→RPM table. Fans differ (the Laptop
+// 16's two fans, a Desktop's case fans), and a single table measured on the fastest one gave
+// every other fan the wrong top speed. Tables go to `fan.calibrations`; `fan.calibration` keeps
+// the fastest fan's table as the combined fallback the UI wizard used to write.
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::types::{Config, FanCalibrateReport, FanCalibration};
+
+/// Duties measured per fan, high to low so each step only has to spin down
+const DUTIES: [u32; 5] = [100, 80, 60, 40, 20];
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+/// Readings the stability check looks at
+const STABLE_WINDOW: usize = 5;
+/// A window whose readings spread less than this (standard deviation) counts as settled
+const STABLE_STDEV_RPM: f64 = 30.0;
+/// Give up waiting for a steady reading after this and take the median so far
+const STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Median of the last `STABLE_WINDOW` readings once they have settled.
+fn settled_rpm(readings: &[u32]) -> Option<u32> {
+    if readings.len() < STABLE_WINDOW {
+        return None;
+    }
+    let window = &readings[readings.len() - STABLE_WINDOW..];
+    let mean = window.iter().map(|&r| r as f64).sum::<f64>() / window.len() as f64;
+    let variance = window.iter().map(|&r| (r as f64 - mean).powi(2)).sum::<f64>() / window.len() as f64;
+    (variance.sqrt() <= STABLE_STDEV_RPM).then(|| median(window))
+}
+
+fn median(readings: &[u32]) -> u32 {
+    let mut sorted = readings.to_vec();
+    sorted.sort_unstable();
+    sorted.get(sorted.len() / 2).copied().unwrap_or_default()
+}
+
+async fn read_rpm(cli: &FrameworkTool, fan_index: u32) -> Result<u32, String> {
+    let t = cli.thermal_max_age(SAMPLE_INTERVAL / 2).await?;
+    t.fans
+        .get(fan_index as usize)
+        .map(|f| f.rpm)
+        .ok_or_else(|| format!("fan {} missing from thermal report", fan_index))
+}
+
+async fn calibrate_fan(cli: &FrameworkTool, fan_index: u32) -> Result<FanCalibration, String> {
+    let mut points = vec![[0, 0]];
+    for duty in DUTIES {
+        cli.set_fan_duty(duty, Some(fan_index)).await?;
+        let started = Instant::now();
+        let mut readings = Vec::new();
+        let rpm = loop {
+            sleep(SAMPLE_INTERVAL).await;
+            readings.push(read_rpm(cli, fan_index).await?);
+            if let Some(rpm) = settled_rpm(&readings) {
+                break rpm;
+            }
+            if started.elapsed() >= STEP_TIMEOUT {
+                break median(&readings[readings.len().saturating_sub(STABLE_WINDOW)..]);
+            }
+        };
+        points.push([duty, rpm]);
+    }
+    points.sort_by_key(|p| p[0]);
+    info!("fan calibration: fan {}: {:?}", fan_index, points);
+    Ok(FanCalibration {
+        points,
+        updated_at: chrono::Utc::now().timestamp(),
+        fan_index: Some(fan_index),
+    })
+}
+
+/// Save `measured` into `fan.calibrations` (keeping other fans' tables) and refresh the combined
+/// table from the fastest fan.
+async fn save(cfg: &Arc<RwLock<Config>>, measured: &[FanCalibration]) -> Result<(), String> {
+    let _writer = crate::config::WRITE_LOCK.lock().await;
+    let running = cfg.read().await.clone();
+    let mut merged = crate::temporary::persisted(&running);
+    let mut tables: Vec<FanCalibration> = merged
+        .fan
+        .calibrations
+        .take()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|c| !measured.iter().any(|m| m.fan_index == c.fan_index))
+        .chain(measured.iter().cloned())
+        .collect();
+    tables.sort_by_key(|c| c.fan_index);
+    let top_rpm = |c: &FanCalibration| c.points.iter().map(|p| p[1]).max().unwrap_or_default();
+    merged.fan.calibration = tables.iter().max_by_key(|c| top_rpm(c)).map(|c| FanCalibration {
+        fan_index: None,
+        ..c.clone()
+    });
+    merged.fan.calibrations = Some(tables);
+    merged.revision = running.revision + 1;
+    crate::config::save(&merged)?;
+    crate::config_check::clear_section("fan");
+    *cfg.write().await = crate::temporary::overlay(merged, &running, &["fan"]);
+    Ok(())
+}
+
+/// Calibrate the requested fan (every fan when `None`). Fan control is held for the duration
+/// and handed back afterwards, also when a step fails.
+pub async fn run(
+    cli: &FrameworkTool,
+    cfg: &Arc<RwLock<Config>>,
+    fan_index: Option<u32>,
+) -> Result<FanCalibrateReport, String> {
+    let count = cli.thermal().await?.fans.len() as u32;
+    if count == 0 {
+        return Err("framework_tool --thermal reported no fans".into());
+    }
+    let fans: Vec<u32> = match fan_index {
+        Some(i) if i >= count => return Err(format!("fan {} not found ({} fans reported)", i, count)),
+        Some(i) => vec![i],
+        None => (0..count).collect(),
+    };
+
+    let hold = crate::tasks::fan_curve::ExternalHold::acquire();
+    let mut measured = Vec::new();
+    let mut outcome = Ok(());
+    for &i in &fans {
+        match calibrate_fan(cli, i).await {
+            Ok(c) => measured.push(c),
+            Err(e) => {
+                outcome = Err(format!("fan {}: {}", i, e));
+                break;
+            }
+        }
+    }
+    if crate::safe_mode::is_active() {
+        if let Err(e) = cli.autofanctrl().await {
+            warn!("fan calibration: failed to restore EC fan control: {}", e);
+        }
+    }
+    drop(hold);
+    outcome?;
+
+    save(cfg, &measured).await?;
+    Ok(FanCalibrateReport { calibrations: measured })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_a_steady_window() {
+        assert_eq!(settled_rpm(&[4000, 4100, 4200, 4150]), None);
+        // Still spinning down
+        assert_eq!(settled_rpm(&[5000, 4600, 4300, 4150, 4100]), None);
+        assert_eq!(settled_rpm(&[5000, 4120, 4100, 4080, 4110, 4090]), Some(4100));
+    }
+}
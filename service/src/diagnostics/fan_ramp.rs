@@ -0,0 +1,280 @@
+// Fan ramp test: per fan, settle at a low duty, command a step to a high duty and sample RPM
+// densely to measure spin-up latency (90% of the way to the target RPM) and overshoot. The
+// result is kept in `fan_ramp.json` next to the config; curves with `auto_rate_limit` turn each
+// fan's measured slew rate into their per-poll step size.
+
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::types::{FanControlConfig, FanRampReport, FanRampRequest, FanRampResult};
+
+pub const DEFAULT_FROM_DUTY_PCT: u32 = 20;
+pub const DEFAULT_TO_DUTY_PCT: u32 = 80;
+/// Time at the low duty before the step.
+const SETTLE: Duration = Duration::from_secs(8);
+/// How long RPM is sampled after the step.
+const RAMP_WINDOW: Duration = Duration::from_secs(12);
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// The tail of the window averaged into the steady-state RPM.
+const STEADY_WINDOW_MS: u64 = 2_000;
+/// Overshoot tolerated before the suggested slew rate is scaled down.
+const OVERSHOOT_TOLERANCE_PCT: f32 = 10.0;
+
+fn report_path() -> PathBuf {
+    crate::config::config_path().with_file_name("fan_ramp.json")
+}
+
+fn last() -> &'static RwLock<Option<FanRampReport>> {
+    static LAST: OnceLock<RwLock<Option<FanRampReport>>> = OnceLock::new();
+    LAST.get_or_init(|| {
+        let loaded = std::fs::read_to_string(report_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok());
+        RwLock::new(loaded)
+    })
+}
+
+/// Per-poll spin-up step derived from the last ramp test: the slowest tested fan when
+/// `fan_index` is `None` (all fans driven together), `None` when the fan was never tested.
+pub fn tuned_step(fan_index: Option<u32>, poll: Duration) -> Option<u32> {
+    let guard = last().read().ok()?;
+    let report = guard.as_ref()?;
+    let pct_per_sec = report
+        .fans
+        .iter()
+        .filter(|f| fan_index.is_none_or(|i| f.fan_index == i))
+        .map(|f| f.suggested_pct_per_sec)
+        .min()?;
+    Some(((pct_per_sec as u64 * poll.as_millis() as u64 / 1000) as u32).max(1))
+}
+
+/// Step each requested fan and measure its response. Fan control is held for the duration and
+/// handed back afterwards (to the fan task, or the EC in safe mode), also when a step fails.
+pub async fn run(
+    cli: &FrameworkTool,
+    fan_config: &FanControlConfig,
+    req: &FanRampRequest,
+) -> Result<FanRampReport, String> {
+    let from = req.from_duty_pct.unwrap_or(DEFAULT_FROM_DUTY_PCT);
+    let to = req.to_duty_pct.unwrap_or(DEFAULT_TO_DUTY_PCT);
+    let count = cli.thermal().await?.fans.len() as u32;
+    if count == 0 {
+        return Err("framework_tool --thermal reported no fans".into());
+    }
+    let fans: Vec<u32> = match req.fan_index {
+        Some(i) if i >= count => return Err(format!("fan {} not found ({} fans reported)", i, count)),
+        Some(i) => vec![i],
+        None => (0..count).collect(),
+    };
+
+    let hold = crate::tasks::fan_curve::ExternalHold::acquire();
+    let mut results = Vec::new();
+    let mut outcome = Ok(());
+    for &i in &fans {
+        let expected_rpm = fan_config.calibration_for(i).and_then(|c| rpm_at(&c.points, to));
+        match step_fan(cli, i, from, to, expected_rpm).await {
+            Ok(r) => {
+                info!(
+                    "fan ramp: fan {} {}% -> {}%: {} -> {} RPM, latency {:?} ms, overshoot {:.1}%",
+                    i, from, to, r.start_rpm, r.steady_rpm, r.latency_ms, r.overshoot_pct
+                );
+                results.push(r);
+            }
+            Err(e) => {
+                outcome = Err(format!("fan {}: {}", i, e));
+                break;
+            }
+        }
+    }
+    if crate::safe_mode::is_active() {
+        if let Err(e) = cli.autofanctrl().await {
+            warn!("fan ramp: failed to restore EC fan control: {}", e);
+        }
+    }
+    drop(hold);
+    outcome?;
+
+    let report = FanRampReport {
+        fans: results,
+        measured_at_ms: unix_time_ms(),
+    };
+    // A single-fan run keeps the other fans' earlier results
+    let mut merged = report.clone();
+    if let Ok(mut w) = last().write() {
+        if let Some(prev) = w.as_ref() {
+            merged
+                .fans
+                .extend(prev.fans.iter().filter(|p| !fans.contains(&p.fan_index)).cloned());
+            merged.fans.sort_by_key(|f| f.fan_index);
+        }
+        *w = Some(merged.clone());
+    }
+    match serde_json::to_string(&merged) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(report_path(), s) {
+                warn!("fan ramp: failed to write results: {}", e);
+            }
+        }
+        Err(e) => warn!("fan ramp: failed to serialize results: {}", e),
+    }
+    Ok(report)
+}
+
+async fn step_fan(
+    cli: &FrameworkTool,
+    fan_index: u32,
+    from: u32,
+    to: u32,
+    expected_rpm: Option<u32>,
+) -> Result<FanRampResult, String> {
+    cli.set_fan_duty(from, Some(fan_index)).await?;
+    sleep(SETTLE).await;
+    let start_rpm = read_rpm(cli, fan_index).await?;
+
+    cli.set_fan_duty(to, Some(fan_index)).await?;
+    let started = Instant::now();
+    let mut samples = Vec::new();
+    while started.elapsed() < RAMP_WINDOW {
+        sleep(SAMPLE_INTERVAL).await;
+        if let Ok(rpm) = read_rpm(cli, fan_index).await {
+            samples.push((started.elapsed().as_millis() as u64, rpm));
+        }
+    }
+    Ok(analyze(fan_index, from, to, start_rpm, &samples, expected_rpm))
+}
+
+async fn read_rpm(cli: &FrameworkTool, fan_index: u32) -> Result<u32, String> {
+    let t = cli.thermal_max_age(SAMPLE_INTERVAL / 2).await?;
+    t.fans
+        .get(fan_index as usize)
+        .map(|f| f.rpm)
+        .ok_or_else(|| format!("fan {} missing from thermal report", fan_index))
+}
+
+/// Reduce `(ms since step, rpm)` samples to latency/overshoot and a sustainable slew rate.
+fn analyze(
+    fan_index: u32,
+    from: u32,
+    to: u32,
+    start_rpm: u32,
+    samples: &[(u64, u32)],
+    expected_rpm: Option<u32>,
+) -> FanRampResult {
+    let end_ms = samples.last().map(|s| s.0).unwrap_or_default();
+    let tail: Vec<u32> = samples
+        .iter()
+        .filter(|s| s.0 + STEADY_WINDOW_MS >= end_ms)
+        .map(|s| s.1)
+        .collect();
+    let steady_rpm = if tail.is_empty() {
+        start_rpm
+    } else {
+        (tail.iter().map(|&r| r as u64).sum::<u64>() / tail.len() as u64) as u32
+    };
+    let peak_rpm = samples.iter().map(|s| s.1).max().unwrap_or(start_rpm);
+
+    let target = expected_rpm.unwrap_or(steady_rpm);
+    let latency_ms = if target > start_rpm {
+        let threshold = start_rpm + (target - start_rpm) * 9 / 10;
+        samples.iter().find(|s| s.1 >= threshold).map(|s| s.0 as u32)
+    } else {
+        None
+    };
+    let overshoot_pct = if steady_rpm > 0 && peak_rpm > steady_rpm {
+        (peak_rpm - steady_rpm) as f32 * 100.0 / steady_rpm as f32
+    } else {
+        0.0
+    };
+
+    // The duty step spread over the time the fan needed to follow it; a fan that never got
+    // there is treated as taking the whole window.
+    let delta = to.saturating_sub(from).max(1) as f32;
+    let follow_ms = latency_ms
+        .map(|l| l.max(1) as f32)
+        .unwrap_or(RAMP_WINDOW.as_millis() as f32);
+    let mut pct_per_sec = delta * 1000.0 / follow_ms;
+    if overshoot_pct > OVERSHOOT_TOLERANCE_PCT {
+        pct_per_sec *= OVERSHOOT_TOLERANCE_PCT / overshoot_pct;
+    }
+
+    FanRampResult {
+        fan_index,
+        from_duty_pct: from,
+        to_duty_pct: to,
+        start_rpm,
+        steady_rpm,
+        expected_rpm,
+        latency_ms,
+        peak_rpm,
+        overshoot_pct: (overshoot_pct * 10.0).round() / 10.0,
+        suggested_pct_per_sec: (pct_per_sec.round() as u32).clamp(1, 100),
+    }
+}
+
+/// Linear interpolation over calibration `[duty_pct, rpm]` points.
+pub fn rpm_at(points: &[[u32; 2]], duty: u32) -> Option<u32> {
+    let mut pts = points.to_vec();
+    pts.sort_by_key(|p| p[0]);
+    let first = pts.first()?;
+    if duty <= first[0] {
+        return Some(first[1]);
+    }
+    for w in pts.windows(2) {
+        let ([d1, r1], [d2, r2]) = (w[0], w[1]);
+        if duty <= d2 {
+            if d2 == d1 {
+                return Some(r2);
+            }
+            let ratio = (duty - d1) as f32 / (d2 - d1) as f32;
+            return Some((r1 as f32 + ratio * (r2 as f32 - r1 as f32)).round() as u32);
+        }
+    }
+    pts.last().map(|p| p[1])
+}
+
+fn unix_time_ms() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_latency_overshoot_and_slew() {
+        // 1000 -> ~4000 RPM: 90% mark (3700) at 2s, peaks at 4400, settles at 4000
+        let samples: Vec<(u64, u32)> = vec![
+            (500, 1500),
+            (1000, 2400),
+            (1500, 3300),
+            (2000, 3900),
+            (2500, 4400),
+            (3000, 4200),
+            (10_000, 4000),
+            (11_000, 4000),
+            (12_000, 4000),
+        ];
+        let r = analyze(0, 20, 80, 1000, &samples, None);
+        assert_eq!(r.steady_rpm, 4000);
+        assert_eq!(r.peak_rpm, 4400);
+        assert_eq!(r.latency_ms, Some(2000));
+        assert_eq!(r.overshoot_pct, 10.0);
+        // 60 points over 2s
+        assert_eq!(r.suggested_pct_per_sec, 30);
+
+        // Calibration expecting 5000 RPM: the fan never gets there
+        let r = analyze(0, 20, 80, 1000, &samples, Some(5000));
+        assert_eq!(r.latency_ms, None);
+        assert_eq!(r.suggested_pct_per_sec, 5);
+
+        assert_eq!(rpm_at(&[[100, 5000], [0, 0], [50, 3000]], 75), Some(4000));
+    }
+}
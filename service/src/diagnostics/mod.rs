@@ -0,0 +1,32 @@
+pub mod capture;
+pub mod curve_compare;
+pub mod curve_fit;
+pub mod curve_wizard;
+pub mod fan_calibration;
+pub mod fan_ramp;
+pub mod selftest;
+pub mod stress;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Only one diagnostics run may drive the hardware at a time.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Held for the duration of a diagnostics run; releases the slot on drop.
+pub struct RunGuard;
+
+impl RunGuard {
+    /// Claim the diagnostics slot, or `None` when another run is in progress.
+    pub fn try_acquire() -> Option<Self> {
+        RUNNING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| RunGuard)
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
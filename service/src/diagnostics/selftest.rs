@@ -0,0 +1,223 @@
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::hints;
+use crate::state::AppState;
+use crate::types::{RemediationHint, SelftestCheck, SelftestReport, SelftestStatus, UiConfig};
+
+/// Duty written by the fan write test; restored right after.
+const FAN_TEST_DUTY_PCT: u32 = 50;
+/// How long the test duty is held before restoring.
+const FAN_TEST_HOLD: Duration = Duration::from_millis(500);
+
+fn pass(name: &str, detail: impl Into<String>) -> SelftestCheck {
+    SelftestCheck {
+        name: name.to_string(),
+        status: SelftestStatus::Pass,
+        detail: detail.into(),
+        hint: None,
+    }
+}
+
+fn fail(name: &str, detail: impl Into<String>, hint: RemediationHint) -> SelftestCheck {
+    SelftestCheck {
+        name: name.to_string(),
+        status: SelftestStatus::Fail,
+        detail: detail.into(),
+        hint: Some(hint),
+    }
+}
+
+fn skip(name: &str, detail: impl Into<String>) -> SelftestCheck {
+    SelftestCheck {
+        name: name.to_string(),
+        status: SelftestStatus::Skip,
+        detail: detail.into(),
+        hint: None,
+    }
+}
+
+/// Exercise every control path without leaving changes behind: reads go through as-is, writes
+/// either re-write the current value or are restored immediately.
+pub async fn run(state: &AppState) -> SelftestReport {
+    let mut checks = Vec::new();
+    let ui = state.config.read().await.ui.clone();
+
+    let cli_opt = { state.framework_tool.read().await.clone() };
+    match cli_opt {
+        Some(cli) => {
+            checks.push(pass("framework_tool", format!("resolved at {}", cli.path)));
+            checks.push(ec_read(&cli, &ui).await);
+            checks.push(ec_battery(&cli).await);
+            checks.push(fan_write(state, &cli).await);
+        }
+        None => {
+            checks.push(fail(
+                "framework_tool",
+                "framework_tool not found",
+                hints::install_framework_tool(),
+            ));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    checks.push(ryzenadj_info(state, &ui).await);
+
+    #[cfg(target_os = "linux")]
+    {
+        checks.extend(cpufreq_write(state).await);
+        checks.push(rapl_write().await);
+    }
+
+    let passed = checks.iter().all(|c| c.status != SelftestStatus::Fail);
+    for c in checks.iter().filter(|c| c.status == SelftestStatus::Fail) {
+        warn!("selftest: {} failed: {}", c.name, c.detail);
+    }
+    info!("selftest: {} checks, passed={}", checks.len(), passed);
+    SelftestReport { passed, checks }
+}
+
+async fn ec_read(cli: &FrameworkTool, ui: &UiConfig) -> SelftestCheck {
+    match cli.thermal().await {
+        Ok(t) if t.temps.is_empty() && t.fans.is_empty() => fail(
+            "ec_read",
+            "framework_tool --thermal returned no sensors",
+            hints::update_ec_firmware(),
+        ),
+        Ok(t) => {
+            let hottest = t
+                .temps
+                .iter()
+                .max_by_key(|(_, v)| **v)
+                .map(|(k, v)| format!(" (hottest {} at {})", k, crate::units::format_temp(*v as f32, ui)))
+                .unwrap_or_default();
+            pass(
+                "ec_read",
+                format!(
+                    "{} temperature sensors{}, {} fans",
+                    t.temps.len(),
+                    hottest,
+                    t.fans.len()
+                ),
+            )
+        }
+        Err(e) => fail("ec_read", e, hints::privilege()),
+    }
+}
+
+async fn ec_battery(cli: &FrameworkTool) -> SelftestCheck {
+    match cli.power().await {
+        Ok(p) if p.battery_present == Some(true) => pass(
+            "ec_battery",
+            format!(
+                "battery at {}%",
+                p.percentage.map(|v| v.to_string()).unwrap_or("?".into())
+            ),
+        ),
+        Ok(_) => skip("ec_battery", "no battery reported (Desktop or battery disconnected)"),
+        Err(e) => fail("ec_battery", e, hints::privilege()),
+    }
+}
+
+/// Write a test duty, then hand control back: to the fan task (which re-applies the configured
+/// mode) or, when it is not running in safe mode, to the EC.
+async fn fan_write(state: &AppState, cli: &FrameworkTool) -> SelftestCheck {
+    let written = cli.set_fan_duty(FAN_TEST_DUTY_PCT, None).await;
+    if written.is_ok() {
+        sleep(FAN_TEST_HOLD).await;
+    }
+    let restored = if crate::safe_mode::is_active() {
+        cli.autofanctrl().await
+    } else {
+        crate::tasks::fan_curve::request_reapply();
+        Ok(())
+    };
+    let mode = state.config.read().await.fan.mode.clone().unwrap_or_default();
+    match (written, restored) {
+        (Ok(()), Ok(())) => pass(
+            "fan_write",
+            format!("wrote {}% duty and restored {:?} mode", FAN_TEST_DUTY_PCT, mode),
+        ),
+        (Err(e), _) => fail("fan_write", e, hints::privilege()),
+        (Ok(()), Err(e)) => fail(
+            "fan_write",
+            format!("duty write succeeded but restoring EC auto control failed: {}", e),
+            hints::restore_fan_control(),
+        ),
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn ryzenadj_info(state: &AppState, ui: &UiConfig) -> SelftestCheck {
+    let ryz = { state.power.ryzenadj.read().await.clone() };
+    let Some(ryz) = ryz else {
+        return skip(
+            "ryzenadj",
+            "RyzenAdj not installed; TDP and thermal limit control unavailable",
+        );
+    };
+    match ryz.info().await {
+        Ok(info) if info.tdp_watts.is_some() => pass(
+            "ryzenadj",
+            format!(
+                "TDP {} W, thermal limit {}",
+                info.tdp_watts.unwrap_or_default(),
+                info.thermal_limit_c
+                    .map(|v| crate::units::format_temp(v as f32, ui))
+                    .unwrap_or("?".into())
+            ),
+        ),
+        Ok(_) => fail(
+            "ryzenadj",
+            "ryzenadj --info returned no power table",
+            hints::update_ryzenadj(),
+        ),
+        Err(e) => fail("ryzenadj", e, hints::ryzenadj_driver_blocked()),
+    }
+}
+
+/// Re-write the current governor and EPP preference: proves sysfs write access without
+/// changing anything.
+#[cfg(target_os = "linux")]
+async fn cpufreq_write(state: &AppState) -> Vec<SelftestCheck> {
+    let lp = { state.power.linux.read().await.clone() };
+    let Some(lp) = lp else {
+        return vec![skip("cpufreq_write", "no Linux power interfaces detected")];
+    };
+    let current = lp.get_state().await.unwrap_or_default();
+    let mut out = Vec::new();
+    out.push(match current.governor {
+        Some(g) => match lp.set_governor(&g).await {
+            Ok(()) => pass("cpufreq_write", format!("re-applied governor '{}'", g)),
+            Err(e) => fail("cpufreq_write", e, hints::privilege()),
+        },
+        None => skip("cpufreq_write", "cpufreq governor not available"),
+    });
+    out.push(match current.epp_preference {
+        Some(p) => match lp.set_epp_preference(&p).await {
+            Ok(()) => pass("epp_write", format!("re-applied EPP '{}'", p)),
+            Err(e) => fail("epp_write", e, hints::epp_read_only()),
+        },
+        None => skip("epp_write", "AMD P-State EPP not available"),
+    });
+    out
+}
+
+/// Re-write the current long-term RAPL power limit.
+#[cfg(target_os = "linux")]
+async fn rapl_write() -> SelftestCheck {
+    let path = std::path::Path::new(crate::cli::linux_power::RAPL_ZONE).join("constraint_0_power_limit_uw");
+    let current = match tokio::fs::read_to_string(&path).await {
+        Ok(v) => v.trim().to_string(),
+        Err(_) => return skip("rapl_write", "no powercap RAPL zone exposed"),
+    };
+    match tokio::fs::write(&path, &current).await {
+        Ok(()) => pass("rapl_write", format!("re-applied PL1 {} uW", current)),
+        Err(e) => fail(
+            "rapl_write",
+            format!("write {:?}: {}", path, e),
+            hints::rapl_locked(&path.display().to_string()),
+        ),
+    }
+}
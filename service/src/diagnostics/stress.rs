@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use sysinfo::System;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::types::{StressReport, StressRequest};
+
+const MIN_DURATION_SECS: u32 = 5;
+const MAX_DURATION_SECS: u32 = 300;
+const DEFAULT_SAMPLE_MS: u64 = 500;
+const MIN_SAMPLE_MS: u64 = 250;
+
+/// A fan counts as "responding" once it rises this far above its start RPM
+/// (10% of the start value, but never less than this floor).
+const FAN_RESPONSE_MIN_DELTA_RPM: u32 = 200;
+
+struct Sample {
+    at_ms: u64,
+    temps: BTreeMap<String, i32>,
+    rpms: Vec<u32>,
+    clock_mhz: Option<u32>,
+}
+
+/// The built-in CPU load. There is no external command option: the service runs with
+/// Administrator/root rights and the API has no authentication.
+pub(super) struct Load {
+    stop: Arc<AtomicBool>,
+    workers: Vec<std::thread::JoinHandle<()>>,
+}
+
+impl Load {
+    pub(super) fn start(req: &StressRequest) -> Result<Self, String> {
+        let available = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let threads = req
+            .threads
+            .map(|t| t as usize)
+            .unwrap_or(available)
+            .clamp(1, available * 2);
+        let stop = Arc::new(AtomicBool::new(false));
+        let workers = (0..threads)
+            .map(|_| {
+                let stop = stop.clone();
+                std::thread::spawn(move || {
+                    let mut x: u64 = 1;
+                    while !stop.load(Ordering::Relaxed) {
+                        for _ in 0..10_000 {
+                            x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                        }
+                        std::hint::black_box(x);
+                    }
+                })
+            })
+            .collect();
+        info!("stress: started built-in CPU load on {} threads", threads);
+        Ok(Load { stop, workers })
+    }
+
+    /// Stop the workers and wait for them to exit.
+    pub(super) async fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let workers = std::mem::take(&mut self.workers);
+        let _ = tokio::task::spawn_blocking(move || {
+            for w in workers {
+                let _ = w.join();
+            }
+        })
+        .await;
+    }
+}
+
+/// A run dropped midway (the request was cancelled) must not leave the workers pinning the CPU.
+impl Drop for Load {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Run a bounded load while sampling telemetry, then summarize it for curve tuning.
+pub async fn run(cli: &FrameworkTool, req: &StressRequest) -> Result<StressReport, String> {
+    let duration = Duration::from_secs(req.duration_secs.clamp(MIN_DURATION_SECS, MAX_DURATION_SECS) as u64);
+    let sample_every = Duration::from_millis(req.sample_ms.unwrap_or(DEFAULT_SAMPLE_MS).max(MIN_SAMPLE_MS));
+
+    let mut sys = System::new();
+    let started = Instant::now();
+    // The baseline is what lag and deltas are measured against, so it must succeed.
+    let mut samples = vec![take_sample(cli, &mut sys, sample_every, started).await?];
+
+    let load = Load::start(req)?;
+    let deadline = started + duration;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        sleep(sample_every.min(deadline - now)).await;
+        match take_sample(cli, &mut sys, sample_every, started).await {
+            Ok(s) => samples.push(s),
+            Err(e) => warn!("stress: sample failed: {}", e),
+        }
+    }
+    load.stop().await;
+
+    let report = build_report(&samples, started.elapsed());
+    info!(
+        "stress: finished after {} ms with {} samples",
+        report.duration_ms, report.samples
+    );
+    Ok(report)
+}
+
+async fn take_sample(
+    cli: &FrameworkTool,
+    sys: &mut System,
+    sample_every: Duration,
+    started: Instant,
+) -> Result<Sample, String> {
+    let thermal = crate::sensors::thermal_max_age(cli, sample_every / 2).await?;
+    sys.refresh_cpu_frequency();
+    let freqs: Vec<u64> = sys.cpus().iter().map(|c| c.frequency()).filter(|f| *f > 0).collect();
+    let clock_mhz = if freqs.is_empty() {
+        None
+    } else {
+        Some((freqs.iter().sum::<u64>() / freqs.len() as u64) as u32)
+    };
+    Ok(Sample {
+        at_ms: started.elapsed().as_millis() as u64,
+        temps: thermal.temps,
+        rpms: thermal.fans.iter().map(|f| f.rpm).collect(),
+        clock_mhz,
+    })
+}
+
+fn build_report(samples: &[Sample], elapsed: Duration) -> StressReport {
+    let Some(first) = samples.first() else {
+        return StressReport::default();
+    };
+    let duration_ms = elapsed.as_millis() as u64;
+
+    let mut max_temps = first.temps.clone();
+    for s in samples {
+        for (k, v) in &s.temps {
+            let e = max_temps.entry(k.clone()).or_insert(*v);
+            *e = (*e).max(*v);
+        }
+    }
+
+    let peak_clock_mhz = samples.iter().filter_map(|s| s.clock_mhz).max();
+    let second_half: Vec<u32> = samples
+        .iter()
+        .skip(1)
+        .filter(|s| s.at_ms >= duration_ms / 2)
+        .filter_map(|s| s.clock_mhz)
+        .collect();
+    let sustained_clock_mhz = if second_half.is_empty() {
+        None
+    } else {
+        Some((second_half.iter().map(|&c| c as u64).sum::<u64>() / second_half.len() as u64) as u32)
+    };
+
+    let fan_start_rpm = first.rpms.clone();
+    let mut fan_max_rpm = fan_start_rpm.clone();
+    let mut fan_response_lag_ms = vec![None; fan_start_rpm.len()];
+    for s in samples.iter().skip(1) {
+        for (i, &rpm) in s.rpms.iter().enumerate().take(fan_start_rpm.len()) {
+            fan_max_rpm[i] = fan_max_rpm[i].max(rpm);
+            let start = fan_start_rpm[i];
+            let threshold = start + (start / 10).max(FAN_RESPONSE_MIN_DELTA_RPM);
+            if fan_response_lag_ms[i].is_none() && rpm >= threshold {
+                fan_response_lag_ms[i] = Some(s.at_ms);
+            }
+        }
+    }
+
+    StressReport {
+        duration_ms,
+        samples: samples.len() as u32,
+        start_temps: first.temps.clone(),
+        max_temps,
+        peak_clock_mhz,
+        sustained_clock_mhz,
+        fan_start_rpm,
+        fan_max_rpm,
+        fan_response_lag_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(at_ms: u64, apu: i32, rpms: Vec<u32>, clock: u32) -> Sample {
+        Sample {
+            at_ms,
+            temps: BTreeMap::from([("APU".to_string(), apu)]),
+            rpms,
+            clock_mhz: Some(clock),
+        }
+    }
+
+    #[test]
+    fn report_tracks_peaks_and_fan_lag() {
+        let samples = vec![
+            sample(0, 45, vec![1000, 0], 1800),
+            sample(1000, 70, vec![1050, 0], 4500),
+            sample(2000, 85, vec![1400, 0], 4000),
+            sample(3000, 80, vec![2000, 0], 3800),
+        ];
+        let r = build_report(&samples, Duration::from_millis(3000));
+        assert_eq!(r.samples, 4);
+        assert_eq!(r.start_temps.get("APU"), Some(&45));
+        assert_eq!(r.max_temps.get("APU"), Some(&85));
+        assert_eq!(r.peak_clock_mhz, Some(4500));
+        // Second half (>= 1500 ms): 4000 and 3800.
+        assert_eq!(r.sustained_clock_mhz, Some(3900));
+        assert_eq!(r.fan_max_rpm, vec![2000, 0]);
+        // +50 RPM is noise; +400 clears the 200 RPM floor. The idle fan never responds.
+        assert_eq!(r.fan_response_lag_ms, vec![Some(2000), None]);
+    }
+}
@@ -0,0 +1,79 @@
+// Do-not-disturb detection for signed-in users.
+//
+// The service runs outside the user's desktop session (session 0 / system unit), so it looks the
+// state up on the users' behalf: on Windows the per-user "Do not disturb" toggle in each loaded
+// user hive, on Linux the `Inhibited` property of each active session's notification daemon.
+// Best-effort: any lookup failure reads as "not active".
+
+use std::time::Duration;
+
+use crate::utils::global_cache;
+
+/// True when any signed-in user has do-not-disturb / Focus Assist turned on.
+pub async fn is_active() -> bool {
+    const TTL: Duration = Duration::from_secs(30);
+    global_cache::cache_get_or_update("dnd.active", TTL, true, || async { query().await })
+        .await
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+async fn query() -> Result<bool, String> {
+    // Windows 11 "Do not disturb" (and Focus Assist priority/alarms-only on Windows 10) turns
+    // toasts off globally via NOC_GLOBAL_SETTING_TOASTS_ENABLED = 0.
+    let ps = r#"
+Get-ChildItem Registry::HKEY_USERS -ErrorAction SilentlyContinue |
+  Where-Object { $_.PSChildName -match '^S-1-5-21-[\d-]+$' } |
+  ForEach-Object {
+    $k = "Registry::$($_.Name)\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
+    (Get-ItemProperty -Path $k -Name NOC_GLOBAL_SETTING_TOASTS_ENABLED -ErrorAction SilentlyContinue).NOC_GLOBAL_SETTING_TOASTS_ENABLED
+  }
+"#;
+    let out = tokio::process::Command::new("powershell")
+        .arg("-NoProfile")
+        .arg("-NonInteractive")
+        .arg("-Command")
+        .arg(ps)
+        .output()
+        .await
+        .map_err(|e| format!("failed to run powershell: {e}"))?;
+    if !out.status.success() {
+        return Err(format!("DND query exited with {}", out.status));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).lines().any(|l| l.trim() == "0"))
+}
+
+#[cfg(target_os = "linux")]
+async fn query() -> Result<bool, String> {
+    let users = tokio::process::Command::new("loginctl")
+        .args(["list-users", "--no-legend"])
+        .output()
+        .await
+        .map_err(|e| format!("failed to run loginctl: {e}"))?;
+    for line in String::from_utf8_lossy(&users.stdout).lines() {
+        // "<uid> <name> [linger] [state]"
+        let Some(name) = line.split_whitespace().nth(1) else {
+            continue;
+        };
+        let Ok(out) = tokio::process::Command::new("busctl")
+            .arg(format!("--machine={}@.host", name))
+            .args([
+                "--user",
+                "get-property",
+                "org.freedesktop.Notifications",
+                "/org/freedesktop/Notifications",
+                "org.freedesktop.Notifications",
+                "Inhibited",
+            ])
+            .output()
+            .await
+        else {
+            continue;
+        };
+        // busctl prints "b true" / "b false"
+        if out.status.success() && String::from_utf8_lossy(&out.stdout).trim() == "b true" {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
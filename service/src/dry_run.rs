@@ -0,0 +1,115 @@
+// `POST /config?dry_run=true`: report the hardware writes a config change would cause without
+// making them. Every write path (framework_tool/ryzenadj/powercfg setters, sysfs writes) asks
+// `intercept` first; inside `Plan::add` that records the exact command line or file write and
+// skips it. Reads still run, so the plan is computed against the live machine by the same setter
+// code the background tasks use.
+
+use std::cell::RefCell;
+use std::future::Future;
+
+use crate::state::AppState;
+use crate::types::{Config, PlannedChange};
+
+tokio::task_local! {
+    static RECORDER: RefCell<Vec<String>>;
+}
+
+/// Inside a dry run, record `command` and return `true`: the caller must not perform the write.
+pub fn intercept(command: impl FnOnce() -> String) -> bool {
+    RECORDER.try_with(|r| r.borrow_mut().push(command())).is_ok()
+}
+
+/// Shell-style rendering of a CLI invocation.
+pub fn command_line(program: &str, args: &[&str]) -> String {
+    let quote = |s: &str| {
+        if s.is_empty() || s.contains(char::is_whitespace) {
+            format!("\"{}\"", s)
+        } else {
+            s.to_string()
+        }
+    };
+    std::iter::once(program)
+        .chain(args.iter().copied())
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Default)]
+pub struct Plan(Vec<PlannedChange>);
+
+impl Plan {
+    /// Run a setter with writes recorded instead of performed, and file what it would do under
+    /// `setting`.
+    pub async fn add<T>(&mut self, setting: &str, write: impl Future<Output = Result<T, String>>) {
+        let (result, commands) = RECORDER
+            .scope(RefCell::new(Vec::new()), async {
+                let result = write.await;
+                (result, RECORDER.with(|r| r.take()))
+            })
+            .await;
+        self.0.push(PlannedChange {
+            setting: setting.to_string(),
+            commands,
+            error: result.err(),
+        });
+    }
+
+    pub fn into_changes(self) -> Vec<PlannedChange> {
+        self.0
+    }
+}
+
+/// The writes the background tasks would make for a config change from `current` to `new` in
+/// the `touched` sections. Safe mode runs no control tasks, so it plans nothing.
+pub async fn config_changes(state: &AppState, current: &Config, new: &Config, touched: &[&str]) -> Vec<PlannedChange> {
+    let mut plan = Plan::default();
+    if crate::safe_mode::is_active() {
+        return plan.into_changes();
+    }
+    if touched.contains(&"power") {
+        crate::tasks::power::preview(&mut plan, &state.power, &new.power, &state.framework_tool).await;
+    }
+    let Some(cli) = state.framework_tool.read().await.clone() else {
+        return plan.into_changes();
+    };
+    // The fan task only re-anchors when the fan config actually changes
+    let fan_changed = serde_json::to_value(&current.fan).ok() != serde_json::to_value(&new.fan).ok();
+    if touched.contains(&"fan") && fan_changed {
+        crate::tasks::fan_curve::preview(&mut plan, &cli, &new.fan, &new.sensors.zones).await;
+    }
+    if touched.contains(&"battery") {
+        crate::tasks::battery::preview(&mut plan, &cli, &current.battery, &new.battery).await;
+    }
+    if touched.contains(&"keyboard_backlight") {
+        crate::tasks::kblight::preview(&mut plan, &cli, &current.keyboard_backlight, &new.keyboard_backlight).await;
+    }
+    plan.into_changes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn records_instead_of_writing() {
+        assert!(!intercept(|| "outside".into()));
+        let mut plan = Plan::default();
+        plan.add("battery.charge_limit_max_pct", async {
+            if !intercept(|| command_line("C:\\Program Files\\framework_tool.exe", &["--charge-limit", "80"])) {
+                panic!("write was not intercepted");
+            }
+            Ok(())
+        })
+        .await;
+        plan.add("power.ac.tdp_watts", async { Err::<(), _>("unsupported".to_string()) })
+            .await;
+        let changes = plan.into_changes();
+        assert_eq!(
+            changes[0].commands,
+            ["\"C:\\Program Files\\framework_tool.exe\" --charge-limit 80"]
+        );
+        assert!(changes[1].commands.is_empty());
+        assert_eq!(changes[1].error.as_deref(), Some("unsupported"));
+    }
+}
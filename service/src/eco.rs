@@ -0,0 +1,254 @@
+// Eco mode (`POST /eco`): one switch for users who do not want to configure anything. Turning it
+// on writes a fixed bundle into the saved config: a low TDP and the `power` EPP in both the AC and
+// battery power profiles, a quiet fan curve and an 80% charge limit. The values it replaces are
+// kept in `eco_state.json` next to the config and written back when it is turned off. The bundle
+// is ordinary config, so the usual tasks apply it and it survives restarts; settings the power
+// backend does not support are left alone.
+
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::types::{
+    Config, CurveConfig, FanControlMode, GlobalCurveConfig, PowerCapabilities, SettingString, SettingU32, SettingU8,
+};
+
+/// Sections of the config Eco mode writes.
+pub const SECTIONS: [&str; 3] = ["fan", "power", "battery"];
+
+const TDP_WATTS: u32 = 15;
+const EPP: &str = "power";
+const CHARGE_LIMIT_PCT: u8 = 80;
+/// Quieter than the default curve: fans stay off to 50°C and reach full speed only at 95°C
+const CURVE_POINTS: [[u32; 2]; 5] = [[50, 0], [65, 20], [75, 40], [85, 70], [95, 100]];
+
+/// The settings Eco mode owns.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Settings {
+    #[serde(default)]
+    fan_mode: Option<FanControlMode>,
+    #[serde(default)]
+    fan_curve: Option<GlobalCurveConfig>,
+    #[serde(default)]
+    ac_tdp_watts: Option<SettingU32>,
+    #[serde(default)]
+    ac_epp: Option<SettingString>,
+    #[serde(default)]
+    battery_tdp_watts: Option<SettingU32>,
+    #[serde(default)]
+    battery_epp: Option<SettingString>,
+    #[serde(default)]
+    charge_limit_max_pct: Option<SettingU8>,
+}
+
+impl Settings {
+    fn capture(cfg: &Config) -> Self {
+        let ac = cfg.power.ac.as_ref();
+        let battery = cfg.power.battery.as_ref();
+        Self {
+            fan_mode: cfg.fan.mode.clone(),
+            fan_curve: cfg.fan.curve.clone(),
+            ac_tdp_watts: ac.and_then(|p| p.tdp_watts.clone()),
+            ac_epp: ac.and_then(|p| p.epp_preference.clone()),
+            battery_tdp_watts: battery.and_then(|p| p.tdp_watts.clone()),
+            battery_epp: battery.and_then(|p| p.epp_preference.clone()),
+            charge_limit_max_pct: cfg.battery.charge_limit_max_pct.clone(),
+        }
+    }
+
+    fn install(self, cfg: &mut Config) {
+        cfg.fan.mode = self.fan_mode;
+        cfg.fan.curve = self.fan_curve;
+        let ac = cfg.power.ac.get_or_insert_with(Default::default);
+        ac.tdp_watts = self.ac_tdp_watts;
+        ac.epp_preference = self.ac_epp;
+        let battery = cfg.power.battery.get_or_insert_with(Default::default);
+        battery.tdp_watts = self.battery_tdp_watts;
+        battery.epp_preference = self.battery_epp;
+        cfg.battery.charge_limit_max_pct = self.charge_limit_max_pct;
+        crate::fan_profiles::reconcile(&mut cfg.fan);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EcoState {
+    since_ms: i64,
+    /// What Eco mode replaced, restored when it is turned off
+    previous: Settings,
+}
+
+fn state_path() -> PathBuf {
+    crate::config::config_path().with_file_name("eco_state.json")
+}
+
+fn current() -> &'static Mutex<Option<EcoState>> {
+    static CURRENT: OnceLock<Mutex<Option<EcoState>>> = OnceLock::new();
+    CURRENT.get_or_init(|| {
+        let loaded = std::fs::read_to_string(state_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok());
+        Mutex::new(loaded)
+    })
+}
+
+fn store(state: Option<EcoState>) -> Result<(), String> {
+    let path = state_path();
+    match &state {
+        Some(s) => {
+            let json = serde_json::to_string_pretty(s).map_err(|e| e.to_string())?;
+            std::fs::write(&path, json).map_err(|e| format!("failed to write {:?}: {}", path, e))?;
+        }
+        None => match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("failed to remove {:?}: {}", path, e));
+            }
+            _ => {}
+        },
+    }
+    if let Ok(mut c) = current().lock() {
+        *c = state;
+    }
+    Ok(())
+}
+
+/// The Eco bundle on top of `cfg`; what `caps` does not support keeps its current value.
+fn bundle(cfg: &Config, caps: Option<&PowerCapabilities>) -> Settings {
+    let mut s = Settings::capture(cfg);
+    if let Some(caps) = caps.filter(|c| c.supports_tdp) {
+        let watts = TDP_WATTS
+            .max(caps.tdp_min_watts.unwrap_or(TDP_WATTS))
+            .min(caps.tdp_max_watts.unwrap_or(TDP_WATTS));
+        let tdp = Some(SettingU32 {
+            enabled: true,
+            value: watts,
+        });
+        s.ac_tdp_watts = tdp.clone();
+        s.battery_tdp_watts = tdp;
+    }
+    let epp_supported = caps.is_some_and(|c| {
+        c.supports_epp
+            && c.available_epp_preferences
+                .as_ref()
+                .is_none_or(|prefs| prefs.iter().any(|p| p == EPP))
+    });
+    if epp_supported {
+        let epp = Some(SettingString {
+            enabled: true,
+            value: EPP.into(),
+        });
+        s.ac_epp = epp.clone();
+        s.battery_epp = epp;
+    }
+    // Keep the user's sensors and smoothing, only the points change
+    let mut curve = s.fan_curve.take().unwrap_or(GlobalCurveConfig {
+        curve: CurveConfig {
+            sensors: vec!["CPU".into()],
+            hysteresis_down_c: 2,
+            rate_limit_pct_per_step: 100,
+            ..Default::default()
+        },
+        poll_ms: 2000,
+    });
+    if curve.curve.sensors.is_empty() {
+        curve.curve.sensors = vec!["CPU".into()];
+    }
+    curve.curve.points = CURVE_POINTS.to_vec();
+    s.fan_mode = Some(FanControlMode::Curve);
+    s.fan_curve = Some(curve);
+    s.charge_limit_max_pct = Some(SettingU8 {
+        enabled: true,
+        value: CHARGE_LIMIT_PCT,
+    });
+    s
+}
+
+/// A pending Eco switch: `config` is the saved config to write, and `record` must succeed before
+/// it is saved so turning Eco off can find what it replaced.
+pub struct Switch {
+    pub config: Config,
+    state: Option<EcoState>,
+    previous: Option<EcoState>,
+}
+
+impl Switch {
+    pub fn record(&self) -> Result<(), String> {
+        store(self.state.clone())
+    }
+
+    /// Put the Eco state back after the config failed to save.
+    pub fn undo(self) {
+        if let Err(e) = store(self.previous) {
+            warn!("eco: {}", e);
+        }
+    }
+}
+
+/// Turn Eco mode on or off on top of the `saved` config; `None` when it already is.
+pub fn switch(saved: &Config, enable: bool, caps: Option<&PowerCapabilities>) -> Option<Switch> {
+    let previous = current().lock().ok()?.clone();
+    let mut config = saved.clone();
+    let state = match (enable, &previous) {
+        (true, None) => {
+            bundle(saved, caps).install(&mut config);
+            Some(EcoState {
+                since_ms: chrono::Utc::now().timestamp_millis(),
+                previous: Settings::capture(saved),
+            })
+        }
+        (false, Some(p)) => {
+            p.previous.clone().install(&mut config);
+            None
+        }
+        _ => return None,
+    };
+    Some(Switch {
+        config,
+        state,
+        previous,
+    })
+}
+
+/// When Eco mode was turned on, if it is on.
+pub fn since_ms() -> Option<i64> {
+    current().lock().ok()?.as_ref().map(|s| s.since_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PowerProfile;
+
+    #[test]
+    fn bundle_respects_capabilities_and_restores() {
+        let mut cfg = Config::default();
+        cfg.power.ac = Some(PowerProfile {
+            tdp_watts: Some(SettingU32 {
+                enabled: true,
+                value: 45,
+            }),
+            ..Default::default()
+        });
+        let caps = PowerCapabilities {
+            supports_epp: true,
+            available_epp_preferences: Some(vec!["performance".into(), "power".into()]),
+            ..Default::default()
+        };
+        let mut eco = cfg.clone();
+        bundle(&cfg, Some(&caps)).install(&mut eco);
+        let ac = eco.power.ac.as_ref().unwrap();
+        // No TDP control: the configured TDP stays
+        assert_eq!(ac.tdp_watts.as_ref().unwrap().value, 45);
+        assert_eq!(ac.epp_preference.as_ref().unwrap().value, "power");
+        assert_eq!(eco.fan.mode, Some(FanControlMode::Curve));
+        assert_eq!(eco.fan.curve.as_ref().unwrap().curve.sensors, ["CPU"]);
+        assert_eq!(eco.battery.charge_limit_max_pct.as_ref().unwrap().value, 80);
+
+        Settings::capture(&cfg).install(&mut eco);
+        assert_eq!(
+            serde_json::to_value(Settings::capture(&eco)).unwrap(),
+            serde_json::to_value(Settings::capture(&cfg)).unwrap()
+        );
+    }
+}
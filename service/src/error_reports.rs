@@ -0,0 +1,345 @@
+// Opt-in error reports (`logging.error_reports`): a panic, or the same warning/error logged
+// REPEAT_THRESHOLD times within REPEAT_WINDOW_MS, is written as a JSON file to `error_reports/`
+// next to the config, and uploaded to `logging.error_report_url` when one is set. Nothing leaves
+// the machine without that URL.
+//
+// Reports carry the service version, an OS/CPU/mainboard fingerprint and the config without its
+// `network` and `logging` sections or URLs. User and host names are scrubbed from messages.
+// Panics are written synchronously from the panic hook and uploaded on the next start.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use tracing::field::{Field, Visit};
+use tracing::{info, warn, Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::types::{ErrorFingerprint, ErrorReport, ErrorReportKind, LoggingConfig};
+
+/// Same call site this many times within the window makes one report
+const REPEAT_THRESHOLD: u32 = 5;
+const REPEAT_WINDOW_MS: i64 = 10 * 60 * 1000;
+/// Oldest reports are deleted beyond this
+const MAX_REPORTS: usize = 50;
+const UPLOAD_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static UPLOAD_URL: RwLock<Option<String>> = RwLock::new(None);
+static REPEATS: Mutex<Repeats> = Mutex::new(Repeats(BTreeMap::new()));
+/// Wakes the upload loop when a report is written
+static WRITTEN: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+/// Apply a saved `logging` section.
+pub fn apply(cfg: &LoggingConfig) {
+    let was = ENABLED.swap(cfg.error_reports, Ordering::Relaxed);
+    if let Ok(mut w) = UPLOAD_URL.write() {
+        *w = cfg.error_report_url.clone().filter(|_| cfg.error_reports);
+    }
+    if was != cfg.error_reports {
+        info!(
+            "error reports {}",
+            if cfg.error_reports { "enabled" } else { "disabled" }
+        );
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn dir() -> PathBuf {
+    crate::config::config_path().with_file_name("error_reports")
+}
+
+/// Per call site: (window start, count in window, already reported in this window)
+struct Repeats(BTreeMap<String, (i64, u32, bool)>);
+
+impl Repeats {
+    /// Count one occurrence of `site`; returns the count when it reaches the threshold.
+    fn record(&mut self, site: &str, now: i64) -> Option<u32> {
+        let entry = self.0.entry(site.to_string()).or_insert((now, 0, false));
+        if now - entry.0 > REPEAT_WINDOW_MS {
+            *entry = (now, 0, false);
+        }
+        entry.1 += 1;
+        if entry.1 >= REPEAT_THRESHOLD && !entry.2 {
+            entry.2 = true;
+            return Some(entry.1);
+        }
+        None
+    }
+}
+
+/// Remove user and host names: the account segment of home paths, then the names themselves.
+fn scrub(message: &str) -> String {
+    static HOME: OnceLock<regex::Regex> = OnceLock::new();
+    let home = HOME.get_or_init(|| regex::Regex::new(r#"(?i)(/home/|/Users/)[^/\s'"]+|(\\Users\\)[^\\'"]+"#).unwrap());
+    let mut out = home
+        .replace_all(message, |c: &regex::Captures| {
+            format!("{}<user>", c.get(1).or(c.get(2)).map_or("", |m| m.as_str()))
+        })
+        .into_owned();
+    let names = ["USER", "USERNAME", "SUDO_USER"]
+        .iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .chain(sysinfo::System::host_name());
+    for name in names.filter(|n| n.len() >= 3 && n != "root") {
+        out = out.replace(&name, "<redacted>");
+    }
+    out
+}
+
+fn fingerprint() -> ErrorFingerprint {
+    static CPU: OnceLock<String> = OnceLock::new();
+    let cpu = CPU.get_or_init(|| {
+        let sys =
+            sysinfo::System::new_with_specifics(sysinfo::RefreshKind::new().with_cpu(sysinfo::CpuRefreshKind::new()));
+        sys.cpus()
+            .iter()
+            .map(|c| c.brand().trim())
+            .find(|b| !b.is_empty())
+            .unwrap_or_default()
+            .to_string()
+    });
+    let firmware = crate::firmware::last();
+    ErrorFingerprint {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: sysinfo::System::long_os_version().unwrap_or_default(),
+        kernel: sysinfo::System::kernel_version().unwrap_or_default(),
+        arch: std::env::consts::ARCH.to_string(),
+        cpu: cpu.clone(),
+        mainboard: firmware.as_ref().and_then(|f| f.mainboard.clone()),
+        bios_version: firmware.as_ref().and_then(|f| f.bios_version.clone()),
+        ec_version: firmware.and_then(|f| f.ec_version),
+    }
+}
+
+/// The saved config without the sections and fields that may identify the user's setup.
+fn config_snapshot() -> serde_json::Value {
+    let mut cfg = serde_json::to_value(crate::config::load()).unwrap_or_default();
+    if let Some(map) = cfg.as_object_mut() {
+        map.remove("network");
+        map.remove("logging");
+        for (section, field) in [
+            ("updates", "firmware_feed_url"),
+            ("reports", "webhook_url"),
+            ("backup", "dir"),
+        ] {
+            if let Some(s) = map.get_mut(section).and_then(|s| s.as_object_mut()) {
+                s.remove(field);
+            }
+        }
+    }
+    cfg
+}
+
+fn write(kind: ErrorReportKind, message: &str, location: Option<String>, count: Option<u32>) {
+    let created_ms = now_ms();
+    let report = ErrorReport {
+        id: format!(
+            "{}-{}",
+            created_ms,
+            match kind {
+                ErrorReportKind::Panic => "panic",
+                ErrorReportKind::RepeatedError => "repeated_error",
+            }
+        ),
+        kind,
+        created_ms,
+        message: scrub(message.trim_end()),
+        location,
+        count,
+        fingerprint: fingerprint(),
+        config: config_snapshot(),
+        sent: false,
+    };
+    let dir = dir();
+    let result = std::fs::create_dir_all(&dir).and_then(|_| {
+        std::fs::write(
+            dir.join(format!("{}.json", report.id)),
+            serde_json::to_vec_pretty(&report)?,
+        )
+    });
+    match result {
+        Ok(()) => {
+            prune();
+            WRITTEN.notify_one();
+        }
+        Err(e) => warn!("error reports: failed to write report: {}", e),
+    }
+}
+
+fn prune() {
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(dir()) {
+        Ok(entries) => entries.flatten().map(|e| e.path()).collect(),
+        Err(_) => return,
+    };
+    files.sort();
+    let excess = files.len().saturating_sub(MAX_REPORTS);
+    for path in files.into_iter().take(excess) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Write a report for a panic. Called from the panic hook, before hardware defaults are restored.
+pub fn record_panic(info: &std::panic::PanicHookInfo<'_>) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic".into());
+    let location = info.location().map(|l| format!("{}:{}", l.file(), l.line()));
+    write(ErrorReportKind::Panic, &payload, location, None);
+}
+
+/// Reports on disk, newest first.
+pub fn list() -> Vec<ErrorReport> {
+    let mut out: Vec<ErrorReport> = std::fs::read_dir(dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| std::fs::read(e.path()).ok())
+                .filter_map(|b| serde_json::from_slice(&b).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    out.sort_by_key(|r: &ErrorReport| std::cmp::Reverse(r.created_ms));
+    out
+}
+
+async fn upload(url: &str, report: &ErrorReport) -> Result<(), String> {
+    let body = serde_json::to_string(report).map_err(|e| e.to_string())?;
+    let resp = reqwest::Client::new()
+        .post(url)
+        .header("content-type", "application/json")
+        .header("user-agent", "framework-control-service")
+        .timeout(std::time::Duration::from_secs(30))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("endpoint returned {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// Upload unsent reports when an endpoint is configured: at start (panics of the previous run),
+/// when a report is written, and periodically to retry failed uploads.
+pub async fn run() {
+    loop {
+        let url = UPLOAD_URL.read().ok().and_then(|u| u.clone());
+        if let Some(url) = url {
+            for mut report in list().into_iter().filter(|r| !r.sent) {
+                if let Err(e) = upload(&url, &report).await {
+                    warn!("error reports: upload failed: {}", e);
+                    break;
+                }
+                report.sent = true;
+                if let Ok(bytes) = serde_json::to_vec_pretty(&report) {
+                    let _ = std::fs::write(dir().join(format!("{}.json", report.id)), bytes);
+                }
+                info!("error reports: uploaded {}", report.id);
+            }
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(UPLOAD_INTERVAL) => {}
+            _ = WRITTEN.notified() => {}
+        }
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = value.to_string();
+        }
+    }
+}
+
+/// Counts this crate's warnings and errors per call site.
+struct RepeatLayer;
+
+impl<S: Subscriber> Layer<S> for RepeatLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        // The access log repeats the failures already logged by the handlers; this module's own
+        // warnings would recurse
+        if !ENABLED.load(Ordering::Relaxed)
+            || !meta.target().starts_with(env!("CARGO_CRATE_NAME"))
+            || meta.target().ends_with("::access")
+            || meta.target() == module_path!()
+        {
+            return;
+        }
+        let site = format!("{}:{}", meta.file().unwrap_or(meta.target()), meta.line().unwrap_or(0));
+        // try_lock: a report write that logs must not deadlock on itself
+        let count = match REPEATS.try_lock() {
+            Ok(mut r) => r.record(&site, now_ms()),
+            Err(_) => return,
+        };
+        if let Some(count) = count {
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            let level = if *meta.level() == Level::ERROR {
+                "error"
+            } else {
+                "warning"
+            };
+            write(
+                ErrorReportKind::RepeatedError,
+                &format!("{} ({}x within 10 min): {}", level, count, visitor.0),
+                Some(site),
+                Some(count),
+            );
+        }
+    }
+}
+
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    RepeatLayer.with_filter(LevelFilter::WARN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_once_per_window_and_scrubs_user_paths() {
+        let mut r = Repeats(BTreeMap::new());
+        let hits: Vec<Option<u32>> = (0..6).map(|i| r.record("tasks/fan_curve.rs:120", i * 1000)).collect();
+        assert_eq!(hits[..4], [None; 4]);
+        assert_eq!(hits[4], Some(5));
+        assert_eq!(hits[5], None);
+        // A new window can report again
+        let later: Vec<Option<u32>> = (0..5)
+            .map(|i| r.record("tasks/fan_curve.rs:120", REPEAT_WINDOW_MS + 10_000 + i))
+            .collect();
+        assert_eq!(later[4], Some(5));
+
+        assert_eq!(
+            scrub("failed to read /home/jdoe/.config/x and C:\\Users\\Jane Doe\\AppData"),
+            "failed to read /home/<user>/.config/x and C:\\Users\\<user>\\AppData"
+        );
+    }
+}
@@ -0,0 +1,344 @@
+// Fan RPM anomaly detection: a clogged or wearing fan turns at a different speed for the same
+// duty long before it stops. Per fan and 10% duty band, the first `LEARN_SAMPLES` steady readings
+// at a duty the service applied learn the fan's normal RPM and its spread. The baseline then
+// stays frozen, so slow wear is not learnt as normal; until a band is learnt, the fan's
+// calibration (when there is one) stands in for it. Every steady reading is scored against its
+// band and the score smoothed: a sustained drift below or above the baseline, or a spread far
+// beyond the learnt one, raises a `fan_anomaly:<index>` warning once it has lasted `CONFIRM_MS`.
+//
+// Readings are skipped while the duty just changed, under EC control or RPM targets (no applied
+// duty), at 0% and while fan contention is detected. Marking a fan cleaned
+// (`POST /fans/stats/reset`) relearns it. Baselines persist in `fan_anomaly.json`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::types::{AlertSeverity, FanAnomaly, FanAnomalyKind, FanControlConfig};
+
+const DUTY_BAND_PCT: u32 = 10;
+/// Steady readings that make up a band's baseline (about 10 minutes at the default poll)
+const LEARN_SAMPLES: u32 = 600;
+/// A new duty is scored only once the fan had this long to reach its speed
+const SETTLE_MS: i64 = 10_000;
+/// Weight of the newest reading in the smoothed score
+const SMOOTHING: f64 = 0.05;
+/// Smoothed readings needed before the score means anything
+const WARMUP_SAMPLES: u32 = 20;
+/// Smoothed score (in baseline standard deviations) past which a reading is anomalous
+const Z_LIMIT: f64 = 3.0;
+/// Single readings are clamped so one glitch cannot dominate the smoothed score
+const Z_CLAMP: f64 = 10.0;
+/// Floor for a band's spread; a very steady fan would otherwise flag a few RPM of drift
+const MIN_STD_RPM: f64 = 60.0;
+/// Spread assumed around the calibrated RPM while a band is still learning
+const CALIBRATION_SPREAD: f64 = 0.08;
+const CONFIRM_MS: i64 = 10 * 60_000;
+const SAVE_INTERVAL_MS: i64 = 5 * 60_000;
+
+/// Running mean and variance of a band's RPM (Welford)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Band {
+    n: u32,
+    mean: f64,
+    m2: f64,
+}
+
+impl Band {
+    fn add(&mut self, rpm: f64) {
+        self.n += 1;
+        let d = rpm - self.mean;
+        self.mean += d / self.n as f64;
+        self.m2 += d * (rpm - self.mean);
+    }
+
+    fn learnt(&self) -> bool {
+        self.n >= LEARN_SAMPLES
+    }
+
+    fn std(&self) -> f64 {
+        let var = if self.n > 1 { self.m2 / (self.n - 1) as f64 } else { 0.0 };
+        var.sqrt().max(MIN_STD_RPM)
+    }
+}
+
+/// Live scoring state, rebuilt after a restart
+#[derive(Debug, Default)]
+struct Watch {
+    duty: Option<u32>,
+    duty_since_ms: i64,
+    samples: u32,
+    mean_z: f64,
+    mean_z2: f64,
+    pending_since_ms: Option<i64>,
+    flagged: Option<FanAnomaly>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FanModel {
+    #[serde(default)]
+    bands: BTreeMap<u32, Band>,
+    #[serde(skip)]
+    watch: Watch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Transition {
+    Raised,
+    Cleared,
+}
+
+impl FanModel {
+    fn learnt_bands(&self) -> u32 {
+        self.bands.values().filter(|b| b.learnt()).count() as u32
+    }
+
+    /// Fold in one reading. `duty` is the duty the service applied (`None` when it is not in
+    /// control), `calibrated_rpm` the fan's calibrated RPM at that duty.
+    fn observe(&mut self, ts_ms: i64, duty: Option<u32>, rpm: u32, calibrated_rpm: Option<u32>) -> Option<Transition> {
+        let w = &mut self.watch;
+        let Some(duty) = duty else {
+            w.duty = None;
+            return None;
+        };
+        if w.duty != Some(duty) {
+            w.duty = Some(duty);
+            w.duty_since_ms = ts_ms;
+            return None;
+        }
+        if duty == 0 || ts_ms - w.duty_since_ms < SETTLE_MS {
+            return None;
+        }
+        let band = self.bands.entry(duty.min(100) / DUTY_BAND_PCT).or_default();
+        let (expected, std) = if band.learnt() {
+            (band.mean, band.std())
+        } else {
+            band.add(rpm as f64);
+            let expected = calibrated_rpm.filter(|r| *r > 0)? as f64;
+            (expected, (expected * CALIBRATION_SPREAD).max(MIN_STD_RPM))
+        };
+        let z = ((rpm as f64 - expected) / std).clamp(-Z_CLAMP, Z_CLAMP);
+        if w.samples == 0 {
+            (w.mean_z, w.mean_z2) = (z, z * z);
+        } else {
+            w.mean_z += SMOOTHING * (z - w.mean_z);
+            w.mean_z2 += SMOOTHING * (z * z - w.mean_z2);
+        }
+        w.samples = w.samples.saturating_add(1);
+        if w.samples < WARMUP_SAMPLES {
+            return None;
+        }
+
+        let spread = (w.mean_z2 - w.mean_z * w.mean_z).max(0.0).sqrt();
+        let kind = if w.mean_z < -Z_LIMIT {
+            Some(FanAnomalyKind::Slow)
+        } else if w.mean_z > Z_LIMIT {
+            Some(FanAnomalyKind::Fast)
+        } else if spread > Z_LIMIT {
+            Some(FanAnomalyKind::Unsteady)
+        } else {
+            None
+        };
+        let Some(kind) = kind else {
+            w.pending_since_ms = None;
+            return w.flagged.take().map(|_| Transition::Cleared);
+        };
+        let since_ms = *w.pending_since_ms.get_or_insert(ts_ms);
+        if ts_ms - since_ms < CONFIRM_MS || w.flagged.as_ref().is_some_and(|f| f.kind == kind) {
+            return None;
+        }
+        w.flagged = Some(FanAnomaly {
+            kind,
+            duty_pct: duty,
+            expected_rpm: expected.round() as u32,
+            observed_rpm: (expected + w.mean_z * std).max(0.0).round() as u32,
+            since_ms,
+        });
+        Some(Transition::Raised)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Models {
+    #[serde(default)]
+    fans: BTreeMap<u32, FanModel>,
+    #[serde(skip)]
+    last_saved_ms: i64,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+fn state_path() -> PathBuf {
+    crate::config::config_path().with_file_name("fan_anomaly.json")
+}
+
+fn current() -> &'static Mutex<Models> {
+    static CURRENT: OnceLock<Mutex<Models>> = OnceLock::new();
+    CURRENT.get_or_init(|| {
+        let loaded = std::fs::read_to_string(state_path())
+            .ok()
+            .and_then(|s| serde_json::from_str::<Models>(&s).ok());
+        Mutex::new(loaded.unwrap_or_default())
+    })
+}
+
+fn alert_id(fan_index: u32) -> String {
+    format!("fan_anomaly:{}", fan_index)
+}
+
+fn message(fan_index: u32, a: &FanAnomaly) -> String {
+    let pct = |observed: u32| {
+        (observed as f64 / a.expected_rpm.max(1) as f64 * 100.0 - 100.0)
+            .abs()
+            .round()
+    };
+    match a.kind {
+        FanAnomalyKind::Slow => format!(
+            "Fan {} turns {:.0}% slower than usual at {}% duty ({} RPM, expected ~{}); check it for dust or a worn bearing",
+            fan_index,
+            pct(a.observed_rpm),
+            a.duty_pct,
+            a.observed_rpm,
+            a.expected_rpm
+        ),
+        FanAnomalyKind::Fast => format!(
+            "Fan {} turns {:.0}% faster than usual at {}% duty ({} RPM, expected ~{}); its air path may be blocked",
+            fan_index,
+            pct(a.observed_rpm),
+            a.duty_pct,
+            a.observed_rpm,
+            a.expected_rpm
+        ),
+        FanAnomalyKind::Unsteady => format!(
+            "Fan {} speed is unsteady at a constant {}% duty; its bearing may be wearing",
+            fan_index, a.duty_pct
+        ),
+    }
+}
+
+/// Fold one telemetry sample's fan RPMs in and raise or clear the anomaly warnings.
+pub fn record_sample(ts_ms: i64, rpms: &[u32], fan: &FanControlConfig) {
+    let contended = crate::tasks::fan_contention::status(fan).detected;
+    let Ok(mut models) = current().lock() else { return };
+    for (i, &rpm) in rpms.iter().enumerate() {
+        let i = i as u32;
+        let duty = crate::tasks::fan_restore::applied_duty(i).filter(|_| !contended);
+        let calibrated_rpm = duty.and_then(|d| {
+            let calibration = fan.calibration_for(i)?;
+            crate::diagnostics::fan_ramp::rpm_at(&calibration.points, d)
+        });
+        let model = models.fans.entry(i).or_default();
+        let learnt = model.learnt_bands();
+        let transition = model.observe(ts_ms, duty, rpm, calibrated_rpm);
+        if model.learnt_bands() != learnt {
+            info!("fan anomaly: fan {} learnt its RPM baseline for another duty band", i);
+        }
+        match transition {
+            Some(Transition::Raised) => {
+                if let Some(a) = &model.watch.flagged {
+                    let message = message(i, a);
+                    warn!("fan anomaly: {}", message);
+                    crate::alerts::raise(&alert_id(i), AlertSeverity::Warning, message);
+                }
+            }
+            Some(Transition::Cleared) => {
+                info!("fan anomaly: fan {} is back at its usual speed", i);
+                crate::alerts::clear(&alert_id(i));
+            }
+            None => {}
+        }
+        models.dirty |= duty.is_some();
+    }
+    if models.dirty && ts_ms - models.last_saved_ms >= SAVE_INTERVAL_MS {
+        models.last_saved_ms = ts_ms;
+        models.dirty = false;
+        save(&models);
+    }
+}
+
+/// Forget a fan's (all fans' when `None`) baseline so it is learnt again, e.g. after cleaning.
+pub fn relearn(fan_index: Option<u32>) {
+    let Ok(mut models) = current().lock() else { return };
+    for (&i, m) in models.fans.iter_mut() {
+        if fan_index.is_none_or(|f| f == i) {
+            *m = FanModel::default();
+            crate::alerts::clear(&alert_id(i));
+        }
+    }
+    save(&models);
+}
+
+/// Learnt bands and the confirmed anomaly, if any, for one fan
+pub fn status(fan_index: u32) -> (u32, Option<FanAnomaly>) {
+    let Ok(models) = current().lock() else {
+        return (0, None);
+    };
+    models
+        .fans
+        .get(&fan_index)
+        .map(|m| (m.learnt_bands(), m.watch.flagged.clone()))
+        .unwrap_or_default()
+}
+
+fn save(models: &Models) {
+    let path = state_path();
+    match serde_json::to_string(models) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("fan anomaly: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("fan anomaly: failed to serialize baselines: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `secs` one-second readings at `duty`, `rpm(t)` giving the RPM; the last transition.
+    fn run(m: &mut FanModel, t: &mut i64, secs: i64, duty: u32, rpm: impl Fn(i64) -> u32) -> Option<Transition> {
+        let mut last = None;
+        for _ in 0..secs {
+            *t += 1_000;
+            last = m.observe(*t, Some(duty), rpm(*t), None).or(last);
+        }
+        last
+    }
+
+    #[test]
+    fn learns_a_baseline_then_flags_sustained_drift() {
+        let mut m = FanModel::default();
+        let mut t = 0;
+        // Learning at 40% with ±50 RPM of noise; nothing can be flagged yet without a calibration
+        let noisy = |base: u32| move |t: i64| if t % 2_000 == 0 { base + 50 } else { base - 50 };
+        assert_eq!(run(&mut m, &mut t, 700, 40, noisy(3000)), None);
+        assert_eq!(m.learnt_bands(), 1);
+        assert!((m.bands[&4].mean - 3000.0).abs() < 1.0);
+
+        // A short dip is smoothed away; a lasting 15% drop is flagged after the confirm window
+        assert_eq!(run(&mut m, &mut t, 30, 40, noisy(2000)), None);
+        assert_eq!(run(&mut m, &mut t, 120, 40, noisy(3000)), None);
+        assert_eq!(run(&mut m, &mut t, 500, 40, noisy(2550)), None);
+        assert_eq!(run(&mut m, &mut t, 200, 40, noisy(2550)), Some(Transition::Raised));
+        let flagged = m.watch.flagged.clone().unwrap();
+        assert_eq!(
+            (flagged.kind, flagged.duty_pct, flagged.expected_rpm),
+            (FanAnomalyKind::Slow, 40, 3000)
+        );
+        assert!((2500..=2600).contains(&flagged.observed_rpm));
+
+        // Back to normal clears it; a duty without a baseline is only learnt
+        assert_eq!(run(&mut m, &mut t, 200, 40, noisy(3000)), Some(Transition::Cleared));
+        assert_eq!(run(&mut m, &mut t, 700, 70, noisy(1000)), None);
+        assert_eq!(m.learnt_bands(), 2);
+
+        // Swings at a steady duty are flagged as unsteady
+        let swinging = |t: i64| if t % 4_000 < 2_000 { 1500 } else { 500 };
+        assert_eq!(run(&mut m, &mut t, 700, 70, swinging), Some(Transition::Raised));
+        assert_eq!(m.watch.flagged.as_ref().unwrap().kind, FanAnomalyKind::Unsteady);
+    }
+}
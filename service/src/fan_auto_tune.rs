@@ -0,0 +1,614 @@
+// Curve auto-tuner (`fan.auto_tune`): over days of normal use, runs variants of `fan.curve` one
+// after another, each for `trial_minutes`, as a temporary override of the fan section (like
+// `POST /fan/compare`), and scores each from the telemetry recorded meanwhile: a weighted sum of
+// estimated fan noise and average temperature, plus a penalty per degree the hottest reading
+// went above `max_temp_c`. The first trial runs the curve unchanged as the baseline. Each next
+// candidate nudges one point's duty a few percent, within `max_offset_pct` of the baseline and
+// without breaking a rising curve; simulated annealing decides whether the run continues from
+// it, taking a worse curve now and then while the temperature is high so that a noisy trial
+// (the workload differs from hour to hour) does not stall the search. After `trials` the saved
+// curve is back and the best variant, if it beat the baseline, waits as a proposal for
+// `POST /fan/auto-tune/accept`. Point temperatures are never moved.
+//
+// A change to the saved curve or to `fan.auto_tune` starts the run over; another temporary fan
+// override (a fan boost, `POST /config/temporary`) abandons the trial in progress, which runs
+// again once that override ends. The run, the proposal and the history of trials and decisions
+// persist in `auto_tune.json` next to the config, so a restart resumes with the next trial.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::types::{
+    AutoTuneEvent, AutoTuneEventKind, AutoTunePhase, AutoTuneProposal, AutoTuneStatus, Config, FanAutoTuneConfig,
+    FanControlConfig, FanControlMode, TelemetrySample,
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_EVENTS: usize = 500;
+/// The trial's override outlives it by this much, so it only reverts on its own when the
+/// service stopped tuning without cleaning up
+const OVERRIDE_MARGIN_SECS: u64 = 5 * 60;
+/// Largest nudge of one point per trial
+const MAX_STEP_PCT: u32 = 5;
+/// Annealing temperature (in cost units) at the first and the last trial
+const START_TEMPERATURE: f32 = 2.0;
+const END_TEMPERATURE: f32 = 0.1;
+/// Cost per degree the hottest reading went above `max_temp_c`
+const OVERHEAT_PENALTY: f32 = 5.0;
+/// Noise cost of a trial during which no fan spun
+const SILENT_DBA: f32 = 20.0;
+/// A trial counts when telemetry covers at least this share of it (the machine may sleep)
+const MIN_COVERAGE: f32 = 0.5;
+/// A proposal has to beat the baseline by this much cost
+const MIN_GAIN: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Run {
+    settings: FanAutoTuneConfig,
+    baseline: Vec<[u32; 2]>,
+    /// Where annealing stands, and its cost
+    current: Vec<[u32; 2]>,
+    current_cost: f32,
+    /// Curve of the next (or running) trial
+    candidate: Vec<[u32; 2]>,
+    baseline_result: Option<AutoTuneEvent>,
+    best: Option<AutoTuneEvent>,
+    trials_done: u32,
+    temperature: f32,
+    rng: u64,
+    finished: bool,
+}
+
+/// Running totals of a trial's telemetry
+#[derive(Debug, Default)]
+struct Tally {
+    first_ts_ms: Option<i64>,
+    last_ts_ms: i64,
+    samples: u32,
+    temp_sum: f64,
+    temps: u32,
+    max_temp_c: Option<i32>,
+    /// Sum of the sound energy of every sample, silent ones adding nothing
+    energy: f64,
+}
+
+#[derive(Debug)]
+struct Trial {
+    points: Vec<[u32; 2]>,
+    started_ms: i64,
+    ends_ms: i64,
+    /// Expiry of the override this trial set; anything else in its place is someone else's
+    override_expires_ms: i64,
+    tally: Tally,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    #[serde(default)]
+    run: Option<Run>,
+    #[serde(default)]
+    proposal: Option<AutoTuneProposal>,
+    #[serde(default)]
+    history: VecDeque<AutoTuneEvent>,
+    #[serde(skip)]
+    trial: Option<Trial>,
+    /// Another fan override holds the trial back
+    #[serde(skip)]
+    waiting: bool,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// xorshift64; good enough to pick nudges, and its state persists with the run.
+fn next_random(state: &mut u64) -> f32 {
+    let mut x = *state;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    *state = x;
+    (x >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn bounds(base: u32, max_offset: u32) -> (u32, u32) {
+    (base.saturating_sub(max_offset), (base + max_offset).min(100))
+}
+
+/// `current` with one point's duty moved up or down by 1..=MAX_STEP_PCT, kept within
+/// `max_offset` of `baseline` and, when the baseline rises, rising too.
+fn neighbor(current: &[[u32; 2]], baseline: &[[u32; 2]], max_offset: u32, rng: &mut u64) -> Vec<[u32; 2]> {
+    let mut next = current.to_vec();
+    if next.is_empty() || next.len() != baseline.len() {
+        return next;
+    }
+    let i = ((next_random(rng) * next.len() as f32) as usize).min(next.len() - 1);
+    let step = 1 + ((next_random(rng) * MAX_STEP_PCT as f32) as u32).min(MAX_STEP_PCT - 1);
+    let (lo, hi) = bounds(baseline[i][1], max_offset);
+    next[i][1] = if next_random(rng) < 0.5 {
+        (next[i][1] + step).min(hi)
+    } else {
+        next[i][1].saturating_sub(step).max(lo)
+    };
+    if baseline.windows(2).all(|w| w[1][1] >= w[0][1]) {
+        for j in i + 1..next.len() {
+            next[j][1] = next[j][1].max(next[j - 1][1]);
+        }
+        for j in (0..i).rev() {
+            next[j][1] = next[j][1].min(next[j + 1][1]);
+        }
+    }
+    next
+}
+
+fn cost(settings: &FanAutoTuneConfig, avg_temp_c: f32, max_temp_c: i32, noise_dba: Option<f32>) -> f32 {
+    let w = settings.noise_weight.clamp(0.0, 1.0);
+    let overheat = (max_temp_c - settings.max_temp_c as i32).max(0) as f32;
+    w * noise_dba.unwrap_or(SILENT_DBA) + (1.0 - w) * avg_temp_c + OVERHEAT_PENALTY * overheat
+}
+
+impl Tally {
+    fn add(&mut self, s: &TelemetrySample, sensors: &[String], zones: &BTreeMap<String, Vec<String>>) {
+        self.first_ts_ms.get_or_insert(s.ts_ms);
+        self.last_ts_ms = s.ts_ms;
+        self.samples += 1;
+        if let Some(t) = crate::diagnostics::curve_compare::temperature(s, sensors, zones) {
+            self.temp_sum += t as f64;
+            self.temps += 1;
+            self.max_temp_c = Some(self.max_temp_c.map_or(t, |m| m.max(t)));
+        }
+        if let Some(db) = crate::diagnostics::curve_compare::noise_dba(&s.rpms) {
+            self.energy += 10f64.powf(db as f64 / 10.0);
+        }
+    }
+
+    /// The trial's scored event; `None` when too little of it was recorded.
+    fn score(&self, points: &[[u32; 2]], duration_ms: i64, settings: &FanAutoTuneConfig) -> Option<AutoTuneEvent> {
+        let covered = self.last_ts_ms - self.first_ts_ms?;
+        if self.temps == 0 || (covered as f32) < duration_ms as f32 * MIN_COVERAGE {
+            return None;
+        }
+        let round1 = |v: f32| (v * 10.0).round() / 10.0;
+        let avg_temp_c = (self.temp_sum / self.temps as f64) as f32;
+        let max_temp_c = self.max_temp_c?;
+        let noise_dba = (self.energy > 0.0).then(|| (10.0 * (self.energy / self.samples as f64).log10()) as f32);
+        Some(AutoTuneEvent {
+            ts_ms: now_ms(),
+            kind: AutoTuneEventKind::Trial,
+            points: points.to_vec(),
+            cost: Some(round1(cost(settings, avg_temp_c, max_temp_c, noise_dba))),
+            avg_temp_c: Some(round1(avg_temp_c)),
+            max_temp_c: Some(max_temp_c),
+            noise_dba: noise_dba.map(round1),
+            kept: None,
+        })
+    }
+}
+
+impl Run {
+    fn new(settings: FanAutoTuneConfig, baseline: Vec<[u32; 2]>) -> Self {
+        Self {
+            settings,
+            current: baseline.clone(),
+            current_cost: 0.0,
+            candidate: baseline.clone(),
+            baseline,
+            baseline_result: None,
+            best: None,
+            trials_done: 0,
+            temperature: START_TEMPERATURE,
+            rng: (now_ms() as u64) | 1,
+            finished: false,
+        }
+    }
+
+    /// Fold a finished trial in and pick the next candidate; returns the trial marked kept or not.
+    fn step(&mut self, mut trial: AutoTuneEvent) -> AutoTuneEvent {
+        let cost = trial.cost.unwrap_or(f32::MAX);
+        let kept = if self.trials_done == 0 {
+            true
+        } else {
+            let delta = cost - self.current_cost;
+            delta <= 0.0 || next_random(&mut self.rng) < (-delta / self.temperature.max(f32::EPSILON)).exp()
+        };
+        trial.kept = Some(kept);
+        if self.trials_done == 0 {
+            self.baseline_result = Some(trial.clone());
+        }
+        if kept {
+            self.current = self.candidate.clone();
+            self.current_cost = cost;
+        }
+        if self.best.as_ref().is_none_or(|b| cost < b.cost.unwrap_or(f32::MAX)) {
+            self.best = Some(trial.clone());
+        }
+        self.trials_done += 1;
+        let steps = self.settings.trials.saturating_sub(1).max(1) as f32;
+        self.temperature *= (END_TEMPERATURE / START_TEMPERATURE).powf(1.0 / steps);
+        self.candidate = neighbor(
+            &self.current,
+            &self.baseline,
+            self.settings.max_offset_pct,
+            &mut self.rng,
+        );
+        trial
+    }
+
+    /// The best curve, when it beat the baseline by enough to be worth proposing.
+    fn proposal(&self) -> Option<AutoTuneProposal> {
+        let (best, baseline) = (self.best.clone()?, self.baseline_result.clone()?);
+        let gain = baseline.cost? - best.cost?;
+        (gain >= MIN_GAIN && best.points != self.baseline).then(|| AutoTuneProposal {
+            created_ms: now_ms(),
+            points: best.points.clone(),
+            baseline_points: self.baseline.clone(),
+            best,
+            baseline,
+        })
+    }
+}
+
+fn state_path() -> PathBuf {
+    crate::config::config_path().with_file_name("auto_tune.json")
+}
+
+fn state() -> &'static Mutex<State> {
+    static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+    STATE.get_or_init(|| {
+        let loaded = std::fs::read_to_string(state_path())
+            .ok()
+            .and_then(|s| serde_json::from_str::<State>(&s).ok());
+        Mutex::new(loaded.unwrap_or_default())
+    })
+}
+
+fn save(st: &State) {
+    let path = state_path();
+    match serde_json::to_string(st) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("auto-tune: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("auto-tune: failed to serialize state: {}", e),
+    }
+}
+
+fn push_event(st: &mut State, event: AutoTuneEvent) {
+    st.history.push_back(event);
+    while st.history.len() > MAX_EVENTS {
+        st.history.pop_front();
+    }
+}
+
+fn event(kind: AutoTuneEventKind, points: &[[u32; 2]]) -> AutoTuneEvent {
+    AutoTuneEvent {
+        ts_ms: now_ms(),
+        kind,
+        points: points.to_vec(),
+        cost: None,
+        avg_temp_c: None,
+        max_temp_c: None,
+        noise_dba: None,
+        kept: None,
+    }
+}
+
+/// Settings of an enabled tuner with the saved curve to tune, in curve mode.
+fn tunable(fan: &FanControlConfig) -> Option<(FanAutoTuneConfig, Vec<[u32; 2]>)> {
+    let settings = fan.auto_tune.clone().filter(|t| t.enabled)?;
+    if fan.mode != Some(FanControlMode::Curve) {
+        return None;
+    }
+    let points = fan.curve.as_ref()?.curve.points.clone();
+    (!points.is_empty()).then_some((settings, points))
+}
+
+pub fn status(cfg: &Config) -> AutoTuneStatus {
+    let saved = crate::temporary::persisted(cfg);
+    let enabled = tunable(&saved.fan).is_some();
+    let Ok(st) = state().lock() else {
+        return AutoTuneStatus {
+            revision: cfg.revision,
+            phase: AutoTunePhase::Idle,
+            trials_done: 0,
+            trials: 0,
+            trial_points: None,
+            trial_ends_ms: None,
+            proposal: None,
+            history: Vec::new(),
+        };
+    };
+    let run = st.run.as_ref().filter(|_| enabled);
+    let phase = match run {
+        None => AutoTunePhase::Idle,
+        Some(r) if r.finished => AutoTunePhase::Finished,
+        Some(_) if st.waiting => AutoTunePhase::Waiting,
+        Some(_) => AutoTunePhase::Running,
+    };
+    let trial = st.trial.as_ref().filter(|_| phase == AutoTunePhase::Running);
+    AutoTuneStatus {
+        revision: cfg.revision,
+        phase,
+        trials_done: run.map(|r| r.trials_done).unwrap_or_default(),
+        trials: run.map(|r| r.settings.trials).unwrap_or_default(),
+        trial_points: trial.map(|t| t.points.clone()),
+        trial_ends_ms: trial.map(|t| t.ends_ms),
+        proposal: st.proposal.clone(),
+        history: st.history.iter().cloned().collect(),
+    }
+}
+
+pub fn proposal() -> Option<AutoTuneProposal> {
+    state().lock().ok()?.proposal.clone()
+}
+
+/// Record that the proposal was saved as `fan.curve`.
+pub fn accepted() {
+    let Ok(mut st) = state().lock() else { return };
+    if let Some(p) = st.proposal.take() {
+        push_event(&mut st, event(AutoTuneEventKind::Accepted, &p.points));
+        save(&st);
+    }
+}
+
+/// Drop the proposal; false when there was none.
+pub fn dismiss() -> bool {
+    let Ok(mut st) = state().lock() else { return false };
+    let Some(p) = st.proposal.take() else { return false };
+    push_event(&mut st, event(AutoTuneEventKind::Dismissed, &p.points));
+    save(&st);
+    true
+}
+
+/// Put `points` in place as a temporary override of the fan section, extending the tuner's own
+/// override (`ours`). `None` while someone else's fan override is in effect.
+async fn apply(cfg: &Arc<RwLock<Config>>, points: &[[u32; 2]], ttl_s: u64, ours: Option<i64>) -> Option<i64> {
+    let _writer = crate::config::WRITE_LOCK.lock().await;
+    if let Some(expires) = crate::temporary::expires_at_ms("fan") {
+        if Some(expires) != ours {
+            return None;
+        }
+    }
+    let running = cfg.read().await.clone();
+    let mut next = running.clone();
+    next.fan.curve.as_mut()?.curve.points = points.to_vec();
+    crate::temporary::begin(&running, &["fan"], ttl_s);
+    next.revision = running.revision + 1;
+    *cfg.write().await = next;
+    crate::tasks::fan_curve::request_reapply();
+    crate::temporary::expires_at_ms("fan")
+}
+
+/// Put the saved fan config back if the tuner's override is still the one in effect.
+async fn restore(cfg: &Arc<RwLock<Config>>, trial: Option<Trial>) {
+    if let Some(t) = trial {
+        if crate::temporary::expires_at_ms("fan") == Some(t.override_expires_ms) {
+            crate::temporary::revert_section(cfg, "fan").await;
+        }
+    }
+}
+
+/// One pass of the tuner; the state lock is never held across an await.
+async fn tick(cfg: &Arc<RwLock<Config>>, samples: &Arc<RwLock<VecDeque<TelemetrySample>>>) {
+    let (running, saved) = {
+        let running = cfg.read().await.clone();
+        let saved = crate::temporary::persisted(&running);
+        (running, saved)
+    };
+    let tunable = tunable(&saved.fan);
+    let ended_trial = {
+        let Ok(mut st) = state().lock() else { return };
+        let outdated = match (&tunable, &st.run) {
+            (None, _) => true,
+            (Some((settings, points)), Some(run)) => run.settings != *settings || run.baseline != *points,
+            (Some(_), None) => false,
+        };
+        if outdated {
+            if let Some(run) = st.run.take() {
+                if tunable.is_some() {
+                    info!("auto-tune: curve or settings changed, starting over");
+                    push_event(&mut st, event(AutoTuneEventKind::Restarted, &run.baseline));
+                    if let Some(p) = st.proposal.take() {
+                        push_event(&mut st, event(AutoTuneEventKind::Dismissed, &p.points));
+                    }
+                }
+                save(&st);
+            }
+            st.trial.take()
+        } else {
+            None
+        }
+    };
+    restore(cfg, ended_trial).await;
+    let Some((settings, baseline)) = tunable else { return };
+
+    let trial_ms = settings.trial_minutes as i64 * 60_000;
+    let ttl_s = settings.trial_minutes as u64 * 60 + OVERRIDE_MARGIN_SECS;
+    // Telemetry the trial in progress has not seen yet
+    let since = state()
+        .lock()
+        .ok()
+        .and_then(|st| st.trial.as_ref().map(|t| t.started_ms.max(t.tally.last_ts_ms + 1)));
+    let fresh: Vec<TelemetrySample> = match since {
+        Some(since) => samples
+            .read()
+            .await
+            .iter()
+            .filter(|s| s.ts_ms >= since)
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+    let sensors = saved
+        .fan
+        .curve
+        .as_ref()
+        .map(|c| c.curve.sensors.clone())
+        .unwrap_or_default();
+    let next = {
+        let Ok(mut st) = state().lock() else { return };
+        for s in &fresh {
+            if let Some(t) = st.trial.as_mut().filter(|t| s.ts_ms > t.tally.last_ts_ms) {
+                t.tally.add(s, &sensors, &running.sensors.zones);
+            }
+        }
+        advance(&mut st, settings, baseline)
+    };
+    match next {
+        Next::Idle => {}
+        Next::Finish => {
+            let trial = state().lock().ok().and_then(|mut st| st.trial.take());
+            restore(cfg, trial).await;
+        }
+        Next::Apply(points, ours) => {
+            let applied = apply(cfg, &points, ttl_s, ours).await;
+            let Ok(mut st) = state().lock() else { return };
+            st.waiting = applied.is_none();
+            st.trial = applied.map(|expires| {
+                let started_ms = now_ms();
+                Trial {
+                    points,
+                    started_ms,
+                    ends_ms: started_ms + trial_ms,
+                    override_expires_ms: expires,
+                    tally: Tally::default(),
+                }
+            });
+        }
+    }
+}
+
+enum Next {
+    Idle,
+    /// Run these points next, extending the tuner's override if it has one
+    Apply(Vec<[u32; 2]>, Option<i64>),
+    /// The run is over; put the saved curve back
+    Finish,
+}
+
+/// Score the trial in progress once it is over and decide what comes next.
+fn advance(st: &mut State, settings: FanAutoTuneConfig, baseline: Vec<[u32; 2]>) -> Next {
+    let mut run = st.run.take().unwrap_or_else(|| Run::new(settings, baseline));
+    let next = advance_run(st, &mut run);
+    st.run = Some(run);
+    next
+}
+
+fn advance_run(st: &mut State, run: &mut Run) -> Next {
+    if run.finished {
+        return Next::Finish;
+    }
+    let Some(trial) = st.trial.as_ref() else {
+        return Next::Apply(run.candidate.clone(), None);
+    };
+    if crate::temporary::expires_at_ms("fan") != Some(trial.override_expires_ms) {
+        info!("auto-tune: trial interrupted by another fan change, it will run again");
+        st.trial = None;
+        st.waiting = true;
+        return Next::Idle;
+    }
+    if now_ms() < trial.ends_ms {
+        return Next::Idle;
+    }
+    let ours = Some(trial.override_expires_ms);
+    let duration_ms = trial.ends_ms - trial.started_ms;
+    let Some(scored) = trial.tally.score(&trial.points, duration_ms, &run.settings) else {
+        info!("auto-tune: too little telemetry during the trial, running it again");
+        return Next::Apply(run.candidate.clone(), ours);
+    };
+    let ev = run.step(scored);
+    info!(
+        "auto-tune: trial {}/{} cost {:.1}{}",
+        run.trials_done,
+        run.settings.trials,
+        ev.cost.unwrap_or_default(),
+        if ev.kept == Some(true) { " (kept)" } else { "" }
+    );
+    push_event(st, ev);
+    if run.trials_done < run.settings.trials {
+        save(st);
+        return Next::Apply(run.candidate.clone(), ours);
+    }
+    run.finished = true;
+    let done = match run.proposal() {
+        Some(p) => {
+            info!(
+                "auto-tune: proposing a curve with cost {:.1} (started at {:.1})",
+                p.best.cost.unwrap_or_default(),
+                p.baseline.cost.unwrap_or_default()
+            );
+            let ev = AutoTuneEvent {
+                ts_ms: now_ms(),
+                kind: AutoTuneEventKind::Proposed,
+                kept: None,
+                ..p.best.clone()
+            };
+            st.proposal = Some(p);
+            ev
+        }
+        None => {
+            info!("auto-tune: no curve beat the one the run started from");
+            event(AutoTuneEventKind::Unchanged, &run.baseline)
+        }
+    };
+    push_event(st, done);
+    save(st);
+    Next::Finish
+}
+
+pub async fn run(cfg: Arc<RwLock<Config>>, samples: Arc<RwLock<VecDeque<TelemetrySample>>>) {
+    loop {
+        let started = std::time::Instant::now();
+        tick(&cfg, &samples).await;
+        crate::metrics::record_task_loop("fan_auto_tune", started.elapsed());
+        sleep(CHECK_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nudges_stay_in_bounds_and_annealing_keeps_improvements() {
+        let baseline = vec![[40, 20], [60, 40], [75, 70], [90, 100]];
+        let mut rng = 0x9e37_79b9_7f4a_7c15;
+        let mut current = baseline.clone();
+        for _ in 0..500 {
+            current = neighbor(&current, &baseline, 10, &mut rng);
+            for (p, b) in current.iter().zip(&baseline) {
+                assert_eq!(p[0], b[0]);
+                assert!(p[1] + 10 >= b[1] && p[1] <= (b[1] + 10).min(100));
+            }
+            assert!(current.windows(2).all(|w| w[1][1] >= w[0][1]));
+        }
+
+        let settings = FanAutoTuneConfig {
+            enabled: true,
+            trials: 3,
+            ..Default::default()
+        };
+        assert_eq!(cost(&settings, 60.0, 80, Some(30.0)), 45.0);
+        // Each degree above max_temp_c costs more than a degree on average
+        assert_eq!(cost(&settings, 60.0, 92, None), 50.0);
+
+        let trial = |run: &Run, c: f32| AutoTuneEvent {
+            cost: Some(c),
+            ..event(AutoTuneEventKind::Trial, &run.candidate)
+        };
+        let mut run = Run::new(settings, baseline.clone());
+        assert_eq!(run.step(trial(&run, 45.0)).kept, Some(true));
+        let better = run.candidate.clone();
+        assert_eq!(run.step(trial(&run, 40.0)).kept, Some(true));
+        assert_eq!(run.current, better);
+        assert_eq!(run.best.as_ref().and_then(|b| b.cost), Some(40.0));
+        assert!(run.proposal().is_some_and(|p| p.baseline_points == baseline));
+    }
+}
@@ -0,0 +1,250 @@
+// Fan hour-meter: the telemetry task feeds every sample's RPMs in and each fan accumulates run
+// time (any RPM) and duty-weighted run time, the better measure of wear and dust intake. The
+// duty is what the service applied to that fan; under EC control it is estimated from the RPM
+// against the calibrated (or highest seen) top speed. Totals persist in `fan_hours.json` next to
+// the config. With `fan.maintenance_hours` set, a fan whose duty-weighted hours since it was last
+// marked cleaned reach the threshold raises a `fan_maintenance:<index>` reminder.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::types::{FanControlConfig, FanHourMeter, FanStatsResponse};
+
+/// Longer gaps between samples (sleep, service stopped) are not counted as run time.
+const MAX_SAMPLE_GAP_MS: i64 = 60_000;
+/// Persist at most this often; a crash loses at most this much run time.
+const SAVE_INTERVAL_MS: i64 = 5 * 60_000;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FanMeter {
+    #[serde(default)]
+    run_ms: i64,
+    /// Run time scaled by duty (0..1)
+    #[serde(default)]
+    weighted_ms: f64,
+    /// `weighted_ms` when the fan was last marked cleaned
+    #[serde(default)]
+    serviced_weighted_ms: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    serviced_at_ms: Option<i64>,
+    /// Highest RPM seen, the full-speed reference without a calibration
+    #[serde(default)]
+    max_rpm: u32,
+}
+
+impl FanMeter {
+    fn hours_since_service(&self) -> f64 {
+        (self.weighted_ms - self.serviced_weighted_ms) / MS_PER_HOUR
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Meters {
+    #[serde(default)]
+    fans: BTreeMap<u32, FanMeter>,
+    #[serde(skip)]
+    last_ts_ms: Option<i64>,
+    #[serde(skip)]
+    last_saved_ms: i64,
+}
+
+impl Meters {
+    /// `duties[i]` is the duty applied to fan `i` when the service is in control, `max_rpms[i]` its
+    /// calibrated top speed.
+    fn record(&mut self, ts_ms: i64, rpms: &[u32], duties: &[Option<u32>], max_rpms: &[Option<u32>]) {
+        let dt = self
+            .last_ts_ms
+            .map(|last| ts_ms - last)
+            .filter(|dt| (0..=MAX_SAMPLE_GAP_MS).contains(dt))
+            .unwrap_or(0);
+        self.last_ts_ms = Some(ts_ms);
+
+        for (i, &rpm) in rpms.iter().enumerate() {
+            let m = self.fans.entry(i as u32).or_default();
+            m.max_rpm = m.max_rpm.max(rpm);
+            if rpm == 0 || dt == 0 {
+                continue;
+            }
+            let duty = match duties.get(i).copied().flatten() {
+                Some(d) => d.min(100) as f64 / 100.0,
+                None => {
+                    let full = max_rpms
+                        .get(i)
+                        .copied()
+                        .flatten()
+                        .filter(|r| *r > 0)
+                        .unwrap_or(m.max_rpm);
+                    (rpm as f64 / full as f64).min(1.0)
+                }
+            };
+            m.run_ms += dt;
+            m.weighted_ms += dt as f64 * duty;
+        }
+    }
+}
+
+fn state_path() -> PathBuf {
+    crate::config::config_path().with_file_name("fan_hours.json")
+}
+
+fn current() -> &'static Mutex<Meters> {
+    static CURRENT: OnceLock<Mutex<Meters>> = OnceLock::new();
+    CURRENT.get_or_init(|| {
+        let loaded = std::fs::read_to_string(state_path())
+            .ok()
+            .and_then(|s| serde_json::from_str::<Meters>(&s).ok());
+        Mutex::new(loaded.unwrap_or_default())
+    })
+}
+
+fn alert_id(fan_index: u32) -> String {
+    format!("fan_maintenance:{}", fan_index)
+}
+
+/// Fold one telemetry sample's fan RPMs in, then raise or clear the maintenance reminders.
+pub fn record_sample(ts_ms: i64, rpms: &[u32], fan: &FanControlConfig) {
+    let duties: Vec<Option<u32>> = (0..rpms.len() as u32)
+        .map(crate::tasks::fan_restore::applied_duty)
+        .collect();
+    let max_rpms: Vec<Option<u32>> = (0..rpms.len() as u32)
+        .map(|i| fan.calibration_for(i).and_then(|c| c.points.iter().map(|p| p[1]).max()))
+        .collect();
+    let Ok(mut meters) = current().lock() else { return };
+    meters.record(ts_ms, rpms, &duties, &max_rpms);
+
+    for (&i, m) in &meters.fans {
+        let hours = m.hours_since_service();
+        match fan.maintenance_hours {
+            Some(limit) if hours >= limit as f64 => crate::alerts::raise(
+                &alert_id(i),
+                crate::types::AlertSeverity::Info,
+                format!(
+                    "Fan {} has run {:.0} duty-weighted hours since it was last cleaned; check it for dust",
+                    i, hours
+                ),
+            ),
+            _ => crate::alerts::clear(&alert_id(i)),
+        }
+    }
+
+    if ts_ms - meters.last_saved_ms >= SAVE_INTERVAL_MS {
+        meters.last_saved_ms = ts_ms;
+        save(&meters);
+    }
+}
+
+/// Mark a fan (all fans when `None`) as cleaned: restarts its hours-since-service count.
+pub fn mark_serviced(fan_index: Option<u32>) {
+    let now_ms = unix_time_ms();
+    let Ok(mut meters) = current().lock() else { return };
+    for (&i, m) in meters.fans.iter_mut() {
+        if fan_index.is_none_or(|f| f == i) {
+            m.serviced_weighted_ms = m.weighted_ms;
+            m.serviced_at_ms = Some(now_ms);
+            crate::alerts::clear(&alert_id(i));
+        }
+    }
+    info!(
+        "fan hours: marked {} as cleaned",
+        fan_index.map_or("all fans".to_string(), |i| format!("fan {}", i))
+    );
+    save(&meters);
+}
+
+/// The meters as kept in `fan_hours.json`, for backups.
+pub fn export() -> Option<serde_json::Value> {
+    let meters = current().lock().ok()?;
+    serde_json::to_value(&*meters).ok()
+}
+
+/// Replace the meters with ones from `export`; reminders follow on the next sample.
+pub fn restore(value: serde_json::Value) -> Result<(), String> {
+    let restored: Meters = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    let Ok(mut meters) = current().lock() else {
+        return Err("fan hours are unavailable".to_string());
+    };
+    meters.fans = restored.fans;
+    save(&meters);
+    Ok(())
+}
+
+pub fn snapshot(maintenance_hours: Option<u32>) -> FanStatsResponse {
+    let Ok(meters) = current().lock() else {
+        return FanStatsResponse {
+            fans: Vec::new(),
+            maintenance_hours,
+        };
+    };
+    let fans = meters
+        .fans
+        .iter()
+        .map(|(&i, m)| {
+            let since = m.hours_since_service();
+            let (baseline_bands, anomaly) = crate::fan_anomaly::status(i);
+            FanHourMeter {
+                fan_index: i,
+                run_hours: round2(m.run_ms as f64 / MS_PER_HOUR),
+                duty_weighted_hours: round2(m.weighted_ms / MS_PER_HOUR),
+                hours_since_service: round2(since),
+                serviced_at_ms: m.serviced_at_ms,
+                maintenance_due: maintenance_hours.is_some_and(|h| since >= h as f64),
+                baseline_bands,
+                anomaly,
+            }
+        })
+        .collect();
+    FanStatsResponse {
+        fans,
+        maintenance_hours,
+    }
+}
+
+fn unix_time_ms() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn round2(v: f64) -> f32 {
+    ((v * 100.0).round() / 100.0) as f32
+}
+
+fn save(meters: &Meters) {
+    let path = state_path();
+    match serde_json::to_string(meters) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("fan hours: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("fan hours: failed to serialize fan hours: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_run_time_by_duty() {
+        let mut m = Meters::default();
+        // Fan 0 driven at 50% by the service, fan 1 under EC control at half its 4000 RPM top
+        // speed, then a long gap that must not count.
+        m.record(0, &[3000, 4000], &[Some(50), None], &[]);
+        m.record(10_000, &[3000, 2000], &[Some(50), None], &[]);
+        m.record(20_000, &[0, 2000], &[Some(0), None], &[]);
+        m.record(200_000, &[3000, 2000], &[Some(50), None], &[]);
+        assert_eq!((m.fans[&0].run_ms, m.fans[&0].weighted_ms), (10_000, 5_000.0));
+        assert_eq!((m.fans[&1].run_ms, m.fans[&1].weighted_ms), (20_000, 10_000.0));
+        // The fan's calibrated top speed takes precedence over the highest RPM seen
+        m.record(210_000, &[0, 2000], &[None, None], &[Some(3000), Some(8000)]);
+        assert_eq!(m.fans[&1].weighted_ms, 12_500.0);
+    }
+}
@@ -0,0 +1,85 @@
+// Named fan profiles: `fan.profiles` maps a name ("silent", "gaming", ...) to a full global curve.
+// Activating one copies its curve into `fan.curve`, switches to curve mode and records the name
+// in `fan.active_profile`. That is an ordinary fan config change, so the fan task follows the new
+// curve on its next tick. Editing `fan.curve` directly leaves no profile active. Managed
+// profiles (`managed_profiles.rs`) are looked up alongside `fan.profiles` and win on a name clash.
+
+use crate::types::{FanControlConfig, FanControlMode, FanProfiles, GlobalCurveConfig};
+
+/// Curve of the profile `name`: a managed profile, else `fan.profiles`.
+pub fn get(fan: &FanControlConfig, name: &str) -> Option<GlobalCurveConfig> {
+    crate::managed_profiles::get(name).or_else(|| fan.profiles.as_ref()?.get(name).cloned())
+}
+
+pub fn exists(fan: &FanControlConfig, name: &str) -> bool {
+    get(fan, name).is_some()
+}
+
+/// Switch `fan` to the profile `name`. An unknown name is only recorded, for validation to
+/// reject.
+pub fn activate(fan: &mut FanControlConfig, name: &str) {
+    if let Some(curve) = get(fan, name) {
+        fan.curve = Some(curve);
+        fan.mode = Some(FanControlMode::Curve);
+    }
+    fan.active_profile = Some(name.to_string());
+}
+
+/// Drop `active_profile` once `fan.curve` no longer matches that profile (edited, or the profile
+/// was changed or removed).
+pub fn reconcile(fan: &mut FanControlConfig) {
+    let Some(name) = fan.active_profile.as_ref() else {
+        return;
+    };
+    let same = |a, b| serde_json::to_value(a).ok() == serde_json::to_value(b).ok();
+    let matches = match (get(fan, name), fan.curve.as_ref()) {
+        (Some(profile), Some(curve)) => same(&profile, curve),
+        _ => false,
+    };
+    if !matches {
+        fan.active_profile = None;
+    }
+}
+
+pub fn list(fan: &FanControlConfig, revision: u64) -> FanProfiles {
+    FanProfiles {
+        active_profile: fan.active_profile.clone(),
+        scheduled_profile: crate::tasks::fan_schedule::current(fan),
+        profiles: {
+            let mut names: Vec<String> = fan.profiles.iter().flatten().map(|(name, _)| name.clone()).collect();
+            names.extend(crate::managed_profiles::names());
+            names.sort();
+            names.dedup();
+            names
+        },
+        managed: crate::managed_profiles::names(),
+        wake_times: crate::wake::armed(),
+        revision,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GlobalCurveConfig;
+
+    #[test]
+    fn activation_sticks_until_the_curve_is_edited() {
+        let quiet = GlobalCurveConfig {
+            poll_ms: 2000,
+            ..Default::default()
+        };
+        let mut fan = FanControlConfig {
+            profiles: Some([("quiet".to_string(), quiet)].into()),
+            ..Default::default()
+        };
+        activate(&mut fan, "quiet");
+        assert_eq!(fan.mode, Some(FanControlMode::Curve));
+        reconcile(&mut fan);
+        assert_eq!(fan.active_profile.as_deref(), Some("quiet"));
+
+        fan.curve.as_mut().unwrap().poll_ms = 500;
+        reconcile(&mut fan);
+        assert_eq!(fan.active_profile, None);
+    }
+}
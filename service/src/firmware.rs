@@ -0,0 +1,179 @@
+// Firmware update awareness: compares the BIOS/EC versions from `framework_tool --versions`
+// against a release feed and reports newer releases. Nothing is ever flashed; updating stays with
+// Framework's own tooling (EFI shell / LVFS / Windows installer).
+//
+// Framework publishes BIOS releases as knowledge-base pages and LVFS cabinets, not as a
+// machine-readable feed, so the feed is a URL the user configures (`updates.firmware_feed_url`
+// or `FRAMEWORK_CONTROL_FIRMWARE_FEED`) serving `{"releases": [FirmwareRelease, ...]}`.
+
+use std::cmp::Ordering;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+
+use crate::cli::FrameworkTool;
+use crate::types::{AlertSeverity, FirmwareComponent, FirmwareRelease, FirmwareUpdates, UpdatesConfig};
+
+static LAST: Mutex<Option<FirmwareUpdates>> = Mutex::new(None);
+
+#[derive(Deserialize)]
+struct Feed {
+    releases: Vec<FirmwareRelease>,
+}
+
+pub fn feed_url(cfg: &UpdatesConfig) -> Option<String> {
+    cfg.firmware_feed_url
+        .clone()
+        .or_else(|| std::env::var("FRAMEWORK_CONTROL_FIRMWARE_FEED").ok())
+        .or_else(|| option_env!("FRAMEWORK_CONTROL_FIRMWARE_FEED").map(String::from))
+        .filter(|u| !u.is_empty())
+}
+
+/// Numeric groups of a version string: "03.05" -> [3, 5], EC build
+/// "lilac-3.0.3-413f018 2024-03-19" -> [3, 0, 3] (the first dotted run only, so hashes and dates
+/// do not count).
+fn version_key(v: &str) -> Vec<u64> {
+    v.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .find(|run| !run.is_empty())
+        .unwrap_or_default()
+        .split('.')
+        .filter_map(|n| n.parse().ok())
+        .collect()
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    version_key(a).cmp(&version_key(b))
+}
+
+/// Newest release per component for `mainboard` that is newer than the installed version.
+fn newer_releases(
+    releases: Vec<FirmwareRelease>,
+    mainboard: &str,
+    installed: impl Fn(FirmwareComponent) -> Option<String>,
+) -> Vec<FirmwareRelease> {
+    let mut out: Vec<FirmwareRelease> = Vec::new();
+    for r in releases {
+        if !r.mainboard.eq_ignore_ascii_case(mainboard.trim()) {
+            continue;
+        }
+        let Some(current) = installed(r.component) else {
+            continue;
+        };
+        if compare_versions(&r.version, &current) != Ordering::Greater {
+            continue;
+        }
+        match out.iter_mut().find(|o| o.component == r.component) {
+            Some(o) if compare_versions(&r.version, &o.version) == Ordering::Greater => *o = r,
+            Some(_) => {}
+            None => out.push(r),
+        }
+    }
+    out
+}
+
+async fn fetch(url: &str) -> Result<Feed, String> {
+    let resp = reqwest::Client::new()
+        .get(url)
+        .header("user-agent", "framework-control-service")
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("feed returned {}", resp.status()));
+    }
+    let text = resp.text().await.map_err(|e| e.to_string())?;
+    serde_json::from_str(&text).map_err(|e| format!("invalid feed: {}", e))
+}
+
+/// Raise the `firmware_update` info alert while newer releases are listed, clear it otherwise.
+fn raise_alert(updates: &[FirmwareRelease]) {
+    if updates.is_empty() {
+        crate::alerts::clear("firmware_update");
+        return;
+    }
+    let list: Vec<String> = updates
+        .iter()
+        .map(|r| match r.component {
+            FirmwareComponent::Bios => format!("BIOS {}", r.version),
+            FirmwareComponent::Ec => format!("EC {}", r.version),
+        })
+        .collect();
+    crate::alerts::raise(
+        "firmware_update",
+        AlertSeverity::Info,
+        format!("Firmware update available: {}", list.join(", ")),
+    );
+}
+
+/// Check the feed now, update the `firmware_update` alert and keep the result for `last`.
+pub async fn check(cli: Option<&FrameworkTool>, cfg: &UpdatesConfig) -> FirmwareUpdates {
+    let mut out = FirmwareUpdates {
+        checked_at_ms: Some(chrono::Utc::now().timestamp_millis()),
+        ..Default::default()
+    };
+    let result: Result<(), String> = async {
+        let cli = cli.ok_or("framework_tool not found")?;
+        let versions = cli.versions().await?;
+        out.mainboard = versions.mainboard_type.clone();
+        out.bios_version = versions.uefi_version.clone();
+        out.ec_version = versions.ec_build_version.clone();
+        let url = feed_url(cfg).ok_or("no firmware feed configured (updates.firmware_feed_url)")?;
+        let mainboard = out
+            .mainboard
+            .clone()
+            .ok_or("mainboard type not reported by --versions")?;
+        out.updates = newer_releases(fetch(&url).await?.releases, &mainboard, |c| match c {
+            FirmwareComponent::Bios => out.bios_version.clone(),
+            FirmwareComponent::Ec => out.ec_version.clone(),
+        });
+        Ok(())
+    }
+    .await;
+    out.error = result.err();
+    if out.error.is_none() {
+        raise_alert(&out.updates);
+    }
+    if let Ok(mut last) = LAST.lock() {
+        *last = Some(out.clone());
+    }
+    out
+}
+
+/// Result of the most recent check, if any has run.
+pub fn last() -> Option<FirmwareUpdates> {
+    LAST.lock().ok().and_then(|l| l.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(component: FirmwareComponent, version: &str) -> FirmwareRelease {
+        FirmwareRelease {
+            mainboard: "Laptop 13 (AMD Ryzen 7040Series)".into(),
+            component,
+            version: version.into(),
+            released: None,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn reports_newest_newer_release_per_component() {
+        assert_eq!(version_key("03.05"), [3, 5]);
+        assert_eq!(version_key("lilac-3.0.3-413f018 2024-03-19"), [3, 0, 3]);
+        let releases = vec![
+            release(FirmwareComponent::Bios, "3.03"),
+            release(FirmwareComponent::Bios, "3.07"),
+            release(FirmwareComponent::Bios, "3.06"),
+            release(FirmwareComponent::Ec, "3.0.3"),
+        ];
+        let updates = newer_releases(releases, "laptop 13 (amd ryzen 7040series)", |c| match c {
+            FirmwareComponent::Bios => Some("03.05".into()),
+            FirmwareComponent::Ec => Some("lilac-3.0.3-413f018 2024-03-19".into()),
+        });
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].version, "3.07");
+    }
+}
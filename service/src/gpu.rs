@@ -0,0 +1,94 @@
+// Graphics adapter enumeration for `/system`.
+//
+// Windows enumerates adapters through DXGI in-process (no PowerShell/WMI spawn) and classifies
+// them by PCI vendor/device ID rather than by marketing name: NVIDIA is always discrete, Intel
+// only for the Arc (DG2/Battlemage) ranges, AMD unless the device ID is a known APU graphics core.
+
+use crate::types::GpuInfo;
+
+#[cfg(any(target_os = "windows", test))]
+const VENDOR_AMD: u32 = 0x1002;
+#[cfg(any(target_os = "windows", test))]
+const VENDOR_NVIDIA: u32 = 0x10DE;
+#[cfg(any(target_os = "windows", test))]
+const VENDOR_INTEL: u32 = 0x8086;
+
+/// AMD APU graphics (Renoir/Cezanne/Barcelo, Rembrandt, Phoenix, Hawk Point, Strix, Strix Halo)
+#[cfg(any(target_os = "windows", test))]
+const AMD_APU_DEVICE_IDS: &[u32] = &[
+    0x1636, 0x1638, 0x15E7, 0x164C, 0x1681, 0x15BF, 0x15C8, 0x1900, 0x1901, 0x150E, 0x1114, 0x1586,
+];
+
+#[cfg(any(target_os = "windows", test))]
+pub fn is_discrete(vendor_id: u32, device_id: u32) -> bool {
+    match vendor_id {
+        VENDOR_NVIDIA => true,
+        VENDOR_INTEL => matches!(device_id, 0x5690..=0x56FF | 0xE200..=0xE2FF),
+        VENDOR_AMD => !AMD_APU_DEVICE_IDS.contains(&device_id),
+        _ => false,
+    }
+}
+
+/// Hardware graphics adapters (software/basic render adapters excluded).
+pub async fn adapters() -> Vec<GpuInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        match tokio::task::spawn_blocking(enumerate_dxgi).await {
+            Ok(Ok(gpus)) => return gpus,
+            Ok(Err(e)) => tracing::warn!("gpu: DXGI enumeration failed: {}", e),
+            Err(e) => tracing::warn!("gpu: DXGI enumeration task failed: {}", e),
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+fn enumerate_dxgi() -> Result<Vec<GpuInfo>, String> {
+    use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIFactory1, DXGI_ADAPTER_FLAG_SOFTWARE};
+
+    // Plain DXGI factory/adapter calls; descriptors are copied out by value
+    let factory: IDXGIFactory1 = unsafe { CreateDXGIFactory1() }.map_err(|e| e.to_string())?;
+    let mut gpus = Vec::new();
+    let mut index = 0;
+    // EnumAdapters1 returns DXGI_ERROR_NOT_FOUND past the last adapter
+    while let Ok(adapter) = unsafe { factory.EnumAdapters1(index) } {
+        index += 1;
+        let Ok(desc) = (unsafe { adapter.GetDesc1() }) else {
+            continue;
+        };
+        if desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32 != 0 {
+            continue;
+        }
+        let len = desc
+            .Description
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(desc.Description.len());
+        let name = String::from_utf16_lossy(&desc.Description[..len]).trim().to_string();
+        gpus.push(GpuInfo {
+            discrete: is_discrete(desc.VendorId, desc.DeviceId),
+            name,
+            vendor_id: desc.VendorId,
+            device_id: desc.DeviceId,
+        });
+    }
+    Ok(gpus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_framework_gpus_by_pci_id() {
+        // Radeon 780M (FW13/FW16 iGPU) vs RX 7700S (FW16 module)
+        assert!(!is_discrete(VENDOR_AMD, 0x15BF));
+        assert!(is_discrete(VENDOR_AMD, 0x7480));
+        // Iris Xe vs Arc A370M
+        assert!(!is_discrete(VENDOR_INTEL, 0x9A49));
+        assert!(is_discrete(VENDOR_INTEL, 0x5693));
+        assert!(is_discrete(VENDOR_NVIDIA, 0x2F58));
+        // Microsoft Basic Render Driver
+        assert!(!is_discrete(0x1414, 0x008C));
+    }
+}
@@ -0,0 +1,169 @@
+// Remediation hints attached to API errors, section availability and selftest results. Each hint
+// has a stable `key` (the UI's translation lookup) with `params` to interpolate, plus the English
+// `message` as fallback, so the UI can show an actionable fix rather than only an error code.
+
+use std::collections::BTreeMap;
+
+use crate::types::RemediationHint;
+
+fn hint(key: &str, message: impl Into<String>) -> RemediationHint {
+    RemediationHint {
+        key: key.to_string(),
+        message: message.into(),
+        params: BTreeMap::new(),
+    }
+}
+
+impl RemediationHint {
+    fn with(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+pub fn privilege() -> RemediationHint {
+    #[cfg(target_os = "windows")]
+    let message = "Run the Framework Control service as Administrator (the installed service does this by default)";
+    #[cfg(not(target_os = "windows"))]
+    let message = "Run the service as root (the packaged systemd unit does this by default)";
+    hint("run_elevated", message)
+}
+
+pub fn install_framework_tool() -> RemediationHint {
+    hint(
+        "install_framework_tool",
+        "Install framework_tool (winget install FrameworkComputer.framework_tool on Windows, or place it on PATH on Linux) and restart the service",
+    )
+}
+
+pub fn update_ec_firmware() -> RemediationHint {
+    hint(
+        "update_ec_firmware",
+        "Update framework_tool and the BIOS; older EC firmware may not report thermal data",
+    )
+}
+
+pub fn restore_fan_control() -> RemediationHint {
+    hint(
+        "restore_fan_control",
+        "Restart the service or run `framework_tool --autofanctrl` to hand the fans back to the EC",
+    )
+}
+
+#[cfg(target_os = "windows")]
+pub fn update_ryzenadj() -> RemediationHint {
+    hint(
+        "update_ryzenadj",
+        "This CPU may be unsupported by the installed RyzenAdj; try updating it from the Power panel",
+    )
+}
+
+pub fn ryzenadj_driver_blocked() -> RemediationHint {
+    hint(
+        "ryzenadj_driver_blocked",
+        "Run as Administrator; if it still fails, Memory Integrity (Core Isolation) may be blocking the RyzenAdj driver",
+    )
+}
+
+pub fn epp_read_only() -> RemediationHint {
+    hint(
+        "epp_read_only",
+        "EPP is read-only under the 'performance' governor and requires amd_pstate in active mode (kernel parameter amd_pstate=active); otherwise run as root",
+    )
+}
+
+pub fn rapl_locked(path: &str) -> RemediationHint {
+    hint(
+        "rapl_locked",
+        format!(
+            "RAPL limits are often locked by firmware on AMD; as root, a permission error on {} means the BIOS does not allow OS power limits",
+            path
+        ),
+    )
+    .with("path", path)
+}
+
+pub fn enable_power_backend() -> RemediationHint {
+    hint(
+        "enable_power_backend",
+        "Set the power backend to Auto in the Power panel to manage TDP, EPP and frequency limits",
+    )
+}
+
+pub fn install_power_backend() -> RemediationHint {
+    #[cfg(target_os = "windows")]
+    let (key, message) = (
+        "install_ryzenadj",
+        "Install RyzenAdj from the Power panel to manage TDP and thermal limits",
+    );
+    #[cfg(not(target_os = "windows"))]
+    let (key, message) = (
+        "enable_cpufreq",
+        "No cpufreq or AMD P-State interface is exposed; boot with the kernel parameter amd_pstate=active (kernel 6.3+) and check that cpufreq is not disabled",
+    );
+    hint(key, message)
+}
+
+pub fn confirm_operation() -> RemediationHint {
+    hint(
+        "confirm_operation",
+        "Show the warning to the user and repeat the request with confirm=true once they agree",
+    )
+}
+
+/// Hint for an error `code` and its (usually CLI or sysfs) `message`, when there is a known fix.
+pub fn for_error(code: &str, message: &str) -> Option<RemediationHint> {
+    match code {
+        "cli_unavailable" => return Some(install_framework_tool()),
+        "backend_disabled" => return Some(enable_power_backend()),
+        "backend_unavailable" => return Some(install_power_backend()),
+        "ryzenadj_unavailable" => return Some(ryzenadj_driver_blocked()),
+        _ => {}
+    }
+    let lower = message.to_lowercase();
+    let denied = ["permission denied", "access is denied", "os error 13", "os error 5)"]
+        .iter()
+        .any(|s| lower.contains(s));
+    if lower.contains("intel-rapl") {
+        let path = message
+            .split(|c: char| c == '"' || c.is_whitespace())
+            .find(|s| s.contains("intel-rapl"))
+            .map(|s| s.trim_end_matches(':'))
+            .unwrap_or_default();
+        Some(rapl_locked(path))
+    } else if lower.contains("energy_performance_preference") || lower.contains("failed to set epp") {
+        Some(epp_read_only())
+    } else if denied {
+        Some(privilege())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_errors() {
+        assert_eq!(
+            for_error("cli_unavailable", "framework_tool not found").unwrap().key,
+            "install_framework_tool"
+        );
+        let rapl = for_error(
+            "cli_failed",
+            "Failed to write to /sys/class/powercap/intel-rapl:0/constraint_0_power_limit_uw: Permission denied (os error 13)",
+        )
+        .unwrap();
+        assert_eq!(rapl.key, "rapl_locked");
+        assert_eq!(
+            rapl.params["path"],
+            "/sys/class/powercap/intel-rapl:0/constraint_0_power_limit_uw"
+        );
+        assert_eq!(
+            for_error("cli_failed", "EC access: Permission denied").unwrap().key,
+            "run_elevated"
+        );
+        assert!(for_error("cli_failed", "unexpected output").is_none());
+    }
+}
@@ -0,0 +1,394 @@
+// Optional forwarding of service log events to the platform log: journald or local syslog on
+// Linux, the Event Log on Windows, or a remote syslog collector over UDP on either, so fleet
+// tooling can collect service errors. Runs as a tracing layer next to the console output with
+// its own level (`logging.forward_level`, independent of RUST_LOG); settings apply live.
+
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+use tracing::field::{Field, Visit};
+use tracing::subscriber::Interest;
+use tracing::{info, warn, Event, Level, Metadata, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Filter, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::types::{LogForwardTarget, LogLevel, LoggingConfig};
+
+const IDENTIFIER: &str = "framework-control";
+#[cfg(target_os = "linux")]
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+#[cfg(unix)]
+const SYSLOG_SOCKET: &str = "/dev/log";
+/// syslog facility "daemon"
+const FACILITY_DAEMON: u8 = 3;
+/// Dependencies (poem, hyper, ...) are only forwarded from this rank (warn) up
+const DEPENDENCY_MAX_RANK: u8 = 2;
+
+/// Most verbose rank forwarded (1 error .. 4 debug); 0 while forwarding is off
+static MAX_RANK: AtomicU8 = AtomicU8::new(0);
+static SINK: RwLock<Option<Sink>> = RwLock::new(None);
+
+fn rank(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 1,
+        Level::WARN => 2,
+        Level::INFO => 3,
+        Level::DEBUG => 4,
+        _ => 5,
+    }
+}
+
+fn level_rank(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Error => 1,
+        LogLevel::Warn => 2,
+        LogLevel::Info => 3,
+        LogLevel::Debug => 4,
+    }
+}
+
+/// syslog severity (also journald's PRIORITY)
+fn severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        _ => 7,
+    }
+}
+
+/// (Re)open the sink for `cfg`. On failure forwarding stays off and the error is logged.
+pub fn apply(cfg: &LoggingConfig) {
+    let sink = if cfg.forward {
+        match Sink::open(cfg) {
+            Ok(s) => Some(s),
+            Err(e) => {
+                disable();
+                warn!("log forwarding disabled: {}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    let description = sink.as_ref().map(Sink::describe);
+    if let Ok(mut w) = SINK.write() {
+        *w = sink;
+    }
+    match description {
+        Some(d) => {
+            let level = cfg.forward_level.unwrap_or_default();
+            MAX_RANK.store(level_rank(level), Ordering::Relaxed);
+            info!("forwarding {:?} and above logs to {}", level, d);
+        }
+        None => MAX_RANK.store(0, Ordering::Relaxed),
+    }
+}
+
+fn disable() {
+    MAX_RANK.store(0, Ordering::Relaxed);
+    if let Ok(mut w) = SINK.write() {
+        *w = None;
+    }
+}
+
+/// Whether `target` can be opened on this platform; `syslog` needs an address without `/dev/log`.
+pub fn target_available(target: LogForwardTarget, has_address: bool) -> bool {
+    match target {
+        LogForwardTarget::Auto => true,
+        LogForwardTarget::Journald => cfg!(target_os = "linux"),
+        LogForwardTarget::Syslog => cfg!(unix) || has_address,
+        LogForwardTarget::EventLog => cfg!(target_os = "windows"),
+    }
+}
+
+enum Sink {
+    #[cfg(target_os = "linux")]
+    Journald(std::os::unix::net::UnixDatagram),
+    #[cfg(unix)]
+    Syslog(std::os::unix::net::UnixDatagram),
+    Remote {
+        socket: UdpSocket,
+        address: String,
+    },
+    #[cfg(target_os = "windows")]
+    EventLog(event_log::Source),
+}
+
+impl Sink {
+    fn open(cfg: &LoggingConfig) -> Result<Self, String> {
+        let target = cfg.forward_target.unwrap_or_default();
+        if target == LogForwardTarget::Syslog {
+            if let Some(address) = cfg.syslog_address.as_deref() {
+                let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| format!("bind UDP socket: {e}"))?;
+                socket
+                    .connect(address)
+                    .map_err(|e| format!("connect to syslog collector {address}: {e}"))?;
+                return Ok(Sink::Remote {
+                    socket,
+                    address: address.to_string(),
+                });
+            }
+        }
+        Self::open_local(target)
+    }
+
+    #[cfg(unix)]
+    fn open_local(target: LogForwardTarget) -> Result<Self, String> {
+        use std::os::unix::net::UnixDatagram;
+        let connect = |path: &str| {
+            let socket = UnixDatagram::unbound().map_err(|e| format!("create socket: {e}"))?;
+            socket.connect(path).map_err(|e| format!("connect to {path}: {e}"))?;
+            Ok::<_, String>(socket)
+        };
+        match target {
+            #[cfg(target_os = "linux")]
+            LogForwardTarget::Journald => connect(JOURNALD_SOCKET).map(Sink::Journald),
+            #[cfg(target_os = "linux")]
+            LogForwardTarget::Auto if std::path::Path::new(JOURNALD_SOCKET).exists() => {
+                connect(JOURNALD_SOCKET).map(Sink::Journald)
+            }
+            LogForwardTarget::Auto | LogForwardTarget::Syslog => connect(SYSLOG_SOCKET).map(Sink::Syslog),
+            other => Err(format!("{:?} is not available on this platform", other)),
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn open_local(target: LogForwardTarget) -> Result<Self, String> {
+        match target {
+            LogForwardTarget::Auto | LogForwardTarget::EventLog => {
+                event_log::Source::register("FrameworkControl").map(Sink::EventLog)
+            }
+            other => Err(format!("{:?} needs syslog_address on Windows", other)),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            #[cfg(target_os = "linux")]
+            Sink::Journald(_) => "journald".into(),
+            #[cfg(unix)]
+            Sink::Syslog(_) => SYSLOG_SOCKET.into(),
+            Sink::Remote { address, .. } => format!("syslog collector {}", address),
+            #[cfg(target_os = "windows")]
+            Sink::EventLog(_) => "the Windows Event Log".into(),
+        }
+    }
+
+    fn send(&self, level: &Level, message: &str) {
+        let pri = FACILITY_DAEMON * 8 + severity(level);
+        let _ = match self {
+            #[cfg(target_os = "linux")]
+            Sink::Journald(socket) => socket.send(&journald_datagram(severity(level), message)),
+            #[cfg(unix)]
+            Sink::Syslog(socket) => {
+                socket.send(format!("<{}>{}[{}]: {}", pri, IDENTIFIER, std::process::id(), message).as_bytes())
+            }
+            Sink::Remote { socket, .. } => socket.send(rfc5424(pri, message).as_bytes()),
+            #[cfg(target_os = "windows")]
+            Sink::EventLog(source) => source.report(level, message),
+        };
+    }
+}
+
+/// journald native protocol: `KEY=value` lines, with the length-prefixed form for values that
+/// contain newlines.
+#[cfg(any(target_os = "linux", test))]
+fn journald_datagram(priority: u8, message: &str) -> Vec<u8> {
+    let mut out = format!("PRIORITY={}\nSYSLOG_IDENTIFIER={}\n", priority, IDENTIFIER).into_bytes();
+    if message.contains('\n') {
+        out.extend_from_slice(b"MESSAGE\n");
+        out.extend_from_slice(&(message.len() as u64).to_le_bytes());
+        out.extend_from_slice(message.as_bytes());
+        out.push(b'\n');
+    } else {
+        out.extend_from_slice(format!("MESSAGE={}\n", message).as_bytes());
+    }
+    out
+}
+
+fn rfc5424(pri: u8, message: &str) -> String {
+    static HOST: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    let host = HOST.get_or_init(|| {
+        sysinfo::System::host_name()
+            .filter(|h| !h.is_empty() && !h.contains(' '))
+            .unwrap_or_else(|| "-".into())
+    });
+    let ts = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    format!(
+        "<{}>1 {} {} {} {} - - {}",
+        pri,
+        ts,
+        host,
+        IDENTIFIER,
+        std::process::id(),
+        message
+    )
+}
+
+#[cfg(target_os = "windows")]
+mod event_log {
+    use std::ffi::c_void;
+
+    use tracing::Level;
+
+    const EVENTLOG_ERROR_TYPE: u16 = 0x0001;
+    const EVENTLOG_WARNING_TYPE: u16 = 0x0002;
+    const EVENTLOG_INFORMATION_TYPE: u16 = 0x0004;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegisterEventSourceW(server: *const u16, source: *const u16) -> *mut c_void;
+        fn DeregisterEventSource(handle: *mut c_void) -> i32;
+        fn ReportEventW(
+            handle: *mut c_void,
+            kind: u16,
+            category: u16,
+            event_id: u32,
+            user_sid: *const c_void,
+            num_strings: u16,
+            data_size: u32,
+            strings: *const *const u16,
+            data: *const c_void,
+        ) -> i32;
+    }
+
+    /// Event source handle. Without a registered message file Event Viewer shows the text as the
+    /// event's insertion string, which is all the service needs.
+    pub struct Source(*mut c_void);
+
+    // The handle is only passed to ReportEventW, which is safe to call from any thread
+    unsafe impl Send for Source {}
+    unsafe impl Sync for Source {}
+
+    impl Source {
+        pub fn register(name: &str) -> Result<Self, String> {
+            let wide: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+            let handle = unsafe { RegisterEventSourceW(std::ptr::null(), wide.as_ptr()) };
+            if handle.is_null() {
+                return Err(format!(
+                    "RegisterEventSource failed: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            Ok(Source(handle))
+        }
+
+        pub fn report(&self, level: &Level, message: &str) -> std::io::Result<usize> {
+            let kind = match *level {
+                Level::ERROR => EVENTLOG_ERROR_TYPE,
+                Level::WARN => EVENTLOG_WARNING_TYPE,
+                _ => EVENTLOG_INFORMATION_TYPE,
+            };
+            let wide: Vec<u16> = message.encode_utf16().chain(Some(0)).collect();
+            let strings = [wide.as_ptr()];
+            let ok = unsafe {
+                ReportEventW(
+                    self.0,
+                    kind,
+                    0,
+                    0,
+                    std::ptr::null(),
+                    1,
+                    0,
+                    strings.as_ptr(),
+                    std::ptr::null(),
+                )
+            };
+            if ok == 0 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(message.len())
+            }
+        }
+    }
+
+    impl Drop for Source {
+        fn drop(&mut self) {
+            unsafe {
+                DeregisterEventSource(self.0);
+            }
+        }
+    }
+}
+
+/// `message` field first, then any other fields as `key=value`.
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0.insert_str(0, value);
+        } else {
+            self.0.push_str(&format!(" {}={}", field.name(), value));
+        }
+    }
+}
+
+struct ForwardLayer;
+
+impl<S: Subscriber> Layer<S> for ForwardLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        // try_read: `apply` logs while holding the write lock
+        let Ok(sink) = SINK.try_read() else { return };
+        let Some(sink) = sink.as_ref() else { return };
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        sink.send(event.metadata().level(), &visitor.0);
+    }
+}
+
+/// Per-layer filter so the console keeps following RUST_LOG. Interest stays `sometimes` because
+/// the level can change at runtime.
+struct ForwardFilter;
+
+impl<S> Filter<S> for ForwardFilter {
+    fn enabled(&self, meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        let r = rank(meta.level());
+        r <= MAX_RANK.load(Ordering::Relaxed)
+            && (r <= DEPENDENCY_MAX_RANK || meta.target().starts_with(env!("CARGO_CRATE_NAME")))
+    }
+
+    fn callsite_enabled(&self, _meta: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::DEBUG)
+    }
+}
+
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    ForwardLayer.with_filter(ForwardFilter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journald_uses_length_prefix_for_multiline_messages() {
+        let single = journald_datagram(4, "fan apply failed");
+        assert_eq!(
+            single,
+            b"PRIORITY=4\nSYSLOG_IDENTIFIER=framework-control\nMESSAGE=fan apply failed\n"
+        );
+        let multi = journald_datagram(3, "a\nb");
+        let expected_tail: Vec<u8> = [&b"MESSAGE\n"[..], &3u64.to_le_bytes(), b"a\nb\n"].concat();
+        assert!(multi.ends_with(&expected_tail));
+    }
+}
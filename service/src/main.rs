@@ -2,39 +2,80 @@ use std::net::SocketAddr;
 
 use poem::http::Method;
 use poem::middleware::Cors;
-use poem::{get, listener::TcpListener, EndpointExt, Route};
-use poem_openapi::OpenApiService;
-use tracing::info;
+use poem::{get, listener::TcpListener, Endpoint, EndpointExt, Route};
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::prelude::*;
 
+use crate::types::AlertSeverity;
+
+mod actions;
+mod alerts;
+mod backups;
+mod battery_eta;
+mod battery_sessions;
+mod bootstrap;
+mod bridge;
 mod cli;
 mod config;
+mod config_check;
+mod config_reload;
+mod device;
+mod diagnostics;
+mod dnd;
+mod dry_run;
+mod eco;
+mod error_reports;
+mod fan_anomaly;
+mod fan_auto_tune;
+mod fan_hours;
+mod fan_profiles;
+mod firmware;
+mod gpu;
+mod hints;
+mod log_forward;
+mod managed_profiles;
+mod metrics;
+mod network;
+mod reports;
+mod request_id;
 mod routes;
+mod safe_mode;
+mod sensors;
 mod shortcuts;
+mod shutdown;
 mod state;
 mod tasks;
+mod temporary;
 pub mod types;
+mod units;
 mod update;
 mod utils;
+mod validation;
+mod wake;
 
 mod r#static;
 
+/// Raised while a new port fails to bind and the server stays on the last good one
+const PORT_BIND_ALERT: &str = "port_bind_failed";
+
 #[tokio::main]
 async fn main() {
     let _ = dotenvy::dotenv();
-    tracing_subscriber::fmt()
-        .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "info,tower_http=info".into()))
-        .without_time()
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .without_time()
+                .with_filter(tracing_subscriber::EnvFilter::new(
+                    std::env::var("RUST_LOG").unwrap_or_else(|_| "info,tower_http=info".into()),
+                )),
+        )
+        .with(log_forward::layer())
+        .with(error_reports::layer())
         .init();
 
     // If we're only generating OpenAPI, do it immediately and exit without requiring env or starting tasks
     let flag_arg = std::env::args().any(|a| a == "--generate-openapi");
     if flag_arg {
-        let api = OpenApiService::new(
-            crate::routes::Api,
-            "framework-control-service",
-            env!("CARGO_PKG_VERSION"),
-        )
-        .server("");
         let out = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
             .join("..")
             .join("web")
@@ -42,60 +83,53 @@ async fn main() {
         if let Some(parent) = out.parent() {
             let _ = std::fs::create_dir_all(parent);
         }
-        let spec_json = api.spec();
-        let _ = std::fs::write(&out, spec_json);
+        match routes::spec("").and_then(|s| serde_json::to_string_pretty(&s).map_err(|e| e.to_string())) {
+            Ok(spec_json) => {
+                let _ = std::fs::write(&out, spec_json);
+            }
+            Err(e) => error!("failed to generate the OpenAPI document: {}", e),
+        }
         return;
     }
 
+    // Config management hooks (`config_reload.rs`): validate a file, or have the running service
+    // reload its config, and exit with the outcome
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|a| a == "--check-config") {
+        let path = args.get(i + 1).filter(|p| !p.starts_with("--"));
+        std::process::exit(config_reload::check_file(path.map(String::as_str)));
+    }
+    if args.iter().any(|a| a == "--reload-config") {
+        let port = args
+            .iter()
+            .position(|a| a == "--port")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|p| p.parse().ok());
+        std::process::exit(config_reload::request_reload(port).await);
+    }
+
     // Check if installer requested shortcut creation on first run
     shortcuts::create_shortcuts_if_installer_requested().await;
 
-    let state = state::AppState::initialize().await;
+    safe_mode::evaluate();
+    if !safe_mode::is_active() {
+        // Put the fans back where they were before tool resolution (and the fan task) catch up
+        tokio::spawn(tasks::fan_restore::restore());
+    }
 
-    // Determine bind address to derive self-origins for CORS
-    // Use runtime env var, or fall back to compile-time baked value (required)
-    let bind_host = "127.0.0.1";
-    let configured_port: u16 = std::env::var("FRAMEWORK_CONTROL_PORT")
-        .ok()
-        .or_else(|| option_env!("FRAMEWORK_CONTROL_PORT").map(String::from))
-        .expect("FRAMEWORK_CONTROL_PORT must be set (either at runtime or baked at compile-time)")
-        .parse()
-        .expect("FRAMEWORK_CONTROL_PORT must be a valid u16");
-    let self_origins = vec![format!("http://{}:{}", bind_host, configured_port)];
-
-    // Merge configured origins with self-origins (dedup), then apply common rules
-    // Use runtime env var, or fall back to compile-time baked value (optional)
-    let mut origins: Vec<String> = std::env::var("FRAMEWORK_CONTROL_ALLOWED_ORIGINS")
-        .ok()
-        .or_else(|| option_env!("FRAMEWORK_CONTROL_ALLOWED_ORIGINS").map(String::from))
-        .unwrap_or_default()
-        .split(',')
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
-        .collect();
-    for so in &self_origins {
-        if !origins.iter().any(|o| o.eq_ignore_ascii_case(so)) {
-            origins.push(so.clone());
-        }
+    let state = state::AppState::initialize().await;
+    shutdown::install(&state);
+    {
+        let logging = &state.config.read().await.logging;
+        log_forward::apply(logging);
+        error_reports::apply(logging);
     }
-    let cors = origins
-        .iter()
-        .fold(Cors::new(), |c, origin| c.allow_origin(origin.as_str()))
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers(["content-type"])
-        .max_age(600);
 
     // Boot background tasks (fan curve if enabled)
     tasks::boot(&state).await;
 
     // Build OpenApiService from routes::Api
-    let api = OpenApiService::new(
-        crate::routes::Api,
-        "framework-control-service",
-        env!("CARGO_PKG_VERSION"),
-    )
-    .server("");
+    let api = routes::openapi("");
 
     // Build the actual Poem app and apply CORS globally (API and static UI)
     let app = Route::new()
@@ -103,9 +137,101 @@ async fn main() {
         .at("/", get(r#static::serve_static))
         .at("/*path", get(r#static::serve_static))
         .data(state.clone())
-        .with(cors);
+        .with(cors())
+        .around(|ep, mut req| async move {
+            // Behind a reverse proxy: drop the base path so the routes below match
+            if let Some(redirect) = network::strip_base_path(&mut req) {
+                return Ok(redirect);
+            }
+            let method = req.method().to_string();
+            let path = req.uri().path().to_string();
+            let id = request_id::from_header(req.header(request_id::HEADER));
+            let started = std::time::Instant::now();
+            let mut resp = request_id::scope(id.clone(), ep.get_response(req)).await;
+            let elapsed = started.elapsed();
+            let status = resp.status().as_u16();
+            metrics::record_http(&method, &path, status, elapsed);
+            let duration_ms = (elapsed.as_secs_f64() * 100_000.0).round() / 100.0;
+            // Pollers hit the API every few seconds: successes only at debug
+            match status {
+                500.. => warn!(target: "framework_control_service::access", request_id = %id, method = %method, path = %path, status, duration_ms, "request failed"),
+                400.. => info!(target: "framework_control_service::access", request_id = %id, method = %method, path = %path, status, duration_ms, "request rejected"),
+                _ => debug!(target: "framework_control_service::access", request_id = %id, method = %method, path = %path, status, duration_ms, "request"),
+            }
+            if let Ok(value) = id.parse() {
+                resp.headers_mut().insert(request_id::HEADER, value);
+            }
+            Ok(resp)
+        });
+
+    // Serve until the port changes, then rebind; a port that fails to bind falls back to the last good one
+    let app = std::sync::Arc::new(app);
+    let mut port_rx = network::subscribe();
+    let mut last_good: Option<u16> = None;
+    loop {
+        let port = *port_rx.borrow_and_update();
+        let addr: SocketAddr = (network::BIND_HOST.parse::<std::net::IpAddr>().unwrap(), port).into();
+        info!("listening on http://{}", addr);
+        let mut shutdown_rx = port_rx.clone();
+        let shutdown = async move {
+            if shutdown_rx.changed().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        };
+        let result = poem::Server::new(TcpListener::bind(addr))
+            .run_with_graceful_shutdown(app.clone(), shutdown, Some(std::time::Duration::from_secs(5)))
+            .await;
+        match (result, last_good) {
+            (Ok(()), _) => {
+                alerts::clear(PORT_BIND_ALERT);
+                last_good = Some(port);
+            }
+            (Err(e), Some(good)) if good != port => {
+                error!("failed to bind {}: {}; staying on port {}", addr, e, good);
+                alerts::raise(
+                    PORT_BIND_ALERT,
+                    AlertSeverity::Warning,
+                    format!("Could not listen on port {} ({}); still using port {}", port, e, good),
+                );
+                network::fall_back_to(good);
+            }
+            (Err(e), _) => panic!("failed to bind {}: {}", addr, e),
+        }
+    }
+}
+
+/// Port and allowed origins: `network` config section, else the (runtime or baked) env values.
+/// Origins are checked per request so they can change live.
+fn cors() -> Cors {
+    Cors::new()
+        .allow_origins_fn(network::origin_allowed)
+        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+        .allow_headers(["content-type", request_id::HEADER])
+        .expose_headers([request_id::HEADER])
+        .max_age(600)
+}
 
-    let addr: SocketAddr = (bind_host.parse::<std::net::IpAddr>().unwrap(), configured_port).into();
-    info!("listening on http://{}", addr);
-    poem::Server::new(TcpListener::bind(addr)).run(app).await.unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn preflight_allows_delete() {
+        let app = Route::new().at("/api/fan/override", poem::delete(poem::endpoint::make_sync(|_| "")));
+        let app = app.with(cors());
+        let origin = format!("http://{}:{}", network::BIND_HOST, network::port());
+        let req = poem::Request::builder()
+            .method(Method::OPTIONS)
+            .uri(poem::http::Uri::from_static("/api/fan/override"))
+            .header("origin", origin)
+            .header("access-control-request-method", "DELETE")
+            .finish();
+        let resp = app.get_response(req).await;
+        assert!(resp.status().is_success());
+        let allowed = resp
+            .header("access-control-allow-methods")
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        assert!(allowed.contains("DELETE"), "allowed methods: {}", allowed);
+    }
 }
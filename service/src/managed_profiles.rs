@@ -0,0 +1,188 @@
+// Managed fan profiles: read-only profiles provisioned as drop-in JSON files, one per profile, in
+// `profiles.d` next to the config (or the directory in `FRAMEWORK_CONTROL_MANAGED_PROFILES`). The
+// file name without `.json` is the profile name and the content is a `fan.profiles` value (a
+// global curve). Admins push one standard set to many machines with the file distribution they
+// already use (Intune, GPO, Ansible); the service never writes the directory.
+//
+// The files are read at startup and again whenever the directory changes. Managed profiles join
+// `fan.profiles` wherever a profile is looked up (`fan_profiles::get`) and win over a config
+// profile of the same name; writes that put one of their names in `fan.profiles` are rejected.
+// When the active profile's file changes its new curve is applied and saved, like a scheduled
+// switch; when the file goes away the curve stays but no profile is active. Files that do not
+// parse or validate are skipped with a warning.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::types::{Config, FanControlConfig, GlobalCurveConfig};
+
+const ENV_DIR: &str = "FRAMEWORK_CONTROL_MANAGED_PROFILES";
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+static PROFILES: std::sync::RwLock<BTreeMap<String, GlobalCurveConfig>> = std::sync::RwLock::new(BTreeMap::new());
+
+pub fn dir() -> PathBuf {
+    match std::env::var(ENV_DIR) {
+        Ok(d) if !d.trim().is_empty() => PathBuf::from(d),
+        _ => crate::config::config_path().with_file_name("profiles.d"),
+    }
+}
+
+/// Profile files with their modification time and size, to notice changes without parsing.
+fn fingerprint(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let mut out: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|x| x == "json"))
+        .map(|p| {
+            let meta = p.metadata().ok();
+            let modified = meta.as_ref().and_then(|m| m.modified().ok());
+            let len = meta.map(|m| m.len()).unwrap_or(0);
+            (p, modified, len)
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+/// A profile file's curve, validated like a `fan.profiles` entry.
+fn parse(name: &str, raw: &str) -> Result<GlobalCurveConfig, String> {
+    let curve: GlobalCurveConfig = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    let fan = FanControlConfig {
+        profiles: Some([(name.to_string(), curve.clone())].into()),
+        ..Default::default()
+    };
+    let mut errs = crate::validation::Errors::default();
+    crate::validation::fan(&mut errs, &fan);
+    errs.into_result().map_err(|fields| {
+        fields
+            .iter()
+            .map(|f| format!("{}: {}", f.field, f.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    })?;
+    Ok(curve)
+}
+
+fn read_all(dir: &Path) -> BTreeMap<String, GlobalCurveConfig> {
+    let mut out = BTreeMap::new();
+    for (path, _, _) in fingerprint(dir) {
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| parse(name, &raw));
+        match parsed {
+            Ok(curve) => {
+                out.insert(name.to_string(), curve);
+            }
+            Err(e) => warn!("managed profiles: skipping {}: {}", path.display(), e),
+        }
+    }
+    out
+}
+
+/// Read the directory again; true when the set of managed profiles changed.
+pub fn load() -> bool {
+    let dir = dir();
+    let next = read_all(&dir);
+    let Ok(mut w) = PROFILES.write() else { return false };
+    let same = serde_json::to_value(&*w).ok() == serde_json::to_value(&next).ok();
+    if !same {
+        info!(
+            "managed profiles from {}: {}",
+            dir.display(),
+            next.keys().cloned().collect::<Vec<_>>().join(", ")
+        );
+        *w = next;
+    }
+    !same
+}
+
+pub fn get(name: &str) -> Option<GlobalCurveConfig> {
+    PROFILES.read().ok()?.get(name).cloned()
+}
+
+pub fn is_managed(name: &str) -> bool {
+    PROFILES.read().is_ok_and(|p| p.contains_key(name))
+}
+
+/// Managed profile names, sorted
+pub fn names() -> Vec<String> {
+    PROFILES.read().map(|p| p.keys().cloned().collect()).unwrap_or_default()
+}
+
+/// Bring the active profile in line with the reloaded files. `false` when it has to wait for a
+/// temporary fan override to end.
+async fn follow(cfg: &Arc<tokio::sync::RwLock<Config>>) -> Result<bool, String> {
+    let _writer = crate::config::WRITE_LOCK.lock().await;
+    if crate::temporary::active().iter().any(|o| o.section == "fan") {
+        return Ok(false);
+    }
+    let running = cfg.read().await.clone();
+    let mut merged = crate::temporary::persisted(&running);
+    let Some(name) = merged.fan.active_profile.clone() else {
+        return Ok(true);
+    };
+    let before = serde_json::to_value(&merged.fan).ok();
+    if crate::fan_profiles::get(&merged.fan, &name).is_some() {
+        crate::fan_profiles::activate(&mut merged.fan, &name);
+    } else {
+        merged.fan.active_profile = None;
+    }
+    if serde_json::to_value(&merged.fan).ok() == before {
+        return Ok(true);
+    }
+    merged.revision = running.revision + 1;
+    crate::config::save(&merged)?;
+    crate::config_check::clear_section("fan");
+    *cfg.write().await = crate::temporary::overlay(merged, &running, &["fan"]);
+    info!(
+        "managed profiles: active profile '{}' updated (revision {})",
+        name,
+        running.revision + 1
+    );
+    Ok(true)
+}
+
+/// Watch the directory and reload on change. The first load happens in `AppState::initialize`,
+/// ahead of the startup config check.
+pub async fn run(cfg: Arc<tokio::sync::RwLock<Config>>) {
+    let mut seen = fingerprint(&dir());
+    let mut pending = false;
+    loop {
+        sleep(CHECK_INTERVAL).await;
+        let now = fingerprint(&dir());
+        if now != seen {
+            seen = now;
+            pending |= load();
+        }
+        if pending {
+            match follow(&cfg).await {
+                Ok(done) => pending = !done,
+                Err(e) => warn!("managed profiles: failed to save the updated profile: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_files_are_validated_like_config_profiles() {
+        let curve = parse("office", r#"{"sensors": ["APU"], "points": [[40, 20], [80, 100]]}"#).unwrap();
+        assert_eq!(curve.curve.points, vec![[40, 20], [80, 100]]);
+        assert!(parse("office", r#"{"points": [[40, 120]]}"#).is_err());
+        assert!(parse("office", "not json").is_err());
+    }
+}
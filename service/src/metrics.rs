@@ -0,0 +1,344 @@
+// Service-internal metrics (CLI calls, cache efficiency, task loop and HTTP timings), exported in
+// Prometheus text format by `GET /api/metrics`. Hardware readings are not duplicated here.
+//
+// CLI calls are broken down by command (the first flag, e.g. `--thermal`), and `/health` carries
+// a per-command latency summary (`cli_latency`): the numbers behind moving EC access in-process
+// and behind the wrappers' timeouts. A call slower than `SLOW_CLI_CALL` is logged with its
+// exact arguments.
+//
+// Active alerts are appended as `ALERTS` / `ALERTS_FOR_STATE`, the series Prometheus writes for
+// its own alerting rules, so a Prometheus rule on `ALERTS{alertname="..."}` forwards them to
+// Alertmanager while the thresholds stay in the service config only.
+//
+// Label sets are small and fixed (tool names, cache keys, task names, API paths), so a single
+// mutex-guarded map is plenty; recording never awaits.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use tracing::warn;
+
+use crate::types::{Alert, AlertSeverity, CliLatency};
+
+/// Histogram bucket upper bounds in seconds.
+const BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// CLI calls taking longer are logged with their arguments. EC reads normally finish well
+/// under a second; the wrappers give up after 60s.
+pub const SLOW_CLI_CALL: Duration = Duration::from_secs(2);
+
+const HELP: &[(&str, &str, &str)] = &[
+    (
+        "fc_cli_calls_total",
+        "counter",
+        "External CLI invocations by tool and result",
+    ),
+    (
+        "fc_cli_call_duration_seconds",
+        "histogram",
+        "External CLI invocation wall time by tool and command",
+    ),
+    (
+        "fc_cli_slow_calls_total",
+        "counter",
+        "External CLI invocations slower than the slow-call threshold",
+    ),
+    (
+        "fc_cache_requests_total",
+        "counter",
+        "Global cache lookups by key and result (hit/miss)",
+    ),
+    (
+        "fc_task_loop_duration_seconds",
+        "histogram",
+        "Background task loop iteration time (excluding sleep)",
+    ),
+    (
+        "fc_http_request_duration_seconds",
+        "histogram",
+        "HTTP request handling time",
+    ),
+];
+
+#[derive(Default)]
+struct Histogram {
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if self.counts.is_empty() {
+            self.counts = vec![0; BUCKETS.len()];
+        }
+        for (i, le) in BUCKETS.iter().enumerate() {
+            if seconds <= *le {
+                self.counts[i] += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    /// Upper bound of the bucket holding quantile `q`, in seconds; `None` when the quantile is
+    /// past the last bucket or nothing was observed.
+    fn quantile(&self, q: f64) -> Option<f64> {
+        let rank = ((q * self.count as f64).ceil() as u64).max(1);
+        BUCKETS
+            .iter()
+            .zip(&self.counts)
+            .find(|(_, c)| **c >= rank)
+            .map(|(le, _)| *le)
+    }
+}
+
+/// Per-command CLI statistics behind `/health`'s `cli_latency`.
+#[derive(Default)]
+struct CliStats {
+    latency: Histogram,
+    errors: u64,
+    slow: u64,
+    max_s: f64,
+}
+
+/// (metric name, rendered label set such as `tool="framework_tool"`)
+type Key = (&'static str, String);
+
+#[derive(Default)]
+struct Registry {
+    counters: BTreeMap<Key, u64>,
+    histograms: BTreeMap<Key, Histogram>,
+    /// By (tool, command)
+    cli: BTreeMap<(String, String), CliStats>,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn inc_counter(name: &'static str, labels: &[(&str, &str)]) {
+    if let Ok(mut r) = registry().lock() {
+        *r.counters.entry((name, render_labels(labels))).or_insert(0) += 1;
+    }
+}
+
+pub fn observe(name: &'static str, labels: &[(&str, &str)], elapsed: Duration) {
+    if let Ok(mut r) = registry().lock() {
+        r.histograms
+            .entry((name, render_labels(labels)))
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+}
+
+/// The command of a CLI call, as a metrics label: its first flag without a value (`--thermal`,
+/// `--stapm-limit`). Flags form a small fixed set, unlike the values.
+fn command_label(args: &[&str]) -> String {
+    args.iter()
+        .find(|a| a.starts_with('-'))
+        .map(|a| a.split('=').next().unwrap_or(a).to_string())
+        .unwrap_or_else(|| "none".to_string())
+}
+
+pub fn record_cli_call(tool: &str, args: &[&str], elapsed: Duration, ok: bool) {
+    let result = if ok { "ok" } else { "error" };
+    let command = command_label(args);
+    let slow = elapsed > SLOW_CLI_CALL;
+    if slow {
+        warn!(
+            "slow {} call: {} ms for `{}` ({})",
+            tool,
+            elapsed.as_millis(),
+            args.join(" "),
+            result
+        );
+        inc_counter("fc_cli_slow_calls_total", &[("tool", tool), ("command", &command)]);
+    }
+    inc_counter("fc_cli_calls_total", &[("tool", tool), ("result", result)]);
+    observe(
+        "fc_cli_call_duration_seconds",
+        &[("tool", tool), ("command", &command)],
+        elapsed,
+    );
+    if let Ok(mut r) = registry().lock() {
+        let stats = r.cli.entry((tool.to_string(), command)).or_default();
+        let seconds = elapsed.as_secs_f64();
+        stats.latency.observe(seconds);
+        stats.max_s = stats.max_s.max(seconds);
+        stats.errors += u64::from(!ok);
+        stats.slow += u64::from(slow);
+    }
+}
+
+/// Latency summary per CLI command since startup. Percentiles are bucket upper bounds (capped at
+/// the slowest call), so they are coarse but never understate.
+pub fn cli_latency() -> Vec<CliLatency> {
+    let Ok(r) = registry().lock() else {
+        return Vec::new();
+    };
+    let ms = |s: f64| (s * 1000.0).round() as u64;
+    r.cli
+        .iter()
+        .map(|((tool, command), s)| {
+            let quantile = |q| ms(s.latency.quantile(q).unwrap_or(s.max_s).min(s.max_s));
+            CliLatency {
+                tool: tool.clone(),
+                command: command.clone(),
+                calls: s.latency.count,
+                errors: s.errors,
+                slow_calls: s.slow,
+                p50_ms: quantile(0.5),
+                p95_ms: quantile(0.95),
+                max_ms: ms(s.max_s),
+                mean_ms: ms(s.latency.sum / s.latency.count.max(1) as f64),
+            }
+        })
+        .collect()
+}
+
+pub fn record_cache(key: &str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    inc_counter("fc_cache_requests_total", &[("key", key), ("result", result)]);
+}
+
+pub fn record_task_loop(task: &str, elapsed: Duration) {
+    observe("fc_task_loop_duration_seconds", &[("task", task)], elapsed);
+}
+
+pub fn record_http(method: &str, path: &str, status: u16, elapsed: Duration) {
+    // API paths carry no parameters, so they are safe labels; everything else is the static UI.
+    let path = if status == 404 {
+        "unmatched"
+    } else if path.starts_with("/api/") {
+        path
+    } else {
+        "static"
+    };
+    observe(
+        "fc_http_request_duration_seconds",
+        &[("method", method), ("path", path), ("status", &status.to_string())],
+        elapsed,
+    );
+}
+
+/// Prometheus text exposition of everything recorded so far.
+pub fn render() -> String {
+    let Ok(r) = registry().lock() else {
+        return String::new();
+    };
+    let mut out = String::new();
+    for (name, kind, help) in HELP {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for ((n, labels), v) in r.counters.iter().filter(|((n, _), _)| n == name) {
+            let _ = writeln!(out, "{}{{{}}} {}", n, labels, v);
+        }
+        for ((n, labels), h) in r.histograms.iter().filter(|((n, _), _)| n == name) {
+            let sep = if labels.is_empty() { "" } else { "," };
+            for (le, c) in BUCKETS.iter().zip(&h.counts) {
+                let _ = writeln!(out, "{}_bucket{{{}{}le=\"{}\"}} {}", n, labels, sep, le, c);
+            }
+            let _ = writeln!(out, "{}_bucket{{{}{}le=\"+Inf\"}} {}", n, labels, sep, h.count);
+            let _ = writeln!(out, "{}_sum{{{}}} {}", n, labels, h.sum);
+            let _ = writeln!(out, "{}_count{{{}}} {}", n, labels, h.count);
+        }
+    }
+    drop(r);
+    render_alerts(&mut out, &crate::alerts::active());
+    out
+}
+
+fn render_alerts(out: &mut String, alerts: &[Alert]) {
+    let _ = writeln!(out, "# HELP ALERTS Active service alerts (1 while firing)");
+    let _ = writeln!(out, "# TYPE ALERTS gauge");
+    for a in alerts {
+        let labels = render_labels(&[
+            ("alertname", &a.id),
+            ("alertstate", "firing"),
+            ("severity", severity_label(a.severity)),
+        ]);
+        let _ = writeln!(out, "ALERTS{{{}}} 1", labels);
+    }
+    let _ = writeln!(
+        out,
+        "# HELP ALERTS_FOR_STATE Unix time in seconds at which each active alert was first raised"
+    );
+    let _ = writeln!(out, "# TYPE ALERTS_FOR_STATE gauge");
+    for a in alerts {
+        let labels = render_labels(&[("alertname", &a.id), ("severity", severity_label(a.severity))]);
+        let _ = writeln!(out, "ALERTS_FOR_STATE{{{}}} {}", labels, a.since_ms / 1000);
+    }
+}
+
+fn severity_label(s: AlertSeverity) -> &'static str {
+    match s {
+        AlertSeverity::Info => "info",
+        AlertSeverity::Warning => "warning",
+        AlertSeverity::Critical => "critical",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let mut h = Histogram::default();
+        h.observe(0.003);
+        h.observe(0.2);
+        h.observe(60.0);
+        assert_eq!(h.counts[0], 1); // <= 5ms
+        assert_eq!(h.counts[5], 2); // <= 250ms
+        assert_eq!(*h.counts.last().unwrap(), 2); // <= 30s; the 60s call only lands in +Inf
+        assert_eq!(h.count, 3);
+    }
+
+    #[test]
+    fn cli_calls_are_labelled_by_command() {
+        assert_eq!(command_label(&["--thermal"]), "--thermal");
+        assert_eq!(command_label(&["--fansetduty", "40", "1"]), "--fansetduty");
+        assert_eq!(command_label(&["--stapm-limit=15000", "--dump-table"]), "--stapm-limit");
+        assert_eq!(command_label(&[]), "none");
+
+        let mut h = Histogram::default();
+        for _ in 0..9 {
+            h.observe(0.03);
+        }
+        h.observe(4.0);
+        assert_eq!(h.quantile(0.5), Some(0.05));
+        assert_eq!(h.quantile(0.95), Some(5.0));
+        h.observe(45.0);
+        h.observe(45.0);
+        assert_eq!(h.quantile(0.95), None);
+    }
+
+    #[test]
+    fn alerts_render_as_prometheus_series() {
+        let mut out = String::new();
+        render_alerts(
+            &mut out,
+            &[Alert {
+                id: "weak_charger".into(),
+                severity: AlertSeverity::Warning,
+                message: "Charger too weak".into(),
+                since_ms: 1_700_000_000_500,
+                since: None,
+            }],
+        );
+        assert!(out.contains("ALERTS{alertname=\"weak_charger\",alertstate=\"firing\",severity=\"warning\"} 1\n"));
+        assert!(out.contains("ALERTS_FOR_STATE{alertname=\"weak_charger\",severity=\"warning\"} 1700000000\n"));
+    }
+}
@@ -0,0 +1,202 @@
+// Listening port, CORS origins and the base path for reverse proxies.
+//
+// The `network` config section wins over FRAMEWORK_CONTROL_PORT / FRAMEWORK_CONTROL_ALLOWED_ORIGINS
+// / FRAMEWORK_CONTROL_BASE_PATH (runtime env, the installer's `bootstrap.json`, or baked at build
+// time), which stay the installer defaults. With no port anywhere the service falls back to
+// `DEFAULT_PORT` rather than refusing to start. Origins and the base path are checked per
+// request, so changes apply immediately; a port change is published to `main`, which shuts the
+// server down gracefully and rebinds on the new port.
+//
+// With a base path such as `/framework`, requests under it have it stripped before routing, so a
+// proxy may forward the prefix or not. Unprefixed paths keep working for direct local access.
+
+use std::sync::{OnceLock, RwLock};
+
+use poem::IntoResponse;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::types::NetworkConfig;
+
+/// Loopback only: the API has no authentication.
+pub const BIND_HOST: &str = "127.0.0.1";
+/// Port of the published builds, used when nothing else sets one
+pub const DEFAULT_PORT: u16 = 8090;
+
+struct Effective {
+    port: u16,
+    origins: Vec<String>,
+    base_path: Option<String>,
+}
+
+fn env_port() -> Option<u16> {
+    std::env::var("FRAMEWORK_CONTROL_PORT")
+        .ok()
+        .and_then(|p| p.trim().parse().ok())
+        .or_else(crate::bootstrap::port)
+        .or_else(|| option_env!("FRAMEWORK_CONTROL_PORT").and_then(|p| p.trim().parse().ok()))
+}
+
+fn env_origins() -> Vec<String> {
+    std::env::var("FRAMEWORK_CONTROL_ALLOWED_ORIGINS")
+        .ok()
+        .or_else(crate::bootstrap::allowed_origins)
+        .or_else(|| option_env!("FRAMEWORK_CONTROL_ALLOWED_ORIGINS").map(String::from))
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn env_base_path() -> Option<String> {
+    std::env::var("FRAMEWORK_CONTROL_BASE_PATH")
+        .ok()
+        .or_else(|| option_env!("FRAMEWORK_CONTROL_BASE_PATH").map(String::from))
+}
+
+/// `/framework/` -> `/framework`; empty and `/` mean no base path.
+fn normalize_base_path(p: &str) -> Option<String> {
+    let p = p.trim().trim_end_matches('/');
+    (!p.is_empty()).then(|| p.to_string())
+}
+
+fn resolve(cfg: &NetworkConfig) -> Effective {
+    let port = cfg.port.or_else(env_port).unwrap_or_else(|| {
+        warn!(
+            "no port configured (network.port, FRAMEWORK_CONTROL_PORT or bootstrap.json); using {}",
+            DEFAULT_PORT
+        );
+        DEFAULT_PORT
+    });
+    let origins = cfg.allowed_origins.clone().unwrap_or_else(env_origins);
+    let base_path = cfg
+        .base_path
+        .clone()
+        .or_else(env_base_path)
+        .and_then(|p| normalize_base_path(&p));
+    Effective {
+        port,
+        origins,
+        base_path,
+    }
+}
+
+fn current() -> &'static RwLock<Effective> {
+    static CURRENT: OnceLock<RwLock<Effective>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(resolve(&crate::config::load().network)))
+}
+
+fn port_sender() -> &'static watch::Sender<u16> {
+    static PORT: OnceLock<watch::Sender<u16>> = OnceLock::new();
+    PORT.get_or_init(|| watch::channel(port()).0)
+}
+
+/// Port the server is (or should be) listening on.
+pub fn port() -> u16 {
+    current().read().map(|e| e.port).unwrap_or_default()
+}
+
+/// Port `cfg` resolves to, without touching the running state (for command-line clients).
+pub fn port_for(cfg: &NetworkConfig) -> u16 {
+    resolve(cfg).port
+}
+
+/// Notified whenever the port changes.
+pub fn subscribe() -> watch::Receiver<u16> {
+    port_sender().subscribe()
+}
+
+/// Apply a saved `network` section: origins immediately, the port via a rebind.
+pub fn apply(cfg: &NetworkConfig) {
+    let next = resolve(cfg);
+    let port = next.port;
+    if let Ok(mut w) = current().write() {
+        *w = next;
+    }
+    port_sender().send_if_modified(|p| {
+        if *p == port {
+            return false;
+        }
+        info!("network: port changed {} -> {}", p, port);
+        *p = port;
+        true
+    });
+}
+
+/// Go back to `port` after the configured one failed to bind (config is left as saved).
+pub fn fall_back_to(port: u16) {
+    if let Ok(mut w) = current().write() {
+        w.port = port;
+    }
+    port_sender().send_replace(port);
+}
+
+/// Base path the UI and API are served under behind a reverse proxy, without trailing slash.
+pub fn base_path() -> Option<String> {
+    current().read().ok().and_then(|e| e.base_path.clone())
+}
+
+/// Where a request path goes given the base path.
+#[derive(Debug, PartialEq)]
+enum Routed {
+    /// Route this (prefix-stripped) path
+    Path(String),
+    /// The bare base path: redirect to it with a trailing slash so the UI's relative URLs resolve
+    Redirect(String),
+}
+
+fn route(base: Option<&str>, path: &str) -> Routed {
+    let Some(base) = base else {
+        return Routed::Path(path.to_string());
+    };
+    match path.strip_prefix(base) {
+        Some("") => Routed::Redirect(format!("{}/", base)),
+        Some(rest) if rest.starts_with('/') => Routed::Path(rest.to_string()),
+        _ => Routed::Path(path.to_string()),
+    }
+}
+
+/// Strip the base path from `req` before routing; returns the redirect for the bare base path.
+pub fn strip_base_path(req: &mut poem::Request) -> Option<poem::Response> {
+    let base = base_path()?;
+    let path = match route(Some(&base), req.uri().path()) {
+        Routed::Redirect(to) => return Some(poem::web::Redirect::permanent(to).into_response()),
+        Routed::Path(p) => p,
+    };
+    let path_and_query = match req.uri().query() {
+        Some(q) => format!("{}?{}", path, q),
+        None => path,
+    };
+    if let Ok(uri) = path_and_query.parse() {
+        *req.uri_mut() = uri;
+    }
+    None
+}
+
+/// CORS check: configured origins plus the service's own origin.
+pub fn origin_allowed(origin: &str) -> bool {
+    let Ok(e) = current().read() else { return false };
+    origin.eq_ignore_ascii_case(&format!("http://{}:{}", BIND_HOST, e.port))
+        || e.origins.iter().any(|o| o.eq_ignore_ascii_case(origin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_path_is_stripped_when_present() {
+        assert_eq!(normalize_base_path(" /framework/ ").as_deref(), Some("/framework"));
+        assert_eq!(normalize_base_path("/"), None);
+        let base = Some("/framework");
+        assert_eq!(route(base, "/framework/api/config"), Routed::Path("/api/config".into()));
+        assert_eq!(route(base, "/framework/"), Routed::Path("/".into()));
+        assert_eq!(route(base, "/framework"), Routed::Redirect("/framework/".into()));
+        // Direct access without the prefix, and lookalike prefixes, are left alone
+        assert_eq!(route(base, "/api/config"), Routed::Path("/api/config".into()));
+        assert_eq!(route(base, "/frameworks/x"), Routed::Path("/frameworks/x".into()));
+        assert_eq!(route(None, "/framework/x"), Routed::Path("/framework/x".into()));
+    }
+}
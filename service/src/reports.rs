@@ -0,0 +1,274 @@
+// Scheduled telemetry reports: the telemetry task feeds every sample into a running period
+// accumulator (persisted in `report_state.json` next to the config so a restart does not lose
+// the day), and the `reports` task turns it into a `TelemetryReport` once the period is over.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::types::{DeviceIdentity, ReportsConfig, TelemetryReport, TelemetrySample, UiConfig};
+
+/// Longer gaps between samples (sleep, service stopped) are not counted as fan time.
+const MAX_SAMPLE_GAP_MS: i64 = 10_000;
+/// The hottest sensor must drop this far below the throttle temperature before a new event counts.
+const THROTTLE_RESET_C: i32 = 3;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Accumulator {
+    period_start_ms: i64,
+    #[serde(default)]
+    last_ts_ms: Option<i64>,
+    #[serde(default)]
+    max_temps: BTreeMap<String, i32>,
+    /// Per sensor: (sum, count)
+    #[serde(default)]
+    temp_sums: BTreeMap<String, (f64, u64)>,
+    #[serde(default)]
+    fan_ms_above: i64,
+    #[serde(default)]
+    throttle_events: u32,
+    #[serde(default)]
+    throttling: bool,
+    #[serde(default)]
+    battery_health_start_pct: Option<f32>,
+}
+
+impl Accumulator {
+    fn new(now_ms: i64) -> Self {
+        Self {
+            period_start_ms: now_ms,
+            ..Default::default()
+        }
+    }
+
+    fn record(&mut self, sample: &TelemetrySample, cfg: &ReportsConfig) {
+        let dt = self
+            .last_ts_ms
+            .map(|last| sample.ts_ms - last)
+            .filter(|dt| (0..=MAX_SAMPLE_GAP_MS).contains(dt))
+            .unwrap_or(0);
+        self.last_ts_ms = Some(sample.ts_ms);
+
+        for (name, &temp) in &sample.temps {
+            let max = self.max_temps.entry(name.clone()).or_insert(temp);
+            *max = (*max).max(temp);
+            let sum = self.temp_sums.entry(name.clone()).or_insert((0.0, 0));
+            sum.0 += temp as f64;
+            sum.1 += 1;
+        }
+        if sample.rpms.iter().any(|&rpm| rpm >= cfg.fan_rpm_threshold) {
+            self.fan_ms_above += dt;
+        }
+        if let Some(&hottest) = sample.temps.values().max() {
+            let limit = cfg.throttle_temp_c as i32;
+            if hottest >= limit && !self.throttling {
+                self.throttle_events += 1;
+                self.throttling = true;
+            } else if hottest < limit - THROTTLE_RESET_C {
+                self.throttling = false;
+            }
+        }
+    }
+
+    fn report(
+        &self,
+        now_ms: i64,
+        cfg: &ReportsConfig,
+        ui: &UiConfig,
+        health_end: Option<f32>,
+        device: DeviceIdentity,
+    ) -> TelemetryReport {
+        let avg_temps: BTreeMap<String, f32> = self
+            .temp_sums
+            .iter()
+            .filter(|(_, (_, n))| *n > 0)
+            .map(|(name, (sum, n))| (name.clone(), ((sum / *n as f64) * 10.0).round() as f32 / 10.0))
+            .collect();
+        let fan_hours = ((self.fan_ms_above as f64 / 3_600_000.0) * 100.0).round() as f32 / 100.0;
+
+        let subject = format!(
+            "Framework Control report: {} to {}",
+            crate::units::format_timestamp(self.period_start_ms, ui),
+            crate::units::format_timestamp(now_ms, ui)
+        );
+        let mut text = String::new();
+        text.push_str("Temperatures (max / avg):\n");
+        for (name, max) in &self.max_temps {
+            let avg = avg_temps.get(name).copied().unwrap_or(*max as f32);
+            text.push_str(&format!(
+                "  {}: {} / {}\n",
+                name,
+                crate::units::format_temp(*max as f32, ui),
+                crate::units::format_temp(avg, ui)
+            ));
+        }
+        text.push_str(&format!(
+            "Fans at or above {} RPM: {:.2} h\n",
+            cfg.fan_rpm_threshold, fan_hours
+        ));
+        text.push_str(&format!(
+            "Throttling events (hottest sensor at {}): {}\n",
+            crate::units::format_temp(cfg.throttle_temp_c as f32, ui),
+            self.throttle_events
+        ));
+        if let (Some(start), Some(end)) = (self.battery_health_start_pct, health_end) {
+            text.push_str(&format!(
+                "Battery health: {:.1}% -> {:.1}% ({:+.2} points)\n",
+                start,
+                end,
+                end - start
+            ));
+        }
+
+        TelemetryReport {
+            period_start_ms: self.period_start_ms,
+            period_end_ms: now_ms,
+            max_temps: self.max_temps.clone(),
+            avg_temps,
+            fan_hours_above_threshold: fan_hours,
+            fan_rpm_threshold: cfg.fan_rpm_threshold,
+            throttle_events: self.throttle_events,
+            battery_health_start_pct: self.battery_health_start_pct,
+            battery_health_end_pct: health_end,
+            subject,
+            text,
+            device,
+        }
+    }
+}
+
+fn state_path() -> PathBuf {
+    crate::config::config_path().with_file_name("report_state.json")
+}
+
+fn current() -> &'static Mutex<Accumulator> {
+    static CURRENT: OnceLock<Mutex<Accumulator>> = OnceLock::new();
+    CURRENT.get_or_init(|| {
+        let loaded = std::fs::read_to_string(state_path())
+            .ok()
+            .and_then(|s| serde_json::from_str::<Accumulator>(&s).ok());
+        Mutex::new(loaded.unwrap_or_else(|| Accumulator::new(unix_time_ms())))
+    })
+}
+
+/// Fold one telemetry sample into the current period.
+pub fn record_sample(sample: &TelemetrySample, cfg: &ReportsConfig) {
+    if let Ok(mut acc) = current().lock() {
+        acc.record(sample, cfg);
+    }
+}
+
+/// Battery health (full-charge vs design capacity) at the start of the period, set once.
+pub fn set_battery_health_start(pct: f32) {
+    if let Ok(mut acc) = current().lock() {
+        acc.battery_health_start_pct.get_or_insert(pct);
+    }
+}
+
+/// Report for the period so far (does not reset it).
+pub fn current_report(cfg: &ReportsConfig, ui: &UiConfig, health_end: Option<f32>) -> TelemetryReport {
+    let acc = current().lock().map(|a| a.clone()).unwrap_or_default();
+    acc.report(unix_time_ms(), cfg, ui, health_end, crate::device::identity())
+}
+
+/// Close the current period and start the next one.
+pub fn start_new_period(health_start: Option<f32>) {
+    if let Ok(mut acc) = current().lock() {
+        *acc = Accumulator::new(unix_time_ms());
+        acc.battery_health_start_pct = health_start;
+    }
+    save();
+}
+
+/// The running period as kept in `report_state.json`, for backups.
+pub fn export() -> Option<serde_json::Value> {
+    let acc = current().lock().ok()?.clone();
+    serde_json::to_value(acc).ok()
+}
+
+/// Replace the running period with one from `export`.
+pub fn restore(value: serde_json::Value) -> Result<(), String> {
+    let restored: Accumulator = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    if let Ok(mut acc) = current().lock() {
+        *acc = restored;
+    }
+    save();
+    Ok(())
+}
+
+/// Persist the running period.
+pub fn save() {
+    let Ok(acc) = current().lock().map(|a| a.clone()) else {
+        return;
+    };
+    let path = state_path();
+    match serde_json::to_string(&acc) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("reports: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("reports: failed to serialize report state: {}", e),
+    }
+}
+
+/// Full-charge capacity as a percentage of design capacity, when the battery reports both.
+pub async fn battery_health_pct(cli: &crate::cli::FrameworkTool) -> Option<f32> {
+    let p = cli.power_with_fallback().await.ok()?;
+    let full = p.last_full_charge_capacity_mah? as f32;
+    let design = p.design_capacity_mah.filter(|d| *d > 0)? as f32;
+    Some((full / design * 1000.0).round() / 10.0)
+}
+
+fn unix_time_ms() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ts_ms: i64, temp: i32, rpm: u32) -> TelemetrySample {
+        TelemetrySample {
+            ts_ms,
+            temps: BTreeMap::from([("APU".to_string(), temp)]),
+            rpms: vec![rpm],
+            duty_pct: None,
+            power_rails: None,
+        }
+    }
+
+    #[test]
+    fn accumulates_fan_time_and_throttle_events() {
+        let cfg = ReportsConfig::default();
+        let mut acc = Accumulator::new(0);
+        // 1s samples: loud and throttling, a dip below the reset band, throttling again,
+        // then a long gap that must not count as fan time.
+        for (ts, temp, rpm) in [
+            (0, 96, 4000),
+            (1000, 97, 4000),
+            (2000, 90, 4000),
+            (3000, 95, 2000),
+            (60_000, 60, 4000),
+        ] {
+            acc.record(&sample(ts, temp, rpm), &cfg);
+        }
+        assert_eq!(acc.throttle_events, 2);
+        assert_eq!(acc.fan_ms_above, 2000);
+        assert_eq!(acc.max_temps["APU"], 97);
+        let device = DeviceIdentity {
+            device_id: "test".into(),
+            model: Default::default(),
+            mainboard_type: None,
+        };
+        let report = acc.report(60_000, &cfg, &UiConfig::default(), None, device);
+        assert_eq!(report.avg_temps["APU"], 87.6);
+    }
+}
@@ -0,0 +1,67 @@
+// Request IDs for correlating a bug report with the service log. The HTTP middleware in `main.rs`
+// takes the caller's `X-Request-Id` (when it looks like an ID) or assigns one, runs the request
+// with it in scope, echoes it back in the `X-Request-Id` response header and logs one structured
+// access line. Error envelopes built while handling the request carry it too.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+pub const HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// ID of the request being handled by this task, if any.
+pub fn current() -> Option<String> {
+    CURRENT.try_with(|id| id.clone()).ok()
+}
+
+/// Run `fut` with `id` as the current request ID.
+pub async fn scope<F: std::future::Future>(id: String, fut: F) -> F::Output {
+    CURRENT.scope(id, fut).await
+}
+
+/// The caller's ID when it is a plausible token, else a fresh one.
+pub fn from_header(value: Option<&str>) -> String {
+    match value.map(str::trim) {
+        Some(v) if is_valid(v) => v.to_string(),
+        _ => next(),
+    }
+}
+
+fn is_valid(id: &str) -> bool {
+    (1..=64).contains(&id.len())
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+/// `<start time>-<sequence>` in hex: unique across restarts, sortable within one.
+fn next() -> String {
+    static START: OnceLock<u64> = OnceLock::new();
+    static SEQ: AtomicU64 = AtomicU64::new(1);
+    let start = *START.get_or_init(|| {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    });
+    format!("{:x}-{:06x}", start, SEQ.fetch_add(1, Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_plausible_caller_ids_only() {
+        assert_eq!(from_header(Some(" abc-123_x.y ")), "abc-123_x.y");
+        let generated = from_header(Some("bad id\nwith newline"));
+        assert_ne!(generated, "bad id\nwith newline");
+        assert!(is_valid(&generated));
+        assert_ne!(from_header(None), from_header(None));
+        assert!(!is_valid(&"a".repeat(65)));
+    }
+}
@@ -0,0 +1,457 @@
+// Admin endpoints: config reads and writes, temporary overrides, backups, updates and logs.
+
+use super::*;
+use crate::types::Empty;
+use crate::update::check_and_apply_now;
+use poem_openapi::param::Query;
+
+pub struct AdminApi;
+
+#[OpenApi(tag = "ApiTags::Admin")]
+impl AdminApi {
+    /// Update: apply latest by downloading MSI and invoking msiexec (Windows only). Needs
+    /// `confirm=true`: the service restarts during the install
+    #[oai(path = "/update/apply", method = "post", operation_id = "applyUpdate")]
+    async fn apply_update(
+        &self,
+        /// The user has seen the warning and agreed
+        confirm: Query<Option<bool>>,
+    ) -> ApiResult<Empty> {
+        if confirm.0 != Some(true) {
+            return Err(confirmation_required(
+                "the latest release will be installed; the service restarts and fan and power control pause meanwhile"
+                    .to_string(),
+            ));
+        }
+        match check_and_apply_now().await {
+            Ok(_applied) => Ok(Json(Empty {})),
+            Err(e) => {
+                error!("apply update failed: {}", e);
+                Err(bad_gateway("apply_failed", e))
+            }
+        }
+    }
+
+    /// Get config
+    #[oai(path = "/config", method = "get", operation_id = "getConfig")]
+    async fn get_config(&self, state: Data<&AppState>) -> ApiResult<crate::types::Config> {
+        let cfg = state.config.read().await.clone();
+        Ok(Json(cfg))
+    }
+
+    /// Export the saved config (temporary overrides excluded) with this device's identity, for
+    /// `POST /config/import` here or on another machine
+    #[oai(path = "/config/export", method = "get", operation_id = "exportConfig")]
+    async fn export_config(&self, state: Data<&AppState>) -> ApiResult<crate::types::ConfigBundle> {
+        let saved = crate::temporary::persisted(&*state.config.read().await);
+        Ok(Json(crate::device::bundle(&saved)))
+    }
+
+    /// Import a bundle from `GET /config/export`, replacing the sections it contains. The current
+    /// config is backed up under `backups/<device_id>/` first. A bundle from a different Framework
+    /// model is applied without its model-specific settings (`power`, `sensors`, fan calibrations,
+    /// per-fan overrides and the dGPU fan) unless `force=true`; the result lists what was skipped
+    #[oai(path = "/config/import", method = "post", operation_id = "importConfig")]
+    async fn import_config(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::ConfigImportRequest>,
+        /// Apply model-specific settings from a different model too
+        force: Query<Option<bool>>,
+        /// Needed to turn the charge limit off
+        confirm: Query<Option<bool>>,
+    ) -> ApiResult<crate::types::ConfigImportResult> {
+        let crate::types::ConfigImportRequest { device, config } = req.0;
+        let (force, confirm) = (force.0 == Some(true), confirm.0 == Some(true));
+        import_partial(&state, device, config, force, confirm, "config import")
+            .await
+            .map(Json)
+    }
+
+    /// Nightly backup archive (`backup.dir`, default `backups` next to the config): this
+    /// device's snapshots and those of earlier installations, newest first
+    #[oai(path = "/backups", method = "get", operation_id = "listBackups")]
+    async fn list_backups(&self, state: Data<&AppState>) -> ApiResult<crate::types::BackupList> {
+        let cfg = state.config.read().await.backup.clone();
+        Ok(Json(crate::backups::list(&cfg)))
+    }
+
+    /// Take a snapshot now (saved config, fan hours, charging history and report period), also
+    /// when nightly backups are off; counts towards `backup.retention`
+    #[oai(path = "/backups", method = "post", operation_id = "createBackup")]
+    async fn create_backup(&self, state: Data<&AppState>) -> ApiResult<crate::types::BackupEntry> {
+        let (cfg, saved) = {
+            let c = state.config.read().await;
+            (c.backup.clone(), crate::temporary::persisted(&c))
+        };
+        match crate::backups::create(&cfg, &saved) {
+            Ok(entry) => {
+                info!("backup: wrote {} on request", entry.name);
+                Ok(Json(entry))
+            }
+            Err(e) => {
+                error!("backup failed: {}", e);
+                Err(bad_gateway("backup_failed", e))
+            }
+        }
+    }
+
+    /// Restore a snapshot from `GET /backups`. The config part is applied like `POST
+    /// /config/import` (current config backed up first, a different model's settings skipped
+    /// unless `force`); the history part replaces the fan hours, charging history and report
+    /// period. 422 `invalid_request` for an unknown snapshot
+    #[oai(path = "/backups/restore", method = "post", operation_id = "restoreBackup")]
+    async fn restore_backup(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::BackupRestoreRequest>,
+        /// Needed when the restored config turns the charge limit off
+        confirm: Query<Option<bool>>,
+    ) -> ApiResult<crate::types::BackupRestoreResult> {
+        let req = req.0;
+        let cfg = state.config.read().await.backup.clone();
+        let invalid = |field: &str, message: String| {
+            ApiErrorResponse::UnprocessableEntity(Json(crate::types::ValidationErrorEnvelope {
+                code: "invalid_request".into(),
+                message: format!("{}: {}", field, message),
+                fields: vec![crate::types::FieldError {
+                    field: field.into(),
+                    message,
+                }],
+                request_id: crate::request_id::current(),
+            }))
+        };
+        if !crate::backups::is_device_id(&req.device_id) {
+            return Err(invalid("device_id", "not a device ID".to_string()));
+        }
+        let snapshot = crate::backups::read(&cfg, &req.device_id, &req.name).map_err(|e| invalid("name", e))?;
+
+        let config = if req.config != Some(false) {
+            let partial: PartialConfig =
+                serde_json::from_value(snapshot.config).map_err(|e| invalid("config", e.to_string()))?;
+            let what = format!("backup restore ({})", req.name);
+            let (force, confirm) = (req.force == Some(true), confirm.0 == Some(true));
+            Some(import_partial(&state, Some(snapshot.device), partial, force, confirm, &what).await?)
+        } else {
+            None
+        };
+        let (history, warnings) = if req.history != Some(false) {
+            crate::backups::restore_history(snapshot.history)
+        } else {
+            Default::default()
+        };
+        for w in &warnings {
+            warn!("backup restore: {}", w);
+        }
+        if !history.is_empty() {
+            info!(
+                "backup restore: history restored from {} ({})",
+                req.name,
+                history.join(", ")
+            );
+        }
+        Ok(Json(crate::types::BackupRestoreResult {
+            config,
+            history,
+            warnings,
+        }))
+    }
+
+    /// Reload `config.json` after another program (Ansible, Nix, ...) rewrote it. Every section
+    /// is validated as a write would be; the sections that differ from the running config are
+    /// applied and temporary overrides of them end. A file that does not parse (422
+    /// `unreadable_config`) or fails validation (422 `invalid_config`) changes nothing. The file
+    /// is not rewritten and its `revision` is ignored. With `dry_run=true` nothing is applied and
+    /// the response lists the planned hardware writes. The outcome is kept as `last_reload` in
+    /// `/health`
+    #[oai(path = "/config/reload", method = "post", operation_id = "reloadConfig")]
+    async fn reload_config(
+        &self,
+        state: Data<&AppState>,
+        /// Validate and report what would change, without applying anything
+        dry_run: Query<Option<bool>>,
+    ) -> ApiResult<crate::types::ConfigReloadResult> {
+        let _writer = config::WRITE_LOCK.lock().await;
+        let running = state.config.read().await.clone();
+        let rejected = |code: &str, fields: Vec<crate::types::FieldError>| {
+            crate::config_reload::record(crate::types::ConfigReloadStatus {
+                at_ms: chrono::Utc::now().timestamp_millis(),
+                ok: false,
+                revision: running.revision,
+                changed: Vec::new(),
+                errors: fields.clone(),
+            });
+            ApiErrorResponse::UnprocessableEntity(Json(crate::types::ValidationErrorEnvelope {
+                code: code.into(),
+                message: format!(
+                    "config file rejected ({} problem(s)); nothing was applied",
+                    fields.len()
+                ),
+                fields,
+                request_id: crate::request_id::current(),
+            }))
+        };
+        let file = match crate::config_reload::read(&config::config_path()) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                warn!("config reload: {}", e);
+                let field = crate::types::FieldError {
+                    field: String::new(),
+                    message: e,
+                };
+                return Err(rejected(crate::config_reload::CODE_UNREADABLE, vec![field]));
+            }
+        };
+        let touched = crate::config_reload::changed_sections(&crate::temporary::persisted(&running), &file);
+        if let Err(ApiErrorResponse::UnprocessableEntity(Json(envelope))) =
+            validate_sections(&state, &file, &crate::config_reload::SECTIONS, Default::default()).await
+        {
+            return Err(rejected(crate::config_reload::CODE_INVALID, envelope.fields));
+        }
+
+        let changed: Vec<String> = touched.iter().map(|s| s.to_string()).collect();
+        if dry_run.0 == Some(true) {
+            let new = crate::temporary::overlay(file, &running, &touched);
+            let changes = crate::dry_run::config_changes(&state, &running, &new, &touched).await;
+            return Ok(Json(crate::types::ConfigReloadResult {
+                revision: running.revision,
+                changed,
+                dry_run: Some(changes),
+            }));
+        }
+        // A file that parses replaces whatever the startup check found wrong with the old one
+        crate::config_check::clear_section("");
+        let revision = if touched.is_empty() {
+            running.revision
+        } else {
+            apply_config(&state, &running, file, &touched).await
+        };
+        info!(
+            "config reloaded from file (revision {}, changed: {})",
+            revision,
+            if changed.is_empty() {
+                "none".to_string()
+            } else {
+                changed.join(", ")
+            }
+        );
+        crate::config_reload::record(crate::types::ConfigReloadStatus {
+            at_ms: chrono::Utc::now().timestamp_millis(),
+            ok: true,
+            revision,
+            changed: changed.clone(),
+            errors: Vec::new(),
+        });
+        Ok(Json(crate::types::ConfigReloadResult {
+            revision,
+            changed,
+            dry_run: None,
+        }))
+    }
+
+    /// Set config (partial). `revision` must match the current config revision (optimistic
+    /// concurrency), so concurrent editors cannot silently overwrite each other. With
+    /// `dry_run=true` nothing is saved and the response lists the planned hardware writes.
+    /// Turning the charge limit off needs `confirm=true`.
+    #[oai(path = "/config", method = "post", operation_id = "setConfig")]
+    async fn set_config(
+        &self,
+        state: Data<&AppState>,
+        req: Json<PartialConfig>,
+        /// Validate and report the hardware writes the change would cause, without saving or
+        /// applying anything
+        dry_run: Query<Option<bool>>,
+        /// Needed to turn the charge limit off
+        confirm: Query<Option<bool>>,
+    ) -> ApiResult<crate::types::ConfigWriteResult> {
+        let req = req.0;
+        // Serialize writers so the revision check and the save happen atomically
+        let _writer = config::WRITE_LOCK.lock().await;
+        let touched = touched_sections(&req);
+        let running = state.config.read().await.clone();
+        let current_revision = running.revision;
+        check_revision(req.revision, current_revision).map_err(|(code, message)| conflict(code, message))?;
+        let mut errs = crate::validation::Errors::default();
+        crate::validation::managed_profiles(&mut errs, &req);
+        // Merge onto the saved values: a permanent write ends a temporary override of its sections
+        let mut merged = crate::temporary::persisted(&running);
+        merge_partial(&mut merged, req);
+        validate_sections(&state, &merged, &touched, errs).await?;
+
+        if dry_run.0 == Some(true) {
+            let new = crate::temporary::overlay(merged, &running, &touched);
+            let changes = crate::dry_run::config_changes(&state, &running, &new, &touched).await;
+            info!("set_config dry run: {} planned change(s)", changes.len());
+            return Ok(Json(crate::types::ConfigWriteResult {
+                revision: current_revision,
+                dry_run: Some(changes),
+            }));
+        }
+        if let Some(warning) = charge_limit_warning(&running, &merged) {
+            if confirm.0 != Some(true) {
+                return Err(confirmation_required(warning));
+            }
+        }
+
+        let revision = commit_config(&state, &running, merged, &touched).await?;
+        info!("set_config applied successfully (revision {})", revision);
+        Ok(Json(crate::types::ConfigWriteResult {
+            revision,
+            dry_run: None,
+        }))
+    }
+
+    /// Apply a partial config for `ttl_s` seconds without saving it (e.g. "try 35 W TDP for an
+    /// hour"); the overridden sections revert to their saved values when the TTL runs out or the
+    /// service restarts. A permanent `POST /config` to a section ends its override
+    #[oai(path = "/config/temporary", method = "post", operation_id = "setTemporaryConfig")]
+    async fn set_temporary_config(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::TemporaryConfigRequest>,
+        /// Needed to turn the charge limit off
+        confirm: Query<Option<bool>>,
+    ) -> ApiResult<crate::types::TemporaryOverrides> {
+        let crate::types::TemporaryConfigRequest { ttl_s, patch } = req.0;
+        let _writer = config::WRITE_LOCK.lock().await;
+        let touched = touched_sections(&patch);
+        let running = state.config.read().await.clone();
+        check_revision(patch.revision, running.revision).map_err(|(code, message)| conflict(code, message))?;
+        let mut errs = crate::validation::Errors::default();
+        crate::validation::managed_profiles(&mut errs, &patch);
+        let mut merged = running.clone();
+        merge_partial(&mut merged, patch);
+        crate::validation::temporary(&mut errs, ttl_s, &touched);
+        validate_sections(&state, &merged, &touched, errs).await?;
+        if let Some(warning) = charge_limit_warning(&running, &merged) {
+            if confirm.0 != Some(true) {
+                return Err(confirmation_required(warning));
+            }
+        }
+
+        crate::temporary::begin(&running, &touched, ttl_s);
+        merged.revision = running.revision + 1;
+        if touched.contains(&"logging") {
+            crate::log_forward::apply(&merged.logging);
+            crate::error_reports::apply(&merged.logging);
+        }
+        let revision = merged.revision;
+        {
+            let mut w = state.config.write().await;
+            *w = merged;
+        }
+        info!(
+            "temporary override of {} for {}s (revision {})",
+            touched.join(", "),
+            ttl_s,
+            revision
+        );
+        Ok(Json(crate::types::TemporaryOverrides {
+            revision,
+            overrides: crate::temporary::active(),
+        }))
+    }
+
+    /// Active temporary overrides and when each reverts
+    #[oai(path = "/config/temporary", method = "get", operation_id = "getTemporaryConfig")]
+    async fn get_temporary_config(&self, state: Data<&AppState>) -> ApiResult<crate::types::TemporaryOverrides> {
+        Ok(Json(crate::types::TemporaryOverrides {
+            revision: state.config.read().await.revision,
+            overrides: crate::temporary::active(),
+        }))
+    }
+
+    /// Revert every temporary override now
+    #[oai(
+        path = "/config/temporary",
+        method = "delete",
+        operation_id = "revertTemporaryConfig"
+    )]
+    async fn revert_temporary_config(&self, state: Data<&AppState>) -> ApiResult<crate::types::TemporaryOverrides> {
+        crate::temporary::revert(&state.config, true).await;
+        Ok(Json(crate::types::TemporaryOverrides {
+            revision: state.config.read().await.revision,
+            overrides: crate::temporary::active(),
+        }))
+    }
+
+    /// Opt-in error reports kept on this machine (panics and repeated errors), newest first
+    #[oai(path = "/error-reports", method = "get", operation_id = "getErrorReports")]
+    async fn get_error_reports(&self) -> ApiResult<Vec<crate::types::ErrorReport>> {
+        Ok(Json(crate::error_reports::list()))
+    }
+
+    /// Logs: retrieve recent service logs
+    #[oai(path = "/logs", method = "get", operation_id = "getLogs")]
+    async fn get_logs(&self) -> Result<poem_openapi::payload::PlainText<String>, ApiErrorResponse> {
+        match get_service_logs().await {
+            Ok(logs) => Ok(poem_openapi::payload::PlainText(logs)),
+            Err(e) => {
+                error!("Failed to retrieve logs: {}", e);
+                Err(bad_gateway("logs_failed", e))
+            }
+        }
+    }
+}
+
+pub(super) const SCOPES: &[(&str, AuthScope)] = &[
+    ("applyUpdate", AuthScope::Admin),
+    ("getConfig", AuthScope::Read),
+    ("exportConfig", AuthScope::Admin),
+    ("importConfig", AuthScope::Admin),
+    ("listBackups", AuthScope::Admin),
+    ("createBackup", AuthScope::Admin),
+    ("restoreBackup", AuthScope::Admin),
+    ("reloadConfig", AuthScope::Admin),
+    ("setConfig", AuthScope::Admin),
+    ("setTemporaryConfig", AuthScope::Control),
+    ("getTemporaryConfig", AuthScope::Read),
+    ("revertTemporaryConfig", AuthScope::Control),
+    ("getErrorReports", AuthScope::Admin),
+    ("getLogs", AuthScope::Admin),
+];
+
+async fn get_service_logs() -> Result<String, String> {
+    #[cfg(target_os = "linux")]
+    {
+        use tokio::process::Command;
+        let output = Command::new("journalctl")
+            .arg("-u")
+            .arg("framework-control")
+            .arg("-n")
+            .arg("500")
+            .arg("--no-pager")
+            .output()
+            .await
+            .map_err(|e| format!("failed to run journalctl: {}", e))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(format!(
+                "journalctl failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Read FrameworkControlService.out.log from the service directory
+        let exe = std::env::current_exe().map_err(|e| format!("failed to get current exe path: {}", e))?;
+        let dir = exe.parent().ok_or_else(|| "failed to get exe directory".to_string())?;
+        let log_path = dir.join("FrameworkControlService.out.log");
+
+        let contents = std::fs::read_to_string(&log_path).map_err(|e| format!("failed to read log file: {}", e))?;
+
+        // Return last 500 lines (approximate)
+        let lines: Vec<&str> = contents.lines().collect();
+        let start = lines.len().saturating_sub(500);
+        Ok(lines[start..].join("\n"))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        Err("log retrieval not supported on this platform".to_string())
+    }
+}
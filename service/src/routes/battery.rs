@@ -0,0 +1,62 @@
+// Battery endpoints: charge sessions and storage mode.
+
+use super::*;
+
+pub struct BatteryApi;
+
+#[OpenApi(tag = "ApiTags::Battery")]
+impl BatteryApi {
+    /// Charge/discharge sessions (SoC range, duration, energy, power profile) logged by the service
+    #[oai(path = "/battery/sessions", method = "get", operation_id = "getBatterySessions")]
+    async fn get_battery_sessions(&self) -> ApiResult<crate::types::BatterySessionsResponse> {
+        Ok(Json(crate::battery_sessions::snapshot()))
+    }
+
+    /// Storage mode settings and where the battery stands in its band
+    #[oai(path = "/battery/storage-mode", method = "get", operation_id = "getStorageMode")]
+    async fn get_storage_mode(&self, state: Data<&AppState>) -> ApiResult<crate::types::StorageModeStatus> {
+        let cfg = state.config.read().await;
+        Ok(Json(storage_mode_status(&cfg.battery, cfg.revision)))
+    }
+
+    /// Turn storage mode on or off: the charge limit holds the battery around `target_pct`
+    /// (default 60%), charge-rate boosts are off and the charge is topped up when it falls out of
+    /// the band. Saved like a `POST /config` write of `battery.storage_mode`
+    #[oai(path = "/battery/storage-mode", method = "post", operation_id = "setStorageMode")]
+    async fn set_storage_mode(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::StorageModeRequest>,
+    ) -> ApiResult<crate::types::StorageModeStatus> {
+        let req = req.0;
+        let _writer = config::WRITE_LOCK.lock().await;
+        let running = state.config.read().await.clone();
+        let mut merged = crate::temporary::persisted(&running);
+        let storage = merged.battery.storage_mode.get_or_insert_with(Default::default);
+        storage.enabled = req.enabled;
+        if let Some(pct) = req.target_pct {
+            storage.target_pct = pct;
+        }
+        if let Some(pct) = req.band_pct {
+            storage.band_pct = pct;
+        }
+        let touched = ["battery"];
+        validate_sections(&state, &merged, &touched, Default::default()).await?;
+        let battery = merged.battery.clone();
+        let revision = commit_sections(&state, &running, merged, &touched)
+            .await
+            .map_err(|e| bad_gateway("save_failed", e))?;
+        info!(
+            "battery storage mode {} (revision {})",
+            if req.enabled { "on" } else { "off" },
+            revision
+        );
+        Ok(Json(storage_mode_status(&battery, revision)))
+    }
+}
+
+pub(super) const SCOPES: &[(&str, AuthScope)] = &[
+    ("getBatterySessions", AuthScope::Read),
+    ("getStorageMode", AuthScope::Read),
+    ("setStorageMode", AuthScope::Control),
+];
@@ -0,0 +1,552 @@
+// Fan endpoints: profiles, overrides, the auto-tuner, contention, hour-meters, calibration
+// and curve tools (fit, compare and the tuning wizard).
+
+use super::*;
+
+pub struct FanApi;
+
+#[OpenApi(tag = "ApiTags::Fan")]
+impl FanApi {
+    /// Named fan profiles and the active one
+    #[oai(path = "/fan/profiles", method = "get", operation_id = "getFanProfiles")]
+    async fn get_fan_profiles(&self, state: Data<&AppState>) -> ApiResult<crate::types::FanProfiles> {
+        let cfg = state.config.read().await;
+        Ok(Json(crate::fan_profiles::list(&cfg.fan, cfg.revision)))
+    }
+
+    /// Switch to a named fan profile: its curve becomes `fan.curve` in curve mode. Saved like a
+    /// `POST /config` write of the `fan` section; 422 for an unknown name
+    #[oai(path = "/fan/profile/activate", method = "post", operation_id = "activateFanProfile")]
+    async fn activate_fan_profile(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::FanProfileActivateRequest>,
+    ) -> ApiResult<crate::types::FanProfiles> {
+        let _writer = config::WRITE_LOCK.lock().await;
+        let running = state.config.read().await.clone();
+        let mut merged = crate::temporary::persisted(&running);
+        crate::fan_profiles::activate(&mut merged.fan, &req.0.name);
+        let touched = ["fan"];
+        validate_sections(&state, &merged, &touched, Default::default()).await?;
+        let fan = merged.fan.clone();
+        let revision = commit_sections(&state, &running, merged, &touched)
+            .await
+            .map_err(|e| bad_gateway("save_failed", e))?;
+        info!("fan profile '{}' active (revision {})", req.0.name, revision);
+        Ok(Json(crate::fan_profiles::list(&fan, revision)))
+    }
+
+    /// Fan boost in effect, if any
+    #[oai(path = "/fan/override", method = "get", operation_id = "getFanOverride")]
+    async fn get_fan_override(&self, state: Data<&AppState>) -> ApiResult<crate::types::FanOverrideStatus> {
+        Ok(Json(fan_override_status(&*state.config.read().await)))
+    }
+
+    /// Fan boost: hold every fan at `duty_pct` for `duration_s` seconds without editing the
+    /// config, then return to the configured fan mode. Applied as a temporary override of the
+    /// `fan` section (see `POST /config/temporary`), so a permanent fan write or a restart ends
+    /// it; a new boost replaces the duty and restarts the countdown
+    #[oai(path = "/fan/override", method = "post", operation_id = "setFanOverride")]
+    async fn set_fan_override(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::FanOverrideRequest>,
+    ) -> ApiResult<crate::types::FanOverrideStatus> {
+        let req = req.0;
+        let mut errs = crate::validation::Errors::default();
+        crate::validation::fan_override(&mut errs, &req);
+        if let Err(fields) = errs.into_result() {
+            return Err(ApiErrorResponse::UnprocessableEntity(Json(
+                crate::types::ValidationErrorEnvelope {
+                    code: "invalid_request".into(),
+                    message: format!("{} invalid field(s)", fields.len()),
+                    fields,
+                    request_id: crate::request_id::current(),
+                },
+            )));
+        }
+        let _writer = config::WRITE_LOCK.lock().await;
+        let running = state.config.read().await.clone();
+        crate::temporary::begin(&running, &["fan"], req.duration_s);
+        let mut next = running.clone();
+        // Per-fan overrides and duties would keep some fans off the boost
+        next.fan.mode = Some(crate::types::FanControlMode::Manual);
+        next.fan.manual = Some(crate::types::ManualConfig {
+            duty_pct: req.duty_pct,
+            fan_duties: None,
+        });
+        next.fan.overrides = None;
+        next.revision = running.revision + 1;
+        if crate::tasks::fan_contention::backed_off() {
+            crate::tasks::fan_contention::reassert();
+        }
+        let status = fan_override_status(&next);
+        {
+            let mut w = state.config.write().await;
+            *w = next;
+        }
+        crate::tasks::fan_curve::request_reapply();
+        info!(
+            "fan boost to {}% for {}s (revision {})",
+            req.duty_pct, req.duration_s, status.revision
+        );
+        Ok(Json(status))
+    }
+
+    /// End the fan boost now: the configured fan mode returns
+    #[oai(path = "/fan/override", method = "delete", operation_id = "clearFanOverride")]
+    async fn clear_fan_override(&self, state: Data<&AppState>) -> ApiResult<crate::types::FanOverrideStatus> {
+        crate::temporary::revert_section(&state.config, "fan").await;
+        Ok(Json(fan_override_status(&*state.config.read().await)))
+    }
+
+    /// Curve auto-tuner (`fan.auto_tune`): run progress, the proposed curve and the history of
+    /// trials and decisions
+    #[oai(path = "/fan/auto-tune", method = "get", operation_id = "getFanAutoTune")]
+    async fn get_fan_auto_tune(&self, state: Data<&AppState>) -> ApiResult<crate::types::AutoTuneStatus> {
+        Ok(Json(crate::fan_auto_tune::status(&*state.config.read().await)))
+    }
+
+    /// Save the proposed curve as `fan.curve` and turn the tuner off, like a `POST /config` write
+    /// of the `fan` section. 409 `no_proposal` without one, `stale_proposal` when the saved curve
+    /// is no longer the one the run started from
+    #[oai(path = "/fan/auto-tune/accept", method = "post", operation_id = "acceptFanAutoTune")]
+    async fn accept_fan_auto_tune(&self, state: Data<&AppState>) -> ApiResult<crate::types::AutoTuneStatus> {
+        let Some(proposal) = crate::fan_auto_tune::proposal() else {
+            return Err(conflict("no_proposal", "the auto-tuner has no curve to propose".into()));
+        };
+        let _writer = config::WRITE_LOCK.lock().await;
+        let running = state.config.read().await.clone();
+        let mut merged = crate::temporary::persisted(&running);
+        let Some(curve) = merged
+            .fan
+            .curve
+            .as_mut()
+            .filter(|c| c.curve.points == proposal.baseline_points)
+        else {
+            return Err(conflict(
+                "stale_proposal",
+                "fan.curve changed since the tuning run; dismiss the proposal".into(),
+            ));
+        };
+        curve.curve.points = proposal.points.clone();
+        merged.fan.active_profile = None;
+        if let Some(t) = merged.fan.auto_tune.as_mut() {
+            t.enabled = false;
+        }
+        let touched = ["fan"];
+        validate_sections(&state, &merged, &touched, Default::default()).await?;
+        let revision = commit_sections(&state, &running, merged, &touched)
+            .await
+            .map_err(|e| bad_gateway("save_failed", e))?;
+        crate::fan_auto_tune::accepted();
+        info!("auto-tuned fan curve saved (revision {})", revision);
+        Ok(Json(crate::fan_auto_tune::status(&*state.config.read().await)))
+    }
+
+    /// Drop the proposed curve; 409 `no_proposal` without one
+    #[oai(
+        path = "/fan/auto-tune/proposal",
+        method = "delete",
+        operation_id = "dismissFanAutoTune"
+    )]
+    async fn dismiss_fan_auto_tune(&self, state: Data<&AppState>) -> ApiResult<crate::types::AutoTuneStatus> {
+        if !crate::fan_auto_tune::dismiss() {
+            return Err(conflict("no_proposal", "the auto-tuner has no curve to propose".into()));
+        }
+        Ok(Json(crate::fan_auto_tune::status(&*state.config.read().await)))
+    }
+
+    /// dGPU module fan: backend in use, RPM, duty and the GPU temperature its curve follows
+    #[oai(path = "/fan/gpu", method = "get", operation_id = "getGpuFan")]
+    async fn get_gpu_fan(&self, state: Data<&AppState>) -> ApiResult<crate::types::GpuFanStatus> {
+        let cli = { state.framework_tool.read().await.clone() };
+        let (cfg, zones) = {
+            let c = state.config.read().await;
+            (c.fan.gpu.clone().unwrap_or_default(), c.sensors.zones.clone())
+        };
+        Ok(Json(
+            crate::tasks::fan_curve::gpu_fan_status(cli.as_ref(), &cfg, &zones).await,
+        ))
+    }
+
+    /// Fan control contention: whether another program appears to be driving the fans, and
+    /// whether the service has backed off
+    #[oai(path = "/fan/contention", method = "get", operation_id = "getFanContention")]
+    async fn get_fan_contention(&self, state: Data<&AppState>) -> ApiResult<crate::types::FanContentionStatus> {
+        let fan = state.config.read().await.fan.clone();
+        Ok(Json(crate::tasks::fan_contention::status(&fan)))
+    }
+
+    /// Resolve fan control contention: back off (leave the fans to the other program) or
+    /// re-assert the configured fan mode
+    #[oai(path = "/fan/contention", method = "post", operation_id = "resolveFanContention")]
+    async fn resolve_fan_contention(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::FanContentionRequest>,
+    ) -> ApiResult<crate::types::FanContentionStatus> {
+        match req.0.action {
+            crate::types::FanContentionAction::BackOff => crate::tasks::fan_contention::back_off(),
+            crate::types::FanContentionAction::Reassert => crate::tasks::fan_contention::reassert(),
+        }
+        let fan = state.config.read().await.fan.clone();
+        Ok(Json(crate::tasks::fan_contention::status(&fan)))
+    }
+
+    /// Fan hour-meters: run time and duty-weighted run time per fan, cleaning reminders and RPM
+    /// anomalies
+    #[oai(path = "/fan/stats", method = "get", operation_id = "getFanStats")]
+    async fn get_fan_stats(&self, state: Data<&AppState>) -> ApiResult<crate::types::FanStatsResponse> {
+        let maintenance_hours = state.config.read().await.fan.maintenance_hours;
+        Ok(Json(crate::fan_hours::snapshot(maintenance_hours)))
+    }
+
+    /// Former path of `GET /fan/stats`
+    #[oai(
+        path = "/fans/stats",
+        method = "get",
+        operation_id = "getFanStatsDeprecated",
+        deprecated
+    )]
+    async fn get_fan_stats_deprecated(&self, state: Data<&AppState>) -> ApiResult<crate::types::FanStatsResponse> {
+        self.get_fan_stats(state).await
+    }
+
+    /// Mark a fan (or all fans) as cleaned; restarts its hours-since-service count and relearns its
+    /// RPM baseline
+    #[oai(path = "/fan/stats/reset", method = "post", operation_id = "resetFanStats")]
+    async fn reset_fan_stats(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::FanServiceRequest>,
+    ) -> ApiResult<crate::types::FanStatsResponse> {
+        crate::fan_hours::mark_serviced(req.0.fan_index);
+        crate::fan_anomaly::relearn(req.0.fan_index);
+        let maintenance_hours = state.config.read().await.fan.maintenance_hours;
+        Ok(Json(crate::fan_hours::snapshot(maintenance_hours)))
+    }
+
+    /// Former path of `POST /fan/stats/reset`
+    #[oai(
+        path = "/fans/stats/reset",
+        method = "post",
+        operation_id = "resetFanStatsDeprecated",
+        deprecated
+    )]
+    async fn reset_fan_stats_deprecated(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::FanServiceRequest>,
+    ) -> ApiResult<crate::types::FanStatsResponse> {
+        self.reset_fan_stats(state, req).await
+    }
+
+    /// Diagnostics: step each fan from a low to a high duty and measure spin-up latency and
+    /// overshoot; results feed curves with `auto_rate_limit`
+    #[oai(path = "/diagnostics/fan-ramp", method = "post", operation_id = "runFanRampTest")]
+    async fn run_fan_ramp_test(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::FanRampRequest>,
+    ) -> ApiResult<crate::types::FanRampReport> {
+        let mut errs = crate::validation::Errors::default();
+        crate::validation::fan_ramp(&mut errs, &req.0);
+        if let Err(fields) = errs.into_result() {
+            return Err(ApiErrorResponse::UnprocessableEntity(Json(
+                crate::types::ValidationErrorEnvelope {
+                    code: "invalid_request".into(),
+                    message: format!("{} invalid field(s)", fields.len()),
+                    fields,
+                    request_id: crate::request_id::current(),
+                },
+            )));
+        }
+        let cli = require_framework_tool_async(&state).await?;
+        let Some(_guard) = crate::diagnostics::RunGuard::try_acquire() else {
+            return Err(ApiErrorResponse::ServiceUnavailable(Json(
+                crate::types::ErrorEnvelope {
+                    code: "diagnostics_busy".into(),
+                    message: "another diagnostics run is in progress".into(),
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                },
+            )));
+        };
+        let fan_config = state.config.read().await.fan.clone();
+        match crate::diagnostics::fan_ramp::run(&cli, &fan_config, &req.0).await {
+            Ok(report) => Ok(Json(report)),
+            Err(e) => {
+                error!("fan ramp test failed: {}", e);
+                Err(bad_gateway("fan_ramp_failed", e))
+            }
+        }
+    }
+
+    /// Calibrate fans: per fan header (or only `fan_index`), step the duty from 100% down to 20%
+    /// and record the settled RPM. Tables are saved per fan in `fan.calibrations`; `fan.calibration`
+    /// is refreshed from the fastest fan. Takes about a minute per fan
+    #[oai(path = "/fan/calibrate", method = "post", operation_id = "calibrateFans")]
+    async fn calibrate_fans(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::FanCalibrateRequest>,
+    ) -> ApiResult<crate::types::FanCalibrateReport> {
+        let cli = require_framework_tool_async(&state).await?;
+        let Some(_guard) = crate::diagnostics::RunGuard::try_acquire() else {
+            return Err(ApiErrorResponse::ServiceUnavailable(Json(
+                crate::types::ErrorEnvelope {
+                    code: "diagnostics_busy".into(),
+                    message: "another diagnostics run is in progress".into(),
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                },
+            )));
+        };
+        match crate::diagnostics::fan_calibration::run(&cli, &state.config, req.0.fan_index).await {
+            Ok(report) => Ok(Json(report)),
+            Err(e) => {
+                error!("fan calibration failed: {}", e);
+                Err(bad_gateway("calibration_failed", e))
+            }
+        }
+    }
+
+    /// Compare two fan curves: run two `fan.profiles` back-to-back under the same load
+    /// (`settle_secs` idle then `duration_secs` of load each; the saved fan config is put back
+    /// afterwards), or compare two windows of the telemetry history. Reports average/max
+    /// temperature, fan time above an RPM threshold and an RPM-based noise estimate per curve
+    #[oai(path = "/fan/compare", method = "post", operation_id = "compareFanCurves")]
+    async fn compare_fan_curves(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::CurveCompareRequest>,
+    ) -> ApiResult<crate::types::CurveCompareReport> {
+        let (fan, zones, default_threshold) = {
+            let c = state.config.read().await;
+            (c.fan.clone(), c.sensors.zones.clone(), c.reports.fan_rpm_threshold)
+        };
+        let mut errs = crate::validation::Errors::default();
+        crate::validation::curve_compare(&mut errs, &req.0, &fan);
+        if let Err(fields) = errs.into_result() {
+            return Err(ApiErrorResponse::UnprocessableEntity(Json(
+                crate::types::ValidationErrorEnvelope {
+                    code: "invalid_request".into(),
+                    message: format!("{} invalid field(s)", fields.len()),
+                    fields,
+                    request_id: crate::request_id::current(),
+                },
+            )));
+        }
+        let threshold = req.0.fan_rpm_threshold.unwrap_or(default_threshold);
+
+        if let (Some(a), Some(b)) = (&req.0.window_a, &req.0.window_b) {
+            let samples: Vec<crate::types::TelemetrySample> = {
+                let r = state.telemetry_samples.read().await;
+                r.iter().cloned().collect()
+            };
+            return crate::diagnostics::curve_compare::compare_windows(
+                &samples,
+                a,
+                b,
+                &req.0.sensors,
+                &zones,
+                threshold,
+            )
+            .map(Json)
+            .map_err(|message| {
+                ApiErrorResponse::Conflict(Json(crate::types::ErrorEnvelope {
+                    code: "insufficient_telemetry".into(),
+                    message,
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                }))
+            });
+        }
+
+        let cli = require_framework_tool_async(&state).await?;
+        if crate::temporary::active().iter().any(|o| o.section == "fan") {
+            return Err(conflict(
+                "fan_override_active",
+                "a temporary fan override is active; revert it before comparing curves".into(),
+            ));
+        }
+        let Some(_guard) = crate::diagnostics::RunGuard::try_acquire() else {
+            return Err(ApiErrorResponse::ServiceUnavailable(Json(
+                crate::types::ErrorEnvelope {
+                    code: "diagnostics_busy".into(),
+                    message: "another diagnostics run is in progress".into(),
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                },
+            )));
+        };
+        match crate::diagnostics::curve_compare::run(&cli, &state.config, &req.0, threshold).await {
+            Ok(report) => Ok(Json(report)),
+            Err(e) => {
+                error!("curve comparison failed: {}", e);
+                Err(bad_gateway("curve_compare_failed", e))
+            }
+        }
+    }
+
+    /// Propose fan curve points from recent telemetry (temperature response to the applied duty)
+    /// that hold `target_max_temp_c` without exceeding `max_duty_pct`; nothing is saved
+    #[oai(path = "/fan/curve/fit", method = "post", operation_id = "fitFanCurve")]
+    async fn fit_fan_curve(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::CurveFitRequest>,
+    ) -> ApiResult<crate::types::CurveFitResult> {
+        let mut errs = crate::validation::Errors::default();
+        crate::validation::curve_fit(&mut errs, &req.0);
+        if let Err(fields) = errs.into_result() {
+            return Err(ApiErrorResponse::UnprocessableEntity(Json(
+                crate::types::ValidationErrorEnvelope {
+                    code: "invalid_request".into(),
+                    message: format!("{} invalid field(s)", fields.len()),
+                    fields,
+                    request_id: crate::request_id::current(),
+                },
+            )));
+        }
+        let samples: Vec<crate::types::TelemetrySample> = {
+            let r = state.telemetry_samples.read().await;
+            r.iter().cloned().collect()
+        };
+        crate::diagnostics::curve_fit::fit(&samples, &req.0)
+            .map(Json)
+            .map_err(|message| {
+                ApiErrorResponse::Conflict(Json(crate::types::ErrorEnvelope {
+                    code: "insufficient_telemetry".into(),
+                    message,
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                }))
+            })
+    }
+
+    /// Start the curve tuning wizard: under load, hold the fans at each of `duties` (highest
+    /// first) until the temperature settles, then propose the quietest curve that keeps the
+    /// sensors under `max_temp_c`. Returns at once; follow the run with `GET /fan/curve-wizard`.
+    /// 503 `diagnostics_busy` while another diagnostics run is in progress
+    #[oai(path = "/fan/curve-wizard", method = "post", operation_id = "startCurveWizard")]
+    async fn start_curve_wizard(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::CurveWizardRequest>,
+    ) -> ApiResult<crate::types::CurveWizardStatus> {
+        let mut errs = crate::validation::Errors::default();
+        crate::validation::curve_wizard(&mut errs, &req.0);
+        if let Err(fields) = errs.into_result() {
+            return Err(ApiErrorResponse::UnprocessableEntity(Json(
+                crate::types::ValidationErrorEnvelope {
+                    code: "invalid_request".into(),
+                    message: format!("{} invalid field(s)", fields.len()),
+                    fields,
+                    request_id: crate::request_id::current(),
+                },
+            )));
+        }
+        let cli = require_framework_tool_async(&state).await?;
+        let Some(guard) = crate::diagnostics::RunGuard::try_acquire() else {
+            return Err(ApiErrorResponse::ServiceUnavailable(Json(
+                crate::types::ErrorEnvelope {
+                    code: "diagnostics_busy".into(),
+                    message: "another diagnostics run is in progress".into(),
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                },
+            )));
+        };
+        Ok(Json(crate::diagnostics::curve_wizard::start(
+            cli,
+            state.config.clone(),
+            req.0,
+            guard,
+        )))
+    }
+
+    /// Curve tuning wizard: progress of the run, the measured steps and the proposed curve
+    #[oai(path = "/fan/curve-wizard", method = "get", operation_id = "getCurveWizard")]
+    async fn get_curve_wizard(&self) -> ApiResult<crate::types::CurveWizardStatus> {
+        Ok(Json(crate::diagnostics::curve_wizard::status()))
+    }
+
+    /// Stop the wizard run in progress; the fans go back to the configured mode and nothing is
+    /// proposed. 409 `not_running` without one
+    #[oai(
+        path = "/fan/curve-wizard/cancel",
+        method = "post",
+        operation_id = "cancelCurveWizard"
+    )]
+    async fn cancel_curve_wizard(&self) -> ApiResult<crate::types::CurveWizardStatus> {
+        if !crate::diagnostics::curve_wizard::cancel() {
+            return Err(conflict("not_running", "no curve wizard run is in progress".into()));
+        }
+        Ok(Json(crate::diagnostics::curve_wizard::status()))
+    }
+
+    /// Save the wizard's proposed curve as `fan.curve` (following the sensors it measured) and
+    /// switch to curve mode, like a `POST /config` write of the `fan` section. 409 `no_proposal`
+    /// without one
+    #[oai(path = "/fan/curve-wizard/apply", method = "post", operation_id = "applyCurveWizard")]
+    async fn apply_curve_wizard(&self, state: Data<&AppState>) -> ApiResult<crate::types::CurveWizardStatus> {
+        let Some(proposal) = crate::diagnostics::curve_wizard::status().proposal else {
+            return Err(conflict(
+                "no_proposal",
+                "the curve wizard has no curve to propose".into(),
+            ));
+        };
+        let _writer = config::WRITE_LOCK.lock().await;
+        let running = state.config.read().await.clone();
+        let mut merged = crate::temporary::persisted(&running);
+        let curve = merged.fan.curve.get_or_insert_with(|| crate::types::GlobalCurveConfig {
+            curve: crate::types::CurveConfig {
+                hysteresis_down_c: 2,
+                rate_limit_pct_per_step: 100,
+                ..Default::default()
+            },
+            poll_ms: 2000,
+        });
+        curve.curve.points = proposal.points.clone();
+        if !proposal.sensors.is_empty() {
+            curve.curve.sensors = proposal.sensors.clone();
+        }
+        merged.fan.mode = Some(crate::types::FanControlMode::Curve);
+        merged.fan.active_profile = None;
+        let touched = ["fan"];
+        validate_sections(&state, &merged, &touched, Default::default()).await?;
+        let revision = commit_sections(&state, &running, merged, &touched)
+            .await
+            .map_err(|e| bad_gateway("save_failed", e))?;
+        info!("curve wizard: proposed fan curve saved (revision {})", revision);
+        Ok(Json(crate::diagnostics::curve_wizard::status()))
+    }
+}
+
+pub(super) const SCOPES: &[(&str, AuthScope)] = &[
+    ("getFanProfiles", AuthScope::Read),
+    ("activateFanProfile", AuthScope::Control),
+    ("getFanOverride", AuthScope::Read),
+    ("setFanOverride", AuthScope::Control),
+    ("clearFanOverride", AuthScope::Control),
+    ("getFanAutoTune", AuthScope::Read),
+    ("acceptFanAutoTune", AuthScope::Control),
+    ("dismissFanAutoTune", AuthScope::Control),
+    ("getGpuFan", AuthScope::Read),
+    ("getFanContention", AuthScope::Read),
+    ("resolveFanContention", AuthScope::Control),
+    ("getFanStats", AuthScope::Read),
+    ("getFanStatsDeprecated", AuthScope::Read),
+    ("resetFanStats", AuthScope::Control),
+    ("resetFanStatsDeprecated", AuthScope::Control),
+    ("runFanRampTest", AuthScope::Control),
+    ("calibrateFans", AuthScope::Control),
+    ("compareFanCurves", AuthScope::Control),
+    ("fitFanCurve", AuthScope::Read),
+    ("startCurveWizard", AuthScope::Control),
+    ("getCurveWizard", AuthScope::Read),
+    ("cancelCurveWizard", AuthScope::Control),
+    ("applyCurveWizard", AuthScope::Control),
+];
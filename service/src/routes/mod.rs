@@ -0,0 +1,786 @@
+use crate::config; // for save/load
+use crate::shortcuts;
+use crate::state::AppState;
+use crate::types::PartialConfig;
+use poem::web::Data;
+use poem_openapi::payload::Json;
+use poem_openapi::{ApiResponse, OpenApi, Tags};
+use tracing::{error, info, warn};
+
+mod admin;
+mod battery;
+mod fan;
+mod power;
+mod system;
+mod telemetry;
+
+#[derive(ApiResponse)]
+enum ApiErrorResponse {
+    #[oai(status = 502)]
+    BadGateway(Json<crate::types::ErrorEnvelope>),
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<crate::types::ErrorEnvelope>),
+    #[oai(status = 409)]
+    Conflict(Json<crate::types::ErrorEnvelope>),
+    #[oai(status = 422)]
+    UnprocessableEntity(Json<crate::types::ValidationErrorEnvelope>),
+}
+
+type ApiResult<T> = Result<Json<T>, ApiErrorResponse>;
+
+async fn require_framework_tool_async(
+    state: &AppState,
+) -> Result<crate::cli::framework_tool::FrameworkTool, ApiErrorResponse> {
+    let cli_opt = { state.framework_tool.read().await.clone() };
+    match cli_opt {
+        Some(cli) => Ok(cli),
+        None => Err(ApiErrorResponse::ServiceUnavailable(Json(
+            crate::types::ErrorEnvelope {
+                code: "cli_unavailable".into(),
+                message: "framework_tool not found".into(),
+                request_id: crate::request_id::current(),
+                hint: Some(crate::hints::install_framework_tool()),
+            },
+        ))),
+    }
+}
+
+/// Capabilities of the backend `choice` selects; `None` when it is disabled or not available yet.
+async fn power_capabilities(
+    state: &AppState,
+    choice: Option<crate::types::PowerBackendKind>,
+) -> Option<crate::types::PowerCapabilities> {
+    let backend = state.power.select(choice.unwrap_or_default()).await?;
+    Some(backend.capabilities().await)
+}
+
+fn bad_gateway(code: &str, message: String) -> ApiErrorResponse {
+    ApiErrorResponse::BadGateway(Json(crate::types::ErrorEnvelope {
+        code: code.into(),
+        hint: crate::hints::for_error(code, &message),
+        message,
+        request_id: crate::request_id::current(),
+    }))
+}
+
+/// Error for a combined response none of whose sections could be read.
+fn section_error(code: &str, message: String) -> ApiErrorResponse {
+    match code {
+        "cli_unavailable" => ApiErrorResponse::ServiceUnavailable(Json(crate::types::ErrorEnvelope {
+            code: code.into(),
+            hint: crate::hints::for_error(code, &message),
+            message,
+            request_id: crate::request_id::current(),
+        })),
+        _ => bad_gateway(code, message),
+    }
+}
+
+fn map_cli_err(e: String) -> ApiErrorResponse {
+    bad_gateway("cli_failed", e)
+}
+
+/// Sections a partial config writes, by their `Config` field name.
+fn touched_sections(req: &PartialConfig) -> Vec<&'static str> {
+    [
+        (req.fan.is_some(), "fan"),
+        (req.power.is_some(), "power"),
+        (req.battery.is_some(), "battery"),
+        (req.telemetry.is_some(), "telemetry"),
+        (req.keyboard_backlight.is_some(), "keyboard_backlight"),
+        (req.reports.is_some(), "reports"),
+        (req.network.is_some(), "network"),
+        (req.sensors.is_some(), "sensors"),
+        (req.logging.is_some(), "logging"),
+        (req.backup.is_some(), "backup"),
+        (req.updates.is_some(), "updates"),
+        (req.ui.is_some(), "ui"),
+    ]
+    .into_iter()
+    .filter_map(|(touched, section)| touched.then_some(section))
+    .collect()
+}
+
+/// Save a validated `merged` config as the next revision and apply it (`apply_config`). The
+/// caller holds `config::WRITE_LOCK`.
+async fn commit_config(
+    state: &AppState,
+    running: &crate::types::Config,
+    mut merged: crate::types::Config,
+    touched: &[&'static str],
+) -> Result<u64, ApiErrorResponse> {
+    merged.revision = running.revision + 1;
+    if let Err(e) = config::save(&merged) {
+        error!("config save error: {}", e);
+        return Err(bad_gateway("save_failed", e));
+    }
+    Ok(apply_config(state, running, merged, touched).await)
+}
+
+/// Make a validated `merged` config the next revision without saving it: side effects of the
+/// touched sections, then the running config. The caller holds `config::WRITE_LOCK`.
+async fn apply_config(
+    state: &AppState,
+    running: &crate::types::Config,
+    mut merged: crate::types::Config,
+    touched: &[&'static str],
+) -> u64 {
+    let revision = running.revision + 1;
+    merged.revision = revision;
+    if touched.contains(&"network") {
+        let previous_port = crate::network::port();
+        crate::network::apply(&merged.network);
+        let port = crate::network::port();
+        // Existing shortcuts point at the old port
+        if port != previous_port && shortcuts::shortcuts_exist() {
+            if let Err(e) = shortcuts::create_shortcuts(port).await {
+                warn!("failed to update shortcuts for port {}: {}", port, e);
+            }
+        }
+    }
+    if touched.contains(&"logging") {
+        crate::log_forward::apply(&merged.logging);
+        crate::error_reports::apply(&merged.logging);
+    }
+    // Changing the fan settings takes the fans back from another program
+    if touched.contains(&"fan") && crate::tasks::fan_contention::backed_off() {
+        crate::tasks::fan_contention::reassert();
+    }
+    for section in touched {
+        crate::config_check::clear_section(section);
+    }
+    {
+        let mut w = state.config.write().await;
+        *w = crate::temporary::overlay(merged, running, touched);
+    }
+    crate::temporary::end(touched);
+    revision
+}
+
+fn conflict(code: &str, message: String) -> ApiErrorResponse {
+    ApiErrorResponse::Conflict(Json(crate::types::ErrorEnvelope {
+        code: code.into(),
+        message,
+        request_id: crate::request_id::current(),
+        hint: None,
+    }))
+}
+
+/// 409 `confirmation_required`, for a request without `confirm=true`. Operations that are hard to
+/// undo (removing RyzenAdj, installing an update, turning the charge limit off or lifting it on
+/// AC) are never taken on a bare request, so a script bug or a replayed call cannot trigger them;
+/// `warning` says what would happen, for the client to show first.
+fn confirmation_required(warning: String) -> ApiErrorResponse {
+    ApiErrorResponse::Conflict(Json(crate::types::ErrorEnvelope {
+        code: "confirmation_required".into(),
+        message: warning,
+        request_id: crate::request_id::current(),
+        hint: Some(crate::hints::confirm_operation()),
+    }))
+}
+
+/// Warning for a write that turns off a charge limit `running` enforces, or sets it aside on AC
+/// through `power.ac.lift_battery_limits` (ignored in storage mode).
+fn charge_limit_warning(running: &crate::types::Config, new: &crate::types::Config) -> Option<String> {
+    let limit = |c: &crate::types::Config| {
+        c.battery
+            .charge_limit_max_pct
+            .as_ref()
+            .filter(|l| l.enabled && l.value < 100)
+            .map(|l| l.value)
+    };
+    let lifted = |c: &crate::types::Config| {
+        c.power.ac.as_ref().and_then(|p| p.lift_battery_limits) == Some(true)
+            && !c.battery.storage_mode.as_ref().is_some_and(|s| s.enabled)
+    };
+    let pct = limit(running).filter(|_| !lifted(running))?;
+    if limit(new).is_none() {
+        Some(format!(
+            "the battery will charge to 100% instead of {}%, which wears it faster when kept plugged in",
+            pct
+        ))
+    } else if lifted(new) {
+        Some(format!(
+            "on AC the battery will charge to 100% instead of {}%, which wears it faster when kept plugged in",
+            pct
+        ))
+    } else {
+        None
+    }
+}
+
+/// Error code and message unless the writer sent the revision it read (409 via `conflict`).
+fn check_revision(sent: Option<u64>, current_revision: u64) -> Result<(), (&'static str, String)> {
+    match sent {
+        Some(r) if r == current_revision => Ok(()),
+        Some(r) => Err((
+            "stale_revision",
+            format!(
+                "config changed since revision {} (now {}); reload and retry",
+                r, current_revision
+            ),
+        )),
+        None => Err((
+            "revision_required",
+            format!("send the current config revision ({}) with the write", current_revision),
+        )),
+    }
+}
+
+/// Merge a partial config into `merged`: fields that are present overwrite, lists are replaced
+/// as a whole.
+fn merge_partial(merged: &mut crate::types::Config, req: PartialConfig) {
+    if let Some(fan) = req.fan {
+        let mut new_fan = merged.fan.clone();
+        // Overwrite sections only if provided
+        if let Some(m) = fan.mode {
+            new_fan.mode = Some(m);
+        }
+        if let Some(man) = fan.manual {
+            new_fan.manual = Some(man);
+        }
+        if let Some(cur) = fan.curve {
+            new_fan.curve = Some(cur);
+        }
+        if let Some(t) = fan.target {
+            new_fan.target = Some(t);
+        }
+        if let Some(r) = fan.rpm {
+            new_fan.rpm = Some(r);
+        }
+        if let Some(p) = fan.profiles {
+            new_fan.profiles = if p.is_empty() { None } else { Some(p) };
+        }
+        if let Some(s) = fan.schedule {
+            new_fan.schedule = Some(s);
+        }
+        if let Some(cal) = fan.calibration {
+            new_fan.calibration = Some(cal);
+        }
+        if let Some(cals) = fan.calibrations {
+            new_fan.calibrations = if cals.is_empty() { None } else { Some(cals) };
+        }
+        // Overrides are replaced wholesale when provided. An empty array clears them entirely.
+        if let Some(ov) = fan.overrides {
+            new_fan.overrides = if ov.is_empty() { None } else { Some(ov) };
+        }
+        if let Some(gpu) = fan.gpu {
+            new_fan.gpu = Some(gpu);
+        }
+        if let Some(h) = fan.maintenance_hours {
+            new_fan.maintenance_hours = if h == 0 { None } else { Some(h) };
+        }
+        if let Some(p) = fan.on_contention {
+            new_fan.on_contention = Some(p);
+        }
+        if let Some(f) = fan.failsafe {
+            new_fan.failsafe = Some(f);
+        }
+        if let Some(t) = fan.auto_tune {
+            new_fan.auto_tune = Some(t);
+        }
+        if let Some(o) = fan.duty_offsets {
+            new_fan.duty_offsets = if o.is_empty() { None } else { Some(o) };
+        }
+        crate::fan_profiles::reconcile(&mut new_fan);
+        merged.fan = new_fan;
+    }
+    if let Some(pow) = req.power {
+        let mut new_pow = merged.power.clone();
+        if let Some(ac_in) = pow.ac {
+            let mut ac = new_pow.ac.unwrap_or_default();
+            if let Some(s) = ac_in.tdp_watts {
+                ac.tdp_watts = Some(s);
+            }
+            if let Some(s) = ac_in.stapm_watts {
+                ac.stapm_watts = Some(s);
+            }
+            if let Some(s) = ac_in.fast_limit_watts {
+                ac.fast_limit_watts = Some(s);
+            }
+            if let Some(s) = ac_in.slow_limit_watts {
+                ac.slow_limit_watts = Some(s);
+            }
+            if let Some(s) = ac_in.thermal_limit_c {
+                ac.thermal_limit_c = Some(s);
+            }
+            if let Some(s) = ac_in.epp_preference {
+                ac.epp_preference = Some(s);
+            }
+            if let Some(s) = ac_in.governor {
+                ac.governor = Some(s);
+            }
+            if let Some(s) = ac_in.min_freq_mhz {
+                ac.min_freq_mhz = Some(s);
+            }
+            if let Some(s) = ac_in.max_freq_mhz {
+                ac.max_freq_mhz = Some(s);
+            }
+            if let Some(s) = ac_in.boost {
+                ac.boost = Some(s);
+            }
+            if let Some(s) = ac_in.scheduler_hints {
+                ac.scheduler_hints = Some(s);
+            }
+            if let Some(b) = ac_in.lift_battery_limits {
+                ac.lift_battery_limits = Some(b);
+            }
+            new_pow.ac = Some(ac);
+        }
+        if let Some(bat_in) = pow.battery {
+            let mut bat = new_pow.battery.unwrap_or_default();
+            if let Some(s) = bat_in.tdp_watts {
+                bat.tdp_watts = Some(s);
+            }
+            if let Some(s) = bat_in.stapm_watts {
+                bat.stapm_watts = Some(s);
+            }
+            if let Some(s) = bat_in.fast_limit_watts {
+                bat.fast_limit_watts = Some(s);
+            }
+            if let Some(s) = bat_in.slow_limit_watts {
+                bat.slow_limit_watts = Some(s);
+            }
+            if let Some(s) = bat_in.thermal_limit_c {
+                bat.thermal_limit_c = Some(s);
+            }
+            if let Some(s) = bat_in.epp_preference {
+                bat.epp_preference = Some(s);
+            }
+            if let Some(s) = bat_in.governor {
+                bat.governor = Some(s);
+            }
+            if let Some(s) = bat_in.min_freq_mhz {
+                bat.min_freq_mhz = Some(s);
+            }
+            if let Some(s) = bat_in.max_freq_mhz {
+                bat.max_freq_mhz = Some(s);
+            }
+            if let Some(s) = bat_in.boost {
+                bat.boost = Some(s);
+            }
+            if let Some(s) = bat_in.scheduler_hints {
+                bat.scheduler_hints = Some(s);
+            }
+            if let Some(b) = bat_in.lift_battery_limits {
+                bat.lift_battery_limits = Some(b);
+            }
+            new_pow.battery = Some(bat);
+        }
+        if let Some(c) = pow.chargers {
+            new_pow.chargers = Some(c);
+        }
+        if let Some(b) = pow.backend {
+            new_pow.backend = Some(b);
+        }
+        if let Some(b) = pow.budget {
+            new_pow.budget = Some(b);
+        }
+        merged.power = new_pow;
+    }
+    if let Some(up) = req.updates {
+        let mut new_up = merged.updates.clone();
+        new_up.auto_install = up.auto_install;
+        if let Some(defer) = up.defer_during_dnd {
+            new_up.defer_during_dnd = Some(defer);
+        }
+        if let Some(url) = up.firmware_feed_url {
+            new_up.firmware_feed_url = Some(url).filter(|u| !u.is_empty());
+        }
+        merged.updates = new_up;
+    }
+    if let Some(bat) = req.battery {
+        let mut new_bat = merged.battery.clone();
+        if let Some(s) = bat.charge_limit_max_pct {
+            new_bat.charge_limit_max_pct = Some(s);
+        }
+        if let Some(s) = bat.charge_rate_c {
+            new_bat.charge_rate_c = Some(s);
+            new_bat.charge_rate_soc_threshold_pct = bat.charge_rate_soc_threshold_pct;
+        }
+        if let Some(w) = bat.min_charger_watts {
+            // 0 turns the weak-charger alert off
+            new_bat.min_charger_watts = if w == 0 { None } else { Some(w) };
+        }
+        if let Some(s) = bat.storage_mode {
+            new_bat.storage_mode = Some(s);
+        }
+        merged.battery = new_bat;
+    }
+    if let Some(tel) = req.telemetry {
+        merged.telemetry = tel;
+    }
+    if let Some(kb) = req.keyboard_backlight {
+        merged.keyboard_backlight = kb;
+    }
+    if let Some(reports) = req.reports {
+        merged.reports = reports;
+    }
+    if let Some(network) = req.network {
+        merged.network = network;
+    }
+    if let Some(sensors) = req.sensors {
+        merged.sensors = sensors;
+    }
+    if let Some(logging) = req.logging {
+        merged.logging = logging;
+    }
+    if let Some(backup) = req.backup {
+        merged.backup = backup;
+    }
+    if let Some(ui) = req.ui {
+        let mut new_ui = merged.ui.clone();
+        if let Some(theme) = ui.theme {
+            new_ui.theme = Some(theme);
+        }
+        if let Some(unit) = ui.temperature_unit {
+            new_ui.temperature_unit = Some(unit);
+        }
+        if let Some(clock) = ui.clock_format {
+            new_ui.clock_format = Some(clock);
+        }
+        merged.ui = new_ui;
+    }
+}
+
+/// Save `merged` (the saved config with a change to `touched` made by the service itself rather
+/// than sent as a patch) as the next revision and make it the running config, as `POST /config`
+/// does. Callers hold `config::WRITE_LOCK` and have validated `merged`.
+async fn commit_sections(
+    state: &AppState,
+    running: &crate::types::Config,
+    mut merged: crate::types::Config,
+    touched: &[&str],
+) -> Result<u64, String> {
+    merged.revision = running.revision + 1;
+    if let Err(e) = config::save(&merged) {
+        error!("config save error: {}", e);
+        return Err(e);
+    }
+    if touched.contains(&"fan") && crate::tasks::fan_contention::backed_off() {
+        crate::tasks::fan_contention::reassert();
+    }
+    for section in touched {
+        crate::config_check::clear_section(section);
+    }
+    {
+        let mut w = state.config.write().await;
+        *w = crate::temporary::overlay(merged, running, touched);
+    }
+    crate::temporary::end(touched);
+    Ok(running.revision + 1)
+}
+
+/// Apply a config bundle's sections (`POST /config/import`, backup restores): a bundle from a
+/// different known model loses its model-specific settings unless `force`, and the saved config
+/// is backed up under `backups/<device_id>/` first. Turning the charge limit off needs `confirm`,
+/// as in `POST /config`. `what` names the operation in the log.
+async fn import_partial(
+    state: &AppState,
+    device: Option<crate::types::DeviceIdentity>,
+    mut config: PartialConfig,
+    force: bool,
+    confirm: bool,
+    what: &str,
+) -> Result<crate::types::ConfigImportResult, ApiErrorResponse> {
+    if let Some(cli) = state.framework_tool.read().await.clone() {
+        crate::sensors::metadata::detect_board(&cli).await;
+    }
+    let origin = crate::device::compare(device.as_ref(), &crate::device::identity());
+    let skipped = if origin.model_mismatch && !force {
+        crate::device::strip_model_specific(&mut config)
+    } else {
+        Vec::new()
+    };
+    config.revision = None;
+
+    let _writer = config::WRITE_LOCK.lock().await;
+    let touched = touched_sections(&config);
+    let running = state.config.read().await.clone();
+    let mut merged = crate::temporary::persisted(&running);
+    merge_partial(&mut merged, config);
+    validate_sections(state, &merged, &touched, Default::default()).await?;
+    if let Some(warning) = charge_limit_warning(&running, &merged) {
+        if !confirm {
+            return Err(confirmation_required(warning));
+        }
+    }
+
+    let backup_path = match crate::device::backup(&crate::temporary::persisted(&running)) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("config backup failed: {}", e);
+            return Err(bad_gateway("backup_failed", e));
+        }
+    };
+    let revision = commit_config(state, &running, merged, &touched).await?;
+    for w in &origin.warnings {
+        warn!("{}: {}", what, w);
+    }
+    info!(
+        "{}: applied as revision {} (skipped: {}), previous config saved to {}",
+        what,
+        revision,
+        if skipped.is_empty() {
+            "none".to_string()
+        } else {
+            skipped.join(", ")
+        },
+        backup_path.display()
+    );
+    Ok(crate::types::ConfigImportResult {
+        revision,
+        model_mismatch: origin.model_mismatch,
+        skipped,
+        warnings: origin.warnings,
+        backup_path: backup_path.display().to_string(),
+    })
+}
+
+/// The fan boost is the `fan` section's temporary override; `duty_pct` is left out when that
+/// override came from `POST /config/temporary` with something other than a fixed duty.
+fn fan_override_status(cfg: &crate::types::Config) -> crate::types::FanOverrideStatus {
+    let expires_at_ms = crate::temporary::expires_at_ms("fan");
+    let duty_pct = match (&cfg.fan.mode, &cfg.fan.manual) {
+        (Some(crate::types::FanControlMode::Manual), Some(m)) if expires_at_ms.is_some() => Some(m.duty_pct),
+        _ => None,
+    };
+    crate::types::FanOverrideStatus {
+        revision: cfg.revision,
+        duty_pct,
+        expires_at_ms,
+    }
+}
+
+fn storage_mode_status(battery: &crate::types::BatteryConfig, revision: u64) -> crate::types::StorageModeStatus {
+    let cfg = battery.storage_mode.clone().unwrap_or_default();
+    let live = crate::tasks::battery::storage_state().filter(|_| cfg.enabled);
+    crate::types::StorageModeStatus {
+        enabled: cfg.enabled,
+        target_pct: cfg.target_pct,
+        band_pct: cfg.band_pct,
+        phase: live.map(|s| s.phase),
+        soc_pct: live.map(|s| s.soc_pct),
+        charge_limit_pct: live.map(|s| s.limit_pct),
+        revision,
+    }
+}
+
+/// Validate the merged `touched` sections on top of `errs`; 422 listing every invalid field.
+async fn validate_sections(
+    state: &AppState,
+    merged: &crate::types::Config,
+    touched: &[&str],
+    mut errs: crate::validation::Errors,
+) -> Result<(), ApiErrorResponse> {
+    if touched.contains(&"fan") {
+        crate::validation::fan(&mut errs, &merged.fan);
+    }
+    if touched.contains(&"power") {
+        let caps = power_capabilities(state, merged.power.backend).await;
+        crate::validation::power(&mut errs, &merged.power, caps.as_ref());
+    }
+    if touched.contains(&"battery") {
+        crate::validation::battery(&mut errs, &merged.battery);
+    }
+    if touched.contains(&"telemetry") {
+        crate::validation::telemetry(&mut errs, &merged.telemetry);
+    }
+    if touched.contains(&"keyboard_backlight") {
+        crate::validation::keyboard_backlight(&mut errs, &merged.keyboard_backlight);
+    }
+    if touched.contains(&"reports") {
+        crate::validation::reports(&mut errs, &merged.reports);
+    }
+    if touched.contains(&"network") {
+        crate::validation::network(&mut errs, &merged.network);
+    }
+    if touched.contains(&"sensors") {
+        crate::validation::sensors(&mut errs, &merged.sensors);
+    }
+    if touched.contains(&"logging") {
+        crate::validation::logging(&mut errs, &merged.logging);
+    }
+    if touched.contains(&"backup") {
+        crate::validation::backup(&mut errs, &merged.backup);
+    }
+    if touched.contains(&"updates") {
+        crate::validation::updates(&mut errs, &merged.updates);
+    }
+    if let Err(fields) = errs.into_result() {
+        info!("config write rejected: {} invalid field(s)", fields.len());
+        return Err(ApiErrorResponse::UnprocessableEntity(Json(
+            crate::types::ValidationErrorEnvelope {
+                code: "invalid_config".into(),
+                message: format!("{} invalid field(s); nothing was saved", fields.len()),
+                fields,
+                request_id: crate::request_id::current(),
+            },
+        )));
+    }
+    Ok(())
+}
+
+/// Operation groups; each becomes a tag in the spec (and a service in the generated client).
+#[derive(Tags)]
+enum ApiTags {
+    /// Fan curves, profiles, overrides, calibration and fan health
+    Fan,
+    /// Power limits, power profiles and eco mode
+    Power,
+    /// Battery sessions and storage mode
+    Battery,
+    /// Sensors, history, reports, alerts and metrics
+    Telemetry,
+    /// Health, versions, updates, firmware, diagnostics and desktop integration
+    System,
+    /// Config, backups, logs and installing updates
+    Admin,
+}
+
+/// What an operation may do, published per operation as `x-auth-scope` so clients can group and
+/// gate features by role. The service itself listens on loopback only and does not check them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuthScope {
+    Read,
+    Control,
+    Admin,
+}
+
+impl AuthScope {
+    const ALL: [AuthScope; 3] = [AuthScope::Read, AuthScope::Control, AuthScope::Admin];
+
+    fn name(self) -> &'static str {
+        match self {
+            AuthScope::Read => "read",
+            AuthScope::Control => "control",
+            AuthScope::Admin => "admin",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            AuthScope::Read => "Reads state; changes nothing",
+            AuthScope::Control => {
+                "Drives the hardware or changes fan, power and battery behaviour (overrides, profiles, tests)"
+            }
+            AuthScope::Admin => "Changes the saved config or the installation, or reads logs and exports",
+        }
+    }
+}
+
+pub type Api = (
+    fan::FanApi,
+    power::PowerApi,
+    battery::BatteryApi,
+    telemetry::TelemetryApi,
+    system::SystemApi,
+    admin::AdminApi,
+);
+
+/// The API as an OpenAPI service whose spec lists `server` as its base URL.
+pub fn openapi(server: &str) -> poem_openapi::OpenApiService<Api, ()> {
+    poem_openapi::OpenApiService::new(
+        (
+            fan::FanApi,
+            power::PowerApi,
+            battery::BatteryApi,
+            telemetry::TelemetryApi,
+            system::SystemApi,
+            admin::AdminApi,
+        ),
+        "framework-control-service",
+        env!("CARGO_PKG_VERSION"),
+    )
+    .server(server)
+}
+
+fn scope_of(operation_id: &str) -> Option<AuthScope> {
+    [
+        fan::SCOPES,
+        power::SCOPES,
+        battery::SCOPES,
+        telemetry::SCOPES,
+        system::SCOPES,
+        admin::SCOPES,
+    ]
+    .iter()
+    .flat_map(|t| t.iter())
+    .find(|(id, _)| *id == operation_id)
+    .map(|(_, scope)| *scope)
+}
+
+/// The OpenAPI document for `server`, with each operation's `x-auth-scope` and the scopes'
+/// descriptions (`x-auth-scopes`) filled in.
+pub fn spec(server: &str) -> Result<serde_json::Value, String> {
+    let mut spec: serde_json::Value = serde_json::from_str(&openapi(server).spec()).map_err(|e| e.to_string())?;
+    if let Some(paths) = spec.get_mut("paths").and_then(|p| p.as_object_mut()) {
+        for op in paths
+            .values_mut()
+            .filter_map(|p| p.as_object_mut())
+            .flat_map(|p| p.values_mut())
+        {
+            let scope = op.get("operationId").and_then(|id| id.as_str()).and_then(scope_of);
+            if let (Some(scope), Some(op)) = (scope, op.as_object_mut()) {
+                op.insert("x-auth-scope".into(), scope.name().into());
+            }
+        }
+    }
+    spec["x-auth-scopes"] = AuthScope::ALL
+        .iter()
+        .map(|s| (s.name().to_string(), s.description().into()))
+        .collect::<serde_json::Map<_, _>>()
+        .into();
+    Ok(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifting_the_charge_limit_needs_confirmation() {
+        let mut running = crate::types::Config::default();
+        running.battery.charge_limit_max_pct = Some(crate::types::SettingU8 {
+            enabled: true,
+            value: 80,
+        });
+        let mut lifted = running.clone();
+        lifted.power.ac = Some(crate::types::PowerProfile {
+            lift_battery_limits: Some(true),
+            ..Default::default()
+        });
+        assert!(charge_limit_warning(&running, &lifted).is_some());
+        // Already lifted: nothing more to confirm
+        assert!(charge_limit_warning(&lifted, &lifted).is_none());
+        assert!(charge_limit_warning(&running, &running).is_none());
+    }
+
+    #[test]
+    fn every_operation_has_a_tag_and_a_scope() {
+        let spec = spec("").unwrap();
+        let mut ids = Vec::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for (method, op) in item.as_object().unwrap() {
+                let id = op["operationId"].as_str().unwrap();
+                assert_eq!(op["tags"].as_array().map(Vec::len), Some(1), "{} {}", method, path);
+                assert!(op["x-auth-scope"].is_string(), "{} {} has no scope", method, path);
+                ids.push(id.to_string());
+            }
+        }
+        // No stale scope entries
+        for table in [
+            fan::SCOPES,
+            power::SCOPES,
+            battery::SCOPES,
+            telemetry::SCOPES,
+            system::SCOPES,
+            admin::SCOPES,
+        ] {
+            for (id, _) in table {
+                assert!(ids.iter().any(|i| i == id), "scope for unknown operation {}", id);
+            }
+        }
+        assert!(spec["x-auth-scopes"]["admin"].is_string());
+    }
+}
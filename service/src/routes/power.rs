@@ -0,0 +1,246 @@
+// Power endpoints: readings, capabilities, the power budget, eco mode and the RyzenAdj install.
+
+use super::*;
+use crate::types::Empty;
+use poem_openapi::param::Query;
+
+pub struct PowerApi;
+
+#[OpenApi(tag = "ApiTags::Power")]
+impl PowerApi {
+    /// RyzenAdj: install on demand (Windows only)
+    #[oai(path = "/ryzenadj/install", method = "post", operation_id = "installRyzenadj")]
+    async fn install_ryzenadj(&self) -> ApiResult<Empty> {
+        #[cfg(target_os = "windows")]
+        {
+            match crate::cli::ryzen_adj::attempt_install_via_direct_download().await {
+                Ok(_) => {
+                    // Validate resolve, but do not spawn another task (boot task will pick it up)
+                    match crate::cli::ryzen_adj::RyzenAdj::new().await {
+                        Ok(_cli) => Ok(Json(Empty {})),
+                        Err(e) => {
+                            error!("ryzenadj resolve after install failed: {}", e);
+                            Err(bad_gateway("ryzenadj_unavailable", e))
+                        }
+                    }
+                }
+                Err(e) => Err(bad_gateway("install_failed", e)),
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            Err(bad_gateway(
+                "unsupported_platform",
+                "RyzenAdj is only available on Windows. Linux uses native kernel interfaces.".to_string(),
+            ))
+        }
+    }
+
+    /// RyzenAdj: uninstall and remove any downloaded artifacts (Windows only). Needs
+    /// `confirm=true`: TDP and thermal limits go back to the firmware defaults
+    #[oai(path = "/ryzenadj/uninstall", method = "post", operation_id = "uninstallRyzenadj")]
+    async fn uninstall_ryzenadj(
+        &self,
+        _state: Data<&AppState>,
+        /// The user has seen the warning and agreed
+        confirm: Query<Option<bool>>,
+    ) -> ApiResult<Empty> {
+        if confirm.0 != Some(true) {
+            return Err(confirmation_required(
+                "RyzenAdj will be removed; TDP and thermal limits go back to the firmware defaults until it is installed again"
+                    .to_string(),
+            ));
+        }
+        #[cfg(target_os = "windows")]
+        {
+            match crate::cli::ryzen_adj::remove_installed_files().await {
+                Ok(_) => {
+                    // Clear from in-memory state so UI reflects removal soon
+                    {
+                        let mut w = _state.power.ryzenadj.write().await;
+                        *w = None;
+                    }
+                    Ok(Json(Empty {}))
+                }
+                Err(e) => {
+                    error!("uninstall ryzenadj failed: {}", e);
+                    Err(bad_gateway("uninstall_failed", e))
+                }
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            Err(bad_gateway(
+                "unsupported_platform",
+                "RyzenAdj is only available on Windows. Linux uses native kernel interfaces.".to_string(),
+            ))
+        }
+    }
+
+    /// Battery, charger and power control; a section whose backend is missing is left empty and
+    /// flagged in `availability` (503/502 only when neither could be read)
+    #[oai(path = "/power", method = "get", operation_id = "getPower")]
+    async fn get_power(&self, state: Data<&AppState>) -> ApiResult<crate::types::PowerResponse> {
+        let cli = { state.framework_tool.read().await.clone() };
+        let reading = match cli {
+            Some(cli) => match cli.power_with_fallback().await {
+                Ok(p) => Ok((cli, p)),
+                Err(e) => Err(("cli_failed", e)),
+            },
+            None => Err(("cli_unavailable", "framework_tool not found".to_string())),
+        };
+        let choice = { state.config.read().await.power.backend }.unwrap_or_default();
+        let backend = state.power.select(choice).await;
+        if let (Err((code, message)), None) = (&reading, &backend) {
+            return Err(section_error(code, message.clone()));
+        }
+
+        let (battery_api, charger, battery_availability) = match reading {
+            Ok((cli, p)) => {
+                // Also include charge limit min/max when available; do not fail if missing
+                let limits = cli.charge_limit_get().await.unwrap_or_default();
+                // Adapter identity is best-effort: older framework_tool builds lack --pd-ports
+                let charger = if p.ac_present == Some(true) {
+                    cli.charger().await.ok().flatten()
+                } else {
+                    None
+                };
+                let battery = crate::types::BatteryInfo {
+                    eta: crate::battery_eta::estimate(&p, limits.charge_limit_max_pct),
+                    power_info: p,
+                    limits,
+                    limits_lifted: crate::tasks::battery::limits_lifted(),
+                };
+                (Some(battery), charger, crate::types::SectionAvailability::available())
+            }
+            Err((code, message)) => (
+                None,
+                None,
+                crate::types::SectionAvailability::unavailable(code, message),
+            ),
+        };
+
+        let (power_control, power_control_availability) = match backend {
+            Some(backend) => (
+                crate::types::PowerControlInfo {
+                    capabilities: backend.capabilities().await,
+                    current_state: backend.state().await.unwrap_or_default(),
+                },
+                crate::types::SectionAvailability::available(),
+            ),
+            None => (
+                crate::types::PowerControlInfo {
+                    capabilities: Default::default(),
+                    current_state: Default::default(),
+                },
+                if choice == crate::types::PowerBackendKind::Disabled {
+                    crate::types::SectionAvailability::unavailable(
+                        "backend_disabled",
+                        "power backend is disabled in the config".into(),
+                    )
+                } else {
+                    crate::types::SectionAvailability::unavailable(
+                        "backend_unavailable",
+                        "no power backend is available on this machine".into(),
+                    )
+                },
+            ),
+        };
+
+        Ok(Json(crate::types::PowerResponse {
+            battery: battery_api,
+            charger,
+            power_control,
+            availability: crate::types::PowerAvailability {
+                battery: battery_availability,
+                power_control: power_control_availability,
+            },
+        }))
+    }
+
+    /// Power controls this machine supports (TDP range, EPP options, governors, frequency range);
+    /// all `false` while the selected power backend is disabled or not available
+    #[oai(path = "/power/capabilities", method = "get", operation_id = "getPowerCapabilities")]
+    async fn get_power_capabilities(&self, state: Data<&AppState>) -> ApiResult<crate::types::PowerCapabilities> {
+        let choice = { state.config.read().await.power.backend };
+        Ok(Json(power_capabilities(&state, choice).await.unwrap_or_default()))
+    }
+
+    /// Targets of the active power profile next to the values read back (TDP from RyzenAdj or
+    /// RAPL, EPP, governor, frequency limits, fan control), with the settings that have drifted
+    #[oai(path = "/power/state", method = "get", operation_id = "getPowerState")]
+    async fn get_power_state(&self, state: Data<&AppState>) -> ApiResult<crate::types::PowerStateReport> {
+        Ok(Json(
+            crate::tasks::power::state_report(&state.power, &state.config, &state.framework_tool).await,
+        ))
+    }
+
+    /// Split of `power.budget` between CPU TDP and dGPU cap, the loads it is based on, or why
+    /// the budget is not active
+    #[oai(path = "/power/budget", method = "get", operation_id = "getPowerBudget")]
+    async fn get_power_budget(&self) -> ApiResult<crate::types::PowerBudgetStatus> {
+        Ok(Json(crate::tasks::power_budget::status()))
+    }
+
+    /// Whether Eco mode is on
+    #[oai(path = "/eco", method = "get", operation_id = "getEco")]
+    async fn get_eco(&self, state: Data<&AppState>) -> ApiResult<crate::types::EcoStatus> {
+        let since_ms = crate::eco::since_ms();
+        Ok(Json(crate::types::EcoStatus {
+            enabled: since_ms.is_some(),
+            since_ms,
+            revision: state.config.read().await.revision,
+        }))
+    }
+
+    /// Turn Eco mode on (low TDP and `power` EPP in both power profiles, a quiet fan curve, an 80%
+    /// charge limit) or off (the replaced settings come back). Saved like a `POST /config` write
+    #[oai(path = "/eco", method = "post", operation_id = "setEco")]
+    async fn set_eco(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::EcoRequest>,
+    ) -> ApiResult<crate::types::EcoStatus> {
+        let enable = req.0.enabled;
+        let _writer = config::WRITE_LOCK.lock().await;
+        let running = state.config.read().await.clone();
+        let saved = crate::temporary::persisted(&running);
+        let caps = power_capabilities(&state, saved.power.backend).await;
+        let Some(switch) = crate::eco::switch(&saved, enable, caps.as_ref()) else {
+            let since_ms = crate::eco::since_ms();
+            return Ok(Json(crate::types::EcoStatus {
+                enabled: since_ms.is_some(),
+                since_ms,
+                revision: running.revision,
+            }));
+        };
+        let touched = crate::eco::SECTIONS;
+        validate_sections(&state, &switch.config, &touched, Default::default()).await?;
+
+        switch.record().map_err(|e| bad_gateway("save_failed", e))?;
+        let revision = match commit_sections(&state, &running, switch.config.clone(), &touched).await {
+            Ok(revision) => revision,
+            Err(e) => {
+                switch.undo();
+                return Err(bad_gateway("save_failed", e));
+            }
+        };
+        info!("eco mode {} (revision {})", if enable { "on" } else { "off" }, revision);
+        Ok(Json(crate::types::EcoStatus {
+            enabled: enable,
+            since_ms: crate::eco::since_ms(),
+            revision,
+        }))
+    }
+}
+
+pub(super) const SCOPES: &[(&str, AuthScope)] = &[
+    ("installRyzenadj", AuthScope::Admin),
+    ("uninstallRyzenadj", AuthScope::Admin),
+    ("getPower", AuthScope::Read),
+    ("getPowerCapabilities", AuthScope::Read),
+    ("getPowerState", AuthScope::Read),
+    ("getPowerBudget", AuthScope::Read),
+    ("getEco", AuthScope::Read),
+    ("setEco", AuthScope::Control),
+];
@@ -0,0 +1,257 @@
+// System endpoints: health, versions, updates, the bridge, diagnostics and shortcuts.
+
+use super::*;
+use crate::types::{Empty, Health, ShortcutsStatus, SystemInfo, UpdateCheck};
+use crate::update::get_current_and_latest;
+use poem_openapi::param::Query;
+use sysinfo::System;
+
+pub struct SystemApi;
+
+#[OpenApi(tag = "ApiTags::System")]
+impl SystemApi {
+    /// Health: returns overall service health and CLI presence
+    #[oai(path = "/health", method = "get", operation_id = "health")]
+    async fn health(&self, state: Data<&AppState>) -> ApiResult<Health> {
+        let cli_present = state.framework_tool.read().await.is_some();
+        let service_version = env!("CARGO_PKG_VERSION").to_string();
+        Ok(Json(Health {
+            cli_present,
+            service_version,
+            safe_mode: crate::safe_mode::is_active(),
+            safe_mode_reason: crate::safe_mode::reason(),
+            config_issues: crate::config_check::issues(),
+            device: crate::device::identity(),
+            last_reload: crate::config_reload::last(),
+            cli_latency: crate::metrics::cli_latency(),
+        }))
+    }
+
+    /// Update: check for latest version from update feed
+    #[oai(path = "/update/check", method = "get", operation_id = "checkUpdate")]
+    async fn check_update(&self) -> ApiResult<UpdateCheck> {
+        match get_current_and_latest().await {
+            Ok((current, latest)) => Ok(Json(UpdateCheck {
+                current_version: current,
+                latest_version: latest,
+            })),
+            Err(e) => {
+                error!("update check failed: {}", e);
+                Err(bad_gateway("update_check_failed", e))
+            }
+        }
+    }
+
+    /// Tray bridge: compact state. With `since` (a previous `version`), waits up to `wait_s`
+    /// seconds (default and max 60) for the state to change before answering
+    #[oai(path = "/bridge/state", method = "get", operation_id = "getBridgeState")]
+    async fn get_bridge_state(
+        &self,
+        state: Data<&AppState>,
+        since: Query<Option<String>>,
+        wait_s: Query<Option<u64>>,
+    ) -> ApiResult<crate::types::BridgeState> {
+        let snap = match since.0 {
+            Some(since) => {
+                let wait = wait_s
+                    .0
+                    .map(std::time::Duration::from_secs)
+                    .unwrap_or(crate::bridge::MAX_WAIT);
+                crate::bridge::wait_for_change(&state, &since, wait).await
+            }
+            None => crate::bridge::snapshot(&state).await,
+        };
+        Ok(Json(snap))
+    }
+
+    /// Tray bridge: quick actions derived from the config, each a patch for `POST /config`
+    #[oai(path = "/bridge/actions", method = "get", operation_id = "getBridgeActions")]
+    async fn get_bridge_actions(&self, state: Data<&AppState>) -> ApiResult<crate::types::BridgeActions> {
+        let cfg = state.config.read().await;
+        Ok(Json(crate::types::BridgeActions {
+            revision: cfg.revision,
+            actions: crate::bridge::actions(&cfg),
+        }))
+    }
+
+    /// Firmware: newer BIOS/EC releases for this mainboard from the configured release feed (the
+    /// daily check's result; `refresh=true` checks now). Informational only — nothing is flashed
+    #[oai(path = "/firmware/updates", method = "get", operation_id = "getFirmwareUpdates")]
+    async fn get_firmware_updates(
+        &self,
+        state: Data<&AppState>,
+        refresh: Query<Option<bool>>,
+    ) -> ApiResult<crate::types::FirmwareUpdates> {
+        if refresh.0 != Some(true) {
+            if let Some(last) = crate::firmware::last() {
+                return Ok(Json(last));
+            }
+        }
+        let cfg = { state.config.read().await.updates.clone() };
+        let cli = { state.framework_tool.read().await.clone() };
+        Ok(Json(crate::firmware::check(cli.as_ref(), &cfg).await))
+    }
+
+    /// Diagnostics: record every framework_tool call with its raw output, plus parsed thermal,
+    /// power, charge-limit and PD-port reads at a raised rate, for `seconds` (5-300, default 60).
+    /// Returns a zip (`raw.jsonl`, `parsed.jsonl`, `telemetry.json`, `info.json`) to attach to
+    /// parser or EC bug reports. One capture at a time
+    #[oai(path = "/diagnostics/capture", method = "post", operation_id = "runDebugCapture")]
+    async fn run_debug_capture(
+        &self,
+        state: Data<&AppState>,
+        seconds: Query<Option<u32>>,
+    ) -> Result<poem_openapi::payload::Attachment<Vec<u8>>, ApiErrorResponse> {
+        let cli = require_framework_tool_async(&state).await?;
+        let Some(guard) = crate::diagnostics::capture::CaptureGuard::try_acquire() else {
+            return Err(ApiErrorResponse::ServiceUnavailable(Json(
+                crate::types::ErrorEnvelope {
+                    code: "capture_busy".into(),
+                    message: "another debug capture is in progress".into(),
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                },
+            )));
+        };
+        let seconds = seconds.0.unwrap_or(crate::diagnostics::capture::DEFAULT_SECONDS);
+        match crate::diagnostics::capture::run(&guard, &cli, seconds, &state.telemetry_samples).await {
+            Ok(zip) => {
+                let name = format!(
+                    "framework-control-capture-{}.zip",
+                    chrono::Utc::now().format("%Y%m%d-%H%M%S")
+                );
+                Ok(poem_openapi::payload::Attachment::new(zip).filename(name))
+            }
+            Err(e) => {
+                error!("debug capture failed: {}", e);
+                Err(bad_gateway("capture_failed", e))
+            }
+        }
+    }
+
+    /// Diagnostics: run a bounded CPU load while recording high-resolution telemetry, and
+    /// return a compact report for curve tuning
+    #[oai(path = "/diagnostics/stress", method = "post", operation_id = "runStressTest")]
+    async fn run_stress_test(
+        &self,
+        state: Data<&AppState>,
+        req: Json<crate::types::StressRequest>,
+    ) -> ApiResult<crate::types::StressReport> {
+        let cli = require_framework_tool_async(&state).await?;
+        let Some(_guard) = crate::diagnostics::RunGuard::try_acquire() else {
+            return Err(ApiErrorResponse::ServiceUnavailable(Json(
+                crate::types::ErrorEnvelope {
+                    code: "diagnostics_busy".into(),
+                    message: "another diagnostics run is in progress".into(),
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                },
+            )));
+        };
+        match crate::diagnostics::stress::run(&cli, &req.0).await {
+            Ok(report) => Ok(Json(report)),
+            Err(e) => {
+                error!("stress test failed: {}", e);
+                Err(bad_gateway("stress_failed", e))
+            }
+        }
+    }
+
+    /// Diagnostics: non-destructively exercise each control path (EC read, fan duty
+    /// write-and-restore, platform power interfaces) and report pass/fail with remediation hints
+    #[oai(path = "/diagnostics/selftest", method = "post", operation_id = "runSelftest")]
+    async fn run_selftest(&self, state: Data<&AppState>) -> ApiResult<crate::types::SelftestReport> {
+        let Some(_guard) = crate::diagnostics::RunGuard::try_acquire() else {
+            return Err(ApiErrorResponse::ServiceUnavailable(Json(
+                crate::types::ErrorEnvelope {
+                    code: "diagnostics_busy".into(),
+                    message: "another diagnostics run is in progress".into(),
+                    request_id: crate::request_id::current(),
+                    hint: None,
+                },
+            )));
+        };
+        Ok(Json(crate::diagnostics::selftest::run(&state).await))
+    }
+
+    /// This API's OpenAPI document, with the server URL under the configured base path
+    #[oai(path = "/openapi.json", method = "get", operation_id = "getOpenApiSpec")]
+    async fn get_openapi_spec(&self) -> Result<Json<serde_json::Value>, ApiErrorResponse> {
+        let server = format!("{}/api", crate::network::base_path().unwrap_or_default());
+        spec(&server).map(Json).map_err(|e| bad_gateway("spec_failed", e))
+    }
+
+    /// Framework versions (parsed)
+    #[oai(path = "/versions", method = "get", operation_id = "getVersions")]
+    async fn get_versions(
+        &self,
+        state: Data<&AppState>,
+    ) -> ApiResult<crate::cli::framework_tool_parser::VersionsParsed> {
+        let cli = require_framework_tool_async(&state).await?;
+        let v = cli.versions().await.map_err(map_cli_err)?;
+        Ok(Json(v))
+    }
+
+    /// System info
+    #[oai(path = "/system", method = "get", operation_id = "getSystemInfo")]
+    async fn get_system_info(&self) -> ApiResult<SystemInfo> {
+        let sys = System::new_all();
+        let mut cpu = sys.global_cpu_info().brand().trim().to_string();
+        if cpu.is_empty() {
+            if let Some(c) = sys.cpus().iter().find(|c| !c.brand().trim().is_empty()) {
+                cpu = c.brand().trim().to_string();
+            }
+        }
+        let mem_mb = sys.total_memory() / 1024 / 1024;
+        let os = System::name().unwrap_or_else(|| "Unknown OS".into());
+        let gpus = crate::gpu::adapters().await;
+        let dgpu = gpus
+            .iter()
+            .find(|g| g.discrete)
+            .or(gpus.first())
+            .map(|g| g.name.clone());
+        Ok(Json(SystemInfo {
+            cpu,
+            memory_total_mb: mem_mb,
+            os,
+            dgpu,
+            gpus,
+        }))
+    }
+
+    #[oai(path = "/shortcuts/status", method = "get", operation_id = "getShortcutsStatus")]
+    async fn get_shortcuts_status(&self) -> ApiResult<ShortcutsStatus> {
+        let installed = shortcuts::shortcuts_exist();
+        Ok(Json(ShortcutsStatus { installed }))
+    }
+
+    #[oai(path = "/shortcuts/create", method = "post", operation_id = "createShortcuts")]
+    async fn create_shortcuts(&self) -> ApiResult<Empty> {
+        match shortcuts::create_shortcuts(crate::network::port()).await {
+            Ok(_) => {
+                info!("Shortcuts created successfully");
+                Ok(Json(Empty {}))
+            }
+            Err(e) => {
+                error!("Failed to create shortcuts: {}", e);
+                Err(bad_gateway("shortcuts_failed", e))
+            }
+        }
+    }
+}
+
+pub(super) const SCOPES: &[(&str, AuthScope)] = &[
+    ("health", AuthScope::Read),
+    ("checkUpdate", AuthScope::Read),
+    ("getBridgeState", AuthScope::Read),
+    ("getBridgeActions", AuthScope::Read),
+    ("getFirmwareUpdates", AuthScope::Read),
+    ("runDebugCapture", AuthScope::Admin),
+    ("runStressTest", AuthScope::Control),
+    ("runSelftest", AuthScope::Control),
+    ("getOpenApiSpec", AuthScope::Read),
+    ("getVersions", AuthScope::Read),
+    ("getSystemInfo", AuthScope::Read),
+    ("getShortcutsStatus", AuthScope::Read),
+    ("createShortcuts", AuthScope::Admin),
+];
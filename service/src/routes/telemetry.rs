@@ -0,0 +1,140 @@
+// Telemetry endpoints: sensors, history, reports, alerts and metrics.
+
+use super::*;
+use poem_openapi::param::Query;
+use poem_openapi::payload::PlainText;
+
+pub struct TelemetryApi;
+
+#[OpenApi(tag = "ApiTags::Telemetry")]
+impl TelemetryApi {
+    /// Thermal (parsed): EC temperatures plus any extra platform sensors (storage, GPU). Without
+    /// framework_tool the extra sensors are still served, flagged in `availability`
+    #[oai(path = "/thermal", method = "get", operation_id = "getThermal")]
+    async fn get_thermal(&self, state: Data<&AppState>) -> ApiResult<crate::types::ThermalResponse> {
+        let cli = { state.framework_tool.read().await.clone() };
+        let ec = match cli.as_ref() {
+            Some(cli) => crate::sensors::thermal(cli).await.map_err(|e| ("cli_failed", e)),
+            None => Err(("cli_unavailable", "framework_tool not found".to_string())),
+        };
+        let extra = crate::sensors::extra_temps();
+        let extra_availability = if extra.is_empty() {
+            crate::types::SectionAvailability::unavailable(
+                "no_sensors",
+                "no extra sensor provider has reported on this machine".into(),
+            )
+        } else {
+            crate::types::SectionAvailability::available()
+        };
+        let (thermal, ec_availability) = match ec {
+            Ok(t) => (t, crate::types::SectionAvailability::available()),
+            Err((code, message)) if extra.is_empty() => return Err(section_error(code, message)),
+            Err((code, message)) => (
+                crate::cli::framework_tool_parser::ThermalParsed {
+                    temps: extra,
+                    fans: Vec::new(),
+                },
+                crate::types::SectionAvailability::unavailable(code, message),
+            ),
+        };
+        Ok(Json(crate::types::ThermalResponse {
+            thermal,
+            availability: crate::types::ThermalAvailability {
+                ec: ec_availability,
+                extra: extra_availability,
+            },
+        }))
+    }
+
+    /// Sensor catalog: current readings with warn/critical thresholds, location and latency
+    #[oai(path = "/sensors", method = "get", operation_id = "getSensors")]
+    async fn get_sensors(&self, state: Data<&AppState>) -> ApiResult<crate::types::SensorCatalog> {
+        let sensors_cfg = state.config.read().await.sensors.clone();
+        let cli = { state.framework_tool.read().await.clone() };
+        Ok(Json(crate::sensors::catalog(cli.as_ref(), &sensors_cfg).await))
+    }
+
+    /// Every sensor's latest reading, trend over the last minute and threshold level in one
+    /// compact payload, served from the telemetry samples without reading the hardware
+    #[oai(path = "/sensors/heatmap", method = "get", operation_id = "getSensorHeatmap")]
+    async fn get_sensor_heatmap(&self, state: Data<&AppState>) -> ApiResult<crate::types::SensorHeatmap> {
+        let sensors_cfg = state.config.read().await.sensors.clone();
+        let samples = state.telemetry_samples.read().await;
+        Ok(Json(crate::sensors::heatmap(&samples, &sensors_cfg)))
+    }
+
+    /// Telemetry history: returns recent samples collected by the service
+    #[oai(path = "/thermal/history", method = "get", operation_id = "getThermalHistory")]
+    async fn get_thermal_history(&self, state: Data<&AppState>) -> ApiResult<Vec<crate::types::TelemetrySample>> {
+        let samples: Vec<crate::types::TelemetrySample> = {
+            let r = state.telemetry_samples.read().await;
+            r.iter().cloned().collect()
+        };
+        Ok(Json(samples))
+    }
+
+    /// Fan duties and TDPs the service applied and why (curve, manual, override, target,
+    /// emergency, profile, budget, restore), on the telemetry clock. Defaults to the retained
+    /// telemetry window; `since_ms` narrows it
+    #[oai(path = "/actions/history", method = "get", operation_id = "getActionsHistory")]
+    async fn get_actions_history(
+        &self,
+        state: Data<&AppState>,
+        since_ms: Query<Option<i64>>,
+    ) -> ApiResult<Vec<crate::types::ControlAction>> {
+        let oldest_sample = state.telemetry_samples.read().await.front().map(|s| s.ts_ms);
+        let since = match (since_ms.0, oldest_sample) {
+            (Some(since), _) => since,
+            (None, Some(oldest)) => oldest,
+            (None, None) => 0,
+        };
+        Ok(Json(crate::actions::since(since)))
+    }
+
+    /// Preview of the scheduled telemetry report for the period so far (nothing is sent or reset)
+    #[oai(path = "/reports/current", method = "get", operation_id = "getCurrentReport")]
+    async fn get_current_report(&self, state: Data<&AppState>) -> ApiResult<crate::types::TelemetryReport> {
+        let (reports_cfg, ui_cfg) = {
+            let cfg = state.config.read().await;
+            (cfg.reports.clone(), cfg.ui.clone())
+        };
+        let cli = { state.framework_tool.read().await.clone() };
+        let health = match &cli {
+            Some(cli) => crate::reports::battery_health_pct(cli).await,
+            None => None,
+        };
+        Ok(Json(crate::reports::current_report(&reports_cfg, &ui_cfg, health)))
+    }
+
+    /// Alerts: conditions currently needing attention (e.g. an underpowered charger)
+    #[oai(path = "/alerts", method = "get", operation_id = "getAlerts")]
+    async fn get_alerts(&self, state: Data<&AppState>) -> ApiResult<Vec<crate::types::Alert>> {
+        let ui = state.config.read().await.ui.clone();
+        let alerts = crate::alerts::active()
+            .into_iter()
+            .map(|mut a| {
+                a.since = Some(crate::units::format_timestamp(a.since_ms, &ui));
+                a
+            })
+            .collect();
+        Ok(Json(alerts))
+    }
+
+    /// Service-internal metrics (CLI calls, cache hit rates, task loop and HTTP timings) in
+    /// Prometheus text format
+    #[oai(path = "/metrics", method = "get", operation_id = "getMetrics")]
+    async fn get_metrics(&self) -> Result<PlainText<String>, ApiErrorResponse> {
+        Ok(PlainText(crate::metrics::render()))
+    }
+}
+
+pub(super) const SCOPES: &[(&str, AuthScope)] = &[
+    ("getThermal", AuthScope::Read),
+    ("getSensors", AuthScope::Read),
+    ("getSensorHeatmap", AuthScope::Read),
+    ("getThermalHistory", AuthScope::Read),
+    ("getActionsHistory", AuthScope::Read),
+    ("getCurrentReport", AuthScope::Read),
+    ("getAlerts", AuthScope::Read),
+    ("getMetrics", AuthScope::Read),
+];
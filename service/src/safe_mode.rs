@@ -0,0 +1,117 @@
+// Safe mode: start with every control task disabled (monitoring only) so a bad config, e.g. a
+// fan pinned at 0%, can be fixed through the UI/API instead of by editing JSON by hand.
+//
+// Entered explicitly (`--safe-mode` or FRAMEWORK_CONTROL_SAFE_MODE=1) or automatically after
+// CRASH_THRESHOLD consecutive boots that exited before the startup grace period elapsed. The
+// boot counter lives next to the config file and is reset once a boot survives the grace period.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+const CRASH_THRESHOLD: u32 = 3;
+const DEFAULT_GRACE_SECS: u64 = 60;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BootState {
+    /// Boots that have not (yet) outlived the grace period
+    early_exits: u32,
+}
+
+static REASON: OnceLock<Option<String>> = OnceLock::new();
+
+fn boot_state_path() -> PathBuf {
+    crate::config::config_path().with_file_name("boot_state.json")
+}
+
+fn load_boot_state() -> BootState {
+    std::fs::read_to_string(boot_state_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_boot_state(state: &BootState) {
+    let path = boot_state_path();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match serde_json::to_string(state) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("safe mode: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("safe mode: failed to serialize boot state: {}", e),
+    }
+}
+
+fn grace_period() -> Duration {
+    let secs = std::env::var("FRAMEWORK_CONTROL_STARTUP_GRACE_SECS")
+        .ok()
+        .or_else(|| option_env!("FRAMEWORK_CONTROL_STARTUP_GRACE_SECS").map(String::from))
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_GRACE_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Decide whether this boot runs in safe mode. Call once at startup, before tasks boot.
+pub fn evaluate() {
+    let mut boot = load_boot_state();
+    let requested = std::env::args().any(|a| a == "--safe-mode")
+        || std::env::var("FRAMEWORK_CONTROL_SAFE_MODE")
+            .ok()
+            .or_else(|| option_env!("FRAMEWORK_CONTROL_SAFE_MODE").map(String::from))
+            .is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+
+    let reason = if requested {
+        Some("safe mode requested at startup".to_string())
+    } else if boot.early_exits >= CRASH_THRESHOLD {
+        Some(format!(
+            "service exited {} times in a row within {}s of starting",
+            boot.early_exits,
+            grace_period().as_secs()
+        ))
+    } else {
+        None
+    };
+
+    boot.early_exits = boot.early_exits.saturating_add(1);
+    save_boot_state(&boot);
+
+    if let Some(r) = &reason {
+        warn!("safe mode: {}; control tasks are disabled (monitoring only)", r);
+        crate::alerts::raise(
+            "safe_mode",
+            crate::types::AlertSeverity::Warning,
+            format!(
+                "Safe mode: {}. Fan, power and battery control are disabled until the service restarts normally.",
+                r
+            ),
+        );
+    }
+    let _ = REASON.set(reason);
+
+    // Once this boot outlives the grace period it no longer counts as an early exit.
+    tokio::spawn(async move {
+        sleep(grace_period()).await;
+        save_boot_state(&BootState::default());
+        info!("safe mode: startup grace period passed; boot counter reset");
+    });
+}
+
+/// A requested stop is not a crash, even within the grace period.
+pub fn clean_exit() {
+    save_boot_state(&BootState::default());
+}
+
+pub fn is_active() -> bool {
+    reason().is_some()
+}
+
+pub fn reason() -> Option<String> {
+    REASON.get().cloned().flatten()
+}
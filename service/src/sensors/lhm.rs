@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use tokio::process::Command;
+#[cfg(target_os = "windows")]
+use tokio::time::{Duration, Instant};
+#[cfg(target_os = "windows")]
+use tracing::{debug, info};
+
+/// While LibreHardwareMonitor is not running its namespace is looked for again this often, so
+/// PowerShell is not spawned every poll for nothing.
+#[cfg(target_os = "windows")]
+const RETRY_MISSING: Duration = Duration::from_secs(5 * 60);
+
+#[cfg(target_os = "windows")]
+static NEXT_TRY: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Temperatures from LibreHardwareMonitor's WMI namespace (`root/LibreHardwareMonitor`, present
+/// while LHM runs elevated): `CPU Package` (Tctl/Tdie on AMD) and, for discrete adapters,
+/// `dGPU Core`/`dGPU Junction`/`dGPU Mem`. Optional: without LHM nothing is reported. Drives
+/// are left to `windows::storage_temps`, whose disk numbering LHM does not share.
+#[cfg(target_os = "windows")]
+pub async fn temps() -> BTreeMap<String, i32> {
+    if NEXT_TRY
+        .lock()
+        .ok()
+        .and_then(|t| *t)
+        .is_some_and(|t| Instant::now() < t)
+    {
+        return BTreeMap::new();
+    }
+    // Invariant culture: `Value` is a float and the locale may use a decimal comma
+    let ps = "$ns = 'root/LibreHardwareMonitor'; \
+              $inv = [cultureinfo]::InvariantCulture; \
+              Get-CimInstance -Namespace $ns -ClassName Hardware -ErrorAction Stop | ForEach-Object { \
+              'H|{0}|{1}|{2}' -f $_.Identifier, $_.HardwareType, $_.Name }; \
+              Get-CimInstance -Namespace $ns -ClassName Sensor -Filter \"SensorType='Temperature'\" | \
+              ForEach-Object { 'S|{0}|{1}|{2}' -f $_.Parent, $_.Name, $_.Value.ToString($inv) }";
+    let out = match Command::new("powershell")
+        .arg("-NoProfile")
+        .arg("-NonInteractive")
+        .arg("-Command")
+        .arg(ps)
+        .output()
+        .await
+    {
+        Ok(out) if out.status.success() => out,
+        Ok(_) => {
+            let first = NEXT_TRY
+                .lock()
+                .map(|mut t| t.replace(Instant::now() + RETRY_MISSING).is_none());
+            if first.unwrap_or(false) {
+                info!("sensors: LibreHardwareMonitor is not running; its sensors are unavailable");
+            }
+            return BTreeMap::new();
+        }
+        Err(e) => {
+            debug!("sensors: LibreHardwareMonitor query failed: {}", e);
+            return BTreeMap::new();
+        }
+    };
+    let discrete: Vec<String> = crate::gpu::adapters()
+        .await
+        .into_iter()
+        .filter(|g| g.discrete)
+        .map(|g| g.name)
+        .collect();
+    name_temps(&String::from_utf8_lossy(&out.stdout), |hw_type, name| {
+        hw_type == "GpuNvidia" || discrete.iter().any(|d| d.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Name `H|identifier|type|name` and `S|parent|sensor|value` lines for the sensor catalog.
+/// `discrete(type, name)` tells a dGPU from the integrated graphics, which LHM also lists.
+fn name_temps(stdout: &str, discrete: impl Fn(&str, &str) -> bool) -> BTreeMap<String, i32> {
+    let mut hardware: BTreeMap<&str, (&str, &str)> = BTreeMap::new();
+    let mut sensors = Vec::new();
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.trim().splitn(4, '|').map(str::trim).collect();
+        match parts.as_slice() {
+            ["H", id, hw_type, name] => {
+                hardware.insert(id, (hw_type, name));
+            }
+            ["S", parent, sensor, value] => sensors.push((*parent, *sensor, *value)),
+            _ => {}
+        }
+    }
+    let mut temps = BTreeMap::new();
+    for (parent, sensor, value) in sensors {
+        let Some((hw_type, hw_name)) = hardware.get(parent) else {
+            continue;
+        };
+        let Ok(value) = value.parse::<f32>() else {
+            continue;
+        };
+        let c = value.round() as i32;
+        if !(1..=150).contains(&c) {
+            continue;
+        }
+        let name = match (*hw_type, sensor) {
+            ("Cpu", "Core (Tctl/Tdie)" | "Core (Tctl)" | "Package" | "CPU Package") => "CPU Package",
+            (t, "GPU Core") if t.starts_with("Gpu") && discrete(t, hw_name) => "dGPU Core",
+            (t, "GPU Hot Spot") if t.starts_with("Gpu") && discrete(t, hw_name) => "dGPU Junction",
+            (t, "GPU Memory") if t.starts_with("Gpu") && discrete(t, hw_name) => "dGPU Mem",
+            _ => continue,
+        };
+        temps.entry(name.to_string()).or_insert(c);
+    }
+    temps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_cpu_package_and_discrete_gpu_only() {
+        let out = "H|/amdcpu/0|Cpu|AMD Ryzen 9 7940HS\n\
+                   H|/gpu-amd/0|GpuAmd|AMD Radeon 780M Graphics\n\
+                   H|/gpu-amd/1|GpuAmd|AMD Radeon RX 7700S\n\
+                   H|/nvme/0|Storage|WD_BLACK SN850X\n\
+                   S|/amdcpu/0|Core (Tctl/Tdie)|71.625\n\
+                   S|/amdcpu/0|CCD1 (Tdie)|69.5\n\
+                   S|/gpu-amd/0|GPU Core|55\n\
+                   S|/gpu-amd/1|GPU Core|62.4\n\
+                   S|/gpu-amd/1|GPU Hot Spot|77.9\n\
+                   S|/gpu-amd/1|GPU Memory|-0\n\
+                   S|/nvme/0|Composite Temperature|48\n";
+        let temps = name_temps(out, |_, name| name.contains(" RX "));
+        assert_eq!(temps.get("CPU Package"), Some(&72));
+        assert_eq!(temps.get("dGPU Core"), Some(&62));
+        assert_eq!(temps.get("dGPU Junction"), Some(&78));
+        assert_eq!(temps.len(), 3);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+
+/// One `temp*_input` reading from a hwmon chip.
+#[derive(Debug, Clone)]
+pub struct HwmonTemp {
+    /// Chip driver name from `hwmonN/name` (e.g. `spd5118`, `nct6799`)
+    pub chip: String,
+    /// Optional `temp*_label`
+    pub label: Option<String>,
+    pub millidegrees: i64,
+}
+
+/// Chips that report DIMM temperatures: DDR5 SPD hubs and DDR4 JC-42.4 sensors.
+const DIMM_CHIPS: &[&str] = &["spd5118", "jc42"];
+
+/// Readings outside this range are disconnected inputs or firmware placeholders.
+const PLAUSIBLE_C: std::ops::RangeInclusive<i32> = 1..=150;
+
+/// Board-level temperatures the EC does not report: DIMM (SPD hub) and VRM sensors.
+/// These matter most on the Desktop, where system fans often should follow the VRM.
+pub async fn board_temps() -> BTreeMap<String, i32> {
+    name_board_temps(&read_hwmon_temps().await)
+}
+
+/// dGPU module temperatures (`dGPU Edge`, `dGPU Junction`, `dGPU Mem`), so the GPU fan curve
+/// and the main fan curves can follow them. Empty when no dGPU module is installed.
+pub async fn gpu_temps() -> BTreeMap<String, i32> {
+    let mut temps = BTreeMap::new();
+    let Some(gpu) = crate::cli::amdgpu_fan::AmdGpuFan::detect().await else {
+        return temps;
+    };
+    for (label, millidegrees) in gpu.temps().await {
+        if let Some(c) = to_celsius(millidegrees) {
+            temps.insert(format!("dGPU {}", capitalize(&label)), c);
+        }
+    }
+    temps
+}
+
+/// NVMe drive temperatures (`NVMe 0`, `NVMe 1`, ... by controller), from the hwmon device the
+/// nvme driver registers for each controller (kernel 5.5+), so no smartctl is needed. The
+/// drive's Composite temperature is used, as on Windows.
+pub async fn storage_temps() -> BTreeMap<String, i32> {
+    let mut temps = BTreeMap::new();
+    for ctrl in sorted_numbered_entries(Path::new("/sys/class/nvme"), "nvme").await {
+        let Some(index) = ctrl
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix("nvme"))
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        // Under the controller on current kernels, under its PCI device on some older ones
+        let mut hwmons = sorted_numbered_entries(&ctrl, "hwmon").await;
+        hwmons.extend(sorted_numbered_entries(&ctrl.join("device/hwmon"), "hwmon").await);
+        let Some(dir) = hwmons.first() else { continue };
+        let mut inputs = Vec::new();
+        for input in sorted_numbered_entries(dir, "temp").await {
+            let Some(prefix) = input
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix("_input"))
+            else {
+                continue;
+            };
+            let Some(millidegrees) = fs::read_to_string(&input)
+                .await
+                .ok()
+                .and_then(|r| r.trim().parse::<i64>().ok())
+            else {
+                continue;
+            };
+            let label = fs::read_to_string(dir.join(format!("{}_label", prefix))).await.ok();
+            inputs.push((label.map(|l| l.trim().to_string()), millidegrees));
+        }
+        if let Some(c) = composite(&inputs) {
+            temps.insert(format!("NVMe {}", index), c);
+        }
+    }
+    temps
+}
+
+/// The `Composite` reading among an NVMe controller's inputs, else its first (`temp1` is the
+/// composite temperature on drivers without labels).
+fn composite(inputs: &[(Option<String>, i64)]) -> Option<i32> {
+    inputs
+        .iter()
+        .find(|(label, _)| label.as_deref() == Some("Composite"))
+        .or(inputs.first())
+        .and_then(|(_, m)| to_celsius(*m))
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Read every hwmon temperature input, in hwmon then input order.
+pub async fn read_hwmon_temps() -> Vec<HwmonTemp> {
+    let mut out = Vec::new();
+    for dir in sorted_numbered_entries(Path::new("/sys/class/hwmon"), "hwmon").await {
+        let Ok(chip) = fs::read_to_string(dir.join("name")).await else {
+            continue;
+        };
+        let chip = chip.trim().to_string();
+        for input in sorted_numbered_entries(&dir, "temp").await {
+            let Some(file) = input.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some(prefix) = file.strip_suffix("_input") else {
+                continue;
+            };
+            let Ok(raw) = fs::read_to_string(&input).await else {
+                continue;
+            };
+            let Ok(millidegrees) = raw.trim().parse::<i64>() else {
+                continue;
+            };
+            let label = fs::read_to_string(dir.join(format!("{}_label", prefix)))
+                .await
+                .ok()
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty());
+            out.push(HwmonTemp {
+                chip: chip.clone(),
+                label,
+                millidegrees,
+            });
+        }
+    }
+    out
+}
+
+/// Entries of `dir` named `<prefix><number>...`, sorted by that number.
+async fn sorted_numbered_entries(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let mut found: Vec<(u32, PathBuf)> = Vec::new();
+    if let Ok(mut entries) = fs::read_dir(dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some(rest) = name.strip_prefix(prefix) else {
+                continue;
+            };
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if let Ok(n) = digits.parse::<u32>() {
+                found.push((n, path));
+            }
+        }
+    }
+    found.sort_by_key(|(n, _)| *n);
+    found.into_iter().map(|(_, p)| p).collect()
+}
+
+pub fn to_celsius(millidegrees: i64) -> Option<i32> {
+    let c = ((millidegrees as f64) / 1000.0).round() as i32;
+    PLAUSIBLE_C.contains(&c).then_some(c)
+}
+
+/// Name DIMM and VRM readings for the sensor catalog. DIMMs are numbered in hwmon order
+/// (`DIMM 0`, `DIMM 1`, ...); VRM inputs keep their label, disambiguated by chip on clashes.
+fn name_board_temps(readings: &[HwmonTemp]) -> BTreeMap<String, i32> {
+    let mut temps = BTreeMap::new();
+    let mut dimm = 0;
+    for r in readings {
+        let Some(c) = to_celsius(r.millidegrees) else {
+            if DIMM_CHIPS.contains(&r.chip.as_str()) {
+                dimm += 1;
+            }
+            continue;
+        };
+        if DIMM_CHIPS.contains(&r.chip.as_str()) {
+            temps.insert(format!("DIMM {}", dimm), c);
+            dimm += 1;
+            continue;
+        }
+        let Some(label) = r.label.as_deref() else { continue };
+        if !label.to_ascii_lowercase().contains("vrm") {
+            continue;
+        }
+        let name = if temps.contains_key(label) {
+            format!("{} ({})", label, r.chip)
+        } else {
+            label.to_string()
+        };
+        temps.insert(name, c);
+    }
+    temps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(chip: &str, label: Option<&str>, millidegrees: i64) -> HwmonTemp {
+        HwmonTemp {
+            chip: chip.to_string(),
+            label: label.map(str::to_string),
+            millidegrees,
+        }
+    }
+
+    #[test]
+    fn names_dimms_and_vrms_only() {
+        let readings = vec![
+            t("k10temp", Some("Tctl"), 55_000),
+            t("spd5118", None, 41_250),
+            t("spd5118", None, 42_750),
+            t("nct6799", Some("SYSTIN"), 33_000),
+            t("nct6799", Some("VRM"), 61_000),
+            t("it8689", Some("VRM"), 58_000),
+        ];
+        let temps = name_board_temps(&readings);
+        assert_eq!(temps.get("DIMM 0"), Some(&41));
+        assert_eq!(temps.get("DIMM 1"), Some(&43));
+        assert_eq!(temps.get("VRM"), Some(&61));
+        assert_eq!(temps.get("VRM (it8689)"), Some(&58));
+        assert_eq!(temps.len(), 4);
+    }
+
+    #[test]
+    fn nvme_uses_the_composite_temperature() {
+        let inputs = vec![
+            (Some("Sensor 1".to_string()), 71_850),
+            (Some("Composite".to_string()), 54_850),
+        ];
+        assert_eq!(composite(&inputs), Some(55));
+        assert_eq!(composite(&[(None, 48_000)]), Some(48));
+        assert_eq!(composite(&[]), None);
+    }
+
+    #[test]
+    fn implausible_dimm_reading_keeps_numbering() {
+        let readings = vec![t("spd5118", None, -273_000), t("spd5118", None, 40_000)];
+        let temps = name_board_temps(&readings);
+        assert_eq!(temps.get("DIMM 0"), None);
+        assert_eq!(temps.get("DIMM 1"), Some(&40));
+    }
+}
@@ -0,0 +1,300 @@
+// Per-sensor metadata: warn/critical thresholds, physical location and source latency, plus
+// the display name clients show instead of the raw EC or provider name.
+//
+// Each field resolves from the most specific layer that sets it: the user's `sensors.overrides`
+// for the sensor, then those for its thermal zones, then the table for the detected board (EC
+// sensor names and placement differ per mainboard), then defaults for the sensor's kind (DIMM,
+// NVMe, dGPU, VRM). Latency is a property of the source rather than the sensor, so the catalog
+// fills it in from where the reading came from.
+//
+// Display names come from the same two tables (the board's EC names first, then the kind) and
+// carry a translation key, so the web UI, the tray bridge and alert texts label a sensor alike.
+
+use std::sync::OnceLock;
+
+use crate::cli::FrameworkTool;
+use std::collections::BTreeMap;
+
+use crate::types::{BoardModel, SensorLabel, SensorLevel, SensorMetadata, SensorsConfig};
+
+static BOARD: OnceLock<(BoardModel, Option<String>)> = OnceLock::new();
+
+/// Detected board and the raw `--versions` mainboard type; `Unknown` until detection succeeds.
+pub fn board() -> (BoardModel, Option<String>) {
+    BOARD.get().cloned().unwrap_or_default()
+}
+
+/// Detect the board once per run; retried on later calls while `framework_tool` fails.
+pub async fn detect_board(cli: &FrameworkTool) {
+    if BOARD.get().is_some() {
+        return;
+    }
+    if let Ok(v) = cli.versions().await {
+        let model = v.mainboard_type.as_deref().map(classify_board).unwrap_or_default();
+        let _ = BOARD.set((model, v.mainboard_type));
+    }
+}
+
+/// Map the mainboard type (e.g. "Laptop 13 (AMD Ryzen 7040 Series)", "Framework16Amd7080")
+/// to a model by the number following the product prefix.
+fn classify_board(mainboard_type: &str) -> BoardModel {
+    let norm: String = mainboard_type
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let rest = norm.strip_prefix("framework").unwrap_or(&norm);
+    let rest = rest.strip_prefix("laptop").unwrap_or(rest);
+    if rest.starts_with("desktop") {
+        BoardModel::Desktop
+    } else if rest.starts_with("16") {
+        BoardModel::Laptop16
+    } else if rest.starts_with("12") {
+        BoardModel::Laptop12
+    } else if rest.starts_with("13") {
+        if rest.contains("amd") || rest.contains("ryzen") {
+            BoardModel::Laptop13Amd
+        } else {
+            BoardModel::Laptop13Intel
+        }
+    } else {
+        BoardModel::Unknown
+    }
+}
+
+fn meta(warn_c: i32, critical_c: i32, location: &str) -> SensorMetadata {
+    SensorMetadata {
+        critical_c: Some(critical_c),
+        ..warn_only(warn_c, location)
+    }
+}
+
+/// No critical value, so the sensor never triggers the fan emergency override: the system fans
+/// barely cool it, and a drive warm from sustained I/O would otherwise pin them at full speed.
+fn warn_only(warn_c: i32, location: &str) -> SensorMetadata {
+    SensorMetadata {
+        warn_c: Some(warn_c),
+        critical_c: None,
+        location: Some(location.to_string()),
+        latency_ms: None,
+    }
+}
+
+/// EC sensors as named by `framework_tool --thermal` on each board. Die sensors only go critical
+/// above the throttle point, where sitting at Tjmax under load is normal.
+fn board_table(board: BoardModel, name: &str) -> Option<SensorMetadata> {
+    use BoardModel::*;
+    Some(match (board, name) {
+        (Laptop12 | Laptop13Intel | Laptop13Amd | Laptop16, "F75303_Local") => meta(70, 85, "Mainboard, near the EC"),
+        (Laptop12 | Laptop13Intel | Laptop13Amd | Laptop16, "F75303_CPU") => meta(85, 95, "Mainboard, next to the CPU"),
+        (Laptop13Intel | Laptop13Amd | Laptop16, "F75303_DDR") => meta(80, 90, "Mainboard, next to the memory"),
+        (Laptop12 | Laptop13Intel, "PECI") => meta(95, 105, "CPU package"),
+        (Laptop12 | Laptop13Intel, "Battery") => meta(45, 55, "Battery pack"),
+        (Laptop13Amd | Laptop16 | Desktop, "APU") => meta(95, 105, "APU package"),
+        (Laptop16, "dGPU temp") => meta(95, 105, "Graphics module, GPU die"),
+        (Laptop16, "dGPU VR") => meta(100, 115, "Graphics module, voltage regulator"),
+        (Laptop16, "dGPU VRAM") => meta(90, 100, "Graphics module, memory"),
+        (Laptop16, "dGPU AMB") => meta(70, 85, "Graphics module, ambient"),
+        (Desktop, "F75303_APU") => meta(90, 100, "Mainboard, next to the APU"),
+        (Desktop, "F75303_DDR") => meta(85, 95, "Mainboard, next to the memory"),
+        (Desktop, "F75303_AMB") => meta(60, 75, "Mainboard, ambient"),
+        _ => return None,
+    })
+}
+
+/// Defaults by sensor kind for the extra providers, whose names are board-independent.
+fn kind_defaults(name: &str) -> Option<SensorMetadata> {
+    Some(if name.starts_with("DIMM ") {
+        meta(80, 90, "Memory module")
+    } else if name.starts_with("NVMe ") {
+        warn_only(70, "NVMe drive")
+    } else if name.starts_with("Disk ") {
+        warn_only(55, "Drive")
+    } else if name == "CPU Package" {
+        meta(95, 105, "CPU package")
+    } else if name == "dGPU Core" {
+        meta(87, 95, "Discrete GPU, core")
+    } else if name == "dGPU Edge" {
+        meta(90, 100, "Discrete GPU, edge")
+    } else if name == "dGPU Junction" {
+        meta(100, 110, "Discrete GPU, hotspot")
+    } else if name == "dGPU Mem" {
+        meta(95, 105, "Discrete GPU, memory")
+    } else if name.to_ascii_lowercase().contains("vrm") {
+        meta(100, 115, "Voltage regulator")
+    } else {
+        return None;
+    })
+}
+
+fn label(key: &str, text: &str) -> SensorLabel {
+    SensorLabel {
+        key: key.to_string(),
+        text: text.to_string(),
+        params: BTreeMap::new(),
+    }
+}
+
+/// Display names of the EC sensors in `board_table`.
+fn board_label(board: BoardModel, name: &str) -> Option<SensorLabel> {
+    use BoardModel::*;
+    Some(match (board, name) {
+        (Laptop12 | Laptop13Intel | Laptop13Amd | Laptop16, "F75303_Local") => label("mainboard", "Mainboard"),
+        (Laptop12 | Laptop13Intel | Laptop13Amd | Laptop16, "F75303_CPU") => label("cpu_area", "Near CPU"),
+        (Laptop13Intel | Laptop13Amd | Laptop16 | Desktop, "F75303_DDR") => label("memory_area", "Near memory"),
+        (Laptop12 | Laptop13Intel, "PECI") => label("cpu", "CPU"),
+        (Laptop12 | Laptop13Intel, "Battery") => label("battery", "Battery"),
+        (Laptop13Amd | Laptop16 | Desktop, "APU") => label("apu", "APU"),
+        (Laptop16, "dGPU temp") => label("module_gpu", "Graphics module GPU"),
+        (Laptop16, "dGPU VR") => label("module_vrm", "Graphics module VRM"),
+        (Laptop16, "dGPU VRAM") => label("module_memory", "Graphics module memory"),
+        (Laptop16, "dGPU AMB") => label("module_ambient", "Graphics module ambient"),
+        (Desktop, "F75303_APU") => label("apu_area", "Near APU"),
+        (Desktop, "F75303_AMB") => label("ambient", "Ambient"),
+        _ => return None,
+    })
+}
+
+/// Display names by kind for the extra providers; numbered kinds pass the number as `index`.
+fn kind_label(name: &str) -> Option<SensorLabel> {
+    let numbered = |prefix: &str, key: &str, text: &str| {
+        let index = name.strip_prefix(prefix)?.trim();
+        index.parse::<u32>().ok()?;
+        Some(SensorLabel {
+            key: key.to_string(),
+            text: format!("{} {}", text, index),
+            params: BTreeMap::from([("index".to_string(), index.to_string())]),
+        })
+    };
+    numbered("DIMM ", "dimm", "Memory module")
+        .or_else(|| numbered("NVMe ", "nvme", "NVMe drive"))
+        .or_else(|| numbered("Disk ", "disk", "Drive"))
+        .or(match name {
+            "CPU Package" => Some(label("cpu_package", "CPU package")),
+            "dGPU Core" => Some(label("dgpu_core", "dGPU core")),
+            "dGPU Edge" => Some(label("dgpu_edge", "dGPU edge")),
+            "dGPU Junction" => Some(label("dgpu_hotspot", "dGPU hotspot")),
+            "dGPU Mem" => Some(label("dgpu_memory", "dGPU memory")),
+            _ => None,
+        })
+}
+
+/// Display name of `name` on `board`; unknown sensors keep their raw name under the `raw` key.
+pub fn display_name(board: BoardModel, name: &str) -> SensorLabel {
+    board_label(board, name)
+        .or_else(|| kind_label(name))
+        .unwrap_or_else(|| SensorLabel {
+            key: "raw".to_string(),
+            text: name.to_string(),
+            params: BTreeMap::from([("name".to_string(), name.to_string())]),
+        })
+}
+
+/// Fields set in `over` win; the rest come from `base`.
+fn layer(base: SensorMetadata, over: &SensorMetadata) -> SensorMetadata {
+    SensorMetadata {
+        warn_c: over.warn_c.or(base.warn_c),
+        critical_c: over.critical_c.or(base.critical_c),
+        location: over.location.clone().or(base.location),
+        latency_ms: over.latency_ms.or(base.latency_ms),
+    }
+}
+
+pub fn resolve(board: BoardModel, name: &str, cfg: &SensorsConfig) -> SensorMetadata {
+    let mut out = kind_defaults(name).unwrap_or_default();
+    if let Some(b) = board_table(board, name) {
+        out = layer(out, &b);
+    }
+    for zone in super::zones::zones_of(name, &cfg.zones) {
+        if let Some(o) = cfg.overrides.get(&zone) {
+            out = layer(out, o);
+        }
+    }
+    if let Some(o) = cfg.overrides.get(name) {
+        out = layer(out, o);
+    }
+    out
+}
+
+pub fn level(meta: &SensorMetadata, temp_c: i32) -> SensorLevel {
+    if meta.critical_c.is_some_and(|c| temp_c >= c) {
+        SensorLevel::Critical
+    } else if meta.warn_c.is_some_and(|w| temp_c >= w) {
+        SensorLevel::Warn
+    } else {
+        SensorLevel::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board_from_mainboard_type() {
+        assert_eq!(
+            classify_board("Laptop 13 (AMD Ryzen 7040 Series)"),
+            BoardModel::Laptop13Amd
+        );
+        assert_eq!(classify_board("Framework13IntelGen12"), BoardModel::Laptop13Intel);
+        assert_eq!(classify_board("Framework12IntelGen13"), BoardModel::Laptop12);
+        assert_eq!(classify_board("Framework16Amd7080"), BoardModel::Laptop16);
+        assert_eq!(classify_board("FrameworkDesktopAmdAiMax300"), BoardModel::Desktop);
+        assert_eq!(classify_board("Unknown"), BoardModel::Unknown);
+    }
+
+    #[test]
+    fn override_fields_win_over_board_table() {
+        let cfg = SensorsConfig {
+            overrides: BTreeMap::from([
+                (
+                    "APU".to_string(),
+                    SensorMetadata {
+                        critical_c: Some(100),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "CPU".to_string(),
+                    SensorMetadata {
+                        critical_c: Some(90),
+                        location: Some("CPU".to_string()),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+        let m = resolve(BoardModel::Laptop13Amd, "APU", &cfg);
+        // The zone override sits between the board table and the sensor's own override
+        assert_eq!(m.location.as_deref(), Some("CPU"));
+        assert_eq!((m.warn_c, m.critical_c), (Some(95), Some(100)));
+        assert_eq!(level(&m, 94), SensorLevel::Normal);
+        assert_eq!(level(&m, 95), SensorLevel::Warn);
+        assert_eq!(level(&m, 100), SensorLevel::Critical);
+        // No board table entry on an unknown board, and no kind default for EC names
+        assert_eq!(
+            resolve(BoardModel::Unknown, "F75303_Local", &SensorsConfig::default()),
+            SensorMetadata::default()
+        );
+        assert_eq!(
+            resolve(BoardModel::Unknown, "DIMM 0", &SensorsConfig::default()).critical_c,
+            Some(90)
+        );
+        let nvme = resolve(BoardModel::Unknown, "NVMe 0", &SensorsConfig::default());
+        assert_eq!((nvme.warn_c, nvme.critical_c), (Some(70), None));
+    }
+
+    #[test]
+    fn display_names_by_board_then_kind() {
+        assert_eq!(display_name(BoardModel::Laptop13Amd, "F75303_CPU").text, "Near CPU");
+        assert_eq!(display_name(BoardModel::Desktop, "F75303_AMB").key, "ambient");
+        // EC names are board-specific: PECI only exists on Intel boards
+        let raw = display_name(BoardModel::Laptop13Amd, "PECI");
+        assert_eq!((raw.key.as_str(), raw.text.as_str()), ("raw", "PECI"));
+        let dimm = display_name(BoardModel::Unknown, "DIMM 1");
+        assert_eq!((dimm.key.as_str(), dimm.text.as_str()), ("dimm", "Memory module 1"));
+        assert_eq!(dimm.params.get("index").map(String::as_str), Some("1"));
+        assert_eq!(display_name(BoardModel::Unknown, "DIMM A").key, "raw");
+    }
+}
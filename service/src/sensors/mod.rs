@@ -0,0 +1,237 @@
+// Shared sensor catalog.
+//
+// `framework_tool --thermal` only reports what the EC sees. Extra platform providers (storage,
+// GPU, ...) are polled by the `sensors` background task and merged in here, so fan curves,
+// telemetry and `/thermal` all see the same set of sensor names. `metadata` attaches thresholds,
+// location and latency to those names; `zones` groups them into board-independent thermal zones.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+use tokio::time::Duration;
+
+use crate::cli::framework_tool_parser::ThermalParsed;
+use crate::cli::FrameworkTool;
+use crate::types::{
+    HeatmapSensor, SensorCatalog, SensorHeatmap, SensorInfo, SensorLevel, SensorsConfig, TelemetrySample,
+};
+
+pub mod metadata;
+mod nvidia;
+pub mod zones;
+
+/// History the heatmap trends are computed over.
+const HEATMAP_WINDOW_S: u32 = 60;
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(any(target_os = "windows", test))]
+mod lhm;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+fn extra_store() -> &'static RwLock<BTreeMap<String, i32>> {
+    static EXTRA: OnceLock<RwLock<BTreeMap<String, i32>>> = OnceLock::new();
+    EXTRA.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Latest readings from the extra providers (may be empty).
+pub fn extra_temps() -> BTreeMap<String, i32> {
+    extra_store().read().map(|m| m.clone()).unwrap_or_default()
+}
+
+static EXTRA_COLLECTED: AtomicBool = AtomicBool::new(false);
+
+/// Replace the extra readings with a freshly collected set.
+pub fn store_extra_temps(temps: BTreeMap<String, i32>) {
+    if let Ok(mut w) = extra_store().write() {
+        *w = temps;
+    }
+    EXTRA_COLLECTED.store(true, Ordering::Relaxed);
+}
+
+/// True once the extra providers have been polled at least once since startup.
+pub fn extra_collected() -> bool {
+    EXTRA_COLLECTED.load(Ordering::Relaxed)
+}
+
+/// Query every extra provider available on this platform. Slow providers are fine here:
+/// this only runs on the collector task, never on a control loop.
+pub async fn collect_extra_temps() -> BTreeMap<String, i32> {
+    let mut out = BTreeMap::new();
+    #[cfg(target_os = "windows")]
+    {
+        out.extend(windows::storage_temps().await);
+        for (name, value) in lhm::temps().await {
+            out.entry(name).or_insert(value);
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        out.extend(linux::board_temps().await);
+        out.extend(linux::gpu_temps().await);
+        out.extend(linux::storage_temps().await);
+    }
+    out.extend(nvidia::gpu_temps().await);
+    out
+}
+
+/// EC thermal report with extra sensors merged in. EC readings win on name clashes.
+pub async fn thermal(cli: &FrameworkTool) -> Result<ThermalParsed, String> {
+    let mut parsed = cli.thermal().await?;
+    merge_extra(&mut parsed);
+    Ok(parsed)
+}
+
+/// Like [`thermal`], with a caller-chosen maximum cache age for the EC read.
+pub async fn thermal_max_age(cli: &FrameworkTool, max_age: Duration) -> Result<ThermalParsed, String> {
+    let mut parsed = cli.thermal_max_age(max_age).await?;
+    merge_extra(&mut parsed);
+    Ok(parsed)
+}
+
+fn merge_extra(parsed: &mut ThermalParsed) {
+    for (name, value) in extra_temps() {
+        parsed.temps.entry(name).or_insert(value);
+    }
+}
+
+/// Every sensor currently reporting, plus any only named in `overrides` (zone-keyed overrides
+/// aside), with resolved metadata, and the zones they form.
+pub async fn catalog(cli: Option<&FrameworkTool>, cfg: &SensorsConfig) -> SensorCatalog {
+    let ec = match cli {
+        Some(cli) => cli.thermal().await.map(|t| t.temps).unwrap_or_default(),
+        None => BTreeMap::new(),
+    };
+    let extra = extra_temps();
+    let (board, mainboard_type) = metadata::board();
+
+    let mut names: Vec<&String> = ec
+        .keys()
+        .chain(extra.keys())
+        .chain(cfg.overrides.keys().filter(|k| !zones::is_zone(k, &cfg.zones)))
+        .collect();
+    names.sort();
+    names.dedup();
+    let sensors: Vec<SensorInfo> = names
+        .into_iter()
+        .map(|name| {
+            let temp_c = ec.get(name).or(extra.get(name)).copied();
+            let mut meta = metadata::resolve(board, name, cfg);
+            if meta.latency_ms.is_none() && temp_c.is_some() {
+                let source_age = if ec.contains_key(name) {
+                    crate::cli::framework_tool::THERMAL_CACHE_AGE
+                } else {
+                    crate::tasks::sensors::POLL_INTERVAL
+                };
+                meta.latency_ms = Some(source_age.as_millis() as u32);
+            }
+            SensorInfo {
+                name: name.clone(),
+                label: metadata::display_name(board, name),
+                temp_c,
+                level: temp_c.map(|t| metadata::level(&meta, t)).unwrap_or(SensorLevel::Normal),
+                metadata: meta,
+                overridden: cfg.overrides.contains_key(name),
+                zones: zones::zones_of(name, &cfg.zones),
+            }
+        })
+        .collect();
+
+    let temps: BTreeMap<String, i32> = sensors
+        .iter()
+        .filter_map(|s| s.temp_c.map(|t| (s.name.clone(), t)))
+        .collect();
+    let levels = sensors.iter().map(|s| (s.name.clone(), s.level)).collect();
+    SensorCatalog {
+        board,
+        mainboard_type,
+        emergency_active: crate::tasks::fan_curve::emergency_active(),
+        zones: zones::catalog(&temps, &levels, &cfg.zones),
+        sensors,
+    }
+}
+
+/// Latest reading, trend and threshold level of every sensor, from the telemetry samples alone
+/// (no hardware read), for UIs that poll often.
+pub fn heatmap(samples: &VecDeque<TelemetrySample>, cfg: &SensorsConfig) -> SensorHeatmap {
+    let (board, _) = metadata::board();
+    let Some(latest) = samples.back() else {
+        return SensorHeatmap {
+            ts_ms: None,
+            window_s: HEATMAP_WINDOW_S,
+            emergency_active: crate::tasks::fan_curve::emergency_active(),
+            sensors: Vec::new(),
+        };
+    };
+    let cutoff_ms = latest.ts_ms - HEATMAP_WINDOW_S as i64 * 1000;
+    let sensors = latest
+        .temps
+        .iter()
+        .map(|(name, &temp_c)| {
+            let meta = metadata::resolve(board, name, cfg);
+            HeatmapSensor {
+                name: name.clone(),
+                label: metadata::display_name(board, name),
+                temp_c,
+                trend_c_per_min: trend(samples, name, cutoff_ms),
+                level: metadata::level(&meta, temp_c),
+                headroom_c: meta.critical_c.map(|c| c - temp_c),
+            }
+        })
+        .collect();
+    SensorHeatmap {
+        ts_ms: Some(latest.ts_ms),
+        window_s: HEATMAP_WINDOW_S,
+        emergency_active: crate::tasks::fan_curve::emergency_active(),
+        sensors,
+    }
+}
+
+/// °C per minute between the oldest reading of `name` since `cutoff_ms` and the latest one,
+/// rounded to 0.1.
+fn trend(samples: &VecDeque<TelemetrySample>, name: &str, cutoff_ms: i64) -> f32 {
+    let mut readings = samples
+        .iter()
+        .filter(|s| s.ts_ms >= cutoff_ms)
+        .filter_map(|s| s.temps.get(name).map(|&t| (s.ts_ms, t)));
+    let Some(first) = readings.next() else { return 0.0 };
+    let Some(last) = readings.next_back() else { return 0.0 };
+    let minutes = (last.0 - first.0) as f32 / 60_000.0;
+    if minutes <= 0.0 {
+        return 0.0;
+    }
+    ((last.1 - first.1) as f32 / minutes * 10.0).round() / 10.0
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ts_ms: i64, apu: i32) -> TelemetrySample {
+        TelemetrySample {
+            ts_ms,
+            temps: BTreeMap::from([("APU".to_string(), apu)]),
+            rpms: Vec::new(),
+            duty_pct: None,
+            power_rails: None,
+        }
+    }
+
+    #[test]
+    fn trend_covers_the_window_only() {
+        let samples: VecDeque<TelemetrySample> = [
+            sample(0, 90),
+            sample(60_000, 50),
+            sample(90_000, 55),
+            sample(120_000, 60),
+        ]
+        .into();
+        // The 0 ms reading is outside a window starting at 60 s
+        assert_eq!(trend(&samples, "APU", 60_000), 10.0);
+        assert_eq!(trend(&samples, "APU", 120_000), 0.0);
+        assert_eq!(trend(&samples, "NVMe 0", 0), 0.0);
+    }
+}
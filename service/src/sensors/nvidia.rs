@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::process::Command;
+use tracing::{debug, info};
+
+/// Set once `nvidia-smi` turned out not to be installed, so it is not spawned every poll.
+static MISSING: AtomicBool = AtomicBool::new(false);
+
+/// NVIDIA dGPU temperatures from `nvidia-smi` (`dGPU Core`, `dGPU Mem` where the board reports
+/// it), on Windows and on Linux with the proprietary driver, which exposes no hwmon. AMD modules
+/// come from the amdgpu hwmon (Linux) or the EC's own dGPU sensors (Windows). Empty when no
+/// NVIDIA GPU or driver is present.
+pub async fn gpu_temps() -> BTreeMap<String, i32> {
+    if MISSING.load(Ordering::Relaxed) {
+        return BTreeMap::new();
+    }
+    #[cfg(target_os = "linux")]
+    if runtime_suspended().await {
+        // nvidia-smi would wake the GPU (and keep it awake at this poll rate)
+        return BTreeMap::new();
+    }
+    let out = match Command::new("nvidia-smi")
+        .arg("--query-gpu=temperature.gpu,temperature.memory")
+        .arg("--format=csv,noheader,nounits")
+        .output()
+        .await
+    {
+        Ok(out) if out.status.success() => out,
+        Ok(out) => {
+            debug!("sensors: nvidia-smi exited with {}", out.status);
+            return BTreeMap::new();
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            info!("sensors: nvidia-smi not found; NVIDIA GPU temperatures unavailable");
+            MISSING.store(true, Ordering::Relaxed);
+            return BTreeMap::new();
+        }
+        Err(e) => {
+            debug!("sensors: nvidia-smi failed: {}", e);
+            return BTreeMap::new();
+        }
+    };
+    parse_gpu_temps(&String::from_utf8_lossy(&out.stdout))
+}
+
+/// True when every NVIDIA PCI function is runtime-suspended (D3cold), i.e. the dGPU is asleep.
+#[cfg(target_os = "linux")]
+async fn runtime_suspended() -> bool {
+    let Ok(mut entries) = tokio::fs::read_dir("/sys/bus/pci/devices").await else {
+        return false;
+    };
+    let mut found = false;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let dir = entry.path();
+        let vendor = tokio::fs::read_to_string(dir.join("vendor")).await.unwrap_or_default();
+        if vendor.trim() != "0x10de" {
+            continue;
+        }
+        found = true;
+        let status = tokio::fs::read_to_string(dir.join("power/runtime_status"))
+            .await
+            .unwrap_or_default();
+        if status.trim() != "suspended" {
+            return false;
+        }
+    }
+    found
+}
+
+/// Parse `gpu, memory` lines (`[N/A]` where a reading is unsupported). The first GPU is `dGPU`,
+/// any further ones `dGPU <index>`.
+fn parse_gpu_temps(stdout: &str) -> BTreeMap<String, i32> {
+    let mut temps = BTreeMap::new();
+    for (i, line) in stdout.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+        let prefix = if i == 0 {
+            "dGPU".to_string()
+        } else {
+            format!("dGPU {}", i)
+        };
+        let fields = line.split(',').map(|f| f.trim().parse::<i32>().ok());
+        let names = ["Core", "Mem"];
+        for (name, value) in names.iter().zip(fields) {
+            if let Some(c) = value.filter(|c| (1..=150).contains(c)) {
+                temps.insert(format!("{} {}", prefix, name), c);
+            }
+        }
+    }
+    temps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_unsupported_readings() {
+        let temps = parse_gpu_temps("54, [N/A]\n61, 70\n");
+        assert_eq!(temps.get("dGPU Core"), Some(&54));
+        assert_eq!(temps.get("dGPU Mem"), None);
+        assert_eq!(temps.get("dGPU 1 Core"), Some(&61));
+        assert_eq!(temps.get("dGPU 1 Mem"), Some(&70));
+        assert!(parse_gpu_temps("").is_empty());
+    }
+}
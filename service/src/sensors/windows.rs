@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use tokio::process::Command;
+use tracing::debug;
+
+/// A storage query still running after this is killed; a wedged WMI provider must not stall the
+/// sensor loop.
+const STORAGE_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Storage temperatures from the Storage Management API (`Get-StorageReliabilityCounter`), which
+/// reads NVMe SMART health data through the driver, so no vendor tooling is required.
+/// Sensors are named `NVMe <n>` (or `Disk <n>` for other bus types) by physical disk number.
+pub async fn storage_temps() -> BTreeMap<String, i32> {
+    let ps = "Get-PhysicalDisk | ForEach-Object { \
+              $r = $_ | Get-StorageReliabilityCounter -ErrorAction SilentlyContinue; \
+              '{0}|{1}|{2}' -f $_.DeviceId, $_.BusType, $r.Temperature }";
+    let mut cmd = Command::new("powershell");
+    cmd.arg("-NoProfile")
+        .arg("-NonInteractive")
+        .arg("-Command")
+        .arg(ps)
+        .kill_on_drop(true);
+    // On timeout the `output()` future is dropped, and with it (killed) the child
+    let Ok(result) = tokio::time::timeout(STORAGE_QUERY_TIMEOUT, cmd.output()).await else {
+        debug!("sensors: storage query timed out after {:?}", STORAGE_QUERY_TIMEOUT);
+        return BTreeMap::new();
+    };
+    let out = match result {
+        Ok(out) if out.status.success() => out,
+        Ok(out) => {
+            debug!("sensors: storage query exited with {}", out.status);
+            return BTreeMap::new();
+        }
+        Err(e) => {
+            debug!("sensors: storage query failed: {}", e);
+            return BTreeMap::new();
+        }
+    };
+    parse_storage_temps(&String::from_utf8_lossy(&out.stdout))
+}
+
+/// Parse `DeviceId|BusType|Temperature` lines. Disks that report no (or a zero) temperature are skipped.
+fn parse_storage_temps(stdout: &str) -> BTreeMap<String, i32> {
+    let mut temps = BTreeMap::new();
+    for line in stdout.lines() {
+        let mut parts = line.trim().splitn(3, '|');
+        let (Some(id), Some(bus), Some(temp)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let Ok(value) = temp.trim().parse::<i32>() else {
+            continue;
+        };
+        if value <= 0 {
+            continue;
+        }
+        let prefix = if bus.trim().eq_ignore_ascii_case("nvme") {
+            "NVMe"
+        } else {
+            "Disk"
+        };
+        temps.insert(format!("{} {}", prefix, id.trim()), value);
+    }
+    temps
+}
@@ -0,0 +1,120 @@
+// Thermal zones: logical groups (CPU, GPU, VRM, Memory, SSD, Chassis, Battery) over the raw sensor
+// names, so curves and threshold overrides can say "CPU" on every board instead of `APU` on one
+// and `PECI` on another. Each sensor gets a built-in zone from its name; a zone defined in
+// `sensors.zones` replaces the built-in membership of that zone entirely (an empty list hides it).
+
+use std::collections::BTreeMap;
+
+use crate::types::{SensorLevel, ThermalZone};
+
+/// Built-in zone of a sensor, by the EC and extra-provider naming.
+pub fn builtin_zone(name: &str) -> Option<&'static str> {
+    let lower = name.to_ascii_lowercase();
+    Some(match name {
+        "APU" | "PECI" | "F75303_CPU" | "F75303_APU" => "CPU",
+        "F75303_Local" | "F75303_AMB" => "Chassis",
+        "F75303_DDR" => "Memory",
+        "Battery" => "Battery",
+        _ if lower.contains("vrm") || name == "dGPU VR" => "VRM",
+        _ if name.starts_with("dGPU") => "GPU",
+        _ if name.starts_with("DIMM ") => "Memory",
+        _ if name.starts_with("NVMe ") || name.starts_with("Disk ") => "SSD",
+        "CPU Package" => "CPU",
+        _ => return None,
+    })
+}
+
+/// True when `name` names a zone: built-in or defined in `user`.
+pub fn is_zone(name: &str, user: &BTreeMap<String, Vec<String>>) -> bool {
+    const BUILTIN: [&str; 7] = ["CPU", "GPU", "VRM", "Memory", "SSD", "Chassis", "Battery"];
+    BUILTIN.iter().any(|z| z.eq_ignore_ascii_case(name)) || user.keys().any(|z| z.eq_ignore_ascii_case(name))
+}
+
+/// Zones the sensor `name` belongs to: user zones listing it, then its built-in zone unless the
+/// user redefined that zone.
+pub fn zones_of(name: &str, user: &BTreeMap<String, Vec<String>>) -> Vec<String> {
+    let mut out: Vec<String> = user
+        .iter()
+        .filter(|(_, members)| members.iter().any(|m| m.eq_ignore_ascii_case(name)))
+        .map(|(zone, _)| zone.clone())
+        .collect();
+    if let Some(zone) = builtin_zone(name) {
+        if !user.keys().any(|k| k.eq_ignore_ascii_case(zone)) {
+            out.push(zone.to_string());
+        }
+    }
+    out
+}
+
+/// Replace zone names in a curve's sensor list with the reporting sensors they cover. Other
+/// entries pass through as sensor names.
+pub fn expand(
+    requested: &[String],
+    temps: &BTreeMap<String, i32>,
+    user: &BTreeMap<String, Vec<String>>,
+) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for name in requested {
+        let members = temps
+            .keys()
+            .filter(|sensor| zones_of(sensor, user).iter().any(|z| z.eq_ignore_ascii_case(name)));
+        out.extend(members.cloned());
+        out.push(name.clone());
+    }
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Every zone with at least one reporting sensor; a zone reads as its hottest member.
+pub fn catalog(
+    temps: &BTreeMap<String, i32>,
+    levels: &BTreeMap<String, SensorLevel>,
+    user: &BTreeMap<String, Vec<String>>,
+) -> Vec<ThermalZone> {
+    let mut zones: BTreeMap<String, ThermalZone> = BTreeMap::new();
+    for (sensor, &temp) in temps {
+        for zone in zones_of(sensor, user) {
+            let z = zones.entry(zone.clone()).or_insert_with(|| ThermalZone {
+                name: zone,
+                sensors: Vec::new(),
+                temp_c: None,
+                level: SensorLevel::Normal,
+            });
+            z.sensors.push(sensor.clone());
+            z.temp_c = Some(z.temp_c.map_or(temp, |t| t.max(temp)));
+            if let Some(&level) = levels.get(sensor) {
+                z.level = z.level.max(level);
+            }
+        }
+    }
+    zones.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_zone_replaces_builtin_membership() {
+        let temps = BTreeMap::from([
+            ("APU".to_string(), 70),
+            ("F75303_CPU".to_string(), 55),
+            ("F75303_Local".to_string(), 45),
+        ]);
+        let none = BTreeMap::new();
+        assert_eq!(
+            expand(&["cpu".to_string()], &temps, &none),
+            ["APU", "F75303_CPU", "cpu"]
+        );
+
+        let user = BTreeMap::from([("CPU".to_string(), vec!["APU".to_string()])]);
+        assert_eq!(expand(&["CPU".to_string()], &temps, &user), ["APU", "CPU"]);
+        assert!(zones_of("F75303_CPU", &user).is_empty());
+        let zones = catalog(&temps, &BTreeMap::new(), &user);
+        let names: Vec<&str> = zones.iter().map(|z| z.name.as_str()).collect();
+        assert_eq!(names, ["CPU", "Chassis"]);
+        assert_eq!(zones[0].temp_c, Some(70));
+        assert!(is_zone("chassis", &user) && !is_zone("APU", &user));
+    }
+}
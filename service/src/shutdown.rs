@@ -0,0 +1,120 @@
+// Best-effort hardware hand-back when the service goes away: fans back to EC control and managed
+// power settings back to what the backend reported before the service first applied anything.
+// The charge limit and rate stay in the EC: it keeps them while the machine is off, so resetting
+// them on a reboot or shutdown would charge to 100% overnight.
+//
+// Runs on a stop request (Ctrl+C from WinSW, SIGTERM from systemd) and from a panic hook. The
+// hook only hands the hardware back; whether the process goes down is left to the runtime and
+// the task supervisors, which contain panics in spawned tasks and request handlers.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use tokio::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::state::AppState;
+
+/// Upper bound for the whole hand-back; a wedged CLI must not keep a crashed process alive.
+const RESTORE_TIMEOUT: Duration = Duration::from_secs(10);
+
+static STATE: OnceLock<AppState> = OnceLock::new();
+static RESTORED: AtomicBool = AtomicBool::new(false);
+
+/// Register the panic hook and the stop-signal listener. Call once, inside the runtime.
+pub fn install(state: &AppState) {
+    let _ = STATE.set(state.clone());
+
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        crate::error_reports::record_panic(info);
+        error!("panic; restoring hardware defaults");
+        // The panicking thread may be a runtime worker that still holds locks (the hook runs
+        // before unwinding), so restore from a fresh thread with its own runtime
+        let _ = std::thread::spawn(
+            || match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(restore_after_panic()),
+                Err(e) => error!("shutdown: failed to start restore runtime: {}", e),
+            },
+        )
+        .join();
+        previous_hook(info);
+    }));
+
+    tokio::spawn(async {
+        stop_requested().await;
+        info!("stop requested; restoring hardware defaults");
+        restore_defaults().await;
+        crate::safe_mode::clean_exit();
+        std::process::exit(0);
+    });
+}
+
+async fn stop_requested() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = term.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("shutdown: SIGTERM handler unavailable: {}", e),
+        }
+    }
+    if tokio::signal::ctrl_c().await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Hand the hardware back once; later calls return immediately.
+pub async fn restore_defaults() {
+    if RESTORED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let Some(state) = STATE.get() else { return };
+    if tokio::time::timeout(RESTORE_TIMEOUT, restore(state)).await.is_err() {
+        warn!("shutdown: restoring hardware defaults timed out");
+    }
+}
+
+/// Hand the hardware back after a panic. Not marked as done: the tasks may survive the panic and
+/// take control again, so a later stop still has to restore.
+async fn restore_after_panic() {
+    let Some(state) = STATE.get() else { return };
+    if tokio::time::timeout(RESTORE_TIMEOUT, restore(state)).await.is_err() {
+        warn!("shutdown: restoring hardware defaults timed out");
+    }
+}
+
+async fn restore(state: &AppState) {
+    // try_* throughout: a lock held by the panicking thread is never released
+    let cfg = match state.config.try_read() {
+        Ok(c) => c.clone(),
+        Err(_) => crate::config::load(),
+    };
+    let cli = state.framework_tool.try_read().ok().and_then(|g| g.clone());
+
+    if let Some(cli) = &cli {
+        match cli.autofanctrl().await {
+            Ok(()) => info!("shutdown: fans handed back to EC control"),
+            Err(e) => warn!("shutdown: failed to restore EC fan control: {}", e),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    if cfg.fan.gpu.is_some() {
+        if let Some(gpu) = crate::cli::amdgpu_fan::AmdGpuFan::detect().await {
+            if let Err(e) = gpu.auto().await {
+                warn!("shutdown: failed to restore automatic GPU fan control: {}", e);
+            }
+        }
+    }
+
+    crate::tasks::power_budget::release().await;
+    crate::tasks::power::restore_stock(&state.power, &cfg).await;
+    let _ = tokio::task::spawn_blocking(crate::tasks::fan_restore::flush).await;
+}
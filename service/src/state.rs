@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::cli::framework_tool::{resolve_or_install, tool_suspect};
-use crate::cli::FrameworkTool;
+use crate::cli::{FrameworkTool, PowerBackends};
 use crate::types::Config;
 
 #[cfg(target_os = "windows")]
@@ -15,17 +15,18 @@ pub struct AppState {
     pub framework_tool: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
     pub config: Arc<tokio::sync::RwLock<Config>>,
     pub telemetry_samples: Arc<tokio::sync::RwLock<std::collections::VecDeque<crate::types::TelemetrySample>>>,
-
-    #[cfg(target_os = "windows")]
-    pub ryzenadj: Arc<tokio::sync::RwLock<Option<RyzenAdj>>>,
-
-    #[cfg(target_os = "linux")]
-    pub linux_power: Arc<tokio::sync::RwLock<Option<LinuxPower>>>,
+    pub power: PowerBackends,
 }
 
 impl AppState {
     pub async fn initialize() -> Self {
-        let config = Arc::new(tokio::sync::RwLock::new(crate::config::load()));
+        let mut loaded = crate::config::load();
+        // Ahead of the check: the config may name managed profiles
+        crate::managed_profiles::load();
+        crate::config_check::check_loaded(&mut loaded);
+        // Settle the device ID on first start rather than on the first export
+        crate::device::id();
+        let config = Arc::new(tokio::sync::RwLock::new(loaded));
 
         // Wrap framework_tool in a lock and spawn a passive resolver (no auto-install here)
         let framework_tool = Arc::new(tokio::sync::RwLock::new(None));
@@ -51,10 +52,12 @@ impl AppState {
             framework_tool,
             config,
             telemetry_samples: Arc::new(tokio::sync::RwLock::new(Default::default())),
-            #[cfg(target_os = "windows")]
-            ryzenadj,
-            #[cfg(target_os = "linux")]
-            linux_power,
+            power: PowerBackends {
+                #[cfg(target_os = "windows")]
+                ryzenadj,
+                #[cfg(target_os = "linux")]
+                linux: linux_power,
+            },
         }
     }
 
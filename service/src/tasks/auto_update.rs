@@ -6,19 +6,27 @@ use tracing::{error, info};
 use crate::types::Config;
 use crate::update::check_and_apply_now;
 
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const DND_RETRY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
 /// Auto-update background task
 /// Periodically checks for updates and applies them if `auto_install` is enabled.
+/// Installing restarts the service, so it waits while a user has do-not-disturb on.
 pub async fn run(cfg: Arc<tokio::sync::RwLock<Config>>) {
     loop {
         let cfg = cfg.read().await.clone();
         if cfg.updates.auto_install {
+            if cfg.updates.defer_during_dnd.unwrap_or(true) && crate::dnd::is_active().await {
+                info!("auto-update: do-not-disturb is on; deferring");
+                sleep(DND_RETRY_INTERVAL).await;
+                continue;
+            }
             match check_and_apply_now().await {
                 Ok(true) => info!("auto-update: installer launched"),
                 Ok(false) => { /* no update available */ }
                 Err(e) => error!("auto-update: check/apply failed: {}", e),
             }
         }
-        // sleep 6h
-        sleep(Duration::from_secs(6 * 60 * 60)).await;
+        sleep(CHECK_INTERVAL).await;
     }
 }
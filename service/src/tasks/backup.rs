@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::types::Config;
+
+const TICK: Duration = Duration::from_secs(60);
+/// A failed snapshot (archive drive missing, no permission) is retried this much later.
+const RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Nightly backups (`backups.rs`): checks every minute whether today's snapshot is due.
+pub async fn run(cfg_lock: Arc<tokio::sync::RwLock<Config>>) {
+    info!("Backup task started");
+
+    let mut retry_at: Option<Instant> = None;
+    loop {
+        sleep(TICK).await;
+        let (backup_cfg, saved) = {
+            let cfg = cfg_lock.read().await;
+            (cfg.backup.clone(), crate::temporary::persisted(&cfg))
+        };
+        if !backup_cfg.enabled || retry_at.is_some_and(|t| Instant::now() < t) {
+            continue;
+        }
+        // Read back each time: the directory may change or be shared with a reinstall
+        let last = crate::backups::last_snapshot_date(&backup_cfg);
+        if !crate::backups::due(last, chrono::Local::now().naive_local(), backup_cfg.hour) {
+            continue;
+        }
+        match crate::backups::create(&backup_cfg, &saved) {
+            Ok(entry) => {
+                retry_at = None;
+                info!("backup: wrote {} ({} bytes)", entry.name, entry.size_bytes);
+            }
+            Err(e) => {
+                retry_at = Some(Instant::now() + RETRY_AFTER);
+                warn!("backup: nightly snapshot failed: {}", e);
+            }
+        }
+    }
+}
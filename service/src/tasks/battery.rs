@@ -1,37 +1,226 @@
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use tokio::time::{sleep, Duration, Instant};
 use tracing::{debug, info, warn};
 
 use crate::cli::FrameworkTool;
-use crate::types::{BatteryConfig, Config};
+use crate::dry_run::Plan;
+use crate::types::{AlertSeverity, BatteryConfig, Config, StorageModeConfig, StoragePhase};
+
+const WEAK_CHARGER_ALERT: &str = "weak_charger";
+const CHARGE_LIMIT_ALERT: &str = "charge_limit_ignored";
+const STORAGE_DISCHARGE_ALERT: &str = "storage_mode_discharge";
+/// How often storage mode re-reads the charge
+const STORAGE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Time the EC gets to take a charge limit before it is read back
+const VERIFY_DELAY: Duration = Duration::from_secs(1);
+
+/// A charge limit the EC did not take: retried with exponential backoff.
+struct LimitRetry {
+    pct: u8,
+    attempt: u32,
+    next_at: Instant,
+}
+
+/// Delay before retry `attempt` (1-based): 5 s doubling up to the periodic reapply interval.
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs((5u64 << attempt.saturating_sub(1).min(10)).min(REAPPLY_INTERVAL_SECS))
+}
+
+/// Set while the AC profile's `lift_battery_limits` overrides the configured limits.
+static LIMITS_LIFTED: AtomicBool = AtomicBool::new(false);
+
+pub fn limits_lifted() -> bool {
+    LIMITS_LIFTED.load(Ordering::Relaxed)
+}
+
+/// Where storage mode stands, as of its last charge reading.
+#[derive(Debug, Clone, Copy)]
+pub struct StorageState {
+    pub phase: StoragePhase,
+    pub soc_pct: u32,
+    pub limit_pct: u8,
+}
+
+static STORAGE: Mutex<Option<StorageState>> = Mutex::new(None);
+
+pub fn storage_state() -> Option<StorageState> {
+    STORAGE.lock().ok().and_then(|s| *s)
+}
+
+/// Next storage-mode phase at `soc`, and the charge limit that goes with it. The limit rests at the
+/// bottom of the band so the EC does not charge; below the band it moves up to the target until
+/// the charge gets there. The EC cannot discharge on AC, so above the band it only stops charging.
+fn storage_step(phase: StoragePhase, soc: u32, ac: bool, cfg: &StorageModeConfig) -> (StoragePhase, u8) {
+    let target = cfg.target_pct as u32;
+    let low = target.saturating_sub(cfg.band_pct as u32);
+    let next = match phase {
+        _ if ac && soc > target + cfg.band_pct as u32 => StoragePhase::Discharge,
+        StoragePhase::TopUp if soc < target => StoragePhase::TopUp,
+        _ if soc < low => StoragePhase::TopUp,
+        _ => StoragePhase::Hold,
+    };
+    let limit = match next {
+        StoragePhase::TopUp => target,
+        StoragePhase::Hold | StoragePhase::Discharge => low,
+    };
+    (next, (limit as u8).clamp(CL_MIN, CL_MAX))
+}
+
+/// Re-read the charge and move storage mode along; the previous state when the read fails.
+async fn check_storage(cli: &FrameworkTool, cfg: &StorageModeConfig) -> Option<StorageState> {
+    let previous = storage_state();
+    let p = match cli.power_with_fallback().await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("battery: storage mode check skipped: {}", e);
+            return previous;
+        }
+    };
+    let Some(soc) = p.percentage else {
+        return previous;
+    };
+    let phase = previous.map_or(StoragePhase::Hold, |s| s.phase);
+    let (next, limit_pct) = storage_step(phase, soc, p.ac_present == Some(true), cfg);
+    if previous.is_none_or(|s| s.phase != next) {
+        info!(
+            "battery: storage mode {:?} at {}% (charge limit {}%)",
+            next, soc, limit_pct
+        );
+    }
+    if next == StoragePhase::Discharge {
+        crate::alerts::raise(
+            STORAGE_DISCHARGE_ALERT,
+            AlertSeverity::Info,
+            format!(
+                "Storage mode: the battery is at {}%, above the {}-{}% band. \
+                 Unplug the charger to let it discharge; charging stays off until then",
+                soc,
+                cfg.target_pct.saturating_sub(cfg.band_pct),
+                cfg.target_pct + cfg.band_pct
+            ),
+        );
+    } else {
+        crate::alerts::clear(STORAGE_DISCHARGE_ALERT);
+    }
+    let state = StorageState {
+        phase: next,
+        soc_pct: soc,
+        limit_pct,
+    };
+    if let Ok(mut s) = STORAGE.lock() {
+        *s = Some(state);
+    }
+    Some(state)
+}
+
+fn end_storage() -> bool {
+    crate::alerts::clear(STORAGE_DISCHARGE_ALERT);
+    STORAGE.lock().ok().and_then(|mut s| s.take()).is_some()
+}
 
 /// Battery task: applies config.battery settings when they change and periodically every 30 minutes.
+/// While on AC with `power.ac.lift_battery_limits`, applies 100% at 1C instead and puts the
+/// configured limits back afterwards. In storage mode the limit follows the storage band instead
+/// (see `storage_step`). Also watches the adapter's negotiated wattage against `min_charger_watts`.
 pub async fn run(
     framework_tool_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
     cfg: Arc<tokio::sync::RwLock<Config>>,
 ) {
     info!("Battery task started");
 
-    const REAPPLY_INTERVAL_SECS: u64 = 10 * 60;
-    const CL_MIN: u8 = 25;
-    const CL_MAX: u8 = 100;
+    const CHARGER_CHECK_INTERVAL_SECS: u64 = 30;
 
     let mut last_charge_limit_pct: Option<u8> = None;
     let mut last_rate_c: Option<f32> = None;
     let mut last_threshold_pct: Option<u8> = None;
     let mut last_charge_apply_at: Option<Instant> = None;
     let mut last_rate_apply_at: Option<Instant> = None;
+    let mut last_charger_check_at: Option<Instant> = None;
+    let mut limit_retry: Option<LimitRetry> = None;
+    let mut last_storage_check: Option<(Instant, (u8, u8))> = None;
+    let mut resume_generation = crate::tasks::resume::generation();
 
     loop {
         // Clone required shared state each tick
-        let cfg_bat: BatteryConfig = { cfg.read().await.battery.clone() };
+        let loop_started = Instant::now();
+
+        // The EC often drops charge limits across sleep/hibernate; forget what we applied
+        let generation = crate::tasks::resume::generation();
+        if generation != resume_generation {
+            resume_generation = generation;
+            last_charge_limit_pct = None;
+            last_rate_c = None;
+            last_threshold_pct = None;
+        }
+        let (cfg_bat, lift_on_ac): (BatteryConfig, bool) = {
+            let c = cfg.read().await;
+            let lift = c.power.ac.as_ref().and_then(|p| p.lift_battery_limits);
+            (c.battery.clone(), lift == Some(true))
+        };
         let ft_opt = { framework_tool_lock.read().await.clone() };
 
         if let Some(cli) = ft_opt {
-            if let Some(setting) = cfg_bat.charge_limit_max_pct.clone() {
-                if setting.enabled {
-                    let desired = setting.value.clamp(CL_MIN, CL_MAX);
+            // Power reads are cached briefly and shared with the power task
+            let storage = cfg_bat.storage_mode.clone().filter(|s| s.enabled);
+            // Storage mode also keeps the AC profile from lifting the limits
+            let lifted = lift_on_ac
+                && storage.is_none()
+                && cli
+                    .power_with_fallback()
+                    .await
+                    .is_ok_and(|p| p.ac_present == Some(true));
+            if LIMITS_LIFTED.swap(lifted, Ordering::Relaxed) != lifted {
+                if lifted {
+                    info!("battery: AC profile lifts the battery limits; charging to 100% at 1C");
+                } else {
+                    info!("battery: restoring the configured battery limits");
+                }
+            }
+
+            let storage_limit = match &storage {
+                Some(s) => {
+                    let due = last_storage_check.is_none_or(|(t, band)| {
+                        band != (s.target_pct, s.band_pct) || t.elapsed() >= STORAGE_CHECK_INTERVAL
+                    });
+                    if due {
+                        last_storage_check = Some((Instant::now(), (s.target_pct, s.band_pct)));
+                        check_storage(&cli, s).await;
+                    }
+                    // Until the charge has been read, hold at the bottom of the band
+                    Some(storage_state().map_or_else(
+                        || storage_step(StoragePhase::Hold, s.target_pct as u32, false, s).1,
+                        |st| st.limit_pct,
+                    ))
+                }
+                None => {
+                    last_storage_check = None;
+                    let limit_configured = cfg_bat.charge_limit_max_pct.as_ref().is_some_and(|s| s.enabled);
+                    if end_storage() && !limit_configured {
+                        info!("battery: storage mode off; removing its charge limit");
+                        if let Err(e) = cli.charge_limit_set(CL_MAX).await {
+                            warn!("battery: charge_limit_set failed: {}", e);
+                        }
+                        last_charge_limit_pct = None;
+                        last_charge_apply_at = None;
+                        limit_retry = None;
+                        crate::alerts::clear(CHARGE_LIMIT_ALERT);
+                    }
+                    None
+                }
+            };
+
+            // Storage mode takes over the limit; `Some(None)` is a configured but disabled limit
+            let limit_target = match storage_limit {
+                Some(pct) => Some(Some(pct)),
+                None => cfg_bat
+                    .charge_limit_max_pct
+                    .as_ref()
+                    .map(|s| s.enabled.then(|| charge_limit_target(s.value, lifted))),
+            };
+            if let Some(target) = limit_target {
+                if let Some(desired) = target {
                     let need_apply = match last_charge_limit_pct {
                         None => true,
                         Some(prev) => prev != desired,
@@ -42,12 +231,50 @@ pub async fn run(
                             Instant::now().saturating_duration_since(t) >= Duration::from_secs(REAPPLY_INTERVAL_SECS)
                         }
                     };
-                    if need_apply || past_reapply {
+                    if limit_retry.as_ref().is_some_and(|r| r.pct != desired) {
+                        limit_retry = None;
+                    }
+                    let retry_due = limit_retry.as_ref().is_none_or(|r| Instant::now() >= r.next_at);
+                    if (need_apply || past_reapply) && retry_due {
                         debug!("battery: applying charge limit {}%", desired);
                         match cli.charge_limit_set(desired).await {
                             Ok(_) => {
-                                last_charge_limit_pct = Some(desired);
                                 last_charge_apply_at = Some(Instant::now());
+                                match read_back_charge_limit(&cli).await {
+                                    Some(reported) if reported != desired => {
+                                        // Not recorded as applied, so the retry writes it again
+                                        last_charge_limit_pct = None;
+                                        let attempt = limit_retry.as_ref().map_or(1, |r| r.attempt + 1);
+                                        let delay = retry_delay(attempt);
+                                        warn!(
+                                            "battery: EC reports a {}% charge limit after setting {}% (attempt {}); retrying in {:?}",
+                                            reported, desired, attempt, delay
+                                        );
+                                        crate::alerts::raise(
+                                            CHARGE_LIMIT_ALERT,
+                                            AlertSeverity::Warning,
+                                            format!(
+                                                "The EC did not accept the {}% charge limit (it reports {}%); retrying. \
+                                                 Some firmware versions ignore charge limits in certain states; \
+                                                 updating the BIOS may help",
+                                                desired, reported
+                                            ),
+                                        );
+                                        limit_retry = Some(LimitRetry {
+                                            pct: desired,
+                                            attempt,
+                                            next_at: Instant::now() + delay,
+                                        });
+                                    }
+                                    // Unreadable limits count as applied, as before read-back
+                                    _ => {
+                                        if limit_retry.take().is_some() {
+                                            info!("battery: EC accepted the {}% charge limit", desired);
+                                        }
+                                        crate::alerts::clear(CHARGE_LIMIT_ALERT);
+                                        last_charge_limit_pct = Some(desired);
+                                    }
+                                }
                             }
                             Err(e) => {
                                 warn!("battery: charge_limit_set failed: {}", e);
@@ -58,16 +285,15 @@ pub async fn run(
                     // Disabled: stop tracking so we won't keep reapplying an old limit once turned off.
                     last_charge_limit_pct = None;
                     last_charge_apply_at = None;
+                    limit_retry = None;
+                    crate::alerts::clear(CHARGE_LIMIT_ALERT);
                 }
             }
 
             if let Some(setting) = cfg_bat.charge_rate_c.clone() {
                 if setting.enabled {
-                    let mut desired_c = setting.value;
-                    // snap to 0.05 steps like UI
-                    desired_c = (desired_c * 20.0).round() / 20.0;
-                    desired_c = desired_c.clamp(0.05, 1.0);
-                    let desired_threshold = cfg_bat.charge_rate_soc_threshold_pct;
+                    let (desired_c, desired_threshold) =
+                        charge_rate_target(setting.value, cfg_bat.charge_rate_soc_threshold_pct, lifted);
                     let need_apply = match (last_rate_c, last_threshold_pct) {
                         (Some(prev_c), prev_t) => prev_c != desired_c || prev_t != desired_threshold,
                         _ => true,
@@ -101,8 +327,149 @@ pub async fn run(
                     last_rate_apply_at = None;
                 }
             }
+
+            let charger_due = match last_charger_check_at {
+                None => true,
+                Some(t) => {
+                    Instant::now().saturating_duration_since(t) >= Duration::from_secs(CHARGER_CHECK_INTERVAL_SECS)
+                }
+            };
+            if charger_due {
+                last_charger_check_at = Some(Instant::now());
+                check_charger(&cli, cfg_bat.min_charger_watts).await;
+            }
         }
 
+        crate::metrics::record_task_loop("battery", loop_started.elapsed());
         sleep(Duration::from_secs(1)).await;
     }
 }
+
+const REAPPLY_INTERVAL_SECS: u64 = 10 * 60;
+const CL_MIN: u8 = 25;
+const CL_MAX: u8 = 100;
+
+/// Charge limit to apply for a configured `value`; 100% while the AC profile lifts the limits.
+fn charge_limit_target(value: u8, lifted: bool) -> u8 {
+    if lifted {
+        CL_MAX
+    } else {
+        value.clamp(CL_MIN, CL_MAX)
+    }
+}
+
+/// `(rate in C, SoC threshold)` to apply for a configured rate; 1C from 0% while lifted.
+fn charge_rate_target(rate_c: f32, threshold_pct: Option<u8>, lifted: bool) -> (f32, Option<u8>) {
+    if lifted {
+        return (1.0, None);
+    }
+    // snap to 0.05 steps like UI
+    (((rate_c * 20.0).round() / 20.0).clamp(0.05, 1.0), threshold_pct)
+}
+
+/// Max charge limit the EC reports right after a write; `None` when it cannot be read.
+async fn read_back_charge_limit(cli: &FrameworkTool) -> Option<u8> {
+    sleep(VERIFY_DELAY).await;
+    match cli.charge_limit_get_max_age(Duration::ZERO).await {
+        Ok(info) => info.charge_limit_max_pct,
+        Err(e) => {
+            debug!("battery: charge limit read-back failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Dry run of a `battery` write. The task writes an enabled limit when its target changes, so
+/// only targets that differ between `current` and `new` are planned.
+pub async fn preview(plan: &mut Plan, cli: &FrameworkTool, current: &BatteryConfig, new: &BatteryConfig) {
+    let lifted = limits_lifted();
+    let limit = |b: &BatteryConfig| {
+        b.charge_limit_max_pct
+            .as_ref()
+            .filter(|s| s.enabled)
+            .map(|s| charge_limit_target(s.value, lifted))
+    };
+    if let Some(pct) = limit(new).filter(|pct| limit(current) != Some(*pct)) {
+        plan.add("battery.charge_limit_max_pct", cli.charge_limit_set(pct))
+            .await;
+    }
+    let rate = |b: &BatteryConfig| {
+        b.charge_rate_c
+            .as_ref()
+            .filter(|s| s.enabled)
+            .map(|s| charge_rate_target(s.value, b.charge_rate_soc_threshold_pct, lifted))
+    };
+    if let Some((c, threshold)) = rate(new).filter(|r| rate(current) != Some(*r)) {
+        plan.add("battery.charge_rate_c", cli.charge_rate_limit_set(c, threshold))
+            .await;
+    }
+}
+
+/// Raise the weak-charger alert while the active adapter negotiates less than `min_watts`,
+/// and clear it once a strong enough adapter (or none) is connected. Read errors keep the
+/// current alert state.
+async fn check_charger(cli: &FrameworkTool, min_watts: Option<u32>) {
+    let Some(min_watts) = min_watts.filter(|w| *w > 0) else {
+        crate::alerts::clear(WEAK_CHARGER_ALERT);
+        return;
+    };
+    match cli.power_with_fallback().await {
+        Ok(p) if p.ac_present != Some(true) => {
+            crate::alerts::clear(WEAK_CHARGER_ALERT);
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            debug!("battery: charger check skipped: {}", e);
+            return;
+        }
+    }
+    match cli.charger().await {
+        Ok(Some(c)) if c.negotiated_watts.is_some_and(|w| w < min_watts as f32) => {
+            let location = c.location.map(|l| format!(" ({})", l)).unwrap_or_default();
+            crate::alerts::raise(
+                WEAK_CHARGER_ALERT,
+                AlertSeverity::Warning,
+                format!(
+                    "Charger on USB-C port {}{} negotiated {:.0} W, below the {} W minimum; \
+                     performance and charging speed may be limited",
+                    c.port,
+                    location,
+                    c.negotiated_watts.unwrap_or_default(),
+                    min_watts
+                ),
+            );
+        }
+        Ok(_) => crate::alerts::clear(WEAK_CHARGER_ALERT),
+        Err(e) => debug!("battery: charger check skipped: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_retries_back_off_up_to_the_reapply_interval() {
+        assert_eq!(retry_delay(1), Duration::from_secs(5));
+        assert_eq!(retry_delay(3), Duration::from_secs(20));
+        assert_eq!(retry_delay(12), Duration::from_secs(REAPPLY_INTERVAL_SECS));
+    }
+
+    #[test]
+    fn storage_mode_tops_up_below_the_band_and_holds_in_it() {
+        let cfg = StorageModeConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        use StoragePhase::*;
+        assert_eq!(storage_step(Hold, 58, true, &cfg), (Hold, 55));
+        assert_eq!(storage_step(Hold, 54, false, &cfg), (TopUp, 60));
+        // Keeps charging through the band until the target
+        assert_eq!(storage_step(TopUp, 57, true, &cfg), (TopUp, 60));
+        assert_eq!(storage_step(TopUp, 60, true, &cfg), (Hold, 55));
+        // Above the band: charging stops, and unplugging lets it drift down
+        assert_eq!(storage_step(Hold, 80, true, &cfg), (Discharge, 55));
+        assert_eq!(storage_step(Discharge, 80, false, &cfg), (Hold, 55));
+    }
+}
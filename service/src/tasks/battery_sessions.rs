@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info};
+
+use crate::cli::FrameworkTool;
+use crate::types::Config;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Charging history: samples battery voltage/current/SoC and feeds the session tracker, tagging
+/// each session with the power profile (`power.ac`, a `power.chargers` tier or `power.battery`) active when it started.
+/// The same samples drive the smoothed rate behind the `/power` time estimates.
+pub async fn run(
+    cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
+    cfg_lock: Arc<tokio::sync::RwLock<Config>>,
+) {
+    info!("Battery sessions task started");
+
+    loop {
+        let Some(cli) = ({ cli_lock.read().await.clone() }) else {
+            sleep(SAMPLE_INTERVAL).await;
+            continue;
+        };
+        match cli.power_with_fallback().await {
+            Ok(p) if p.battery_present != Some(false) => {
+                let power = { cfg_lock.read().await.power.clone() };
+                let profile = crate::tasks::power::active_profile(&power, &cli)
+                    .await
+                    .and_then(|a| a.profile);
+                let now_ms = unix_time_ms();
+                crate::battery_sessions::record(now_ms, &p, profile.as_ref());
+                crate::battery_eta::record(now_ms, &p);
+            }
+            Ok(_) => {}
+            Err(e) => debug!("battery sessions: power read failed: {}", e),
+        }
+        sleep(SAMPLE_INTERVAL).await;
+    }
+}
+
+fn unix_time_ms() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
@@ -0,0 +1,230 @@
+// Fan control contention: another program (fw-fanctrl, a vendor tool, a script) writing the fans
+// while this service drives them. The fan task only writes when its duty changes, so after such a
+// write the fans silently follow the other program.
+//
+// Like the power reconcilers' drift check, but the EC has no duty readback: each fan task tick
+// compares every fan's RPM with what its calibration table predicts for the duty last commanded. A
+// mismatch that persists for CONFIRM_MS at an unchanged duty raises the `fan_contention` alert,
+// and `fan.on_contention` then leaves it at that, re-applies our duties, or backs off (no fan
+// writes until re-asserted through the API or a `fan` config write).
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::types::{AlertSeverity, ContendedFan, FanContentionPolicy, FanContentionStatus, FanControlConfig};
+
+const ALERT: &str = "fan_contention";
+/// How long a fan must stay off its expected RPM (at an unchanged duty) before it counts
+const CONFIRM_MS: i64 = 15_000;
+/// Deviation tolerated around the calibrated RPM: the larger of these
+const MIN_DEVIATION_RPM: u32 = 600;
+const DEVIATION_PCT: u32 = 30;
+
+static BACKED_OFF: AtomicBool = AtomicBool::new(false);
+static WATCH: Mutex<Watch> = Mutex::new(Watch {
+    pending: BTreeMap::new(),
+    contended: Vec::new(),
+    since_ms: None,
+});
+
+#[derive(Debug, PartialEq)]
+enum Transition {
+    Detected,
+    Cleared,
+}
+
+struct Watch {
+    /// Per fan: duty and time of the first mismatching reading at that duty
+    pending: BTreeMap<u32, (u32, i64)>,
+    contended: Vec<ContendedFan>,
+    since_ms: Option<i64>,
+}
+
+fn deviates(expected: u32, observed: u32) -> bool {
+    observed.abs_diff(expected) > MIN_DEVIATION_RPM.max(expected * DEVIATION_PCT / 100)
+}
+
+impl Watch {
+    fn reset(&mut self) {
+        self.pending.clear();
+        self.contended.clear();
+        self.since_ms = None;
+    }
+
+    fn observe(&mut self, now_ms: i64, readings: Vec<ContendedFan>) -> Option<Transition> {
+        let mut contended = Vec::new();
+        let mut pending = BTreeMap::new();
+        for r in readings {
+            if !deviates(r.expected_rpm, r.observed_rpm) {
+                continue;
+            }
+            let first = match self.pending.get(&r.fan_index) {
+                Some((duty, first)) if *duty == r.commanded_duty_pct => *first,
+                _ => now_ms,
+            };
+            pending.insert(r.fan_index, (r.commanded_duty_pct, first));
+            if now_ms - first >= CONFIRM_MS {
+                contended.push(r);
+            }
+        }
+        self.pending = pending;
+        self.contended = contended;
+        match (self.contended.is_empty(), self.since_ms) {
+            (false, None) => {
+                self.since_ms = Some(now_ms);
+                Some(Transition::Detected)
+            }
+            (true, Some(_)) => {
+                self.since_ms = None;
+                Some(Transition::Cleared)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The fan task must not write the fans.
+pub fn backed_off() -> bool {
+    BACKED_OFF.load(Ordering::Relaxed)
+}
+
+/// Stop writing the fans and leave them to the other program.
+pub fn back_off() {
+    if !BACKED_OFF.swap(true, Ordering::Relaxed) {
+        info!("fan contention: backing off; fans left to the other program");
+    }
+    if let Ok(mut w) = WATCH.lock() {
+        w.reset();
+    }
+    crate::alerts::raise(
+        ALERT,
+        AlertSeverity::Info,
+        "Fan control backed off: another program is controlling the fans".into(),
+    );
+}
+
+/// Take the fans back: re-apply the configured mode from scratch.
+pub fn reassert() {
+    if BACKED_OFF.swap(false, Ordering::Relaxed) {
+        info!("fan contention: re-asserting fan control");
+    }
+    if let Ok(mut w) = WATCH.lock() {
+        w.reset();
+    }
+    crate::alerts::clear(ALERT);
+    super::fan_curve::request_reapply();
+}
+
+/// Compare each fan's RPM with the calibrated RPM for the duty this service last applied, and
+/// act on `fan.on_contention` when contention is confirmed. Called from the fan task after it
+/// has written the fans.
+pub async fn check(cli: &FrameworkTool, config: &FanControlConfig) {
+    if !config.is_calibrated() {
+        return;
+    }
+    let Ok(thermal) = cli.thermal().await else { return };
+    let readings: Vec<ContendedFan> = thermal
+        .fans
+        .iter()
+        .enumerate()
+        .filter_map(|(i, fan)| {
+            let i = i as u32;
+            let duty = super::fan_restore::applied_duty(i)?;
+            let calibration = config.calibration_for(i)?;
+            Some(ContendedFan {
+                fan_index: i,
+                commanded_duty_pct: duty,
+                expected_rpm: crate::diagnostics::fan_ramp::rpm_at(&calibration.points, duty)?,
+                observed_rpm: fan.rpm,
+            })
+        })
+        .collect();
+    let (transition, contended) = {
+        let Ok(mut w) = WATCH.lock() else { return };
+        let t = w.observe(chrono::Utc::now().timestamp_millis(), readings);
+        (t, w.contended.clone())
+    };
+    match transition {
+        Some(Transition::Detected) => {
+            let fans: Vec<String> = contended
+                .iter()
+                .map(|f| {
+                    format!(
+                        "fan {} at {} RPM (expected ~{} RPM for {}%)",
+                        f.fan_index, f.observed_rpm, f.expected_rpm, f.commanded_duty_pct
+                    )
+                })
+                .collect();
+            let message = format!(
+                "Another program appears to be controlling the fans: {}",
+                fans.join(", ")
+            );
+            warn!("fan contention: {}", message);
+            match config.on_contention.unwrap_or_default() {
+                FanContentionPolicy::Notify => crate::alerts::raise(ALERT, AlertSeverity::Warning, message),
+                FanContentionPolicy::BackOff => back_off(),
+                FanContentionPolicy::Reassert => {
+                    info!("fan contention: re-asserting configured duties");
+                    reassert();
+                }
+            }
+        }
+        Some(Transition::Cleared) => {
+            info!("fan contention: fans back at their expected speed");
+            crate::alerts::clear(ALERT);
+        }
+        None => {}
+    }
+}
+
+pub fn status(config: &FanControlConfig) -> FanContentionStatus {
+    let (fans, since_ms) = WATCH
+        .lock()
+        .map(|w| (w.contended.clone(), w.since_ms))
+        .unwrap_or_default();
+    FanContentionStatus {
+        detected: since_ms.is_some(),
+        since_ms,
+        fans,
+        backed_off: backed_off(),
+        policy: config.on_contention.unwrap_or_default(),
+        calibrated: config.is_calibrated(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(duty: u32, observed_rpm: u32) -> Vec<ContendedFan> {
+        vec![ContendedFan {
+            fan_index: 0,
+            commanded_duty_pct: duty,
+            expected_rpm: 2400,
+            observed_rpm,
+        }]
+    }
+
+    #[test]
+    fn confirms_only_persistent_mismatch_at_unchanged_duty() {
+        let mut w = Watch {
+            pending: BTreeMap::new(),
+            contended: Vec::new(),
+            since_ms: None,
+        };
+        // Within tolerance
+        assert_eq!(w.observe(0, reading(30, 2900)), None);
+        assert_eq!(w.observe(1_000, reading(30, 5200)), None);
+        // Our own duty change restarts the timer
+        assert_eq!(w.observe(10_000, reading(35, 5200)), None);
+        assert_eq!(w.observe(20_000, reading(35, 5200)), None);
+        assert_eq!(w.observe(25_000, reading(35, 5200)), Some(Transition::Detected));
+        assert_eq!(w.contended[0].observed_rpm, 5200);
+        assert_eq!(w.observe(26_000, reading(35, 5200)), None);
+        assert_eq!(w.observe(27_000, reading(35, 2400)), Some(Transition::Cleared));
+    }
+}
@@ -1,11 +1,58 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use tokio::time::{sleep, Duration};
 use tracing::{debug, info, warn};
 
+use super::fan_restore;
 use crate::cli::FrameworkTool;
-use crate::types::{Config, CurveConfig, FanControlMode};
+use crate::dry_run::Plan;
+use crate::types::{
+    AlertSeverity, Config, ControlActionKind, ControlActionReason, CurveBoostConfig, CurveConfig, FanControlConfig,
+    FanControlMode, FanFailsafeAction, GpuFanBackend, GpuFanConfig, GpuFanStatus, RpmCurveConfig, SensorAggregate,
+    SensorsConfig, TargetTempConfig,
+};
+
+/// Set when something outside this task wrote the fans (diagnostics, ...) so the next tick
+/// drops its cached duties and re-applies the configured mode from scratch.
+static REAPPLY_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+pub fn request_reapply() {
+    REAPPLY_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Set while a diagnostics run drives the fans itself; the task then skips its writes.
+static EXTERNAL_HOLD: AtomicBool = AtomicBool::new(false);
+
+/// Keeps the fan task off the fans until dropped, then has it re-apply the configured mode.
+pub struct ExternalHold;
+
+impl ExternalHold {
+    pub fn acquire() -> Self {
+        EXTERNAL_HOLD.store(true, Ordering::Relaxed);
+        ExternalHold
+    }
+}
+
+impl Drop for ExternalHold {
+    fn drop(&mut self) {
+        EXTERNAL_HOLD.store(false, Ordering::Relaxed);
+        request_reapply();
+    }
+}
+
+/// Set while a critical sensor reading holds every EC fan at 100%, whatever the configured mode.
+static EMERGENCY: AtomicBool = AtomicBool::new(false);
+/// The override ends once every sensor is this far below its critical threshold
+const EMERGENCY_RELEASE_MARGIN_C: i32 = 5;
+const EMERGENCY_ALERT: &str = "fan_emergency";
+const FAILSAFE_ALERT: &str = "fan_failsafe";
+
+pub fn emergency_active() -> bool {
+    EMERGENCY.load(Ordering::Relaxed)
+}
 
 /// Main fan control task that runs continuously based on config
 pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg: Arc<tokio::sync::RwLock<Config>>) {
@@ -14,21 +61,33 @@ pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg:
     let mut global = CurveStepper::new();
     let mut per_fan_curve_steppers: HashMap<u32, CurveStepper> = HashMap::new();
     let mut last_manual_duty: HashMap<Option<u32>, u32> = HashMap::new();
+    let mut gpu = GpuFanControl::new();
+    let mut target = TargetStepper::new();
+    let mut rpm_steppers: HashMap<Option<u32>, RpmStepper> = HashMap::new();
 
     let mut last_mode: Option<FanControlMode> = None;
     let mut last_per_fan_active = false;
+    let mut last_duty_offsets = None;
     let mut fan_count: Option<u32> = None;
+    let mut emergency_applied = false;
+    let mut failsafe = FailsafeTracker::default();
+    let mut failsafe_applied = false;
 
     loop {
         let loop_started = std::time::Instant::now();
-        let config = cfg.read().await.fan.clone();
-        let mode = config.mode.unwrap_or(FanControlMode::Disabled);
+        let (config, sensors_cfg, ui) = {
+            let c = cfg.read().await;
+            (c.fan.clone(), c.sensors.clone(), c.ui.clone())
+        };
+        let mode = config.mode.clone().unwrap_or(FanControlMode::Disabled);
 
-        let overrides = config.overrides.clone().unwrap_or_default();
-        let per_fan_active = !overrides.is_empty();
+        let gpu_cfg = config.gpu.clone().unwrap_or_default();
+        let (gpu_ec_index, per_fan_active) = per_fan_layout(&config);
 
         let poll_interval = Duration::from_millis(match mode {
             FanControlMode::Curve => config.curve.as_ref().map(|c| c.poll_ms).unwrap_or(500),
+            FanControlMode::Target => config.target.as_ref().map(|t| t.poll_ms).unwrap_or(500),
+            FanControlMode::Rpm => config.rpm.as_ref().map(|r| r.poll_ms).unwrap_or(500),
             _ => 500,
         });
 
@@ -40,15 +99,120 @@ pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg:
                 global.reset();
                 per_fan_curve_steppers.clear();
                 last_manual_duty.clear();
+                gpu.reset();
+                target.reset();
+                rpm_steppers.clear();
                 fan_count = None;
                 sleep(poll_interval).await;
                 continue;
             }
         };
 
-        // Reset transient control state when the mode or the global/per-fan
-        // topology changes so we re-anchor cleanly.
-        if last_mode != Some(mode.clone()) || last_per_fan_active != per_fan_active {
+        // Ahead of the diagnostics hold: a critical reading outranks any test in progress
+        let hot = critical_sensors(&cli, &sensors_cfg, emergency_active()).await;
+
+        // In `disabled` the EC has the fans already, so failed reads only count elsewhere
+        let failsafe_cfg = config.failsafe.clone().unwrap_or_default();
+        let read_ok = hot.is_some() || mode == FanControlMode::Disabled;
+        match failsafe.observe(read_ok, failsafe_cfg.failed_reads) {
+            Some(true) => {
+                let message = match failsafe_cfg.action {
+                    FanFailsafeAction::Duty => format!(
+                        "Temperatures unreadable for {} polls; fans held at {}%",
+                        failsafe.failed, failsafe_cfg.duty_pct
+                    ),
+                    FanFailsafeAction::Auto => format!(
+                        "Temperatures unreadable for {} polls; fans handed back to the EC",
+                        failsafe.failed
+                    ),
+                };
+                warn!("{}", message);
+                crate::alerts::raise(FAILSAFE_ALERT, AlertSeverity::Critical, message);
+                failsafe_applied = false;
+            }
+            Some(false) => {
+                info!("Temperatures readable again; resuming {:?} fan mode", mode);
+                crate::alerts::clear(FAILSAFE_ALERT);
+                last_mode = None;
+            }
+            None => {}
+        }
+        if failsafe.engaged {
+            if !failsafe_applied {
+                let applied = match failsafe_cfg.action {
+                    FanFailsafeAction::Duty => cli.set_fan_duty(failsafe_cfg.duty_pct, None).await.map(|()| {
+                        fan_restore::record_duty(&cli, None, failsafe_cfg.duty_pct);
+                        crate::actions::record(
+                            ControlActionKind::FanDuty,
+                            None,
+                            failsafe_cfg.duty_pct,
+                            ControlActionReason::Failsafe,
+                        );
+                    }),
+                    FanFailsafeAction::Auto => cli.autofanctrl().await.map(|()| fan_restore::record_auto(&cli)),
+                };
+                match applied {
+                    Ok(()) => failsafe_applied = true,
+                    Err(e) => warn!("Fan failsafe failed: {}", e),
+                }
+            }
+            sleep(poll_interval).await;
+            continue;
+        }
+
+        let hot = hot.unwrap_or_default();
+        if !hot.is_empty() {
+            if !EMERGENCY.swap(true, Ordering::Relaxed) {
+                let readings: Vec<String> = hot
+                    .iter()
+                    .map(|(name, temp, critical)| {
+                        format!(
+                            "{} at {} (critical {})",
+                            name,
+                            crate::units::format_temp(*temp as f32, &ui),
+                            crate::units::format_temp(*critical as f32, &ui)
+                        )
+                    })
+                    .collect();
+                let message = format!("Fans forced to 100%: {}", readings.join(", "));
+                warn!("{}", message);
+                crate::alerts::raise(EMERGENCY_ALERT, AlertSeverity::Critical, message);
+                emergency_applied = false;
+            }
+            if !emergency_applied {
+                match cli.set_fan_duty(100, None).await {
+                    Ok(()) => {
+                        emergency_applied = true;
+                        crate::actions::record(ControlActionKind::FanDuty, None, 100, ControlActionReason::Emergency);
+                    }
+                    Err(e) => warn!("Emergency fan override failed: {}", e),
+                }
+            }
+            sleep(poll_interval).await;
+            continue;
+        }
+        if EMERGENCY.swap(false, Ordering::Relaxed) {
+            info!("Sensors back below critical; resuming {:?} fan mode", mode);
+            crate::alerts::clear(EMERGENCY_ALERT);
+            last_mode = None;
+        }
+
+        if EXTERNAL_HOLD.load(Ordering::Relaxed) || super::fan_contention::backed_off() {
+            sleep(poll_interval).await;
+            continue;
+        }
+
+        if REAPPLY_REQUESTED.swap(false, Ordering::Relaxed) {
+            debug!("Fan reapply requested; re-anchoring current mode");
+            last_mode = None;
+        }
+
+        // Reset transient control state when the mode, the global/per-fan topology or the duty
+        // offsets change so we re-anchor cleanly.
+        if last_mode != Some(mode.clone())
+            || last_per_fan_active != per_fan_active
+            || last_duty_offsets != config.duty_offsets
+        {
             debug!(
                 "Fan state change: mode {:?} -> {:?}, per_fan {} -> {}",
                 last_mode, mode, last_per_fan_active, per_fan_active
@@ -56,12 +220,16 @@ pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg:
             global.reset();
             per_fan_curve_steppers.clear();
             last_manual_duty.clear();
+            target.reset();
+            rpm_steppers.clear();
+            // autofanctrl or a global duty may have just overwritten the GPU fan too
+            gpu.reset();
         }
 
         match &mode {
             FanControlMode::Disabled => {
                 if last_mode != Some(FanControlMode::Disabled) {
-                    let _ = cli.autofanctrl().await;
+                    set_auto(&cli).await;
                 }
             }
 
@@ -72,22 +240,25 @@ pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg:
                         sleep(poll_interval).await;
                         continue;
                     };
-                    for i in 0..count {
-                        let duty = overrides
-                            .iter()
-                            .find(|o| o.index == i)
-                            .and_then(|o| o.manual.as_ref())
-                            .map(|m| m.duty_pct.min(100))
-                            .or(global_duty);
-                        if let Some(duty) = duty {
-                            apply_manual(&cli, &mut last_manual_duty, Some(i), duty).await;
+                    for i in (0..count).filter(|i| Some(*i) != gpu_ec_index) {
+                        if let Some(duty) = manual_duty_for(&config, i) {
+                            let reason = override_reason(&config, i, ControlActionReason::Manual);
+                            apply_manual(&cli, &config, &mut last_manual_duty, Some(i), duty, reason).await;
                         }
                     }
                 } else if let Some(duty) = global_duty {
-                    apply_manual(&cli, &mut last_manual_duty, None, duty).await;
+                    apply_manual(
+                        &cli,
+                        &config,
+                        &mut last_manual_duty,
+                        None,
+                        duty,
+                        ControlActionReason::Manual,
+                    )
+                    .await;
                 } else {
                     // No manual duty configured: fall back to firmware auto.
-                    let _ = cli.autofanctrl().await;
+                    set_auto(&cli).await;
                 }
             }
 
@@ -97,44 +268,394 @@ pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg:
                         sleep(poll_interval).await;
                         continue;
                     };
-                    for i in 0..count {
-                        let curve = overrides
-                            .iter()
-                            .find(|o| o.index == i)
-                            .and_then(|o| o.curve.clone())
-                            .or_else(|| config.curve.as_ref().map(|c| c.curve.clone()));
-                        let Some(curve) = curve else { continue };
+                    for i in (0..count).filter(|i| Some(*i) != gpu_ec_index) {
+                        let Some(curve) = curve_for(&config, i) else { continue };
+                        let curve = with_tuned_rate(curve, Some(i), poll_interval);
                         let stepper = per_fan_curve_steppers.entry(i).or_insert_with(CurveStepper::new);
-                        apply_curve(&cli, stepper, &curve, Some(i)).await;
+                        let reason = override_reason(&config, i, ControlActionReason::Curve);
+                        apply_curve(&cli, &config, stepper, &curve, Some(i), &sensors_cfg.zones, reason).await;
                     }
                 } else {
                     let Some(curve) = config.curve.as_ref().map(|c| c.curve.clone()) else {
                         warn!("Curve mode without curve config; falling back to platform auto");
-                        let _ = cli.autofanctrl().await;
+                        set_auto(&cli).await;
                         sleep(poll_interval).await;
                         continue;
                     };
-                    apply_curve(&cli, &mut global, &curve, None).await;
+                    let curve = with_tuned_rate(curve, None, poll_interval);
+                    apply_curve(
+                        &cli,
+                        &config,
+                        &mut global,
+                        &curve,
+                        None,
+                        &sensors_cfg.zones,
+                        ControlActionReason::Curve,
+                    )
+                    .await;
                 }
             }
+
+            FanControlMode::Target => {
+                let Some(target_cfg) = config.target.as_ref() else {
+                    warn!("Target mode without target config; falling back to platform auto");
+                    set_auto(&cli).await;
+                    sleep(poll_interval).await;
+                    continue;
+                };
+                // One duty for every system fan; skipping the GPU's EC fan or trimming fans needs
+                // a command per fan
+                let fans: Vec<Option<u32>> = if gpu_ec_index.is_some() || has_duty_offsets(&config) {
+                    let Some(count) = ensure_fan_count(&cli, &mut fan_count).await else {
+                        sleep(poll_interval).await;
+                        continue;
+                    };
+                    (0..count).filter(|i| Some(*i) != gpu_ec_index).map(Some).collect()
+                } else {
+                    vec![None]
+                };
+                apply_target(&cli, &config, &mut target, target_cfg, &fans, &sensors_cfg.zones).await;
+            }
+
+            FanControlMode::Rpm => {
+                let Some(rpm_cfg) = config.rpm.as_ref() else {
+                    warn!("RPM mode without rpm config; falling back to platform auto");
+                    set_auto(&cli).await;
+                    sleep(poll_interval).await;
+                    continue;
+                };
+                let fans: Vec<Option<u32>> = if gpu_ec_index.is_some() || has_fan_rpm_curves(rpm_cfg) {
+                    let Some(count) = ensure_fan_count(&cli, &mut fan_count).await else {
+                        sleep(poll_interval).await;
+                        continue;
+                    };
+                    (0..count).filter(|i| Some(*i) != gpu_ec_index).map(Some).collect()
+                } else {
+                    vec![None]
+                };
+                apply_rpm(&cli, &mut rpm_steppers, rpm_cfg, &fans, &sensors_cfg.zones).await;
+            }
+        }
+
+        gpu.tick(&cli, &gpu_cfg, &sensors_cfg.zones).await;
+        if mode != FanControlMode::Disabled {
+            super::fan_contention::check(&cli, &config).await;
         }
 
         last_mode = Some(mode);
         last_per_fan_active = per_fan_active;
+        last_duty_offsets = config.duty_offsets.clone();
 
         let elapsed = loop_started.elapsed();
+        crate::metrics::record_task_loop("fan_curve", elapsed);
         if elapsed < poll_interval {
             sleep(poll_interval - elapsed).await;
         }
     }
 }
 
+/// The EC fan handed to the GPU controller, and whether fans need individual commands (the GPU
+/// fan is skipped, which needs the per-fan path too).
+fn per_fan_layout(config: &FanControlConfig) -> (Option<u32>, bool) {
+    let gpu_ec_index = match config.gpu.as_ref() {
+        Some(g) if matches!(g.mode, Some(FanControlMode::Manual) | Some(FanControlMode::Curve)) => g.ec_fan_index,
+        _ => None,
+    };
+    let has_manual_duties = config
+        .manual
+        .as_ref()
+        .and_then(|m| m.fan_duties.as_ref())
+        .is_some_and(|d| !d.is_empty());
+    let per_fan_active = config.overrides.as_ref().is_some_and(|o| !o.is_empty())
+        || (config.mode == Some(FanControlMode::Manual) && has_manual_duties)
+        || gpu_ec_index.is_some()
+        || has_duty_offsets(config);
+    (gpu_ec_index, per_fan_active)
+}
+
+fn has_duty_offsets(config: &FanControlConfig) -> bool {
+    config.duty_offsets.as_ref().is_some_and(|o| !o.is_empty())
+}
+
+/// Manual duty of fan `i`. Precedence: per-fan override, then the manual per-fan table, then
+/// the global duty.
+fn manual_duty_for(config: &FanControlConfig, i: u32) -> Option<u32> {
+    let overrides = config.overrides.as_deref().unwrap_or_default();
+    let manual = config.manual.as_ref();
+    overrides
+        .iter()
+        .find(|o| o.index == i)
+        .and_then(|o| o.manual.as_ref())
+        .map(|m| m.duty_pct.min(100))
+        .or_else(|| {
+            manual
+                .and_then(|m| m.fan_duties.as_ref())
+                .and_then(|d| d.iter().find(|d| d.index == i))
+                .map(|d| d.duty_pct.min(100))
+        })
+        .or(manual.map(|m| m.duty_pct.min(100)))
+}
+
+/// `Override` when fan `i` has an override for `mode_reason` (`Manual` or `Curve`), else that.
+fn override_reason(config: &FanControlConfig, i: u32, mode_reason: ControlActionReason) -> ControlActionReason {
+    let overridden = config.overrides.as_deref().unwrap_or_default().iter().any(|o| {
+        o.index == i
+            && match mode_reason {
+                ControlActionReason::Curve => o.curve.is_some(),
+                _ => o.manual.is_some(),
+            }
+    });
+    if overridden {
+        ControlActionReason::Override
+    } else {
+        mode_reason
+    }
+}
+
+/// Curve of fan `i`: its override's, else the global one.
+fn curve_for(config: &FanControlConfig, i: u32) -> Option<CurveConfig> {
+    config
+        .overrides
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .find(|o| o.index == i)
+        .and_then(|o| o.curve.clone())
+        .or_else(|| config.curve.as_ref().map(|c| c.curve.clone()))
+}
+
+/// Dry run of a `fan` write: the commands the task issues when it re-anchors on the new config.
+/// Curves start at their target duty for the current temperature. The dGPU module fan is not
+/// covered.
+pub async fn preview(
+    plan: &mut Plan,
+    cli: &FrameworkTool,
+    config: &FanControlConfig,
+    zones: &BTreeMap<String, Vec<String>>,
+) {
+    let (gpu_ec_index, per_fan_active) = per_fan_layout(config);
+    let fans: Vec<u32> = if per_fan_active {
+        let count = cli.thermal().await.map(|t| t.fans.len() as u32).unwrap_or(0);
+        (0..count).filter(|i| Some(*i) != gpu_ec_index).collect()
+    } else {
+        Vec::new()
+    };
+    match config.mode.clone().unwrap_or(FanControlMode::Disabled) {
+        FanControlMode::Disabled => plan.add("fan.mode", cli.autofanctrl()).await,
+        FanControlMode::Manual if per_fan_active => {
+            for i in fans {
+                if let Some(duty) = manual_duty_for(config, i) {
+                    let duty = config.offset_duty(Some(i), duty);
+                    plan.add(&format!("fan.manual (fan {})", i), cli.set_fan_duty(duty, Some(i)))
+                        .await;
+                }
+            }
+        }
+        FanControlMode::Manual => match config.manual.as_ref() {
+            Some(m) => {
+                plan.add("fan.manual.duty_pct", cli.set_fan_duty(m.duty_pct.min(100), None))
+                    .await
+            }
+            None => plan.add("fan.mode", cli.autofanctrl()).await,
+        },
+        FanControlMode::Curve if per_fan_active => {
+            for i in fans {
+                if let Some(curve) = curve_for(config, i) {
+                    preview_curve(plan, cli, config, &curve, Some(i), zones).await;
+                }
+            }
+        }
+        FanControlMode::Curve => match config.curve.as_ref() {
+            Some(c) => preview_curve(plan, cli, config, &c.curve, None, zones).await,
+            None => plan.add("fan.mode", cli.autofanctrl()).await,
+        },
+        FanControlMode::Target => match config.target.as_ref() {
+            Some(t) => {
+                let Some(temp) = target_temperature(cli, &t.sensors, zones).await else {
+                    let err = format!("no reading for target sensors {:?}", t.sensors);
+                    plan.add("fan.target", async { Err::<(), _>(err) }).await;
+                    return;
+                };
+                let Some(duty) = TargetStepper::new().next(temp, t) else {
+                    return;
+                };
+                let fans = if gpu_ec_index.is_some() || has_duty_offsets(config) {
+                    fans.into_iter().map(Some).collect()
+                } else {
+                    vec![None]
+                };
+                for i in fans {
+                    let setting = match i {
+                        Some(i) => format!("fan.target (fan {})", i),
+                        None => "fan.target".to_string(),
+                    };
+                    plan.add(&setting, cli.set_fan_duty(config.offset_duty(i, duty), i))
+                        .await;
+                }
+            }
+            None => plan.add("fan.mode", cli.autofanctrl()).await,
+        },
+        FanControlMode::Rpm => match config.rpm.as_ref() {
+            Some(r) => {
+                let fans: Vec<Option<u32>> = if gpu_ec_index.is_some() || has_fan_rpm_curves(r) {
+                    let count = cli.thermal().await.map(|t| t.fans.len() as u32).unwrap_or(0);
+                    (0..count).filter(|i| Some(*i) != gpu_ec_index).map(Some).collect()
+                } else {
+                    vec![None]
+                };
+                let Some(temp) = rpm_temperature(cli, r, zones).await else {
+                    let err = format!("no reading for rpm sensors {:?}", r.sensors);
+                    plan.add("fan.rpm", async { Err::<(), _>(err) }).await;
+                    return;
+                };
+                for i in fans {
+                    let setting = match i {
+                        Some(i) => format!("fan.rpm (fan {})", i),
+                        None => "fan.rpm".to_string(),
+                    };
+                    let Some(rpm) = RpmStepper::new().next(temp, rpm_points_for(r, i), r.hysteresis_c) else {
+                        continue;
+                    };
+                    plan.add(&setting, cli.set_fan_rpm(rpm, i)).await;
+                }
+            }
+            None => plan.add("fan.mode", cli.autofanctrl()).await,
+        },
+    }
+}
+
+async fn preview_curve(
+    plan: &mut Plan,
+    cli: &FrameworkTool,
+    config: &FanControlConfig,
+    curve: &CurveConfig,
+    fan_index: Option<u32>,
+    zones: &BTreeMap<String, Vec<String>>,
+) {
+    let setting = match fan_index {
+        Some(i) => format!("fan.curve (fan {})", i),
+        None => "fan.curve".to_string(),
+    };
+    let Some(temp) = curve_temperature(cli, curve, zones).await else {
+        let err = format!("no reading for curve sensors {:?}", curve.sensors);
+        plan.add(&setting, async { Err::<(), _>(err) }).await;
+        return;
+    };
+    let mut stepper = CurveStepper::new();
+    stepper.ambient_shift_c = ambient_shift(cli, curve, zones).await;
+    if let Some(duty) = stepper.next(temp, curve) {
+        let duty = config.offset_duty(fan_index, duty);
+        plan.add(&setting, cli.set_fan_duty(duty, fan_index)).await;
+    }
+}
+
+/// Sensors at or above their critical threshold as `(name, temp, critical)`; `None` when the
+/// EC temperatures cannot be read. While the override is `active`, sensors within the release
+/// margin below it still count.
+async fn critical_sensors(
+    cli: &FrameworkTool,
+    sensors_cfg: &SensorsConfig,
+    active: bool,
+) -> Option<Vec<(String, i32, i32)>> {
+    let thermal = crate::sensors::thermal(cli).await.ok()?;
+    // A report without a single temperature leaves nothing to control by
+    if thermal.temps.is_empty() {
+        return None;
+    }
+    let (board, _) = crate::sensors::metadata::board();
+    let margin = if active { EMERGENCY_RELEASE_MARGIN_C } else { 0 };
+    let hot = thermal
+        .temps
+        .into_iter()
+        .filter_map(|(name, temp)| {
+            let critical = crate::sensors::metadata::resolve(board, &name, sensors_cfg).critical_c?;
+            (temp >= critical - margin).then_some((name, temp, critical))
+        })
+        .collect();
+    Some(hot)
+}
+
+/// Consecutive failed thermal reads behind `fan.failsafe`.
+#[derive(Default)]
+struct FailsafeTracker {
+    failed: u32,
+    engaged: bool,
+}
+
+impl FailsafeTracker {
+    /// Count one poll's read; returns `Some(engaged)` when the failsafe engages or releases.
+    fn observe(&mut self, read_ok: bool, threshold: u32) -> Option<bool> {
+        self.failed = if read_ok { 0 } else { self.failed.saturating_add(1) };
+        let engage = self.failed >= threshold.max(1);
+        (engage != self.engaged).then(|| {
+            self.engaged = engage;
+            engage
+        })
+    }
+}
+
+/// Gap between `stop_below_c` and the restart temperature when `restart_at_c` is unset
+const DEFAULT_RESTART_GAP_C: u32 = 5;
+
+/// Rise rates are measured over at least this much history: one poll apart, a single 1 °C
+/// step of the sensor already reads as several °C/s
+const BOOST_WINDOW: Duration = Duration::from_secs(2);
+
+/// Temperature derivative behind a curve's `boost`.
+#[derive(Default)]
+struct BoostTracker {
+    samples: VecDeque<(Instant, i32)>,
+    until: Option<Instant>,
+}
+
+impl BoostTracker {
+    fn reset(&mut self) {
+        self.samples.clear();
+        self.until = None;
+    }
+
+    /// Record `temp` at `now`; returns the boost duty while a boost lasts.
+    fn update(&mut self, temp: i32, now: Instant, cfg: Option<&CurveBoostConfig>) -> Option<u32> {
+        let Some(cfg) = cfg else {
+            self.reset();
+            return None;
+        };
+        self.samples.push_back((now, temp));
+        // Keep the newest sample that is at least a window old as the reference
+        while self.samples.len() > 2 && now.duration_since(self.samples[1].0) >= BOOST_WINDOW {
+            self.samples.pop_front();
+        }
+        if let Some(&(since, from)) = self.samples.front() {
+            let span = now.duration_since(since);
+            if span >= BOOST_WINDOW && (temp - from) as f32 / span.as_secs_f32() > cfg.rise_c_per_s {
+                self.until = Some(now + Duration::from_secs(cfg.duration_s as u64));
+            }
+        }
+        match self.until {
+            Some(until) if now < until => Some(cfg.duty_pct),
+            _ => {
+                self.until = None;
+                None
+            }
+        }
+    }
+}
+
 /// Encapsulates the hysteresis + rate-limit state machine for a single fan.
 struct CurveStepper {
     last_duty: Option<u32>,
     active_target: Option<u32>,
     transition_start_temp: i32,
     anchored: bool,
+    /// Held at 0% by the curve's `stop_below_c`
+    stopped: bool,
+    /// When the temperature dropped below `stop_below_c`, while `stop_delay_s` runs
+    below_stop_since: Option<Instant>,
+    boost: BoostTracker,
+    /// The last duty came from the curve's `boost`
+    boosting: bool,
+    /// Degrees the curve's points move by its `ambient` sensor; set before each `next`
+    ambient_shift_c: i32,
 }
 
 impl CurveStepper {
@@ -144,6 +665,11 @@ impl CurveStepper {
             active_target: None,
             transition_start_temp: 0,
             anchored: false,
+            stopped: false,
+            below_stop_since: None,
+            boost: BoostTracker::default(),
+            boosting: false,
+            ambient_shift_c: 0,
         }
     }
 
@@ -151,6 +677,10 @@ impl CurveStepper {
         self.last_duty = None;
         self.active_target = None;
         self.anchored = false;
+        self.stopped = false;
+        self.below_stop_since = None;
+        self.boost.reset();
+        self.boosting = false;
     }
 
     fn note_applied(&mut self, duty: u32) {
@@ -159,6 +689,48 @@ impl CurveStepper {
 
     /// Advance the state machine for the given temperature and return the duty to apply, or `None` when the current duty should be held.
     fn next(&mut self, temp: i32, curve: &CurveConfig) -> Option<u32> {
+        self.next_at(temp, curve, Instant::now())
+    }
+
+    fn next_at(&mut self, temp: i32, curve: &CurveConfig, now: Instant) -> Option<u32> {
+        let boost = self.boost.update(temp, now, curve.boost.as_ref());
+        // Fan stop: off below `stop_below_c`, back on only at the restart temperature so a
+        // sensor hovering around the threshold does not start and stop the fan every tick.
+        // `stop_delay_s` keeps it on the curve for a while first, so a short dip does not stop it
+        if let Some(stop) = curve.stop_below_c {
+            let restart = curve.restart_at_c.unwrap_or(stop + DEFAULT_RESTART_GAP_C) as i32;
+            if temp >= stop as i32 {
+                self.below_stop_since = None;
+            }
+            if self.stopped && temp >= restart {
+                self.stopped = false;
+                self.anchored = false;
+            } else if !self.stopped && temp < stop as i32 {
+                let since = *self.below_stop_since.get_or_insert(now);
+                let delay = Duration::from_secs(curve.stop_delay_s.unwrap_or(0) as u64);
+                if now.duration_since(since) >= delay {
+                    self.stopped = true;
+                    self.below_stop_since = None;
+                }
+            }
+        } else {
+            self.stopped = false;
+            self.below_stop_since = None;
+        }
+        if self.stopped {
+            self.boosting = false;
+            return (self.last_duty != Some(0)).then_some(0);
+        }
+
+        // Boost skips the rate limit; re-anchoring afterwards winds the fan back down to the
+        // curve at the spin-down rate
+        self.boosting = boost.is_some();
+        if let Some(duty) = boost {
+            let duty = duty.max(self.last_duty.unwrap_or(0));
+            self.anchored = false;
+            return (self.last_duty != Some(duty)).then_some(duty);
+        }
+
         // Anchor hysteresis on first evaluation after a reset.
         if !self.anchored {
             self.transition_start_temp = temp;
@@ -166,7 +738,7 @@ impl CurveStepper {
             self.anchored = true;
         }
 
-        let curve_target = calculate_duty_from_curve(temp, &curve.points);
+        let curve_target = calculate_duty_from_curve(temp, &curve.points, self.ambient_shift_c);
 
         match self.active_target {
             None => {
@@ -174,14 +746,15 @@ impl CurveStepper {
                 self.transition_start_temp = temp;
             }
             Some(current) if curve_target != current => {
+                let down = curve.hysteresis_down() as i32;
+                let up = curve.hysteresis_up_c as i32;
                 if curve_target > current {
-                    // Increasing – accept immediately.
-                    self.active_target = Some(curve_target);
-                    self.transition_start_temp = temp;
-                } else if curve.hysteresis_c == 0
-                    || temp >= self.transition_start_temp
-                    || temp <= self.transition_start_temp - curve.hysteresis_c as i32
-                {
+                    // Increasing – accept once above the up band (immediately without one).
+                    if up == 0 || temp <= self.transition_start_temp || temp >= self.transition_start_temp + up {
+                        self.active_target = Some(curve_target);
+                        self.transition_start_temp = temp;
+                    }
+                } else if down == 0 || temp >= self.transition_start_temp || temp <= self.transition_start_temp - down {
                     // Decreasing – accept once outside the hysteresis band (or
                     // immediately if hysteresis is disabled / temp has risen).
                     self.active_target = Some(curve_target);
@@ -216,36 +789,499 @@ impl CurveStepper {
     }
 }
 
-/// Evaluate a curve for one fan and apply the resulting duty (if it changed).
-async fn apply_curve(cli: &FrameworkTool, stepper: &mut CurveStepper, curve: &CurveConfig, fan_index: Option<u32>) {
-    let Some(temp) = get_max_sensor_temperature(cli, &curve.sensors).await else {
+/// Temperatures this close to the target leave the duty alone
+const TARGET_DEADBAND_C: i32 = 1;
+/// Duty change per tick for each degree away from the target
+const TARGET_GAIN_PCT_PER_C: i32 = 2;
+/// Per-tick limits: heat is chased quickly, the fans wind down gently
+const TARGET_MAX_UP_PCT: i32 = 10;
+const TARGET_MAX_DOWN_PCT: i32 = 3;
+/// First duty after a reset: 50% at the target, 5% more per degree above it (less below)
+const TARGET_START_PCT_PER_C: i32 = 5;
+
+/// Duty controller of the `target` mode. Each tick the duty moves toward holding the sensor at
+/// the target in proportion to the error, so it settles wherever the current load needs.
+struct TargetStepper {
+    last_duty: Option<u32>,
+}
+
+impl TargetStepper {
+    fn new() -> Self {
+        Self { last_duty: None }
+    }
+
+    fn reset(&mut self) {
+        self.last_duty = None;
+    }
+
+    fn note_applied(&mut self, duty: u32) {
+        self.last_duty = Some(duty);
+    }
+
+    /// Duty to apply at `temp`, or `None` to hold the current one.
+    fn next(&self, temp: i32, cfg: &TargetTempConfig) -> Option<u32> {
+        let max = cfg.max_duty_pct.min(100) as i32;
+        let min = (cfg.min_duty_pct as i32).min(max);
+        let error = temp - cfg.target_c as i32;
+        let next = match self.last_duty {
+            None => 50 + error * TARGET_START_PCT_PER_C,
+            Some(prev) if error.abs() <= TARGET_DEADBAND_C => prev as i32,
+            Some(prev) => prev as i32 + (error * TARGET_GAIN_PCT_PER_C).clamp(-TARGET_MAX_DOWN_PCT, TARGET_MAX_UP_PCT),
+        }
+        .clamp(min, max) as u32;
+        (self.last_duty != Some(next)).then_some(next)
+    }
+}
+
+/// RPM targets closer than this to the applied one are not re-sent
+const RPM_DEADBAND: u32 = 50;
+
+/// Controller of the `rpm` mode for one fan (or all at once): the curve's RPM at the current
+/// temperature, with the same rise-now, fall-after-hysteresis rule as the duty curve. The EC
+/// ramps the fan to the target itself, so there is no rate limit.
+struct RpmStepper {
+    last_rpm: Option<u32>,
+    /// Curve RPM being held and the temperature it was accepted at
+    active: Option<(u32, i32)>,
+}
+
+impl RpmStepper {
+    fn new() -> Self {
+        Self {
+            last_rpm: None,
+            active: None,
+        }
+    }
+
+    fn note_applied(&mut self, rpm: u32) {
+        self.last_rpm = Some(rpm);
+    }
+
+    /// RPM to apply at `temp`, or `None` to hold the current target.
+    fn next(&mut self, temp: i32, points: &[[u32; 2]], hysteresis_c: u32) -> Option<u32> {
+        let wanted = rpm_from_curve(temp, points)?;
+        let target = match self.active {
+            Some((held, since)) if wanted < held && temp > since - hysteresis_c as i32 => held,
+            _ => {
+                self.active = Some((wanted, temp));
+                wanted
+            }
+        };
+        match self.last_rpm {
+            Some(prev) if prev.abs_diff(target) < RPM_DEADBAND => None,
+            _ => Some(target),
+        }
+    }
+}
+
+/// Linear interpolation over `[temp_c, rpm]` points, flat outside them.
+fn rpm_from_curve(temp: i32, points: &[[u32; 2]]) -> Option<u32> {
+    let first = points.first()?;
+    if temp <= first[0] as i32 {
+        return Some(first[1]);
+    }
+    for pair in points.windows(2) {
+        let ([t1, r1], [t2, r2]) = (pair[0], pair[1]);
+        if temp <= t2 as i32 {
+            if t2 == t1 {
+                return Some(r2);
+            }
+            let ratio = (temp as f64 - t1 as f64) / (t2 as f64 - t1 as f64);
+            return Some((r1 as f64 + ratio * (r2 as f64 - r1 as f64)).round() as u32);
+        }
+    }
+    points.last().map(|p| p[1])
+}
+
+/// The rpm mode temperature: its sensors combined by `aggregate`, or the hottest sensor when none
+/// are configured.
+async fn rpm_temperature(
+    cli: &FrameworkTool,
+    cfg: &RpmCurveConfig,
+    zones: &BTreeMap<String, Vec<String>>,
+) -> Option<i32> {
+    if cfg.sensors.is_empty() {
+        return target_temperature(cli, &[], zones).await;
+    }
+    let how = cfg.aggregate.unwrap_or_default();
+    get_sensor_temperature(cli, &cfg.sensors, how, cfg.weights.as_ref(), zones).await
+}
+
+fn has_fan_rpm_curves(cfg: &RpmCurveConfig) -> bool {
+    cfg.fans.as_ref().is_some_and(|f| !f.is_empty())
+}
+
+/// Points of fan `fan_index`: its own curve, else the shared one.
+fn rpm_points_for(cfg: &RpmCurveConfig, fan_index: Option<u32>) -> &[[u32; 2]] {
+    cfg.fans
+        .iter()
+        .flatten()
+        .find(|f| Some(f.index) == fan_index)
+        .map(|f| f.points.as_slice())
+        .unwrap_or(&cfg.points)
+}
+
+/// Step each fan's RPM controller and apply its target (`None` is every fan at once).
+async fn apply_rpm(
+    cli: &FrameworkTool,
+    steppers: &mut HashMap<Option<u32>, RpmStepper>,
+    cfg: &RpmCurveConfig,
+    fans: &[Option<u32>],
+    zones: &BTreeMap<String, Vec<String>>,
+) {
+    let Some(temp) = rpm_temperature(cli, cfg, zones).await else {
+        warn!("Failed to read the rpm mode sensors {:?}, continuing...", cfg.sensors);
+        return;
+    };
+    for &fan_index in fans {
+        let stepper = steppers.entry(fan_index).or_insert_with(RpmStepper::new);
+        let Some(rpm) = stepper.next(temp, rpm_points_for(cfg, fan_index), cfg.hysteresis_c) else {
+            continue;
+        };
+        match cli.set_fan_rpm(rpm, fan_index).await {
+            Ok(()) => {
+                stepper.note_applied(rpm);
+                fan_restore::record_rpm(cli, fan_index, rpm);
+                crate::actions::record(ControlActionKind::FanRpm, fan_index, rpm, ControlActionReason::Rpm);
+                debug!("RPM: fan {:?} -> {} RPM at {}°C", fan_index, rpm, temp);
+            }
+            Err(e) => warn!("Failed to set fan {:?} RPM: {}", fan_index, e),
+        }
+    }
+}
+
+/// Where the dGPU fan duty goes: the amdgpu PWM, or an EC fan chosen in config.
+enum GpuFanTarget {
+    #[cfg(target_os = "linux")]
+    Amdgpu(crate::cli::amdgpu_fan::AmdGpuFan),
+    Ec(u32),
+}
+
+impl GpuFanTarget {
+    async fn resolve(cfg: &GpuFanConfig) -> Option<Self> {
+        if let Some(i) = cfg.ec_fan_index {
+            return Some(Self::Ec(i));
+        }
+        Self::detect_native().await
+    }
+
+    #[cfg(target_os = "linux")]
+    async fn detect_native() -> Option<Self> {
+        crate::cli::amdgpu_fan::AmdGpuFan::detect().await.map(Self::Amdgpu)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn detect_native() -> Option<Self> {
+        None
+    }
+
+    fn backend(&self) -> GpuFanBackend {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Amdgpu(_) => GpuFanBackend::Amdgpu,
+            Self::Ec(_) => GpuFanBackend::Ec,
+        }
+    }
+
+    async fn set_duty(&self, cli: &FrameworkTool, percent: u32, reason: ControlActionReason) -> Result<(), String> {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Amdgpu(g) => {
+                g.set_duty(percent).await?;
+                crate::actions::record(ControlActionKind::GpuFanDuty, None, percent, reason);
+            }
+            Self::Ec(i) => {
+                cli.set_fan_duty(percent, Some(*i)).await?;
+                fan_restore::record_duty(cli, Some(*i), percent);
+                crate::actions::record(ControlActionKind::FanDuty, Some(*i), percent, reason);
+            }
+        }
+        Ok(())
+    }
+
+    /// Give the fan back to its default controller.
+    async fn release(&self) {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Amdgpu(g) => {
+                if let Err(e) = g.auto().await {
+                    warn!("Failed to restore dGPU fan auto mode: {}", e);
+                }
+            }
+            // The system fan control picks the EC fan back up on its next re-anchor.
+            Self::Ec(_) => request_reapply(),
+        }
+    }
+}
+
+/// Manual/curve control of the dGPU module fan, run after the system fans each tick.
+struct GpuFanControl {
+    stepper: CurveStepper,
+    last_manual_duty: Option<u32>,
+    last_mode: Option<FanControlMode>,
+}
+
+impl GpuFanControl {
+    fn new() -> Self {
+        Self {
+            stepper: CurveStepper::new(),
+            last_manual_duty: None,
+            last_mode: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.stepper.reset();
+        self.last_manual_duty = None;
+    }
+
+    async fn tick(&mut self, cli: &FrameworkTool, cfg: &GpuFanConfig, zones: &BTreeMap<String, Vec<String>>) {
+        let mode = cfg.mode.clone().unwrap_or(FanControlMode::Disabled);
+        let was_controlled = matches!(
+            self.last_mode,
+            Some(FanControlMode::Manual) | Some(FanControlMode::Curve)
+        );
+        if self.last_mode.as_ref() != Some(&mode) {
+            self.reset();
+        }
+        self.last_mode = Some(mode.clone());
+
+        let controlled = matches!(mode, FanControlMode::Manual | FanControlMode::Curve);
+        if !controlled && !was_controlled {
+            return;
+        }
+        let Some(target) = GpuFanTarget::resolve(cfg).await else {
+            debug!("GPU fan: no dGPU fan found");
+            return;
+        };
+
+        match mode {
+            // Config validation keeps `target` off the dGPU fan
+            FanControlMode::Disabled | FanControlMode::Target | FanControlMode::Rpm => target.release().await,
+            FanControlMode::Manual => {
+                let Some(duty) = cfg.duty_pct.map(|d| d.min(100)) else {
+                    return;
+                };
+                if self.last_manual_duty == Some(duty) {
+                    return;
+                }
+                match target.set_duty(cli, duty, ControlActionReason::Manual).await {
+                    Ok(()) => {
+                        self.last_manual_duty = Some(duty);
+                        debug!("Manual: GPU fan -> {}%", duty);
+                    }
+                    Err(e) => warn!("Failed to set GPU fan duty: {}", e),
+                }
+            }
+            FanControlMode::Curve => {
+                let Some(curve) = cfg.curve.as_ref() else { return };
+                let Some(temp) = gpu_temperature(cli, Some(curve), zones).await else {
+                    warn!("Failed to read GPU temperature for the GPU fan curve, continuing...");
+                    return;
+                };
+                self.stepper.ambient_shift_c = ambient_shift(cli, curve, zones).await;
+                if let Some(next) = self.stepper.next(temp, curve) {
+                    let reason = if self.stepper.boosting {
+                        ControlActionReason::Boost
+                    } else {
+                        ControlActionReason::Curve
+                    };
+                    match target.set_duty(cli, next, reason).await {
+                        Ok(()) => {
+                            self.stepper.note_applied(next);
+                            debug!("Curve: GPU fan -> {}% at {}°C", next, temp);
+                        }
+                        Err(e) => warn!("Failed to set GPU fan duty: {}", e),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The curve's sensors combined as it asks, or the hottest sensor with "gpu" in its name when
+/// none are configured (`dGPU Edge`/`dGPU Junction` on Linux, the EC's dGPU sensors on Windows,
+/// `dGPU Core` from nvidia-smi on NVIDIA modules).
+async fn gpu_temperature(
+    cli: &FrameworkTool,
+    curve: Option<&CurveConfig>,
+    zones: &BTreeMap<String, Vec<String>>,
+) -> Option<i32> {
+    if let Some(curve) = curve.filter(|c| !c.sensors.is_empty()) {
+        return curve_temperature(cli, curve, zones).await;
+    }
+    let output = crate::sensors::thermal(cli).await.ok()?;
+    output
+        .temps
+        .iter()
+        .filter(|(name, _)| name.to_ascii_lowercase().contains("gpu"))
+        .map(|(_, v)| *v)
+        .max()
+}
+
+/// Current dGPU fan state for `GET /fan/gpu`.
+pub async fn gpu_fan_status(
+    cli: Option<&FrameworkTool>,
+    cfg: &GpuFanConfig,
+    zones: &BTreeMap<String, Vec<String>>,
+) -> GpuFanStatus {
+    let target = GpuFanTarget::resolve(cfg).await;
+    let mut status = GpuFanStatus {
+        backend: target.as_ref().map(|t| t.backend()),
+        rpm: None,
+        duty_pct: None,
+        temp_c: None,
+    };
+    match &target {
+        #[cfg(target_os = "linux")]
+        Some(GpuFanTarget::Amdgpu(g)) => {
+            status.rpm = g.rpm().await;
+            status.duty_pct = g.duty_pct().await;
+        }
+        Some(GpuFanTarget::Ec(i)) => {
+            if let Some(cli) = cli {
+                status.rpm = cli
+                    .thermal()
+                    .await
+                    .ok()
+                    .and_then(|t| t.fans.get(*i as usize).map(|f| f.rpm));
+            }
+        }
+        None => {}
+    }
+    if let Some(cli) = cli {
+        status.temp_c = gpu_temperature(cli, cfg.curve.as_ref(), zones).await;
+    }
+    status
+}
+
+/// Evaluate a curve for one fan and apply the resulting duty (if it changed), trimmed by the
+/// fan's duty offset.
+async fn apply_curve(
+    cli: &FrameworkTool,
+    config: &FanControlConfig,
+    stepper: &mut CurveStepper,
+    curve: &CurveConfig,
+    fan_index: Option<u32>,
+    zones: &BTreeMap<String, Vec<String>>,
+    reason: ControlActionReason,
+) {
+    let Some(temp) = curve_temperature(cli, curve, zones).await else {
         warn!("Failed to select temperature for fan {:?}, continuing...", fan_index);
         return;
     };
+    let was_boosting = stepper.boosting;
+    stepper.ambient_shift_c = ambient_shift(cli, curve, zones).await;
     if let Some(next) = stepper.next(temp, curve) {
-        match cli.set_fan_duty(next, fan_index).await {
+        let reason = if stepper.boosting {
+            ControlActionReason::Boost
+        } else {
+            reason
+        };
+        if stepper.boosting && !was_boosting {
+            info!(
+                "Curve: fan {:?} boosted to {}%, {}°C and rising fast",
+                fan_index, next, temp
+            );
+        }
+        let duty = config.offset_duty(fan_index, next);
+        match cli.set_fan_duty(duty, fan_index).await {
             Ok(()) => {
                 stepper.note_applied(next);
-                debug!("Curve: fan {:?} -> {}% at {}°C", fan_index, next, temp);
+                fan_restore::record_duty(cli, fan_index, duty);
+                crate::actions::record(ControlActionKind::FanDuty, fan_index, duty, reason);
+                debug!("Curve: fan {:?} -> {}% at {}°C", fan_index, duty, temp);
             }
             Err(e) => warn!("Failed to set fan {:?} duty: {}", fan_index, e),
         }
     }
 }
 
-/// Apply a manual duty for one fan, skipping redundant CLI calls.
+/// Step the `target` mode controller and apply its duty to `fans` (`None` is every fan at once).
+async fn apply_target(
+    cli: &FrameworkTool,
+    config: &FanControlConfig,
+    stepper: &mut TargetStepper,
+    cfg: &TargetTempConfig,
+    fans: &[Option<u32>],
+    zones: &BTreeMap<String, Vec<String>>,
+) {
+    let Some(temp) = target_temperature(cli, &cfg.sensors, zones).await else {
+        warn!(
+            "Failed to read the target mode sensors {:?}, continuing...",
+            cfg.sensors
+        );
+        return;
+    };
+    let Some(next) = stepper.next(temp, cfg) else { return };
+    let mut applied = true;
+    for &fan_index in fans {
+        let duty = config.offset_duty(fan_index, next);
+        match cli.set_fan_duty(duty, fan_index).await {
+            Ok(()) => {
+                fan_restore::record_duty(cli, fan_index, duty);
+                crate::actions::record(ControlActionKind::FanDuty, fan_index, duty, ControlActionReason::Target);
+            }
+            Err(e) => {
+                warn!("Failed to set fan {:?} duty: {}", fan_index, e);
+                applied = false;
+            }
+        }
+    }
+    if applied {
+        stepper.note_applied(next);
+        debug!("Target: fans -> {}% at {}°C (target {}°C)", next, temp, cfg.target_c);
+    }
+}
+
+/// Hottest of the configured sensors, or of every sensor when none are configured.
+async fn target_temperature(
+    cli: &FrameworkTool,
+    sensors: &[String],
+    zones: &BTreeMap<String, Vec<String>>,
+) -> Option<i32> {
+    if !sensors.is_empty() {
+        return get_sensor_temperature(cli, sensors, SensorAggregate::Max, None, zones).await;
+    }
+    crate::sensors::thermal(cli).await.ok()?.temps.values().copied().max()
+}
+
+/// With `auto_rate_limit`, replace the spin-up step by the one measured by the fan ramp test.
+fn with_tuned_rate(mut curve: CurveConfig, fan_index: Option<u32>, poll: Duration) -> CurveConfig {
+    if curve.auto_rate_limit {
+        if let Some(step) = crate::diagnostics::fan_ramp::tuned_step(fan_index, poll) {
+            curve.rate_limit_pct_per_step = step;
+        }
+    }
+    curve
+}
+
+/// Hand the fans back to the EC and remember that for the next start.
+async fn set_auto(cli: &FrameworkTool) {
+    match cli.autofanctrl().await {
+        Ok(()) => fan_restore::record_auto(cli),
+        Err(e) => warn!("Failed to restore EC fan control: {}", e),
+    }
+}
+
+/// Apply a manual duty for one fan, trimmed by its duty offset, skipping redundant CLI calls.
 async fn apply_manual(
     cli: &FrameworkTool,
+    config: &FanControlConfig,
     last_manual_duty: &mut HashMap<Option<u32>, u32>,
     fan_index: Option<u32>,
     duty: u32,
+    reason: ControlActionReason,
 ) {
+    let duty = config.offset_duty(fan_index, duty);
     if last_manual_duty.get(&fan_index) == Some(&duty) {
         return;
     }
     match cli.set_fan_duty(duty, fan_index).await {
         Ok(()) => {
             last_manual_duty.insert(fan_index, duty);
+            fan_restore::record_duty(cli, fan_index, duty);
+            crate::actions::record(ControlActionKind::FanDuty, fan_index, duty, reason);
             debug!("Manual: fan {:?} -> {}%", fan_index, duty);
         }
         Err(e) => warn!("Failed to set fan {:?} duty: {}", fan_index, e),
@@ -266,34 +1302,92 @@ async fn ensure_fan_count(cli: &FrameworkTool, cached: &mut Option<u32>) -> Opti
     Some(count)
 }
 
-/// Read thermal and return the maximum temperature across the provided sensors.
-async fn get_max_sensor_temperature(cli: &FrameworkTool, sensors: &[String]) -> Option<i32> {
-    let output = cli.thermal().await.ok()?;
+/// The curve temperature: its sensors combined by its `aggregate`.
+async fn curve_temperature(
+    cli: &FrameworkTool,
+    curve: &CurveConfig,
+    zones: &BTreeMap<String, Vec<String>>,
+) -> Option<i32> {
+    let how = curve.aggregate.unwrap_or_default();
+    get_sensor_temperature(cli, &curve.sensors, how, curve.weights.as_ref(), zones).await
+}
+
+/// Degrees the curve's `ambient` sensor moves it; 0 without one or while it has no reading.
+async fn ambient_shift(cli: &FrameworkTool, curve: &CurveConfig, zones: &BTreeMap<String, Vec<String>>) -> i32 {
+    let Some(ambient) = curve.ambient.as_ref() else {
+        return 0;
+    };
+    let sensors = std::slice::from_ref(&ambient.sensor);
+    get_sensor_temperature(cli, sensors, SensorAggregate::Max, None, zones)
+        .await
+        .map_or(0, |t| ambient.shift_c(t))
+}
+
+/// Read thermal and combine the provided sensors (hottest, mean or weighted mean); zone names
+/// count as all of their member sensors.
+async fn get_sensor_temperature(
+    cli: &FrameworkTool,
+    sensors: &[String],
+    how: SensorAggregate,
+    weights: Option<&BTreeMap<String, f32>>,
+    zones: &BTreeMap<String, Vec<String>>,
+) -> Option<i32> {
+    let output = crate::sensors::thermal(cli).await.ok()?;
     let temps = &output.temps;
-    let mut best: Option<i32> = None;
-    for name in sensors {
-        if let Some(&v) = temps.get(name) {
-            best = Some(match best {
-                Some(b) => b.max(v),
-                None => v,
-            });
-            continue;
+    let mut readings: BTreeMap<&str, i32> = BTreeMap::new();
+    for name in &crate::sensors::zones::expand(sensors, temps, zones) {
+        let hit = temps
+            .get_key_value(name.as_str())
+            .or_else(|| temps.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)));
+        if let Some((k, &v)) = hit {
+            readings.insert(k.as_str(), v);
         }
-        if let Some((_, v)) = temps.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)) {
-            let v = *v;
-            best = Some(match best {
-                Some(b) => b.max(v),
-                None => v,
+    }
+    aggregate(&readings, how, weights, zones)
+}
+
+fn aggregate(
+    readings: &BTreeMap<&str, i32>,
+    how: SensorAggregate,
+    weights: Option<&BTreeMap<String, f32>>,
+    zones: &BTreeMap<String, Vec<String>>,
+) -> Option<i32> {
+    let weight = |sensor: &str| -> f32 {
+        let Some(weights) = weights.filter(|_| how == SensorAggregate::Weighted) else {
+            return 1.0;
+        };
+        let lookup = |name: &str| {
+            weights
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, w)| *w)
+        };
+        lookup(sensor)
+            .or_else(|| {
+                crate::sensors::zones::zones_of(sensor, zones)
+                    .iter()
+                    .find_map(|z| lookup(z))
+            })
+            .unwrap_or(1.0)
+    };
+    match how {
+        SensorAggregate::Max => readings.values().copied().max(),
+        SensorAggregate::Avg | SensorAggregate::Weighted => {
+            let (sum, total) = readings.iter().fold((0.0f32, 0.0f32), |(sum, total), (sensor, &v)| {
+                let w = weight(sensor);
+                (sum + w * v as f32, total + w)
             });
+            (total > 0.0).then(|| (sum / total).round() as i32)
         }
     }
-    best
 }
 
-/// Calculate fan duty from temperature using the curve points
+/// Calculate fan duty from temperature using the curve points, moved `shift_c` degrees up (down
+/// when negative) by ambient compensation
 /// Always includes anchor points at [0,0] and [100,100] like the frontend
-fn calculate_duty_from_curve(temp: i32, points: &[[u32; 2]]) -> u32 {
-    let temp = temp as f64;
+fn calculate_duty_from_curve(temp: i32, points: &[[u32; 2]], shift_c: i32) -> u32 {
+    // Moving the points up is reading the temperature that much lower
+    let temp = (temp - shift_c) as f64;
 
     let mut full_curve = Vec::with_capacity(points.len() + 2);
     full_curve.push([0, 0]);
@@ -344,29 +1438,53 @@ mod tests {
         let points = [[40, 20], [60, 40], [75, 80]];
 
         // Test interpolation with anchor points
-        assert_eq!(calculate_duty_from_curve(0, &points), 0); // Start anchor
-        assert_eq!(calculate_duty_from_curve(20, &points), 10); // Between [0,0] and [40,20]
-        assert_eq!(calculate_duty_from_curve(40, &points), 20); // Exact point
-        assert_eq!(calculate_duty_from_curve(50, &points), 30); // Between [40,20] and [60,40]
-        assert_eq!(calculate_duty_from_curve(60, &points), 40); // Exact point
-        assert_eq!(calculate_duty_from_curve(75, &points), 80); // Exact point
-        assert_eq!(calculate_duty_from_curve(87, &points), 90); // Between [75,80] and [100,100]
-        assert_eq!(calculate_duty_from_curve(100, &points), 100); // End anchor
+        assert_eq!(calculate_duty_from_curve(0, &points, 0), 0); // Start anchor
+        assert_eq!(calculate_duty_from_curve(20, &points, 0), 10); // Between [0,0] and [40,20]
+        assert_eq!(calculate_duty_from_curve(40, &points, 0), 20); // Exact point
+        assert_eq!(calculate_duty_from_curve(50, &points, 0), 30); // Between [40,20] and [60,40]
+        assert_eq!(calculate_duty_from_curve(60, &points, 0), 40); // Exact point
+        assert_eq!(calculate_duty_from_curve(75, &points, 0), 80); // Exact point
+        assert_eq!(calculate_duty_from_curve(87, &points, 0), 90); // Between [75,80] and [100,100]
+        assert_eq!(calculate_duty_from_curve(100, &points, 0), 100); // End anchor
 
         // Test with empty points (just anchors)
         let empty: [[u32; 2]; 0] = [];
-        assert_eq!(calculate_duty_from_curve(0, &empty), 0);
-        assert_eq!(calculate_duty_from_curve(50, &empty), 50); // Linear from [0,0] to [100,100]
-        assert_eq!(calculate_duty_from_curve(75, &empty), 75);
-        assert_eq!(calculate_duty_from_curve(100, &empty), 100);
+        assert_eq!(calculate_duty_from_curve(0, &empty, 0), 0);
+        assert_eq!(calculate_duty_from_curve(50, &empty, 0), 50); // Linear from [0,0] to [100,100]
+        assert_eq!(calculate_duty_from_curve(75, &empty, 0), 75);
+        assert_eq!(calculate_duty_from_curve(100, &empty, 0), 100);
 
         // Test with single point
         let single = [[50, 30]];
-        assert_eq!(calculate_duty_from_curve(0, &single), 0); // Start anchor
-        assert_eq!(calculate_duty_from_curve(25, &single), 15); // Between [0,0] and [50,30]
-        assert_eq!(calculate_duty_from_curve(50, &single), 30); // Exact point
-        assert_eq!(calculate_duty_from_curve(75, &single), 65); // Between [50,30] and [100,100]
-        assert_eq!(calculate_duty_from_curve(100, &single), 100); // End anchor
+        assert_eq!(calculate_duty_from_curve(0, &single, 0), 0); // Start anchor
+        assert_eq!(calculate_duty_from_curve(25, &single, 0), 15); // Between [0,0] and [50,30]
+        assert_eq!(calculate_duty_from_curve(50, &single, 0), 30); // Exact point
+        assert_eq!(calculate_duty_from_curve(75, &single, 0), 65); // Between [50,30] and [100,100]
+        assert_eq!(calculate_duty_from_curve(100, &single, 0), 100); // End anchor
+    }
+
+    #[test]
+    fn ambient_sensor_moves_the_curve() {
+        let ambient = crate::types::CurveAmbientConfig {
+            sensor: "F75303_AMB".into(),
+            reference_c: 25,
+            shift_per_c: 1.0,
+            max_shift_c: 10,
+        };
+        assert_eq!(ambient.shift_c(30), 5);
+        assert_eq!(ambient.shift_c(45), 10);
+        assert_eq!(ambient.shift_c(20), -5);
+        let halved = crate::types::CurveAmbientConfig {
+            shift_per_c: -0.5,
+            ..ambient
+        };
+        assert_eq!(halved.shift_c(31), -3);
+
+        let points = [[40, 20], [60, 40], [75, 80]];
+        // A 5 °C warmer room: 65 °C now gets what 60 °C got
+        assert_eq!(calculate_duty_from_curve(65, &points, 5), 40);
+        assert_eq!(calculate_duty_from_curve(55, &points, -5), 40);
+        assert_eq!(calculate_duty_from_curve(3, &points, 5), 0);
     }
 
     #[test]
@@ -383,16 +1501,78 @@ mod tests {
         assert_eq!(apply_rate_limit(30, 80, 100), 80);
     }
 
-    fn curve(points: Vec<[u32; 2]>, hysteresis_c: u32, rate_limit_pct_per_step: u32) -> CurveConfig {
+    #[test]
+    fn duty_offsets_trim_single_fans_and_force_per_fan_writes() {
+        use crate::types::FanDutyOffset;
+
+        let mut config = FanControlConfig {
+            mode: Some(FanControlMode::Curve),
+            ..Default::default()
+        };
+        assert!(!per_fan_layout(&config).1);
+        config.duty_offsets = Some(vec![
+            FanDutyOffset {
+                index: 1,
+                offset_pct: -10,
+                scale: 1.0,
+            },
+            FanDutyOffset {
+                index: 2,
+                offset_pct: 5,
+                scale: 0.8,
+            },
+        ]);
+        assert!(per_fan_layout(&config).1);
+        assert_eq!(config.offset_duty(Some(0), 50), 50);
+        assert_eq!(config.offset_duty(None, 50), 50);
+        assert_eq!(config.offset_duty(Some(1), 50), 40);
+        // A spinning fan is never trimmed to a stop
+        assert_eq!(config.offset_duty(Some(1), 5), 1);
+        assert_eq!(config.offset_duty(Some(1), 12), 2);
+        assert_eq!(config.offset_duty(Some(2), 50), 45);
+        assert_eq!(config.offset_duty(Some(2), 100), 85);
+        // A stopped fan stays stopped
+        assert_eq!(config.offset_duty(Some(2), 0), 0);
+    }
+
+    fn curve(points: Vec<[u32; 2]>, hysteresis_down_c: u32, rate_limit_pct_per_step: u32) -> CurveConfig {
         CurveConfig {
             sensors: vec![],
             points,
-            hysteresis_c,
+            hysteresis_up_c: 0,
+            hysteresis_down_c,
+            hysteresis_c: None,
             rate_limit_pct_per_step,
             rate_limit_down_pct_per_step: None,
+            auto_rate_limit: false,
+            stop_below_c: None,
+            restart_at_c: None,
+            stop_delay_s: None,
+            aggregate: None,
+            weights: None,
+            boost: None,
+            ambient: None,
         }
     }
 
+    #[test]
+    fn aggregates_sensors() {
+        let readings = BTreeMap::from([("APU", 80), ("F75303_Local", 40), ("Battery", 30)]);
+        let zones = BTreeMap::from([("Skin".to_string(), vec!["F75303_Local".to_string()])]);
+        let weights = BTreeMap::from([("apu".to_string(), 2.0), ("Skin".to_string(), 0.0)]);
+        assert_eq!(aggregate(&readings, SensorAggregate::Max, None, &zones), Some(80));
+        assert_eq!(
+            aggregate(&readings, SensorAggregate::Avg, Some(&weights), &zones),
+            Some(50)
+        );
+        // APU counts twice, the Skin zone's sensor not at all, Battery once
+        assert_eq!(
+            aggregate(&readings, SensorAggregate::Weighted, Some(&weights), &zones),
+            Some(63)
+        );
+        assert_eq!(aggregate(&BTreeMap::new(), SensorAggregate::Avg, None, &zones), None);
+    }
+
     #[test]
     fn stepper_anchors_then_holds() {
         let c = curve(vec![[40, 20], [60, 40]], 0, 100);
@@ -419,6 +1599,29 @@ mod tests {
         assert_eq!(dropped, Some(34));
     }
 
+    #[test]
+    fn stepper_up_band_delays_speeding_up() {
+        let mut c = curve(vec![[40, 20], [60, 40]], 0, 100);
+        c.hysteresis_up_c = 3;
+        let mut s = CurveStepper::new();
+        assert_eq!(s.next(50, &c), Some(30));
+        s.note_applied(30);
+        // 2°C warmer is inside the 3°C up band
+        assert_eq!(s.next(52, &c), None);
+        assert_eq!(s.next(53, &c), Some(33));
+        s.note_applied(33);
+        // Spin-down has no band here and follows at once
+        assert_eq!(s.next(52, &c), Some(32));
+    }
+
+    #[test]
+    fn legacy_hysteresis_is_the_down_band() {
+        let c: CurveConfig = serde_json::from_str(r#"{"points":[[40,20]],"hysteresis_c":6}"#).unwrap();
+        assert_eq!((c.hysteresis_up_c, c.hysteresis_down()), (0, 6));
+        let c: CurveConfig = serde_json::from_str(r#"{"points":[[40,20]]}"#).unwrap();
+        assert_eq!(c.hysteresis_down(), 2);
+    }
+
     #[test]
     fn stepper_rate_limit_steps_toward_target() {
         let c = curve(vec![[40, 20], [60, 80]], 0, 10);
@@ -428,6 +1631,120 @@ mod tests {
         assert_eq!(s.next(60, &c), Some(30));
     }
 
+    #[test]
+    fn target_stepper_closes_in_on_the_target() {
+        let t = TargetTempConfig {
+            sensors: vec![],
+            target_c: 75,
+            min_duty_pct: 20,
+            max_duty_pct: 90,
+            poll_ms: 2000,
+        };
+        let mut s = TargetStepper::new();
+        // Starts from 50% at the target, scaled by the distance to it
+        assert_eq!(s.next(80, &t), Some(75));
+        assert_eq!(s.next(60, &t), Some(20));
+        s.note_applied(75);
+        // Within the deadband: hold
+        assert_eq!(s.next(76, &t), None);
+        // Hot: up by the gain, capped per tick and by max_duty_pct
+        assert_eq!(s.next(78, &t), Some(81));
+        assert_eq!(s.next(95, &t), Some(85));
+        s.note_applied(88);
+        assert_eq!(s.next(95, &t), Some(90));
+        // Cool: down gently
+        assert_eq!(s.next(65, &t), Some(85));
+    }
+
+    #[test]
+    fn stepper_stops_below_threshold_and_restarts_above_the_gap() {
+        let mut c = curve(vec![[30, 20], [60, 40]], 0, 100);
+        c.stop_below_c = Some(40);
+        let mut s = CurveStepper::new();
+        s.note_applied(30);
+        assert_eq!(s.next(39, &c), Some(0));
+        s.note_applied(0);
+        // Between the thresholds: stays stopped
+        assert_eq!(s.next(43, &c), None);
+        // Restart at 45 (stop + 5) follows the curve again
+        assert_eq!(s.next(45, &c), Some(30));
+        s.note_applied(30);
+        assert_eq!(s.next(42, &c), Some(28));
+    }
+
+    #[test]
+    fn stepper_keeps_spinning_for_the_stop_delay() {
+        let mut c = curve(vec![[30, 20], [60, 40]], 0, 100);
+        c.stop_below_c = Some(40);
+        c.stop_delay_s = Some(30);
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+        let mut s = CurveStepper::new();
+        s.note_applied(28);
+        // Below the threshold, the delay runs and the curve still applies
+        assert_eq!(s.next_at(39, &c, at(0)), Some(26));
+        s.note_applied(26);
+        assert_eq!(s.next_at(38, &c, at(20)), Some(25));
+        s.note_applied(25);
+        // Back above it restarts the delay
+        assert_eq!(s.next_at(40, &c, at(25)), Some(27));
+        s.note_applied(27);
+        assert_eq!(s.next_at(38, &c, at(40)), Some(25));
+        s.note_applied(25);
+        assert_eq!(s.next_at(38, &c, at(69)), None);
+        assert_eq!(s.next_at(38, &c, at(70)), Some(0));
+    }
+
+    #[test]
+    fn rpm_stepper_follows_the_curve_with_hysteresis() {
+        let points = [[40, 1500], [80, 5500]];
+        assert_eq!(rpm_from_curve(20, &points), Some(1500));
+        assert_eq!(rpm_from_curve(90, &points), Some(5500));
+        let mut s = RpmStepper::new();
+        assert_eq!(s.next(60, &points, 3), Some(3500));
+        s.note_applied(3500);
+        // Within the RPM deadband: no write
+        assert_eq!(s.next(60, &points, 3), None);
+        // Cooling inside the hysteresis band holds, beyond it follows
+        assert_eq!(s.next(58, &points, 3), None);
+        assert_eq!(s.next(56, &points, 3), Some(3100));
+        assert_eq!(rpm_from_curve(50, &[]), None);
+    }
+
+    #[test]
+    fn boost_triggers_on_a_fast_rise_and_holds_for_its_duration() {
+        let cfg = CurveBoostConfig {
+            rise_c_per_s: 2.0,
+            duty_pct: 90,
+            duration_s: 10,
+        };
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+        let mut b = BoostTracker::default();
+        // 1 °C every other 400ms poll: 1.25 °C/s
+        for i in 0..8 {
+            assert_eq!(b.update(50 + i / 2, at(i as u64 * 400), Some(&cfg)), None);
+        }
+        // +6 °C over the last 2s
+        assert_eq!(b.update(57, at(3200), Some(&cfg)), Some(90));
+        assert_eq!(b.update(57, at(13_000), Some(&cfg)), Some(90));
+        assert_eq!(b.update(57, at(13_300), Some(&cfg)), None);
+    }
+
+    #[test]
+    fn failsafe_engages_after_consecutive_failures_only() {
+        let mut f = FailsafeTracker::default();
+        assert_eq!(f.observe(false, 3), None);
+        assert_eq!(f.observe(false, 3), None);
+        assert_eq!(f.observe(true, 3), None);
+        assert_eq!(f.observe(false, 3), None);
+        assert_eq!(f.observe(false, 3), None);
+        assert_eq!(f.observe(false, 3), Some(true));
+        assert_eq!(f.observe(false, 3), None);
+        assert!(f.engaged);
+        assert_eq!(f.observe(true, 3), Some(false));
+    }
+
     #[test]
     fn stepper_separate_down_rate_limit() {
         // Fast spin-up (100 = instant), slow spin-down (5% per step).
@@ -0,0 +1,229 @@
+// Last-known-good fan state: every duty/RPM/auto write from the fan control task is mirrored to
+// `fan_state.json` next to the config, together with the resolved framework_tool path. At the
+// next start the state is re-applied straight away with that cached path, before the resolver
+// (PATH lookup, `--versions` check, maybe a winget install) and the fan task have caught up, so
+// fans never sit on EC defaults or on a stale manual duty for the first seconds after boot.
+//
+// Only changes are written, on a blocking thread off the fan task; bursts of changes collapse into
+// one write of the latest state, and the stop path flushes whatever is still pending.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct FanSnapshot {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_path: Option<String>,
+    /// EC automatic fan control was the last thing applied
+    #[serde(default)]
+    auto: bool,
+    /// Duty last applied to all fans at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    global_duty: Option<u32>,
+    /// Per-fan duties applied after the global one, by fan index
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    fan_duties: BTreeMap<u32, u32>,
+    /// RPM target last applied to all fans at once (`rpm` mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    global_rpm: Option<u32>,
+    /// Per-fan RPM targets, by fan index
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    fan_rpms: BTreeMap<u32, u32>,
+}
+
+fn snapshot_path() -> PathBuf {
+    crate::config::config_path().with_file_name("fan_state.json")
+}
+
+fn current() -> &'static Mutex<FanSnapshot> {
+    static CURRENT: OnceLock<Mutex<FanSnapshot>> = OnceLock::new();
+    CURRENT.get_or_init(|| Mutex::new(load()))
+}
+
+fn load() -> FanSnapshot {
+    std::fs::read_to_string(snapshot_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// A change not yet written to `fan_state.json`
+static DIRTY: AtomicBool = AtomicBool::new(false);
+/// Held for a whole write, so a flush never overwrites a newer snapshot with an older one
+static WRITE: Mutex<()> = Mutex::new(());
+
+fn update(cli: &FrameworkTool, f: impl FnOnce(&mut FanSnapshot)) {
+    {
+        let Ok(mut snap) = current().lock() else { return };
+        let mut next = snap.clone();
+        next.tool_path = Some(cli.path.clone());
+        f(&mut next);
+        if next == *snap {
+            return;
+        }
+        *snap = next;
+    }
+    // A flush already queued picks this change up too
+    if !DIRTY.swap(true, Ordering::SeqCst) {
+        match tokio::runtime::Handle::try_current() {
+            Ok(rt) => {
+                rt.spawn_blocking(flush);
+            }
+            Err(_) => flush(),
+        }
+    }
+}
+
+/// Write the current snapshot if it changed since the last write. Blocking.
+pub fn flush() {
+    let Ok(_writing) = WRITE.lock() else { return };
+    if !DIRTY.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let Some(snap) = current().lock().ok().map(|s| s.clone()) else {
+        return;
+    };
+    let path = snapshot_path();
+    match serde_json::to_string(&snap) {
+        Ok(s) => {
+            if let Err(e) = std::fs::write(&path, s) {
+                warn!("fan restore: failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("fan restore: failed to serialize fan state: {}", e),
+    }
+}
+
+/// Record a successful `--fansetduty` (all fans when `fan_index` is `None`).
+pub fn record_duty(cli: &FrameworkTool, fan_index: Option<u32>, duty: u32) {
+    update(cli, |s| {
+        s.auto = false;
+        match fan_index {
+            Some(i) => {
+                s.fan_duties.insert(i, duty);
+                s.fan_rpms.remove(&i);
+            }
+            None => {
+                s.global_duty = Some(duty);
+                s.fan_duties.clear();
+                s.global_rpm = None;
+                s.fan_rpms.clear();
+            }
+        }
+    });
+}
+
+/// Record a successful `--fansetrpm` (all fans when `fan_index` is `None`).
+pub fn record_rpm(cli: &FrameworkTool, fan_index: Option<u32>, rpm: u32) {
+    update(cli, |s| {
+        s.auto = false;
+        match fan_index {
+            Some(i) => {
+                s.fan_rpms.insert(i, rpm);
+                s.fan_duties.remove(&i);
+            }
+            None => {
+                s.global_rpm = Some(rpm);
+                s.fan_rpms.clear();
+                s.global_duty = None;
+                s.fan_duties.clear();
+            }
+        }
+    });
+}
+
+/// Record a successful `--autofanctrl`.
+pub fn record_auto(cli: &FrameworkTool) {
+    update(cli, |s| {
+        s.auto = true;
+        s.global_duty = None;
+        s.fan_duties.clear();
+        s.global_rpm = None;
+        s.fan_rpms.clear();
+    });
+}
+
+/// Highest duty currently applied by the service, or `None` while the EC is in control.
+pub fn last_duty() -> Option<u32> {
+    let snap = current().lock().ok()?;
+    if snap.auto {
+        return None;
+    }
+    snap.global_duty
+        .into_iter()
+        .chain(snap.fan_duties.values().copied())
+        .max()
+}
+
+/// Whether the last writes left the EC holding RPM targets (`rpm` mode).
+pub fn holds_rpm() -> bool {
+    current()
+        .lock()
+        .is_ok_and(|s| !s.auto && (s.global_rpm.is_some() || !s.fan_rpms.is_empty()))
+}
+
+/// Duty currently applied by the service to fan `fan_index`, or `None` while the EC is in control
+/// (automatically or holding an RPM target).
+pub fn applied_duty(fan_index: u32) -> Option<u32> {
+    let snap = current().lock().ok()?;
+    if snap.auto {
+        return None;
+    }
+    if let Some(&duty) = snap.fan_duties.get(&fan_index) {
+        return Some(duty);
+    }
+    if snap.fan_rpms.contains_key(&fan_index) || snap.global_rpm.is_some() {
+        return None;
+    }
+    snap.global_duty
+}
+
+/// Re-apply the persisted fan state using the cached tool path, then have the fan task
+/// re-anchor so it overwrites anything this wrote once it is running.
+pub async fn restore() {
+    let snap = { current().lock().map(|s| s.clone()).unwrap_or_default() };
+    if !snap.auto
+        && snap.global_duty.is_none()
+        && snap.fan_duties.is_empty()
+        && snap.global_rpm.is_none()
+        && snap.fan_rpms.is_empty()
+    {
+        return;
+    }
+    let Some(cli) = snap.tool_path.clone().and_then(FrameworkTool::from_cached_path) else {
+        return;
+    };
+    let result = if snap.auto {
+        cli.autofanctrl().await
+    } else {
+        let mut result = Ok(());
+        if let Some(duty) = snap.global_duty {
+            result = cli.set_fan_duty(duty, None).await;
+        }
+        for (&i, &duty) in &snap.fan_duties {
+            result = result.and(cli.set_fan_duty(duty, Some(i)).await);
+        }
+        if let Some(rpm) = snap.global_rpm {
+            result = result.and(cli.set_fan_rpm(rpm, None).await);
+        }
+        for (&i, &rpm) in &snap.fan_rpms {
+            result = result.and(cli.set_fan_rpm(rpm, Some(i)).await);
+        }
+        result
+    };
+    match result {
+        Ok(()) => info!(
+            "fan restore: re-applied last fan state (auto: {}, duty: {:?}, per-fan: {:?}, rpm: {:?}, per-fan rpm: {:?})",
+            snap.auto, snap.global_duty, snap.fan_duties, snap.global_rpm, snap.fan_rpms
+        ),
+        Err(e) => warn!("fan restore: failed to re-apply last fan state: {}", e),
+    }
+    crate::tasks::fan_curve::request_reapply();
+}
@@ -0,0 +1,218 @@
+// Fan profile schedule: `fan.schedule` maps local time-of-day windows to `fan.profiles`. The
+// task checks the clock twice a minute and, when the profile the schedule calls for changes,
+// activates it the way `POST /fan/profile/activate` does (saved config, new revision). Between
+// boundaries it leaves the fans alone, so a profile picked by hand holds until the next window
+// starts or ends. A temporary override of the fan section postpones the switch until it ends.
+//
+// Rules with `wake` get a daily OS wake timer `wake_lead_minutes` before their start (see
+// `wake.rs`) and their window opens at that time, so the profile is in place when the window
+// starts. With `sleep_after`, a resume within a few minutes of the wake time marks the machine
+// as woken for the rule, and it is suspended again when the rule's window ends.
+
+use std::sync::Arc;
+
+use chrono::Timelike;
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::types::{Config, FanControlConfig, FanScheduleConfig};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// A resume this soon after a rule's wake time counts as that wake timer firing
+const WAKE_MATCH_MINUTES: u16 = 10;
+
+/// Minutes since midnight of "HH:MM".
+pub fn parse_hhmm(s: &str) -> Option<u16> {
+    let (h, m) = s.trim().split_once(':')?;
+    if h.is_empty() || h.len() > 2 || m.len() != 2 {
+        return None;
+    }
+    let (h, m): (u16, u16) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// Index of the rule in effect at `minute` (since local midnight). A `wake` rule's window opens
+/// at its wake time, ahead of `start`.
+fn scheduled_rule(schedule: &FanScheduleConfig, minute: u16) -> Option<usize> {
+    let lead = crate::wake::lead_minutes(schedule);
+    schedule.rules.iter().position(|r| {
+        let (Some(start), Some(end)) = (parse_hhmm(&r.start), parse_hhmm(&r.end)) else {
+            return false;
+        };
+        let start = if r.wake {
+            crate::wake::before(start, lead)
+        } else {
+            start
+        };
+        if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        }
+    })
+}
+
+/// Profile `schedule` calls for at `minute` (since local midnight).
+fn scheduled(schedule: &FanScheduleConfig, minute: u16) -> Option<&str> {
+    match scheduled_rule(schedule, minute) {
+        Some(i) => Some(schedule.rules[i].profile.as_str()),
+        None => schedule.default_profile.as_deref(),
+    }
+}
+
+/// The `sleep_after` rule a resume at `minute` was the wake timer of, if any.
+fn woken_for(schedule: &FanScheduleConfig, minute: u16) -> Option<usize> {
+    let lead = crate::wake::lead_minutes(schedule);
+    let i = scheduled_rule(schedule, minute)?;
+    let rule = &schedule.rules[i];
+    let wake_at = crate::wake::before(parse_hhmm(&rule.start)?, lead);
+    let late = (minute + 24 * 60 - wake_at) % (24 * 60);
+    (rule.wake && rule.sleep_after && late <= WAKE_MATCH_MINUTES).then_some(i)
+}
+
+fn local_minute() -> u16 {
+    let now = chrono::Local::now();
+    (now.hour() * 60 + now.minute()) as u16
+}
+
+/// Profile the enabled schedule calls for right now.
+pub fn current(fan: &FanControlConfig) -> Option<String> {
+    let schedule = fan.schedule.as_ref().filter(|s| s.enabled)?;
+    scheduled(schedule, local_minute()).map(str::to_string)
+}
+
+/// Activate `name` in the saved and running config. `Ok(false)` when it has to wait for a
+/// temporary fan override to end.
+async fn activate(cfg: &Arc<RwLock<Config>>, name: &str) -> Result<bool, String> {
+    let _writer = crate::config::WRITE_LOCK.lock().await;
+    if crate::temporary::active().iter().any(|o| o.section == "fan") {
+        return Ok(false);
+    }
+    let running = cfg.read().await.clone();
+    let mut merged = crate::temporary::persisted(&running);
+    if !crate::fan_profiles::exists(&merged.fan, name) {
+        return Err(format!("no fan profile named '{}'", name));
+    }
+    crate::fan_profiles::activate(&mut merged.fan, name);
+    merged.revision = running.revision + 1;
+    crate::config::save(&merged)?;
+    crate::config_check::clear_section("fan");
+    *cfg.write().await = crate::temporary::overlay(merged, &running, &["fan"]);
+    info!(
+        "fan schedule: profile '{}' active (revision {})",
+        name,
+        running.revision + 1
+    );
+    Ok(true)
+}
+
+pub async fn run(cfg: Arc<RwLock<Config>>) {
+    // What the schedule last called for, once it was in effect
+    let mut applied: Option<String> = None;
+    let mut last_error: Option<String> = None;
+    // Wake times last handed to the OS; `None` until the first sync clears leftovers
+    let mut armed: Option<Vec<u16>> = None;
+    let mut resume_generation = crate::tasks::resume::generation();
+    // `sleep_after` rule whose wake timer woke the machine
+    let mut woken: Option<usize> = None;
+    loop {
+        let started = std::time::Instant::now();
+        let fan = { cfg.read().await.fan.clone() };
+        let schedule = fan.schedule.clone().unwrap_or_default();
+        let wanted = crate::wake::wake_minutes(&schedule);
+        if armed.as_ref() != Some(&wanted) {
+            if let Err(e) = crate::wake::sync(&wanted).await {
+                warn!("fan schedule: could not set up wake timers: {}", e);
+            }
+            armed = Some(wanted);
+        }
+        let minute = local_minute();
+        let generation = crate::tasks::resume::generation();
+        if generation != resume_generation {
+            resume_generation = generation;
+            woken = woken_for(&schedule, minute).filter(|_| schedule.enabled);
+        }
+        if let Some(i) = woken {
+            if !schedule.enabled || scheduled_rule(&schedule, minute) != Some(i) {
+                woken = None;
+                if schedule.rules.get(i).is_some_and(|r| r.sleep_after) {
+                    if let Err(e) = crate::wake::suspend().await {
+                        warn!("fan schedule: could not suspend after the window: {}", e);
+                    }
+                }
+            }
+        }
+        let due = current(&fan);
+        if fan.schedule.as_ref().is_none_or(|s| !s.enabled) {
+            applied = None;
+        } else if due.is_some() && due != applied {
+            let name = due.clone().unwrap_or_default();
+            let result = if fan.active_profile.as_deref() == Some(name.as_str()) {
+                Ok(true)
+            } else {
+                activate(&cfg, &name).await
+            };
+            match result {
+                Ok(true) => {
+                    applied = due;
+                    last_error = None;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    if last_error.as_ref() != Some(&e) {
+                        warn!("fan schedule: {}", e);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        crate::metrics::record_task_loop("fan_schedule", started.elapsed());
+        sleep(CHECK_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FanScheduleRule;
+
+    #[test]
+    fn windows_wrap_past_midnight() {
+        let rule = |profile: &str, start: &str, end: &str| FanScheduleRule {
+            profile: profile.into(),
+            start: start.into(),
+            end: end.into(),
+            wake: false,
+            sleep_after: false,
+        };
+        let schedule = FanScheduleConfig {
+            enabled: true,
+            rules: vec![rule("quiet", "22:00", "08:00"), rule("meeting", "13:00", "14:30")],
+            default_profile: Some("balanced".into()),
+            wake_lead_minutes: None,
+        };
+        let at = |t: &str| scheduled(&schedule, parse_hhmm(t).unwrap());
+        assert_eq!(at("23:59"), Some("quiet"));
+        assert_eq!(at("07:59"), Some("quiet"));
+        assert_eq!(at("08:00"), Some("balanced"));
+        assert_eq!(at("14:00"), Some("meeting"));
+        assert_eq!(at("14:30"), Some("balanced"));
+        assert_eq!(parse_hhmm("24:00"), None);
+        assert_eq!(parse_hhmm("7:05"), Some(425));
+        assert_eq!(parse_hhmm("7:5"), None);
+
+        let mut render = rule("render", "02:00", "04:00");
+        render.wake = true;
+        render.sleep_after = true;
+        let schedule = FanScheduleConfig {
+            rules: vec![render],
+            wake_lead_minutes: Some(10),
+            ..schedule.clone()
+        };
+        // Pre-heated from the wake time; only a resume right after it counts as the timer's
+        assert_eq!(scheduled(&schedule, parse_hhmm("01:50").unwrap()), Some("render"));
+        assert_eq!(woken_for(&schedule, parse_hhmm("01:52").unwrap()), Some(0));
+        assert_eq!(woken_for(&schedule, parse_hhmm("03:00").unwrap()), None);
+    }
+}
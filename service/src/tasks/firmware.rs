@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration};
+use tracing::debug;
+
+use crate::cli::FrameworkTool;
+use crate::types::Config;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// Daily firmware feed check (see `firmware::check`). Never flashes anything.
+pub async fn run(
+    cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
+    cfg_lock: Arc<tokio::sync::RwLock<Config>>,
+) {
+    sleep(STARTUP_DELAY).await;
+    loop {
+        let updates_cfg = { cfg_lock.read().await.updates.clone() };
+        if crate::firmware::feed_url(&updates_cfg).is_some() {
+            let cli = { cli_lock.read().await.clone() };
+            let result = crate::firmware::check(cli.as_ref(), &updates_cfg).await;
+            if let Some(e) = &result.error {
+                debug!("firmware: check failed: {}", e);
+            }
+        } else {
+            crate::alerts::clear("firmware_update");
+        }
+        sleep(CHECK_INTERVAL).await;
+    }
+}
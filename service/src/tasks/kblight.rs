@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::dry_run::Plan;
+use crate::types::{Config, KeyboardBacklightConfig};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Back-off after a failed ALS read (no sensor on this model, EC busy, ...).
+const ALS_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keyboard backlight automation: maps the ambient light sensor through the configured
+/// lux steps, capped on battery. Writes only when the target changes so manual Fn+Space
+/// changes stick until the light level moves to another step.
+pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg: Arc<tokio::sync::RwLock<Config>>) {
+    info!("Keyboard backlight task started");
+
+    let mut last_applied: Option<u32> = None;
+    let mut resume_generation = crate::tasks::resume::generation();
+
+    loop {
+        let config = cfg.read().await.keyboard_backlight.clone();
+        if !config.enabled {
+            last_applied = None;
+            sleep(POLL_INTERVAL).await;
+            continue;
+        }
+        let Some(cli) = ({ cli_lock.read().await.clone() }) else {
+            sleep(POLL_INTERVAL).await;
+            continue;
+        };
+
+        // The EC restores its own backlight level on resume
+        let generation = crate::tasks::resume::generation();
+        if generation != resume_generation {
+            resume_generation = generation;
+            last_applied = None;
+        }
+
+        let lux = match cli.als_lux().await {
+            Ok(lux) => lux,
+            Err(e) => {
+                warn!("kblight: ambient light read failed: {}", e);
+                sleep(ALS_RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+        let on_ac = cli.power().await.ok().and_then(|p| p.ac_present).unwrap_or(true);
+        let target = brightness_for(lux, on_ac, &config);
+
+        if last_applied != Some(target) {
+            match cli.set_kblight(target).await {
+                Ok(()) => {
+                    debug!("kblight: {}% at {} lux (ac: {})", target, lux, on_ac);
+                    last_applied = Some(target);
+                }
+                Err(e) => warn!("kblight: set failed: {}", e),
+            }
+        }
+
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Dry run of a `keyboard_backlight` write: the write for the current light level, unless the
+/// task already holds that target.
+pub async fn preview(
+    plan: &mut Plan,
+    cli: &FrameworkTool,
+    current: &KeyboardBacklightConfig,
+    new: &KeyboardBacklightConfig,
+) {
+    if !new.enabled {
+        return;
+    }
+    let lux = match cli.als_lux().await {
+        Ok(lux) => lux,
+        Err(e) => {
+            plan.add("keyboard_backlight", async { Err::<(), _>(e) }).await;
+            return;
+        }
+    };
+    let on_ac = cli.power().await.ok().and_then(|p| p.ac_present).unwrap_or(true);
+    let target = brightness_for(lux, on_ac, new);
+    if current.enabled && brightness_for(lux, on_ac, current) == target {
+        return;
+    }
+    plan.add("keyboard_backlight", cli.set_kblight(target)).await;
+}
+
+/// Brightness of the highest `[lux, pct]` step at or below `lux` (the lowest step applies
+/// below the first threshold), capped by `battery_max_pct` when off AC.
+fn brightness_for(lux: u32, on_ac: bool, config: &KeyboardBacklightConfig) -> u32 {
+    let mut steps = config.points.clone();
+    steps.sort_by_key(|p| p[0]);
+    let pct = steps
+        .iter()
+        .rev()
+        .find(|p| p[0] <= lux)
+        .or(steps.first())
+        .map(|p| p[1].min(100))
+        .unwrap_or(0);
+    match config.battery_max_pct {
+        Some(cap) if !on_ac => pct.min(cap),
+        _ => pct,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_follow_ambient_light_and_battery_cap() {
+        let cfg = KeyboardBacklightConfig {
+            enabled: true,
+            points: vec![[150, 0], [0, 50], [20, 30]],
+            battery_max_pct: Some(20),
+        };
+        assert_eq!(brightness_for(0, true, &cfg), 50);
+        assert_eq!(brightness_for(19, true, &cfg), 50);
+        assert_eq!(brightness_for(20, true, &cfg), 30);
+        assert_eq!(brightness_for(400, true, &cfg), 0);
+        assert_eq!(brightness_for(0, false, &cfg), 20);
+    }
+}
@@ -1,50 +1,114 @@
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
 use crate::state::AppState;
 
 pub async fn boot(state: &AppState) {
-    // Fan curve task: always start; it will wait until framework_tool is available
+    if crate::safe_mode::is_active() {
+        // Safe mode: no control tasks; hand the fans back to the EC once the tool is available
+        let ft_clone = state.framework_tool.clone();
+        tokio::spawn(async move {
+            restore_ec_fan_control(ft_clone).await;
+        });
+    } else {
+        boot_control_tasks(state);
+    }
+
+    // Resume watcher: reapply settings and re-validate tools after sleep/hibernate/fast startup
+    {
+        #[cfg(target_os = "windows")]
+        let ryz_clone = state.power.ryzenadj.clone();
+        tokio::spawn(async move {
+            crate::tasks::resume::run(
+                #[cfg(target_os = "windows")]
+                ryz_clone,
+            )
+            .await;
+        });
+    }
+
+    // Auto-update background task (kept in safe mode: an update may be the fix)
+    {
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::tasks::auto_update::run(cfg_clone).await;
+        });
+    }
+
+    // Firmware release feed check (read-only, kept in safe mode)
     {
         let ft_clone = state.framework_tool.clone();
         let cfg_clone = state.config.clone();
         tokio::spawn(async move {
-            crate::tasks::fan_curve::run(ft_clone, cfg_clone).await;
+            crate::tasks::firmware::run(ft_clone, cfg_clone).await;
         });
     }
 
-    // Power settings task: start once at boot; it will wait until power backend is available
-    #[cfg(target_os = "windows")]
+    // Uploads opt-in error reports (kept in safe mode: it is what a crash loop leaves behind)
+    tokio::spawn(crate::error_reports::run());
+
+    // Reverts temporary config overrides as they expire (kept in safe mode)
     {
-        let power_backend = state.ryzenadj.clone();
         let cfg_clone = state.config.clone();
-        let ft_clone = state.framework_tool.clone();
         tokio::spawn(async move {
-            crate::tasks::power::run(power_backend, cfg_clone, ft_clone).await;
+            crate::temporary::run(cfg_clone).await;
         });
     }
 
-    #[cfg(target_os = "linux")]
+    // Managed profile directory watcher
     {
-        let power_backend = state.linux_power.clone();
         let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::managed_profiles::run(cfg_clone).await;
+        });
+    }
+
+    // Charging history (monitoring only, kept in safe mode)
+    {
         let ft_clone = state.framework_tool.clone();
+        let cfg_clone = state.config.clone();
         tokio::spawn(async move {
-            crate::tasks::power::run(power_backend, cfg_clone, ft_clone).await;
+            crate::tasks::battery_sessions::run(ft_clone, cfg_clone).await;
         });
     }
 
-    // Battery settings task: applies charge limit and rate on change and periodically
+    // Scheduled telemetry reports (monitoring only, kept in safe mode)
     {
         let ft_clone = state.framework_tool.clone();
         let cfg_clone = state.config.clone();
         tokio::spawn(async move {
-            crate::tasks::battery::run(ft_clone, cfg_clone).await;
+            crate::tasks::reports::run(ft_clone, cfg_clone).await;
         });
     }
 
-    // Auto-update background task
+    // Nightly config and history snapshots (files only, kept in safe mode)
     {
         let cfg_clone = state.config.clone();
         tokio::spawn(async move {
-            crate::tasks::auto_update::run(cfg_clone).await;
+            crate::tasks::backup::run(cfg_clone).await;
+        });
+    }
+
+    // Extra sensor collector (storage/GPU providers merged into the sensor catalog) and
+    // threshold alerts (monitoring only, kept in safe mode)
+    {
+        let ft_clone = state.framework_tool.clone();
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::tasks::sensors::run(ft_clone, cfg_clone).await;
+        });
+    }
+
+    // One-shot check of the config against the detected sensors and fans
+    {
+        let ft_clone = state.framework_tool.clone();
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::config_check::check_hardware(ft_clone, cfg_clone).await;
         });
     }
 
@@ -53,14 +117,117 @@ pub async fn boot(state: &AppState) {
         let ft_clone = state.framework_tool.clone();
         let cfg_clone = state.config.clone();
         let samples_clone = state.telemetry_samples.clone();
+        #[cfg(target_os = "windows")]
+        let ryz_clone = state.power.ryzenadj.clone();
         tokio::spawn(async move {
-            crate::tasks::telemetry::run(ft_clone, cfg_clone, samples_clone).await;
+            crate::tasks::telemetry::run(
+                ft_clone,
+                cfg_clone,
+                samples_clone,
+                #[cfg(target_os = "windows")]
+                ryz_clone,
+            )
+            .await;
         });
     }
 }
 
+/// Tasks that write to hardware (fans, power limits, charging); skipped in safe mode.
+fn boot_control_tasks(state: &AppState) {
+    // Fan curve task: always start; it will wait until framework_tool is available
+    {
+        let ft_clone = state.framework_tool.clone();
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::tasks::fan_curve::run(ft_clone, cfg_clone).await;
+        });
+    }
+
+    // Fan profile schedule: idles until `fan.schedule` is enabled
+    {
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::tasks::fan_schedule::run(cfg_clone).await;
+        });
+    }
+
+    // Curve auto-tuner: idles until `fan.auto_tune` is enabled in curve mode
+    {
+        let cfg_clone = state.config.clone();
+        let samples_clone = state.telemetry_samples.clone();
+        tokio::spawn(async move {
+            crate::fan_auto_tune::run(cfg_clone, samples_clone).await;
+        });
+    }
+
+    // Power settings task: start once at boot; it will wait until a power backend is available
+    {
+        let backends = state.power.clone();
+        let cfg_clone = state.config.clone();
+        let ft_clone = state.framework_tool.clone();
+        tokio::spawn(async move {
+            crate::tasks::power::run(backends, cfg_clone, ft_clone).await;
+        });
+    }
+
+    // CPU/dGPU power budget: idles until `power.budget` is enabled
+    {
+        let backends = state.power.clone();
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::tasks::power_budget::run(backends, cfg_clone).await;
+        });
+    }
+
+    // Battery settings task: applies charge limit and rate on change and periodically
+    {
+        let ft_clone = state.framework_tool.clone();
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::tasks::battery::run(ft_clone, cfg_clone).await;
+        });
+    }
+
+    // Keyboard backlight automation (ambient light + AC state)
+    {
+        let ft_clone = state.framework_tool.clone();
+        let cfg_clone = state.config.clone();
+        tokio::spawn(async move {
+            crate::tasks::kblight::run(ft_clone, cfg_clone).await;
+        });
+    }
+}
+
+async fn restore_ec_fan_control(framework_tool_lock: Arc<RwLock<Option<FrameworkTool>>>) {
+    loop {
+        let ft_opt = { framework_tool_lock.read().await.clone() };
+        if let Some(cli) = ft_opt {
+            match cli.autofanctrl().await {
+                Ok(()) => {
+                    crate::tasks::fan_restore::record_auto(&cli);
+                    info!("safe mode: restored EC automatic fan control");
+                }
+                Err(e) => warn!("safe mode: failed to restore EC fan control: {}", e),
+            }
+            return;
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+}
+
 pub mod auto_update;
+pub mod backup;
 pub mod battery;
+pub mod battery_sessions;
+pub mod fan_contention;
 pub mod fan_curve;
+pub mod fan_restore;
+pub mod fan_schedule;
+pub mod firmware;
+pub mod kblight;
 pub mod power;
+pub mod power_budget;
+pub mod reports;
+pub mod resume;
+pub mod sensors;
 pub mod telemetry;
@@ -1,46 +1,319 @@
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 
 use tokio::time::{sleep, Duration, Instant};
 use tracing::{debug, info, warn};
 
-use crate::cli::FrameworkTool;
-use crate::types::Config;
+use crate::cli::{FrameworkTool, PowerBackend, PowerBackends};
+use crate::dry_run::Plan;
+use crate::types::{
+    ApplyError, Config, ControlActionKind, ControlActionReason, FanControlMode, PowerBackendKind, PowerCapabilities,
+    PowerConfig, PowerProfile, PowerSource, PowerState, PowerStateReport, SchedulerHints, SettingBool, SettingString,
+    SettingU32,
+};
 use crate::utils::reconciler::{ReconcileOutcome, Reconciler, ReconcilerPolicy, SettingIo};
 
-#[cfg(target_os = "windows")]
-use crate::cli::RyzenAdj;
+const LOOP_INTERVAL_SECS: u64 = 1;
 
-#[cfg(target_os = "linux")]
-use crate::cli::LinuxPower;
+/// What the first selected backend reported before the task applied anything; `restore_stock`
+/// puts it back on shutdown.
+static BASELINE: Mutex<Option<(PowerBackendKind, PowerState)>> = Mutex::new(None);
+/// Last apply error per setting, cleared once an apply succeeds; reported in `/power/state`.
+static APPLY_ERRORS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// The profile in effect and where it lives in the config.
+pub struct ActiveProfile {
+    pub source: PowerSource,
+    /// `power.ac`, `power.chargers[i].profile` or `power.battery`
+    pub path: String,
+    /// Active charger's negotiated wattage; only read on AC with `power.chargers` configured
+    pub charger_watts: Option<f32>,
+    pub profile: Option<PowerProfile>,
+}
 
-const LOOP_INTERVAL_SECS: u64 = 1;
+/// `power.battery` off AC; on AC the first `power.chargers` tier matching `charger_watts`, else
+/// `power.ac`.
+fn select_profile(
+    power: &PowerConfig,
+    source: PowerSource,
+    charger_watts: Option<f32>,
+) -> (String, Option<&PowerProfile>) {
+    match source {
+        PowerSource::Battery => ("power.battery".into(), power.battery.as_ref()),
+        PowerSource::Ac => {
+            let tier =
+                charger_watts.and_then(|w| power.chargers.iter().flatten().enumerate().find(|(_, c)| c.matches(w)));
+            match tier {
+                Some((i, c)) => (format!("power.chargers[{}].profile", i), Some(&c.profile)),
+                None => ("power.ac".into(), power.ac.as_ref()),
+            }
+        }
+    }
+}
 
-async fn get_profile(
-    cfg: &Arc<tokio::sync::RwLock<Config>>,
+pub async fn active_profile(power: &PowerConfig, cli: &FrameworkTool) -> Option<ActiveProfile> {
+    let p = cli.power_with_fallback().await.ok()?;
+    let source = if p.ac_present? {
+        PowerSource::Ac
+    } else {
+        PowerSource::Battery
+    };
+    let has_tiers = power.chargers.as_ref().is_some_and(|c| !c.is_empty());
+    let charger_watts = if source == PowerSource::Ac && has_tiers {
+        cli.charger().await.ok().flatten().and_then(|c| c.negotiated_watts)
+    } else {
+        None
+    };
+    let (path, profile) = select_profile(power, source, charger_watts);
+    Some(ActiveProfile {
+        source,
+        path,
+        charger_watts,
+        profile: profile.cloned(),
+    })
+}
+
+async fn current_profile(
+    power: &PowerConfig,
     framework_tool_lock: &Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
-) -> Option<crate::types::PowerProfile> {
-    let Some(ft) = framework_tool_lock.read().await.clone() else {
-        return None;
+) -> Option<ActiveProfile> {
+    let cli = framework_tool_lock.read().await.clone()?;
+    active_profile(power, &cli).await
+}
+
+/// `profile` with the CPU TDP share of an active `power.budget` in place of its `tdp_watts` and
+/// individual power limits.
+fn with_budget(mut profile: PowerProfile) -> PowerProfile {
+    if let Some(w) = crate::tasks::power_budget::cpu_watts() {
+        profile.tdp_watts = Some(SettingU32 {
+            enabled: true,
+            value: w,
+        });
+        profile.stapm_watts = None;
+        profile.fast_limit_watts = None;
+        profile.slow_limit_watts = None;
+    }
+    profile
+}
+
+/// The values `run` reconciles towards: enabled settings of `profile` that `caps` supports.
+fn targets(profile: &PowerProfile, caps: &PowerCapabilities) -> PowerState {
+    let u32_target =
+        |s: Option<&SettingU32>, supported: bool| s.filter(|s| supported && s.enabled && s.value > 0).map(|s| s.value);
+    let bool_target = |s: Option<&SettingBool>, supported: bool| s.filter(|s| supported && s.enabled).map(|s| s.value);
+    let str_target = |s: Option<&SettingString>, supported: bool| {
+        s.filter(|s| supported && s.enabled && !s.value.trim().is_empty())
+            .map(|s| s.value.clone())
     };
+    // With any individual limit set, all three are targets and `tdp_watts` only fills the gaps
+    let split = caps.supports_tdp && profile.splits_tdp();
+    let [stapm, fast, slow] = if split { profile.power_limits() } else { [None; 3] };
+    PowerState {
+        tdp_limit_watts: u32_target(profile.tdp_watts.as_ref(), caps.supports_tdp && !split),
+        stapm_limit_watts: stapm,
+        fast_limit_watts: fast,
+        slow_limit_watts: slow,
+        thermal_limit_c: u32_target(profile.thermal_limit_c.as_ref(), caps.supports_thermal),
+        epp_preference: str_target(profile.epp_preference.as_ref(), caps.supports_epp),
+        governor: str_target(profile.governor.as_ref(), caps.supports_governor),
+        min_freq_mhz: u32_target(profile.min_freq_mhz.as_ref(), caps.supports_frequency_limits),
+        max_freq_mhz: u32_target(profile.max_freq_mhz.as_ref(), caps.supports_frequency_limits),
+        boost: bool_target(profile.boost.as_ref(), caps.supports_boost),
+        scheduler_hints: profile
+            .scheduler_hints
+            .as_ref()
+            .filter(|s| caps.supports_scheduler_hints && s.enabled && !s.value.is_empty())
+            .map(|s| s.value.clone()),
+    }
+}
+
+/// Names of the `target` fields the observed state does not match.
+fn drift(target: &PowerState, observed: &PowerState) -> Vec<String> {
+    fn differs<T: PartialEq>(t: &Option<T>, o: &Option<T>) -> bool {
+        t.is_some() && t != o
+    }
+    [
+        (
+            "tdp_limit_watts",
+            differs(&target.tdp_limit_watts, &observed.tdp_limit_watts),
+        ),
+        (
+            "stapm_limit_watts",
+            differs(&target.stapm_limit_watts, &observed.stapm_limit_watts),
+        ),
+        (
+            "fast_limit_watts",
+            differs(&target.fast_limit_watts, &observed.fast_limit_watts),
+        ),
+        (
+            "slow_limit_watts",
+            differs(&target.slow_limit_watts, &observed.slow_limit_watts),
+        ),
+        (
+            "thermal_limit_c",
+            differs(&target.thermal_limit_c, &observed.thermal_limit_c),
+        ),
+        (
+            "epp_preference",
+            differs(&target.epp_preference, &observed.epp_preference),
+        ),
+        ("governor", differs(&target.governor, &observed.governor)),
+        ("min_freq_mhz", differs(&target.min_freq_mhz, &observed.min_freq_mhz)),
+        ("max_freq_mhz", differs(&target.max_freq_mhz, &observed.max_freq_mhz)),
+        ("boost", differs(&target.boost, &observed.boost)),
+        (
+            "scheduler_hints",
+            target
+                .scheduler_hints
+                .as_ref()
+                .is_some_and(|t| !t.satisfied_by(observed.scheduler_hints.as_ref())),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, d)| *d)
+    .map(|(name, _)| name.to_string())
+    .collect()
+}
 
-    let cfg_power = { cfg.read().await.power.clone() };
+/// What the reconcilers compare against: the backend state, with the configured frequency
+/// limits in place of the live clocks, which move with load.
+async fn observe(backend: &dyn PowerBackend, caps: &PowerCapabilities) -> PowerState {
+    let mut observed = backend.state().await.unwrap_or_default();
+    if caps.supports_frequency_limits {
+        let limits = backend.frequency_limits().await.ok();
+        observed.min_freq_mhz = limits.map(|l| l.0);
+        observed.max_freq_mhz = limits.map(|l| l.1);
+    }
+    observed
+}
 
-    let Ok(p) = ft.power().await else {
-        return None;
+/// Dry run of a `power` write: the setter calls the reconcilers would make for the profile of
+/// the current power source, i.e. for every target the backend does not already report.
+pub async fn preview(
+    plan: &mut Plan,
+    backends: &PowerBackends,
+    power: &PowerConfig,
+    framework_tool_lock: &Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
+) {
+    let Some(backend) = backends.select(power.backend.unwrap_or_default()).await else {
+        return;
     };
-    let Some(ac_present) = p.ac_present else {
-        return None;
+    let Some(ActiveProfile {
+        path,
+        profile: Some(profile),
+        ..
+    }) = current_profile(power, framework_tool_lock).await
+    else {
+        return;
+    };
+    let profile = &with_budget(profile);
+    let caps = backend.capabilities().await;
+    let target = targets(profile, &caps);
+    let drifted = drift(&target, &observe(backend.as_ref(), &caps).await);
+    let drifted = |name: &str| drifted.iter().any(|d| d == name);
+    let b = backend.as_ref();
+
+    if let Some(w) = target.tdp_limit_watts.filter(|_| drifted("tdp_limit_watts")) {
+        plan.add(&format!("{}.tdp_watts", path), b.set_tdp_watts(w)).await;
+    }
+    if drifted("stapm_limit_watts") || drifted("fast_limit_watts") || drifted("slow_limit_watts") {
+        plan.add(
+            &format!("{}.stapm_watts/fast_limit_watts/slow_limit_watts", path),
+            b.set_power_limits(
+                target.stapm_limit_watts,
+                target.fast_limit_watts,
+                target.slow_limit_watts,
+            ),
+        )
+        .await;
+    }
+    if let Some(c) = target.thermal_limit_c.filter(|_| drifted("thermal_limit_c")) {
+        plan.add(&format!("{}.thermal_limit_c", path), b.set_thermal_limit_c(c))
+            .await;
+    }
+    if let Some(g) = target.governor.as_deref().filter(|_| drifted("governor")) {
+        plan.add(&format!("{}.governor", path), b.set_governor(g)).await;
+    }
+    if let Some(e) = target.epp_preference.as_deref().filter(|_| drifted("epp_preference")) {
+        plan.add(&format!("{}.epp_preference", path), b.set_epp_preference(e))
+            .await;
+    }
+    if drifted("min_freq_mhz") || drifted("max_freq_mhz") {
+        plan.add(
+            &format!("{}.min_freq_mhz/max_freq_mhz", path),
+            b.set_frequency_limits(target.min_freq_mhz, target.max_freq_mhz),
+        )
+        .await;
+    }
+    if let Some(on) = target.boost.filter(|_| drifted("boost")) {
+        plan.add(&format!("{}.boost", path), b.set_boost(on)).await;
+    }
+    if let Some(hints) = target.scheduler_hints.as_ref().filter(|_| drifted("scheduler_hints")) {
+        plan.add(&format!("{}.scheduler_hints", path), b.set_scheduler_hints(hints))
+            .await;
+    }
+}
+
+/// Targets of the active profile next to what the backend and fans report right now.
+pub async fn state_report(
+    backends: &PowerBackends,
+    cfg: &Arc<tokio::sync::RwLock<Config>>,
+    framework_tool_lock: &Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
+) -> PowerStateReport {
+    let (power, fan_mode) = {
+        let c = cfg.read().await;
+        (c.power.clone(), c.fan.mode.clone())
+    };
+    let active = current_profile(&power, framework_tool_lock).await;
+    let profile = active.as_ref().and_then(|a| a.profile.clone()).map(with_budget);
+
+    let backend = backends.select(power.backend.unwrap_or_default()).await;
+    let (target, observed) = match &backend {
+        Some(b) => {
+            let caps = b.capabilities().await;
+            let target = profile.as_ref().map(|p| targets(p, &caps)).unwrap_or_default();
+            (target, observe(b.as_ref(), &caps).await)
+        }
+        None => (PowerState::default(), PowerState::default()),
     };
 
-    let maybe_profile = if ac_present { cfg_power.ac } else { cfg_power.battery };
+    let fan_duty_pct = crate::tasks::fan_restore::last_duty();
+    let fan_drift = match fan_mode {
+        Some(FanControlMode::Disabled) => fan_duty_pct.is_some(),
+        Some(FanControlMode::Manual | FanControlMode::Curve | FanControlMode::Target) => fan_duty_pct.is_none(),
+        Some(FanControlMode::Rpm) => !crate::tasks::fan_restore::holds_rpm(),
+        None => false,
+    };
 
-    maybe_profile
+    PowerStateReport {
+        source: active.as_ref().map(|a| a.source),
+        profile: active.as_ref().map(|a| a.path.clone()),
+        charger_watts: active.as_ref().and_then(|a| a.charger_watts),
+        backend: backend.map(|b| b.kind()),
+        drift: drift(&target, &observed),
+        target,
+        observed,
+        fan_mode,
+        fan_duty_pct,
+        fan_drift,
+        apply_errors: APPLY_ERRORS
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(setting, message)| ApplyError {
+                setting: setting.clone(),
+                hint: crate::hints::for_error("apply_failed", message),
+                message: message.clone(),
+            })
+            .collect(),
+    }
 }
 
 fn log_outcome(setting: &str, target: &str, outcome: &ReconcileOutcome) {
     match outcome {
         ReconcileOutcome::ApplyFailed(e) => {
             warn!("power: {} apply failed (target={}): {}", setting, target, e);
+            APPLY_ERRORS.lock().unwrap().insert(setting.to_string(), e.clone());
         }
         ReconcileOutcome::Noop => {}
         ReconcileOutcome::Cooldown { remaining } => {
@@ -60,103 +333,134 @@ fn log_outcome(setting: &str, target: &str, outcome: &ReconcileOutcome) {
             );
         }
         other => {
+            if matches!(other, ReconcileOutcome::Applied | ReconcileOutcome::AppliedImmediate) {
+                APPLY_ERRORS.lock().unwrap().remove(setting);
+            }
             debug!("power: {} outcome={:?} (target={})", setting, other, target);
         }
     }
 }
 
-#[cfg(target_os = "windows")]
-struct WindowsTdpIo {
-    ryz: RyzenAdj,
+struct TdpIo<'b> {
+    backend: &'b dyn PowerBackend,
 }
 
-#[cfg(target_os = "windows")]
-impl SettingIo<u32> for WindowsTdpIo {
+impl SettingIo<u32> for TdpIo<'_> {
     fn read_current<'a>(
         &'a self,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<u32>, String>> + Send + 'a>> {
-        Box::pin(async move { Ok(self.ryz.info().await.ok().and_then(|i| i.tdp_watts)) })
+        Box::pin(async move { Ok(self.backend.state().await.ok().and_then(|s| s.tdp_limit_watts)) })
     }
 
     fn apply_target<'a>(
         &'a self,
         target: &'a u32,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
-        Box::pin(async move { self.ryz.set_tdp_watts(*target).await })
+        self.backend.set_tdp_watts(*target)
     }
 }
 
-#[cfg(target_os = "windows")]
-struct WindowsThermalIo {
-    ryz: RyzenAdj,
+struct PowerLimitsIo<'b> {
+    backend: &'b dyn PowerBackend,
+    target: PowerLimits,
 }
 
-#[cfg(target_os = "windows")]
-impl SettingIo<u32> for WindowsThermalIo {
+/// `(stapm, fast, slow)` watts; `None` is left alone.
+type PowerLimits = (Option<u32>, Option<u32>, Option<u32>);
+
+impl SettingIo<PowerLimits> for PowerLimitsIo<'_> {
+    fn read_current<'a>(
+        &'a self,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<PowerLimits>, String>> + Send + 'a>> {
+        let mask = (
+            self.target.0.is_some(),
+            self.target.1.is_some(),
+            self.target.2.is_some(),
+        );
+        Box::pin(async move {
+            let s = self.backend.state().await?;
+            if s.stapm_limit_watts.is_none() && s.fast_limit_watts.is_none() && s.slow_limit_watts.is_none() {
+                return Ok(None);
+            }
+            Ok(Some((
+                s.stapm_limit_watts.filter(|_| mask.0),
+                s.fast_limit_watts.filter(|_| mask.1),
+                s.slow_limit_watts.filter(|_| mask.2),
+            )))
+        })
+    }
+
+    fn apply_target<'a>(
+        &'a self,
+        target: &'a PowerLimits,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
+        self.backend.set_power_limits(target.0, target.1, target.2)
+    }
+}
+
+struct ThermalIo<'b> {
+    backend: &'b dyn PowerBackend,
+}
+
+impl SettingIo<u32> for ThermalIo<'_> {
     fn read_current<'a>(
         &'a self,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<u32>, String>> + Send + 'a>> {
-        Box::pin(async move { Ok(self.ryz.info().await.ok().and_then(|i| i.thermal_limit_c)) })
+        Box::pin(async move { Ok(self.backend.state().await.ok().and_then(|s| s.thermal_limit_c)) })
     }
 
     fn apply_target<'a>(
         &'a self,
         target: &'a u32,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
-        Box::pin(async move { self.ryz.set_thermal_limit_c(*target).await })
+        self.backend.set_thermal_limit_c(*target)
     }
 }
 
-#[cfg(target_os = "linux")]
-struct LinuxGovernorIo {
-    lp: LinuxPower,
+struct GovernorIo<'b> {
+    backend: &'b dyn PowerBackend,
 }
 
-#[cfg(target_os = "linux")]
-impl SettingIo<String> for LinuxGovernorIo {
+impl SettingIo<String> for GovernorIo<'_> {
     fn read_current<'a>(
         &'a self,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<String>, String>> + Send + 'a>> {
-        Box::pin(async move { self.lp.get_state().await.map(|s| s.governor).map_err(|e| e) })
+        Box::pin(async move { self.backend.state().await.map(|s| s.governor) })
     }
 
     fn apply_target<'a>(
         &'a self,
         target: &'a String,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
-        Box::pin(async move { self.lp.set_governor(target).await })
+        self.backend.set_governor(target)
     }
 }
 
-#[cfg(target_os = "linux")]
-struct LinuxEppIo {
-    lp: LinuxPower,
+struct EppIo<'b> {
+    backend: &'b dyn PowerBackend,
 }
 
-#[cfg(target_os = "linux")]
-impl SettingIo<String> for LinuxEppIo {
+impl SettingIo<String> for EppIo<'_> {
     fn read_current<'a>(
         &'a self,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<String>, String>> + Send + 'a>> {
-        Box::pin(async move { self.lp.get_state().await.map(|s| s.epp_preference).map_err(|e| e) })
+        Box::pin(async move { self.backend.state().await.map(|s| s.epp_preference) })
     }
 
     fn apply_target<'a>(
         &'a self,
         target: &'a String,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
-        Box::pin(async move { self.lp.set_epp_preference(target).await })
+        self.backend.set_epp_preference(target)
     }
 }
 
-#[cfg(target_os = "linux")]
-struct LinuxFreqLimitsIo {
-    lp: LinuxPower,
+struct FreqLimitsIo<'b> {
+    backend: &'b dyn PowerBackend,
     target: (Option<u32>, Option<u32>),
 }
 
-#[cfg(target_os = "linux")]
-impl SettingIo<(Option<u32>, Option<u32>)> for LinuxFreqLimitsIo {
+impl SettingIo<(Option<u32>, Option<u32>)> for FreqLimitsIo<'_> {
     fn read_current<'a>(
         &'a self,
     ) -> std::pin::Pin<
@@ -164,7 +468,7 @@ impl SettingIo<(Option<u32>, Option<u32>)> for LinuxFreqLimitsIo {
     > {
         let mask = (self.target.0.is_some(), self.target.1.is_some());
         Box::pin(async move {
-            let (cur_min, cur_max) = self.lp.get_configured_frequency_limits().await?;
+            let (cur_min, cur_max) = self.backend.frequency_limits().await?;
             Ok(Some((
                 if mask.0 { Some(cur_min) } else { None },
                 if mask.1 { Some(cur_max) } else { None },
@@ -176,109 +480,495 @@ impl SettingIo<(Option<u32>, Option<u32>)> for LinuxFreqLimitsIo {
         &'a self,
         target: &'a (Option<u32>, Option<u32>),
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
-        Box::pin(async move { self.lp.set_freq_limits(target.0, target.1).await })
+        self.backend.set_frequency_limits(target.0, target.1)
     }
 }
 
-#[cfg(target_os = "windows")]
-pub async fn run(
-    power_backend_lock: Arc<tokio::sync::RwLock<Option<RyzenAdj>>>,
-    cfg: Arc<tokio::sync::RwLock<Config>>,
-    framework_tool_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
-) {
-    info!("Power task started (Windows/RyzenAdj)");
-
-    let now = Instant::now();
-
-    let mut tdp = Reconciler::new(ReconcilerPolicy::default(), now);
+struct BoostIo<'b> {
+    backend: &'b dyn PowerBackend,
+}
 
-    let mut thermal = Reconciler::new(ReconcilerPolicy::default(), now);
+impl SettingIo<bool> for BoostIo<'_> {
+    fn read_current<'a>(
+        &'a self,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<bool>, String>> + Send + 'a>> {
+        Box::pin(async move { self.backend.state().await.map(|s| s.boost) })
+    }
 
-    loop {
-        let Some(ryz) = power_backend_lock.read().await.clone() else {
-            sleep(Duration::from_secs(LOOP_INTERVAL_SECS)).await;
-            continue;
-        };
+    fn apply_target<'a>(
+        &'a self,
+        target: &'a bool,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
+        self.backend.set_boost(*target)
+    }
+}
 
-        let Some(profile) = get_profile(&cfg, &framework_tool_lock).await else {
-            sleep(Duration::from_secs(LOOP_INTERVAL_SECS)).await;
-            continue;
-        };
+struct SchedulerHintsIo<'b> {
+    backend: &'b dyn PowerBackend,
+    target: SchedulerHints,
+}
 
-        if let Some(setting) = profile.tdp_watts.as_ref() {
-            let enabled = setting.enabled && setting.value > 0;
-            let io = WindowsTdpIo { ryz: ryz.clone() };
-            let outcome = tdp.reconcile(enabled, Some(setting.value), &io).await;
-            if let crate::utils::reconciler::ReconcileOutcome::ApplyFailed(e) = outcome {
-                warn!("power: tdp apply failed: {}", e);
-            }
-        }
+impl SettingIo<SchedulerHints> for SchedulerHintsIo<'_> {
+    fn read_current<'a>(
+        &'a self,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<SchedulerHints>, String>> + Send + 'a>> {
+        Box::pin(async move {
+            let observed = self.backend.state().await?.scheduler_hints;
+            Ok(observed.map(|o| mask_hints(&self.target, o)))
+        })
+    }
 
-        if let Some(setting) = profile.thermal_limit_c.as_ref() {
-            let enabled = setting.enabled && setting.value > 0;
-            let io = WindowsThermalIo { ryz: ryz.clone() };
-            let outcome = thermal.reconcile(enabled, Some(setting.value), &io).await;
-            if let crate::utils::reconciler::ReconcileOutcome::ApplyFailed(e) = outcome {
-                warn!("power: thermal apply failed: {}", e);
-            }
-        }
+    fn apply_target<'a>(
+        &'a self,
+        target: &'a SchedulerHints,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
+        self.backend.set_scheduler_hints(target)
+    }
+}
 
-        sleep(Duration::from_secs(LOOP_INTERVAL_SECS)).await;
+/// `observed` with only the fields `target` sets, so unmanaged settings never count as drift.
+fn mask_hints(target: &SchedulerHints, observed: SchedulerHints) -> SchedulerHints {
+    let keep = |t: Option<u32>, o: Option<u32>| t.and(o);
+    SchedulerHints {
+        heterogeneous_policy: keep(target.heterogeneous_policy, observed.heterogeneous_policy),
+        processor_min_pct: keep(target.processor_min_pct, observed.processor_min_pct),
+        processor_max_pct: keep(target.processor_max_pct, observed.processor_max_pct),
+        standby_timeout_min: keep(target.standby_timeout_min, observed.standby_timeout_min),
     }
 }
 
-#[cfg(target_os = "linux")]
+/// Applies the AC/battery power profile through whichever backend `power.backend` selects,
+/// reconciling only the settings that backend reports as supported.
 pub async fn run(
-    power_backend_lock: Arc<tokio::sync::RwLock<Option<LinuxPower>>>,
+    backends: PowerBackends,
     cfg: Arc<tokio::sync::RwLock<Config>>,
     framework_tool_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
 ) {
-    info!("Power task started (Linux native)");
+    info!("Power task started");
 
     let now = Instant::now();
 
+    let mut tdp = Reconciler::new(ReconcilerPolicy::default(), now);
+    let mut limits = Reconciler::new(ReconcilerPolicy::default(), now);
+    let mut thermal = Reconciler::new(ReconcilerPolicy::default(), now);
     let mut governor = Reconciler::new(ReconcilerPolicy::default(), now);
     let mut epp = Reconciler::new(ReconcilerPolicy::default(), now);
     let mut freq_limits = Reconciler::new(ReconcilerPolicy::default(), now);
+    let mut boost = Reconciler::new(ReconcilerPolicy::default(), now);
+    let mut scheduler_hints = Reconciler::new(ReconcilerPolicy::default(), now);
+    let mut resume_generation = crate::tasks::resume::generation();
+    let mut active_kind = None;
+    let mut active_path = None;
+    let mut budget_tdp = false;
 
     loop {
-        let Some(lp) = power_backend_lock.read().await.clone() else {
+        let choice = { cfg.read().await.power.backend.unwrap_or_default() };
+        let Some(backend) = backends.select(choice).await else {
             sleep(Duration::from_secs(LOOP_INTERVAL_SECS)).await;
             continue;
         };
+        let loop_started = std::time::Instant::now();
+
+        // Firmware resets STAPM/thermal limits and cpufreq policies can come back reset across
+        // sleep and hibernate; a newly selected backend starts from whatever the last one left
+        let generation = crate::tasks::resume::generation();
+        if generation != resume_generation || active_kind != Some(backend.kind()) {
+            if active_kind != Some(backend.kind()) {
+                info!("power: using {:?} backend", backend.kind());
+                if active_kind.is_none() {
+                    let observed = backend.state().await.unwrap_or_default();
+                    if let Ok(mut baseline) = BASELINE.lock() {
+                        *baseline = Some((backend.kind(), observed));
+                    }
+                }
+                active_kind = Some(backend.kind());
+            }
+            resume_generation = generation;
+            tdp.force_reapply();
+            limits.force_reapply();
+            thermal.force_reapply();
+            governor.force_reapply();
+            epp.force_reapply();
+            freq_limits.force_reapply();
+            boost.force_reapply();
+            scheduler_hints.force_reapply();
+        }
 
-        let Some(profile) = get_profile(&cfg, &framework_tool_lock).await else {
+        let power = { cfg.read().await.power.clone() };
+        let Some(active) = current_profile(&power, &framework_tool_lock).await else {
             sleep(Duration::from_secs(LOOP_INTERVAL_SECS)).await;
             continue;
         };
+        if active_path.as_ref() != Some(&active.path) {
+            match active.charger_watts {
+                Some(w) => info!("power: {} in effect ({:.0} W charger)", active.path, w),
+                None => info!("power: {} in effect", active.path),
+            }
+            active_path = Some(active.path);
+        }
+        let Some(profile) = active.profile.map(with_budget) else {
+            sleep(Duration::from_secs(LOOP_INTERVAL_SECS)).await;
+            continue;
+        };
+        let caps = backend.capabilities().await;
+        let backend = backend.as_ref();
+
+        // The budget let go of a TDP the profile does not set: back to the pre-service value
+        let budgeted = crate::tasks::power_budget::cpu_watts().is_some();
+        if budget_tdp && !budgeted && !profile.tdp_watts.as_ref().is_some_and(|s| s.enabled) && !profile.splits_tdp() {
+            let baseline = BASELINE.lock().ok().and_then(|b| b.clone());
+            if let Some(w) = baseline
+                .filter(|(kind, _)| *kind == backend.kind())
+                .and_then(|(_, state)| state.tdp_limit_watts)
+            {
+                info!("power: restoring the {} W TDP after the power budget", w);
+                match backend.set_tdp_watts(w).await {
+                    Ok(()) => crate::actions::record(ControlActionKind::Tdp, None, w, ControlActionReason::Restore),
+                    Err(e) => warn!("power: failed to restore TDP: {}", e),
+                }
+            }
+        }
+        budget_tdp = budgeted;
+
+        // Individual limits take over from `tdp_watts`; disabling the TDP reconciler meanwhile
+        // makes it apply again as soon as they are turned off
+        let split = caps.supports_tdp && profile.splits_tdp();
+        if let Some(setting) = profile.tdp_watts.as_ref().filter(|_| caps.supports_tdp) {
+            let enabled = setting.enabled && setting.value > 0 && !split;
+            let outcome = tdp.reconcile(enabled, Some(setting.value), &TdpIo { backend }).await;
+            log_outcome("tdp", &format!("{} W", setting.value), &outcome);
+            if matches!(outcome, ReconcileOutcome::Applied | ReconcileOutcome::AppliedImmediate) {
+                let reason = if budgeted {
+                    ControlActionReason::Budget
+                } else {
+                    ControlActionReason::Profile
+                };
+                crate::actions::record(ControlActionKind::Tdp, None, setting.value, reason);
+            }
+        }
+
+        if caps.supports_tdp {
+            let [stapm, fast, slow] = profile.power_limits();
+            let target = (stapm, fast, slow);
+            let io = PowerLimitsIo { backend, target };
+            let outcome = limits.reconcile(split, Some(target), &io).await;
+            log_outcome(
+                "power limits",
+                &format!("{:?}/{:?}/{:?} W", target.0, target.1, target.2),
+                &outcome,
+            );
+            if let Some(w) =
+                stapm.filter(|_| matches!(outcome, ReconcileOutcome::Applied | ReconcileOutcome::AppliedImmediate))
+            {
+                crate::actions::record(ControlActionKind::Tdp, None, w, ControlActionReason::Profile);
+            }
+        }
 
-        if let Some(setting) = profile.governor.as_ref() {
+        if let Some(setting) = profile.thermal_limit_c.as_ref().filter(|_| caps.supports_thermal) {
+            let enabled = setting.enabled && setting.value > 0;
+            let outcome = thermal
+                .reconcile(enabled, Some(setting.value), &ThermalIo { backend })
+                .await;
+            log_outcome("thermal", &format!("{} °C", setting.value), &outcome);
+        }
+
+        if let Some(setting) = profile.governor.as_ref().filter(|_| caps.supports_governor) {
             let enabled = setting.enabled && !setting.value.trim().is_empty();
-            let io = LinuxGovernorIo { lp: lp.clone() };
-            let outcome = governor.reconcile(enabled, Some(setting.value.clone()), &io).await;
+            let outcome = governor
+                .reconcile(enabled, Some(setting.value.clone()), &GovernorIo { backend })
+                .await;
             log_outcome("governor", &format!("'{}'", setting.value), &outcome);
         }
 
-        if let Some(setting) = profile.epp_preference.as_ref() {
+        if let Some(setting) = profile.epp_preference.as_ref().filter(|_| caps.supports_epp) {
             let enabled = setting.enabled && !setting.value.trim().is_empty();
-            let io = LinuxEppIo { lp: lp.clone() };
-            let outcome = epp.reconcile(enabled, Some(setting.value.clone()), &io).await;
+            let outcome = epp
+                .reconcile(enabled, Some(setting.value.clone()), &EppIo { backend })
+                .await;
             log_outcome("epp", &format!("'{}'", setting.value), &outcome);
         }
 
         let min_setting = profile.min_freq_mhz.as_ref();
         let max_setting = profile.max_freq_mhz.as_ref();
-        if min_setting.is_some() || max_setting.is_some() {
+        if caps.supports_frequency_limits && (min_setting.is_some() || max_setting.is_some()) {
             let target = (
                 min_setting.filter(|s| s.enabled && s.value > 0).map(|s| s.value),
                 max_setting.filter(|s| s.enabled && s.value > 0).map(|s| s.value),
             );
             let enabled = target.0.is_some() || target.1.is_some();
-            let io = LinuxFreqLimitsIo { lp: lp.clone(), target };
+            let io = FreqLimitsIo { backend, target };
             let outcome = freq_limits.reconcile(enabled, Some(target), &io).await;
             log_outcome("freq limits", &format!("{:?}-{:?} MHz", target.0, target.1), &outcome);
         }
 
+        if let Some(setting) = profile.boost.as_ref().filter(|_| caps.supports_boost) {
+            let outcome = boost
+                .reconcile(setting.enabled, Some(setting.value), &BoostIo { backend })
+                .await;
+            log_outcome("boost", if setting.value { "on" } else { "off" }, &outcome);
+        }
+
+        if let Some(setting) = profile
+            .scheduler_hints
+            .as_ref()
+            .filter(|_| caps.supports_scheduler_hints)
+        {
+            let enabled = setting.enabled && !setting.value.is_empty();
+            let io = SchedulerHintsIo {
+                backend,
+                target: setting.value.clone(),
+            };
+            let outcome = scheduler_hints
+                .reconcile(enabled, Some(setting.value.clone()), &io)
+                .await;
+            log_outcome("scheduler hints", &format!("{:?}", setting.value), &outcome);
+        }
+
+        crate::metrics::record_task_loop("power", loop_started.elapsed());
         sleep(Duration::from_secs(LOOP_INTERVAL_SECS)).await;
     }
 }
+
+/// Put the managed settings back to the pre-service baseline. Frequency limits go back to the
+/// hardware range rather than the baseline, which may itself be a leftover from a previous run.
+pub async fn restore_stock(backends: &PowerBackends, cfg: &Config) {
+    let Some((kind, baseline)) = BASELINE.lock().ok().and_then(|b| b.clone()) else {
+        return;
+    };
+    let Some(backend) = backends.select(cfg.power.backend.unwrap_or_default()).await else {
+        return;
+    };
+    if backend.kind() != kind {
+        return;
+    }
+    let caps = backend.capabilities().await;
+    // Only settings some profile manages; anything else was never touched
+    let tiers = cfg.power.chargers.iter().flatten().map(|c| &c.profile);
+    let profile_targets: Vec<PowerState> = [&cfg.power.ac, &cfg.power.battery]
+        .into_iter()
+        .flatten()
+        .chain(tiers)
+        .map(|p| targets(p, &caps))
+        .collect();
+    let managed = |f: fn(&PowerState) -> bool| profile_targets.iter().any(f);
+
+    let budgeted = cfg.power.budget.as_ref().is_some_and(|b| b.enabled);
+
+    let mut results = Vec::new();
+    if let Some(v) = baseline
+        .tdp_limit_watts
+        .filter(|_| budgeted || managed(|t| t.tdp_limit_watts.is_some()))
+    {
+        results.push(("tdp", backend.set_tdp_watts(v).await));
+    }
+    let split_managed =
+        managed(|t| t.stapm_limit_watts.is_some() || t.fast_limit_watts.is_some() || t.slow_limit_watts.is_some());
+    if split_managed {
+        results.push((
+            "power limits",
+            backend
+                .set_power_limits(
+                    baseline.stapm_limit_watts,
+                    baseline.fast_limit_watts,
+                    baseline.slow_limit_watts,
+                )
+                .await,
+        ));
+    }
+    if let Some(v) = baseline
+        .thermal_limit_c
+        .filter(|_| managed(|t| t.thermal_limit_c.is_some()))
+    {
+        results.push(("thermal", backend.set_thermal_limit_c(v).await));
+    }
+    if let Some(v) = baseline.governor.filter(|_| managed(|t| t.governor.is_some())) {
+        results.push(("governor", backend.set_governor(&v).await));
+    }
+    // After the governor: EPP writes are rejected under 'performance'
+    if let Some(v) = baseline
+        .epp_preference
+        .filter(|_| managed(|t| t.epp_preference.is_some()))
+    {
+        results.push(("epp", backend.set_epp_preference(&v).await));
+    }
+    if managed(|t| t.min_freq_mhz.is_some() || t.max_freq_mhz.is_some()) {
+        results.push(("freq limits", backend.set_frequency_limits(None, None).await));
+    }
+    if let Some(v) = baseline.boost.filter(|_| managed(|t| t.boost.is_some())) {
+        results.push(("boost", backend.set_boost(v).await));
+    }
+    // Back to the baseline value of each field some profile sets
+    if let Some(stock) = baseline.scheduler_hints {
+        let managed_fields = profile_targets.iter().filter_map(|t| t.scheduler_hints.as_ref()).fold(
+            SchedulerHints::default(),
+            |acc, t| SchedulerHints {
+                heterogeneous_policy: acc.heterogeneous_policy.or(t.heterogeneous_policy),
+                processor_min_pct: acc.processor_min_pct.or(t.processor_min_pct),
+                processor_max_pct: acc.processor_max_pct.or(t.processor_max_pct),
+                standby_timeout_min: acc.standby_timeout_min.or(t.standby_timeout_min),
+            },
+        );
+        let restore = mask_hints(&managed_fields, stock);
+        if !restore.is_empty() {
+            results.push(("scheduler hints", backend.set_scheduler_hints(&restore).await));
+        }
+    }
+    for (setting, result) in results {
+        match result {
+            Ok(()) => info!("power: restored stock {}", setting),
+            Err(e) => warn!("power: failed to restore stock {}: {}", setting, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChargerProfile;
+
+    #[test]
+    fn drift_only_covers_supported_enabled_targets() {
+        let profile = PowerProfile {
+            tdp_watts: Some(SettingU32 {
+                enabled: true,
+                value: 30,
+            }),
+            governor: Some(SettingString {
+                enabled: false,
+                value: "powersave".into(),
+            }),
+            max_freq_mhz: Some(SettingU32 {
+                enabled: true,
+                value: 3000,
+            }),
+            ..Default::default()
+        };
+        let caps = PowerCapabilities {
+            supports_governor: true,
+            supports_frequency_limits: true,
+            ..Default::default()
+        };
+        let target = targets(&profile, &caps);
+        assert_eq!(target.tdp_limit_watts, None);
+        assert_eq!(target.governor, None);
+        assert_eq!(target.max_freq_mhz, Some(3000));
+
+        let observed = PowerState {
+            governor: Some("performance".into()),
+            min_freq_mhz: Some(400),
+            max_freq_mhz: Some(5100),
+            ..Default::default()
+        };
+        assert_eq!(drift(&target, &observed), vec!["max_freq_mhz"]);
+        let observed = PowerState {
+            max_freq_mhz: Some(3000),
+            ..observed
+        };
+        assert!(drift(&target, &observed).is_empty());
+    }
+
+    #[test]
+    fn individual_limits_fall_back_to_tdp() {
+        let setting = |value| Some(SettingU32 { enabled: true, value });
+        let mut profile = PowerProfile {
+            tdp_watts: setting(30),
+            fast_limit_watts: setting(45),
+            ..Default::default()
+        };
+        let caps = PowerCapabilities {
+            supports_tdp: true,
+            ..Default::default()
+        };
+        let target = targets(&profile, &caps);
+        assert_eq!(target.tdp_limit_watts, None);
+        assert_eq!(
+            (
+                target.stapm_limit_watts,
+                target.fast_limit_watts,
+                target.slow_limit_watts
+            ),
+            (Some(30), Some(45), Some(30))
+        );
+
+        let observed = PowerState {
+            tdp_limit_watts: Some(30),
+            stapm_limit_watts: Some(30),
+            fast_limit_watts: Some(30),
+            slow_limit_watts: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(drift(&target, &observed), vec!["fast_limit_watts"]);
+
+        profile.fast_limit_watts.as_mut().unwrap().enabled = false;
+        let target = targets(&profile, &caps);
+        assert_eq!(target.tdp_limit_watts, Some(30));
+        assert_eq!(target.fast_limit_watts, None);
+    }
+
+    #[test]
+    fn scheduler_hints_only_compare_set_fields() {
+        let target = SchedulerHints {
+            heterogeneous_policy: Some(4),
+            processor_max_pct: Some(80),
+            ..Default::default()
+        };
+        let observed = SchedulerHints {
+            heterogeneous_policy: Some(4),
+            processor_min_pct: Some(5),
+            processor_max_pct: Some(100),
+            standby_timeout_min: Some(15),
+        };
+        assert!(!target.satisfied_by(Some(&observed)));
+        assert!(!target.satisfied_by(None));
+        let masked = mask_hints(&target, observed.clone());
+        assert_eq!(masked.processor_min_pct, None);
+        assert_eq!(masked.processor_max_pct, Some(100));
+
+        let target_state = PowerState {
+            scheduler_hints: Some(target),
+            ..Default::default()
+        };
+        let observed = PowerState {
+            scheduler_hints: Some(SchedulerHints {
+                processor_max_pct: Some(80),
+                ..observed
+            }),
+            ..Default::default()
+        };
+        assert!(drift(&target_state, &observed).is_empty());
+    }
+
+    #[test]
+    fn charger_tiers_replace_ac_profile() {
+        let tier = |min_watts, max_watts, tdp| ChargerProfile {
+            min_watts,
+            max_watts,
+            profile: PowerProfile {
+                tdp_watts: Some(SettingU32 {
+                    enabled: true,
+                    value: tdp,
+                }),
+                ..Default::default()
+            },
+        };
+        let power = PowerConfig {
+            ac: Some(PowerProfile::default()),
+            chargers: Some(vec![tier(Some(100), None, 54), tier(None, Some(45), 15)]),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_profile(&power, PowerSource::Ac, Some(140.0)).0,
+            "power.chargers[0].profile"
+        );
+        assert_eq!(
+            select_profile(&power, PowerSource::Ac, Some(30.0)).0,
+            "power.chargers[1].profile"
+        );
+        assert_eq!(select_profile(&power, PowerSource::Ac, Some(65.0)).0, "power.ac");
+        assert_eq!(select_profile(&power, PowerSource::Ac, None).0, "power.ac");
+        assert_eq!(
+            select_profile(&power, PowerSource::Battery, Some(140.0)).0,
+            "power.battery"
+        );
+    }
+}
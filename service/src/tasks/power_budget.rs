@@ -0,0 +1,250 @@
+// CPU/dGPU power budget (Framework 16): `power.budget.total_watts` is shared between the CPU TDP
+// and the dGPU power cap, re-split every `interval_s` in proportion to their smoothed utilization,
+// each side kept between its floor and its hardware maximum. Headroom one side cannot use goes
+// to the other. The dGPU cap is written here through the amdgpu hwmon (Linux only); the CPU share
+// is handed to the power task, which applies it in place of the active profile's `tdp_watts`.
+// When the budget is turned off the dGPU cap goes back to its driver default and the power task
+// back to the profile.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use sysinfo::System;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::cli::PowerBackends;
+use crate::types::{Config, PowerBudgetConfig, PowerBudgetStatus, PowerConfig};
+
+const DEFAULT_MIN_CPU_WATTS: u32 = 15;
+const DEFAULT_MIN_GPU_WATTS: u32 = 30;
+const DEFAULT_INTERVAL_S: u32 = 5;
+/// Utilization below this counts as this, so an idle side keeps a share
+const LOAD_FLOOR_PCT: f32 = 5.0;
+/// Weight of the newest utilization reading
+const LOAD_ALPHA: f32 = 0.5;
+/// Smaller reassignments are skipped
+const MIN_STEP_WATTS: u32 = 2;
+
+/// CPU TDP the power task should apply; 0 while the budget is not active
+static CPU_WATTS: AtomicU32 = AtomicU32::new(0);
+static STATUS: Mutex<Option<PowerBudgetStatus>> = Mutex::new(None);
+
+/// CPU TDP set by the budget, overriding the active profile's `tdp_watts`.
+pub fn cpu_watts() -> Option<u32> {
+    Some(CPU_WATTS.load(Ordering::Relaxed)).filter(|w| *w > 0)
+}
+
+pub fn status() -> PowerBudgetStatus {
+    STATUS.lock().ok().and_then(|s| s.clone()).unwrap_or_default()
+}
+
+/// `(cpu_watts, gpu_watts)` for `total` split by load, within each side's `(min, max)`.
+fn split(total: u32, cpu_load: f32, gpu_load: f32, cpu: (u32, u32), gpu: (u32, u32)) -> (u32, u32) {
+    let c = cpu_load.max(LOAD_FLOOR_PCT);
+    let g = gpu_load.max(LOAD_FLOOR_PCT);
+    let share = (total as f32 * c / (c + g)).round() as u32;
+    let cpu_w = share.clamp(cpu.0.min(cpu.1), cpu.1);
+    let gpu_w = total.saturating_sub(cpu_w).clamp(gpu.0.min(gpu.1), gpu.1);
+    // What the GPU cannot take goes back to the CPU
+    let cpu_w = total.saturating_sub(gpu_w).clamp(cpu.0.min(cpu.1), cpu.1);
+    (cpu_w, gpu_w)
+}
+
+struct GpuReading {
+    busy_pct: Option<u32>,
+    cap_watts: Option<u32>,
+    range_watts: (u32, u32),
+}
+
+#[cfg(target_os = "linux")]
+async fn gpu_read() -> Result<GpuReading, String> {
+    let gpu = crate::cli::amdgpu_power::AmdGpuPower::detect()
+        .await
+        .ok_or("no dGPU with a power cap found (amdgpu power1_cap)")?;
+    Ok(GpuReading {
+        busy_pct: gpu.busy_pct().await,
+        cap_watts: gpu.cap_watts().await,
+        range_watts: gpu.cap_range_watts().await.ok_or("dGPU power cap range not reported")?,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn gpu_read() -> Result<GpuReading, String> {
+    Err("setting the dGPU power cap is only supported on Linux (amdgpu)".into())
+}
+
+#[cfg(target_os = "linux")]
+async fn gpu_set_cap(watts: u32) -> Result<(), String> {
+    let gpu = crate::cli::amdgpu_power::AmdGpuPower::detect()
+        .await
+        .ok_or("dGPU power cap no longer found")?;
+    gpu.set_cap_watts(watts).await
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn gpu_set_cap(_watts: u32) -> Result<(), String> {
+    Err("setting the dGPU power cap is only supported on Linux (amdgpu)".into())
+}
+
+/// Stop overriding the CPU TDP and put the dGPU cap back to its driver default.
+pub async fn release() {
+    if CPU_WATTS.swap(0, Ordering::Relaxed) == 0 {
+        return;
+    }
+    info!("power budget: released; CPU TDP back to the power profile");
+    #[cfg(target_os = "linux")]
+    if let Some(gpu) = crate::cli::amdgpu_power::AmdGpuPower::detect().await {
+        if let Some(w) = gpu.default_cap_watts().await {
+            if let Err(e) = gpu.set_cap_watts(w).await {
+                warn!("power budget: failed to restore the dGPU power cap: {}", e);
+            }
+        }
+    }
+}
+
+struct Loads {
+    sys: System,
+    smoothed: Option<(f32, f32)>,
+}
+
+impl Loads {
+    fn update(&mut self, gpu_busy: f32) -> (f32, f32) {
+        self.sys.refresh_cpu_usage();
+        let cpu = self.sys.global_cpu_info().cpu_usage();
+        let next = match self.smoothed {
+            Some((c, g)) => (c + LOAD_ALPHA * (cpu - c), g + LOAD_ALPHA * (gpu_busy - g)),
+            None => (cpu, gpu_busy),
+        };
+        self.smoothed = Some(next);
+        next
+    }
+}
+
+async fn tick(
+    backends: &PowerBackends,
+    power: &PowerConfig,
+    budget: &PowerBudgetConfig,
+    loads: &mut Loads,
+) -> Result<PowerBudgetStatus, String> {
+    let backend = backends
+        .select(power.backend.unwrap_or_default())
+        .await
+        .ok_or("no power backend available")?;
+    let caps = backend.capabilities().await;
+    if !caps.supports_tdp {
+        return Err("the power backend cannot set the CPU TDP".into());
+    }
+    let gpu = gpu_read().await?;
+    let (cpu_load, gpu_load) = loads.update(gpu.busy_pct.unwrap_or(0) as f32);
+
+    let cpu_range = (
+        budget
+            .min_cpu_watts
+            .unwrap_or(DEFAULT_MIN_CPU_WATTS)
+            .max(caps.tdp_min_watts.unwrap_or(0)),
+        caps.tdp_max_watts.unwrap_or(budget.total_watts),
+    );
+    let gpu_range = (
+        budget
+            .min_gpu_watts
+            .unwrap_or(DEFAULT_MIN_GPU_WATTS)
+            .max(gpu.range_watts.0),
+        gpu.range_watts.1,
+    );
+    let (mut cpu_w, mut gpu_w) = split(budget.total_watts, cpu_load, gpu_load, cpu_range, gpu_range);
+
+    // Skip small moves; both sides change together so the total holds
+    let current_cpu = cpu_watts();
+    if let (Some(c), Some(g)) = (current_cpu, gpu.cap_watts) {
+        if cpu_w.abs_diff(c) < MIN_STEP_WATTS && gpu_w.abs_diff(g) < MIN_STEP_WATTS {
+            (cpu_w, gpu_w) = (c, g);
+        }
+    }
+    if gpu.cap_watts != Some(gpu_w) {
+        gpu_set_cap(gpu_w).await?;
+    }
+    // The power task applies the CPU share on its next pass (within a second)
+    if current_cpu != Some(cpu_w) {
+        if current_cpu.is_none() {
+            info!(
+                "power budget: sharing {} W (CPU {} W, dGPU {} W)",
+                budget.total_watts, cpu_w, gpu_w
+            );
+        }
+        CPU_WATTS.store(cpu_w, Ordering::Relaxed);
+    }
+    Ok(PowerBudgetStatus {
+        active: true,
+        reason: None,
+        total_watts: Some(budget.total_watts),
+        cpu_watts: Some(cpu_w),
+        gpu_watts: Some(gpu_w),
+        cpu_load_pct: Some(cpu_load),
+        gpu_load_pct: Some(gpu_load),
+        updated_ms: Some(chrono::Utc::now().timestamp_millis()),
+    })
+}
+
+pub async fn run(backends: PowerBackends, cfg: Arc<tokio::sync::RwLock<Config>>) {
+    let mut loads = Loads {
+        sys: System::new(),
+        smoothed: None,
+    };
+    let mut last_reason: Option<String> = None;
+    loop {
+        let started = std::time::Instant::now();
+        let power = { cfg.read().await.power.clone() };
+        let budget = power.budget.clone().filter(|b| b.enabled);
+        let interval = budget.as_ref().and_then(|b| b.interval_s).unwrap_or(DEFAULT_INTERVAL_S);
+        let next = match &budget {
+            None => {
+                release().await;
+                loads.smoothed = None;
+                last_reason = None;
+                PowerBudgetStatus::default()
+            }
+            Some(b) => match tick(&backends, &power, b, &mut loads).await {
+                Ok(status) => {
+                    last_reason = None;
+                    status
+                }
+                Err(reason) => {
+                    if last_reason.as_ref() != Some(&reason) {
+                        warn!("power budget: inactive: {}", reason);
+                    }
+                    release().await;
+                    loads.smoothed = None;
+                    last_reason = Some(reason.clone());
+                    PowerBudgetStatus {
+                        reason: Some(reason),
+                        total_watts: Some(b.total_watts),
+                        ..Default::default()
+                    }
+                }
+            },
+        };
+        if let Ok(mut s) = STATUS.lock() {
+            *s = Some(next);
+        }
+        crate::metrics::record_task_loop("power_budget", started.elapsed());
+        sleep(Duration::from_secs(interval as u64)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_by_load_within_limits() {
+        // Gaming: the GPU is busy, the CPU gets what is left above its floor
+        assert_eq!(split(140, 30.0, 90.0, (15, 54), (30, 100)), (40, 100));
+        // Compiling: the CPU is capped at its maximum, the rest goes to the GPU
+        assert_eq!(split(140, 100.0, 0.0, (15, 54), (30, 100)), (54, 86));
+        // Both idle: an even split
+        assert_eq!(split(100, 0.0, 1.0, (15, 54), (30, 100)), (50, 50));
+        // Floors win over the load ratio
+        assert_eq!(split(60, 0.0, 100.0, (15, 54), (30, 100)), (15, 45));
+    }
+}
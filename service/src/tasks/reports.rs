@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::cli::FrameworkTool;
+use crate::types::{Config, ReportInterval, TelemetryReport};
+
+const TICK: Duration = Duration::from_secs(60);
+/// Persist the running period this often so a crash loses at most a few minutes.
+const SAVE_EVERY_TICKS: u32 = 5;
+
+/// Scheduled telemetry reports: once the daily/weekly period has elapsed, compose the report
+/// from the accumulator fed by the telemetry task and POST it to the configured webhook.
+/// A failed delivery keeps the period open and is retried on the next tick.
+pub async fn run(
+    cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
+    cfg_lock: Arc<tokio::sync::RwLock<Config>>,
+) {
+    info!("Reports task started");
+
+    let mut ticks: u32 = 0;
+    loop {
+        sleep(TICK).await;
+        let (reports_cfg, ui_cfg) = {
+            let cfg = cfg_lock.read().await;
+            (cfg.reports.clone(), cfg.ui.clone())
+        };
+        if !reports_cfg.enabled {
+            continue;
+        }
+        let cli = { cli_lock.read().await.clone() };
+        let health = match &cli {
+            Some(cli) => crate::reports::battery_health_pct(cli).await,
+            None => None,
+        };
+        if let Some(pct) = health {
+            crate::reports::set_battery_health_start(pct);
+        }
+
+        let period_ms = match reports_cfg.interval {
+            ReportInterval::Daily => 24 * 60 * 60 * 1000,
+            ReportInterval::Weekly => 7 * 24 * 60 * 60 * 1000,
+        };
+        let report = crate::reports::current_report(&reports_cfg, &ui_cfg, health);
+        if report.period_end_ms - report.period_start_ms < period_ms {
+            ticks += 1;
+            if ticks.is_multiple_of(SAVE_EVERY_TICKS) {
+                crate::reports::save();
+            }
+            continue;
+        }
+
+        match &reports_cfg.webhook_url {
+            Some(url) => match deliver(url, &report).await {
+                Ok(()) => info!("reports: delivered \"{}\"", report.subject),
+                Err(e) => {
+                    warn!("reports: delivery to webhook failed: {}", e);
+                    continue;
+                }
+            },
+            None => info!("reports: {}\n{}", report.subject, report.text),
+        }
+        crate::reports::start_new_period(health);
+    }
+}
+
+async fn deliver(url: &str, report: &TelemetryReport) -> Result<(), String> {
+    let body = serde_json::to_string(report).map_err(|e| e.to_string())?;
+    let resp = reqwest::Client::new()
+        .post(url)
+        .header("content-type", "application/json")
+        .header("user-agent", "framework-control-service")
+        .timeout(std::time::Duration::from_secs(30))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("webhook returned {}", resp.status()));
+    }
+    Ok(())
+}
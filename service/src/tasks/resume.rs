@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use tokio::time::{sleep, Duration};
+use tracing::info;
+
+#[cfg(target_os = "windows")]
+use crate::cli::RyzenAdj;
+#[cfg(target_os = "windows")]
+use std::sync::Arc;
+#[cfg(target_os = "windows")]
+use tokio::sync::RwLock;
+#[cfg(target_os = "windows")]
+use tracing::warn;
+
+const TICK: Duration = Duration::from_secs(5);
+/// Wall-clock time that must go missing between ticks to count as a suspend/resume.
+const RESUME_GAP: Duration = Duration::from_secs(30);
+
+/// Bumped on every detected resume; control tasks compare it to force a full reapply.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Detects sleep, hibernate and Windows Fast Startup (a hibernated kernel session, so the service
+/// process survives but no boot path runs) by the wall clock jumping ahead of our tick. The EC
+/// frequently resets fan mode, charge limits and power limits across these transitions, so on
+/// resume every control task reapplies its settings and the CLI tools are re-validated.
+pub async fn run(#[cfg(target_os = "windows")] ryzenadj_lock: Arc<RwLock<Option<RyzenAdj>>>) {
+    info!("Resume watcher started");
+
+    let mut last_wall = SystemTime::now();
+    loop {
+        sleep(TICK).await;
+        let now = SystemTime::now();
+        let elapsed = now.duration_since(last_wall).unwrap_or_default();
+        last_wall = now;
+        if elapsed < TICK + RESUME_GAP {
+            continue;
+        }
+
+        let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+        info!(
+            "resume: wall clock jumped {}s (sleep/hibernate/fast startup); reapplying settings (generation {})",
+            elapsed.as_secs(),
+            generation
+        );
+        crate::tasks::fan_curve::request_reapply();
+        // Let the resolver re-run `--versions` before anyone trusts the cached path again.
+        crate::cli::framework_tool::mark_suspect();
+
+        // The ryzenadj resolver only acts on a missing handle; drop it if it no longer runs
+        // (driver not reloaded after hibernate, binary removed while asleep, ...).
+        #[cfg(target_os = "windows")]
+        {
+            let present = { ryzenadj_lock.read().await.is_some() };
+            if present && RyzenAdj::new().await.is_err() {
+                *ryzenadj_lock.write().await = None;
+                warn!("resume: ryzenadj not runnable after resume; re-resolving");
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration};
+use tracing::info;
+
+use crate::cli::FrameworkTool;
+use crate::sensors::metadata;
+use crate::types::{AlertSeverity, Config, SensorLevel};
+
+/// How often the extra providers are polled
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// A raised threshold alert clears once the sensor is this far below its warn threshold
+const ALERT_CLEAR_MARGIN_C: i32 = 3;
+
+/// Extra sensor collector: polls the platform providers in `crate::sensors` and publishes
+/// their readings for the control loops, which must never wait on a slow provider. Also raises
+/// the per-sensor warn/critical alerts (monitoring only, so it runs in safe mode too).
+pub async fn run(cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>, cfg: Arc<tokio::sync::RwLock<Config>>) {
+    info!("Sensor collector task started");
+
+    let mut raised: BTreeSet<String> = BTreeSet::new();
+    loop {
+        let started = std::time::Instant::now();
+        let temps = crate::sensors::collect_extra_temps().await;
+        crate::sensors::store_extra_temps(temps);
+
+        let cli = { cli_lock.read().await.clone() };
+        if let Some(cli) = cli {
+            metadata::detect_board(&cli).await;
+            if let Ok(thermal) = crate::sensors::thermal(&cli).await {
+                let (sensors_cfg, ui) = {
+                    let c = cfg.read().await;
+                    (c.sensors.clone(), c.ui.clone())
+                };
+                let (board, _) = metadata::board();
+                let mut still_raised = BTreeSet::new();
+                for (name, temp) in &thermal.temps {
+                    let meta = metadata::resolve(board, name, &sensors_cfg);
+                    let id = format!("sensor_temp:{}", name);
+                    let (severity, threshold, label) = match metadata::level(&meta, *temp) {
+                        SensorLevel::Critical => (AlertSeverity::Critical, meta.critical_c, "critical"),
+                        SensorLevel::Warn => (AlertSeverity::Warning, meta.warn_c, "warning"),
+                        SensorLevel::Normal => {
+                            let clear_below = meta.warn_c.map(|w| w - ALERT_CLEAR_MARGIN_C);
+                            if raised.contains(&id) && clear_below.is_some_and(|c| *temp >= c) {
+                                still_raised.insert(id);
+                            }
+                            continue;
+                        }
+                    };
+                    let location = meta.location.map(|l| format!(" ({})", l)).unwrap_or_default();
+                    crate::alerts::raise(
+                        &id,
+                        severity,
+                        format!(
+                            "{}{} at {}, {} threshold {}",
+                            metadata::display_name(board, name).text,
+                            location,
+                            crate::units::format_temp(*temp as f32, &ui),
+                            label,
+                            crate::units::format_temp(threshold.unwrap_or_default() as f32, &ui)
+                        ),
+                    );
+                    still_raised.insert(id);
+                }
+                for id in raised.difference(&still_raised) {
+                    crate::alerts::clear(id);
+                }
+                raised = still_raised;
+            }
+        }
+
+        crate::metrics::record_task_loop("sensors", started.elapsed());
+        sleep(POLL_INTERVAL).await;
+    }
+}
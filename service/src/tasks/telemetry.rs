@@ -5,22 +5,30 @@ use tokio::time::{sleep, Duration};
 use tracing::{info, warn};
 
 use crate::cli::FrameworkTool;
-use crate::types::{Config, TelemetrySample};
+use crate::types::{Config, PowerRails, TelemetrySample};
+
+#[cfg(target_os = "windows")]
+use crate::cli::RyzenAdj;
 
 pub async fn run(
     cli_lock: Arc<tokio::sync::RwLock<Option<FrameworkTool>>>,
     cfg_lock: Arc<tokio::sync::RwLock<Config>>,
     samples_lock: Arc<tokio::sync::RwLock<VecDeque<TelemetrySample>>>,
+    #[cfg(target_os = "windows")] ryzenadj_lock: Arc<tokio::sync::RwLock<Option<RyzenAdj>>>,
 ) {
     info!("Telemetry task started");
 
     loop {
         // Snapshot config at loop start
-        let tel_cfg = {
+        let (tel_cfg, reports_cfg, fan_cfg) = {
             let cfg = cfg_lock.read().await;
-            cfg.telemetry.clone()
+            (cfg.telemetry.clone(), cfg.reports.clone(), cfg.fan.clone())
         };
-        let poll_interval = Duration::from_millis(tel_cfg.poll_ms.max(200));
+        let mut poll_ms = tel_cfg.poll_ms.max(200);
+        if crate::diagnostics::capture::active() {
+            poll_ms = poll_ms.min(crate::diagnostics::capture::TELEMETRY_POLL_MS);
+        }
+        let poll_interval = Duration::from_millis(poll_ms);
 
         // Obtain CLI
         let maybe_cli = { cli_lock.read().await.clone() };
@@ -30,14 +38,26 @@ pub async fn run(
         };
 
         // Read thermal
-        match cli.thermal().await {
+        let started = std::time::Instant::now();
+        #[cfg(target_os = "windows")]
+        let power_rails = read_power_rails(&ryzenadj_lock).await;
+        #[cfg(not(target_os = "windows"))]
+        let power_rails: Option<PowerRails> = None;
+        match crate::sensors::thermal(&cli).await {
             Ok(parsed) => {
                 let now_ms = unix_time_ms();
                 let sample = TelemetrySample {
                     ts_ms: now_ms,
                     temps: parsed.temps,
                     rpms: parsed.fans.iter().map(|f| f.rpm).collect(),
+                    duty_pct: crate::tasks::fan_restore::last_duty(),
+                    power_rails,
                 };
+                if reports_cfg.enabled {
+                    crate::reports::record_sample(&sample, &reports_cfg);
+                }
+                crate::fan_hours::record_sample(now_ms, &sample.rpms, &fan_cfg);
+                crate::fan_anomaly::record_sample(now_ms, &sample.rpms, &fan_cfg);
                 {
                     let mut w = samples_lock.write().await;
                     w.push_back(sample);
@@ -56,11 +76,19 @@ pub async fn run(
                 warn!("telemetry read failed: {}", e);
             }
         }
+        crate::metrics::record_task_loop("telemetry", started.elapsed());
 
         sleep(poll_interval).await;
     }
 }
 
+/// Rail power from the (cached) ryzenadj info read; `None` without ryzenadj or a decodable table.
+#[cfg(target_os = "windows")]
+async fn read_power_rails(ryzenadj_lock: &tokio::sync::RwLock<Option<RyzenAdj>>) -> Option<PowerRails> {
+    let ryz = { ryzenadj_lock.read().await.clone() }?;
+    ryz.info().await.ok()?.rails
+}
+
 fn unix_time_ms() -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     let now = SystemTime::now();
@@ -0,0 +1,205 @@
+// Temporary config overrides (`POST /config/temporary`): a partial config applied to the running
+// config only, for a TTL. `config.json` is never written, so a restart reverts them too. The
+// saved value of each overridden section is kept here and put back by the revert task when the
+// TTL runs out. A permanent `POST /config` write to a section ends its override.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tracing::info;
+
+use crate::types::{Config, TemporaryOverride};
+
+struct Override {
+    /// The section as saved in `config.json`
+    saved: Value,
+    expires_at_ms: i64,
+}
+
+static OVERRIDES: Mutex<BTreeMap<String, Override>> = Mutex::new(BTreeMap::new());
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn sections(cfg: &Config) -> serde_json::Map<String, Value> {
+    match serde_json::to_value(cfg) {
+        Ok(Value::Object(m)) => m,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/// `base` with `replace` sections swapped in; sections that do not parse back are left as-is.
+fn with_sections(base: Config, replace: impl IntoIterator<Item = (String, Value)>) -> Config {
+    let mut map = sections(&base);
+    for (section, value) in replace {
+        map.insert(section, value);
+    }
+    serde_json::from_value(Value::Object(map)).unwrap_or(base)
+}
+
+/// Start (or extend) overrides for `touched`, remembering their saved values from `running`
+/// unless an override of the section is already active.
+pub fn begin(running: &Config, touched: &[&str], ttl_s: u64) {
+    let saved = sections(&persisted(running));
+    let expires_at_ms = now_ms() + ttl_s as i64 * 1000;
+    let Ok(mut overrides) = OVERRIDES.lock() else { return };
+    for section in touched {
+        let Some(value) = saved.get(*section) else { continue };
+        overrides
+            .entry(section.to_string())
+            .and_modify(|o| o.expires_at_ms = expires_at_ms)
+            .or_insert(Override {
+                saved: value.clone(),
+                expires_at_ms,
+            });
+    }
+}
+
+/// The running config as saved: overridden sections back at their saved values.
+pub fn persisted(running: &Config) -> Config {
+    let saved: Vec<(String, Value)> = match OVERRIDES.lock() {
+        Ok(o) => o.iter().map(|(k, v)| (k.clone(), v.saved.clone())).collect(),
+        Err(_) => Vec::new(),
+    };
+    with_sections(running.clone(), saved)
+}
+
+/// `saved` with the temporary values of still-overridden sections (other than `except`) taken
+/// from `running`.
+pub fn overlay(saved: Config, running: &Config, except: &[&str]) -> Config {
+    let keep: Vec<String> = match OVERRIDES.lock() {
+        Ok(o) => o.keys().filter(|k| !except.contains(&k.as_str())).cloned().collect(),
+        Err(_) => Vec::new(),
+    };
+    let current = sections(running);
+    with_sections(
+        saved,
+        keep.into_iter()
+            .filter_map(|k| current.get(&k).cloned().map(|v| (k, v))),
+    )
+}
+
+/// Drop the overrides of `touched` without restoring them (a permanent write replaced them).
+pub fn end(touched: &[&str]) {
+    if let Ok(mut o) = OVERRIDES.lock() {
+        for section in touched {
+            if o.remove(*section).is_some() {
+                info!("temporary override of {} ended by a permanent write", section);
+            }
+        }
+    }
+}
+
+/// When the override of `section` reverts (unix ms), if it has one.
+pub fn expires_at_ms(section: &str) -> Option<i64> {
+    OVERRIDES.lock().ok()?.get(section).map(|o| o.expires_at_ms)
+}
+
+pub fn active() -> Vec<TemporaryOverride> {
+    match OVERRIDES.lock() {
+        Ok(o) => o
+            .iter()
+            .map(|(section, o)| TemporaryOverride {
+                section: section.clone(),
+                expires_at_ms: o.expires_at_ms,
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Put expired overrides (all of them with `all`) back to their saved values in the running
+/// config, bumping its revision. Returns the reverted sections.
+pub async fn revert(cfg: &Arc<tokio::sync::RwLock<Config>>, all: bool) -> Vec<String> {
+    let now = now_ms();
+    revert_where(cfg, |_, v| all || v.expires_at_ms <= now).await
+}
+
+/// Put the override of `section` back now, ahead of its TTL; false when it had none.
+pub async fn revert_section(cfg: &Arc<tokio::sync::RwLock<Config>>, section: &str) -> bool {
+    !revert_where(cfg, |k, _| k == section).await.is_empty()
+}
+
+async fn revert_where(cfg: &Arc<tokio::sync::RwLock<Config>>, due: impl Fn(&str, &Override) -> bool) -> Vec<String> {
+    let _writer = crate::config::WRITE_LOCK.lock().await;
+    let restore: Vec<(String, Value)> = {
+        let Ok(mut o) = OVERRIDES.lock() else { return Vec::new() };
+        let due: Vec<String> = o.iter().filter(|(k, v)| due(k, v)).map(|(k, _)| k.clone()).collect();
+        due.into_iter()
+            .filter_map(|k| o.remove(&k).map(|v| (k, v.saved)))
+            .collect()
+    };
+    if restore.is_empty() {
+        return Vec::new();
+    }
+    let reverted: Vec<String> = restore.iter().map(|(k, _)| k.clone()).collect();
+    let mut w = cfg.write().await;
+    let mut next = with_sections(w.clone(), restore);
+    next.revision = w.revision + 1;
+    if reverted.iter().any(|s| s == "logging") {
+        crate::log_forward::apply(&next.logging);
+        crate::error_reports::apply(&next.logging);
+    }
+    *w = next;
+    info!(
+        "temporary override of {} reverted (revision {})",
+        reverted.join(", "),
+        w.revision
+    );
+    if reverted.iter().any(|s| s == "fan") {
+        crate::tasks::fan_curve::request_reapply();
+    }
+    reverted
+}
+
+/// Revert overrides as they expire.
+pub async fn run(cfg: Arc<tokio::sync::RwLock<Config>>) {
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let due = OVERRIDES
+            .lock()
+            .map(|o| o.values().any(|v| v.expires_at_ms <= now_ms()))
+            .unwrap_or(false);
+        if due {
+            revert(&cfg, false).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SettingU8, TelemetryConfig};
+
+    #[test]
+    fn saved_values_survive_permanent_writes_to_other_sections() {
+        let mut saved = Config::default();
+        saved.battery.charge_limit_max_pct = Some(SettingU8 {
+            enabled: true,
+            value: 80,
+        });
+        let mut running = saved.clone();
+        running.battery.charge_limit_max_pct = Some(SettingU8 {
+            enabled: true,
+            value: 100,
+        });
+        begin(&saved, &["battery"], 60);
+        assert_eq!(active()[0].section, "battery");
+
+        // A permanent write to telemetry saves the battery section's saved value...
+        let mut written = persisted(&running);
+        written.telemetry = TelemetryConfig {
+            poll_ms: 1000,
+            ..Default::default()
+        };
+        assert_eq!(written.battery.charge_limit_max_pct.as_ref().unwrap().value, 80);
+        // ...while the running config keeps the override
+        let next = overlay(written, &running, &["telemetry"]);
+        assert_eq!(next.battery.charge_limit_max_pct.as_ref().unwrap().value, 100);
+        assert_eq!(next.telemetry.poll_ms, 1000);
+        end(&["battery"]);
+        assert!(active().is_empty());
+    }
+}
@@ -2,7 +2,7 @@ use poem_openapi::{Enum, Object};
 use serde::{Deserialize, Serialize};
 
 // Core config types
-#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
 pub struct Config {
     #[serde(default)]
     pub fan: FanControlConfig,
@@ -16,19 +16,21 @@ pub struct Config {
     pub telemetry: TelemetryConfig,
     #[serde(default)]
     pub ui: UiConfig,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            fan: FanControlConfig::default(),
-            power: PowerConfig::default(),
-            battery: BatteryConfig::default(),
-            updates: UpdatesConfig::default(),
-            telemetry: TelemetryConfig::default(),
-            ui: UiConfig::default(),
-        }
-    }
+    #[serde(default)]
+    pub keyboard_backlight: KeyboardBacklightConfig,
+    #[serde(default)]
+    pub reports: ReportsConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub sensors: SensorsConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    /// Bumped on every accepted `POST /config`; writers must send back the revision they read
+    #[serde(default)]
+    pub revision: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Enum, Default)]
@@ -41,6 +43,12 @@ pub enum FanControlMode {
     Manual,
     #[oai(rename = "curve")]
     Curve,
+    /// Hold the hottest selected sensor near `fan.target.target_c`
+    #[oai(rename = "target")]
+    Target,
+    /// Follow `fan.rpm` (temperature→RPM); the EC closes the loop on the fan speed
+    #[oai(rename = "rpm")]
+    Rpm,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
@@ -51,10 +59,266 @@ pub struct FanControlConfig {
     pub manual: Option<ManualConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub curve: Option<GlobalCurveConfig>,
+    /// Settings of the `target` (hold temperature) mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<TargetTempConfig>,
+    /// Settings of the `rpm` mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm: Option<RpmCurveConfig>,
+    /// Named curves to switch between with `POST /fan/profile/activate`; replaced wholesale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<std::collections::BTreeMap<String, GlobalCurveConfig>>,
+    /// Profile whose curve `curve` holds; set by activation and dropped once `curve` is edited.
+    /// Ignored in `POST /config`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    /// Time-of-day rules that activate `profiles`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<FanScheduleConfig>,
+    /// Combined duty→RPM table (the fastest fan); the fallback for fans without their own
     #[serde(skip_serializing_if = "Option::is_none")]
     pub calibration: Option<FanCalibration>,
+    /// Per-fan tables from `POST /fan/calibrate`, each tagged with its `fan_index`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibrations: Option<Vec<FanCalibration>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub overrides: Option<Vec<FanOverride>>,
+    /// Duty-weighted run hours after which a fan raises a cleaning reminder; 0 turns it off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance_hours: Option<u32>,
+    /// Framework 16 dGPU module fan, controlled independently of the system fans
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<GpuFanConfig>,
+    /// What to do when another program is found driving the fans (default notify)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_contention: Option<FanContentionPolicy>,
+    /// What to do when thermal reads keep failing (default: 100% after 10 failed polls)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failsafe: Option<FanFailsafeConfig>,
+    /// Opt-in curve auto-tuner; see `GET /fan/auto-tune`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_tune: Option<FanAutoTuneConfig>,
+    /// Per-fan trim of every duty the fan task writes in manual, curve and target mode, for fans
+    /// that are louder or quieter than the others at the same duty; replaced wholesale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duty_offsets: Option<Vec<FanDutyOffset>>,
+}
+
+impl FanControlConfig {
+    /// Duty to write to fan `fan_index` (`None` is every fan at once) for a commanded `duty`.
+    pub fn offset_duty(&self, fan_index: Option<u32>, duty: u32) -> u32 {
+        fan_index
+            .and_then(|i| self.duty_offsets.iter().flatten().find(|o| o.index == i))
+            .map_or(duty, |o| o.apply(duty))
+    }
+
+    /// Fan `fan_index`'s own calibration, else the combined one.
+    pub fn calibration_for(&self, fan_index: u32) -> Option<&FanCalibration> {
+        self.calibrations
+            .iter()
+            .flatten()
+            .find(|c| c.fan_index == Some(fan_index) && !c.points.is_empty())
+            .or(self.calibration.as_ref().filter(|c| !c.points.is_empty()))
+    }
+
+    /// Any calibration table, per fan or combined.
+    pub fn is_calibrated(&self) -> bool {
+        self.calibration
+            .iter()
+            .chain(self.calibrations.iter().flatten())
+            .any(|c| !c.points.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FanContentionPolicy {
+    /// Raise the `fan_contention` alert only
+    #[default]
+    #[oai(rename = "notify")]
+    Notify,
+    /// Stop writing the fans until re-asserted
+    #[oai(rename = "back_off")]
+    BackOff,
+    /// Re-apply the configured duties
+    #[oai(rename = "reassert")]
+    Reassert,
+}
+
+/// Fan task behaviour while the temperatures cannot be read; applies in every mode but
+/// `disabled`, where the EC already has the fans
+#[derive(Debug, Clone, Serialize, Deserialize, Object, PartialEq)]
+pub struct FanFailsafeConfig {
+    /// Consecutive failed thermal reads (one per poll) before the failsafe engages
+    #[serde(default = "default_failsafe_failed_reads")]
+    #[oai(default = "default_failsafe_failed_reads")]
+    pub failed_reads: u32,
+    #[serde(default)]
+    #[oai(default)]
+    pub action: FanFailsafeAction,
+    /// Duty for the `duty` action
+    #[serde(default = "default_failsafe_duty_pct")]
+    #[oai(default = "default_failsafe_duty_pct")]
+    pub duty_pct: u32,
+}
+
+impl Default for FanFailsafeConfig {
+    fn default() -> Self {
+        Self {
+            failed_reads: default_failsafe_failed_reads(),
+            action: FanFailsafeAction::default(),
+            duty_pct: default_failsafe_duty_pct(),
+        }
+    }
+}
+
+fn default_failsafe_failed_reads() -> u32 {
+    10
+}
+fn default_failsafe_duty_pct() -> u32 {
+    100
+}
+
+/// Curve auto-tuner: in curve mode, runs `trials` variants of `fan.curve` for `trial_minutes`
+/// each during normal use, nudging point duties by simulated annealing to lower a weighted cost
+/// of fan noise and temperature. The best variant is proposed for acceptance; the saved curve is
+/// never changed on its own.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, PartialEq)]
+pub struct FanAutoTuneConfig {
+    #[serde(default)]
+    #[oai(default)]
+    pub enabled: bool,
+    /// 0 optimizes for temperature only, 1 for quiet only
+    #[serde(default = "default_auto_tune_noise_weight")]
+    #[oai(default = "default_auto_tune_noise_weight")]
+    pub noise_weight: f32,
+    /// Trials whose hottest reading passes this are penalized for every degree above it
+    #[serde(default = "default_auto_tune_max_temp_c")]
+    #[oai(default = "default_auto_tune_max_temp_c")]
+    pub max_temp_c: u32,
+    /// How far (duty %) a point may move from the curve the run started from
+    #[serde(default = "default_auto_tune_max_offset_pct")]
+    #[oai(default = "default_auto_tune_max_offset_pct")]
+    pub max_offset_pct: u32,
+    #[serde(default = "default_auto_tune_trial_minutes")]
+    #[oai(default = "default_auto_tune_trial_minutes")]
+    pub trial_minutes: u32,
+    /// Trials per run, the first on the unchanged curve
+    #[serde(default = "default_auto_tune_trials")]
+    #[oai(default = "default_auto_tune_trials")]
+    pub trials: u32,
+}
+
+impl Default for FanAutoTuneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            noise_weight: default_auto_tune_noise_weight(),
+            max_temp_c: default_auto_tune_max_temp_c(),
+            max_offset_pct: default_auto_tune_max_offset_pct(),
+            trial_minutes: default_auto_tune_trial_minutes(),
+            trials: default_auto_tune_trials(),
+        }
+    }
+}
+
+fn default_auto_tune_noise_weight() -> f32 {
+    0.5
+}
+fn default_auto_tune_max_temp_c() -> u32 {
+    90
+}
+fn default_auto_tune_max_offset_pct() -> u32 {
+    15
+}
+fn default_auto_tune_trial_minutes() -> u32 {
+    60
+}
+fn default_auto_tune_trials() -> u32 {
+    48
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FanFailsafeAction {
+    /// Every EC fan at `duty_pct`
+    #[default]
+    #[oai(rename = "duty")]
+    Duty,
+    /// Hand the fans back to the EC's own control
+    #[oai(rename = "auto")]
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum FanContentionAction {
+    #[oai(rename = "back_off")]
+    BackOff,
+    #[oai(rename = "reassert")]
+    Reassert,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanContentionRequest {
+    pub action: FanContentionAction,
+}
+
+/// A fan whose RPM does not match the duty the service last commanded
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct ContendedFan {
+    pub fan_index: u32,
+    pub commanded_duty_pct: u32,
+    /// RPM the calibration predicts for the commanded duty
+    pub expected_rpm: u32,
+    pub observed_rpm: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct FanContentionStatus {
+    /// Another program appears to be driving the fans
+    pub detected: bool,
+    pub since_ms: Option<i64>,
+    pub fans: Vec<ContendedFan>,
+    /// The service has stopped writing the fans until re-asserted
+    pub backed_off: bool,
+    pub policy: FanContentionPolicy,
+    /// Detection compares RPM against the fan calibrations; without one it is off
+    pub calibrated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct GpuFanConfig {
+    #[serde(default)]
+    pub mode: Option<FanControlMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duty_pct: Option<u32>,
+    /// Curve keyed to GPU temperature; empty `sensors` means every sensor with "gpu" in its name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<CurveConfig>,
+    /// EC fan index to drive instead of the amdgpu PWM (Windows, or kernels without amdgpu
+    /// fan control). That fan is then left out of the system fan control.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ec_fan_index: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuFanBackend {
+    #[oai(rename = "amdgpu")]
+    Amdgpu,
+    #[oai(rename = "ec")]
+    Ec,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct GpuFanStatus {
+    /// How the fan is driven; `None` when no dGPU fan was found
+    pub backend: Option<GpuFanBackend>,
+    pub rpm: Option<u32>,
+    pub duty_pct: Option<u32>,
+    /// Hottest GPU sensor the curve would follow
+    pub temp_c: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
@@ -69,6 +333,48 @@ pub struct FanOverride {
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
 pub struct ManualConfig {
     pub duty_pct: u32,
+    /// Optional per-fan duties by fan index; fans not listed run at `duty_pct`.
+    /// Ignored when this config is itself a per-fan override.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_duties: Option<Vec<FanDuty>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanDuty {
+    pub index: u32,
+    pub duty_pct: u32,
+}
+
+/// Trim of one fan's duty: the commanded duty times `scale`, plus `offset_pct`, within 1–100.
+/// A stopped fan (0%) stays stopped and a spinning one keeps spinning, so stopping stays with the
+/// curve's `stop_below_c`/restart/stop delay logic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct FanDutyOffset {
+    pub index: u32,
+    /// Percentage points added after scaling, e.g. -10 runs the fan 10% lower
+    #[serde(default)]
+    #[oai(default)]
+    pub offset_pct: i32,
+    #[serde(default = "default_duty_scale")]
+    #[oai(default = "default_duty_scale")]
+    pub scale: f32,
+}
+
+fn default_duty_scale() -> f32 {
+    1.0
+}
+
+impl FanDutyOffset {
+    pub fn apply(&self, duty: u32) -> u32 {
+        if duty == 0 {
+            return 0;
+        }
+        let trimmed = (duty as f32 * self.scale + self.offset_pct as f32).round();
+        if !trimmed.is_finite() {
+            return duty;
+        }
+        trimmed.clamp(1.0, 100.0) as u32
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
@@ -77,12 +383,120 @@ pub struct CurveConfig {
     pub sensors: Vec<String>,
     #[serde(default = "default_points")]
     pub points: Vec<[u32; 2]>,
+    /// Degrees the temperature must climb past where the duty last changed before the fan
+    /// speeds up; 0 reacts at once
+    #[serde(default)]
+    #[oai(default)]
+    pub hysteresis_up_c: u32,
+    /// Degrees it must fall from there before the fan slows down
     #[serde(default = "default_hysteresis_c")]
-    pub hysteresis_c: u32,
+    #[oai(default = "default_hysteresis_c")]
+    pub hysteresis_down_c: u32,
+    /// Former single band, which only held back spin-down; when set it is used in place of
+    /// `hysteresis_down_c`, so configs and clients from before the split keep working
+    #[oai(deprecated, skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hysteresis_c: Option<u32>,
     #[serde(default = "default_rate_limit_pct_per_step")]
     pub rate_limit_pct_per_step: u32,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rate_limit_down_pct_per_step: Option<u32>,
+    /// Derive the spin-up step from the last fan ramp test (`/diagnostics/fan-ramp`) instead of
+    /// `rate_limit_pct_per_step`; ignored for fans that were never tested
+    #[serde(default)]
+    #[oai(default)]
+    pub auto_rate_limit: bool,
+    /// Stop the fan (0% duty) once the curve temperature is below this; unset keeps it spinning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_below_c: Option<u32>,
+    /// Temperature at which a stopped fan starts again (default `stop_below_c` + 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_at_c: Option<u32>,
+    /// Seconds the temperature must stay below `stop_below_c` before the fan stops; until then
+    /// it keeps following the curve (unset stops at once)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_delay_s: Option<u32>,
+    /// How the selected sensors combine into the curve temperature (default `max`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<SensorAggregate>,
+    /// Weights for `weighted`, by sensor or zone name (a zone's weight applies to its members);
+    /// unlisted sensors weigh 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<std::collections::BTreeMap<String, f32>>,
+    /// Jump to a high duty while the curve temperature climbs quickly; unset follows the curve
+    /// (and its rate limit) only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<CurveBoostConfig>,
+    /// Move the curve with the room temperature read from an ambient sensor; unset uses the
+    /// points as drawn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambient: Option<CurveAmbientConfig>,
+}
+
+impl CurveConfig {
+    pub fn hysteresis_down(&self) -> u32 {
+        self.hysteresis_c.unwrap_or(self.hysteresis_down_c)
+    }
+}
+
+/// Boost of a curve: once the temperature rises faster than `rise_c_per_s`, the fan goes
+/// straight to `duty_pct` (never below the duty it already has) and stays there for
+/// `duration_s` after the last fast rise, then winds back down to the curve.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct CurveBoostConfig {
+    #[serde(default = "default_boost_rise_c_per_s")]
+    #[oai(default = "default_boost_rise_c_per_s")]
+    pub rise_c_per_s: f32,
+    #[serde(default = "default_boost_duty_pct")]
+    #[oai(default = "default_boost_duty_pct")]
+    pub duty_pct: u32,
+    #[serde(default = "default_boost_duration_s")]
+    #[oai(default = "default_boost_duration_s")]
+    pub duration_s: u32,
+}
+
+/// Ambient compensation of a curve: every degree `sensor` reads above `reference_c` moves the
+/// curve `shift_per_c` degrees up (below it, down), at most `max_shift_c` either way. The
+/// components run warmer in a warm room at the same load, so the default of one degree per
+/// degree keeps the fans following the load rather than the room.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct CurveAmbientConfig {
+    /// Sensor or zone read as the ambient temperature
+    pub sensor: String,
+    /// Ambient temperature at which the curve applies as drawn
+    #[serde(default = "default_ambient_reference_c")]
+    #[oai(default = "default_ambient_reference_c")]
+    pub reference_c: i32,
+    /// Negative values move the curve down instead, so the fans start earlier in a warm room
+    #[serde(default = "default_ambient_shift_per_c")]
+    #[oai(default = "default_ambient_shift_per_c")]
+    pub shift_per_c: f32,
+    #[serde(default = "default_ambient_max_shift_c")]
+    #[oai(default = "default_ambient_max_shift_c")]
+    pub max_shift_c: u32,
+}
+
+impl CurveAmbientConfig {
+    /// Degrees the curve moves up at an ambient reading of `ambient_c`.
+    pub fn shift_c(&self, ambient_c: i32) -> i32 {
+        let max = self.max_shift_c as i32;
+        (((ambient_c - self.reference_c) as f32 * self.shift_per_c).round() as i32).clamp(-max, max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorAggregate {
+    /// Hottest sensor
+    #[default]
+    #[oai(rename = "max")]
+    Max,
+    /// Mean of the sensors
+    #[oai(rename = "avg")]
+    Avg,
+    /// Mean weighted by `weights`
+    #[oai(rename = "weighted")]
+    Weighted,
 }
 
 /// Global fan curve: [`CurveConfig`] plus the shared control-loop poll interval.
@@ -95,6 +509,118 @@ pub struct GlobalCurveConfig {
     pub poll_ms: u64,
 }
 
+/// Fan profile schedule: when the local time enters a rule's window its profile is activated,
+/// and `default_profile` (if set) when it leaves all of them. Profiles activated by hand in
+/// between stay until the next boundary.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct FanScheduleConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// The first rule whose window contains the current time wins
+    #[serde(default)]
+    pub rules: Vec<FanScheduleRule>,
+    /// Profile for the times no rule covers; unset leaves the last one active
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// How long before a `wake` rule's start the machine wakes (default 5 minutes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wake_lead_minutes: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanScheduleRule {
+    /// Name in `fan.profiles`
+    pub profile: String,
+    /// Local time "HH:MM", inclusive
+    pub start: String,
+    /// Local time "HH:MM", exclusive; before `start` for windows past midnight ("22:00"-"08:00")
+    pub end: String,
+    /// Arm a daily OS wake timer (Windows scheduled task, systemd timer) `wake_lead_minutes`
+    /// before `start`; the profile applies from the wake on, so the window starts pre-heated
+    #[serde(default)]
+    #[oai(default)]
+    pub wake: bool,
+    /// Suspend again when the window ends, if the machine resumed at this rule's wake time
+    #[serde(default)]
+    #[oai(default)]
+    pub sleep_after: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct FanProfileActivateRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct FanProfiles {
+    pub active_profile: Option<String>,
+    /// Profile the schedule calls for right now, when it is enabled
+    pub scheduled_profile: Option<String>,
+    /// Profile names, sorted
+    pub profiles: Vec<String>,
+    /// Names among `profiles` provisioned from the managed profile directory; read-only
+    pub managed: Vec<String>,
+    /// Daily wake timers armed for the schedule (local "HH:MM")
+    pub wake_times: Vec<String>,
+    /// Current config revision
+    pub revision: u64,
+}
+
+/// Hold-temperature fan mode: instead of following a curve, the duty is stepped up while the
+/// hottest of `sensors` is above `target_c` and down while it is below.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct TargetTempConfig {
+    #[serde(default)]
+    #[oai(default)]
+    pub sensors: Vec<String>,
+    pub target_c: u32,
+    /// Duty never goes below this, e.g. to keep the fans audible but steady
+    #[serde(default)]
+    #[oai(default)]
+    pub min_duty_pct: u32,
+    #[serde(default = "default_max_duty_pct")]
+    #[oai(default = "default_max_duty_pct")]
+    pub max_duty_pct: u32,
+    #[serde(default = "default_poll_ms")]
+    #[oai(default = "default_poll_ms")]
+    pub poll_ms: u64,
+}
+
+/// `rpm` mode: a temperature→RPM curve set with `--fansetrpm`, so each fan holds a speed
+/// rather than a duty whose RPM drifts with dust, voltage and fan wear
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct RpmCurveConfig {
+    /// Sensors the curve follows; empty means the hottest sensor
+    #[serde(default)]
+    #[oai(default)]
+    pub sensors: Vec<String>,
+    /// How the selected sensors combine (default `max`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<SensorAggregate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<std::collections::BTreeMap<String, f32>>,
+    /// `[temp_c, rpm]` points; held flat below the first and above the last
+    pub points: Vec<[u32; 2]>,
+    #[serde(default = "default_hysteresis_c")]
+    #[oai(default = "default_hysteresis_c")]
+    pub hysteresis_c: u32,
+    #[serde(default = "default_poll_ms")]
+    #[oai(default = "default_poll_ms")]
+    pub poll_ms: u64,
+    /// Points for individual fans (by EC fan index); other fans use `points`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fans: Option<Vec<FanRpmCurve>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanRpmCurve {
+    pub index: u32,
+    pub points: Vec<[u32; 2]>,
+}
+
+fn default_max_duty_pct() -> u32 {
+    100
+}
 fn default_points() -> Vec<[u32; 2]> {
     vec![[40, 0], [60, 40], [75, 80], [85, 100]]
 }
@@ -107,6 +633,24 @@ fn default_hysteresis_c() -> u32 {
 fn default_rate_limit_pct_per_step() -> u32 {
     100
 }
+fn default_boost_rise_c_per_s() -> f32 {
+    2.0
+}
+fn default_boost_duty_pct() -> u32 {
+    100
+}
+fn default_boost_duration_s() -> u32 {
+    10
+}
+fn default_ambient_reference_c() -> i32 {
+    25
+}
+fn default_ambient_shift_per_c() -> f32 {
+    1.0
+}
+fn default_ambient_max_shift_c() -> u32 {
+    10
+}
 
 #[derive(Serialize, Object)]
 pub struct UpdateCheck {
@@ -119,13 +663,74 @@ pub struct SystemInfo {
     pub cpu: String,
     pub memory_total_mb: u64,
     pub os: String,
+    /// Name of the first discrete adapter in `gpus` (falls back to the first adapter)
     pub dgpu: Option<String>,
+    /// Graphics adapters with PCI IDs (Windows; empty elsewhere)
+    pub gpus: Vec<GpuInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct GpuInfo {
+    pub name: String,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    /// Classified from the PCI vendor/device ID
+    pub discrete: bool,
 }
 
 #[derive(Serialize, Object)]
 pub struct Health {
     pub cli_present: bool,
     pub service_version: String,
+    /// True when control tasks are disabled (monitoring only); see `safe_mode_reason`
+    pub safe_mode: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safe_mode_reason: Option<String>,
+    /// Problems the startup config check found and worked around (`config_check.rs`)
+    pub config_issues: Vec<ConfigIssue>,
+    pub device: DeviceIdentity,
+    /// Last `POST /config/reload` since startup, if any
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_reload: Option<ConfigReloadStatus>,
+    /// Latency of each `framework_tool`/`ryzenadj` command since startup
+    pub cli_latency: Vec<CliLatency>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct CliLatency {
+    /// `framework_tool` or `ryzenadj`
+    pub tool: String,
+    /// First flag of the invocation (`--thermal`, `--fansetduty`, ...)
+    pub command: String,
+    pub calls: u64,
+    pub errors: u64,
+    /// Calls over the slow-call threshold (2s), each logged with its arguments
+    pub slow_calls: u64,
+    pub mean_ms: u64,
+    /// Upper bound of the latency bucket holding the median
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigIssueAction {
+    /// The offending entry was dropped or replaced in the running config
+    #[oai(rename = "repaired")]
+    Repaired,
+    /// The section runs on defaults; the original is in `config.quarantine.json`
+    #[oai(rename = "quarantined")]
+    Quarantined,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ConfigIssue {
+    /// Config path of the problem (e.g. `fan.curve.sensors`); empty for the whole file
+    pub field: String,
+    pub message: String,
+    pub action: ConfigIssueAction,
 }
 
 #[derive(Serialize, Object, Default)]
@@ -136,74 +741,700 @@ pub struct ShortcutsStatus {
 #[derive(Serialize, Object, Default)]
 pub struct Empty {}
 
-#[derive(Debug, Clone, Deserialize, Object)]
+#[derive(Debug, Clone, Deserialize, Object, Default)]
 pub struct PartialConfig {
+    /// Revision from the last `GET /config` (or write); stale revisions are rejected with 409
+    pub revision: Option<u64>,
     pub fan: Option<FanControlConfig>,
     pub power: Option<PowerConfig>,
     pub battery: Option<BatteryConfig>,
     pub updates: Option<UpdatesConfig>,
     pub telemetry: Option<TelemetryConfig>,
     pub ui: Option<UiConfig>,
+    pub keyboard_backlight: Option<KeyboardBacklightConfig>,
+    pub reports: Option<ReportsConfig>,
+    pub network: Option<NetworkConfig>,
+    pub sensors: Option<SensorsConfig>,
+    pub logging: Option<LoggingConfig>,
+    pub backup: Option<BackupConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct UpdatesConfig {
-    #[serde(default)]
-    pub auto_install: bool,
+/// `POST /config/temporary`
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct TemporaryConfigRequest {
+    /// Seconds until the override reverts (10 s to 24 h)
+    pub ttl_s: u64,
+    /// Sections to override; `revision` as for `POST /config`
+    pub patch: PartialConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct UiConfig {
-    /// Preferred UI theme (matches DaisyUI theme names)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub theme: Option<String>,
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct TemporaryOverride {
+    /// Overridden config section (`power`, `fan`, ...)
+    pub section: String,
+    /// When the section reverts to its saved value (unix ms)
+    pub expires_at_ms: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object)]
-pub struct TelemetryConfig {
-    #[serde(default = "default_telemetry_poll_ms")]
-    pub poll_ms: u64,
-    #[serde(default = "default_telemetry_retain_seconds")]
-    pub retain_seconds: u64,
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct TemporaryOverrides {
+    /// Current config revision
+    pub revision: u64,
+    pub overrides: Vec<TemporaryOverride>,
 }
 
-impl Default for TelemetryConfig {
-    fn default() -> Self {
-        Self {
-            poll_ms: default_telemetry_poll_ms(),
-            retain_seconds: default_telemetry_retain_seconds(),
-        }
-    }
+/// `POST /fan/override`
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct FanOverrideRequest {
+    /// Duty for every fan (0-100%)
+    pub duty_pct: u32,
+    /// Seconds until the configured fan mode returns (10 s to 24 h)
+    pub duration_s: u64,
 }
 
-fn default_telemetry_poll_ms() -> u64 {
-    1000
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct FanOverrideStatus {
+    /// Current config revision
+    pub revision: u64,
+    /// Duty the fans are held at; `None` without an override
+    #[oai(skip_serializing_if_is_none)]
+    pub duty_pct: Option<u32>,
+    /// When the configured fan mode returns (unix ms); `None` without an override
+    #[oai(skip_serializing_if_is_none)]
+    pub expires_at_ms: Option<i64>,
 }
-fn default_telemetry_retain_seconds() -> u64 {
-    1800
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoTunePhase {
+    /// `fan.auto_tune` is off or the fans are not in curve mode
+    #[oai(rename = "idle")]
+    Idle,
+    /// A trial curve is running
+    #[oai(rename = "running")]
+    Running,
+    /// Another temporary fan override is in effect; the trial resumes after it
+    #[oai(rename = "waiting")]
+    Waiting,
+    /// All trials ran; see `proposal`
+    #[oai(rename = "finished")]
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoTuneEventKind {
+    /// A trial ended; `kept` tells whether the run continues from its curve
+    #[oai(rename = "trial")]
+    Trial,
+    /// The saved curve or `fan.auto_tune` changed, so the run started over
+    #[oai(rename = "restarted")]
+    Restarted,
+    /// The run ended with a curve better than the one it started from
+    #[oai(rename = "proposed")]
+    Proposed,
+    /// The run ended without beating the curve it started from
+    #[oai(rename = "unchanged")]
+    Unchanged,
+    #[oai(rename = "accepted")]
+    Accepted,
+    #[oai(rename = "dismissed")]
+    Dismissed,
 }
 
+/// One entry in the auto-tuner's history
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
-pub struct TelemetrySample {
+pub struct AutoTuneEvent {
     pub ts_ms: i64,
-    pub temps: std::collections::BTreeMap<String, i32>,
-    pub rpms: Vec<u32>,
+    pub kind: AutoTuneEventKind,
+    /// The curve the event is about
+    pub points: Vec<[u32; 2]>,
+    /// Weighted cost (lower is better)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub cost: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub avg_temp_c: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub max_temp_c: Option<i32>,
+    /// Estimated noise of all fans, as in `POST /fan/compare`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub noise_dba: Option<f32>,
+    /// For trials: whether annealing moved on from this curve
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub kept: Option<bool>,
 }
 
-// Fan calibration types
+/// A tuned curve waiting for `POST /fan/auto-tune/accept`
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
-pub struct FanCalibration {
-    /// Calibration data points: [duty_pct, rpm]
+pub struct AutoTuneProposal {
+    pub created_ms: i64,
     pub points: Vec<[u32; 2]>,
-    /// Unix timestamp (seconds)
-    pub updated_at: i64,
+    /// `fan.curve` points the run started from
+    pub baseline_points: Vec<[u32; 2]>,
+    /// The best trial of the proposed curve
+    pub best: AutoTuneEvent,
+    /// The first trial, on the unchanged curve
+    pub baseline: AutoTuneEvent,
 }
 
-// Generic API error envelope
-#[derive(Debug, Clone, Serialize, Deserialize, Object)]
-pub struct ErrorEnvelope {
-    pub code: String,
-    pub message: String,
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct AutoTuneStatus {
+    /// Current config revision
+    pub revision: u64,
+    pub phase: AutoTunePhase,
+    /// Trials finished in this run
+    pub trials_done: u32,
+    pub trials: u32,
+    /// Curve of the trial in progress
+    #[oai(skip_serializing_if_is_none)]
+    pub trial_points: Option<Vec<[u32; 2]>>,
+    /// When the trial in progress ends (unix ms)
+    #[oai(skip_serializing_if_is_none)]
+    pub trial_ends_ms: Option<i64>,
+    #[oai(skip_serializing_if_is_none)]
+    pub proposal: Option<AutoTuneProposal>,
+    /// Newest last
+    pub history: Vec<AutoTuneEvent>,
+}
+
+/// `POST /eco`
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct EcoRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct EcoStatus {
+    pub enabled: bool,
+    /// When Eco mode was turned on (unix ms)
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_ms: Option<i64>,
+    /// Current config revision
+    pub revision: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct ConfigWriteResult {
+    /// New config revision to send with the next write (unchanged by a dry run)
+    pub revision: u64,
+    /// With `dry_run`: the hardware writes the change would cause; nothing was saved or applied
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<Vec<PlannedChange>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ConfigReloadResult {
+    /// Running config revision; bumped only when something changed
+    pub revision: u64,
+    /// Sections that differed from the running config (`fan`, `power`, ...); empty when the file
+    /// already matched
+    pub changed: Vec<String>,
+    /// With `dry_run`: the hardware writes the reload would cause; nothing was applied
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<Vec<PlannedChange>>,
+}
+
+/// Outcome of the last `POST /config/reload`, reported by `/health`
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ConfigReloadStatus {
+    pub at_ms: i64,
+    /// The file was loaded (or already matched); false when it was rejected
+    pub ok: bool,
+    pub revision: u64,
+    pub changed: Vec<String>,
+    /// Why the file was rejected; `field` is empty when it could not be read or parsed
+    pub errors: Vec<FieldError>,
+}
+
+/// This installation's identity (`device.rs`), carried by exports and reports
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct DeviceIdentity {
+    /// Random ID generated on first start and kept in `device.json` next to the config
+    pub device_id: String,
+    /// Board detected from `framework_tool --versions`; `unknown` until detection succeeds
+    #[serde(default)]
+    pub model: BoardModel,
+    /// Raw mainboard type reported by the EC
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mainboard_type: Option<String>,
+}
+
+/// `GET /config/export`: the saved config with the device it came from
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct ConfigBundle {
+    pub device: DeviceIdentity,
+    pub exported_at_ms: i64,
+    pub service_version: String,
+    pub config: Config,
+}
+
+/// `POST /config/import`: a bundle from `GET /config/export`. Sections missing from `config`
+/// keep their current values; its `revision` is ignored
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct ConfigImportRequest {
+    /// Device the bundle was exported on; a bundle without one is treated as from another device
+    pub device: Option<DeviceIdentity>,
+    pub config: PartialConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct ConfigImportResult {
+    pub revision: u64,
+    /// The bundle was exported on a different Framework model
+    pub model_mismatch: bool,
+    /// Model-specific settings that were not applied (`power`, `sensors`, `fan.calibration`, ...)
+    pub skipped: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Backup of the config that was replaced
+    pub backup_path: String,
+}
+
+/// What the service would do for one setting (`dry_run.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct PlannedChange {
+    /// Config path the writes follow from, e.g. `battery.charge_limit_max_pct`
+    pub setting: String,
+    /// Command lines (`framework_tool --charge-limit 80`) and sysfs writes (`echo 1 > /sys/...`)
+    pub commands: Vec<String>,
+    /// The setter stopped before writing, e.g. the backend lacks the control
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct UpdatesConfig {
+    #[serde(default)]
+    pub auto_install: bool,
+    /// Hold automatic installs while a user has do-not-disturb on (default true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defer_during_dnd: Option<bool>,
+    /// JSON feed of BIOS/EC releases checked against `--versions` (see `/firmware/updates`);
+    /// `FRAMEWORK_CONTROL_FIRMWARE_FEED` when unset
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firmware_feed_url: Option<String>,
+}
+
+/// Overrides for the installer's `FRAMEWORK_CONTROL_PORT` / `FRAMEWORK_CONTROL_ALLOWED_ORIGINS`.
+/// The service always binds to loopback.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default, PartialEq)]
+pub struct NetworkConfig {
+    /// Listening port; changing it rebinds the server without a restart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Extra CORS origins (the service's own origin is always allowed); replaces the env list when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_origins: Option<Vec<String>>,
+    /// Path prefix when served behind a reverse proxy (e.g. `/framework`); requests under it are
+    /// routed with the prefix stripped, and unprefixed paths keep working
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_path: Option<String>,
+}
+
+/// Forwarding of service logs to the platform log or a remote syslog collector
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct LoggingConfig {
+    #[serde(default)]
+    pub forward: bool,
+    /// Least severe level forwarded (default warn)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_level: Option<LogLevel>,
+    /// Default auto: journald (else local syslog) on Linux, the Event Log on Windows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_target: Option<LogForwardTarget>,
+    /// `host:port` of a remote syslog collector (UDP, RFC 5424), used with the `syslog` target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub syslog_address: Option<String>,
+    /// Opt-in: write a report for panics and repeated errors to `error_reports/` next to the config
+    #[serde(default)]
+    pub error_reports: bool,
+    /// Also POST each report (JSON) here; nothing is uploaded without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_report_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[oai(rename = "error")]
+    Error,
+    #[default]
+    #[oai(rename = "warn")]
+    Warn,
+    #[oai(rename = "info")]
+    Info,
+    #[oai(rename = "debug")]
+    Debug,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogForwardTarget {
+    #[default]
+    #[oai(rename = "auto")]
+    Auto,
+    #[oai(rename = "journald")]
+    Journald,
+    #[oai(rename = "syslog")]
+    Syslog,
+    #[oai(rename = "eventlog")]
+    EventLog,
+}
+
+/// Per-sensor metadata overrides and thermal zones. Replaced wholesale on write.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct SensorsConfig {
+    /// Keyed by sensor name, or by zone name to apply to every sensor in the zone
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    #[oai(default)]
+    pub overrides: std::collections::BTreeMap<String, SensorMetadata>,
+    /// Zone name -> sensor names. Replaces the built-in members of a zone with the same name
+    /// (CPU, GPU, VRM, Memory, SSD, Chassis, Battery); zone names work wherever curves take
+    /// sensor names
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    #[oai(default)]
+    pub zones: std::collections::BTreeMap<String, Vec<String>>,
+}
+
+/// Static facts about a sensor. Unset fields fall back to the board table, then to the
+/// defaults for the sensor's kind.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default, PartialEq)]
+pub struct SensorMetadata {
+    /// Raises a warning alert at or above this temperature (°C)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warn_c: Option<i32>,
+    /// Raises a critical alert and forces the fans to 100% at or above this temperature (°C)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical_c: Option<i32>,
+    /// Where the sensor sits (e.g. "CPU package", "Left DIMM")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// How stale a reading can be: cache age or provider poll interval (ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u32>,
+}
+
+/// Display name for a sensor. `key` is stable for translation (`raw` when the sensor is not
+/// known and `text` is its raw name) and `params` fill its placeholders; `text` is the English
+/// name.
+#[derive(Debug, Clone, Serialize, Object, PartialEq)]
+pub struct SensorLabel {
+    pub key: String,
+    pub text: String,
+    #[oai(skip_serializing_if_is_empty)]
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub params: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Enum, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardModel {
+    #[oai(rename = "laptop12")]
+    Laptop12,
+    #[oai(rename = "laptop13_intel")]
+    Laptop13Intel,
+    #[oai(rename = "laptop13_amd")]
+    Laptop13Amd,
+    #[oai(rename = "laptop16")]
+    Laptop16,
+    #[oai(rename = "desktop")]
+    Desktop,
+    #[default]
+    #[oai(rename = "unknown")]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorLevel {
+    #[oai(rename = "normal")]
+    Normal,
+    #[oai(rename = "warn")]
+    Warn,
+    #[oai(rename = "critical")]
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct SensorInfo {
+    /// Raw name as reported by the source; what curves, zones and overrides refer to
+    pub name: String,
+    pub label: SensorLabel,
+    /// Latest reading (°C); `None` when the sensor is only known from metadata
+    pub temp_c: Option<i32>,
+    /// Resolved metadata: defaults, board table and user override merged
+    pub metadata: SensorMetadata,
+    pub level: SensorLevel,
+    /// True when `sensors.overrides` has an entry for this sensor
+    pub overridden: bool,
+    /// Thermal zones the sensor belongs to
+    pub zones: Vec<String>,
+}
+
+/// A thermal zone with its reporting members; reads as the hottest of them
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct ThermalZone {
+    pub name: String,
+    pub sensors: Vec<String>,
+    pub temp_c: Option<i32>,
+    /// Highest level among the members
+    pub level: SensorLevel,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct SensorCatalog {
+    pub board: BoardModel,
+    /// Mainboard type as reported by `framework_tool --versions`
+    pub mainboard_type: Option<String>,
+    /// True while a critical reading holds the fans at 100%
+    pub emergency_active: bool,
+    pub sensors: Vec<SensorInfo>,
+    pub zones: Vec<ThermalZone>,
+}
+
+/// One sensor in `GET /sensors/heatmap`
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct HeatmapSensor {
+    pub name: String,
+    pub label: SensorLabel,
+    pub temp_c: i32,
+    /// Change over the trend window in °C per minute (0 with a single sample)
+    pub trend_c_per_min: f32,
+    pub level: SensorLevel,
+    /// Degrees left before the critical threshold, when the sensor has one
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headroom_c: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct SensorHeatmap {
+    /// Time of the telemetry sample the readings come from; `None` before the first sample
+    pub ts_ms: Option<i64>,
+    /// Seconds of history the trends cover
+    pub window_s: u32,
+    /// True while a critical reading holds the fans at 100%
+    pub emergency_active: bool,
+    pub sensors: Vec<HeatmapSensor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct UiConfig {
+    /// Preferred UI theme (matches DaisyUI theme names)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Unit for temperatures in service-formatted text (default Celsius)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature_unit: Option<TemperatureUnit>,
+    /// Clock for service-formatted times (default 24h)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clock_format: Option<ClockFormat>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    #[oai(rename = "celsius")]
+    Celsius,
+    #[oai(rename = "fahrenheit")]
+    Fahrenheit,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+pub enum ClockFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    #[oai(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    #[oai(rename = "12h")]
+    H12,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct TelemetryConfig {
+    #[serde(default = "default_telemetry_poll_ms")]
+    pub poll_ms: u64,
+    #[serde(default = "default_telemetry_retain_seconds")]
+    pub retain_seconds: u64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            poll_ms: default_telemetry_poll_ms(),
+            retain_seconds: default_telemetry_retain_seconds(),
+        }
+    }
+}
+
+fn default_telemetry_poll_ms() -> u64 {
+    1000
+}
+fn default_telemetry_retain_seconds() -> u64 {
+    1800
+}
+
+/// What a logged control action set.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlActionKind {
+    /// EC fan duty (%), one fan or all of them
+    #[oai(rename = "fan_duty")]
+    FanDuty,
+    /// dGPU module fan duty (%) through the amdgpu PWM
+    #[oai(rename = "gpu_fan_duty")]
+    GpuFanDuty,
+    /// EC fan speed target (RPM), one fan or all of them
+    #[oai(rename = "fan_rpm")]
+    FanRpm,
+    /// CPU TDP (W)
+    #[oai(rename = "tdp")]
+    Tdp,
+}
+
+/// Why the service applied a control action.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlActionReason {
+    #[oai(rename = "curve")]
+    Curve,
+    #[oai(rename = "manual")]
+    Manual,
+    /// A per-fan override (`fan.overrides`) of the manual duty or curve
+    #[oai(rename = "override")]
+    Override,
+    /// Hold-temperature mode
+    #[oai(rename = "target")]
+    Target,
+    /// RPM curve mode
+    #[oai(rename = "rpm")]
+    Rpm,
+    /// A curve's `boost` on a fast temperature rise
+    #[oai(rename = "boost")]
+    Boost,
+    /// Critical sensor reading: fans forced to 100%
+    #[oai(rename = "emergency")]
+    Emergency,
+    /// Thermal reads kept failing: fans at the `fan.failsafe` duty
+    #[oai(rename = "failsafe")]
+    Failsafe,
+    /// The active power profile
+    #[oai(rename = "profile")]
+    Profile,
+    /// The CPU share of `power.budget`
+    #[oai(rename = "budget")]
+    Budget,
+    /// Back to the value from before the service changed it
+    #[oai(rename = "restore")]
+    Restore,
+}
+
+/// One entry of `GET /actions/history`; `ts_ms` is on the telemetry sample clock.
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct ControlAction {
+    pub ts_ms: i64,
+    pub kind: ControlActionKind,
+    /// Fan the duty went to; `None` for all fans (or a non-fan action)
+    pub fan_index: Option<u32>,
+    pub value: u32,
+    pub reason: ControlActionReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct TelemetrySample {
+    pub ts_ms: i64,
+    pub temps: std::collections::BTreeMap<String, i32>,
+    pub rpms: Vec<u32>,
+    /// Highest fan duty the service had applied; `None` under EC automatic control
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duty_pct: Option<u32>,
+    /// Per-rail power (Windows/RyzenAdj only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power_rails: Option<PowerRails>,
+}
+
+/// Where the package power goes, in watts
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default, PartialEq)]
+pub struct PowerRails {
+    /// Whole-package (socket) power
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket_watts: Option<f32>,
+    /// Sum over all CPU cores
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core_watts: Option<f32>,
+    /// SoC rail (memory controller, fabric, I/O)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soc_watts: Option<f32>,
+    /// Socket power not attributed to cores or SoC; dominated by the iGPU under graphics load
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gfx_watts: Option<f32>,
+}
+
+// Fan calibration types
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanCalibration {
+    /// Calibration data points: [duty_pct, rpm]
+    pub points: Vec<[u32; 2]>,
+    /// Unix timestamp (seconds)
+    pub updated_at: i64,
+    /// Fan the table was measured on; unset for the combined table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_index: Option<u32>,
+}
+
+// Generic API error envelope
+/// One rejected config field, addressed by its JSON path (e.g. `fan.curve.points[2][1]`).
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ValidationErrorEnvelope {
+    pub code: String,
+    pub message: String,
+    pub fields: Vec<FieldError>,
+    /// ID of the failed request, also sent as the `X-Request-Id` header and logged with it
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// Actionable fix for an error. `key` is stable for translation and `params` fill its
+/// placeholders; `message` is the English text.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct RemediationHint {
+    pub key: String,
+    pub message: String,
+    #[oai(skip_serializing_if_is_empty)]
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub params: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ErrorEnvelope {
+    pub code: String,
+    pub message: String,
+    /// ID of the failed request, also sent as the `X-Request-Id` header and logged with it
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<RemediationHint>,
 }
 
 // Power config stored in Config and applied at boot (and on set)
@@ -215,75 +1446,620 @@ pub struct SettingU32 {
     pub value: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct SettingString {
-    /// Whether this setting should be applied
-    pub enabled: bool,
-    /// The last chosen value (kept even when disabled)
-    pub value: String,
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct SettingString {
+    /// Whether this setting should be applied
+    pub enabled: bool,
+    /// The last chosen value (kept even when disabled)
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct SettingBool {
+    /// Whether this setting should be applied
+    pub enabled: bool,
+    /// The last chosen value (kept even when disabled)
+    pub value: bool,
+}
+
+/// Windows processor scheduling and standby settings of the active power scheme (powercfg).
+/// Unset fields are left as the scheme has them.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default, PartialEq)]
+pub struct SchedulerHints {
+    /// Heterogeneous thread scheduling policy (SCHEDPOLICY): 0 all processors, 1 performant,
+    /// 2 prefer performant, 3 efficient, 4 prefer efficient, 5 automatic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heterogeneous_policy: Option<u32>,
+    /// Minimum processor state in % (PROCTHROTTLEMIN)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processor_min_pct: Option<u32>,
+    /// Maximum processor state in % (PROCTHROTTLEMAX)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processor_max_pct: Option<u32>,
+    /// Sleep after this many idle minutes; 0 never (STANDBYIDLE)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standby_timeout_min: Option<u32>,
+}
+
+impl SchedulerHints {
+    /// True when every field set here has the same value in `observed`.
+    pub fn satisfied_by(&self, observed: Option<&SchedulerHints>) -> bool {
+        let Some(o) = observed else { return false };
+        let same = |t: Option<u32>, o: Option<u32>| t.is_none() || t == o;
+        same(self.heterogeneous_policy, o.heterogeneous_policy)
+            && same(self.processor_min_pct, o.processor_min_pct)
+            && same(self.processor_max_pct, o.processor_max_pct)
+            && same(self.standby_timeout_min, o.standby_timeout_min)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == SchedulerHints::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct SettingSchedulerHints {
+    /// Whether this setting should be applied
+    pub enabled: bool,
+    /// The last chosen values (kept even when disabled)
+    pub value: SchedulerHints,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct PowerProfile {
+    // Windows: Direct TDP control
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tdp_watts: Option<SettingU32>,
+
+    // Windows: individual ryzenadj limits; each one set replaces `tdp_watts` for that limit only
+    /// Sustained (STAPM) limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stapm_watts: Option<SettingU32>,
+    /// Short-boost (PPT fast) limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fast_limit_watts: Option<SettingU32>,
+    /// Long-boost (PPT slow) limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_limit_watts: Option<SettingU32>,
+
+    // Windows/some Linux: Thermal limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermal_limit_c: Option<SettingU32>,
+
+    // Linux AMD P-State: Energy Performance Preference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epp_preference: Option<SettingString>,
+
+    // Linux cpufreq: Governor selection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub governor: Option<SettingString>,
+
+    // Linux cpufreq: Frequency limits (in MHz)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_freq_mhz: Option<SettingU32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_freq_mhz: Option<SettingU32>,
+
+    // Linux cpufreq/amd-pstate boost, Windows processor performance boost mode (powercfg)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<SettingBool>,
+
+    // Windows: processor scheduling and standby settings of the power scheme (powercfg)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduler_hints: Option<SettingSchedulerHints>,
+
+    /// AC profile only: while it is active, charge to 100% at the full rate regardless of
+    /// `battery.charge_limit_max_pct`/`charge_rate_c`, so the battery is not left topping up slowly
+    /// under sustained load; the battery limits come back when AC is unplugged or this is turned off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lift_battery_limits: Option<bool>,
+}
+
+impl PowerProfile {
+    /// Whether any of the individual STAPM/fast/slow limits is in effect.
+    pub fn splits_tdp(&self) -> bool {
+        [&self.stapm_watts, &self.fast_limit_watts, &self.slow_limit_watts]
+            .into_iter()
+            .any(|s| s.as_ref().is_some_and(|s| s.enabled && s.value > 0))
+    }
+
+    /// `[stapm, fast, slow]` watts to apply: each individual limit that is enabled, else `tdp_watts`.
+    pub fn power_limits(&self) -> [Option<u32>; 3] {
+        let value = |s: &Option<SettingU32>| s.as_ref().filter(|s| s.enabled && s.value > 0).map(|s| s.value);
+        let tdp = value(&self.tdp_watts);
+        [
+            value(&self.stapm_watts).or(tdp),
+            value(&self.fast_limit_watts).or(tdp),
+            value(&self.slow_limit_watts).or(tdp),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct PowerConfig {
+    /// Profile used when AC power is present (plugged in / charging)
+    pub ac: Option<PowerProfile>,
+    /// Profile used when running on battery (not charging)
+    pub battery: Option<PowerProfile>,
+    /// Used in place of `ac` while the active USB-C charger's negotiated wattage is in range
+    /// (first match wins); `ac` still applies when no tier matches or the wattage is unknown.
+    /// Replaced as a whole on write
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chargers: Option<Vec<ChargerProfile>>,
+    /// Which backend applies the profiles; `auto` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<PowerBackendKind>,
+    /// Framework 16: share a total budget between CPU TDP and the dGPU power cap by load.
+    /// Replaced as a whole on write
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<PowerBudgetConfig>,
+}
+
+/// While enabled, the CPU TDP comes from the budget instead of the active profile's `tdp_watts`.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct PowerBudgetConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Combined CPU TDP + dGPU cap (W)
+    pub total_watts: u32,
+    /// CPU TDP floor (default 15 W)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_cpu_watts: Option<u32>,
+    /// dGPU cap floor (default 30 W)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_gpu_watts: Option<u32>,
+    /// Seconds between reassignments (default 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_s: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Object, Default)]
+pub struct PowerBudgetStatus {
+    /// The coordinator is setting the limits
+    pub active: bool,
+    /// Why it is not, when enabled
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_watts: Option<u32>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_watts: Option<u32>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_watts: Option<u32>,
+    /// Smoothed utilization the split is based on
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_load_pct: Option<f32>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_load_pct: Option<f32>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_ms: Option<i64>,
+}
+
+/// A power profile for chargers negotiating `min_watts..=max_watts` (either bound optional),
+/// e.g. `min_watts: 100` for a dock, `max_watts: 45` for a travel charger.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct ChargerProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_watts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_watts: Option<u32>,
+    pub profile: PowerProfile,
+}
+
+impl ChargerProfile {
+    pub fn matches(&self, watts: f32) -> bool {
+        self.min_watts.is_none_or(|min| watts >= min as f32) && self.max_watts.is_none_or(|max| watts <= max as f32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Enum, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerBackendKind {
+    /// The platform's native backend: RyzenAdj on Windows, sysfs on Linux
+    #[default]
+    #[oai(rename = "auto")]
+    Auto,
+    #[oai(rename = "ryzenadj")]
+    Ryzenadj,
+    /// AMD P-State EPP and cpufreq governor/frequency limits
+    #[oai(rename = "linux")]
+    Linux,
+    /// Leave power limits to the OS or another tool
+    #[oai(rename = "disabled")]
+    Disabled,
+}
+
+// Battery config stored in Config and applied at boot (and on set)
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct SettingU8 {
+    /// Whether this setting should be applied
+    pub enabled: bool,
+    /// The last chosen value (kept even when disabled)
+    pub value: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct SettingF32 {
+    /// Whether this setting should be applied
+    pub enabled: bool,
+    /// The last chosen value (kept even when disabled)
+    pub value: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct BatteryConfig {
+    /// EC charge limit maximum percent (25-100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_limit_max_pct: Option<SettingU8>,
+    /// Charge rate in C (0.05 - 1.0). When disabled, use 1.0C to approximate no limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_rate_c: Option<SettingF32>,
+    /// Optional SoC threshold (%) for rate limiting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_rate_soc_threshold_pct: Option<u8>,
+    /// Raise an alert when the connected adapter negotiates less than this many watts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_charger_watts: Option<u32>,
+    /// Long-term storage: hold the battery around a mid charge instead of `charge_limit_max_pct`
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_mode: Option<StorageModeConfig>,
+}
+
+/// While enabled the battery task ignores `charge_limit_max_pct` and the AC profile's
+/// `lift_battery_limits`, holds the charge limit at the bottom of the band and tops up to
+/// `target_pct` when the charge falls below it.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct StorageModeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Charge to keep the battery at (30-80, default 60)
+    #[serde(default = "default_storage_target_pct")]
+    #[oai(default = "default_storage_target_pct")]
+    pub target_pct: u8,
+    /// Allowed drift either side of `target_pct` (2-15, default 5)
+    #[serde(default = "default_storage_band_pct")]
+    #[oai(default = "default_storage_band_pct")]
+    pub band_pct: u8,
+}
+
+fn default_storage_target_pct() -> u8 {
+    60
+}
+fn default_storage_band_pct() -> u8 {
+    5
+}
+
+impl Default for StorageModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_pct: default_storage_target_pct(),
+            band_pct: default_storage_band_pct(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum StoragePhase {
+    /// In band (or above it off AC): the limit sits at the bottom of the band
+    #[oai(rename = "hold")]
+    Hold,
+    /// Below the band: charging back up to the target
+    #[oai(rename = "top_up")]
+    TopUp,
+    /// Above the band on AC: the EC cannot be made to discharge, so the user is asked to unplug
+    #[oai(rename = "discharge")]
+    Discharge,
+}
+
+/// `POST /battery/storage-mode`; unset fields keep their configured values
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct StorageModeRequest {
+    pub enabled: bool,
+    pub target_pct: Option<u8>,
+    pub band_pct: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct StorageModeStatus {
+    pub enabled: bool,
+    pub target_pct: u8,
+    pub band_pct: u8,
+    /// `None` while storage mode is off or before the battery task has checked the charge
+    pub phase: Option<StoragePhase>,
+    pub soc_pct: Option<u32>,
+    /// Charge limit the battery task is applying for storage mode
+    pub charge_limit_pct: Option<u8>,
+    /// Current config revision
+    pub revision: u64,
+}
+
+/// Automatic keyboard backlight driven by the ambient light sensor and AC state
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct KeyboardBacklightConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `[lux, brightness_pct]` steps: the highest step at or below the current reading wins
+    #[serde(default = "default_kblight_points")]
+    pub points: Vec<[u32; 2]>,
+    /// Brightness cap while running on battery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery_max_pct: Option<u32>,
+}
+
+impl Default for KeyboardBacklightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            points: default_kblight_points(),
+            battery_max_pct: None,
+        }
+    }
+}
+
+fn default_kblight_points() -> Vec<[u32; 2]> {
+    vec![[0, 50], [20, 30], [150, 0]]
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportInterval {
+    #[default]
+    #[oai(rename = "daily")]
+    Daily,
+    #[oai(rename = "weekly")]
+    Weekly,
+}
+
+/// Periodic health summary delivered to a webhook
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ReportsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub interval: ReportInterval,
+    /// Receives the report as a JSON POST; `subject`/`text` make it usable by email relays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Fan time is counted while any fan spins at or above this speed
+    #[serde(default = "default_report_fan_rpm_threshold")]
+    pub fan_rpm_threshold: u32,
+    /// A throttling event is counted each time the hottest sensor reaches this temperature
+    #[serde(default = "default_report_throttle_temp_c")]
+    pub throttle_temp_c: u32,
+}
+
+impl Default for ReportsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: ReportInterval::default(),
+            webhook_url: None,
+            fan_rpm_threshold: default_report_fan_rpm_threshold(),
+            throttle_temp_c: default_report_throttle_temp_c(),
+        }
+    }
+}
+
+fn default_report_fan_rpm_threshold() -> u32 {
+    3000
+}
+fn default_report_throttle_temp_c() -> u32 {
+    95
+}
+
+/// Nightly snapshots of the config and the long-term history (`backups.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct BackupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Archive directory; default `backups` next to the config. Snapshots go to a
+    /// `<device_id>` directory inside it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    /// Snapshots kept per device; older ones are deleted
+    #[serde(default = "default_backup_retention")]
+    #[oai(default = "default_backup_retention")]
+    pub retention: u32,
+    /// Local hour (0–23) of the nightly snapshot; taken at the next chance when the machine is
+    /// off or asleep then
+    #[serde(default = "default_backup_hour")]
+    #[oai(default = "default_backup_hour")]
+    pub hour: u32,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: None,
+            retention: default_backup_retention(),
+            hour: default_backup_hour(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct PowerProfile {
-    // Windows: Direct TDP control
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tdp_watts: Option<SettingU32>,
+fn default_backup_retention() -> u32 {
+    14
+}
+fn default_backup_hour() -> u32 {
+    3
+}
 
-    // Windows/some Linux: Thermal limit
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub thermal_limit_c: Option<SettingU32>,
+/// One snapshot in the backup archive
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct BackupEntry {
+    /// Device whose directory holds the snapshot; differs from this device's after a reinstall
+    pub device_id: String,
+    /// File name, `snapshot-<timestamp>.json`
+    pub name: String,
+    pub created_at_ms: i64,
+    pub size_bytes: u64,
+    /// The snapshot was taken on this installation
+    pub this_device: bool,
+}
 
-    // Linux AMD P-State: Energy Performance Preference
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub epp_preference: Option<SettingString>,
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct BackupList {
+    /// Archive directory
+    pub dir: String,
+    /// Newest first
+    pub entries: Vec<BackupEntry>,
+}
 
-    // Linux cpufreq: Governor selection
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub governor: Option<SettingString>,
+/// `POST /backups/restore`
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct BackupRestoreRequest {
+    pub device_id: String,
+    pub name: String,
+    /// Restore the config (default true)
+    pub config: Option<bool>,
+    /// Restore the fan hours, charging history and report period (default true)
+    pub history: Option<bool>,
+    /// Apply model-specific settings from a snapshot of a different model too
+    pub force: Option<bool>,
+}
 
-    // Linux cpufreq: Frequency limits (in MHz)
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct BackupRestoreResult {
+    /// Outcome of the config restore, as for `POST /config/import`
+    #[oai(skip_serializing_if_is_none)]
+    pub config: Option<ConfigImportResult>,
+    /// History restored (`fan_hours`, `battery_sessions`, `report_period`)
+    pub history: Vec<String>,
+    /// History entries in the snapshot that could not be restored
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct TelemetryReport {
+    pub period_start_ms: i64,
+    pub period_end_ms: i64,
+    pub max_temps: std::collections::BTreeMap<String, i32>,
+    pub avg_temps: std::collections::BTreeMap<String, f32>,
+    /// Hours any fan ran at or above `fan_rpm_threshold`
+    pub fan_hours_above_threshold: f32,
+    pub fan_rpm_threshold: u32,
+    /// Times the hottest sensor reached `throttle_temp_c`
+    pub throttle_events: u32,
+    /// Full-charge capacity as % of design at the start and end of the period
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub min_freq_mhz: Option<SettingU32>,
+    pub battery_health_start_pct: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub max_freq_mhz: Option<SettingU32>,
+    pub battery_health_end_pct: Option<f32>,
+    /// Plain-text rendering for email-style delivery
+    pub subject: String,
+    pub text: String,
+    /// Machine the report describes, so one webhook can collect several
+    pub device: DeviceIdentity,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct PowerConfig {
-    /// Profile used when AC power is present (plugged in / charging)
-    pub ac: Option<PowerProfile>,
-    /// Profile used when running on battery (not charging)
-    pub battery: Option<PowerProfile>,
+// Battery charge/discharge session log (`/battery/sessions`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum BatterySessionKind {
+    #[oai(rename = "charging")]
+    Charging,
+    #[oai(rename = "discharging")]
+    Discharging,
 }
 
-// Battery config stored in Config and applied at boot (and on set)
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct SettingU8 {
-    /// Whether this setting should be applied
-    pub enabled: bool,
-    /// The last chosen value (kept even when disabled)
-    pub value: u8,
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct BatterySession {
+    pub kind: BatterySessionKind,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub start_soc_pct: u32,
+    pub end_soc_pct: u32,
+    /// Energy into (charging) or out of (discharging) the battery, integrated from voltage × current
+    pub energy_wh: f32,
+    /// Mean battery power over the session
+    pub avg_watts: f32,
+    /// Power profile in effect when the session started (`power.ac` or `power.battery`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<PowerProfile>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct SettingF32 {
-    /// Whether this setting should be applied
-    pub enabled: bool,
-    /// The last chosen value (kept even when disabled)
-    pub value: f32,
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct BatterySessionsResponse {
+    /// Completed sessions, oldest first
+    pub sessions: Vec<BatterySession>,
+    /// Session in progress, if the battery is charging or discharging right now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<BatterySession>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
-pub struct BatteryConfig {
-    /// EC charge limit maximum percent (25-100)
+/// Hour-meter of one fan header (`fan_hours.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanHourMeter {
+    pub fan_index: u32,
+    /// Time spent spinning at any speed
+    pub run_hours: f32,
+    /// Run time scaled by duty: an hour at 50% counts as half an hour
+    pub duty_weighted_hours: f32,
+    /// Duty-weighted hours since the fan was last marked cleaned (or since tracking began)
+    pub hours_since_service: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub charge_limit_max_pct: Option<SettingU8>,
-    /// Charge rate in C (0.05 - 1.0). When disabled, use 1.0C to approximate no limit.
+    pub serviced_at_ms: Option<i64>,
+    /// `hours_since_service` reached `fan.maintenance_hours`
+    pub maintenance_due: bool,
+    /// 10% duty bands with a learnt RPM baseline for anomaly detection
+    pub baseline_bands: u32,
+    /// The fan's RPM has strayed from its baseline at a steady duty
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub charge_rate_c: Option<SettingF32>,
-    /// Optional SoC threshold (%) for rate limiting
+    pub anomaly: Option<FanAnomaly>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum FanAnomalyKind {
+    /// Slower than it used to be at this duty: dust build-up, a worn bearing or a stalling fan
+    #[oai(rename = "slow")]
+    Slow,
+    /// Faster than it used to be: the airflow path is likely blocked, so the fan runs unloaded
+    #[oai(rename = "fast")]
+    Fast,
+    /// Speed swings at a constant duty, typical of a failing bearing
+    #[oai(rename = "unsteady")]
+    Unsteady,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanAnomaly {
+    pub kind: FanAnomalyKind,
+    /// Duty the fan was commanded to when the anomaly was confirmed
+    pub duty_pct: u32,
+    /// Baseline RPM for that duty
+    pub expected_rpm: u32,
+    /// Smoothed RPM at that duty
+    pub observed_rpm: u32,
+    pub since_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanStatsResponse {
+    pub fans: Vec<FanHourMeter>,
+    /// Configured reminder threshold (`fan.maintenance_hours`)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub charge_rate_soc_threshold_pct: Option<u8>,
+    pub maintenance_hours: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct FanServiceRequest {
+    /// Fan that was cleaned; all fans when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_index: Option<u32>,
 }
 
 // API-facing union of battery info (flatten of parsed + limits)
@@ -293,6 +2069,21 @@ pub struct BatteryInfo {
     pub power_info: crate::cli::framework_tool_parser::PowerBatteryInfo,
     #[oai(flatten)]
     pub limits: crate::cli::framework_tool_parser::BatteryChargeLimitInfo,
+    #[oai(flatten)]
+    pub eta: BatteryEta,
+    /// The AC profile's `lift_battery_limits` is overriding the configured charge limit and rate
+    pub limits_lifted: bool,
+}
+
+/// Charge/discharge estimates from the smoothed battery current (`battery_eta.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct BatteryEta {
+    /// Smoothed charge or discharge current (mA); `present_rate_ma` swings with load
+    pub smoothed_rate_ma: Option<u32>,
+    /// Minutes until the charge limit (100% without one) is reached; only while charging
+    pub time_to_full_min: Option<u32>,
+    /// Minutes until empty; only while discharging
+    pub time_to_empty_min: Option<u32>,
 }
 
 // Combined power response used by /power
@@ -302,8 +2093,78 @@ pub struct PowerResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub battery: Option<BatteryInfo>,
 
+    /// Adapter currently charging the system (framework_tool --pd-ports)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charger: Option<crate::cli::framework_tool_parser::PdPortInfo>,
+
     /// Power control information
     pub power_control: PowerControlInfo,
+
+    /// Which sections could be read; an unavailable section is empty or defaulted
+    pub availability: PowerAvailability,
+}
+
+/// Whether one section of a combined response could be read. The rest of the response is still
+/// served when only some of its backends are missing.
+#[derive(Debug, Clone, Serialize, Deserialize, Object, Default)]
+pub struct SectionAvailability {
+    pub available: bool,
+    /// Why the section is unavailable: `cli_unavailable`, `cli_failed`, `backend_unavailable`,
+    /// `backend_disabled` or `no_sensors`
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<RemediationHint>,
+}
+
+impl SectionAvailability {
+    pub fn available() -> Self {
+        Self {
+            available: true,
+            code: None,
+            message: None,
+            hint: None,
+        }
+    }
+
+    pub fn unavailable(code: &str, message: String) -> Self {
+        Self {
+            available: false,
+            code: Some(code.to_string()),
+            hint: crate::hints::for_error(code, &message),
+            message: Some(message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct PowerAvailability {
+    /// `battery` and `charger` (framework_tool)
+    pub battery: SectionAvailability,
+    /// `power_control` (the selected power backend)
+    pub power_control: SectionAvailability,
+}
+
+// Response of /thermal: the readings plus which sources contributed
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ThermalResponse {
+    #[oai(flatten)]
+    #[serde(flatten)]
+    pub thermal: crate::cli::framework_tool_parser::ThermalParsed,
+    pub availability: ThermalAvailability,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ThermalAvailability {
+    /// EC temperatures and all fans (framework_tool --thermal)
+    pub ec: SectionAvailability,
+    /// Extra platform sensors (storage, DIMM, VRM, GPU)
+    pub extra: SectionAvailability,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
@@ -322,6 +2183,9 @@ pub struct PowerCapabilities {
     pub supports_epp: bool,
     pub supports_governor: bool,
     pub supports_frequency_limits: bool,
+    pub supports_boost: bool,
+    #[serde(default)]
+    pub supports_scheduler_hints: bool,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub available_epp_preferences: Option<Vec<String>>,
@@ -342,6 +2206,13 @@ pub struct PowerState {
     // Method-specific (platform populates what it can)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tdp_limit_watts: Option<u32>,
+    /// RyzenAdj: the individual limits `tdp_limit_watts` is the lowest of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stapm_limit_watts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fast_limit_watts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_limit_watts: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thermal_limit_c: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -352,6 +2223,53 @@ pub struct PowerState {
     pub min_freq_mhz: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_freq_mhz: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boost: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduler_hints: Option<SchedulerHints>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerSource {
+    #[oai(rename = "ac")]
+    Ac,
+    #[oai(rename = "battery")]
+    Battery,
+}
+
+/// What the power and fan tasks are trying to apply next to what the hardware reports.
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct PowerStateReport {
+    /// Power source selecting the profile; `None` while AC state is unknown
+    pub source: Option<PowerSource>,
+    /// Config path of the profile in effect (`power.ac`, `power.chargers[1].profile`, `power.battery`)
+    pub profile: Option<String>,
+    /// Negotiated wattage of the active charger while on AC, when known
+    pub charger_watts: Option<f32>,
+    /// Backend applying the profile; `None` when disabled or not available
+    pub backend: Option<PowerBackendKind>,
+    /// Enabled settings of the active profile that the backend supports
+    pub target: PowerState,
+    /// Values read back from the backend (configured frequency limits, not live clocks)
+    pub observed: PowerState,
+    /// `target` fields whose observed value differs
+    pub drift: Vec<String>,
+    pub fan_mode: Option<FanControlMode>,
+    /// Highest duty the service last applied; `None` while the EC controls the fans
+    pub fan_duty_pct: Option<u32>,
+    /// The service drives the fans while `fan_mode` is disabled, or the other way around
+    pub fan_drift: bool,
+    /// Settings whose last apply failed, until one succeeds
+    pub apply_errors: Vec<ApplyError>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct ApplyError {
+    /// Power task setting (`tdp`, `power limits`, `thermal`, `governor`, `epp`, `freq limits`)
+    pub setting: String,
+    pub message: String,
+    pub hint: Option<RemediationHint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
@@ -365,3 +2283,469 @@ pub struct SetRateLimitRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub battery_soc_threshold_pct: Option<u8>,
 }
+
+// Diagnostics types
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct StressRequest {
+    /// Load duration in seconds (clamped to 5-300)
+    pub duration_secs: u32,
+    /// Worker threads for the built-in CPU load (defaults to all logical CPUs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    /// Telemetry sampling interval in milliseconds (default 500, min 250)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct FanRampRequest {
+    /// Fan to test (default: every fan)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_index: Option<u32>,
+    /// Duty the fan settles at before the step (default 20)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_duty_pct: Option<u32>,
+    /// Duty commanded by the step (default 80)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_duty_pct: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanRampResult {
+    pub fan_index: u32,
+    pub from_duty_pct: u32,
+    pub to_duty_pct: u32,
+    pub start_rpm: u32,
+    /// RPM the fan settled at after the step
+    pub steady_rpm: u32,
+    /// RPM predicted for `to_duty_pct` by the fan's calibration, when calibrated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_rpm: Option<u32>,
+    /// Time from the step until the fan covered 90% of the way to its target RPM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u32>,
+    pub peak_rpm: u32,
+    /// How far the peak went past the steady RPM, in percent
+    pub overshoot_pct: f32,
+    /// Duty change per second this fan follows without lagging or overshooting; curves with
+    /// `auto_rate_limit` derive their step size from it
+    pub suggested_pct_per_sec: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct FanCalibrateRequest {
+    /// Fan to calibrate (default: every fan)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_index: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct FanCalibrateReport {
+    /// Tables measured by this run, one per fan
+    pub calibrations: Vec<FanCalibration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FanRampReport {
+    pub fans: Vec<FanRampResult>,
+    /// When the test ran (unix ms)
+    pub measured_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Object, Default)]
+pub struct StressReport {
+    pub duration_ms: u64,
+    /// Number of telemetry samples recorded during the run
+    pub samples: u32,
+    /// Temperatures at the start of the run
+    pub start_temps: std::collections::BTreeMap<String, i32>,
+    /// Maximum temperature observed per sensor
+    pub max_temps: std::collections::BTreeMap<String, i32>,
+    /// Highest average core clock observed (MHz)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_clock_mhz: Option<u32>,
+    /// Average core clock over the second half of the run (MHz)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sustained_clock_mhz: Option<u32>,
+    /// Fan RPM at the start of the run, by fan index
+    pub fan_start_rpm: Vec<u32>,
+    /// Maximum fan RPM observed, by fan index
+    pub fan_max_rpm: Vec<u32>,
+    /// Time from load start until each fan's RPM clearly rose above its start value (ms).
+    /// `None` when the fan never responded within the run.
+    pub fan_response_lag_ms: Vec<Option<u64>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct CurveFitRequest {
+    /// Sensors the curve will follow (hottest wins); empty means every sensor
+    #[serde(default)]
+    pub sensors: Vec<String>,
+    /// Temperature the fitted curve should hold the sensors at or below
+    pub target_max_temp_c: u32,
+    /// Noise cap: highest duty the curve may use up to the target temperature
+    pub max_duty_pct: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct CurveFitResult {
+    /// Candidate `[temp_c, duty_pct]` points, ready for `fan.curve.points`
+    pub points: Vec<[u32; 2]>,
+    /// True when the telemetry shows `max_duty_pct` holding temperatures at the target
+    pub meets_target: bool,
+    /// Telemetry samples with a known fan duty that went into the fit
+    pub samples_used: u32,
+    /// Hottest temperature seen in the analysed telemetry
+    pub observed_max_temp_c: i32,
+    pub note: String,
+}
+
+/// Either two `fan.profiles` run back-to-back under the same load (`profile_a`/`profile_b`), or
+/// two windows of the telemetry history (`window_a`/`window_b`).
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct CurveCompareRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_a: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_b: Option<String>,
+    /// Load seconds per profile (30-600, default 120)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u32>,
+    /// Idle seconds under each profile before its load starts, so both runs start from a
+    /// settled machine (0-300, default 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settle_secs: Option<u32>,
+    /// Worker threads for the built-in CPU load (defaults to all logical CPUs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_a: Option<TimeWindow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_b: Option<TimeWindow>,
+    /// Sensors or zones compared (hottest wins); empty means every sensor
+    #[serde(default)]
+    #[oai(default)]
+    pub sensors: Vec<String>,
+    /// Fan time counts while any fan spins at or above this (default `reports.fan_rpm_threshold`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_rpm_threshold: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct TimeWindow {
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Object, Default)]
+pub struct CurveCompareResult {
+    /// Profile name, or `window A`/`window B`
+    pub label: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub samples: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_temp_c: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_temp_c: Option<i32>,
+    /// Seconds any fan spun at or above the RPM threshold
+    pub fan_secs_above_threshold: f32,
+    /// Mean RPM of the fastest fan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_rpm: Option<u32>,
+    /// Rough noise level estimated from fan RPM (energy average over the window), not a measurement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub est_noise_dba: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct CurveCompareReport {
+    pub a: CurveCompareResult,
+    pub b: CurveCompareResult,
+    pub fan_rpm_threshold: u32,
+    /// B minus A
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_avg_temp_c: Option<f32>,
+    /// B minus A
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_noise_dba: Option<f32>,
+    pub summary: String,
+}
+
+/// `POST /fan/curve-wizard`
+#[derive(Debug, Clone, Deserialize, Object)]
+pub struct CurveWizardRequest {
+    /// Ceiling the proposed curve should hold the sensors under at full load
+    pub max_temp_c: u32,
+    /// Sensors or zones measured, hottest wins; empty means every sensor. The proposed curve
+    /// follows the same ones
+    #[serde(default)]
+    #[oai(default)]
+    pub sensors: Vec<String>,
+    /// Duties to measure, run from the highest down (default 100, 80, 60, 45, 30)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duties: Option<Vec<u32>>,
+    /// Longest a step may wait for the temperature to settle (60-900, default 240)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_secs: Option<u32>,
+    /// Worker threads for the built-in CPU load (defaults to all logical CPUs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveWizardPhase {
+    /// No run since the service started
+    #[oai(rename = "idle")]
+    Idle,
+    #[oai(rename = "running")]
+    Running,
+    /// All steps ran; see `proposal`
+    #[oai(rename = "finished")]
+    Finished,
+    #[oai(rename = "cancelled")]
+    Cancelled,
+    /// See `error`
+    #[oai(rename = "failed")]
+    Failed,
+}
+
+/// One measured duty level
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct CurveWizardStep {
+    pub duty_pct: u32,
+    /// Temperature the sensors settled at under load (mean of the last 30 s)
+    pub steady_temp_c: f32,
+    /// False when the step ran out of time before the temperature stopped moving
+    pub settled: bool,
+    /// The step was cut short because the temperature passed the ceiling by the trip margin
+    pub tripped: bool,
+    pub secs: u32,
+    /// Mean RPM of the fastest fan
+    #[oai(skip_serializing_if_is_none)]
+    pub avg_rpm: Option<u32>,
+    /// Rough noise level estimated from fan RPM, not a measurement
+    #[oai(skip_serializing_if_is_none)]
+    pub est_noise_dba: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct CurveWizardProposal {
+    /// Candidate `[temp_c, duty_pct]` points, ready for `fan.curve.points`
+    pub points: Vec<[u32; 2]>,
+    /// Sensors the curve follows
+    pub sensors: Vec<String>,
+    /// Lowest duty expected to hold the full load under the ceiling
+    pub hold_duty_pct: u32,
+    /// False when even the highest measured duty let the sensors pass the ceiling
+    pub meets_target: bool,
+    /// Estimated fan noise at `hold_duty_pct`, interpolated between the measured steps
+    #[oai(skip_serializing_if_is_none)]
+    pub est_noise_dba: Option<f32>,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize, Object)]
+pub struct CurveWizardStatus {
+    pub phase: CurveWizardPhase,
+    /// When the run started (unix ms)
+    #[oai(skip_serializing_if_is_none)]
+    pub started_ms: Option<i64>,
+    #[oai(skip_serializing_if_is_none)]
+    pub max_temp_c: Option<u32>,
+    /// Duties of the run, in the order they are measured
+    pub duties: Vec<u32>,
+    /// Duty of the step in progress
+    #[oai(skip_serializing_if_is_none)]
+    pub current_duty_pct: Option<u32>,
+    /// Latest reading of the sensors during the step in progress
+    #[oai(skip_serializing_if_is_none)]
+    pub current_temp_c: Option<i32>,
+    /// Finished steps, in the order they ran
+    pub steps: Vec<CurveWizardStep>,
+    #[oai(skip_serializing_if_is_none)]
+    pub proposal: Option<CurveWizardProposal>,
+    #[oai(skip_serializing_if_is_none)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum SelftestStatus {
+    #[oai(rename = "pass")]
+    Pass,
+    #[oai(rename = "fail")]
+    Fail,
+    /// Not applicable on this machine/platform (e.g. no RAPL zone)
+    #[oai(rename = "skip")]
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct SelftestCheck {
+    /// Control path exercised (e.g. "ec_read", "fan_write")
+    pub name: String,
+    pub status: SelftestStatus,
+    pub detail: String,
+    /// What to do about a failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<RemediationHint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct SelftestReport {
+    /// True when no check failed
+    pub passed: bool,
+    pub checks: Vec<SelftestCheck>,
+}
+
+// Tray companion bridge (`bridge.rs`)
+/// Compact snapshot for a tray icon and tooltip.
+#[derive(Debug, Clone, Object)]
+pub struct BridgeState {
+    /// Changes whenever a discrete field below changes (not temperatures or RPMs); pass it back
+    /// as `since` to wait for the next change
+    pub version: String,
+    pub config_revision: u64,
+    pub fan_mode: Option<FanControlMode>,
+    pub fan_rpms: Vec<u32>,
+    pub max_temp_c: Option<i32>,
+    pub hottest_sensor: Option<String>,
+    /// Display name of `hottest_sensor`
+    pub hottest_sensor_label: Option<String>,
+    pub battery_pct: Option<u32>,
+    pub ac_present: Option<bool>,
+    pub charging: Option<bool>,
+    /// Highest severity among the active alerts
+    pub alert_severity: Option<AlertSeverity>,
+    pub alert_count: u32,
+    pub safe_mode: bool,
+}
+
+/// One tray menu entry: POST `patch` (with the current `revision`) to `/config` to run it.
+#[derive(Debug, Clone, Object)]
+pub struct QuickAction {
+    pub id: String,
+    pub label: String,
+    /// The action's setting is what the config holds now (a checked menu item)
+    pub active: bool,
+    /// Ask the user first, then post the patch with `confirm=true` (e.g. turning the charge
+    /// limit off)
+    pub confirm: bool,
+    pub patch: PartialConfig,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BridgeActions {
+    /// Config revision the patches were derived from
+    pub revision: u64,
+    pub actions: Vec<QuickAction>,
+}
+
+// Firmware update awareness (`firmware.rs`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum FirmwareComponent {
+    #[oai(rename = "bios")]
+    Bios,
+    #[oai(rename = "ec")]
+    Ec,
+}
+
+/// One release in the firmware feed, which is `{"releases": [FirmwareRelease, ...]}`.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct FirmwareRelease {
+    /// Matched case-insensitively against the mainboard type from `--versions`
+    /// (e.g. "Laptop 13 (AMD Ryzen 7040Series)")
+    pub mainboard: String,
+    pub component: FirmwareComponent,
+    pub version: String,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released: Option<String>,
+    /// Release notes / download page
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Object, Default)]
+pub struct FirmwareUpdates {
+    pub mainboard: Option<String>,
+    pub bios_version: Option<String>,
+    pub ec_version: Option<String>,
+    /// Newest release per component that is newer than the installed one
+    pub updates: Vec<FirmwareRelease>,
+    /// Time of the last check (unix ms); `None` until one has run
+    pub checked_at_ms: Option<i64>,
+    /// Why the last check failed (no feed configured, feed unreachable, framework_tool missing)
+    pub error: Option<String>,
+}
+
+// Error report types
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorReportKind {
+    #[oai(rename = "panic")]
+    Panic,
+    /// The same warning or error logged repeatedly
+    #[oai(rename = "repeated_error")]
+    RepeatedError,
+}
+
+/// Machine description attached to error reports (no user or host names)
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ErrorFingerprint {
+    pub version: String,
+    pub os: String,
+    pub kernel: String,
+    pub arch: String,
+    pub cpu: String,
+    pub mainboard: Option<String>,
+    pub bios_version: Option<String>,
+    pub ec_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct ErrorReport {
+    pub id: String,
+    pub kind: ErrorReportKind,
+    pub created_ms: i64,
+    /// Panic message or the repeated log line, with user and host names scrubbed
+    pub message: String,
+    /// Source location (`file:line`)
+    pub location: Option<String>,
+    /// Occurrences within the window, for repeated errors
+    pub count: Option<u32>,
+    pub fingerprint: ErrorFingerprint,
+    /// Saved config without `network`, `logging` and URLs
+    pub config: serde_json::Value,
+    /// Uploaded to `logging.error_report_url`
+    pub sent: bool,
+}
+
+// Alert types
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    #[oai(rename = "info")]
+    Info,
+    #[oai(rename = "warning")]
+    Warning,
+    #[oai(rename = "critical")]
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct Alert {
+    /// Stable identifier of the condition (e.g. "weak_charger")
+    pub id: String,
+    pub severity: AlertSeverity,
+    pub message: String,
+    /// When the condition was first detected (unix ms)
+    pub since_ms: i64,
+    /// `since_ms` as local time, formatted per `ui.clock_format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
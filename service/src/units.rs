@@ -0,0 +1,49 @@
+// User-facing unit preferences (`ui.temperature_unit`, `ui.clock_format`) for strings the
+// service formats itself: alert timestamps, diagnostics details, exports. Numeric API fields
+// stay in canonical units (Celsius, unix ms) so clients can convert on their own.
+
+use chrono::{Local, TimeZone};
+
+use crate::types::{ClockFormat, TemperatureUnit, UiConfig};
+
+fn convert_temp(celsius: f32, unit: TemperatureUnit) -> f32 {
+    match unit {
+        TemperatureUnit::Celsius => celsius,
+        TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+    }
+}
+
+/// e.g. "72 °C" or "162 °F" (rounded to whole degrees).
+pub fn format_temp(celsius: f32, ui: &UiConfig) -> String {
+    let unit = ui.temperature_unit.unwrap_or_default();
+    let symbol = match unit {
+        TemperatureUnit::Celsius => "°C",
+        TemperatureUnit::Fahrenheit => "°F",
+    };
+    format!("{:.0} {}", convert_temp(celsius, unit), symbol)
+}
+
+/// Local date and time of a unix-ms timestamp, e.g. "2025-03-01 14:05:09" or "2025-03-01 2:05:09 PM".
+pub fn format_timestamp(ts_ms: i64, ui: &UiConfig) -> String {
+    let Some(dt) = Local.timestamp_millis_opt(ts_ms).single() else {
+        return ts_ms.to_string();
+    };
+    match ui.clock_format.unwrap_or_default() {
+        ClockFormat::H24 => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ClockFormat::H12 => dt.format("%Y-%m-%d %-I:%M:%S %p").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temps_follow_unit_preference() {
+        let mut ui = UiConfig::default();
+        assert_eq!(format_temp(72.4, &ui), "72 °C");
+        ui.temperature_unit = Some(TemperatureUnit::Fahrenheit);
+        assert_eq!(format_temp(100.0, &ui), "212 °F");
+        assert_eq!(format_temp(-40.0, &ui), "-40 °F");
+    }
+}
@@ -4,6 +4,7 @@ use tracing::{error, info};
 pub fn parse_github_repo_env() -> Option<(String, String)> {
     let repo = std::env::var("FRAMEWORK_CONTROL_UPDATE_REPO")
         .ok()
+        .or_else(crate::bootstrap::update_repo)
         .or_else(|| option_env!("FRAMEWORK_CONTROL_UPDATE_REPO").map(String::from))?;
     if repo.contains('/') && !repo.contains("github.com") {
         let mut it = repo.splitn(2, '/');
@@ -64,7 +65,7 @@ async fn extract_and_replace_binary(tarball_url: &str) -> Result<(), String> {
 
     // Download and extract tarball (download_to_path handles tar.gz extraction automatically)
     let extracted_dir =
-        crate::utils::download::download_to_path(tarball_url, &tmp_dir.to_string_lossy().to_string()).await?;
+        crate::utils::download::download_to_path(tarball_url, tmp_dir.to_string_lossy().as_ref()).await?;
 
     // Find the extracted binary
     let extracted_binary = std::path::Path::new(&extracted_dir).join("framework-control");
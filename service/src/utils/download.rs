@@ -29,7 +29,7 @@ async fn download_raw_to_file(url: &str, dest_file_path: &str) -> Result<(), Str
         use tokio::io::AsyncWriteExt;
         file.flush().await.map_err(|e| format!("flush failed: {e}"))?;
     }
-    if let Ok(meta) = std::fs::metadata(&dest_file_path) {
+    if let Ok(meta) = std::fs::metadata(dest_file_path) {
         info!("downloaded size: {} bytes", meta.len());
     }
     Ok(())
@@ -46,7 +46,7 @@ pub async fn download_to_path(url: &str, root_dir: &str) -> Result<String, Strin
 
     // Ensure root directory exists
     let root_dir_p = std::path::Path::new(root_dir);
-    let _ = std::fs::create_dir_all(&root_dir_p);
+    let _ = std::fs::create_dir_all(root_dir_p);
 
     // Derive filename from URL (strip query string if present)
     let url_last = url.rsplit('/').next().unwrap_or("download.bin");
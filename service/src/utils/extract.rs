@@ -85,7 +85,7 @@ async fn peek_archive_for_suffixes(
     tmp_dir: &std::path::Path,
     preferred_suffixes: &[&str],
 ) -> Result<bool, String> {
-    let extract_dir_s = crate::utils::download::download_to_path(url, &tmp_dir.to_string_lossy().to_string()).await?;
+    let extract_dir_s = crate::utils::download::download_to_path(url, tmp_dir.to_string_lossy().as_ref()).await?;
     let extract_dir = std::path::Path::new(&extract_dir_s).to_path_buf();
     // walk and find preferred
     let mut stack = vec![extract_dir];
@@ -4,11 +4,13 @@ use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, RwLock};
 
+type CacheMap = HashMap<String, (Arc<dyn Any + Send + Sync>, Instant)>;
+
 struct CacheState {
-    values: RwLock<HashMap<String, (Arc<dyn Any + Send + Sync>, Instant)>>,
+    values: RwLock<CacheMap>,
     // Separate store for negative (error) cache entries. We keep it distinct to
     // preserve type expectations of callers that only cache successful values.
-    error_values: RwLock<HashMap<String, (Arc<dyn Any + Send + Sync>, Instant)>>,
+    error_values: RwLock<CacheMap>,
     locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
 }
 
@@ -50,6 +52,7 @@ where
         if let Some((arc_any, ts)) = values.get(key) {
             if ts.elapsed() < ttl {
                 if let Some(v) = arc_any.as_ref().downcast_ref::<T>() {
+                    crate::metrics::record_cache(key, true);
                     return Ok(v.clone());
                 }
             }
@@ -61,6 +64,7 @@ where
         if let Some((arc_any, ts)) = error_values.get(key) {
             if ts.elapsed() < ttl {
                 if let Some(err) = arc_any.as_ref().downcast_ref::<E>() {
+                    crate::metrics::record_cache(key, true);
                     return Err(err.clone());
                 }
             }
@@ -77,6 +81,7 @@ where
         if let Some((arc_any, ts)) = values.get(key) {
             if ts.elapsed() < ttl {
                 if let Some(v) = arc_any.as_ref().downcast_ref::<T>() {
+                    crate::metrics::record_cache(key, true);
                     return Ok(v.clone());
                 }
             }
@@ -87,6 +92,7 @@ where
         if let Some((arc_any, ts)) = error_values.get(key) {
             if ts.elapsed() < ttl {
                 if let Some(err) = arc_any.as_ref().downcast_ref::<E>() {
+                    crate::metrics::record_cache(key, true);
                     return Err(err.clone());
                 }
             }
@@ -94,6 +100,7 @@ where
     }
 
     // Refresh via factory
+    crate::metrics::record_cache(key, false);
     match factory().await {
         Ok(value) => {
             // On success, replace success cache and clear any error cache
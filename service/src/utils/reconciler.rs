@@ -94,6 +94,13 @@ where
         }
     }
 
+    /// Forget the last applied target so the next reconcile applies immediately, bypassing the
+    /// quiet window and cooldown (e.g. after resume, when firmware may have reset the value).
+    pub fn force_reapply(&mut self) {
+        self.state.warmed_up = true;
+        self.state.last_target = None;
+    }
+
     pub async fn reconcile(&mut self, enabled: bool, target: Option<T>, io: &dyn SettingIo<T>) -> ReconcileOutcome {
         let now = Instant::now();
 
@@ -134,10 +141,7 @@ where
             }
         }
 
-        let current = match io.read_current().await {
-            Ok(v) => v,
-            Err(_) => None,
-        };
+        let current = io.read_current().await.unwrap_or_default();
 
         if let Some(ref cur) = current {
             match self.state.last_observed.as_ref() {
@@ -0,0 +1,922 @@
+// Server-side validation for `POST /config`. Values are checked after merging, per provided
+// section, so a bad request is rejected as a whole (422 with one entry per offending field)
+// instead of being persisted and then applied by the background tasks.
+
+use std::collections::BTreeMap;
+
+use crate::tasks::fan_schedule::parse_hhmm;
+use crate::types::{
+    BackupConfig, BatteryConfig, CurveCompareRequest, CurveConfig, CurveFitRequest, CurveWizardRequest,
+    FanControlConfig, FanControlMode, FanOverrideRequest, FanRampRequest, FieldError, KeyboardBacklightConfig,
+    LoggingConfig, ManualConfig, NetworkConfig, PartialConfig, PowerCapabilities, PowerConfig, PowerProfile,
+    ReportsConfig, SensorsConfig, TelemetryConfig, UpdatesConfig,
+};
+
+pub const MIN_POLL_MS: u64 = 200;
+pub const MAX_POLL_MS: u64 = 5000;
+pub const CHARGE_LIMIT_MIN_PCT: u8 = 25;
+pub const CHARGE_LIMIT_MAX_PCT: u8 = 100;
+pub const CHARGE_RATE_MIN_C: f32 = 0.05;
+pub const CHARGE_RATE_MAX_C: f32 = 1.0;
+pub const THERMAL_LIMIT_MIN_C: u32 = 50;
+pub const THERMAL_LIMIT_MAX_C: u32 = 100;
+const CURVE_MAX_TEMP_C: u32 = 110;
+/// Far more than any hand-drawn curve needs; keeps config files and the editor manageable
+const MAX_CURVE_POINTS: usize = 32;
+/// Trims beyond these stop being a trim: the curve itself is the place for that
+const DUTY_OFFSET_MAX_PCT: i32 = 50;
+const DUTY_SCALE_MIN: f32 = 0.5;
+const DUTY_SCALE_MAX: f32 = 1.5;
+/// Longer delays would keep a fan running long after the machine has gone idle
+const STOP_DELAY_MAX_S: u32 = 600;
+/// Lower targets would just pin the fans at full speed
+const TARGET_MIN_C: u32 = 40;
+const HYSTERESIS_MAX_C: u32 = 10;
+const WAKE_LEAD_MAX_MIN: u32 = 120;
+const AUTO_TUNE_MAX_OFFSET_PCT: u32 = 50;
+const AUTO_TUNE_MIN_TRIAL_MIN: u32 = 15;
+const AUTO_TUNE_MAX_TRIALS: u32 = 500;
+/// Duty levels one curve wizard run may measure
+const CURVE_WIZARD_MAX_STEPS: usize = 8;
+/// Above any laptop or case fan Framework ships
+const RPM_MAX: u32 = 10_000;
+const SENSOR_WEIGHT_MAX: f32 = 100.0;
+/// Sensors jitter by a degree between polls; slower thresholds would boost on noise
+const BOOST_RISE_MIN_C_PER_S: f32 = 0.5;
+const BOOST_RISE_MAX_C_PER_S: f32 = 20.0;
+const BOOST_DURATION_MAX_S: u32 = 300;
+/// Room temperatures a machine is plausibly used in
+const AMBIENT_REFERENCE_MIN_C: i32 = 0;
+const AMBIENT_REFERENCE_MAX_C: i32 = 50;
+const AMBIENT_SHIFT_PER_C_MAX: f32 = 2.0;
+/// A larger shift would move most curves off the sensors' working range
+const AMBIENT_MAX_SHIFT_C: u32 = 30;
+/// SCHEDPOLICY indexes run from 0 (all processors) to 5 (automatic)
+const SCHED_POLICY_MAX: u32 = 5;
+const STANDBY_TIMEOUT_MAX_MIN: u32 = 24 * 60;
+const SENSOR_THRESHOLD_MIN_C: i32 = 20;
+const SENSOR_THRESHOLD_MAX_C: i32 = 150;
+
+#[derive(Default)]
+pub struct Errors(Vec<FieldError>);
+
+impl Errors {
+    fn push(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    fn check<T: PartialOrd + std::fmt::Display + Copy>(&mut self, field: &str, value: T, min: T, max: T) {
+        if value < min || value > max {
+            self.push(field, format!("{} is outside {}..={}", value, min, max));
+        }
+    }
+
+    pub fn into_result(self) -> Result<(), Vec<FieldError>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+pub fn fan(errs: &mut Errors, fan: &FanControlConfig) {
+    if let Some(m) = &fan.manual {
+        manual(errs, "fan.manual", m);
+    }
+    if let Some(c) = &fan.curve {
+        curve(errs, "fan.curve", &c.curve);
+        errs.check("fan.curve.poll_ms", c.poll_ms, MIN_POLL_MS, MAX_POLL_MS);
+    }
+    for (name, c) in fan.profiles.iter().flatten() {
+        if name.trim().is_empty() {
+            errs.push("fan.profiles", "profile names must not be empty");
+        }
+        curve(errs, &format!("fan.profiles.{}", name), &c.curve);
+        errs.check(
+            &format!("fan.profiles.{}.poll_ms", name),
+            c.poll_ms,
+            MIN_POLL_MS,
+            MAX_POLL_MS,
+        );
+    }
+    if let Some(name) = &fan.active_profile {
+        if !crate::fan_profiles::exists(fan, name) {
+            errs.push("fan.active_profile", format!("no fan profile named '{}'", name));
+        }
+    }
+    if let Some(s) = &fan.schedule {
+        let known = |name: &str| crate::fan_profiles::exists(fan, name);
+        for (i, r) in s.rules.iter().enumerate() {
+            let field = format!("fan.schedule.rules[{}]", i);
+            if !known(&r.profile) {
+                errs.push(
+                    format!("{}.profile", field),
+                    format!("no fan profile named '{}'", r.profile),
+                );
+            }
+            let start = parse_hhmm(&r.start);
+            let end = parse_hhmm(&r.end);
+            if start.is_none() {
+                errs.push(format!("{}.start", field), "expected a local time as HH:MM");
+            }
+            if end.is_none() {
+                errs.push(format!("{}.end", field), "expected a local time as HH:MM");
+            }
+            if start.is_some() && start == end {
+                errs.push(format!("{}.end", field), "must differ from start");
+            }
+            if r.sleep_after && !r.wake {
+                errs.push(format!("{}.sleep_after", field), "only applies to rules with wake");
+            }
+        }
+        if let Some(lead) = s.wake_lead_minutes {
+            errs.check("fan.schedule.wake_lead_minutes", lead, 1, WAKE_LEAD_MAX_MIN);
+        }
+        if let Some(name) = s.default_profile.as_deref().filter(|n| !known(n)) {
+            errs.push(
+                "fan.schedule.default_profile",
+                format!("no fan profile named '{}'", name),
+            );
+        }
+    }
+    if let Some(t) = &fan.target {
+        errs.check("fan.target.target_c", t.target_c, TARGET_MIN_C, CURVE_MAX_TEMP_C);
+        errs.check("fan.target.max_duty_pct", t.max_duty_pct, 1, 100);
+        errs.check("fan.target.min_duty_pct", t.min_duty_pct, 0, t.max_duty_pct.min(100));
+        errs.check("fan.target.poll_ms", t.poll_ms, MIN_POLL_MS, MAX_POLL_MS);
+    } else if fan.mode == Some(FanControlMode::Target) {
+        errs.push("fan.target", "target mode needs a target temperature");
+    }
+    if let Some(r) = &fan.rpm {
+        rpm_points(errs, "fan.rpm.points", &r.points);
+        errs.check("fan.rpm.hysteresis_c", r.hysteresis_c, 0, HYSTERESIS_MAX_C);
+        errs.check("fan.rpm.poll_ms", r.poll_ms, MIN_POLL_MS, MAX_POLL_MS);
+        for (name, w) in r.weights.iter().flatten() {
+            errs.check(&format!("fan.rpm.weights.{}", name), *w, 0.0, SENSOR_WEIGHT_MAX);
+        }
+        let mut seen = std::collections::BTreeSet::new();
+        for (i, f) in r.fans.iter().flatten().enumerate() {
+            if !seen.insert(f.index) {
+                errs.push(
+                    format!("fan.rpm.fans[{}].index", i),
+                    format!("fan {} already has points", f.index),
+                );
+            }
+            rpm_points(errs, &format!("fan.rpm.fans[{}].points", i), &f.points);
+        }
+    } else if fan.mode == Some(FanControlMode::Rpm) {
+        errs.push("fan.rpm", "rpm mode needs an RPM curve");
+    }
+    for (i, o) in fan.overrides.iter().flatten().enumerate() {
+        if let Some(m) = &o.manual {
+            manual(errs, &format!("fan.overrides[{}].manual", i), m);
+        }
+        if let Some(c) = &o.curve {
+            curve(errs, &format!("fan.overrides[{}].curve", i), c);
+        }
+    }
+    let mut offset = std::collections::BTreeSet::new();
+    for (i, o) in fan.duty_offsets.iter().flatten().enumerate() {
+        if !offset.insert(o.index) {
+            errs.push(
+                format!("fan.duty_offsets[{}].index", i),
+                format!("fan {} already has an offset", o.index),
+            );
+        }
+        errs.check(
+            &format!("fan.duty_offsets[{}].offset_pct", i),
+            o.offset_pct,
+            -DUTY_OFFSET_MAX_PCT,
+            DUTY_OFFSET_MAX_PCT,
+        );
+        let scale_field = format!("fan.duty_offsets[{}].scale", i);
+        if o.scale.is_finite() && o.scale > 0.0 {
+            errs.check(&scale_field, o.scale, DUTY_SCALE_MIN, DUTY_SCALE_MAX);
+        } else {
+            errs.push(scale_field, format!("{} is not a positive number", o.scale));
+        }
+    }
+    let mut calibrated = std::collections::BTreeSet::new();
+    for (i, c) in fan.calibrations.iter().flatten().enumerate() {
+        match c.fan_index {
+            None => errs.push(
+                format!("fan.calibrations[{}].fan_index", i),
+                "per-fan tables need a fan_index",
+            ),
+            Some(f) if !calibrated.insert(f) => errs.push(
+                format!("fan.calibrations[{}].fan_index", i),
+                format!("fan {} already has a table", f),
+            ),
+            Some(_) => {}
+        }
+    }
+    if let Some(g) = &fan.gpu {
+        if matches!(g.mode, Some(FanControlMode::Target | FanControlMode::Rpm)) {
+            errs.push("fan.gpu.mode", "the dGPU fan supports disabled, manual and curve");
+        }
+        if let Some(d) = g.duty_pct {
+            errs.check("fan.gpu.duty_pct", d, 0, 100);
+        }
+        if let Some(c) = &g.curve {
+            curve(errs, "fan.gpu.curve", c);
+        }
+    }
+    if let Some(f) = &fan.failsafe {
+        errs.check("fan.failsafe.failed_reads", f.failed_reads, 1, 100);
+        // Below this the failsafe is no safer than the duty it replaces
+        errs.check("fan.failsafe.duty_pct", f.duty_pct, 30, 100);
+    }
+    if let Some(t) = &fan.auto_tune {
+        errs.check("fan.auto_tune.noise_weight", t.noise_weight, 0.0, 1.0);
+        errs.check("fan.auto_tune.max_temp_c", t.max_temp_c, TARGET_MIN_C, CURVE_MAX_TEMP_C);
+        errs.check(
+            "fan.auto_tune.max_offset_pct",
+            t.max_offset_pct,
+            1,
+            AUTO_TUNE_MAX_OFFSET_PCT,
+        );
+        // Shorter trials mostly measure how the last workload ended
+        errs.check(
+            "fan.auto_tune.trial_minutes",
+            t.trial_minutes,
+            AUTO_TUNE_MIN_TRIAL_MIN,
+            24 * 60,
+        );
+        errs.check("fan.auto_tune.trials", t.trials, 2, AUTO_TUNE_MAX_TRIALS);
+    }
+}
+
+fn manual(errs: &mut Errors, path: &str, m: &ManualConfig) {
+    errs.check(&format!("{}.duty_pct", path), m.duty_pct, 0, 100);
+    for (i, d) in m.fan_duties.iter().flatten().enumerate() {
+        errs.check(&format!("{}.fan_duties[{}].duty_pct", path, i), d.duty_pct, 0, 100);
+    }
+}
+
+/// `[temp_c, value]` points: at least one, no more than `MAX_CURVE_POINTS`, temperatures
+/// strictly increasing (each offender is reported at its own index).
+fn points(errs: &mut Errors, field: &str, points: &[[u32; 2]], value_max: u32) {
+    if points.is_empty() {
+        errs.push(field, "needs at least one point");
+    }
+    if points.len() > MAX_CURVE_POINTS {
+        errs.push(field, format!("at most {} points", MAX_CURVE_POINTS));
+    }
+    for (i, p) in points.iter().enumerate() {
+        errs.check(&format!("{}[{}][0]", field, i), p[0], 0, CURVE_MAX_TEMP_C);
+        errs.check(&format!("{}[{}][1]", field, i), p[1], 0, value_max);
+        if i > 0 && p[0] <= points[i - 1][0] {
+            errs.push(
+                format!("{}[{}][0]", field, i),
+                format!("must be above the previous point's {} °C", points[i - 1][0]),
+            );
+        }
+    }
+}
+
+fn rpm_points(errs: &mut Errors, field: &str, pts: &[[u32; 2]]) {
+    points(errs, field, pts, RPM_MAX);
+}
+
+fn curve(errs: &mut Errors, path: &str, c: &CurveConfig) {
+    points(errs, &format!("{}.points", path), &c.points, 100);
+    errs.check(
+        &format!("{}.hysteresis_up_c", path),
+        c.hysteresis_up_c,
+        0,
+        HYSTERESIS_MAX_C,
+    );
+    errs.check(
+        &format!("{}.hysteresis_down_c", path),
+        c.hysteresis_down_c,
+        0,
+        HYSTERESIS_MAX_C,
+    );
+    if let Some(h) = c.hysteresis_c {
+        errs.check(&format!("{}.hysteresis_c", path), h, 0, HYSTERESIS_MAX_C);
+    }
+    // A zero step would freeze the fan at whatever duty it had when the curve started.
+    errs.check(
+        &format!("{}.rate_limit_pct_per_step", path),
+        c.rate_limit_pct_per_step,
+        1,
+        100,
+    );
+    if let Some(stop) = c.stop_below_c {
+        errs.check(&format!("{}.stop_below_c", path), stop, 0, CURVE_MAX_TEMP_C);
+        if let Some(restart) = c.restart_at_c {
+            if restart <= stop {
+                errs.push(format!("{}.restart_at_c", path), "must be above stop_below_c");
+            }
+        }
+    }
+    if let Some(delay) = c.stop_delay_s {
+        errs.check(&format!("{}.stop_delay_s", path), delay, 0, STOP_DELAY_MAX_S);
+    }
+    if let Some(down) = c.rate_limit_down_pct_per_step {
+        errs.check(&format!("{}.rate_limit_down_pct_per_step", path), down, 1, 100);
+    }
+    for (name, w) in c.weights.iter().flatten() {
+        errs.check(&format!("{}.weights.{}", path, name), *w, 0.0, SENSOR_WEIGHT_MAX);
+    }
+    if let Some(b) = &c.boost {
+        errs.check(
+            &format!("{}.boost.rise_c_per_s", path),
+            b.rise_c_per_s,
+            BOOST_RISE_MIN_C_PER_S,
+            BOOST_RISE_MAX_C_PER_S,
+        );
+        errs.check(&format!("{}.boost.duty_pct", path), b.duty_pct, 1, 100);
+        errs.check(
+            &format!("{}.boost.duration_s", path),
+            b.duration_s,
+            1,
+            BOOST_DURATION_MAX_S,
+        );
+    }
+    if let Some(a) = &c.ambient {
+        if a.sensor.trim().is_empty() {
+            errs.push(format!("{}.ambient.sensor", path), "name the ambient sensor");
+        }
+        errs.check(
+            &format!("{}.ambient.reference_c", path),
+            a.reference_c,
+            AMBIENT_REFERENCE_MIN_C,
+            AMBIENT_REFERENCE_MAX_C,
+        );
+        errs.check(
+            &format!("{}.ambient.shift_per_c", path),
+            a.shift_per_c,
+            -AMBIENT_SHIFT_PER_C_MAX,
+            AMBIENT_SHIFT_PER_C_MAX,
+        );
+        errs.check(
+            &format!("{}.ambient.max_shift_c", path),
+            a.max_shift_c,
+            0,
+            AMBIENT_MAX_SHIFT_C,
+        );
+    }
+}
+
+/// `caps` narrows TDP/frequency ranges and allowed EPP/governor names when the platform backend
+/// is available; without it only the generic bounds apply.
+pub fn power(errs: &mut Errors, power: &PowerConfig, caps: Option<&PowerCapabilities>) {
+    if let Some(kind) = power.backend {
+        if !crate::cli::power_backend::available(kind) {
+            errs.push(
+                "power.backend",
+                format!("'{:?}' is not available on this platform", kind).to_lowercase(),
+            );
+        }
+    }
+    if let Some(p) = &power.ac {
+        profile(errs, "power.ac", p, caps);
+    }
+    if let Some(p) = &power.battery {
+        profile(errs, "power.battery", p, caps);
+        if p.lift_battery_limits == Some(true) {
+            errs.push("power.battery.lift_battery_limits", "only applies to the AC profile");
+        }
+    }
+    for (i, c) in power.chargers.iter().flatten().enumerate() {
+        let path = format!("power.chargers[{}]", i);
+        if let (Some(min), Some(max)) = (c.min_watts, c.max_watts) {
+            if min > max {
+                errs.push(format!("{}.min_watts", path), "must not exceed max_watts");
+            }
+        }
+        profile(errs, &format!("{}.profile", path), &c.profile, caps);
+        if c.profile.lift_battery_limits.is_some() {
+            errs.push(
+                format!("{}.profile.lift_battery_limits", path),
+                "set it on power.ac; it applies for any charger",
+            );
+        }
+    }
+    if let Some(b) = &power.budget {
+        errs.check("power.budget.total_watts", b.total_watts, 20, 300);
+        let min_cpu = b.min_cpu_watts.unwrap_or(0);
+        let min_gpu = b.min_gpu_watts.unwrap_or(0);
+        if min_cpu + min_gpu > b.total_watts {
+            errs.push(
+                "power.budget.min_cpu_watts",
+                "min_cpu_watts and min_gpu_watts together exceed total_watts",
+            );
+        }
+        if let Some(i) = b.interval_s {
+            errs.check("power.budget.interval_s", i, 2, 60);
+        }
+        if b.enabled && caps.is_some_and(|c| !c.supports_tdp) {
+            errs.push("power.budget.enabled", "the power backend cannot set the CPU TDP");
+        }
+    }
+}
+
+fn profile(errs: &mut Errors, path: &str, p: &PowerProfile, caps: Option<&PowerCapabilities>) {
+    let tdp_min = caps.and_then(|c| c.tdp_min_watts).unwrap_or(1);
+    let tdp_max = caps.and_then(|c| c.tdp_max_watts).unwrap_or(u32::MAX);
+    let limits = [
+        ("tdp_watts", &p.tdp_watts),
+        ("stapm_watts", &p.stapm_watts),
+        ("fast_limit_watts", &p.fast_limit_watts),
+        ("slow_limit_watts", &p.slow_limit_watts),
+    ];
+    for (field, setting) in limits {
+        if let Some(s) = setting {
+            errs.check(&format!("{}.{}", path, field), s.value, tdp_min, tdp_max);
+        }
+    }
+    if let Some(s) = &p.thermal_limit_c {
+        errs.check(
+            &format!("{}.thermal_limit_c", path),
+            s.value,
+            THERMAL_LIMIT_MIN_C,
+            THERMAL_LIMIT_MAX_C,
+        );
+    }
+    let freq_min = caps.and_then(|c| c.frequency_min_mhz).unwrap_or(0);
+    let freq_max = caps.and_then(|c| c.frequency_max_mhz).unwrap_or(u32::MAX);
+    if let Some(s) = &p.min_freq_mhz {
+        errs.check(&format!("{}.min_freq_mhz", path), s.value, freq_min, freq_max);
+    }
+    if let Some(s) = &p.max_freq_mhz {
+        errs.check(&format!("{}.max_freq_mhz", path), s.value, freq_min, freq_max);
+    }
+    if let (Some(lo), Some(hi)) = (&p.min_freq_mhz, &p.max_freq_mhz) {
+        if lo.enabled && hi.enabled && lo.value > hi.value {
+            errs.push(
+                format!("{}.min_freq_mhz", path),
+                format!("{} is above max_freq_mhz {}", lo.value, hi.value),
+            );
+        }
+    }
+    let allowed = |list: Option<&Vec<String>>, value: &str| list.is_none_or(|l| l.iter().any(|v| v == value));
+    if let Some(s) = &p.epp_preference {
+        if !allowed(caps.and_then(|c| c.available_epp_preferences.as_ref()), &s.value) {
+            errs.push(
+                format!("{}.epp_preference", path),
+                format!("'{}' is not offered by this system", s.value),
+            );
+        }
+    }
+    if let Some(s) = &p.governor {
+        if !allowed(caps.and_then(|c| c.available_governors.as_ref()), &s.value) {
+            errs.push(
+                format!("{}.governor", path),
+                format!("'{}' is not offered by this system", s.value),
+            );
+        }
+    }
+    if let Some(s) = &p.scheduler_hints {
+        let hints = &s.value;
+        let field = |name: &str| format!("{}.scheduler_hints.value.{}", path, name);
+        if let Some(v) = hints.heterogeneous_policy {
+            errs.check(&field("heterogeneous_policy"), v, 0, SCHED_POLICY_MAX);
+        }
+        if let Some(v) = hints.processor_min_pct {
+            errs.check(&field("processor_min_pct"), v, 0, 100);
+        }
+        if let Some(v) = hints.processor_max_pct {
+            errs.check(&field("processor_max_pct"), v, 1, 100);
+        }
+        if let (Some(lo), Some(hi)) = (hints.processor_min_pct, hints.processor_max_pct) {
+            if lo > hi {
+                errs.push(
+                    field("processor_min_pct"),
+                    format!("{} is above processor_max_pct {}", lo, hi),
+                );
+            }
+        }
+        if let Some(v) = hints.standby_timeout_min {
+            errs.check(&field("standby_timeout_min"), v, 0, STANDBY_TIMEOUT_MAX_MIN);
+        }
+    }
+}
+
+pub fn battery(errs: &mut Errors, bat: &BatteryConfig) {
+    if let Some(s) = &bat.charge_limit_max_pct {
+        errs.check(
+            "battery.charge_limit_max_pct",
+            s.value,
+            CHARGE_LIMIT_MIN_PCT,
+            CHARGE_LIMIT_MAX_PCT,
+        );
+    }
+    if let Some(s) = &bat.charge_rate_c {
+        errs.check("battery.charge_rate_c", s.value, CHARGE_RATE_MIN_C, CHARGE_RATE_MAX_C);
+    }
+    if let Some(t) = bat.charge_rate_soc_threshold_pct {
+        errs.check("battery.charge_rate_soc_threshold_pct", t, 0, 100);
+    }
+    if let Some(s) = &bat.storage_mode {
+        errs.check("battery.storage_mode.target_pct", s.target_pct, 30, 80);
+        errs.check("battery.storage_mode.band_pct", s.band_pct, 2, 15);
+        if s.target_pct.saturating_sub(s.band_pct) < CHARGE_LIMIT_MIN_PCT {
+            errs.push(
+                "battery.storage_mode.band_pct",
+                format!(
+                    "the band must stay above the {}% minimum charge limit",
+                    CHARGE_LIMIT_MIN_PCT
+                ),
+            );
+        }
+    }
+}
+
+pub fn telemetry(errs: &mut Errors, tel: &TelemetryConfig) {
+    errs.check("telemetry.poll_ms", tel.poll_ms, MIN_POLL_MS, u64::MAX);
+    if tel.retain_seconds == 0 {
+        errs.push("telemetry.retain_seconds", "must be greater than 0");
+    }
+}
+
+pub fn keyboard_backlight(errs: &mut Errors, kb: &KeyboardBacklightConfig) {
+    for (i, [_, pct]) in kb.points.iter().enumerate() {
+        errs.check(&format!("keyboard_backlight.points[{}][1]", i), *pct, 0, 100);
+    }
+    if let Some(cap) = kb.battery_max_pct {
+        errs.check("keyboard_backlight.battery_max_pct", cap, 0, 100);
+    }
+}
+
+pub fn updates(errs: &mut Errors, u: &UpdatesConfig) {
+    if let Some(url) = &u.firmware_feed_url {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            errs.push("updates.firmware_feed_url", "must be an http:// or https:// URL");
+        }
+    }
+}
+
+pub fn reports(errs: &mut Errors, r: &ReportsConfig) {
+    if let Some(url) = &r.webhook_url {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            errs.push("reports.webhook_url", "must be an http:// or https:// URL");
+        }
+    }
+    errs.check("reports.fan_rpm_threshold", r.fan_rpm_threshold, 500, 10_000);
+    errs.check(
+        "reports.throttle_temp_c",
+        r.throttle_temp_c,
+        THERMAL_LIMIT_MIN_C,
+        THERMAL_LIMIT_MAX_C,
+    );
+}
+
+pub fn backup(errs: &mut Errors, b: &BackupConfig) {
+    if let Some(dir) = &b.dir {
+        if !std::path::Path::new(dir).is_absolute() {
+            errs.push("backup.dir", "must be an absolute path");
+        }
+    }
+    errs.check("backup.retention", b.retention, 1, 365);
+    errs.check("backup.hour", b.hour, 0, 23);
+}
+
+pub fn network(errs: &mut Errors, n: &NetworkConfig) {
+    if let Some(port) = n.port {
+        errs.check("network.port", port, 1024, u16::MAX);
+    }
+    for (i, origin) in n.allowed_origins.iter().flatten().enumerate() {
+        let host = origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"));
+        let valid = host.is_some_and(|h| !h.is_empty() && !h.contains('/') && !h.contains(char::is_whitespace));
+        if !valid {
+            errs.push(
+                format!("network.allowed_origins[{}]", i),
+                "must be an origin like http://localhost:5173 (scheme and host, no path)",
+            );
+        }
+    }
+    if let Some(base) = n.base_path.as_deref().map(|b| b.trim().trim_end_matches('/')) {
+        let allowed = |c: char| c.is_ascii_alphanumeric() || "/-_.~".contains(c);
+        if !base.is_empty()
+            && (!base.starts_with('/') || base.contains("..") || base.contains("//") || !base.chars().all(allowed))
+        {
+            errs.push(
+                "network.base_path",
+                "must be a path like /framework (letters, digits, '-', '_', '.', '~' and '/')",
+            );
+        } else if base == "/api" || base.starts_with("/api/") {
+            errs.push("network.base_path", "must not be under /api");
+        }
+    }
+}
+
+/// Thresholds are also checked against the board/kind values they are layered over, so an
+/// override cannot put the warn level at or above the effective critical one.
+pub fn sensors(errs: &mut Errors, s: &SensorsConfig) {
+    zones(errs, &s.zones);
+    let (board, _) = crate::sensors::metadata::board();
+    for (name, o) in &s.overrides {
+        let field = format!("sensors.overrides.{}", name);
+        if name.trim().is_empty() {
+            errs.push("sensors.overrides", "sensor names must not be empty");
+            continue;
+        }
+        if let Some(w) = o.warn_c {
+            errs.check(
+                &format!("{}.warn_c", field),
+                w,
+                SENSOR_THRESHOLD_MIN_C,
+                SENSOR_THRESHOLD_MAX_C,
+            );
+        }
+        if let Some(c) = o.critical_c {
+            errs.check(
+                &format!("{}.critical_c", field),
+                c,
+                SENSOR_THRESHOLD_MIN_C,
+                SENSOR_THRESHOLD_MAX_C,
+            );
+        }
+        if o.latency_ms == Some(0) {
+            errs.push(format!("{}.latency_ms", field), "must be greater than 0");
+        }
+        let resolved = crate::sensors::metadata::resolve(board, name, s);
+        if let (Some(w), Some(c)) = (resolved.warn_c, resolved.critical_c) {
+            if w >= c {
+                errs.push(
+                    format!("{}.warn_c", field),
+                    format!("warn threshold {} must be below the critical threshold {}", w, c),
+                );
+            }
+        }
+    }
+}
+
+fn zones(errs: &mut Errors, zones: &BTreeMap<String, Vec<String>>) {
+    for (name, members) in zones {
+        if name.trim().is_empty() {
+            errs.push("sensors.zones", "zone names must not be empty");
+            continue;
+        }
+        for (i, m) in members.iter().enumerate() {
+            if m.trim().is_empty() {
+                errs.push(
+                    format!("sensors.zones.{}[{}]", name, i),
+                    "sensor names must not be empty",
+                );
+            }
+        }
+    }
+}
+
+pub fn logging(errs: &mut Errors, l: &LoggingConfig) {
+    let target = l.forward_target.unwrap_or_default();
+    if !crate::log_forward::target_available(target, l.syslog_address.is_some()) {
+        errs.push(
+            "logging.forward_target",
+            format!("'{:?}' is not available on this platform", target),
+        );
+    }
+    if let Some(address) = &l.syslog_address {
+        let valid = address
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok_and(|p| p > 0));
+        if !valid {
+            errs.push(
+                "logging.syslog_address",
+                "must be host:port (e.g. logs.example.com:514)",
+            );
+        }
+    }
+    if let Some(url) = &l.error_report_url {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            errs.push("logging.error_report_url", "must be an http:// or https:// URL");
+        }
+    }
+}
+
+/// Temporary overrides: bounded TTL, and no `network` (rebinding the server is not something to
+/// undo on a timer).
+/// Managed profiles are read-only: a write may not put one of their names in `fan.profiles`.
+pub fn managed_profiles(errs: &mut Errors, patch: &PartialConfig) {
+    let profiles = patch.fan.as_ref().and_then(|f| f.profiles.as_ref());
+    for name in profiles.into_iter().flat_map(|p| p.keys()) {
+        if crate::managed_profiles::is_managed(name) {
+            errs.push(
+                format!("fan.profiles.{}", name),
+                format!(
+                    "'{}' is a managed profile and read-only; it is provisioned from {}",
+                    name,
+                    crate::managed_profiles::dir().display()
+                ),
+            );
+        }
+    }
+}
+
+pub fn temporary(errs: &mut Errors, ttl_s: u64, touched: &[&str]) {
+    errs.check("ttl_s", ttl_s, 10, 24 * 60 * 60);
+    if touched.is_empty() {
+        errs.push("patch", "no config section to override");
+    }
+    if touched.contains(&"network") {
+        errs.push("patch.network", "cannot be overridden temporarily");
+    }
+}
+
+pub fn fan_override(errs: &mut Errors, req: &FanOverrideRequest) {
+    errs.check("duty_pct", req.duty_pct, 0, 100);
+    errs.check("duration_s", req.duration_s, 10, 24 * 60 * 60);
+}
+
+pub fn fan_ramp(errs: &mut Errors, req: &FanRampRequest) {
+    use crate::diagnostics::fan_ramp::{DEFAULT_FROM_DUTY_PCT, DEFAULT_TO_DUTY_PCT};
+    let from = req.from_duty_pct.unwrap_or(DEFAULT_FROM_DUTY_PCT);
+    let to = req.to_duty_pct.unwrap_or(DEFAULT_TO_DUTY_PCT);
+    errs.check("from_duty_pct", from, 0, 90);
+    errs.check("to_duty_pct", to, 10, 100);
+    if to < from + 10 {
+        errs.push("to_duty_pct", "must be at least 10 above from_duty_pct");
+    }
+}
+
+pub fn curve_fit(errs: &mut Errors, req: &CurveFitRequest) {
+    errs.check("target_max_temp_c", req.target_max_temp_c, 30, 100);
+    errs.check("max_duty_pct", req.max_duty_pct, 10, 100);
+}
+
+pub fn curve_wizard(errs: &mut Errors, req: &CurveWizardRequest) {
+    errs.check("max_temp_c", req.max_temp_c, TARGET_MIN_C, THERMAL_LIMIT_MAX_C);
+    if let Some(duties) = &req.duties {
+        if !(2..=CURVE_WIZARD_MAX_STEPS).contains(&duties.len()) {
+            errs.push("duties", format!("give 2 to {} duties", CURVE_WIZARD_MAX_STEPS));
+        }
+        for (i, &d) in duties.iter().enumerate() {
+            errs.check(&format!("duties[{}]", i), d, 10, 100);
+        }
+    }
+    if let Some(s) = req.step_secs {
+        errs.check("step_secs", s, 60, 900);
+    }
+}
+
+pub fn curve_compare(errs: &mut Errors, req: &CurveCompareRequest, fan: &FanControlConfig) {
+    let live = req.profile_a.is_some() || req.profile_b.is_some();
+    let history = req.window_a.is_some() || req.window_b.is_some();
+    if live == history {
+        errs.push(
+            "profile_a",
+            "give either profile_a and profile_b, or window_a and window_b",
+        );
+        return;
+    }
+    if live {
+        for (field, name) in [("profile_a", &req.profile_a), ("profile_b", &req.profile_b)] {
+            match name {
+                None => errs.push(field, "required for a live comparison"),
+                Some(n) if !crate::fan_profiles::exists(fan, n) => {
+                    errs.push(field, format!("no fan profile named '{}'", n))
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(d) = req.duration_secs {
+            errs.check("duration_secs", d, 30, 600);
+        }
+        if let Some(s) = req.settle_secs {
+            errs.check("settle_secs", s, 0, 300);
+        }
+    } else {
+        for (field, window) in [("window_a", &req.window_a), ("window_b", &req.window_b)] {
+            match window {
+                None => errs.push(field, "required for a historical comparison"),
+                Some(w) if w.start_ms >= w.end_ms => errs.push(format!("{}.end_ms", field), "must be after start_ms"),
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{GlobalCurveConfig, SettingU32};
+
+    fn fields(errs: Errors) -> Vec<String> {
+        errs.into_result()
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn rejects_dangerous_fan_values() {
+        let mut cfg = FanControlConfig {
+            manual: Some(ManualConfig {
+                duty_pct: 150,
+                fan_duties: None,
+            }),
+            curve: Some(GlobalCurveConfig {
+                curve: CurveConfig {
+                    points: vec![[40, 0], [80, 120]],
+                    rate_limit_pct_per_step: 0,
+                    ..Default::default()
+                },
+                poll_ms: 50,
+            }),
+            ..Default::default()
+        };
+        let mut errs = Errors::default();
+        fan(&mut errs, &cfg);
+        assert_eq!(
+            fields(errs),
+            vec![
+                "fan.manual.duty_pct",
+                "fan.curve.points[1][1]",
+                "fan.curve.rate_limit_pct_per_step",
+                "fan.curve.poll_ms",
+            ]
+        );
+
+        cfg.manual = None;
+        cfg.curve = Some(GlobalCurveConfig {
+            curve: CurveConfig {
+                points: vec![[40, 0], [85, 100]],
+                hysteresis_down_c: 2,
+                rate_limit_pct_per_step: 100,
+                ..Default::default()
+            },
+            poll_ms: 500,
+        });
+        let mut errs = Errors::default();
+        fan(&mut errs, &cfg);
+        assert!(errs.into_result().is_ok());
+    }
+
+    #[test]
+    fn curve_points_must_increase_and_stay_bounded() {
+        let mut errs = Errors::default();
+        points(
+            &mut errs,
+            "fan.curve.points",
+            &[[40, 20], [60, 50], [60, 60], [55, 80]],
+            100,
+        );
+        assert_eq!(fields(errs), vec!["fan.curve.points[2][0]", "fan.curve.points[3][0]"]);
+
+        let many: Vec<[u32; 2]> = (0..40).map(|i| [i * 2, 50]).collect();
+        let mut errs = Errors::default();
+        points(&mut errs, "fan.curve.points", &many, 100);
+        assert_eq!(fields(errs), vec!["fan.curve.points"]);
+    }
+
+    #[test]
+    fn tdp_checked_against_capabilities() {
+        let caps = PowerCapabilities {
+            tdp_min_watts: Some(5),
+            tdp_max_watts: Some(60),
+            ..Default::default()
+        };
+        let cfg = PowerConfig {
+            ac: Some(PowerProfile {
+                tdp_watts: Some(SettingU32 {
+                    enabled: true,
+                    value: 120,
+                }),
+                fast_limit_watts: Some(SettingU32 {
+                    enabled: true,
+                    value: 70,
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut errs = Errors::default();
+        power(&mut errs, &cfg, Some(&caps));
+        assert_eq!(fields(errs), vec!["power.ac.tdp_watts", "power.ac.fast_limit_watts"]);
+    }
+
+    #[test]
+    fn network_origins_must_be_bare_origins() {
+        let cfg = NetworkConfig {
+            port: Some(80),
+            allowed_origins: Some(vec![
+                "http://localhost:5173".into(),
+                "https://example.com/app".into(),
+                "localhost:5173".into(),
+            ]),
+            base_path: Some("/api/".into()),
+        };
+        let mut errs = Errors::default();
+        network(&mut errs, &cfg);
+        assert_eq!(
+            fields(errs),
+            vec![
+                "network.port",
+                "network.allowed_origins[1]",
+                "network.allowed_origins[2]",
+                "network.base_path"
+            ]
+        );
+    }
+}
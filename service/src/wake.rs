@@ -0,0 +1,276 @@
+// OS wake timers for `fan.schedule` rules with `wake`: one daily timer per wake time, a scheduled
+// task set to wake the computer on Windows and a `WakeSystem=` systemd timer on Linux. Both only
+// wake the machine; the schedule task applies the profile once it runs again. The timers are
+// replaced as a set whenever the wanted wake times change, and once at startup to drop any a
+// previous config left behind. `suspend` puts the machine back to sleep for `sleep_after`.
+//
+// Windows honours the timers only while "Allow wake timers" is enabled in the power plan; on
+// Linux the service has to run as root to install the units.
+
+use std::sync::Mutex;
+
+use tracing::info;
+
+use crate::tasks::fan_schedule::parse_hhmm;
+use crate::types::FanScheduleConfig;
+
+const DEFAULT_LEAD_MINUTES: u32 = 5;
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+#[cfg(target_os = "linux")]
+const SYSTEMD_DIR: &str = "/etc/systemd/system";
+#[cfg(any(target_os = "linux", test))]
+const UNIT_PREFIX: &str = "framework-control-wake-";
+#[cfg(any(target_os = "windows", test))]
+const TASK_PATH: &str = r"\FrameworkControl\";
+
+/// Wake times currently armed, minutes since local midnight
+static ARMED: Mutex<Vec<u16>> = Mutex::new(Vec::new());
+
+pub fn lead_minutes(schedule: &FanScheduleConfig) -> u16 {
+    schedule
+        .wake_lead_minutes
+        .unwrap_or(DEFAULT_LEAD_MINUTES)
+        .min(MINUTES_PER_DAY as u32) as u16
+}
+
+/// `minute` moved `back` minutes earlier, wrapping past midnight.
+pub fn before(minute: u16, back: u16) -> u16 {
+    (minute + MINUTES_PER_DAY - back % MINUTES_PER_DAY) % MINUTES_PER_DAY
+}
+
+/// Wake times (minutes since local midnight) the enabled schedule's `wake` rules need, sorted.
+pub fn wake_minutes(schedule: &FanScheduleConfig) -> Vec<u16> {
+    if !schedule.enabled {
+        return Vec::new();
+    }
+    let lead = lead_minutes(schedule);
+    let mut out: Vec<u16> = schedule
+        .rules
+        .iter()
+        .filter(|r| r.wake)
+        .filter_map(|r| parse_hhmm(&r.start))
+        .map(|start| before(start, lead))
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+fn hhmm(minute: u16) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+/// Armed wake times as "HH:MM"
+pub fn armed() -> Vec<String> {
+    ARMED
+        .lock()
+        .map(|a| a.iter().map(|&m| hhmm(m)).collect())
+        .unwrap_or_default()
+}
+
+/// Replace the service's wake timers with one per `minutes` entry.
+pub async fn sync(minutes: &[u16]) -> Result<(), String> {
+    sync_os(minutes).await?;
+    if let Ok(mut a) = ARMED.lock() {
+        *a = minutes.to_vec();
+    }
+    if !minutes.is_empty() {
+        info!(
+            "wake timers armed daily at {}",
+            minutes.iter().map(|&m| hhmm(m)).collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn unit_name(minute: u16) -> String {
+    format!("{}{:02}{:02}", UNIT_PREFIX, minute / 60, minute % 60)
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn timer_unit(minute: u16) -> String {
+    format!(
+        "[Unit]\nDescription=Framework Control scheduled wake ({})\n\n\
+         [Timer]\nOnCalendar=*-*-* {}:00\nWakeSystem=true\n\n\
+         [Install]\nWantedBy=timers.target\n",
+        hhmm(minute),
+        hhmm(minute)
+    )
+}
+
+#[cfg(target_os = "linux")]
+const SERVICE_UNIT: &str = "[Unit]\nDescription=Framework Control scheduled wake\n\n\
+                            [Service]\nType=oneshot\nExecStart=/bin/true\n";
+
+#[cfg(target_os = "linux")]
+async fn systemctl(args: &[&str]) -> Result<(), String> {
+    let out = tokio::process::Command::new("systemctl")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("systemctl: {}", e))?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "systemctl {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn sync_os(minutes: &[u16]) -> Result<(), String> {
+    use std::path::Path;
+
+    let dir = Path::new(SYSTEMD_DIR);
+    let wanted: Vec<String> = minutes.iter().map(|&m| unit_name(m)).collect();
+    let existing: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.strip_prefix(UNIT_PREFIX)?.strip_suffix(".timer")?;
+            Some(name.trim_end_matches(".timer").to_string())
+        })
+        .collect();
+    let stale: Vec<&String> = existing.iter().filter(|n| !wanted.contains(n)).collect();
+    let added: Vec<(u16, &String)> = minutes
+        .iter()
+        .copied()
+        .zip(&wanted)
+        .filter(|(_, n)| !existing.contains(n))
+        .collect();
+    if stale.is_empty() && added.is_empty() {
+        return Ok(());
+    }
+    for name in &stale {
+        let _ = systemctl(&["disable", "--now", &format!("{}.timer", name)]).await;
+        for ext in ["timer", "service"] {
+            let _ = std::fs::remove_file(dir.join(format!("{}.{}", name, ext)));
+        }
+    }
+    for (minute, name) in &added {
+        let write = |ext: &str, body: &str| {
+            let path = dir.join(format!("{}.{}", name, ext));
+            std::fs::write(&path, body).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+        };
+        write("service", SERVICE_UNIT)?;
+        write("timer", &timer_unit(*minute))?;
+    }
+    systemctl(&["daemon-reload"]).await?;
+    for (_, name) in &added {
+        systemctl(&["enable", "--now", &format!("{}.timer", name)]).await?;
+    }
+    Ok(())
+}
+
+/// PowerShell that drops the service's wake tasks and registers one per `minutes` entry.
+#[cfg(any(target_os = "windows", test))]
+fn task_script(minutes: &[u16]) -> String {
+    let mut script = format!(
+        "$ErrorActionPreference = 'Stop'; $path = '{}'; \
+         Get-ScheduledTask -TaskPath $path -ErrorAction SilentlyContinue | \
+         Where-Object {{ $_.TaskName -like 'Wake-*' }} | Unregister-ScheduledTask -Confirm:$false; ",
+        TASK_PATH
+    );
+    if !minutes.is_empty() {
+        script.push_str(
+            "$settings = New-ScheduledTaskSettingsSet -WakeToRun -AllowStartIfOnBatteries -DontStopIfGoingOnBatteries; \
+             $action = New-ScheduledTaskAction -Execute 'cmd.exe' -Argument '/c exit 0'; ",
+        );
+    }
+    for &m in minutes {
+        script.push_str(&format!(
+            "Register-ScheduledTask -TaskPath $path -TaskName 'Wake-{}' \
+             -Trigger (New-ScheduledTaskTrigger -Daily -At '{}') -Action $action -Settings $settings \
+             -User 'SYSTEM' -Force | Out-Null; ",
+            hhmm(m).replace(':', ""),
+            hhmm(m)
+        ));
+    }
+    script
+}
+
+#[cfg(target_os = "windows")]
+async fn powershell(script: &str) -> Result<(), String> {
+    let out = tokio::process::Command::new("powershell")
+        .arg("-NoProfile")
+        .arg("-NonInteractive")
+        .arg("-Command")
+        .arg(script)
+        .output()
+        .await
+        .map_err(|e| format!("powershell: {}", e))?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&out.stderr).trim().to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn sync_os(minutes: &[u16]) -> Result<(), String> {
+    powershell(&task_script(minutes)).await
+}
+
+/// Put the machine to sleep (suspend to RAM).
+pub async fn suspend() -> Result<(), String> {
+    info!("suspending after the scheduled window");
+    #[cfg(target_os = "linux")]
+    {
+        systemctl(&["suspend"]).await
+    }
+    #[cfg(target_os = "windows")]
+    {
+        powershell(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             [void][System.Windows.Forms.Application]::SetSuspendState('Suspend', $false, $false)",
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FanScheduleRule;
+
+    #[test]
+    fn wake_times_lead_rule_starts_and_wrap_midnight() {
+        let rule = |start: &str, wake: bool| FanScheduleRule {
+            profile: "render".into(),
+            start: start.into(),
+            end: "06:00".into(),
+            wake,
+            sleep_after: false,
+        };
+        let mut schedule = FanScheduleConfig {
+            enabled: true,
+            rules: vec![
+                rule("00:03", true),
+                rule("02:00", true),
+                rule("03:00", false),
+                rule("02:00", true),
+            ],
+            default_profile: None,
+            wake_lead_minutes: None,
+        };
+        assert_eq!(wake_minutes(&schedule), vec![115, 1438]);
+        schedule.wake_lead_minutes = Some(30);
+        assert_eq!(wake_minutes(&schedule), vec![90, 1413]);
+
+        assert_eq!(unit_name(1413), "framework-control-wake-2333");
+        assert!(timer_unit(90).contains("OnCalendar=*-*-* 01:30:00\nWakeSystem=true"));
+        let script = task_script(&[90]);
+        assert!(script.contains("-TaskName 'Wake-0130'") && script.contains("-Daily -At '01:30'"));
+        assert!(!task_script(&[]).contains("Register-ScheduledTask"));
+
+        schedule.enabled = false;
+        assert!(wake_minutes(&schedule).is_empty());
+    }
+}
@@ -9,7 +9,7 @@ const __dirname = path.dirname(__filename);
 const repoRoot = path.resolve(__dirname, '..', '..');
 const webDir = path.resolve(repoRoot, 'web');
 const serviceDir = path.resolve(repoRoot, 'service');
-const wixXmlPath = path.resolve(serviceDir, 'wix', 'FrameworkControlService.xml');
+const bootstrapPath = path.resolve(serviceDir, 'wix', 'bootstrap.json');
 const serviceEnvPath = path.resolve(serviceDir, '.env');
 const packageJsonPath = path.resolve(webDir, 'package.json');
 
@@ -45,8 +45,8 @@ const readDotEnv = (p) => {
 
 const isValidPort = (v) => Number.isInteger(Number(v)) && Number(v) >= 1 && Number(v) <= 65535;
 
-const replaceTokens = (xml, t) =>
-	xml
+const replaceTokens = (json, t) =>
+	json
 		.replaceAll('@FRAMEWORK_CONTROL_ALLOWED_ORIGINS@', t.ALLOWED_ORIGINS)
 		.replaceAll('@FRAMEWORK_CONTROL_PORT@', t.CONTROL_PORT)
 		.replaceAll('@FRAMEWORK_CONTROL_UPDATE_REPO@', t.UPDATE_REPO);
@@ -147,21 +147,21 @@ async function main() {
 	console.log('[build-msi] Building service binary...');
 	await run('cargo.exe', ['build', '--release'], { cwd: serviceDir });
 
-	// Prepare tokens for WiX XML
+	// Prepare tokens for the installer's bootstrap.json
 	const tokens = {
 		ALLOWED_ORIGINS: config.allowedOrigins,
 		CONTROL_PORT: config.port,
 		UPDATE_REPO: config.updateRepo,
 	};
 
-	// Replace tokens, run wix, restore XML
+	// Replace tokens, run wix, restore the template
 	console.log('[build-msi] Building MSI with cargo-wix...');
-	const original = fs.readFileSync(wixXmlPath, 'utf8');
-	fs.writeFileSync(wixXmlPath, replaceTokens(original, tokens), 'utf8');
+	const original = fs.readFileSync(bootstrapPath, 'utf8');
+	fs.writeFileSync(bootstrapPath, replaceTokens(original, tokens), 'utf8');
 	try {
 		await run('cargo.exe', ['wix', '--nocapture', '-v'], { cwd: serviceDir });
 	} finally {
-		fs.writeFileSync(wixXmlPath, original, 'utf8');
+		fs.writeFileSync(bootstrapPath, original, 'utf8');
 	}
 
 	// Locate the MSI
@@ -0,0 +1,23 @@
+import { AdminService, type Config, type ConfigWriteResult, type PartialConfig } from '../api';
+
+// Config revision last seen by this page. The service rejects writes carrying an older
+// revision (409), so an edit made on top of a config another tab has since changed fails
+// instead of silently overwriting it. Reading the config again picks up the new revision.
+let revision: number | undefined;
+
+export async function getConfig(): Promise<Config> {
+	const cfg = await AdminService.getConfig();
+	revision = cfg.revision;
+	return cfg;
+}
+
+// `confirm` is for writes the service guards (turning the charge limit off): pass it only when
+// the user asked for the change, otherwise the write fails with `confirmation_required`.
+export async function setConfig(patch: PartialConfig, confirm?: boolean): Promise<ConfigWriteResult> {
+	if (revision === undefined) {
+		await getConfig();
+	}
+	const res = await AdminService.setConfig({ ...patch, revision }, undefined, confirm);
+	revision = res.revision;
+	return res;
+}
@@ -0,0 +1,18 @@
+import type { RemediationHint } from '../api';
+import { hintText } from './hints';
+
+// Guarded operations (RyzenAdj uninstall, update apply, turning the charge limit off or lifting it
+// on AC) are refused with 409 `confirmation_required` until they are repeated with `confirm=true`.
+// Run `call` without it first, show the service's warning and repeat only once the user accepts;
+// `null` when they decline. Other errors are rethrown.
+export async function withConfirmation<T>(call: (confirm?: boolean) => Promise<T>): Promise<T | null> {
+	try {
+		return await call();
+	} catch (e) {
+		const err = e as { status?: number; body?: { code?: string; message?: string; hint?: RemediationHint } };
+		if (err?.status !== 409 || err.body?.code !== 'confirmation_required') throw e;
+		const hint = err.body.hint ? `\n\n${hintText(err.body.hint)}` : '';
+		if (!window.confirm(`${err.body.message ?? 'This cannot be easily undone'}.${hint}`)) return null;
+		return await call(true);
+	}
+}
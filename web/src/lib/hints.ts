@@ -0,0 +1,17 @@
+import type { RemediationHint } from '../api';
+
+// Remediation hints come with a stable `key`, `params` and the English `message`. A translated
+// template for the key (placeholders as `{name}`) is filled from `params`; without one the
+// English message is shown.
+export function hintText(hint: RemediationHint, templates: Record<string, string> = {}): string {
+	const template = templates[hint.key];
+	if (!template) return hint.message;
+	return template.replace(/\{(\w+)\}/g, (m, name: string) => hint.params?.[name] ?? m);
+}
+
+// Error body message plus its hint, for errors thrown by the generated client.
+export function apiErrorText(e: unknown, fallback: string): string {
+	const body = (e as { body?: { message?: string; hint?: RemediationHint } })?.body;
+	const message = body?.message || (e instanceof Error ? e.message : fallback);
+	return body?.hint ? `${message}. ${hintText(body.hint)}` : message;
+}
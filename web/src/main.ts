@@ -1,10 +1,11 @@
 import './app.css';
 import App from './App.svelte';
 import { OpenAPI } from './api';
-import { DefaultService } from './api';
+import { getConfig } from './lib/config';
 
-// Derive API base from current origin unless explicitly overridden
-OpenAPI.BASE = (import.meta.env?.VITE_API_BASE as string | undefined) || `${window.location.origin}/api`;
+// Derive API base from the page location (which includes any reverse-proxy base path) unless
+// explicitly overridden
+OpenAPI.BASE = (import.meta.env?.VITE_API_BASE as string | undefined) || new URL('api', document.baseURI).href;
 
 // Apply saved theme early so initial render uses it
 try {
@@ -17,7 +18,7 @@ try {
 // Also query backend config for persisted theme and override if present
 (async () => {
 	try {
-		const cfg = await DefaultService.getConfig();
+		const cfg = await getConfig();
 		const backendTheme = cfg?.ui?.theme;
 		if (backendTheme) {
 			document.documentElement.setAttribute('data-theme', backendTheme);
@@ -7,7 +7,8 @@ export default defineConfig(() => {
 	const repoName = process.env.GITHUB_REPOSITORY?.split('/')?.[1];
 	const isCI = process.env.CI === 'true' || !!process.env.GITHUB_ACTIONS;
 	const inferredPagesBase = repoName ? `/${repoName}/` : '/';
-	const base = explicitBase || (isGitHubPages ? '/framework-control/' : isCI ? inferredPagesBase : '/');
+	// Relative by default so the embedded UI also works under the service's `network.base_path`
+	const base = explicitBase || (isGitHubPages ? '/framework-control/' : isCI ? inferredPagesBase : './');
 	return {
 		base,
 		plugins: [svelte()],